@@ -0,0 +1,111 @@
+//! Timings of the evaluation and of the cloning of a tree of more
+//! than one million nodes, and of the building of many small trees.
+//!
+//! Run with `cargo bench`, or `cargo bench --features arena` to compare
+//! with the building of the small trees in an arena.
+
+use {
+    bet::BeTree,
+    std::{
+        hint::black_box,
+        time::{Duration, Instant},
+    },
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    And,
+    Or,
+    Not,
+}
+
+const ITERATIONS: u32 = 20;
+
+const SMALL_TREES: usize = 100_000;
+
+/// push a balanced expression of the given depth, whose operators
+/// alternate between `&` and `|`, with some negations
+fn push_balanced(tree: &mut BeTree<Op, u32>, depth: u32, next_atom: &mut u32) {
+    if depth == 0 {
+        if next_atom.is_multiple_of(3) {
+            tree.push_operator(Op::Not);
+        }
+        tree.push_atom(*next_atom);
+        *next_atom += 1;
+        return;
+    }
+    tree.open_par();
+    push_balanced(tree, depth - 1, next_atom);
+    tree.push_operator(if depth.is_multiple_of(2) {
+        Op::And
+    } else {
+        Op::Or
+    });
+    push_balanced(tree, depth - 1, next_atom);
+    tree.close_par();
+}
+
+fn eval(tree: &BeTree<Op, u32>) -> Option<bool> {
+    tree.eval(
+        |&atom| atom % 5 != 0,
+        |op, a, b| match (op, b) {
+            (Op::And, Some(b)) => a & b,
+            (Op::Or, Some(b)) => a | b,
+            _ => !a,
+        },
+        |_, _| false, // so that the whole tree is evaluated
+    )
+}
+
+fn time<R>(name: &str, mut f: impl FnMut() -> R) {
+    let mut total = Duration::ZERO;
+    for _ in 0..ITERATIONS {
+        let start = Instant::now();
+        black_box(f());
+        total += start.elapsed();
+    }
+    println!("{:>8}: {:?} per iteration", name, total / ITERATIONS);
+}
+
+fn main() {
+    let mut tree = BeTree::new();
+    let mut next_atom = 0;
+    push_balanced(&mut tree, 20, &mut next_atom);
+    let usage = tree.memory_usage();
+    println!(
+        "tree of {} nodes and {} atoms, about {} bytes",
+        usage.nodes_len, usage.atoms_len, usage.approx_bytes,
+    );
+    time("eval", || eval(&tree));
+    time("clone", || tree.clone());
+    // many short-lived trees, either allocated, reusing a cleared one,
+    // or allocated in an arena reset after each one
+    time("new", || {
+        for _ in 0..SMALL_TREES {
+            let mut small = BeTree::new();
+            push_balanced(&mut small, 3, &mut 0);
+            black_box(eval(&small));
+        }
+    });
+    let mut small = BeTree::new();
+    time("clear", || {
+        for _ in 0..SMALL_TREES {
+            small.clear();
+            push_balanced(&mut small, 3, &mut 0);
+            black_box(eval(&small));
+        }
+    });
+    #[cfg(feature = "arena")]
+    {
+        let mut arena = bet::TreeArena::new();
+        time("arena", || {
+            for _ in 0..SMALL_TREES {
+                let mut small = BeTree::new_in(&arena);
+                push_balanced(&mut small, 3, &mut 0);
+                black_box(eval(&small));
+                drop(small);
+                arena.reset();
+            }
+        });
+    }
+}
@@ -0,0 +1,42 @@
+//! Build arbitrary boolean expressions, and check they can be evaluated
+//! and that replaying their tokens gives the same value.
+//!
+//! Run with `cargo fuzz run eval_round_trip`.
+#![no_main]
+
+use {arbitrary::Arbitrary, bet::*, libfuzzer_sys::fuzz_target};
+
+#[derive(Debug, Clone, Copy, PartialEq, Arbitrary)]
+enum BoolOperator {
+    And,
+    Or,
+    Not,
+}
+
+/// evaluate the expression, whatever the position of the operators,
+/// as arbitrary expressions may have any operator in unary or binary
+/// position
+fn eval(expr: &BeTree<BoolOperator, bool>) -> Option<bool> {
+    expr.eval(
+        |&b| b,
+        |op, a, b| match (op, b) {
+            (BoolOperator::And, Some(b)) => a & b,
+            (BoolOperator::Or, Some(b)) => a | b,
+            (BoolOperator::Not, Some(b)) => a ^ b,
+            (_, None) => !a,
+        },
+        |op, &a| matches!((op, a), (BoolOperator::And, false) | (BoolOperator::Or, true)),
+    )
+}
+
+fuzz_target!(|expr: BeTree<BoolOperator, bool>| {
+    expr.validate_structure().unwrap();
+    let value = eval(&expr);
+    assert_eq!(value.is_some(), !expr.is_empty());
+    let mut replayed = BeTree::new();
+    for token in expr.to_tokens() {
+        replayed.push(token);
+    }
+    replayed.validate_structure().unwrap();
+    assert_eq!(eval(&replayed), value);
+});
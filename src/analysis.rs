@@ -0,0 +1,188 @@
+#[cfg(feature = "std")]
+use std::{
+    collections::{HashMap, HashSet},
+    hash::Hash,
+};
+use {crate::*, core::fmt};
+
+/// The shape of a subtree, identifying it up to structural equality
+/// once the shapes of its operands are known
+#[cfg(feature = "std")]
+#[derive(PartialEq, Eq, Hash)]
+enum Shape<'t, Op, Atom> {
+    Atom(&'t Atom),
+    Node(&'t Op, bool, Option<usize>, Option<usize>),
+}
+
+/// Analysis of the structure of the expression
+#[cfg(feature = "std")]
+impl<Op, Atom> BeTree<Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq + Eq + Hash,
+    Atom: fmt::Debug + Clone + Eq + Hash,
+{
+    /// find the subexpressions which appear several times in the
+    /// expression, as groups of nodes whose subtrees are structurally
+    /// identical (same operators, equal atoms, groups being ignored).
+    ///
+    /// Each group is returned with the number of atoms of the repeated
+    /// subexpression. Subexpressions with less than `min_size` atoms are
+    /// ignored. Only maximal repetitions are reported: occurrences lying
+    /// inside an occurrence of a bigger reported subexpression aren't
+    /// counted (in `(a&b | c) & (a&b | c)`, `a&b` isn't reported).
+    ///
+    /// Groups are sorted by decreasing size, then by position of their
+    /// first occurrence, and nodes in a group are in tree order.
+    pub fn common_subexpressions(&self, min_size: usize) -> Vec<(Vec<NodeId>, usize)> {
+        // reachable nodes in pre-order
+        let mut order = Vec::new();
+        let mut stack = vec![self.head];
+        while let Some(node_idx) = stack.pop() {
+            order.push(node_idx);
+            let node = &self.nodes[node_idx];
+            for child in [node.right(), node.left()] {
+                if let Child::Node(child_idx) = child {
+                    stack.push(child_idx);
+                }
+            }
+        }
+        // bottom-up computation of the class (an id of the shape) and
+        // the number of atoms of all subtrees
+        let mut shape_classes: HashMap<Shape<'_, Op, Atom>, usize> = HashMap::new();
+        let mut classes = vec![None; self.nodes.len()];
+        let mut sizes = vec![0; self.nodes.len()];
+        for &node_idx in order.iter().rev() {
+            let node = &self.nodes[node_idx];
+            let mut child_class = |child: Child| match child {
+                Child::None => (None, 0),
+                Child::Node(child_idx) => (classes[child_idx], sizes[child_idx]),
+                Child::Atom(atom_idx) => {
+                    let shape = Shape::Atom(&self.atoms[atom_idx]);
+                    let next_class = shape_classes.len();
+                    (Some(*shape_classes.entry(shape).or_insert(next_class)), 1)
+                }
+            };
+            let (left_class, left_size) = child_class(node.left());
+            let (right_class, right_size) = child_class(node.right());
+            sizes[node_idx] = left_size + right_size;
+            classes[node_idx] = match &node.operator {
+                Some(operator) => {
+                    let shape = Shape::Node(operator, node.unary, left_class, right_class);
+                    let next_class = shape_classes.len();
+                    Some(*shape_classes.entry(shape).or_insert(next_class))
+                }
+                None => left_class, // a group is transparent
+            };
+        }
+        // grouping of the nodes with an operator by class
+        let mut groups: HashMap<usize, Vec<NodeId>> = HashMap::new();
+        for &node_idx in &order {
+            if self.nodes[node_idx].operator.is_none() || sizes[node_idx] < min_size {
+                continue;
+            }
+            if let Some(class) = classes[node_idx] {
+                groups.entry(class).or_default().push(node_idx);
+            }
+        }
+        let position: HashMap<NodeId, usize> = order
+            .iter()
+            .enumerate()
+            .map(|(i, &node_idx)| (node_idx, i))
+            .collect();
+        let mut groups: Vec<Vec<NodeId>> = groups
+            .into_values()
+            .filter(|nodes| nodes.len() > 1)
+            .collect();
+        groups.sort_by_key(|nodes| (core::cmp::Reverse(sizes[nodes[0]]), position[&nodes[0]]));
+        // removal of the occurrences lying in reported occurrences
+        let mut covered = vec![false; self.nodes.len()];
+        let mut common = Vec::new();
+        for nodes in groups {
+            let nodes: Vec<NodeId> = nodes.into_iter().filter(|&idx| !covered[idx]).collect();
+            if nodes.len() < 2 {
+                continue;
+            }
+            for &node_idx in &nodes {
+                let mut stack = vec![node_idx];
+                while let Some(idx) = stack.pop() {
+                    covered[idx] = true;
+                    let node = &self.nodes[idx];
+                    for child in [node.left(), node.right()] {
+                        if let Child::Node(child_idx) = child {
+                            stack.push(child_idx);
+                        }
+                    }
+                }
+            }
+            let size = sizes[nodes[0]];
+            common.push((nodes, size));
+        }
+        common
+    }
+}
+
+/// Analysis of the atoms
+#[cfg(feature = "std")]
+impl<Op, Atom> BeTree<Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone + Eq + Hash,
+{
+    /// group the ids of the reachable atoms by equality of the atoms.
+    ///
+    /// Groups are in the order of their first atom in the expression, and
+    /// ids in a group are in tree order. An atom linked several times
+    /// (see `dedup_atoms`) is only given once.
+    pub fn atom_groups(&self) -> Vec<Vec<AtomId>> {
+        let mut group_indices: HashMap<&Atom, usize> = HashMap::new();
+        let mut groups: Vec<Vec<AtomId>> = Vec::new();
+        let mut seen = vec![false; self.atoms.len()];
+        for (atom_id, atom) in self.iter_atoms_in_tree() {
+            if core::mem::replace(&mut seen[atom_id], true) {
+                continue;
+            }
+            let next_index = groups.len();
+            let index = *group_indices.entry(atom).or_insert(next_index);
+            if index == next_index {
+                groups.push(Vec::new());
+            }
+            groups[index].push(atom_id);
+        }
+        groups
+    }
+
+    /// count the distinct atoms of the expression
+    pub fn count_distinct_atoms(&self) -> usize {
+        let distinct: HashSet<&Atom> = self.iter_atoms_in_tree().map(|(_, atom)| atom).collect();
+        distinct.len()
+    }
+}
+
+/// Analysis of the atoms, when they can only be compared
+impl<Op, Atom> BeTree<Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone + PartialEq,
+{
+    /// group the ids of the reachable atoms by equality of the atoms,
+    /// exactly like `atom_groups`, but for atoms which can't be hashed.
+    ///
+    /// The complexity is quadratic in the number of distinct atoms.
+    pub fn atom_groups_by_eq(&self) -> Vec<Vec<AtomId>> {
+        let mut groups: Vec<Vec<AtomId>> = Vec::new();
+        let mut seen = vec![false; self.atoms.len()];
+        for (atom_id, atom) in self.iter_atoms_in_tree() {
+            if core::mem::replace(&mut seen[atom_id], true) {
+                continue;
+            }
+            match groups
+                .iter_mut()
+                .find(|group| &self.atoms[group[0]] == atom)
+            {
+                Some(group) => group.push(atom_id),
+                None => groups.push(vec![atom_id]),
+            }
+        }
+        groups
+    }
+}
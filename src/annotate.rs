@@ -0,0 +1,80 @@
+use {crate::*, core::fmt};
+
+/// Values computed for the nodes and atoms of a tree,
+/// as given by `BeTree::annotate`
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeAnnotations<T> {
+    nodes: Vec<Option<T>>,
+    atoms: Vec<Option<T>>,
+    head: NodeId,
+}
+
+impl<T> NodeAnnotations<T> {
+    /// the value of the node, or `None` if the node wasn't reachable
+    /// or was incomplete
+    pub fn node(&self, node_id: NodeId) -> Option<&T> {
+        self.nodes.get(node_id.index()).and_then(Option::as_ref)
+    }
+    /// the value of the atom, or `None` if the atom wasn't reachable
+    pub fn atom(&self, atom_id: AtomId) -> Option<&T> {
+        self.atoms.get(atom_id.index()).and_then(Option::as_ref)
+    }
+    /// the value of the head, that is of the whole expression
+    pub fn head(&self) -> Option<&T> {
+        self.node(self.head)
+    }
+}
+
+/// Annotation
+impl<Op, Atom> BeTree<Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone,
+{
+    /// compute a value for every reachable atom and node, bottom-up.
+    ///
+    /// The `leaf` function gives the values of atoms, and the `node`
+    /// function the value of a node from its operator, whether it's unary,
+    /// and the values of its operands (the second one being `None` for
+    /// a unary operator, or when the right operand is missing). It's
+    /// like `eval` without short-circuit, but with all intermediate
+    /// results kept.
+    ///
+    /// A node without operator (a group) has the value of its content.
+    /// A node without operand (in an incomplete expression) has no value.
+    pub fn annotate<T, Leaf, NodeFn>(&self, leaf: Leaf, node: NodeFn) -> NodeAnnotations<T>
+    where
+        Leaf: Fn(AtomId, &Atom) -> T,
+        NodeFn: Fn(NodeId, &Op, bool, &T, Option<&T>) -> T,
+        T: Clone,
+    {
+        let mut annotations = NodeAnnotations {
+            nodes: vec![None; self.nodes.len()],
+            atoms: vec![None; self.atoms.len()],
+            head: self.head,
+        };
+        for (atom_id, atom) in self.iter_atoms_in_tree() {
+            if annotations.atoms[atom_id].is_none() {
+                annotations.atoms[atom_id] = Some(leaf(atom_id, atom));
+            }
+        }
+        // in reverse pre-order, children come before their parent
+        for (node_id, n) in self.iter_nodes().rev() {
+            let value_of = |child: Child| match child {
+                Child::None => None,
+                Child::Atom(atom_id) => annotations.atoms[atom_id].as_ref(),
+                Child::Node(node_id) => annotations.nodes[node_id].as_ref(),
+            };
+            let left = value_of(n.left());
+            let value = match (&n.operator, left) {
+                (Some(operator), Some(left)) => {
+                    Some(node(node_id, operator, n.unary, left, value_of(n.right())))
+                }
+                (None, Some(left)) => Some(left.clone()),
+                (_, None) => None,
+            };
+            annotations.nodes[node_id] = value;
+        }
+        annotations
+    }
+}
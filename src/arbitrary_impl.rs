@@ -0,0 +1,62 @@
+use {
+    crate::*,
+    arbitrary::{Arbitrary, Result, Unstructured},
+    std::fmt,
+};
+
+/// The kinds of tokens which can be pushed on a tree being generated
+#[derive(Clone, Copy)]
+enum PushAction {
+    Atom,
+    Operator,
+    OpeningPar,
+    ClosingPar,
+}
+
+/// Arbitrary trees are built by pushing tokens chosen among the ones
+/// the tree accepts, then completed with atoms and closing parenthesis,
+/// so they're always valid and complete. The number of pushed tokens
+/// is bounded by the length of the unstructured data.
+impl<'a, Op, Atom> Arbitrary<'a> for BeTree<Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq + Arbitrary<'a>,
+    Atom: fmt::Debug + Clone + Arbitrary<'a>,
+{
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let mut tree = BeTree::new();
+        let mut actions = Vec::with_capacity(4);
+        for _ in 0..u.len() {
+            if u.is_empty() {
+                break;
+            }
+            actions.clear();
+            if tree.accept_atom() {
+                actions.push(PushAction::Atom);
+            }
+            if tree.accept_unary_operator() || tree.accept_binary_operator() {
+                actions.push(PushAction::Operator);
+            }
+            if tree.accept_opening_par() {
+                actions.push(PushAction::OpeningPar);
+            }
+            if tree.accept_closing_par() {
+                actions.push(PushAction::ClosingPar);
+            }
+            match u.choose(&actions)? {
+                PushAction::Atom => tree.push_atom(Atom::arbitrary(u)?),
+                PushAction::Operator => tree.push_operator(Op::arbitrary(u)?),
+                PushAction::OpeningPar => tree.open_par(),
+                PushAction::ClosingPar => tree.close_par(),
+            }
+        }
+        if tree.last_pushed != TokenType::Nothing {
+            if tree.accept_atom() {
+                tree.push_atom(Atom::arbitrary(u)?);
+            }
+            while tree.accept_closing_par() {
+                tree.close_par();
+            }
+        }
+        Ok(tree)
+    }
+}
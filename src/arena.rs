@@ -0,0 +1,360 @@
+use {
+    crate::*,
+    alloc::sync::Arc,
+    allocator_api2::alloc::{AllocError, Allocator, Global},
+    bumpalo::Bump,
+    core::{
+        alloc::Layout,
+        fmt,
+        iter::FromIterator,
+        ops::{Deref, DerefMut},
+        ptr::NonNull,
+        slice,
+    },
+    std::sync::{Mutex, MutexGuard, PoisonError},
+};
+
+/// A bump arena in which the nodes and atoms of trees can be
+/// allocated, with `BeTree::new_in`.
+///
+/// Allocating in the arena is mostly moving a pointer, and dropping a
+/// tree allocated in it doesn't give back its memory: it's released all
+/// at once when the arena and the trees allocated in it are all dropped,
+/// or reused for the next trees when the arena is `reset`.
+///
+/// ```
+/// use bet::{BeTree, TreeArena};
+///
+/// let mut arena = TreeArena::new();
+/// for input in ["a|b", "a&!b", "!(a|b)"] {
+///     let mut expr = BeTree::new_in(&arena);
+///     for c in input.chars() {
+///         match c {
+///             '&' | '|' | '!' => expr.push_operator(c),
+///             '(' => expr.open_par(),
+///             ')' => expr.close_par(),
+///             _ => expr.push_atom(c == 'a'),
+///         }
+///     }
+///     let value = expr.eval(
+///         |&atom| atom,
+///         |&op, a, b| match (op, b) {
+///             ('&', Some(b)) => a & b,
+///             ('|', Some(b)) => a | b,
+///             _ => !a,
+///         },
+///         |_, _| false,
+///     );
+///     assert_eq!(value, Some(input != "!(a|b)"));
+/// }
+/// assert!(arena.allocated_bytes() > 0);
+/// assert!(arena.reset());
+/// ```
+#[derive(Default)]
+pub struct TreeArena {
+    bump: Arc<Mutex<Bump>>,
+}
+
+impl TreeArena {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// create an arena whose first chunk can hold the given number of
+    /// bytes without asking the global allocator for more
+    pub fn with_capacity(bytes: usize) -> Self {
+        Self {
+            bump: Arc::new(Mutex::new(Bump::with_capacity(bytes))),
+        }
+    }
+    /// the number of bytes the arena got from the global allocator,
+    /// including the ones used by trees already dropped
+    pub fn allocated_bytes(&self) -> usize {
+        lock(&self.bump).allocated_bytes()
+    }
+    /// free all the allocations at once, keeping the biggest chunk of
+    /// memory for the next trees
+    ///
+    /// Return `false`, leaving the arena untouched, when some trees
+    /// allocated in the arena are still alive.
+    pub fn reset(&mut self) -> bool {
+        match Arc::get_mut(&mut self.bump) {
+            Some(bump) => {
+                bump.get_mut()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .reset();
+                true
+            }
+            None => false,
+        }
+    }
+    pub(crate) fn allocator(&self) -> TreeAllocator {
+        TreeAllocator(Some(Arc::clone(&self.bump)))
+    }
+}
+
+impl fmt::Debug for TreeArena {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TreeArena")
+            .field("allocated_bytes", &self.allocated_bytes())
+            .finish()
+    }
+}
+
+// the state of the arena is consistent even when a thread panicked
+// while holding the lock, as the bump allocator doesn't panic
+fn lock(bump: &Mutex<Bump>) -> MutexGuard<'_, Bump> {
+    bump.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// The allocator of the storage of a tree: the global allocator, or
+/// a handle keeping alive the arena given to `BeTree::new_in`
+#[derive(Clone, Default)]
+pub(crate) struct TreeAllocator(Option<Arc<Mutex<Bump>>>);
+
+// SAFETY: the blocks of an arena stay valid as long as one of the
+// handles to the arena is alive, as the arena can only be reset when
+// there's none, and the calls are forwarded to allocators keeping the
+// contracts of the trait
+unsafe impl Allocator for TreeAllocator {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        match &self.0 {
+            Some(bump) => (&*lock(bump)).allocate(layout),
+            None => Global.allocate(layout),
+        }
+    }
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        match &self.0 {
+            Some(bump) => (&*lock(bump)).deallocate(ptr, layout),
+            None => Global.deallocate(ptr, layout),
+        }
+    }
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        match &self.0 {
+            Some(bump) => (&*lock(bump)).grow(ptr, old_layout, new_layout),
+            None => Global.grow(ptr, old_layout, new_layout),
+        }
+    }
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        match &self.0 {
+            Some(bump) => (&*lock(bump)).shrink(ptr, old_layout, new_layout),
+            None => Global.shrink(ptr, old_layout, new_layout),
+        }
+    }
+}
+
+/// A vector whose items are either in the global heap or in an arena.
+///
+/// It's the storage of the nodes and atoms of a tree with the `arena`
+/// feature. As with the other storages, up to `N` items are kept inline
+/// when the vector isn't given an arena, so that an empty tree doesn't
+/// allocate, and neither do small expressions with the `inline-storage`
+/// feature. As for `SmallVec`, only the part of the `Vec` API needed by
+/// the tree is provided.
+pub(crate) enum ArenaVec<T, const N: usize> {
+    /// at most `N` items, and no arena
+    Inline(SmallVec<T, N>),
+    Allocated(allocator_api2::vec::Vec<T, TreeAllocator>),
+}
+
+impl<T, const N: usize> ArenaVec<T, N> {
+    pub fn new() -> Self {
+        Self::Inline(SmallVec::new())
+    }
+    pub fn new_in(allocator: TreeAllocator) -> Self {
+        Self::Allocated(allocator_api2::vec::Vec::new_in(allocator))
+    }
+    pub fn with_capacity(capacity: usize) -> Self {
+        if capacity <= N {
+            Self::Inline(SmallVec::with_capacity(capacity))
+        } else {
+            Self::Allocated(allocator_api2::vec::Vec::with_capacity_in(
+                capacity,
+                TreeAllocator::default(),
+            ))
+        }
+    }
+    pub fn capacity(&self) -> usize {
+        match self {
+            Self::Inline(items) => items.capacity(),
+            Self::Allocated(vec) => vec.capacity(),
+        }
+    }
+    pub fn push(&mut self, item: T) {
+        match self {
+            Self::Inline(items) if items.len() < N => items.push(item),
+            Self::Inline(_) => {
+                let mut vec = allocator_api2::vec::Vec::with_capacity_in(
+                    2 * N.max(1),
+                    TreeAllocator::default(),
+                );
+                self.move_into(&mut vec);
+                vec.push(item);
+                *self = Self::Allocated(vec);
+            }
+            Self::Allocated(vec) => vec.push(item),
+        }
+    }
+    pub fn clear(&mut self) {
+        match self {
+            Self::Inline(items) => items.clear(),
+            Self::Allocated(vec) => vec.clear(),
+        }
+    }
+    pub fn shrink_to_fit(&mut self) {
+        match self {
+            Self::Inline(items) => items.shrink_to_fit(),
+            Self::Allocated(vec) => vec.shrink_to_fit(),
+        }
+    }
+    /// tell whether the items are in an arena
+    pub fn is_in_arena(&self) -> bool {
+        match self {
+            Self::Inline(_) => false,
+            Self::Allocated(vec) => vec.allocator().0.is_some(),
+        }
+    }
+    /// move all the items at the end of a vec, leaving this one empty
+    fn move_into(&mut self, target: &mut allocator_api2::vec::Vec<T, TreeAllocator>) {
+        match self {
+            Self::Inline(items) => items.move_into(target),
+            Self::Allocated(vec) => target.append(vec),
+        }
+    }
+}
+
+impl<T, const N: usize> Deref for ArenaVec<T, N> {
+    type Target = [T];
+    fn deref(&self) -> &[T] {
+        match self {
+            Self::Inline(items) => items,
+            Self::Allocated(vec) => vec,
+        }
+    }
+}
+
+impl<T, const N: usize> DerefMut for ArenaVec<T, N> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        match self {
+            Self::Inline(items) => items,
+            Self::Allocated(vec) => vec,
+        }
+    }
+}
+
+impl<T, const N: usize> Default for ArenaVec<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The clone is allocated in the same arena
+impl<T: Clone, const N: usize> Clone for ArenaVec<T, N> {
+    fn clone(&self) -> Self {
+        match self {
+            Self::Inline(items) => Self::Inline(items.clone()),
+            Self::Allocated(vec) => Self::Allocated(vec.clone()),
+        }
+    }
+}
+
+impl<T: PartialEq, const N: usize> PartialEq for ArenaVec<T, N> {
+    fn eq(&self, other: &Self) -> bool {
+        **self == **other
+    }
+}
+
+impl<T: fmt::Debug, const N: usize> fmt::Debug for ArenaVec<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T, const N: usize> Extend<T> for ArenaVec<T, N> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.push(item);
+        }
+    }
+}
+
+impl<T, const N: usize> FromIterator<T> for ArenaVec<T, N> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut vec = Self::new();
+        vec.extend(iter);
+        vec
+    }
+}
+
+impl<T, const N: usize> IntoIterator for ArenaVec<T, N> {
+    type Item = T;
+    type IntoIter = allocator_api2::vec::IntoIter<T, TreeAllocator>;
+    fn into_iter(mut self) -> Self::IntoIter {
+        let mut vec = allocator_api2::vec::Vec::new_in(TreeAllocator::default());
+        self.move_into(&mut vec);
+        vec.into_iter()
+    }
+}
+
+impl<'v, T, const N: usize> IntoIterator for &'v ArenaVec<T, N> {
+    type Item = &'v T;
+    type IntoIter = slice::Iter<'v, T>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T, const N: usize> From<Vec<T>> for ArenaVec<T, N> {
+    fn from(vec: Vec<T>) -> Self {
+        vec.into_iter().collect()
+    }
+}
+
+impl<T, const N: usize> From<ArenaVec<T, N>> for Vec<T> {
+    fn from(arena_vec: ArenaVec<T, N>) -> Self {
+        arena_vec.into_iter().collect()
+    }
+}
+
+impl<Op, Atom> BeTree<Op, Atom> {
+    /// create an empty expression whose nodes and atoms will be
+    /// allocated in the arena
+    ///
+    /// The tree keeps the arena memory alive, even when the arena itself
+    /// is dropped first. Its clones are allocated in the same arena but
+    /// the trees made by transformations, for example with
+    /// `try_map_atoms`, may allocate in the global heap.
+    pub fn new_in(arena: &TreeArena) -> Self {
+        let allocator = arena.allocator();
+        let mut nodes = Nodes::new_in(allocator.clone());
+        nodes.push(Node::empty());
+        Self {
+            atoms: Atoms::new_in(allocator),
+            nodes,
+            head: NodeId::new(0),
+            tail: NodeId::new(0),
+            last_pushed: TokenType::Nothing,
+            op_count: 0,
+            openness: 0,
+            epoch: 0,
+        }
+    }
+}
+
+impl<Op, Atom, Meta> BeTree<Op, Atom, Meta> {
+    /// tell whether the nodes and atoms of the tree are allocated
+    /// in an arena, which is the case of the trees created with
+    /// `new_in` and of their clones
+    pub fn is_in_arena(&self) -> bool {
+        self.nodes.is_in_arena()
+    }
+}
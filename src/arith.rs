@@ -0,0 +1,295 @@
+//! Arithmetic expressions, ready to use: the usual operators with their
+//! precedences, a parser, and evaluation functions.
+//!
+//! ```
+//! use bet::arith::*;
+//!
+//! let expr = parse_arith_expr::<i64>("2 + 3 * -(4 - 1) ^ 2").unwrap();
+//! assert_eq!(eval_i64(&expr), Ok(Some(-25)));
+//! let expr = parse_arith_expr::<i64>("7 / (2 - 2)").unwrap();
+//! assert_eq!(eval_i64(&expr), Err(ArithError::DivisionByZero));
+//! ```
+//!
+//! As the tree builder evaluates operators from left to right, the
+//! parser applies the precedences and associativities by building the
+//! subexpressions and combining them.
+
+use {
+    crate::{parse_borrowed::*, *},
+    core::{convert::TryFrom, fmt, str::FromStr},
+};
+
+/// How a sequence of operators of the same precedence is grouped
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Associativity {
+    /// `a - b - c` is `(a - b) - c`
+    Left,
+    /// `a ^ b ^ c` is `a ^ (b ^ c)`
+    Right,
+}
+
+/// The arithmetic operators
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ArithOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    /// The unary minus
+    Neg,
+    Pow,
+}
+
+impl ArithOp {
+    /// the precedence of the operator: the higher, the tighter
+    ///
+    /// The unary minus binds tighter than the other operators but
+    /// `Pow`, so that `-2^2` is `-(2^2)`.
+    pub fn precedence(self) -> u8 {
+        match self {
+            Self::Add | Self::Sub => 1,
+            Self::Mul | Self::Div => 2,
+            Self::Neg => 3,
+            Self::Pow => 4,
+        }
+    }
+    /// how the operator is grouped with the operators of the same
+    /// precedence
+    pub fn associativity(self) -> Associativity {
+        match self {
+            Self::Pow => Associativity::Right,
+            _ => Associativity::Left,
+        }
+    }
+    /// apply the operator to integer operands
+    pub fn eval_i64(self, a: i64, b: Option<i64>) -> Result<i64, ArithError> {
+        let result = match (self, b) {
+            (Self::Add, Some(b)) => a.checked_add(b),
+            (Self::Sub, Some(b)) => a.checked_sub(b),
+            (Self::Mul, Some(b)) => a.checked_mul(b),
+            (Self::Div, Some(0)) => return Err(ArithError::DivisionByZero),
+            (Self::Div, Some(b)) => a.checked_div(b),
+            (Self::Pow, Some(b)) if b < 0 => return Err(ArithError::NegativeExponent),
+            (Self::Pow, Some(b)) => u32::try_from(b).ok().and_then(|b| a.checked_pow(b)),
+            _ => a.checked_neg(),
+        };
+        result.ok_or(ArithError::Overflow)
+    }
+    /// apply the operator to float operands
+    #[cfg(feature = "std")]
+    pub fn eval_f64(self, a: f64, b: Option<f64>) -> Result<f64, ArithError> {
+        match (self, b) {
+            (Self::Add, Some(b)) => Ok(a + b),
+            (Self::Sub, Some(b)) => Ok(a - b),
+            (Self::Mul, Some(b)) => Ok(a * b),
+            (Self::Div, Some(0.0)) => Err(ArithError::DivisionByZero),
+            (Self::Div, Some(b)) => Ok(a / b),
+            (Self::Pow, Some(b)) => Ok(a.powf(b)),
+            _ => Ok(-a),
+        }
+    }
+}
+
+impl fmt::Display for ArithOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Add => write!(f, "+"),
+            Self::Sub | Self::Neg => write!(f, "-"),
+            Self::Mul => write!(f, "*"),
+            Self::Div => write!(f, "/"),
+            Self::Pow => write!(f, "^"),
+        }
+    }
+}
+
+impl OpArity for ArithOp {
+    fn arity(&self) -> Arity {
+        match self {
+            Self::Neg => Arity::Unary,
+            _ => Arity::Binary,
+        }
+    }
+}
+
+/// Error returned when an arithmetic expression can't be evaluated
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ArithError {
+    /// A divisor is zero
+    DivisionByZero,
+    /// An integer result doesn't fit in an `i64`
+    Overflow,
+    /// An integer is raised to a negative power
+    NegativeExponent,
+}
+
+impl fmt::Display for ArithError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DivisionByZero => write!(f, "division by zero"),
+            Self::Overflow => write!(f, "integer overflow"),
+            Self::NegativeExponent => write!(f, "negative exponent of an integer"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ArithError {}
+
+/// The error of a lexeme found where it can't be
+fn unexpected(offset: usize, lexeme: &Lexeme<'_, ArithOp>) -> ParseError {
+    match lexeme {
+        Lexeme::Word(_) | Lexeme::Quoted(_) => ParseError::UnexpectedAtom { offset },
+        Lexeme::Operator(_) => ParseError::UnexpectedOperator { offset },
+        Lexeme::OpeningPar => ParseError::UnexpectedOpeningPar { offset },
+        Lexeme::ClosingPar => ParseError::UnexpectedClosingPar { offset },
+    }
+}
+
+/// The maximal number of nested subexpressions (parenthesized groups,
+/// negations or operands of a right associative operator) accepted
+/// by `parse_arith_expr`, as the parser is recursive
+pub const MAX_ARITH_DEPTH: usize = 200;
+
+/// A precedence climbing parser of the lexemes of an expression
+struct Parser<'a> {
+    lexemes: Vec<(usize, Lexeme<'a, ArithOp>)>,
+    pos: usize,
+    end: usize,
+    depth: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn next(&mut self) -> Option<&(usize, Lexeme<'a, ArithOp>)> {
+        let lexeme = self.lexemes.get(self.pos);
+        self.pos += 1;
+        lexeme
+    }
+    /// parse the expression starting at the current position and made
+    /// of the binary operators of at least the given precedence
+    fn expr<A>(&mut self, min_precedence: u8) -> Result<BeTree<ArithOp, A>, ParseError>
+    where
+        A: fmt::Debug + Clone + FromStr,
+    {
+        if self.depth == MAX_ARITH_DEPTH {
+            let offset = self
+                .lexemes
+                .get(self.pos)
+                .map_or(self.end, |&(offset, _)| offset);
+            return Err(ParseError::TooDeep { offset });
+        }
+        self.depth += 1;
+        let mut left = self.operand()?;
+        while let Some(&(_, Lexeme::Operator(op))) = self.lexemes.get(self.pos) {
+            let precedence = op.precedence();
+            if precedence < min_precedence {
+                break;
+            }
+            self.pos += 1;
+            let right = match op.associativity() {
+                Associativity::Left => self.expr(precedence + 1)?,
+                Associativity::Right => self.expr(precedence)?,
+            };
+            left = left.combine(op, right);
+        }
+        self.depth -= 1;
+        Ok(left)
+    }
+    /// parse a number, a parenthesized expression, or a negated operand
+    fn operand<A>(&mut self) -> Result<BeTree<ArithOp, A>, ParseError>
+    where
+        A: fmt::Debug + Clone + FromStr,
+    {
+        let end = self.end;
+        match self.next() {
+            None => Err(ParseError::UnexpectedEnd { offset: end }),
+            Some(&(offset, Lexeme::Word(text))) => {
+                let atom = text
+                    .parse()
+                    .map_err(|_| ParseError::InvalidAtom { offset })?;
+                let mut tree = BeTree::new();
+                tree.push_atom(atom);
+                Ok(tree)
+            }
+            // in operand position, a minus is the unary one
+            Some((_, Lexeme::Operator(ArithOp::Sub))) => {
+                let mut tree = self.expr(ArithOp::Neg.precedence())?;
+                tree.wrap_unary(ArithOp::Neg);
+                Ok(tree)
+            }
+            Some((_, Lexeme::OpeningPar)) => {
+                let tree = self.expr(0)?;
+                match self.next() {
+                    None => Err(ParseError::UnexpectedEnd { offset: end }),
+                    Some((_, Lexeme::ClosingPar)) => Ok(tree),
+                    Some((offset, lexeme)) => Err(unexpected(*offset, lexeme)),
+                }
+            }
+            Some((offset, lexeme)) => Err(unexpected(*offset, lexeme)),
+        }
+    }
+}
+
+/// parse an arithmetic expression.
+///
+/// The operators are `+`, `-` (binary or unary), `*`, `/` and `^`, with
+/// the usual precedences (see `ArithOp::precedence`), `^` being right
+/// associative and the other binary operators left associative.
+/// Parenthesis group subexpressions and whitespaces are ignored. Atoms
+/// are parsed with `FromStr`, so that `i64` and `f64` are supported.
+///
+/// An empty input gives an empty tree, and an expression nested deeper
+/// than `MAX_ARITH_DEPTH` levels gives a `ParseError::TooDeep`.
+pub fn parse_arith_expr<A>(input: &str) -> Result<BeTree<ArithOp, A>, ParseError>
+where
+    A: fmt::Debug + Clone + FromStr,
+{
+    let classify = |c: char| match c {
+        '+' => CharClass::Operator(ArithOp::Add),
+        '-' => CharClass::Operator(ArithOp::Sub),
+        '*' => CharClass::Operator(ArithOp::Mul),
+        '/' => CharClass::Operator(ArithOp::Div),
+        '^' => CharClass::Operator(ArithOp::Pow),
+        '(' => CharClass::OpeningPar,
+        ')' => CharClass::ClosingPar,
+        _ if c.is_whitespace() => CharClass::Space,
+        _ => CharClass::Atom,
+    };
+    let mut lexemes = Vec::new();
+    scan(input, classify, |offset, lexeme| {
+        lexemes.push((offset, lexeme));
+        Ok(())
+    })?;
+    if lexemes.is_empty() {
+        return Ok(BeTree::new());
+    }
+    let mut parser = Parser {
+        lexemes,
+        pos: 0,
+        end: input.len(),
+        depth: 0,
+    };
+    let tree = parser.expr(0)?;
+    match parser.next() {
+        None => Ok(tree),
+        Some((offset, lexeme)) => Err(unexpected(*offset, lexeme)),
+    }
+}
+
+/// evaluate an integer expression, with checked operations.
+///
+/// Return `None` if the expression is empty, and the first error of
+/// an operation (for example a division by zero) if any.
+pub fn eval_i64(tree: &BeTree<ArithOp, i64>) -> Result<Option<i64>, ArithError> {
+    tree.eval_faillible(|&n| Ok(n), |op, a, b| op.eval_i64(a, b), |_, _| false)
+}
+
+/// evaluate a float expression.
+///
+/// Return `None` if the expression is empty, and an error if there's
+/// a division by zero.
+#[cfg(feature = "std")]
+pub fn eval_f64(tree: &BeTree<ArithOp, f64>) -> Result<Option<f64>, ArithError> {
+    tree.eval_faillible(|&n| Ok(n), |op, a, b| op.eval_f64(a, b), |_, _| false)
+}
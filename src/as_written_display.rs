@@ -0,0 +1,81 @@
+use {crate::*, core::fmt};
+
+/// A rendering of the expression as it was written, with exactly the
+/// parenthesis of the user, for example `((a)) & (b | c)`, using the
+/// `Display` implementations of the operators and atoms.
+///
+/// Parenthesis are still added where the structure requires them (for
+/// example after a rewriting), so that the rendering always denotes
+/// the same expression. Binary operators are surrounded with spaces
+/// while unary operators are written just before their operand.
+///
+/// It's obtained with `BeTree::display_as_written`.
+pub struct AsWrittenDisplay<'t, Op, Atom> {
+    tree: &'t BeTree<Op, Atom>,
+}
+
+impl<Op, Atom> AsWrittenDisplay<'_, Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq + fmt::Display,
+    Atom: fmt::Debug + Clone + fmt::Display,
+{
+    fn fmt_child(
+        &self,
+        child: Child,
+        needs_group: bool,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        let node_idx = match child {
+            Child::None => return Ok(()),
+            Child::Atom(atom_idx) => return write!(f, "{}", &self.tree.atoms[atom_idx]),
+            Child::Node(node_idx) => node_idx,
+        };
+        let node = &self.tree.nodes[node_idx];
+        let in_parens = node.explicit || (needs_group && node.operator.is_some() && !node.unary);
+        if in_parens {
+            write!(f, "(")?;
+        }
+        match &node.operator {
+            None => self.fmt_child(node.left(), false, f)?,
+            Some(op) if node.unary => {
+                write!(f, "{}", op)?;
+                self.fmt_child(node.left(), true, f)?;
+            }
+            Some(op) => {
+                self.fmt_child(node.left(), false, f)?;
+                write!(f, " {}", op)?;
+                if node.right().is_some() {
+                    write!(f, " ")?;
+                    self.fmt_child(node.right(), true, f)?;
+                }
+            }
+        }
+        if in_parens {
+            write!(f, ")")?;
+        }
+        Ok(())
+    }
+}
+
+impl<Op, Atom> fmt::Display for AsWrittenDisplay<'_, Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq + fmt::Display,
+    Atom: fmt::Debug + Clone + fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_child(Child::Node(self.tree.head), false, f)
+    }
+}
+
+impl<Op, Atom> BeTree<Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq + fmt::Display,
+    Atom: fmt::Debug + Clone + fmt::Display,
+{
+    /// return a displayable rendering of the expression with the
+    /// parenthesis of the user, for example `((a)) & (b | c)`
+    /// (see `was_explicit_group`)
+    pub fn display_as_written(&self) -> AsWrittenDisplay<'_, Op, Atom> {
+        AsWrittenDisplay { tree: self }
+    }
+}
@@ -0,0 +1,45 @@
+use {crate::*, core::fmt};
+
+/// The position of an atom in the tree, as given to the
+/// function of `map_atoms_in_place`
+#[derive(Debug)]
+pub struct AtomContext<'t, Op, Meta = ()> {
+    pub(crate) nodes: &'t [Node<Op, Meta>],
+    pub(crate) ancestors: &'t [NodeId],
+    pub(crate) side: Side,
+}
+
+impl<'t, Op, Meta> AtomContext<'t, Op, Meta>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+{
+    /// the ids of the nodes enclosing the atom, from the head
+    /// to the node holding the atom
+    pub fn ancestors(&self) -> &'t [NodeId] {
+        self.ancestors
+    }
+    /// the id of the node holding the atom
+    pub fn parent(&self) -> NodeId {
+        self.ancestors[self.ancestors.len() - 1]
+    }
+    /// the side of the atom in its parent node
+    pub fn side(&self) -> Side {
+        self.side
+    }
+    /// iterate on the operators enclosing the atom, from the
+    /// root to the atom's parent node, with for each operator
+    /// whether it's unary.
+    ///
+    /// Nodes without operator (i.e. parenthesis) are skipped.
+    pub fn operators(&self) -> impl DoubleEndedIterator<Item = (&'t Op, bool)> + 't {
+        let nodes = self.nodes;
+        self.ancestors.iter().filter_map(move |&node_id| {
+            let node = &nodes[node_id];
+            node.operator.as_ref().map(|op| (op, node.unary))
+        })
+    }
+    /// return the nearest enclosing operator, if any
+    pub fn parent_operator(&self) -> Option<(&'t Op, bool)> {
+        self.operators().next_back()
+    }
+}
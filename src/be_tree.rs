@@ -1,9 +1,11 @@
-use {crate::*, std::fmt};
-
-pub type AtomId = usize;
+use {
+    crate::*,
+    core::{convert::TryFrom, fmt, mem},
+};
 
 #[derive(Debug, Clone, Copy, PartialEq)]
-enum TokenType {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) enum TokenType {
     Nothing,
     Atom,
     Operator,
@@ -11,52 +13,97 @@ enum TokenType {
     ClosingPar,
 }
 
-/// Something that can be added to the tree
-pub enum Token<Op, Atom> {
-    Atom(Atom),
-    Operator(Op),
-    OpeningParenthesis,
-    ClosingParenthesis,
-}
+/// The storage of the atoms of a tree
+#[cfg(not(any(feature = "inline-storage", feature = "arena")))]
+pub(crate) type Atoms<Atom> = Vec<Atom>;
+/// The storage of the nodes of a tree, with the first one inline so
+/// that an empty tree, which has only its head node, doesn't allocate
+#[cfg(not(any(feature = "inline-storage", feature = "arena")))]
+pub(crate) type Nodes<Op, Meta = ()> = SmallVec<Node<Op, Meta>, 1>;
+
+/// The storage of the atoms of a tree, inline up to 4 atoms
+#[cfg(all(feature = "inline-storage", not(feature = "arena")))]
+pub(crate) type Atoms<Atom> = SmallVec<Atom, 4>;
+/// The storage of the nodes of a tree, inline up to 8 nodes
+#[cfg(all(feature = "inline-storage", not(feature = "arena")))]
+pub(crate) type Nodes<Op, Meta = ()> = SmallVec<Node<Op, Meta>, 8>;
+
+/// The storage of the atoms of a tree, in the global heap or in the
+/// arena given to `new_in`
+#[cfg(all(feature = "arena", not(feature = "inline-storage")))]
+pub(crate) type Atoms<Atom> = ArenaVec<Atom, 1>;
+/// The storage of the nodes of a tree, in the global heap or in the
+/// arena given to `new_in`
+#[cfg(all(feature = "arena", not(feature = "inline-storage")))]
+pub(crate) type Nodes<Op, Meta = ()> = ArenaVec<Node<Op, Meta>, 1>;
+
+/// The storage of the atoms of a tree, in the arena given to `new_in`,
+/// or inline up to 4 atoms
+#[cfg(all(feature = "arena", feature = "inline-storage"))]
+pub(crate) type Atoms<Atom> = ArenaVec<Atom, 4>;
+/// The storage of the nodes of a tree, in the arena given to `new_in`,
+/// or inline up to 8 nodes
+#[cfg(all(feature = "arena", feature = "inline-storage"))]
+pub(crate) type Nodes<Op, Meta = ()> = ArenaVec<Node<Op, Meta>, 8>;
 
 /// An expression which may contain unary and binary operations
-#[derive(Debug, Clone)]
-pub struct BeTree<Op, Atom>
-where
-    Op: fmt::Debug + Clone + PartialEq,
-    Atom: fmt::Debug + Clone,
-{
-    atoms: Vec<Atom>,
-    nodes: Vec<Node<Op>>,
-    head: NodeId, // node index - where to start iterating
-    tail: NodeId, // node index - where to add new nodes
-    last_pushed: TokenType,
-    op_count: usize, // number of operators
-    openness: usize, // opening pars minus closing pars
+///
+/// Its `Debug` implementation writes the indented form of `write_tree`,
+/// one line per node or atom. Use `display_compact` for a one line form.
+///
+/// Operator nodes may carry some metadata of type `Meta`, for example
+/// the span of the operator in the parsed string, see
+/// `push_operator_with_meta`. Most features other than building,
+/// accessing and evaluating the tree are only provided for trees
+/// without metadata, which can be obtained with `map_meta`.
+#[derive(Clone)]
+pub struct BeTree<Op, Atom, Meta = ()> {
+    pub(crate) atoms: Atoms<Atom>,
+    pub(crate) nodes: Nodes<Op, Meta>,
+    pub(crate) head: NodeId, // node index - where to start iterating
+    pub(crate) tail: NodeId, // node index - where to add new nodes
+    pub(crate) last_pushed: TokenType,
+    pub(crate) op_count: usize, // number of operators
+    pub(crate) openness: usize, // opening pars minus closing pars
+    pub(crate) epoch: u64,      // incremented by structural edits
 }
 
-impl<Op, Atom> Default for BeTree<Op, Atom>
-where
-    Op: fmt::Debug + Clone + PartialEq,
-    Atom: fmt::Debug + Clone,
-{
+impl<Op, Atom, Meta: Default> Default for BeTree<Op, Atom, Meta> {
     fn default() -> Self {
         Self {
-            atoms: Vec::new(),
-            nodes: vec![Node::empty()],
-            head: 0,
-            tail: 0,
+            atoms: Atoms::new(),
+            nodes: core::iter::once(Node::with_meta(None, false, Meta::default())).collect(),
+            head: NodeId::new(0),
+            tail: NodeId::new(0),
             last_pushed: TokenType::Nothing,
             op_count: 0,
             openness: 0,
+            epoch: 0,
         }
     }
 }
 
-impl<Op, Atom> PartialEq for BeTree<Op, Atom>
+impl<Op, Atom, Meta> fmt::Debug for BeTree<Op, Atom, Meta>
 where
-    Op: fmt::Debug + Clone + PartialEq,
-    Atom: fmt::Debug + Clone + PartialEq,
+    Op: fmt::Debug,
+    Atom: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "BeTree")?;
+        self.write_node(f, self.head, 1)
+    }
+}
+
+/// Compare the representations of the trees, including the orphan
+/// nodes and the state of the builder, but not their edits epochs.
+///
+/// Use `structural_eq` to compare the expressions (this is the
+/// equality consistent with `Hash`).
+impl<Op, Atom, Meta> PartialEq for BeTree<Op, Atom, Meta>
+where
+    Op: PartialEq,
+    Atom: PartialEq,
+    Meta: PartialEq,
 {
     fn eq(&self, other: &Self) -> bool {
         self.atoms == other.atoms
@@ -69,25 +116,118 @@ where
     }
 }
 
-impl<Op, Atom> BeTree<Op, Atom>
-where
-    Op: fmt::Debug + Clone + PartialEq,
-    Atom: fmt::Debug + Clone,
-{
+impl<Op, Atom> BeTree<Op, Atom> {
     /// create an empty expression, ready to be completed
+    ///
+    /// This doesn't allocate.
+    ///
+    /// Use `default` to create a tree with metadata.
     pub fn new() -> Self {
         Self::default()
     }
+}
+
+impl<Op, Atom, Meta> BeTree<Op, Atom, Meta> {
+    /// take the expression, leaving an empty one in its place,
+    /// which doesn't allocate (this is `mem::take`)
+    pub fn take(&mut self) -> Self
+    where
+        Meta: Default,
+    {
+        let epoch = self.epoch;
+        let tree = mem::take(self);
+        self.epoch = epoch;
+        self.bump_epoch();
+        tree
+    }
+
+    /// empty the expression but keep its allocated storage, so that
+    /// building many short-lived trees in turn in the same one doesn't
+    /// allocate once it's big enough.
+    ///
+    /// Node and atom ids of the previous expression become invalid.
+    pub fn clear(&mut self)
+    where
+        Meta: Default,
+    {
+        self.atoms.clear();
+        self.nodes.clear();
+        self.nodes
+            .push(Node::with_meta(None, false, Meta::default()));
+        self.head = NodeId::new(0);
+        self.tail = NodeId::new(0);
+        self.last_pushed = TokenType::Nothing;
+        self.op_count = 0;
+        self.openness = 0;
+        self.bump_epoch();
+    }
+
+    /// a counter incremented by the operations which change the links
+    /// between nodes and atoms, so that ids kept across them may refer
+    /// to other elements or to elements which aren't reachable anymore.
+    ///
+    /// Snapshot it when keeping ids, and compare it before using them.
+    ///
+    /// Pushing tokens, and changing atoms or operators in place, don't
+    /// change the epoch, as existing ids keep referring to the same
+    /// elements. All structural edits (`clear`, `take`, `compact`,
+    /// `remove_atom`, `prune`, `filter_atoms`, `wrap_unary`, rotations,
+    /// `detach`, `attach`, cursor edits, rewrites, etc.) increment it,
+    /// even if they finally change nothing. Functions building a new
+    /// tree with other ids from a tree, like `normalize`, give it an
+    /// epoch greater than the one of the original tree.
+    ///
+    /// The epoch isn't compared by `==`, nor serialized.
+    pub fn edits_epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    /// record a change of the structure, see `edits_epoch`
+    pub(crate) fn bump_epoch(&mut self) {
+        self.epoch = self.epoch.wrapping_add(1);
+    }
+
+    pub fn node(&self, node_id: NodeId) -> Option<&Node<Op, Meta>> {
+        self.nodes.get(node_id.index())
+    }
+
+    /// return the metadata of a node, or none if there's no node
+    /// with this id
+    pub fn node_meta(&self, node_id: NodeId) -> Option<&Meta> {
+        self.node(node_id).map(Node::meta)
+    }
 
-    pub fn node(&self, node_id: NodeId) -> Option<&Node<Op>> {
-        self.nodes.get(node_id)
+    /// return a mutable reference to the metadata of a node, or none
+    /// if there's no node with this id
+    pub fn node_meta_mut(&mut self, node_id: NodeId) -> Option<&mut Meta> {
+        self.nodes
+            .get_mut(node_id.index())
+            .map(|node| &mut node.meta)
     }
 
     pub fn atom(&self, atom_id: AtomId) -> Option<&Atom> {
-        self.atoms.get(atom_id)
+        self.atoms.get(atom_id.index())
+    }
+
+    /// return a mutable reference to an atom, or none if there's
+    /// no atom with this id.
+    ///
+    /// This doesn't change the structure of the tree, so it's a cheap
+    /// way to reuse an expression as a template whose atoms change
+    /// between evaluations.
+    pub fn atom_mut(&mut self, atom_id: AtomId) -> Option<&mut Atom> {
+        self.atoms.get_mut(atom_id.index())
+    }
+
+    /// replace an atom, returning the old one, or none (and dropping
+    /// the new atom) if there's no atom with this id.
+    ///
+    /// As with `atom_mut`, the structure of the tree isn't changed.
+    pub fn set_atom(&mut self, atom_id: AtomId, atom: Atom) -> Option<Atom> {
+        self.atom_mut(atom_id).map(|old| mem::replace(old, atom))
     }
 
-    pub fn head(&self) -> &Node<Op> {
+    pub fn head(&self) -> &Node<Op, Meta> {
         &self.nodes[self.head]
     }
 
@@ -106,21 +246,77 @@ where
         self.atoms.is_empty()
     }
 
+    /// return the number of operators of the tree, as maintained
+    /// while building and editing it, without traversal.
+    ///
+    /// See `stats` or `operator_census` for counts of the operators
+    /// reachable from the head.
+    pub fn op_count(&self) -> usize {
+        self.op_count
+    }
+
+    /// return the number of atoms in the arena, including the ones
+    /// which some edits made unreachable (see `compact`)
+    pub fn atom_count(&self) -> usize {
+        self.atoms.len()
+    }
+
     /// tell whether the tree is exactly one atom
     pub fn is_atomic(&self) -> bool {
         self.atoms.len() == 1 && self.op_count == 0
     }
 
-    /// take the atoms of the tree
+    /// take all the atoms ever pushed or stored in the tree, in the
+    /// order they were stored.
+    ///
+    /// **This includes the atoms which aren't part of the expression
+    /// anymore**, because they were removed or replaced by edits. Use
+    /// `into_reachable_atoms` to get only the atoms of the expression,
+    /// in their order in the expression.
     pub fn atoms(self) -> Vec<Atom> {
-        self.atoms
+        self.atoms.into_iter().collect()
     }
 
-    /// iterate on all atoms
-    pub fn iter_atoms(&self) -> std::slice::Iter<'_, Atom> {
+    /// iterate on all the atoms ever pushed or stored in the tree, in
+    /// the order they were stored.
+    ///
+    /// **This includes the atoms which aren't part of the expression
+    /// anymore**, because they were removed or replaced by edits. Use
+    /// `iter_reachable_atoms` to iterate only on the atoms of the
+    /// expression, in their order in the expression.
+    pub fn iter_atoms(&self) -> core::slice::Iter<'_, Atom> {
         self.atoms.iter()
     }
 
+    /// iterate mutably on all atoms, for example to normalize them
+    /// after parsing.
+    ///
+    /// Like `iter_atoms`, this iterates over the arena of atoms, which,
+    /// after some edits, contains atoms which aren't in the expression
+    /// anymore (call `compact` first to get rid of them).
+    ///
+    /// ```
+    /// use bet::BeTree;
+    ///
+    /// let mut expr = BeTree::new();
+    /// expr.push_atom(" Alpha ".to_string());
+    /// expr.push_operator('&');
+    /// expr.push_atom("BETA".to_string());
+    /// for atom in expr.iter_atoms_mut() {
+    ///     *atom = atom.trim().to_lowercase();
+    /// }
+    /// let known = ["alpha", "beta"];
+    /// let all_known = expr.eval(
+    ///     |atom| known.contains(&atom.as_str()),
+    ///     |_, a, b| a && b.unwrap_or(true),
+    ///     |_, &a| !a,
+    /// );
+    /// assert_eq!(all_known, Some(true));
+    /// ```
+    pub fn iter_atoms_mut(&mut self) -> core::slice::IterMut<'_, Atom> {
+        self.atoms.iter_mut()
+    }
+
     /// returns a reference to the last atom if it's the last
     /// pushed token. Return none in other cases (including
     /// when no atom has been pushed at all)
@@ -132,6 +328,19 @@ where
         }
     }
 
+    /// returns a mutable reference to the last atom if it's the last
+    /// pushed token, for example to fix it while it's being parsed.
+    /// Return none in other cases, as `current_atom`.
+    ///
+    /// Contrary to `mutate_or_create_atom`, this never pushes an atom.
+    pub fn current_atom_mut(&mut self) -> Option<&mut Atom> {
+        if self.last_pushed == TokenType::Atom {
+            self.atoms.last_mut()
+        } else {
+            None
+        }
+    }
+
     /// return the count of open parenthesis minus the
     /// one of closing parenthesis. Illegal closing parenthesis
     /// are ignored (hence why this count can be a usize)
@@ -139,33 +348,41 @@ where
         self.openness
     }
 
-    fn store_node(&mut self, node: Node<Op>) -> usize {
+    pub(crate) fn store_node(&mut self, node: Node<Op, Meta>) -> NodeId {
+        let node_id = NodeId::from_index(self.nodes.len());
         self.nodes.push(node);
-        self.nodes.len() - 1
+        node_id
     }
 
-    fn store_atom(&mut self, atom: Atom) -> usize {
+    pub(crate) fn store_atom(&mut self, atom: Atom) -> AtomId {
+        let atom_id = AtomId::from_index(self.atoms.len());
         self.atoms.push(atom);
-        self.atoms.len() - 1
+        atom_id
     }
 
     fn add_child(&mut self, child: Child) {
         debug_assert!(!self.nodes[self.tail].is_full());
-        if self.nodes[self.tail].left.is_some() {
-            self.nodes[self.tail].right = child;
+        if self.nodes[self.tail].left().is_some() {
+            self.nodes[self.tail].set_right(child);
         } else {
-            self.nodes[self.tail].left = child;
+            self.nodes[self.tail].set_left(child);
         }
     }
 
-    fn add_child_node(&mut self, child_idx: usize) {
-        self.nodes[child_idx].parent = Some(self.tail);
+    fn add_child_node(&mut self, child_idx: NodeId) {
+        self.nodes[child_idx].set_parent(Some(self.tail));
         self.add_child(Child::Node(child_idx));
         self.tail = child_idx;
     }
 
     /// add one of the possible token: parenthesis, operator or atom
-    pub fn push(&mut self, token: Token<Op, Atom>) {
+    ///
+    /// Panics if the tree already holds as many nodes or atoms as ids
+    /// can address (see `MAX_ID` and `try_push`).
+    pub fn push(&mut self, token: Token<Op, Atom>)
+    where
+        Meta: Default,
+    {
         match token {
             Token::Atom(atom) => self.push_atom(atom),
             Token::Operator(op) => self.push_operator(op),
@@ -174,21 +391,41 @@ where
         }
     }
 
+    /// add a token as `push` does, but return an error instead of
+    /// panicking when the tree already holds as many nodes or atoms
+    /// as ids can address
+    pub fn try_push(&mut self, token: Token<Op, Atom>) -> Result<(), IdOverflow>
+    where
+        Meta: Default,
+    {
+        match &token {
+            Token::Atom(_) => {
+                AtomId::try_from(self.atoms.len())?;
+            }
+            Token::Operator(_) | Token::OpeningParenthesis => {
+                NodeId::try_from(self.nodes.len())?;
+            }
+            Token::ClosingParenthesis => {}
+        }
+        self.push(token);
+        Ok(())
+    }
+
     /// add an atom in a left-to-right expression building
     pub fn push_atom(&mut self, atom: Atom) {
         self.last_pushed = TokenType::Atom;
         let atom_idx = self.store_atom(atom);
-        if self.nodes[self.tail].left.is_none() {
-            self.nodes[self.tail].left = Child::Atom(atom_idx);
+        if self.nodes[self.tail].left().is_none() {
+            self.nodes[self.tail].set_left(Child::Atom(atom_idx));
             return;
         }
         let mut current = self.tail;
         loop {
-            if self.nodes[current].right.is_none() {
-                self.nodes[current].right = Child::Atom(atom_idx);
+            if self.nodes[current].right().is_none() {
+                self.nodes[current].set_right(Child::Atom(atom_idx));
                 return;
             }
-            let Some(parent) = self.nodes[current].parent else {
+            let Some(parent) = self.nodes[current].parent() else {
                 // no parent for {current}, atom is useless
                 return;
             };
@@ -210,9 +447,14 @@ where
     }
 
     /// add an opening parenthesis to the expression
-    pub fn open_par(&mut self) {
+    pub fn open_par(&mut self)
+    where
+        Meta: Default,
+    {
         self.last_pushed = TokenType::OpeningPar;
-        let node_idx = self.store_node(Node::empty());
+        let mut node = Node::with_meta(None, false, Meta::default());
+        node.explicit = true;
+        let node_idx = self.store_node(node);
         self.add_child_node(node_idx);
         self.openness += 1;
     }
@@ -220,7 +462,8 @@ where
     /// add a closing parenthesis to the expression
     pub fn close_par(&mut self) {
         self.last_pushed = TokenType::ClosingPar;
-        if let Some(parent) = self.nodes[self.tail].parent {
+        self.close_unary_operators();
+        if let Some(parent) = self.nodes[self.tail].parent() {
             self.tail = parent;
             self.openness -= 1;
         }
@@ -228,49 +471,65 @@ where
         // many closing parenthesis in the future
     }
 
-    fn push_unary_operator(&mut self, operator: Op) {
-        let node_idx = self.store_node(Node {
-            operator: Some(operator),
-            parent: Some(self.tail),
-            left: Child::None,
-            right: Child::None,
-            unary: true,
-        });
+    fn push_unary_operator(&mut self, operator: Op, meta: Meta) {
+        let mut node = Node::with_meta(Some(operator), true, meta);
+        node.set_parent(Some(self.tail));
+        let node_idx = self.store_node(node);
         self.add_child(Child::Node(node_idx));
         self.tail = node_idx;
     }
 
-    fn push_binary_operator(&mut self, operator: Op) {
-        if !self.nodes[self.tail].is_full() {
+    /// move the tail up from the unary operators whose operand is
+    /// complete, as a unary operator applies only to its operand
+    fn close_unary_operators(&mut self) {
+        while self.nodes[self.tail].unary && self.nodes[self.tail].is_full() {
+            let Some(parent) = self.nodes[self.tail].parent() else {
+                break;
+            };
+            self.tail = parent;
+        }
+    }
+
+    fn push_binary_operator(&mut self, operator: Op, meta: Meta) {
+        self.close_unary_operators();
+        // open groups have a parent, so a group without parent is closed
+        // (it's the head kept by a simplification) and stays around its content
+        let closed_group =
+            self.nodes[self.tail].explicit && self.nodes[self.tail].parent().is_none();
+        if !self.nodes[self.tail].is_full() && !closed_group {
             self.nodes[self.tail].operator = Some(operator);
+            self.nodes[self.tail].meta = meta;
             return;
         }
-        // we replace the current tail
-        // which becomes the left child of the new node
-        let new_idx = self.store_node(Node {
-            operator: Some(operator),
-            parent: self.nodes[self.tail].parent,
-            left: Child::Node(self.tail),
-            right: Child::None,
-            unary: false,
-        });
+        // we replace the current tail, which keeps its metadata
+        // and becomes the left child of the new node
+        let mut node = Node::with_meta(Some(operator), false, meta);
+        node.set_parent(self.nodes[self.tail].parent());
+        if !closed_group {
+            // if the tail is an open group, the new node takes its place
+            // between the parenthesis
+            node.explicit = mem::take(&mut self.nodes[self.tail].explicit);
+        }
+        node.set_left(Child::Node(self.tail));
+        let new_idx = self.store_node(node);
         // we connect the parent to the new node
-        let Some(parent_idx) = self.nodes[new_idx].parent else {
+        let Some(parent_idx) = self.nodes[new_idx].parent() else {
             // the replaced node was the head
+            self.nodes[self.tail].set_parent(Some(new_idx));
             self.head = new_idx;
             self.tail = new_idx;
             return;
         };
-        if self.nodes[parent_idx].left == Child::Node(self.tail) {
+        if self.nodes[parent_idx].left() == Child::Node(self.tail) {
             // the connection was to the left child
-            self.nodes[parent_idx].left = Child::Node(new_idx);
+            self.nodes[parent_idx].set_left(Child::Node(new_idx));
         } else {
             // it must have been to the right child
-            debug_assert_eq!(self.nodes[parent_idx].right, Child::Node(self.tail));
-            self.nodes[parent_idx].right = Child::Node(new_idx);
+            debug_assert_eq!(self.nodes[parent_idx].right(), Child::Node(self.tail));
+            self.nodes[parent_idx].set_right(Child::Node(new_idx));
         }
         // we connect the tail to the new node
-        self.nodes[self.tail].parent = Some(new_idx);
+        self.nodes[self.tail].set_parent(Some(new_idx));
         // and we update the tail
         self.tail = new_idx;
     }
@@ -278,15 +537,27 @@ where
     /// add an operator right of the expression
     ///
     /// The context will decide whether it's unary or binary
-    pub fn push_operator(&mut self, operator: Op) {
+    pub fn push_operator(&mut self, operator: Op)
+    where
+        Meta: Default,
+    {
+        self.push_operator_with_meta(operator, Meta::default());
+    }
+
+    /// add an operator right of the expression, as `push_operator`,
+    /// with some metadata attached to its node
+    ///
+    /// The metadata stays with the node of the operator when the
+    /// tree is restructured by the following pushes.
+    pub fn push_operator_with_meta(&mut self, operator: Op, meta: Meta) {
         match self.last_pushed {
             TokenType::Atom | TokenType::ClosingPar => {
                 // the operator is binary
-                self.push_binary_operator(operator);
+                self.push_binary_operator(operator, meta);
             }
             _ => {
                 // the operator is unary
-                self.push_unary_operator(operator);
+                self.push_unary_operator(operator, meta);
             }
         }
         self.last_pushed = TokenType::Operator;
@@ -333,30 +604,154 @@ where
         matches!(self.last_pushed, Atom | ClosingPar if self.openness > 0)
     }
 
-    /// produce a new expression by applying a transformation on all atoms
+    /// produce a new expression by applying a transformation on all atoms,
+    /// consuming the tree.
     ///
-    /// The operation will stop at the first error
+    /// Contrary to `try_map_atoms`, the nodes aren't cloned but moved
+    /// and the atoms are given by value to the mapping function.
+    /// Node and atom ids are preserved.
     #[inline]
-    pub fn try_map_atoms<Atom2, Err, F>(&self, f: F) -> Result<BeTree<Op, Atom2>, Err>
+    pub fn into_map_atoms<Atom2, F>(self, f: F) -> BeTree<Op, Atom2, Meta>
     where
-        Atom2: fmt::Debug + Clone,
-        F: Fn(&Atom) -> Result<Atom2, Err>,
+        F: FnMut(Atom) -> Atom2,
     {
-        let mut atoms = Vec::new();
-        for atom in &self.atoms {
+        BeTree {
+            atoms: self.atoms.into_iter().map(f).collect(),
+            nodes: self.nodes,
+            head: self.head,
+            tail: self.tail,
+            last_pushed: self.last_pushed,
+            op_count: self.op_count,
+            openness: self.openness,
+            epoch: self.epoch,
+        }
+    }
+
+    /// produce a new expression by applying a transformation on all atoms,
+    /// consuming the tree.
+    ///
+    /// The operation will stop at the first error.
+    /// Node and atom ids are preserved.
+    #[inline]
+    pub fn try_into_map_atoms<Atom2, Err, F>(self, mut f: F) -> Result<BeTree<Op, Atom2, Meta>, Err>
+    where
+        F: FnMut(Atom) -> Result<Atom2, Err>,
+    {
+        let mut atoms = Atoms::with_capacity(self.atoms.len());
+        for atom in self.atoms {
             atoms.push(f(atom)?);
         }
         Ok(BeTree {
             atoms,
-            nodes: self.nodes.clone(),
+            nodes: self.nodes,
             head: self.head,
             tail: self.tail,
             last_pushed: self.last_pushed,
             op_count: self.op_count,
             openness: self.openness,
+            epoch: self.epoch,
         })
     }
 
+    /// produce a new expression by applying a transformation on the
+    /// metadata of all nodes, consuming the tree.
+    ///
+    /// Use `map_meta(|_| ())` to get a tree without metadata, on which
+    /// all the features of the crate are available.
+    /// Node and atom ids are preserved.
+    pub fn map_meta<Meta2, F>(self, mut f: F) -> BeTree<Op, Atom, Meta2>
+    where
+        F: FnMut(Meta) -> Meta2,
+    {
+        BeTree {
+            atoms: self.atoms,
+            nodes: self
+                .nodes
+                .into_iter()
+                .map(|node| node.into_meta(&mut f))
+                .collect(),
+            head: self.head,
+            tail: self.tail,
+            last_pushed: self.last_pushed,
+            op_count: self.op_count,
+            openness: self.openness,
+            epoch: self.epoch,
+        }
+    }
+
+    /// apply a transformation on all reachable atoms, in place.
+    ///
+    /// Atoms are visited in tree order (left to right), and the
+    /// transformation function receives, along with the atom, its
+    /// context in the tree (enclosing operators, side).
+    pub fn map_atoms_in_place<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&mut Atom, &AtomContext<Op, Meta>),
+    {
+        let mut ancestors = Vec::new();
+        Self::map_node_atoms_in_place(
+            &self.nodes,
+            &mut self.atoms,
+            &mut ancestors,
+            self.head,
+            &mut f,
+        );
+    }
+
+    fn map_node_atoms_in_place<F>(
+        nodes: &[Node<Op, Meta>],
+        atoms: &mut [Atom],
+        ancestors: &mut Vec<NodeId>,
+        node_idx: NodeId,
+        f: &mut F,
+    ) where
+        F: FnMut(&mut Atom, &AtomContext<Op, Meta>),
+    {
+        ancestors.push(node_idx);
+        let node = &nodes[node_idx];
+        for (child, side) in [(node.left(), Side::Left), (node.right(), Side::Right)] {
+            match child {
+                Child::None => {}
+                Child::Node(child_idx) => {
+                    Self::map_node_atoms_in_place(nodes, atoms, ancestors, child_idx, f);
+                }
+                Child::Atom(atom_idx) => {
+                    let context = AtomContext {
+                        nodes,
+                        ancestors,
+                        side,
+                    };
+                    f(&mut atoms[atom_idx], &context);
+                }
+            }
+        }
+        ancestors.pop();
+    }
+
+    /// apply a transformation on the operators of all reachable nodes,
+    /// in place.
+    ///
+    /// The function receives the operator and whether it's used as unary.
+    /// The structure of the tree isn't changed.
+    pub fn map_operators_in_place<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&mut Op, bool),
+    {
+        let mut stack = vec![self.head];
+        while let Some(node_idx) = stack.pop() {
+            let node = &mut self.nodes[node_idx];
+            if let Some(operator) = node.operator.as_mut() {
+                f(operator, node.unary);
+            }
+            for child in [node.right(), node.left()] {
+                if let Child::Node(child_idx) = child {
+                    stack.push(child_idx);
+                }
+            }
+        }
+    }
+
+    #[cfg(not(feature = "dyn-eval"))]
     fn eval_child<R, EvalAtom, EvalOp, ShortCircuit>(
         &self,
         eval_atom: &EvalAtom,
@@ -376,6 +771,7 @@ where
         }
     }
 
+    #[cfg(not(feature = "dyn-eval"))]
     fn eval_child_faillible<Err, R, EvalAtom, EvalOp, ShortCircuit>(
         &self,
         eval_atom: &EvalAtom,
@@ -397,12 +793,13 @@ where
         })
     }
 
+    #[cfg(not(feature = "dyn-eval"))]
     fn eval_node<R, EvalAtom, EvalOp, ShortCircuit>(
         &self,
         eval_atom: &EvalAtom,
         eval_op: &EvalOp,
         short_circuit: &ShortCircuit,
-        node_idx: usize,
+        node_idx: NodeId,
     ) -> Option<R>
     where
         EvalAtom: Fn(&Atom) -> R,
@@ -410,7 +807,7 @@ where
         ShortCircuit: Fn(&Op, &R) -> bool,
     {
         let node = &self.nodes[node_idx];
-        let left_value = self.eval_child(eval_atom, eval_op, short_circuit, node.left);
+        let left_value = self.eval_child(eval_atom, eval_op, short_circuit, node.left());
         let Some(op) = &node.operator else {
             return left_value;
         };
@@ -421,16 +818,17 @@ where
         if short_circuit(op, &left_value) {
             return Some(left_value);
         }
-        let right_value = self.eval_child(eval_atom, eval_op, short_circuit, node.right);
+        let right_value = self.eval_child(eval_atom, eval_op, short_circuit, node.right());
         Some(eval_op(op, left_value, right_value))
     }
 
+    #[cfg(not(feature = "dyn-eval"))]
     fn eval_node_faillible<Err, R, EvalAtom, EvalOp, ShortCircuit>(
         &self,
         eval_atom: &EvalAtom,
         eval_op: &EvalOp,
         short_circuit: &ShortCircuit,
-        node_idx: usize,
+        node_idx: NodeId,
     ) -> Result<Option<R>, Err>
     where
         EvalAtom: Fn(&Atom) -> Result<R, Err>,
@@ -438,7 +836,8 @@ where
         ShortCircuit: Fn(&Op, &R) -> bool,
     {
         let node = &self.nodes[node_idx];
-        let left_value = self.eval_child_faillible(eval_atom, eval_op, short_circuit, node.left)?;
+        let left_value =
+            self.eval_child_faillible(eval_atom, eval_op, short_circuit, node.left())?;
         let Some(op) = &node.operator else {
             return Ok(left_value);
         };
@@ -450,7 +849,7 @@ where
             return Ok(Some(left_value));
         };
         let right_value =
-            self.eval_child_faillible(eval_atom, eval_op, short_circuit, node.right)?;
+            self.eval_child_faillible(eval_atom, eval_op, short_circuit, node.right())?;
         Ok(Some(eval_op(op, left_value, right_value)?))
     }
 
@@ -478,6 +877,13 @@ where
         EvalOp: Fn(&Op, R, Option<R>) -> R,
         ShortCircuit: Fn(&Op, &R) -> bool,
     {
+        #[cfg(feature = "dyn-eval")]
+        {
+            let (mut eval_atom, mut eval_op, mut short_circuit) =
+                (eval_atom, eval_op, short_circuit);
+            self.eval_dyn(&mut eval_atom, &mut eval_op, &mut short_circuit)
+        }
+        #[cfg(not(feature = "dyn-eval"))]
         self.eval_node(&eval_atom, &eval_op, &short_circuit, self.head)
     }
 
@@ -506,41 +912,323 @@ where
         EvalOp: Fn(&Op, R, Option<R>) -> Result<R, Err>,
         ShortCircuit: Fn(&Op, &R) -> bool,
     {
+        #[cfg(feature = "dyn-eval")]
+        {
+            let (mut eval_atom, mut eval_op, mut short_circuit) =
+                (eval_atom, eval_op, short_circuit);
+            self.eval_faillible_dyn(&mut eval_atom, &mut eval_op, &mut short_circuit)
+        }
+        #[cfg(not(feature = "dyn-eval"))]
         self.eval_node_faillible(&eval_atom, &eval_op, &short_circuit, self.head)
     }
+}
 
-    pub fn simplify(&mut self) {
-        while let Node {
-            operator: None,
-            left: Child::Node(node_id),
-            parent: None,
-            right: Child::None,
-            unary: false,
-        } = self.nodes[self.head]
-        {
-            self.nodes[node_id].parent = None;
+/// Mappings of atoms, cloning the nodes
+impl<Op, Atom, Meta> BeTree<Op, Atom, Meta>
+where
+    Op: Clone,
+    Meta: Clone,
+{
+    /// produce a new expression by applying a transformation on all atoms
+    ///
+    /// The operation will stop at the first error
+    #[inline]
+    pub fn try_map_atoms<Atom2, Err, F>(&self, f: F) -> Result<BeTree<Op, Atom2, Meta>, Err>
+    where
+        F: Fn(&Atom) -> Result<Atom2, Err>,
+    {
+        let mut atoms = Atoms::with_capacity(self.atoms.len());
+        for atom in &self.atoms {
+            atoms.push(f(atom)?);
+        }
+        Ok(BeTree {
+            atoms,
+            nodes: self.nodes.clone(),
+            head: self.head,
+            tail: self.tail,
+            last_pushed: self.last_pushed,
+            op_count: self.op_count,
+            openness: self.openness,
+            epoch: self.epoch,
+        })
+    }
+
+    /// produce a new expression by applying a transformation on all atoms,
+    /// the function receiving the id of the atom along with the atom
+    ///
+    /// Atom ids are preserved: the atom of id `atom_id` in the resulting
+    /// tree is the result of `f(atom_id, atom)`. For this to hold, all
+    /// atoms of the arena are mapped, including the ones which aren't
+    /// reachable anymore from the head.
+    #[inline]
+    pub fn map_atoms_indexed<Atom2, F>(&self, f: F) -> BeTree<Op, Atom2, Meta>
+    where
+        F: Fn(AtomId, &Atom) -> Atom2,
+    {
+        BeTree {
+            atoms: self
+                .atoms
+                .iter()
+                .enumerate()
+                .map(|(atom_idx, atom)| f(AtomId::from_index(atom_idx), atom))
+                .collect(),
+            nodes: self.nodes.clone(),
+            head: self.head,
+            tail: self.tail,
+            last_pushed: self.last_pushed,
+            op_count: self.op_count,
+            openness: self.openness,
+            epoch: self.epoch,
+        }
+    }
+
+    /// produce a new expression by applying a transformation on all atoms,
+    /// the function receiving the id of the atom along with the atom
+    ///
+    /// The operation will stop at the first error.
+    ///
+    /// Atom ids are preserved: the atom of id `atom_id` in the resulting
+    /// tree is the result of `f(atom_id, atom)`. For this to hold, all
+    /// atoms of the arena are mapped, including the ones which aren't
+    /// reachable anymore from the head.
+    #[inline]
+    pub fn try_map_atoms_indexed<Atom2, Err, F>(&self, f: F) -> Result<BeTree<Op, Atom2, Meta>, Err>
+    where
+        F: Fn(AtomId, &Atom) -> Result<Atom2, Err>,
+    {
+        let mut atoms = Atoms::with_capacity(self.atoms.len());
+        for (atom_idx, atom) in self.atoms.iter().enumerate() {
+            atoms.push(f(AtomId::from_index(atom_idx), atom)?);
+        }
+        Ok(BeTree {
+            atoms,
+            nodes: self.nodes.clone(),
+            head: self.head,
+            tail: self.tail,
+            last_pushed: self.last_pushed,
+            op_count: self.op_count,
+            openness: self.openness,
+            epoch: self.epoch,
+        })
+    }
+}
+
+/// Mappings of operators, cloning the atoms
+impl<Op, Atom, Meta> BeTree<Op, Atom, Meta>
+where
+    Atom: Clone,
+    Meta: Clone,
+{
+    /// produce a new expression by applying a transformation on all operators.
+    ///
+    /// The function receives the operator and whether it's used as unary.
+    ///
+    /// The structure of the tree, including node and atom ids, is preserved.
+    #[inline]
+    pub fn map_operators<Op2, F>(&self, f: F) -> BeTree<Op2, Atom, Meta>
+    where
+        F: Fn(&Op, bool) -> Op2,
+    {
+        let nodes = self
+            .nodes
+            .iter()
+            .map(|node| node.with_operator(node.operator.as_ref().map(|op| f(op, node.unary))))
+            .collect();
+        BeTree {
+            atoms: self.atoms.clone(),
+            nodes,
+            head: self.head,
+            tail: self.tail,
+            last_pushed: self.last_pushed,
+            op_count: self.op_count,
+            openness: self.openness,
+            epoch: self.epoch,
+        }
+    }
+
+    /// produce a new expression by applying a transformation on all operators.
+    ///
+    /// The function receives the operator and whether it's used as unary.
+    /// The operation will stop at the first error.
+    ///
+    /// The structure of the tree, including node and atom ids, is preserved.
+    #[inline]
+    pub fn try_map_operators<Op2, Err, F>(&self, f: F) -> Result<BeTree<Op2, Atom, Meta>, Err>
+    where
+        F: Fn(&Op, bool) -> Result<Op2, Err>,
+    {
+        let mut nodes = Nodes::with_capacity(self.nodes.len());
+        for node in &self.nodes {
+            let operator = match &node.operator {
+                Some(op) => Some(f(op, node.unary)?),
+                None => None,
+            };
+            nodes.push(node.with_operator(operator));
+        }
+        Ok(BeTree {
+            atoms: self.atoms.clone(),
+            nodes,
+            head: self.head,
+            tail: self.tail,
+            last_pushed: self.last_pushed,
+            op_count: self.op_count,
+            openness: self.openness,
+            epoch: self.epoch,
+        })
+    }
+}
+
+impl<Op, Atom> BeTree<Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone,
+{
+    /// replace all occurrences of an operator in reachable nodes with
+    /// another one, and return the number of replaced occurrences.
+    ///
+    /// Whether the operator is used as unary or binary isn't considered
+    /// nor changed.
+    pub fn replace_operator(&mut self, from: &Op, to: Op) -> usize {
+        let mut count = 0;
+        self.map_operators_in_place(|operator, _| {
+            if operator == from {
+                *operator = to.clone();
+                count += 1;
+            }
+        });
+        count
+    }
+
+    fn simplify_head(&mut self, keep_explicit_groups: bool) {
+        loop {
+            let head = &self.nodes[self.head];
+            let node_id = match head.left() {
+                Child::Node(node_id)
+                    if head.operator.is_none()
+                        && head.parent().is_none()
+                        && head.right().is_none()
+                        && !head.unary
+                        && !(keep_explicit_groups && head.explicit) =>
+                {
+                    node_id
+                }
+                _ => break,
+            };
+            self.nodes[node_id].set_parent(None);
+            if self.tail == self.head {
+                self.tail = node_id;
+            }
             self.head = node_id;
         }
+        self.bump_epoch();
+        self.debug_check_structure();
     }
 
-    pub fn print_child(&self, child: Child, indent: usize) {
+    /// remove the groups wrapping the whole expression, the head
+    /// becoming the first node with an operator or with an atom
+    pub fn simplify(&mut self) {
+        self.simplify_head(false);
+    }
+
+    /// remove the groups wrapping the whole expression, as `simplify`,
+    /// but keep the ones written with parenthesis (see `was_explicit_group`)
+    pub fn simplify_keeping_explicit_groups(&mut self) {
+        self.simplify_head(true);
+    }
+
+    /// tell whether the node is the one of a group written with
+    /// parenthesis, for example to render the expression with the
+    /// parenthesis of the user even when they're redundant.
+    ///
+    /// When a binary operation is written in parenthesis, its node is
+    /// the group: in `a & (b | c)`, the node of `|` is an explicit group.
+    /// The flag is set by `open_par` and kept by the following pushes,
+    /// by `simplify` and by `normalize`, but the nodes made by other
+    /// transformations aren't explicit groups.
+    pub fn was_explicit_group(&self, node_id: NodeId) -> bool {
+        self.node(node_id).is_some_and(Node::is_explicit_group)
+    }
+}
+
+/// Debug writing of the tree
+impl<Op, Atom, Meta> BeTree<Op, Atom, Meta>
+where
+    Op: fmt::Debug,
+    Atom: fmt::Debug,
+{
+    fn write_indent<W: fmt::Write>(w: &mut W, indent: usize) -> fmt::Result {
         for _ in 0..indent {
-            print!(" ");
+            w.write_char(' ')?;
         }
+        Ok(())
+    }
+
+    /// write the child (and its descendants), one line per node
+    /// or atom, each level being indented with one more space
+    pub fn write_child<W: fmt::Write>(
+        &self,
+        w: &mut W,
+        child: Child,
+        indent: usize,
+    ) -> fmt::Result {
         match child {
-            Child::None => println!("-"),
-            Child::Node(node_id) => self.print_node(node_id, indent + 1),
-            Child::Atom(atom_id) => println!("{:?}", &self.atoms[atom_id]),
+            Child::None => {
+                Self::write_indent(w, indent)?;
+                writeln!(w, "-")
+            }
+            Child::Node(node_id) => self.write_node(w, node_id, indent),
+            Child::Atom(atom_id) => {
+                Self::write_indent(w, indent)?;
+                writeln!(w, "{:?}", &self.atoms[atom_id])
+            }
         }
     }
 
-    pub fn print_node(&self, node_id: NodeId, indent: usize) {
+    /// write the node and its descendants, one line per node or atom
+    pub fn write_node<W: fmt::Write>(
+        &self,
+        w: &mut W,
+        node_id: NodeId,
+        indent: usize,
+    ) -> fmt::Result {
         let node = &self.nodes[node_id];
-        println!("[{}] {:?}", node_id, &node.operator);
-        self.print_child(node.left, indent + 1);
-        self.print_child(node.right, indent + 1);
+        Self::write_indent(w, indent)?;
+        writeln!(w, "[{}] {:?}", node_id, &node.operator)?;
+        self.write_child(w, node.left(), indent + 1)?;
+        self.write_child(w, node.right(), indent + 1)
+    }
+
+    /// write the whole tree, as printed by `print_tree`, for example
+    /// in a log or in a test
+    pub fn write_tree<W: fmt::Write>(&self, w: &mut W) -> fmt::Result {
+        self.write_node(w, self.head, 0)
+    }
+
+    /// write the whole tree, as printed by `print_tree`, into an
+    /// `io::Write` such as a file
+    #[cfg(feature = "std")]
+    pub fn write_tree_io<W: std::io::Write>(&self, w: &mut W) -> std::io::Result<()> {
+        let mut s = String::new();
+        self.write_tree(&mut s)
+            .map_err(|_| std::io::ErrorKind::Other)?;
+        w.write_all(s.as_bytes())
+    }
+
+    #[cfg(feature = "std")]
+    pub fn print_child(&self, child: Child, indent: usize) {
+        let mut s = String::new();
+        let _ = self.write_child(&mut s, child, indent);
+        print!("{}", s);
+    }
+
+    #[cfg(feature = "std")]
+    pub fn print_node(&self, node_id: NodeId, indent: usize) {
+        let mut s = String::new();
+        let _ = self.write_node(&mut s, node_id, indent);
+        print!("{}", s);
     }
 
+    #[cfg(feature = "std")]
     pub fn print_tree(&self) {
         self.print_node(self.head, 0);
     }
@@ -0,0 +1,99 @@
+use {crate::*, core::fmt};
+
+/// Any error returned by the fallible functions of **bet**, so that
+/// they can be handled with a single type or propagated with `?`.
+///
+/// Every variant wraps the more specific error, which is also its
+/// `source`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum BetError {
+    /// A structural edit failed
+    Edit(EditError),
+    /// Parts or deserialized data don't make a valid tree
+    Structure(StructureError),
+    /// The binary encoding couldn't be decoded
+    Decode(DecodeError),
+    /// The s-expression couldn't be parsed
+    Sexpr(SexprError),
+    /// The infix expression couldn't be parsed
+    Parse(ParseError),
+    /// An operator was pushed at a position not matching its arity
+    Push(PushError),
+    /// The tree would have more nodes or atoms than ids can address
+    IdOverflow(IdOverflow),
+    /// The expression can't be translated into a boolean formula
+    #[cfg(feature = "logic")]
+    Cnf(CnfError),
+    /// The expression has too many atoms for its truth table
+    #[cfg(feature = "logic")]
+    TooManyVariables(logic::TooManyVariables),
+    /// The arithmetic expression can't be evaluated
+    #[cfg(feature = "arith")]
+    Arith(arith::ArithError),
+}
+
+impl fmt::Display for BetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Edit(e) => write!(f, "invalid edit: {}", e),
+            Self::Structure(e) => write!(f, "invalid structure: {}", e),
+            Self::Decode(e) => write!(f, "invalid encoding: {}", e),
+            Self::Sexpr(e) => write!(f, "invalid s-expression: {}", e),
+            Self::Parse(e) => write!(f, "invalid expression: {}", e),
+            Self::Push(e) => write!(f, "invalid push: {}", e),
+            Self::IdOverflow(e) => e.fmt(f),
+            #[cfg(feature = "logic")]
+            Self::Cnf(e) => write!(f, "no CNF translation: {}", e),
+            #[cfg(feature = "logic")]
+            Self::TooManyVariables(e) => write!(f, "no truth table: {}", e),
+            #[cfg(feature = "arith")]
+            Self::Arith(e) => write!(f, "invalid computation: {}", e),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for BetError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Edit(e) => Some(e),
+            Self::Structure(e) => Some(e),
+            Self::Decode(e) => Some(e),
+            Self::Sexpr(e) => Some(e),
+            Self::Parse(e) => Some(e),
+            Self::Push(e) => Some(e),
+            Self::IdOverflow(e) => Some(e),
+            #[cfg(feature = "logic")]
+            Self::Cnf(e) => Some(e),
+            #[cfg(feature = "logic")]
+            Self::TooManyVariables(e) => Some(e),
+            #[cfg(feature = "arith")]
+            Self::Arith(e) => Some(e),
+        }
+    }
+}
+
+macro_rules! impl_from_error {
+    ($variant:ident, $error:ty) => {
+        impl From<$error> for BetError {
+            fn from(e: $error) -> Self {
+                Self::$variant(e)
+            }
+        }
+    };
+}
+
+impl_from_error!(Edit, EditError);
+impl_from_error!(Structure, StructureError);
+impl_from_error!(Decode, DecodeError);
+impl_from_error!(Sexpr, SexprError);
+impl_from_error!(Parse, ParseError);
+impl_from_error!(Push, PushError);
+impl_from_error!(IdOverflow, IdOverflow);
+#[cfg(feature = "logic")]
+impl_from_error!(Cnf, CnfError);
+#[cfg(feature = "logic")]
+impl_from_error!(TooManyVariables, logic::TooManyVariables);
+#[cfg(feature = "arith")]
+impl_from_error!(Arith, arith::ArithError);
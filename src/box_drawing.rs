@@ -0,0 +1,128 @@
+use {crate::*, core::fmt};
+
+/// The strings drawing the branches of a tree
+struct BranchChars {
+    branch: &'static str,
+    last_branch: &'static str,
+    vertical: &'static str,
+    space: &'static str,
+    missing: &'static str,
+}
+
+const UNICODE: BranchChars = BranchChars {
+    branch: "├─ ",
+    last_branch: "└─ ",
+    vertical: "│  ",
+    space: "   ",
+    missing: "∅",
+};
+
+const ASCII: BranchChars = BranchChars {
+    branch: "|- ",
+    last_branch: "`- ",
+    vertical: "|  ",
+    space: "   ",
+    missing: "-",
+};
+
+impl<Op, Atom> BeTree<Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone,
+{
+    fn render_branches<W, LO, LA>(
+        &self,
+        w: &mut W,
+        label_op: LO,
+        label_atom: LA,
+        chars: &BranchChars,
+    ) -> fmt::Result
+    where
+        W: fmt::Write,
+        LO: Fn(&Op) -> String,
+        LA: Fn(&Atom) -> String,
+    {
+        // The tree is walked with an explicit stack so that very deep
+        // trees don't overflow the call stack. The prefix of the lines
+        // is a single buffer, truncated when going up.
+        let mut prefix = String::new();
+        let mut prefix_ends: Vec<usize> = Vec::new(); // prefix length per depth
+        let mut stack = vec![(Child::Node(self.head), 0, true)];
+        while let Some((child, depth, last)) = stack.pop() {
+            if depth > 0 {
+                prefix.truncate(prefix_ends[depth - 1]);
+                prefix_ends.truncate(depth);
+                w.write_str(&prefix)?;
+                w.write_str(if last {
+                    chars.last_branch
+                } else {
+                    chars.branch
+                })?;
+            }
+            let node_idx = match self.skip_groups(child) {
+                Child::None => {
+                    writeln!(w, "{}", chars.missing)?;
+                    continue;
+                }
+                Child::Atom(atom_idx) => {
+                    writeln!(w, "{}", label_atom(&self.atoms[atom_idx]))?;
+                    continue;
+                }
+                Child::Node(node_idx) => node_idx,
+            };
+            let node = &self.nodes[node_idx];
+            match &node.operator {
+                Some(op) => writeln!(w, "{}", label_op(op))?,
+                None => writeln!(w, "()")?,
+            }
+            if depth > 0 {
+                prefix.push_str(if last { chars.space } else { chars.vertical });
+            }
+            prefix_ends.push(prefix.len());
+            if node.unary {
+                stack.push((node.left(), depth + 1, true));
+            } else {
+                stack.push((node.right(), depth + 1, true));
+                stack.push((node.left(), depth + 1, false));
+            }
+        }
+        Ok(())
+    }
+
+    /// write the tree with box-drawing connectors, one line per operator
+    /// or atom, the operands being below their operator, as in
+    ///
+    /// ```text
+    /// &
+    /// ├─ |
+    /// │  ├─ A
+    /// │  └─ B
+    /// └─ !
+    ///    └─ C
+    /// ```
+    ///
+    /// Unary operators have a single branch and missing operands are
+    /// written as `∅`, so that incomplete trees are visible. Groups
+    /// without operator aren't shown.
+    pub fn render_unicode<W, LO, LA>(&self, w: &mut W, label_op: LO, label_atom: LA) -> fmt::Result
+    where
+        W: fmt::Write,
+        LO: Fn(&Op) -> String,
+        LA: Fn(&Atom) -> String,
+    {
+        self.render_branches(w, label_op, label_atom, &UNICODE)
+    }
+
+    /// write the tree as `render_unicode` but with only ASCII characters,
+    /// for terminals not supporting box-drawing characters: branches are
+    /// drawn with `|-`, `` `- `` and `|`, and missing operands are
+    /// written as `-`.
+    pub fn render_ascii<W, LO, LA>(&self, w: &mut W, label_op: LO, label_atom: LA) -> fmt::Result
+    where
+        W: fmt::Write,
+        LO: Fn(&Op) -> String,
+        LA: Fn(&Atom) -> String,
+    {
+        self.render_branches(w, label_op, label_atom, &ASCII)
+    }
+}
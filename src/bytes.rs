@@ -0,0 +1,228 @@
+use {crate::*, core::fmt};
+
+/// the version of the binary format written by `to_bytes`
+const FORMAT_VERSION: u8 = 1;
+
+// record types of the binary format
+const RECORD_ATOM: u8 = 0;
+const RECORD_MISSING: u8 = 1;
+const RECORD_OPERATOR: u8 = 2; // the lowest bit is set for unary operators
+const UNARY_BIT: u8 = 1;
+const RECORD_EMPTY_GROUP: u8 = 4;
+const RECORD_END: u8 = 5;
+
+/// write the length as a LEB128 variable length integer
+fn push_length(mut len: usize, bytes: &mut Vec<u8>) {
+    loop {
+        let low = (len & 0x7F) as u8;
+        len >>= 7;
+        if len == 0 {
+            bytes.push(low);
+            return;
+        }
+        bytes.push(low | 0x80);
+    }
+}
+
+/// A reader of the records of the binary format
+struct BytesReader<'b> {
+    bytes: &'b [u8],
+    pos: usize,
+}
+
+impl<'b> BytesReader<'b> {
+    fn read_byte(&mut self) -> Result<u8, DecodeError> {
+        let byte = *self
+            .bytes
+            .get(self.pos)
+            .ok_or(DecodeError::Truncated { offset: self.pos })?;
+        self.pos += 1;
+        Ok(byte)
+    }
+    fn read_length(&mut self) -> Result<usize, DecodeError> {
+        let start = self.pos;
+        let mut len: usize = 0;
+        let mut shift = 0;
+        loop {
+            let byte = self.read_byte()?;
+            let low = (byte & 0x7F) as usize;
+            if shift >= usize::BITS || (low << shift) >> shift != low {
+                return Err(DecodeError::InvalidLength { offset: start });
+            }
+            len |= low << shift;
+            if byte & 0x80 == 0 {
+                return Ok(len);
+            }
+            shift += 7;
+        }
+    }
+    fn read_slice(&mut self, len: usize) -> Result<&'b [u8], DecodeError> {
+        if len > self.bytes.len() - self.pos {
+            return Err(DecodeError::Truncated {
+                offset: self.bytes.len(),
+            });
+        }
+        let slice = &self.bytes[self.pos..self.pos + len];
+        self.pos += len;
+        Ok(slice)
+    }
+}
+
+/// Compact binary serialization
+impl<Op, Atom> BeTree<Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone,
+{
+    fn push_child_bytes<EA, EO>(
+        &self,
+        child: Child,
+        encode_atom: &EA,
+        encode_op: &EO,
+        bytes: &mut Vec<u8>,
+    ) where
+        EA: Fn(&Atom) -> Vec<u8>,
+        EO: Fn(&Op) -> u8,
+    {
+        let node_idx = match self.skip_non_empty_groups(child) {
+            Child::None => {
+                bytes.push(RECORD_MISSING);
+                return;
+            }
+            Child::Atom(atom_idx) => {
+                let payload = encode_atom(&self.atoms[atom_idx]);
+                bytes.push(RECORD_ATOM);
+                push_length(payload.len(), bytes);
+                bytes.extend_from_slice(&payload);
+                return;
+            }
+            Child::Node(node_idx) => node_idx,
+        };
+        let node = &self.nodes[node_idx];
+        let Some(op) = &node.operator else {
+            bytes.push(RECORD_EMPTY_GROUP);
+            return;
+        };
+        self.push_child_bytes(node.left(), encode_atom, encode_op, bytes);
+        if node.unary {
+            bytes.push(RECORD_OPERATOR | UNARY_BIT);
+        } else {
+            self.push_child_bytes(node.right(), encode_atom, encode_op, bytes);
+            bytes.push(RECORD_OPERATOR);
+        }
+        bytes.push(encode_op(op));
+    }
+
+    /// encode the expression in a compact binary format, independent
+    /// of the representation of the tree.
+    ///
+    /// The first byte is the version of the format. It's followed by
+    /// the records of the expression in postfix order:
+    /// * an atom is `0`, the length of its payload as a LEB128 integer,
+    ///   then the payload given by `encode_atom`
+    /// * a missing operand (in an incomplete expression) is `1`
+    /// * an empty group, like the `()` in `a & ()`, is `4`
+    /// * an operator is `2` (binary) or `3` (unary) followed by the
+    ///   byte given by `encode_op`, typically its index in a table
+    ///
+    /// The records are followed by `5`, marking the end of the expression,
+    /// so that a truncated input is detected even when it ends at a record
+    /// boundary. The empty expression is only the version byte and the end
+    /// record. Groups which aren't empty and nodes which aren't reachable
+    /// aren't encoded.
+    pub fn to_bytes<EA, EO>(&self, encode_atom: EA, encode_op: EO) -> Vec<u8>
+    where
+        EA: Fn(&Atom) -> Vec<u8>,
+        EO: Fn(&Op) -> u8,
+    {
+        let mut bytes = vec![FORMAT_VERSION];
+        // an expression without atom may still have empty groups, as `!()`
+        if !self.is_empty() || !self.empty_groups().is_empty() {
+            self.push_child_bytes(Child::Node(self.head), &encode_atom, &encode_op, &mut bytes);
+        }
+        bytes.push(RECORD_END);
+        bytes
+    }
+
+    /// decode an expression encoded with `to_bytes`, operators and
+    /// atoms being built by the given functions, which return `None`
+    /// for invalid inputs.
+    ///
+    /// The input is fully checked: an invalid, truncated, or malicious
+    /// input gives an error, never a panic.
+    pub fn from_bytes<DA, DO>(
+        bytes: &[u8],
+        decode_atom: DA,
+        decode_op: DO,
+    ) -> Result<Self, DecodeError>
+    where
+        DA: Fn(&[u8]) -> Option<Atom>,
+        DO: Fn(u8) -> Option<Op>,
+    {
+        let mut reader = BytesReader { bytes, pos: 0 };
+        let version = reader.read_byte().map_err(|_| DecodeError::Empty)?;
+        if version != FORMAT_VERSION {
+            return Err(DecodeError::UnsupportedVersion(version));
+        }
+        let mut tree = BeTree::new();
+        tree.nodes.clear();
+        let mut stack: Vec<Child> = Vec::new();
+        loop {
+            let offset = reader.pos;
+            match reader.read_byte()? {
+                RECORD_END => break,
+                RECORD_ATOM => {
+                    let len_offset = reader.pos;
+                    let len = reader.read_length()?;
+                    let payload = reader.read_slice(len)?;
+                    let atom = decode_atom(payload)
+                        .ok_or(DecodeError::InvalidAtom { offset: len_offset })?;
+                    stack.push(Child::Atom(tree.store_atom(atom)));
+                }
+                RECORD_MISSING => {
+                    stack.push(Child::None);
+                }
+                RECORD_EMPTY_GROUP => {
+                    stack.push(Child::Node(tree.store_node(Node::empty())));
+                }
+                tag if tag & !UNARY_BIT == RECORD_OPERATOR => {
+                    let op_offset = reader.pos;
+                    let op = decode_op(reader.read_byte()?)
+                        .ok_or(DecodeError::InvalidOperator { offset: op_offset })?;
+                    let unary = tag & UNARY_BIT != 0;
+                    let (left, right) = if unary {
+                        let left = stack.pop().ok_or(DecodeError::MissingOperand { offset })?;
+                        (left, Child::None)
+                    } else {
+                        match (stack.pop(), stack.pop()) {
+                            (Some(right), Some(left)) if left.is_some() => (left, right),
+                            _ => return Err(DecodeError::MissingOperand { offset }),
+                        }
+                    };
+                    let node_idx = tree.store_node(Node::new(Some(op), unary));
+                    tree.op_count += 1;
+                    tree.link_child(node_idx, Side::Left, left);
+                    tree.link_child(node_idx, Side::Right, right);
+                    stack.push(Child::Node(node_idx));
+                }
+                tag => {
+                    return Err(DecodeError::UnknownRecord { offset, tag });
+                }
+            }
+        }
+        if reader.pos < bytes.len() {
+            return Err(DecodeError::TrailingBytes { offset: reader.pos });
+        }
+        if stack.is_empty() {
+            return Ok(BeTree::new());
+        }
+        if stack.len() != 1 || stack[0].is_none() {
+            return Err(DecodeError::Unbalanced {
+                operands: stack.len(),
+            });
+        }
+        tree.set_decoded_root(stack[0]);
+        tree.last_pushed = TokenType::ClosingPar;
+        Ok(tree)
+    }
+}
@@ -0,0 +1,288 @@
+use {crate::*, core::fmt};
+
+/// The maximal depth (number of indentation levels) of the lines
+/// accepted by `BeTree::from_canonical_text`, as the reader is recursive
+pub const MAX_CANONICAL_TEXT_DEPTH: usize = 200;
+
+/// the token written for a missing operand
+const MISSING: &str = "()";
+
+/// the token written for an empty group
+const EMPTY_GROUP: &str = "(())";
+
+/// write the text, quoted if it can't be read back as a bare token
+fn push_canonical_text(text: &str, s: &mut String) {
+    let needs_quotes = text.is_empty()
+        || text
+            .chars()
+            .any(|c| c.is_whitespace() || c.is_control() || matches!(c, '(' | ')' | '"' | '\\'));
+    if !needs_quotes {
+        s.push_str(text);
+        return;
+    }
+    s.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => s.push_str("\\\""),
+            '\\' => s.push_str("\\\\"),
+            '\n' => s.push_str("\\n"),
+            '\r' => s.push_str("\\r"),
+            '\t' => s.push_str("\\t"),
+            _ => s.push(c),
+        }
+    }
+    s.push('"');
+}
+
+/// read a bare or quoted token, or return `None` if the quoting is wrong
+fn read_canonical_text(token: &str) -> Option<String> {
+    let Some(quoted) = token.strip_prefix('"') else {
+        return (!token.contains('"')).then(|| token.to_string());
+    };
+    let mut text = String::new();
+    let mut chars = quoted.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => return chars.as_str().is_empty().then_some(text),
+            '\\' => text.push(match chars.next()? {
+                'n' => '\n',
+                'r' => '\r',
+                't' => '\t',
+                c => c,
+            }),
+            c => text.push(c),
+        }
+    }
+    None
+}
+
+/// A line of a canonical text
+struct Line<'i> {
+    number: usize,
+    depth: usize,
+    token: &'i str,
+}
+
+/// A reader of canonical texts, building a tree
+struct CanonicalTextReader<'i, Op, Atom, PO, PA> {
+    lines: Vec<Line<'i>>,
+    pos: usize,
+    tree: BeTree<Op, Atom>,
+    parse_op: PO,
+    parse_atom: PA,
+}
+
+impl<Op, Atom, PO, PA> CanonicalTextReader<'_, Op, Atom, PO, PA>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone,
+    PO: Fn(&str) -> Option<Op>,
+    PA: Fn(&str) -> Option<Atom>,
+{
+    /// tell whether the line after the current one is an operand of it
+    fn has_operand(&self, depth: usize) -> bool {
+        self.lines
+            .get(self.pos)
+            .is_some_and(|line| line.depth > depth)
+    }
+    /// read the child at the current line, which is at the given depth
+    fn read_child(&mut self, depth: usize) -> Result<Child, CanonicalTextError> {
+        let line = &self.lines[self.pos];
+        let number = line.number;
+        let token = line.token;
+        if depth > MAX_CANONICAL_TEXT_DEPTH {
+            return Err(CanonicalTextError::TooDeep { line: number });
+        }
+        self.pos += 1;
+        if !self.has_operand(depth) {
+            if token == MISSING {
+                return Ok(Child::None);
+            }
+            if token == EMPTY_GROUP {
+                return Ok(Child::Node(self.tree.store_node(Node::empty())));
+            }
+            let atom = read_canonical_text(token)
+                .ok_or(CanonicalTextError::WrongQuoting { line: number })?;
+            let atom =
+                (self.parse_atom)(&atom).ok_or(CanonicalTextError::InvalidAtom { line: number })?;
+            return Ok(Child::Atom(self.tree.store_atom(atom)));
+        }
+        if token == MISSING || token == EMPTY_GROUP {
+            return Err(CanonicalTextError::WrongOperandCount { line: number });
+        }
+        let operator =
+            read_canonical_text(token).ok_or(CanonicalTextError::WrongQuoting { line: number })?;
+        let operator = (self.parse_op)(&operator)
+            .ok_or(CanonicalTextError::InvalidOperator { line: number })?;
+        let mut operands = Vec::new();
+        while self.has_operand(depth) {
+            let operand_line = self.lines[self.pos].number;
+            if self.lines[self.pos].depth != depth + 1 {
+                return Err(CanonicalTextError::WrongIndentation { line: operand_line });
+            }
+            if operands.len() == 2 {
+                return Err(CanonicalTextError::WrongOperandCount { line: number });
+            }
+            if operands.last().is_some_and(|child: &Child| child.is_none()) {
+                return Err(CanonicalTextError::MissingOperand { line: operand_line });
+            }
+            operands.push(self.read_child(depth + 1)?);
+        }
+        let node_idx = self
+            .tree
+            .store_node(Node::new(Some(operator), operands.len() == 1));
+        self.tree.op_count += 1;
+        for (side, operand) in IntoIterator::into_iter([Side::Left, Side::Right]).zip(operands) {
+            self.tree.link_child(node_idx, side, operand);
+        }
+        Ok(Child::Node(node_idx))
+    }
+}
+
+/// Canonical text conversions
+impl<Op, Atom> BeTree<Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone,
+{
+    fn push_canonical_child<OS, AS>(
+        &self,
+        child: Child,
+        depth: usize,
+        op_str: &OS,
+        atom_str: &AS,
+        s: &mut String,
+    ) where
+        OS: Fn(&Op) -> String,
+        AS: Fn(&Atom) -> String,
+    {
+        for _ in 0..depth {
+            s.push_str("  ");
+        }
+        let node_idx = match self.skip_non_empty_groups(child) {
+            Child::None => {
+                s.push_str(MISSING);
+                s.push('\n');
+                return;
+            }
+            Child::Atom(atom_idx) => {
+                push_canonical_text(&atom_str(&self.atoms[atom_idx]), s);
+                s.push('\n');
+                return;
+            }
+            Child::Node(node_idx) => node_idx,
+        };
+        let node = &self.nodes[node_idx];
+        let Some(op) = &node.operator else {
+            s.push_str(EMPTY_GROUP);
+            s.push('\n');
+            return;
+        };
+        push_canonical_text(&op_str(op), s);
+        s.push('\n');
+        self.push_canonical_child(node.left(), depth + 1, op_str, atom_str, s);
+        if !node.unary {
+            self.push_canonical_child(node.right(), depth + 1, op_str, atom_str, s);
+        }
+    }
+
+    /// write the expression in a stable text form suited to version
+    /// control: one operator or atom per line, the operands of an
+    /// operator following it with one more level of indentation (two
+    /// spaces). For example `(a | b) & !c` is written
+    ///
+    /// ```text
+    /// &
+    ///   |
+    ///     a
+    ///     b
+    ///   !
+    ///     c
+    /// ```
+    ///
+    /// The text depends only on the structure of the expression, not on
+    /// the ids of its nodes and atoms nor on the edits which built it, so
+    /// changing an atom changes one line. Groups don't appear, unless
+    /// they're empty. To also ignore the order of the operands of
+    /// commutative operators, call `canonicalize` before.
+    ///
+    /// Operators and atoms are written with the given functions, and
+    /// enclosed in double quotes when they're empty or contain whitespaces,
+    /// control characters, parenthesis, double quotes or backslashes
+    /// (escaped as in Rust strings). A missing operand, in an incomplete
+    /// expression, is written `()`, and an empty group, like the `()` in
+    /// `a & ()`, is written `(())`. The empty expression is an empty text.
+    pub fn to_canonical_text<OS, AS>(&self, op_str: OS, atom_str: AS) -> String
+    where
+        OS: Fn(&Op) -> String,
+        AS: Fn(&Atom) -> String,
+    {
+        let mut s = String::new();
+        // an expression without atom may still have empty groups, as `!()`
+        if !self.is_empty() || !self.empty_groups().is_empty() {
+            self.push_canonical_child(Child::Node(self.head), 0, &op_str, &atom_str, &mut s);
+        }
+        s
+    }
+
+    /// read an expression written with `to_canonical_text`, operators
+    /// and atoms being built with the given functions, which return
+    /// `None` for invalid inputs.
+    ///
+    /// Empty lines are ignored. Expressions nested deeper than
+    /// `MAX_CANONICAL_TEXT_DEPTH` levels are rejected.
+    pub fn from_canonical_text<PO, PA>(
+        input: &str,
+        parse_op: PO,
+        parse_atom: PA,
+    ) -> Result<Self, CanonicalTextError>
+    where
+        PO: Fn(&str) -> Option<Op>,
+        PA: Fn(&str) -> Option<Atom>,
+    {
+        let mut lines = Vec::new();
+        for (idx, line) in input.lines().enumerate() {
+            let token = line.trim_start_matches(' ');
+            let indent = line.len() - token.len();
+            let token = token.trim_end();
+            if token.is_empty() {
+                continue;
+            }
+            if indent % 2 != 0 || token.starts_with(char::is_whitespace) {
+                return Err(CanonicalTextError::WrongIndentation { line: idx + 1 });
+            }
+            lines.push(Line {
+                number: idx + 1,
+                depth: indent / 2,
+                token,
+            });
+        }
+        let mut tree = BeTree::new();
+        if lines.is_empty() {
+            return Ok(tree);
+        }
+        if lines[0].depth != 0 {
+            return Err(CanonicalTextError::WrongIndentation {
+                line: lines[0].number,
+            });
+        }
+        tree.nodes.clear();
+        let mut reader = CanonicalTextReader {
+            lines,
+            pos: 0,
+            tree,
+            parse_op,
+            parse_atom,
+        };
+        let root = reader.read_child(0)?;
+        if let Some(line) = reader.lines.get(reader.pos) {
+            return Err(CanonicalTextError::TrailingInput { line: line.number });
+        }
+        let mut tree = reader.tree;
+        tree.set_decoded_root(root);
+        if !tree.is_empty() {
+            tree.last_pushed = TokenType::ClosingPar;
+        }
+        Ok(tree)
+    }
+}
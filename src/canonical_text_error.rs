@@ -0,0 +1,61 @@
+use core::fmt;
+
+/// Error returned by `BeTree::from_canonical_text`, with the line
+/// (starting at 1) of the problem
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CanonicalTextError {
+    /// The indentation isn't a multiple of two spaces, or is more than
+    /// one level deeper than the previous line
+    WrongIndentation { line: usize },
+    /// A quoted token isn't closed, or is followed by something
+    WrongQuoting { line: usize },
+    /// An operator has more than two operands, or a missing operand
+    /// has operands
+    WrongOperandCount { line: usize },
+    /// An operand is missing while the next one is present
+    MissingOperand { line: usize },
+    /// The operator wasn't accepted by the operator parsing function
+    InvalidOperator { line: usize },
+    /// The atom wasn't accepted by the atom parsing function
+    InvalidAtom { line: usize },
+    /// There's something after the expression
+    TrailingInput { line: usize },
+    /// The line is deeper than `MAX_CANONICAL_TEXT_DEPTH`
+    TooDeep { line: usize },
+}
+
+impl CanonicalTextError {
+    /// the line (starting at 1) of the problem
+    pub fn line(self) -> usize {
+        match self {
+            Self::WrongIndentation { line }
+            | Self::WrongQuoting { line }
+            | Self::WrongOperandCount { line }
+            | Self::MissingOperand { line }
+            | Self::InvalidOperator { line }
+            | Self::InvalidAtom { line }
+            | Self::TrailingInput { line }
+            | Self::TooDeep { line } => line,
+        }
+    }
+}
+
+impl fmt::Display for CanonicalTextError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let problem = match self {
+            Self::WrongIndentation { .. } => "wrong indentation",
+            Self::WrongQuoting { .. } => "wrong quoting",
+            Self::WrongOperandCount { .. } => "an operator needs one or two operands",
+            Self::MissingOperand { .. } => "only the last operand may be missing",
+            Self::InvalidOperator { .. } => "invalid operator",
+            Self::InvalidAtom { .. } => "invalid atom",
+            Self::TrailingInput { .. } => "unexpected input after the expression",
+            Self::TooDeep { .. } => "expression nested too deeply",
+        };
+        write!(f, "{} at line {}", problem, self.line())
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for CanonicalTextError {}
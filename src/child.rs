@@ -1,10 +1,17 @@
 use crate::*;
 
+/// the packed form of `Child::None` and of a missing parent
+pub(crate) const NO_LINK: u32 = u32::MAX;
+
+/// the bit telling atoms from nodes in a packed child
+const ATOM_BIT: u32 = 1 << 31;
+
 /// One of the children of a node
 ///
 /// You probably don't need to use this struct unless
 /// you want to inspect the binary expression tree.
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Child {
     None,
     Node(NodeId),
@@ -17,4 +24,22 @@ impl Child {
     pub fn is_some(self) -> bool {
         !self.is_none()
     }
+    /// pack the child in 32 bits, which is possible as ids are
+    /// at most `MAX_ID`
+    pub(crate) fn pack(self) -> u32 {
+        match self {
+            Self::None => NO_LINK,
+            Self::Node(node_id) => u32::from(node_id),
+            Self::Atom(atom_id) => u32::from(atom_id) | ATOM_BIT,
+        }
+    }
+    pub(crate) fn unpack(packed: u32) -> Self {
+        if packed == NO_LINK {
+            Self::None
+        } else if packed & ATOM_BIT == 0 {
+            Self::Node(NodeId::new_unchecked(packed))
+        } else {
+            Self::Atom(AtomId::new_unchecked(packed & !ATOM_BIT))
+        }
+    }
 }
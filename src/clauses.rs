@@ -0,0 +1,123 @@
+use {crate::*, core::fmt};
+
+/// A possibly negated atom of a clause, as returned by `BeTree::clauses`
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct Literal<'t, Atom> {
+    pub atom: &'t Atom,
+    pub negated: bool,
+}
+
+// not derived, as the atom doesn't have to be Clone
+impl<Atom> Clone for Literal<'_, Atom> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<Atom> Copy for Literal<'_, Atom> {}
+
+/// Extraction of the clauses of normal forms
+impl<Op, Atom> BeTree<Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone,
+{
+    /// push the clauses of the child, which must be operations
+    /// of the outer operator on clauses
+    fn push_clauses<'t>(
+        &'t self,
+        child: Child,
+        parent: NodeId,
+        ops: (&Op, &Op, &Op),
+        clauses: &mut Vec<Vec<Literal<'t, Atom>>>,
+    ) -> Result<(), NotInNormalForm> {
+        let (outer, _, _) = ops;
+        match self.skip_groups(child) {
+            Child::None => Err(NotInNormalForm::MissingOperand(parent)),
+            Child::Node(node_idx)
+                if !self.nodes[node_idx].unary
+                    && self.nodes[node_idx].operator.as_ref() == Some(outer) =>
+            {
+                let node = &self.nodes[node_idx];
+                self.push_clauses(node.left(), node_idx, ops, clauses)?;
+                self.push_clauses(node.right(), node_idx, ops, clauses)
+            }
+            child => {
+                let mut clause = Vec::new();
+                self.push_literals(child, parent, ops, &mut clause)?;
+                clauses.push(clause);
+                Ok(())
+            }
+        }
+    }
+
+    /// push the literals of the child, which must be operations
+    /// of the inner operator on literals
+    fn push_literals<'t>(
+        &'t self,
+        child: Child,
+        parent: NodeId,
+        ops: (&Op, &Op, &Op),
+        clause: &mut Vec<Literal<'t, Atom>>,
+    ) -> Result<(), NotInNormalForm> {
+        let (_, inner, negation) = ops;
+        let node_idx = match self.skip_groups(child) {
+            Child::None => return Err(NotInNormalForm::MissingOperand(parent)),
+            Child::Atom(atom_idx) => {
+                clause.push(Literal {
+                    atom: &self.atoms[atom_idx],
+                    negated: false,
+                });
+                return Ok(());
+            }
+            Child::Node(node_idx) => node_idx,
+        };
+        let node = &self.nodes[node_idx];
+        match &node.operator {
+            Some(op) if node.unary && op == negation => match self.skip_groups(node.left()) {
+                Child::None => Err(NotInNormalForm::MissingOperand(node_idx)),
+                Child::Atom(atom_idx) => {
+                    clause.push(Literal {
+                        atom: &self.atoms[atom_idx],
+                        negated: true,
+                    });
+                    Ok(())
+                }
+                Child::Node(_) => Err(NotInNormalForm::NegatedOperation(node_idx)),
+            },
+            Some(op) if !node.unary && op == inner => {
+                self.push_literals(node.left(), node_idx, ops, clause)?;
+                self.push_literals(node.right(), node_idx, ops, clause)
+            }
+            _ => Err(NotInNormalForm::UnexpectedOperator(node_idx)),
+        }
+    }
+
+    /// return the clauses of an expression in normal form, that is
+    /// operations of the `outer` operator on clauses, which are
+    /// operations of the `inner` operator on atoms, possibly negated
+    /// with the unary `negation` operator.
+    ///
+    /// For a CNF, as built by `to_cnf` after `push_down_unary`, `outer`
+    /// is AND and `inner` is OR. For a DNF, they're swapped.
+    ///
+    /// Clauses and literals come in the order of the expression, and
+    /// aren't deduplicated. A clause may be a single literal, and the
+    /// whole expression a single clause. An empty expression has no
+    /// clause.
+    ///
+    /// If the expression isn't in this normal form, the error gives the
+    /// first offending node.
+    pub fn clauses(
+        &self,
+        outer: &Op,
+        inner: &Op,
+        negation: &Op,
+    ) -> Result<Vec<Vec<Literal<'_, Atom>>>, NotInNormalForm> {
+        let mut clauses = Vec::new();
+        let root = Child::Node(self.head);
+        if !self.skip_groups(root).is_none() {
+            self.push_clauses(root, self.head, (outer, inner, negation), &mut clauses)?;
+        }
+        Ok(clauses)
+    }
+}
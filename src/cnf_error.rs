@@ -0,0 +1,42 @@
+use {crate::*, core::fmt};
+
+/// Error returned by `BeTree::to_dimacs` when the expression can't be
+/// translated into a boolean formula
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CnfError {
+    /// The expression has no atom and no operator
+    EmptyExpression,
+    /// An operator doesn't have the operands it needs
+    MissingOperand { node: NodeId },
+    /// The operator isn't AND or OR used as binary operator,
+    /// or NOT used as unary operator
+    UnsupportedOperator { node: NodeId },
+    /// The variable of the atom is 0, which isn't a valid DIMACS variable
+    InvalidVariable { atom: AtomId },
+    /// There are more variables than what DIMACS supports
+    TooManyVariables,
+}
+
+impl fmt::Display for CnfError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::EmptyExpression => write!(f, "empty expression"),
+            Self::MissingOperand { node } => {
+                write!(f, "missing operand for the operator of node {}", node)
+            }
+            Self::UnsupportedOperator { node } => write!(
+                f,
+                "the operator of node {} isn't a binary AND or OR or a unary NOT",
+                node
+            ),
+            Self::InvalidVariable { atom } => {
+                write!(f, "variable 0 given to atom {}", atom)
+            }
+            Self::TooManyVariables => write!(f, "too many variables"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for CnfError {}
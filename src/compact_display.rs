@@ -0,0 +1,76 @@
+use {crate::*, core::fmt};
+
+/// A one line structural rendering of a tree, like `And(Or('a', 'b'), Not('c'))`,
+/// using the `Debug` implementations of the operators and atoms.
+///
+/// A missing operand is rendered as `_`, so that incomplete trees are
+/// visible, and groups without operator are transparent. An empty tree
+/// is rendered as an empty string.
+///
+/// It's obtained with `BeTree::display_compact` and implements both
+/// `Display` and `Debug`, so that it can be used in assertion messages.
+pub struct CompactDisplay<'t, Op, Atom> {
+    tree: &'t BeTree<Op, Atom>,
+}
+
+impl<Op, Atom> CompactDisplay<'_, Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone,
+{
+    fn fmt_child(&self, child: Child, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match child {
+            Child::None => write!(f, "_"),
+            Child::Atom(atom_idx) => write!(f, "{:?}", &self.tree.atoms[atom_idx]),
+            Child::Node(node_idx) => {
+                let node = &self.tree.nodes[node_idx];
+                let Some(op) = &node.operator else {
+                    return self.fmt_child(node.left(), f);
+                };
+                write!(f, "{:?}(", op)?;
+                self.fmt_child(node.left(), f)?;
+                if !node.unary {
+                    write!(f, ", ")?;
+                    self.fmt_child(node.right(), f)?;
+                }
+                write!(f, ")")
+            }
+        }
+    }
+}
+
+impl<Op, Atom> fmt::Display for CompactDisplay<'_, Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let head = &self.tree.nodes[self.tree.head];
+        if head.operator.is_none() && head.left().is_none() {
+            return Ok(()); // empty tree
+        }
+        self.fmt_child(Child::Node(self.tree.head), f)
+    }
+}
+
+impl<Op, Atom> fmt::Debug for CompactDisplay<'_, Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+impl<Op, Atom> BeTree<Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone,
+{
+    /// return a displayable one line structural rendering of the tree,
+    /// for example `And(Or('a', 'b'), Not('c'))`
+    pub fn display_compact(&self) -> CompactDisplay<'_, Op, Atom> {
+        CompactDisplay { tree: self }
+    }
+}
@@ -0,0 +1,47 @@
+use {crate::*, core::fmt};
+
+/// Error returned by `BeTree::try_convert_ops`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ConversionError<Err> {
+    /// The conversion function refused the operator of the node
+    Rejected { node: NodeId, error: Err },
+    /// The expansion of the operator of the node is empty, or uses
+    /// the right operand of a unary operation
+    InvalidExpansion { node: NodeId },
+}
+
+impl<Err> ConversionError<Err> {
+    /// the node whose operator couldn't be converted
+    pub fn node(&self) -> NodeId {
+        match self {
+            Self::Rejected { node, .. } | Self::InvalidExpansion { node } => *node,
+        }
+    }
+}
+
+impl<Err: fmt::Display> fmt::Display for ConversionError<Err> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Rejected { node, error } => {
+                write!(f, "operator of node {} rejected: {}", node, error)
+            }
+            Self::InvalidExpansion { node } => {
+                write!(f, "invalid expansion of the operator of node {}", node)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<Err> std::error::Error for ConversionError<Err>
+where
+    Err: std::error::Error + 'static,
+{
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Rejected { error, .. } => Some(error),
+            Self::InvalidExpansion { .. } => None,
+        }
+    }
+}
@@ -0,0 +1,284 @@
+use {crate::*, core::fmt};
+
+/// A read-only position in the tree, either on a node or on an atom,
+/// from which the tree can be navigated.
+///
+/// Get it with `BeTree::cursor` or `BeTree::cursor_at`.
+#[derive(Debug)]
+pub struct Cursor<'t, Op, Atom> {
+    tree: &'t BeTree<Op, Atom>,
+    position: Child,
+    link: Option<(NodeId, Side)>,
+}
+
+impl<Op, Atom> Clone for Cursor<'_, Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone,
+{
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<Op, Atom> Copy for Cursor<'_, Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone,
+{
+}
+
+impl<'t, Op, Atom> Cursor<'t, Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone,
+{
+    /// what the cursor is on: a node or an atom
+    pub fn position(&self) -> Child {
+        self.position
+    }
+    /// the id of the node the cursor is on, if it's on a node
+    pub fn node_id(&self) -> Option<NodeId> {
+        match self.position {
+            Child::Node(node_id) => Some(node_id),
+            _ => None,
+        }
+    }
+    /// the node the cursor is on, if it's on a node
+    pub fn node(&self) -> Option<&'t Node<Op>> {
+        self.node_id().map(|node_id| &self.tree.nodes[node_id])
+    }
+    /// the atom the cursor is on, if it's on an atom
+    pub fn atom(&self) -> Option<&'t Atom> {
+        match self.position {
+            Child::Atom(atom_id) => Some(&self.tree.atoms[atom_id]),
+            _ => None,
+        }
+    }
+    /// the operator of the node the cursor is on, if any
+    pub fn operator(&self) -> Option<&'t Op> {
+        self.node().and_then(|node| node.operator.as_ref())
+    }
+    /// the side of the current position in its parent node, or
+    /// `None` if the cursor is on the head
+    pub fn side(&self) -> Option<Side> {
+        self.link.map(|(_, side)| side)
+    }
+    /// a cursor on the parent node, or `None` if the cursor is on the head
+    pub fn parent(&self) -> Option<Self> {
+        let (parent_id, _) = self.link?;
+        Some(Cursor {
+            tree: self.tree,
+            position: Child::Node(parent_id),
+            link: self.tree.node_link(parent_id),
+        })
+    }
+    /// a cursor on the child on the given side of the current node,
+    /// or `None` if there's no such child
+    pub fn child(&self, side: Side) -> Option<Self> {
+        let node_id = self.node_id()?;
+        let position = self.tree.nodes[node_id].child(side);
+        if position.is_none() {
+            return None;
+        }
+        Some(Cursor {
+            tree: self.tree,
+            position,
+            link: Some((node_id, side)),
+        })
+    }
+    /// a cursor on the left child (the operand of a unary operator)
+    pub fn left(&self) -> Option<Self> {
+        self.child(Side::Left)
+    }
+    /// a cursor on the right child
+    pub fn right(&self) -> Option<Self> {
+        self.child(Side::Right)
+    }
+}
+
+/// A position in the tree, either on a node or on an atom, from which
+/// the tree can be navigated and edited.
+///
+/// Edits keep the tree consistent: parent links, head and operator count
+/// are maintained. Replaced nodes and atoms stay in the arena (so that
+/// ids stay valid) but aren't reachable anymore.
+///
+/// Get it with `BeTree::cursor_mut` or `BeTree::cursor_at_mut`.
+#[derive(Debug)]
+pub struct CursorMut<'t, Op, Atom> {
+    tree: &'t mut BeTree<Op, Atom>,
+    position: Child,
+    link: Option<(NodeId, Side)>,
+}
+
+impl<'t, Op, Atom> CursorMut<'t, Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone,
+{
+    /// a read-only cursor at the same position
+    pub fn as_cursor(&self) -> Cursor<'_, Op, Atom> {
+        Cursor {
+            tree: self.tree,
+            position: self.position,
+            link: self.link,
+        }
+    }
+    /// what the cursor is on: a node or an atom
+    pub fn position(&self) -> Child {
+        self.position
+    }
+    /// the node the cursor is on, if it's on a node
+    pub fn node(&self) -> Option<&Node<Op>> {
+        self.as_cursor().node()
+    }
+    /// the atom the cursor is on, if it's on an atom
+    pub fn atom(&self) -> Option<&Atom> {
+        self.as_cursor().atom()
+    }
+    /// the atom the cursor is on, if it's on an atom
+    pub fn atom_mut(&mut self) -> Option<&mut Atom> {
+        match self.position {
+            Child::Atom(atom_id) => Some(&mut self.tree.atoms[atom_id]),
+            _ => None,
+        }
+    }
+    /// move to the parent node, return false (and don't move) if
+    /// the cursor is on the head
+    pub fn move_to_parent(&mut self) -> bool {
+        let Some((parent_id, _)) = self.link else {
+            return false;
+        };
+        self.position = Child::Node(parent_id);
+        self.link = self.tree.node_link(parent_id);
+        true
+    }
+    /// move to the child on the given side, return false (and don't
+    /// move) if there's no such child
+    pub fn move_to_child(&mut self, side: Side) -> bool {
+        let Child::Node(node_id) = self.position else {
+            return false;
+        };
+        let position = self.tree.nodes[node_id].child(side);
+        if position.is_none() {
+            return false;
+        }
+        self.position = position;
+        self.link = Some((node_id, side));
+        true
+    }
+    /// move to the left child (the operand of a unary operator)
+    pub fn move_to_left(&mut self) -> bool {
+        self.move_to_child(Side::Left)
+    }
+    /// move to the right child
+    pub fn move_to_right(&mut self) -> bool {
+        self.move_to_child(Side::Right)
+    }
+    /// put the child at the current position in place of what was there
+    fn set_position(&mut self, child: Child) {
+        self.tree.bump_epoch();
+        match self.link {
+            Some((parent_id, side)) => {
+                self.tree.link_child(parent_id, side, child);
+            }
+            None => {
+                let was_complete = self.tree.openness == 0;
+                self.tree.set_root(child);
+                if let Child::Atom(_) = child {
+                    // the atom has been put in a new head wrapper
+                    self.link = Some((self.tree.head, Side::Left));
+                }
+                if was_complete {
+                    self.tree.tail = self.tree.head;
+                    self.tree.last_pushed = TokenType::ClosingPar;
+                }
+            }
+        }
+        self.position = child;
+        self.tree.fix_tail();
+    }
+    /// replace what's at the cursor (a whole subexpression or an atom)
+    /// with a new atom, the cursor moving to this atom
+    pub fn replace_with_atom(&mut self, atom: Atom) {
+        self.tree.op_count -= self.tree.count_operators(self.position);
+        let atom_id = self.tree.store_atom(atom);
+        self.set_position(Child::Atom(atom_id));
+    }
+    /// insert a unary operator above the cursor, the cursor moving to
+    /// the node of this operator
+    pub fn wrap_in_unary(&mut self, operator: Op) {
+        let node_id = self.tree.new_unary_node(operator, self.position);
+        self.set_position(Child::Node(node_id));
+    }
+    /// insert a binary operator above the cursor, with the given tree as
+    /// operand on the given side and what was at the cursor on the other
+    /// side. The cursor moves to the node of this operator.
+    ///
+    /// For example, with the cursor on `a` in `a | b`, inserting a `&`
+    /// with `c` on the right side gives `(a & c) | b`.
+    ///
+    /// Nothing is done if the inserted tree is empty.
+    pub fn insert_binary_above(&mut self, operator: Op, side: Side, subtree: BeTree<Op, Atom>) {
+        if subtree.is_empty() {
+            return;
+        }
+        let node_id = self.tree.store_node(Node::new(Some(operator), false));
+        self.tree.op_count += 1;
+        let inserted = self.tree.graft(subtree, Some(node_id));
+        self.tree.link_child(node_id, side, Child::Node(inserted));
+        self.tree.link_child(node_id, side.other(), self.position);
+        self.set_position(Child::Node(node_id));
+    }
+}
+
+/// Cursors
+impl<Op, Atom> BeTree<Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone,
+{
+    /// a read-only cursor on the head of the tree
+    pub fn cursor(&self) -> Cursor<'_, Op, Atom> {
+        Cursor {
+            tree: self,
+            position: Child::Node(self.head),
+            link: None,
+        }
+    }
+    /// a read-only cursor on the given node, or `None` if there's
+    /// no such node reachable from the head
+    pub fn cursor_at(&self, node_id: NodeId) -> Option<Cursor<'_, Op, Atom>> {
+        if node_id.index() >= self.nodes.len() || !self.is_reachable(node_id) {
+            return None;
+        }
+        Some(Cursor {
+            tree: self,
+            position: Child::Node(node_id),
+            link: self.node_link(node_id),
+        })
+    }
+    /// a cursor on the head of the tree, allowing edits
+    pub fn cursor_mut(&mut self) -> CursorMut<'_, Op, Atom> {
+        let head = self.head;
+        CursorMut {
+            tree: self,
+            position: Child::Node(head),
+            link: None,
+        }
+    }
+    /// a cursor on the given node, allowing edits, or `None` if there's
+    /// no such node reachable from the head
+    pub fn cursor_at_mut(&mut self, node_id: NodeId) -> Option<CursorMut<'_, Op, Atom>> {
+        if node_id.index() >= self.nodes.len() || !self.is_reachable(node_id) {
+            return None;
+        }
+        let link = self.node_link(node_id);
+        Some(CursorMut {
+            tree: self,
+            position: Child::Node(node_id),
+            link,
+        })
+    }
+}
@@ -0,0 +1,66 @@
+use core::fmt;
+
+/// Error returned by `BeTree::from_bytes`, with the offset in the
+/// input of the problem when it's relevant
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DecodeError {
+    /// The input is empty, without even a version
+    Empty,
+    /// The version of the format isn't supported
+    UnsupportedVersion(u8),
+    /// The input ends in the middle of a record
+    Truncated { offset: usize },
+    /// The record type is unknown
+    UnknownRecord { offset: usize, tag: u8 },
+    /// A length is too big to be valid
+    InvalidLength { offset: usize },
+    /// The atom wasn't accepted by the atom decoding function
+    InvalidAtom { offset: usize },
+    /// The operator wasn't accepted by the operator decoding function
+    InvalidOperator { offset: usize },
+    /// An operator doesn't have the operands it needs
+    MissingOperand { offset: usize },
+    /// The stream doesn't make exactly one expression
+    Unbalanced { operands: usize },
+    /// There are bytes after the end of the expression
+    TrailingBytes { offset: usize },
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "empty input"),
+            Self::UnsupportedVersion(version) => {
+                write!(f, "unsupported format version {}", version)
+            }
+            Self::Truncated { offset } => write!(f, "truncated input at offset {}", offset),
+            Self::UnknownRecord { offset, tag } => {
+                write!(f, "unknown record type {} at offset {}", tag, offset)
+            }
+            Self::InvalidLength { offset } => write!(f, "invalid length at offset {}", offset),
+            Self::InvalidAtom { offset } => write!(f, "invalid atom at offset {}", offset),
+            Self::InvalidOperator { offset } => write!(f, "invalid operator at offset {}", offset),
+            Self::MissingOperand { offset } => {
+                write!(f, "missing operand for the operator at offset {}", offset)
+            }
+            Self::Unbalanced { operands } => {
+                write!(
+                    f,
+                    "the stream makes {} expressions instead of one",
+                    operands
+                )
+            }
+            Self::TrailingBytes { offset } => {
+                write!(
+                    f,
+                    "unexpected bytes after the expression at offset {}",
+                    offset
+                )
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DecodeError {}
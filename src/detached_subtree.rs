@@ -0,0 +1,112 @@
+use {crate::*, core::fmt};
+
+/// A subexpression removed from a tree by `BeTree::detach`, to be
+/// attached elsewhere with `BeTree::attach`.
+///
+/// It's a standalone compact tree, so attaching it can't create a cycle
+/// in the tree it was detached from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DetachedSubtree<Op, Atom> {
+    tree: BeTree<Op, Atom>,
+}
+
+impl<Op, Atom> DetachedSubtree<Op, Atom> {
+    /// the subexpression, as a tree
+    pub fn tree(&self) -> &BeTree<Op, Atom> {
+        &self.tree
+    }
+    pub fn into_tree(self) -> BeTree<Op, Atom> {
+        self.tree
+    }
+}
+
+/// Any tree can be attached
+impl<Op, Atom> From<BeTree<Op, Atom>> for DetachedSubtree<Op, Atom> {
+    fn from(tree: BeTree<Op, Atom>) -> Self {
+        Self { tree }
+    }
+}
+
+/// Moves of subexpressions
+impl<Op, Atom> BeTree<Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone,
+{
+    /// remove the subexpression whose root is the node, and return it.
+    ///
+    /// The tree is repaired as with `prune`: the operator which joined
+    /// the subexpression to its sibling is removed, and so are unary
+    /// operators and groups left empty. Detaching the head leaves an
+    /// empty tree.
+    pub fn detach(&mut self, node_id: NodeId) -> Result<DetachedSubtree<Op, Atom>, EditError> {
+        if node_id.index() >= self.nodes.len() {
+            return Err(EditError::UnknownNode(node_id));
+        }
+        if !self.is_reachable(node_id) {
+            return Err(EditError::UnreachableNode(node_id));
+        }
+        let tree = self.child_tree(Child::Node(node_id));
+        self.prune(node_id)?;
+        Ok(DetachedSubtree { tree })
+    }
+
+    /// attach a detached subexpression as a child of the target node.
+    ///
+    /// Without joining operator, the target must have no child on this
+    /// side, for example a group or a unary operator whose operand was
+    /// detached, or the head of an empty tree.
+    ///
+    /// With a joining operator, the current child on this side, if any,
+    /// becomes the left operand of a new binary operation whose right
+    /// operand is the attached subexpression.
+    ///
+    /// The target must be reachable, so a subexpression can't be attached
+    /// under one of its former nodes. Only binary operations have a right
+    /// child. Attaching an empty tree does nothing.
+    pub fn attach(
+        &mut self,
+        detached: DetachedSubtree<Op, Atom>,
+        target: NodeId,
+        side: Side,
+        joining_op: Option<Op>,
+    ) -> Result<(), EditError> {
+        if target.index() >= self.nodes.len() {
+            return Err(EditError::UnknownNode(target));
+        }
+        if !self.is_reachable(target) {
+            return Err(EditError::UnreachableNode(target));
+        }
+        let node = &self.nodes[target];
+        if side == Side::Right && (node.operator.is_none() || node.unary) {
+            return Err(EditError::NotBinaryNode(target));
+        }
+        let current = node.child(side);
+        if current.is_some() && joining_op.is_none() {
+            return Err(EditError::OccupiedChild(target));
+        }
+        let tree = detached.tree;
+        if tree.is_empty() {
+            return Ok(());
+        }
+        self.bump_epoch();
+        let was_empty = self.is_empty();
+        let root = self.graft(tree, None);
+        let root = match joining_op {
+            Some(op) if current.is_some() => {
+                let node_idx = self.store_node(Node::new(Some(op), false));
+                self.op_count += 1;
+                self.link_child(node_idx, Side::Left, current);
+                self.link_child(node_idx, Side::Right, Child::Node(root));
+                node_idx
+            }
+            _ => root,
+        };
+        self.link_child(target, side, Child::Node(root));
+        if was_empty {
+            self.last_pushed = TokenType::ClosingPar;
+        }
+        self.fix_tail();
+        Ok(())
+    }
+}
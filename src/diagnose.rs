@@ -0,0 +1,210 @@
+use {
+    crate::*,
+    core::{fmt, ops::Range},
+};
+
+/// The maximal width of the excerpt of the input line in a diagnostic,
+/// longer lines being truncated around the span
+const MAX_EXCERPT_WIDTH: usize = 80;
+
+/// The number of columns kept before the span in a truncated line
+const EXCERPT_CONTEXT: usize = 20;
+
+/// The width of a tab in a diagnostic
+const TAB_WIDTH: usize = 4;
+
+/// Something which may know its position in the parsed input, like
+/// an atom, or the metadata of an operator node
+pub trait Spanned {
+    /// the byte range in the input, if known
+    fn span(&self) -> Option<Range<usize>>;
+}
+
+impl Spanned for () {
+    fn span(&self) -> Option<Range<usize>> {
+        None
+    }
+}
+
+impl Spanned for Range<usize> {
+    fn span(&self) -> Option<Range<usize>> {
+        Some(self.clone())
+    }
+}
+
+/// An atom with its span, as made by `try_map_atoms_indexed`
+/// with a side table of the positions of the atoms
+impl<T> Spanned for (T, Range<usize>) {
+    fn span(&self) -> Option<Range<usize>> {
+        Some(self.1.clone())
+    }
+}
+
+/// Evaluation telling which element failed, and diagnostics
+impl<Op, Atom, Meta> BeTree<Op, Atom, Meta> {
+    fn eval_child_located<Err, R, EvalAtom, EvalOp, ShortCircuit>(
+        &self,
+        eval_atom: &EvalAtom,
+        eval_op: &EvalOp,
+        short_circuit: &ShortCircuit,
+        child: Child,
+    ) -> Result<Option<R>, EvalFailure<Err>>
+    where
+        EvalAtom: Fn(&Atom) -> Result<R, Err>,
+        EvalOp: Fn(&Op, R, Option<R>) -> Result<R, Err>,
+        ShortCircuit: Fn(&Op, &R) -> bool,
+    {
+        let node_idx = match child {
+            Child::None => return Ok(None),
+            Child::Atom(atom_idx) => {
+                return eval_atom(&self.atoms[atom_idx])
+                    .map(Some)
+                    .map_err(|error| EvalFailure { error, child });
+            }
+            Child::Node(node_idx) => node_idx,
+        };
+        let node = &self.nodes[node_idx];
+        let left_value = self.eval_child_located(eval_atom, eval_op, short_circuit, node.left())?;
+        let Some(op) = &node.operator else {
+            return Ok(left_value);
+        };
+        let Some(left_value) = left_value else {
+            return Ok(None);
+        };
+        if short_circuit(op, &left_value) {
+            return Ok(Some(left_value));
+        }
+        let right_value =
+            self.eval_child_located(eval_atom, eval_op, short_circuit, node.right())?;
+        eval_op(op, left_value, right_value)
+            .map(Some)
+            .map_err(|error| EvalFailure { error, child })
+    }
+
+    /// evaluate the expression exactly like `eval_faillible`, but
+    /// return with the error the atom or operation which failed, so
+    /// that it can be reported with `diagnose`
+    pub fn eval_faillible_located<Err, R, EvalAtom, EvalOp, ShortCircuit>(
+        &self,
+        eval_atom: EvalAtom,
+        eval_op: EvalOp,
+        short_circuit: ShortCircuit,
+    ) -> Result<Option<R>, EvalFailure<Err>>
+    where
+        EvalAtom: Fn(&Atom) -> Result<R, Err>,
+        EvalOp: Fn(&Op, R, Option<R>) -> Result<R, Err>,
+        ShortCircuit: Fn(&Op, &R) -> bool,
+    {
+        self.eval_child_located(&eval_atom, &eval_op, &short_circuit, Child::Node(self.head))
+    }
+}
+
+impl<Op, Atom, Meta> BeTree<Op, Atom, Meta>
+where
+    Atom: Spanned,
+    Meta: Spanned,
+{
+    /// the span in the input of the element whose evaluation failed
+    fn failure_span<Err>(&self, failure: &EvalFailure<Err>) -> Option<Range<usize>> {
+        match failure.child {
+            Child::Atom(atom_id) => self.atom(atom_id)?.span(),
+            Child::Node(node_id) => self.node_meta(node_id)?.span(),
+            Child::None => None,
+        }
+    }
+
+    /// render the failure of an evaluation (see `eval_faillible_located`)
+    /// as the line of the input containing the failing atom or operation,
+    /// with this element underlined and followed by the error, like
+    ///
+    /// ```text
+    /// 2 | a & bad
+    ///   |     ^^^ unknown atom
+    /// ```
+    ///
+    /// Tabs are expanded, and lines too long are truncated around the
+    /// span. When the span isn't known, or doesn't fit the input, only
+    /// the error is written.
+    pub fn diagnose<Err: fmt::Display>(&self, input: &str, failure: &EvalFailure<Err>) -> String {
+        let span = self
+            .failure_span(failure)
+            .filter(|span| span.start <= span.end && input.get(span.clone()).is_some());
+        match span {
+            Some(span) => render_diagnostic(input, span, &failure.error),
+            None => failure.error.to_string(),
+        }
+    }
+}
+
+/// expand the tabs of the line, returning its chars with, for each
+/// byte offset of a char, the column of this char
+fn expand_line(line: &str) -> (Vec<char>, Vec<(usize, usize)>) {
+    let mut chars = Vec::new();
+    let mut columns = Vec::new();
+    for (offset, c) in line.char_indices() {
+        columns.push((offset, chars.len()));
+        if c == '\t' {
+            let width = TAB_WIDTH - chars.len() % TAB_WIDTH;
+            chars.extend(core::iter::repeat_n(' ', width));
+        } else {
+            chars.push(c);
+        }
+    }
+    columns.push((line.len(), chars.len()));
+    (chars, columns)
+}
+
+fn render_diagnostic(input: &str, span: Range<usize>, error: &impl fmt::Display) -> String {
+    let line_start = input[..span.start].rfind('\n').map_or(0, |i| i + 1);
+    let line_end = input[span.start..]
+        .find('\n')
+        .map_or(input.len(), |i| span.start + i);
+    let line = input[line_start..line_end].trim_end_matches('\r');
+    let line_number = input[..span.start].matches('\n').count() + 1;
+    // a span going over several lines is underlined up to the end of its first line
+    let start = span.start - line_start;
+    let end = span.end.min(line_start + line.len()).max(span.start) - line_start;
+    let (chars, columns) = expand_line(line);
+    let column = |offset: usize| {
+        columns
+            .iter()
+            .find(|&&(char_offset, _)| char_offset >= offset)
+            .map_or(chars.len(), |&(_, column)| column)
+    };
+    let start_column = column(start);
+    let end_column = column(end).max(start_column + 1);
+    // the excerpt of the line, truncated if too long
+    let (first, last) = if chars.len() <= MAX_EXCERPT_WIDTH {
+        (0, chars.len())
+    } else {
+        let first = if end_column <= MAX_EXCERPT_WIDTH {
+            0
+        } else {
+            start_column.saturating_sub(EXCERPT_CONTEXT)
+        };
+        let last = (first + MAX_EXCERPT_WIDTH).max(end_column).min(chars.len());
+        (first, last)
+    };
+    let mut excerpt = String::new();
+    let mut indent = start_column - first;
+    if first > 0 {
+        excerpt.push_str("...");
+        indent += 3;
+    }
+    excerpt.extend(&chars[first..last]);
+    if last < chars.len() {
+        excerpt.push_str("...");
+    }
+    let gutter = line_number.to_string();
+    format!(
+        "{} | {}\n{:w$} | {:indent$}{} {}",
+        gutter,
+        excerpt.trim_end(),
+        "",
+        "",
+        "^".repeat(end_column - start_column),
+        error,
+        w = gutter.len(),
+        indent = indent,
+    )
+}
@@ -0,0 +1,203 @@
+use {crate::*, core::fmt};
+
+/// A difference between two expressions, as found by `BeTree::diff`.
+///
+/// The path of an edit is the list of sides to follow from the root
+/// operator to reach the place of the edit, groups (i.e. parenthesis)
+/// being ignored. Edits never overlap: the path of an edit is valid in
+/// both the old and the new expression.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TreeEdit<Op, Atom> {
+    /// An atom was replaced with another one
+    ReplaceAtom {
+        path: Vec<Side>,
+        old: Atom,
+        new: Atom,
+    },
+    /// An operator was replaced with another one, the operands
+    /// staying at the same place
+    ReplaceOperator { path: Vec<Side>, old: Op, new: Op },
+    /// A unary operator was put above the subexpression
+    WrapUnary { path: Vec<Side>, operator: Op },
+    /// The unary operator above the subexpression was removed
+    UnwrapUnary { path: Vec<Side>, operator: Op },
+    /// The subexpression became one of the operands of a binary operator,
+    /// the new operand being on the given side
+    InsertOperand {
+        path: Vec<Side>,
+        operator: Op,
+        side: Side,
+        operand: BeTree<Op, Atom>,
+    },
+    /// The operand on the given side of a binary operator was removed,
+    /// the other operand taking the place of the operation
+    RemoveOperand {
+        path: Vec<Side>,
+        operator: Op,
+        side: Side,
+        operand: BeTree<Op, Atom>,
+    },
+    /// A whole subexpression was replaced with another one
+    ReplaceSubtree {
+        path: Vec<Side>,
+        old: BeTree<Op, Atom>,
+        new: BeTree<Op, Atom>,
+    },
+}
+
+/// Differences between trees
+impl<Op, Atom> BeTree<Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone + PartialEq,
+{
+    /// if the child is a complete binary node, return its operator,
+    /// left and right operands
+    fn binary_parts(&self, child: Child) -> Option<(&Op, Child, Child)> {
+        let Child::Node(node_idx) = child else {
+            return None;
+        };
+        let node = &self.nodes[node_idx];
+        match &node.operator {
+            Some(operator) if !node.unary && node.right().is_some() => {
+                Some((operator, node.left(), node.right()))
+            }
+            _ => None,
+        }
+    }
+
+    /// if the child is a unary node, return its operator and operand
+    fn unary_parts(&self, child: Child) -> Option<(&Op, Child)> {
+        let Child::Node(node_idx) = child else {
+            return None;
+        };
+        let node = &self.nodes[node_idx];
+        match &node.operator {
+            Some(operator) if node.unary => Some((operator, node.left())),
+            _ => None,
+        }
+    }
+
+    /// find the edits transforming a child of this tree into a child of
+    /// the other one, when they're both at the given path
+    fn diff_child(
+        &self,
+        child: Child,
+        other: &Self,
+        other_child: Child,
+        path: &mut Vec<Side>,
+        edits: &mut Vec<TreeEdit<Op, Atom>>,
+    ) {
+        let (a, b) = (self.skip_groups(child), other.skip_groups(other_child));
+        if self.same_child_as(a, other, b) {
+            return;
+        }
+        let path_vec = path.clone();
+        // a unary operator added or removed
+        if let Some((operator, operand)) = other.unary_parts(b) {
+            if self.same_child_as(a, other, operand) {
+                let operator = operator.clone();
+                edits.push(TreeEdit::WrapUnary {
+                    path: path_vec,
+                    operator,
+                });
+                return;
+            }
+        }
+        if let Some((operator, operand)) = self.unary_parts(a) {
+            if self.same_child_as(operand, other, b) {
+                let operator = operator.clone();
+                edits.push(TreeEdit::UnwrapUnary {
+                    path: path_vec,
+                    operator,
+                });
+                return;
+            }
+        }
+        // an operand added or removed
+        if let Some((operator, left, right)) = other.binary_parts(b) {
+            for (kept, side, operand) in [(left, Side::Right, right), (right, Side::Left, left)] {
+                if self.same_child_as(a, other, kept) {
+                    edits.push(TreeEdit::InsertOperand {
+                        path: path_vec,
+                        operator: operator.clone(),
+                        side,
+                        operand: other.child_tree(operand),
+                    });
+                    return;
+                }
+            }
+        }
+        if let Some((operator, left, right)) = self.binary_parts(a) {
+            for (kept, side, operand) in [(left, Side::Right, right), (right, Side::Left, left)] {
+                if self.same_child_as(kept, other, b) {
+                    edits.push(TreeEdit::RemoveOperand {
+                        path: path_vec,
+                        operator: operator.clone(),
+                        side,
+                        operand: self.child_tree(operand),
+                    });
+                    return;
+                }
+            }
+        }
+        match (a, b) {
+            (Child::Atom(a), Child::Atom(b)) => {
+                edits.push(TreeEdit::ReplaceAtom {
+                    path: path_vec,
+                    old: self.atoms[a].clone(),
+                    new: other.atoms[b].clone(),
+                });
+                return;
+            }
+            (Child::Node(a_idx), Child::Node(b_idx)) => {
+                let (a_node, b_node) = (&self.nodes[a_idx], &other.nodes[b_idx]);
+                let same_shape = a_node.unary == b_node.unary
+                    && a_node.right().is_some() == b_node.right().is_some();
+                if let (Some(old), Some(new), true) =
+                    (&a_node.operator, &b_node.operator, same_shape)
+                {
+                    if old != new {
+                        edits.push(TreeEdit::ReplaceOperator {
+                            path: path_vec,
+                            old: old.clone(),
+                            new: new.clone(),
+                        });
+                    }
+                    for side in [Side::Left, Side::Right] {
+                        path.push(side);
+                        self.diff_child(a_node.child(side), other, b_node.child(side), path, edits);
+                        path.pop();
+                    }
+                    return;
+                }
+            }
+            _ => {}
+        }
+        edits.push(TreeEdit::ReplaceSubtree {
+            path: path_vec,
+            old: self.child_tree(a),
+            new: other.child_tree(b),
+        });
+    }
+
+    /// compute the edits transforming this expression into the other one.
+    ///
+    /// The diff is based on the structure of the expressions, groups
+    /// being ignored: two trees with the same structure, whatever their
+    /// internal representation, have no difference.
+    ///
+    /// The list of edits isn't guaranteed to be minimal, but it's stable:
+    /// the same trees always give the same edits, in tree order.
+    pub fn diff(&self, other: &Self) -> Vec<TreeEdit<Op, Atom>> {
+        let mut edits = Vec::new();
+        self.diff_child(
+            Child::Node(self.head),
+            other,
+            Child::Node(other.head),
+            &mut Vec::new(),
+            &mut edits,
+        );
+        edits
+    }
+}
@@ -0,0 +1,160 @@
+use {crate::*, core::fmt, core::fmt::Write};
+
+/// A CNF formula in the DIMACS format, built by `BeTree::to_dimacs`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DimacsCnf {
+    /// The DIMACS document, starting with its `p cnf` line
+    pub dimacs: String,
+    /// The number of variables, atom and auxiliary ones
+    pub variable_count: u32,
+    /// The number of clauses
+    pub clause_count: usize,
+    /// The auxiliary variables, each one standing for the result
+    /// of the operation of a node
+    pub auxiliary_variables: Vec<(u32, NodeId)>,
+}
+
+/// The state of a Tseitin transformation
+struct Tseitin<'f, IA, IO, IN> {
+    is_and: &'f IA,
+    is_or: &'f IO,
+    is_not: &'f IN,
+    atom_vars: Vec<Option<u32>>,
+    next_var: u32,
+    auxiliary_variables: Vec<(u32, NodeId)>,
+    clauses: Vec<Vec<i64>>,
+}
+
+impl<Op, Atom> BeTree<Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone,
+{
+    /// return the literal standing for the child, adding the clauses
+    /// defining it
+    fn tseitin_literal<IA, IO, IN>(
+        &self,
+        child: Child,
+        parent: NodeId,
+        t: &mut Tseitin<'_, IA, IO, IN>,
+    ) -> Result<i64, CnfError>
+    where
+        IA: Fn(&Op) -> bool,
+        IO: Fn(&Op) -> bool,
+        IN: Fn(&Op) -> bool,
+    {
+        let node_idx = match self.skip_groups(child) {
+            Child::None => return Err(CnfError::MissingOperand { node: parent }),
+            Child::Atom(atom_idx) => {
+                return t.atom_vars[atom_idx]
+                    .map(i64::from)
+                    .ok_or(CnfError::InvalidVariable { atom: atom_idx });
+            }
+            Child::Node(node_idx) => node_idx,
+        };
+        let node = &self.nodes[node_idx];
+        let unsupported = CnfError::UnsupportedOperator { node: node_idx };
+        let op = node.operator.as_ref().ok_or(unsupported)?;
+        if node.unary {
+            if !(t.is_not)(op) {
+                return Err(unsupported);
+            }
+            return Ok(-self.tseitin_literal(node.left(), node_idx, t)?);
+        }
+        let is_and = (t.is_and)(op);
+        if !is_and && !(t.is_or)(op) {
+            return Err(unsupported);
+        }
+        let a = self.tseitin_literal(node.left(), node_idx, t)?;
+        let b = self.tseitin_literal(node.right(), node_idx, t)?;
+        let x = t.next_var;
+        t.next_var = x.checked_add(1).ok_or(CnfError::TooManyVariables)?;
+        t.auxiliary_variables.push((x, node_idx));
+        let x = i64::from(x);
+        if is_and {
+            // x <=> a & b
+            t.clauses.push(vec![-x, a]);
+            t.clauses.push(vec![-x, b]);
+            t.clauses.push(vec![x, -a, -b]);
+        } else {
+            // x <=> a | b
+            t.clauses.push(vec![x, -a]);
+            t.clauses.push(vec![x, -b]);
+            t.clauses.push(vec![-x, a, b]);
+        }
+        Ok(x)
+    }
+
+    /// build a DIMACS CNF document, satisfiable exactly when the
+    /// expression can be true, for example to be given to a SAT solver.
+    ///
+    /// The operators are recognized with the `is_and`, `is_or`, and
+    /// `is_not` functions, AND and OR being binary and NOT unary. Each
+    /// atom is given a variable (at least 1) by `atom_var`, which is called
+    /// once per reachable atom; several atoms may share a variable.
+    ///
+    /// The Tseitin transformation is applied: each binary operation gets an
+    /// auxiliary variable, numbered after the atom variables, so that the
+    /// size of the formula is linear in the size of the expression. The
+    /// auxiliary variables are returned with the nodes they stand for.
+    pub fn to_dimacs<IA, IO, IN, AV>(
+        &self,
+        is_and: IA,
+        is_or: IO,
+        is_not: IN,
+        mut atom_var: AV,
+    ) -> Result<DimacsCnf, CnfError>
+    where
+        IA: Fn(&Op) -> bool,
+        IO: Fn(&Op) -> bool,
+        IN: Fn(&Op) -> bool,
+        AV: FnMut(&Atom) -> u32,
+    {
+        let root = self.skip_groups(Child::Node(self.head));
+        if root.is_none() {
+            return Err(CnfError::EmptyExpression);
+        }
+        let mut atom_vars = vec![None; self.atoms.len()];
+        let mut max_atom_var = 0;
+        for (atom_idx, atom) in self.iter_atoms_in_tree() {
+            let var = atom_var(atom);
+            if var == 0 {
+                return Err(CnfError::InvalidVariable { atom: atom_idx });
+            }
+            if var > i32::MAX as u32 {
+                return Err(CnfError::TooManyVariables);
+            }
+            max_atom_var = max_atom_var.max(var);
+            atom_vars[atom_idx] = Some(var);
+        }
+        let mut t = Tseitin {
+            is_and: &is_and,
+            is_or: &is_or,
+            is_not: &is_not,
+            atom_vars,
+            next_var: max_atom_var + 1,
+            auxiliary_variables: Vec::new(),
+            clauses: Vec::new(),
+        };
+        let root_literal = self.tseitin_literal(root, self.head, &mut t)?;
+        t.clauses.push(vec![root_literal]);
+        let variable_count = t.next_var - 1;
+        if variable_count > i32::MAX as u32 {
+            return Err(CnfError::TooManyVariables);
+        }
+        let mut dimacs = String::new();
+        let _ = writeln!(dimacs, "p cnf {} {}", variable_count, t.clauses.len());
+        for clause in &t.clauses {
+            for literal in clause {
+                let _ = write!(dimacs, "{} ", literal);
+            }
+            dimacs.push_str("0\n");
+        }
+        Ok(DimacsCnf {
+            dimacs,
+            variable_count,
+            clause_count: t.clauses.len(),
+            auxiliary_variables: t.auxiliary_variables,
+        })
+    }
+}
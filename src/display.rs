@@ -0,0 +1,260 @@
+use {crate::*, core::fmt};
+
+/// Fully parenthesized rendering
+impl<Op, Atom> BeTree<Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq + fmt::Display,
+    Atom: fmt::Debug + Clone + fmt::Display,
+{
+    /// write the child, with parenthesis around operations
+    fn fmt_child(&self, child: Child, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match child {
+            Child::None => Ok(()),
+            Child::Atom(atom_idx) => write!(f, "{}", self.atoms[atom_idx]),
+            Child::Node(node_idx) => {
+                let node = &self.nodes[node_idx];
+                if node.operator.is_none() {
+                    return self.fmt_child(node.left(), f);
+                }
+                write!(f, "(")?;
+                self.fmt_operation(node_idx, f)?;
+                write!(f, ")")
+            }
+        }
+    }
+
+    /// write the operation of the node, without enclosing parenthesis
+    fn fmt_operation(&self, node_idx: NodeId, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let node = &self.nodes[node_idx];
+        let Some(op) = &node.operator else {
+            return self.fmt_child(node.left(), f);
+        };
+        if node.unary {
+            write!(f, "{}", op)?;
+            if node.left().is_some() {
+                write!(f, "(")?;
+                self.fmt_operation_or_atom(node.left(), f)?;
+                write!(f, ")")?;
+            }
+            return Ok(());
+        }
+        self.fmt_child(node.left(), f)?;
+        write!(f, " {}", op)?;
+        if node.right().is_some() {
+            write!(f, " ")?;
+            self.fmt_child(node.right(), f)?;
+        }
+        Ok(())
+    }
+
+    /// write the child, without enclosing parenthesis if it's an operation
+    fn fmt_operation_or_atom(&self, child: Child, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.skip_groups(child) {
+            Child::Node(node_idx) => self.fmt_operation(node_idx, f),
+            child => self.fmt_child(child, f),
+        }
+    }
+}
+
+/// Render the expression with all its operations enclosed in parenthesis,
+/// so that it's unambiguous whatever the precedence of the operators, for
+/// example `((a | b) & (!(c)))`.
+///
+/// Unary operators are written before their operand. An incomplete
+/// expression is written as far as it goes, with its dangling operator.
+impl<Op, Atom> fmt::Display for BeTree<Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq + fmt::Display,
+    Atom: fmt::Debug + Clone + fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_child(Child::Node(self.head), f)
+    }
+}
+
+/// The choices of a rendering with parenthesis only where required
+struct MinimalStyle<'r, P, RO, RA> {
+    precedence: &'r P,
+    render_op: &'r RO,
+    render_atom: &'r RA,
+    keep_explicit_groups: bool,
+}
+
+/// Rendering with parenthesis only where required
+impl<Op, Atom> BeTree<Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone,
+{
+    /// the binary operator of the child, if it's a binary operation
+    fn binary_operator(&self, child: Child) -> Option<&Op> {
+        let Child::Node(node_idx) = self.skip_groups(child) else {
+            return None;
+        };
+        let node = &self.nodes[node_idx];
+        if node.unary {
+            None
+        } else {
+            node.operator.as_ref()
+        }
+    }
+
+    /// the unary operator of the child, if it's a unary operation
+    fn unary_operator(&self, child: Child) -> Option<&Op> {
+        let Child::Node(node_idx) = self.skip_groups(child) else {
+            return None;
+        };
+        let node = &self.nodes[node_idx];
+        if node.unary {
+            node.operator.as_ref()
+        } else {
+            None
+        }
+    }
+
+    fn write_minimal<P, RO, RA>(
+        &self,
+        child: Child,
+        in_parens: bool,
+        style: &MinimalStyle<'_, P, RO, RA>,
+        s: &mut String,
+    ) where
+        P: Fn(&Op) -> u8,
+        RO: Fn(&Op, bool) -> String,
+        RA: Fn(&Atom) -> String,
+    {
+        let explicit = style.keep_explicit_groups
+            && matches!(child, Child::Node(node_idx) if self.nodes[node_idx].explicit);
+        if in_parens || explicit {
+            s.push('(');
+            self.write_minimal_content(child, style, s);
+            s.push(')');
+        } else {
+            self.write_minimal_content(child, style, s);
+        }
+    }
+
+    /// write the child, without the parenthesis enclosing it
+    fn write_minimal_content<P, RO, RA>(
+        &self,
+        child: Child,
+        style: &MinimalStyle<'_, P, RO, RA>,
+        s: &mut String,
+    ) where
+        P: Fn(&Op) -> u8,
+        RO: Fn(&Op, bool) -> String,
+        RA: Fn(&Atom) -> String,
+    {
+        let child = if style.keep_explicit_groups {
+            child
+        } else {
+            self.skip_groups(child)
+        };
+        let node_idx = match child {
+            Child::None => return,
+            Child::Atom(atom_idx) => {
+                s.push_str(&(style.render_atom)(&self.atoms[atom_idx]));
+                return;
+            }
+            Child::Node(node_idx) => node_idx,
+        };
+        let node = &self.nodes[node_idx];
+        let Some(op) = &node.operator else {
+            if style.keep_explicit_groups {
+                // the group may contain explicit groups
+                self.write_minimal(node.left(), false, style, s);
+            }
+            return; // empty group
+        };
+        if node.unary {
+            s.push_str(&(style.render_op)(op, true));
+            let in_parens = self.binary_operator(node.left()).is_some();
+            self.write_minimal(node.left(), in_parens, style, s);
+            return;
+        }
+        let p = (style.precedence)(op);
+        // a unary operation binding less tightly than the operator
+        // would take it as operand, like `-2 ^ 2` which is `-(2 ^ 2)`
+        let looser_unary = |child| {
+            self.unary_operator(child)
+                .is_some_and(|unary_op| (style.precedence)(unary_op) < p)
+        };
+        let in_parens = self
+            .binary_operator(node.left())
+            .is_some_and(|left_op| (style.precedence)(left_op) < p)
+            || looser_unary(node.left());
+        self.write_minimal(node.left(), in_parens, style, s);
+        s.push(' ');
+        s.push_str(&(style.render_op)(op, false));
+        if node.right().is_some() {
+            s.push(' ');
+            let in_parens = self
+                .binary_operator(node.right())
+                .is_some_and(|right_op| (style.precedence)(right_op) <= p)
+                || looser_unary(node.right());
+            self.write_minimal(node.right(), in_parens, style, s);
+        }
+    }
+
+    /// render the expression with as few parenthesis as possible, for
+    /// a reader applying the given precedence of binary operators (the
+    /// higher, the tighter) and left associativity.
+    ///
+    /// For example, with `&` having a higher precedence than `|`,
+    /// `(a & b) | c` is rendered as `a & b | c` but `a & (b | c)` keeps
+    /// its parenthesis.
+    ///
+    /// A unary operation is put in parenthesis when it's the operand of
+    /// a binary operator of a higher precedence, for example `(-2) ^ 2`
+    /// when `^` binds tighter than the negation. Binary operators are
+    /// surrounded with spaces while unary operators are written just
+    /// before their operand.
+    pub fn to_string_minimal<P, RO, RA>(
+        &self,
+        precedence: P,
+        render_op: RO,
+        render_atom: RA,
+    ) -> String
+    where
+        P: Fn(&Op) -> u8,
+        RO: Fn(&Op, bool) -> String,
+        RA: Fn(&Atom) -> String,
+    {
+        let style = MinimalStyle {
+            precedence: &precedence,
+            render_op: &render_op,
+            render_atom: &render_atom,
+            keep_explicit_groups: false,
+        };
+        let mut s = String::new();
+        self.write_minimal(Child::Node(self.head), false, &style, &mut s);
+        s
+    }
+
+    /// render the expression like `to_string_minimal`, but keep the
+    /// parenthesis written by the user (see `was_explicit_group`), even
+    /// when they're redundant, like in `((a)) & (b | c)`.
+    ///
+    /// Parenthesis are still added where the precedence requires them.
+    pub fn to_string_minimal_keeping_groups<P, RO, RA>(
+        &self,
+        precedence: P,
+        render_op: RO,
+        render_atom: RA,
+    ) -> String
+    where
+        P: Fn(&Op) -> u8,
+        RO: Fn(&Op, bool) -> String,
+        RA: Fn(&Atom) -> String,
+    {
+        let style = MinimalStyle {
+            precedence: &precedence,
+            render_op: &render_op,
+            render_atom: &render_atom,
+            keep_explicit_groups: true,
+        };
+        let mut s = String::new();
+        self.write_minimal(Child::Node(self.head), false, &style, &mut s);
+        s
+    }
+}
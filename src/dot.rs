@@ -0,0 +1,146 @@
+use {
+    crate::*,
+    core::fmt::{self, Write},
+};
+
+/// escape the label so that it can be put in a quoted DOT string
+fn dot_escape(label: &str) -> String {
+    let mut escaped = String::with_capacity(label.len());
+    for c in label.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Graphviz export
+impl<Op, Atom> BeTree<Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone,
+{
+    /// write the node and the edges to its children
+    fn write_dot_node<LO>(
+        &self,
+        node_id: NodeId,
+        orphan: bool,
+        label_op: &LO,
+        dot: &mut String,
+    ) -> fmt::Result
+    where
+        LO: Fn(&Op) -> String,
+    {
+        let node = &self.nodes[node_id];
+        let label = node
+            .operator
+            .as_ref()
+            .map_or_else(|| "()".to_string(), label_op);
+        write!(dot, "    n{} [label=\"{}\"", node_id, dot_escape(&label))?;
+        if node.unary {
+            dot.push_str(", shape=doublecircle");
+        }
+        if node_id == self.head {
+            dot.push_str(", penwidth=2");
+        }
+        if orphan {
+            dot.push_str(", color=grey, fontcolor=grey");
+        }
+        dot.push_str("];\n");
+        for (side, child) in [(Side::Left, node.left()), (Side::Right, node.right())] {
+            let side = if side == Side::Left { "L" } else { "R" };
+            match child {
+                Child::None => continue,
+                Child::Node(child_id) => write!(dot, "    n{} -> n{}", node_id, child_id)?,
+                Child::Atom(atom_id) => write!(dot, "    n{} -> a{}", node_id, atom_id)?,
+            }
+            write!(dot, " [label=\"{}\"", side)?;
+            if orphan {
+                dot.push_str(", color=grey, fontcolor=grey");
+            }
+            dot.push_str("];\n");
+        }
+        Ok(())
+    }
+
+    fn write_dot<LO, LA>(&self, label_op: LO, label_atom: LA, orphans: bool) -> String
+    where
+        LO: Fn(&Op) -> String,
+        LA: Fn(&Atom) -> String,
+    {
+        let mut dot = String::new();
+        let mut reachable_nodes = vec![false; self.nodes.len()];
+        let mut reachable_atoms = vec![false; self.atoms.len()];
+        dot.push_str("digraph {\n    node [shape=circle];\n");
+        let _ = writeln!(
+            dot,
+            "    head [shape=plaintext];\n    head -> n{};",
+            self.head
+        );
+        for (node_id, node) in self.iter_nodes() {
+            reachable_nodes[node_id] = true;
+            for child in [node.left(), node.right()] {
+                if let Child::Atom(atom_id) = child {
+                    reachable_atoms[atom_id] = true;
+                }
+            }
+            let _ = self.write_dot_node(node_id, false, &label_op, &mut dot);
+        }
+        if orphans {
+            for (node_id, &reachable) in reachable_nodes.iter().enumerate() {
+                if !reachable {
+                    let _ =
+                        self.write_dot_node(NodeId::from_index(node_id), true, &label_op, &mut dot);
+                }
+            }
+        }
+        for (atom_id, atom) in self.atoms.iter().enumerate() {
+            if reachable_atoms[atom_id] {
+                let _ = writeln!(
+                    dot,
+                    "    a{} [label=\"{}\", shape=box];",
+                    atom_id,
+                    dot_escape(&label_atom(atom)),
+                );
+            } else if orphans {
+                let _ = writeln!(
+                    dot,
+                    "    a{} [label=\"{}\", shape=box, color=grey, fontcolor=grey];",
+                    atom_id,
+                    dot_escape(&label_atom(atom)),
+                );
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// build a Graphviz digraph of the tree, for documentation
+    /// or debugging.
+    ///
+    /// The nodes reachable from the head are identified as `n{NodeId}`
+    /// and labelled with their operator (`()` for groups), unary ones
+    /// being double-circled. Atoms are boxes identified as `a{AtomId}`.
+    /// Edges are labelled `L` or `R` and the head is pointed at by a
+    /// `head` node.
+    pub fn to_dot<LO, LA>(&self, label_op: LO, label_atom: LA) -> String
+    where
+        LO: Fn(&Op) -> String,
+        LA: Fn(&Atom) -> String,
+    {
+        self.write_dot(label_op, label_atom, false)
+    }
+
+    /// build a Graphviz digraph of the tree, as `to_dot` but with the
+    /// nodes and atoms which aren't reachable anymore drawn in grey
+    pub fn to_dot_with_orphans<LO, LA>(&self, label_op: LO, label_atom: LA) -> String
+    where
+        LO: Fn(&Op) -> String,
+        LA: Fn(&Atom) -> String,
+    {
+        self.write_dot(label_op, label_atom, true)
+    }
+}
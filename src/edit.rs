@@ -0,0 +1,631 @@
+#[cfg(feature = "std")]
+use std::{collections::HashMap, hash::Hash};
+use {crate::*, core::fmt};
+
+/// Structural edits of the tree
+impl<Op, Atom> BeTree<Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone,
+{
+    /// move all nodes and atoms of another tree into this one,
+    /// re-indexing them, and return the new id of the head of
+    /// the grafted tree, which is given the `parent` parent.
+    ///
+    /// The grafted tree isn't connected as child of `parent`,
+    /// this is the responsibility of the caller.
+    pub(crate) fn graft(&mut self, other: BeTree<Op, Atom>, parent: Option<NodeId>) -> NodeId {
+        let node_offset = self.nodes.len();
+        let atom_offset = self.atoms.len();
+        let shift = |child: Child| match child {
+            Child::None => Child::None,
+            Child::Node(node_idx) => {
+                Child::Node(NodeId::from_index(node_idx.index() + node_offset))
+            }
+            Child::Atom(atom_idx) => {
+                Child::Atom(AtomId::from_index(atom_idx.index() + atom_offset))
+            }
+        };
+        self.atoms.extend(other.atoms);
+        for mut node in other.nodes {
+            node.set_parent(
+                node.parent()
+                    .map(|parent| NodeId::from_index(parent.index() + node_offset)),
+            );
+            node.set_left(shift(node.left()));
+            node.set_right(shift(node.right()));
+            self.nodes.push(node);
+        }
+        self.op_count += other.op_count;
+        let head = NodeId::from_index(other.head.index() + node_offset);
+        self.nodes[head].set_parent(parent);
+        head
+    }
+
+    /// build the expression combining two expressions with a binary
+    /// operator, as if it had been written `(self) operator (other)`.
+    ///
+    /// If one of the expressions is empty, the other one is returned.
+    pub fn combine(mut self, operator: Op, other: Self) -> Self {
+        if other.is_empty() {
+            return self;
+        }
+        if self.is_empty() {
+            return other;
+        }
+        let mut left = self.head;
+        if self.nodes[left].operator.is_some() && !self.nodes[left].unary {
+            // the left operation is kept in a group, as it would be if
+            // parenthesized, so that it's not seen as the start of a chain
+            let mut group = Node::new(None, false);
+            group.set_left(Child::Node(left));
+            let group_idx = self.store_node(group);
+            self.nodes[left].set_parent(Some(group_idx));
+            left = group_idx;
+        }
+        let right = self.graft(other, None);
+        let mut node = Node::new(Some(operator), false);
+        node.set_left(Child::Node(left));
+        node.set_right(Child::Node(right));
+        let head = self.store_node(node);
+        self.nodes[left].set_parent(Some(head));
+        self.nodes[right].set_parent(Some(head));
+        self.head = head;
+        self.tail = head;
+        self.last_pushed = TokenType::ClosingPar;
+        self.op_count += 1;
+        self.openness = 0;
+        self.debug_check_structure();
+        self
+    }
+
+    /// replace atoms with whole expressions.
+    ///
+    /// For every reachable atom for which `f` returns a tree, this tree
+    /// is inserted in place of the atom, as if it had been parenthesized
+    /// there. The inserted trees aren't themselves searched for atoms
+    /// to replace.
+    ///
+    /// The replaced atoms stay in the arena (so that atom ids stay valid)
+    /// but aren't reachable anymore. An empty replacement tree results
+    /// in an empty group, as would `()`.
+    pub fn replace_atoms_with_trees<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&Atom) -> Option<BeTree<Op, Atom>>,
+    {
+        // we first collect the links to atoms so that inserted trees
+        // aren't visited
+        let mut links = Vec::new();
+        let mut stack = vec![self.head];
+        while let Some(node_idx) = stack.pop() {
+            let node = &self.nodes[node_idx];
+            for (child, side) in [(node.right(), Side::Right), (node.left(), Side::Left)] {
+                match child {
+                    Child::None => {}
+                    Child::Node(child_idx) => stack.push(child_idx),
+                    Child::Atom(atom_idx) => links.push((node_idx, side, atom_idx)),
+                }
+            }
+        }
+        self.bump_epoch();
+        for (node_idx, side, atom_idx) in links {
+            let Some(replacement) = f(&self.atoms[atom_idx]) else {
+                continue;
+            };
+            let new_idx = self.graft(replacement, Some(node_idx));
+            self.nodes[node_idx].set_child(side, Child::Node(new_idx));
+        }
+        self.debug_check_structure();
+    }
+
+    /// find the node holding the given atom, and the side of the atom in it
+    pub(crate) fn atom_link(&self, atom_id: AtomId) -> Option<(NodeId, Side)> {
+        let mut stack = vec![self.head];
+        while let Some(node_idx) = stack.pop() {
+            let node = &self.nodes[node_idx];
+            for side in [Side::Left, Side::Right] {
+                match node.child(side) {
+                    Child::Atom(idx) if idx == atom_id => return Some((node_idx, side)),
+                    Child::Node(child_idx) => stack.push(child_idx),
+                    _ => {}
+                }
+            }
+        }
+        None
+    }
+
+    /// return the parent of the node and the side of the node in it
+    pub(crate) fn node_link(&self, node_idx: NodeId) -> Option<(NodeId, Side)> {
+        let parent_idx = self.nodes[node_idx].parent()?;
+        let side = if self.nodes[parent_idx].left() == Child::Node(node_idx) {
+            Side::Left
+        } else {
+            Side::Right
+        };
+        Some((parent_idx, side))
+    }
+
+    /// tell whether the node is reachable from the head
+    pub(crate) fn is_reachable(&self, mut node_idx: NodeId) -> bool {
+        // parent chains can't be trusted for detached nodes, so we check
+        // the links in both directions
+        loop {
+            if node_idx == self.head {
+                return true;
+            }
+            match self.node_link(node_idx) {
+                Some((parent_idx, side))
+                    if self.nodes[parent_idx].child(side) == Child::Node(node_idx) =>
+                {
+                    node_idx = parent_idx;
+                }
+                _ => {
+                    return false;
+                }
+            }
+        }
+    }
+
+    /// repair the building state after an edit: the tail is moved
+    /// to the head if it's not reachable anymore, or if the groups
+    /// above it don't match the open parenthesis
+    pub(crate) fn fix_tail(&mut self) {
+        if !self.is_reachable(self.tail) || self.open_groups() != Some(self.openness) {
+            self.tail = self.head;
+            self.openness = 0;
+        }
+        self.debug_check_structure();
+    }
+
+    /// remove a child from a node, then remove or replace the node
+    /// if it can't stand anymore
+    pub(crate) fn remove_child(&mut self, node_idx: NodeId, side: Side) {
+        self.nodes[node_idx].set_child(side, Child::None);
+        let node = &self.nodes[node_idx];
+        let remaining = node.left().is_some() || node.right().is_some();
+        let standing = node.operator.is_some() && !node.unary && remaining;
+        if standing {
+            // a binary node with one operand left: it's replaced with the operand
+            let other = if node.left().is_some() {
+                node.left()
+            } else {
+                node.right()
+            };
+            self.op_count -= 1;
+            match self.node_link(node_idx) {
+                Some((parent_idx, parent_side)) => {
+                    self.nodes[parent_idx].set_child(parent_side, other);
+                    if let Child::Node(other_idx) = other {
+                        self.nodes[other_idx].set_parent(Some(parent_idx));
+                    }
+                }
+                None => {
+                    if let Child::Node(other_idx) = other {
+                        self.nodes[other_idx].set_parent(None);
+                        self.head = other_idx;
+                    } else {
+                        // the head must be a node, so it's kept as a simple wrapper
+                        let node = &mut self.nodes[node_idx];
+                        node.operator = None;
+                        node.set_left(other);
+                        node.set_right(Child::None);
+                    }
+                }
+            }
+            return;
+        }
+        // the node can't stand without the removed child: it's removed too
+        if self.nodes[node_idx].operator.is_some() {
+            self.op_count -= 1;
+        }
+        match self.node_link(node_idx) {
+            Some((parent_idx, parent_side)) => {
+                self.remove_child(parent_idx, parent_side);
+            }
+            None => {
+                self.clear();
+            }
+        }
+    }
+
+    /// remove an atom from the tree.
+    ///
+    /// The operator which joined the atom to its sibling is removed too,
+    /// the sibling taking its place. Unary operators and groups left empty
+    /// are removed. If the atom is the whole expression, the tree becomes
+    /// empty.
+    ///
+    /// The atom stays in the arena (so that atom ids stay valid) but isn't
+    /// reachable anymore.
+    pub fn remove_atom(&mut self, atom_id: AtomId) -> Result<(), EditError> {
+        if atom_id.index() >= self.atoms.len() {
+            return Err(EditError::UnknownAtom(atom_id));
+        }
+        let (node_idx, side) = self
+            .atom_link(atom_id)
+            .ok_or(EditError::UnreachableAtom(atom_id))?;
+        self.bump_epoch();
+        self.remove_child(node_idx, side);
+        self.fix_tail();
+        Ok(())
+    }
+
+    /// count the operators in the child and its descendants
+    pub(crate) fn count_operators(&self, child: Child) -> usize {
+        let mut count = 0;
+        let mut stack = vec![child];
+        while let Some(child) = stack.pop() {
+            if let Child::Node(node_idx) = child {
+                let node = &self.nodes[node_idx];
+                if node.operator.is_some() {
+                    count += 1;
+                }
+                stack.push(node.left());
+                stack.push(node.right());
+            }
+        }
+        count
+    }
+
+    /// remove a whole subexpression from the tree: the node and all
+    /// its descendants.
+    ///
+    /// The parent of the node is repaired as when removing an atom: a
+    /// binary operator is replaced with its remaining operand, while
+    /// unary operators and groups left empty are removed. If the node
+    /// is the head, the tree becomes empty.
+    ///
+    /// The removed nodes and atoms stay in the arena (so that ids stay
+    /// valid) but aren't reachable anymore.
+    pub fn prune(&mut self, node_id: NodeId) -> Result<(), EditError> {
+        if node_id.index() >= self.nodes.len() {
+            return Err(EditError::UnknownNode(node_id));
+        }
+        if !self.is_reachable(node_id) {
+            return Err(EditError::UnreachableNode(node_id));
+        }
+        self.bump_epoch();
+        let pruned_operators = self.count_operators(Child::Node(node_id));
+        match self.node_link(node_id) {
+            Some((parent_idx, side)) => {
+                self.op_count -= pruned_operators;
+                self.remove_child(parent_idx, side);
+            }
+            None => self.clear(),
+        }
+        self.fix_tail();
+        Ok(())
+    }
+
+    /// remove the atoms not passing the `keep` filter from the child and
+    /// return what should replace the child
+    fn filter_child_atoms<F>(&mut self, child: Child, keep: &F, removed: &mut usize) -> Child
+    where
+        F: Fn(&Atom) -> bool,
+    {
+        match child {
+            Child::None => Child::None,
+            Child::Atom(atom_idx) => {
+                if keep(&self.atoms[atom_idx]) {
+                    child
+                } else {
+                    *removed += 1;
+                    Child::None
+                }
+            }
+            Child::Node(node_idx) => {
+                let left = self.filter_child_atoms(self.nodes[node_idx].left(), keep, removed);
+                let right = self.filter_child_atoms(self.nodes[node_idx].right(), keep, removed);
+                for new_child in [left, right] {
+                    if let Child::Node(new_idx) = new_child {
+                        self.nodes[new_idx].set_parent(Some(node_idx));
+                    }
+                }
+                let node = &mut self.nodes[node_idx];
+                node.set_left(left);
+                node.set_right(right);
+                if node.operator.is_none() || node.unary {
+                    if left.is_none() {
+                        if node.operator.is_some() {
+                            self.op_count -= 1;
+                        }
+                        return Child::None;
+                    }
+                    return child;
+                }
+                match (left, right) {
+                    (Child::None, Child::None) => {
+                        self.op_count -= 1;
+                        Child::None
+                    }
+                    (remaining, Child::None) | (Child::None, remaining) => {
+                        self.op_count -= 1;
+                        remaining
+                    }
+                    _ => child,
+                }
+            }
+        }
+    }
+
+    /// remove all atoms which don't pass the `keep` filter, and return
+    /// the number of removed atoms.
+    ///
+    /// The tree is simplified as if the removed atoms had never been
+    /// there: operators left with only one operand are replaced with
+    /// this operand while unary operators and groups left without
+    /// operand are removed. If no atom is kept, the tree becomes empty.
+    ///
+    /// Removed atoms stay in the arena (so that atom ids stay valid) but
+    /// aren't reachable anymore.
+    pub fn filter_atoms<F>(&mut self, keep: F) -> usize
+    where
+        F: Fn(&Atom) -> bool,
+    {
+        self.bump_epoch();
+        let mut removed = 0;
+        let head = self.head;
+        match self.filter_child_atoms(Child::Node(head), &keep, &mut removed) {
+            Child::None => {
+                self.clear();
+            }
+            Child::Node(node_idx) => {
+                self.nodes[node_idx].set_parent(None);
+                self.head = node_idx;
+                self.fix_tail();
+            }
+            Child::Atom(atom_idx) => {
+                // the head must be a node, so it's kept as a simple wrapper
+                let node = &mut self.nodes[head];
+                node.operator = None;
+                node.unary = false;
+                node.set_left(Child::Atom(atom_idx));
+                node.set_right(Child::None);
+                self.fix_tail();
+            }
+        }
+        removed
+    }
+
+    /// wrap the whole expression in a unary operator, as if it had been
+    /// written `op(expression)`.
+    ///
+    /// Nothing is done if the tree is empty.
+    ///
+    /// After this operation, the tree is in the same state than after a
+    /// closing parenthesis: all parenthesis are closed and a binary
+    /// operator may be pushed.
+    pub fn wrap_unary(&mut self, operator: Op) {
+        if self.is_empty() {
+            return;
+        }
+        self.bump_epoch();
+        let old_head = self.head;
+        let mut node = Node::new(Some(operator), true);
+        node.set_left(Child::Node(old_head));
+        let new_head = self.store_node(node);
+        self.nodes[old_head].set_parent(Some(new_head));
+        self.head = new_head;
+        self.tail = new_head;
+        self.last_pushed = TokenType::ClosingPar;
+        self.op_count += 1;
+        self.openness = 0;
+        self.debug_check_structure();
+    }
+
+    /// if the child is the first node of a pair of directly nested unary
+    /// nodes with the given operator, return the child of the second node
+    fn involutive_pair_end(&self, child: Child, operator: &Op) -> Option<Child> {
+        let is_op_unary = |node: &Node<Op>| node.unary && node.operator.as_ref() == Some(operator);
+        let Child::Node(first_idx) = child else {
+            return None;
+        };
+        if !is_op_unary(&self.nodes[first_idx]) {
+            return None;
+        }
+        // groups (nodes without operator) between the unary nodes are ignored
+        let mut between = self.nodes[first_idx].left();
+        while let Child::Node(idx) = between {
+            let node = &self.nodes[idx];
+            if node.operator.is_some() || node.right().is_some() {
+                break;
+            }
+            between = node.left();
+        }
+        let Child::Node(second_idx) = between else {
+            return None;
+        };
+        if !is_op_unary(&self.nodes[second_idx]) {
+            return None;
+        }
+        Some(self.nodes[second_idx].left())
+    }
+
+    /// remove the pairs of directly nested unary nodes with the given
+    /// operator, and return the number of removed pairs.
+    ///
+    /// `!!x` becomes `x` and `!!!x` becomes `!x`. Groups between the
+    /// unary nodes are ignored, so `!(!x)` also becomes `x`. Unary nodes of
+    /// other operators, or separated by another operator, aren't touched.
+    ///
+    /// This doesn't change the evaluation of the expression if the
+    /// operator is involutive, which is the responsibility of the caller.
+    pub fn simplify_involutive(&mut self, operator: &Op) -> usize {
+        self.bump_epoch();
+        let mut count = 0;
+        // the head is handled separately as it must stay a node
+        while let Some(end) = self.involutive_pair_end(Child::Node(self.head), operator) {
+            count += 1;
+            match end {
+                Child::Node(end_idx) => {
+                    self.nodes[end_idx].set_parent(None);
+                    self.head = end_idx;
+                }
+                _ => {
+                    let head = &mut self.nodes[self.head];
+                    head.operator = None;
+                    head.unary = false;
+                    head.set_left(end);
+                }
+            }
+        }
+        let mut stack = vec![self.head];
+        while let Some(node_idx) = stack.pop() {
+            for side in [Side::Left, Side::Right] {
+                let mut child = self.nodes[node_idx].child(side);
+                while let Some(end) = self.involutive_pair_end(child, operator) {
+                    count += 1;
+                    child = end;
+                }
+                self.nodes[node_idx].set_child(side, child);
+                if let Child::Node(child_idx) = child {
+                    self.nodes[child_idx].set_parent(Some(node_idx));
+                    stack.push(child_idx);
+                }
+            }
+        }
+        self.op_count -= 2 * count;
+        self.fix_tail();
+        count
+    }
+
+    /// swap the left and right operands of a binary node, for example
+    /// changing `a - b` into `b - a`.
+    ///
+    /// Fail if the node doesn't exist, or isn't a binary node with
+    /// its two operands.
+    pub fn swap_children(&mut self, node_id: NodeId) -> Result<(), EditError> {
+        let node = self
+            .nodes
+            .get_mut(node_id.index())
+            .ok_or(EditError::UnknownNode(node_id))?;
+        if node.operator.is_none() || node.unary || node.left().is_none() || node.right().is_none()
+        {
+            return Err(EditError::NotBinaryNode(node_id));
+        }
+        node.swap_children();
+        self.bump_epoch();
+        self.debug_check_structure();
+        Ok(())
+    }
+
+    /// swap the operands of the top level operator of the expression,
+    /// the groups around the whole expression being ignored
+    pub fn swap_root_children(&mut self) -> Result<(), EditError> {
+        let mut node_id = self.head;
+        while let Some(node) = self.nodes.get(node_id.index()) {
+            match (node.operator.is_none(), node.left(), node.right()) {
+                (true, Child::Node(child_id), Child::None) => node_id = child_id,
+                _ => break,
+            }
+        }
+        self.swap_children(node_id)
+    }
+
+    /// rotate the node, its child on the `from` side taking its place,
+    /// the node taking the place of its grandchild on the other side
+    fn rotate(&mut self, node_id: NodeId, from: Side) -> Result<(), EditError> {
+        let is_binary = |node: &Node<Op>| {
+            node.operator.is_some()
+                && !node.unary
+                && node.left().is_some()
+                && node.right().is_some()
+        };
+        let node = self
+            .nodes
+            .get(node_id.index())
+            .ok_or(EditError::UnknownNode(node_id))?;
+        if !is_binary(node) {
+            return Err(EditError::NotBinaryNode(node_id));
+        }
+        let Child::Node(pivot_id) = node.child(from) else {
+            return Err(EditError::NotRotatable(node_id));
+        };
+        if !is_binary(&self.nodes[pivot_id]) {
+            return Err(EditError::NotRotatable(node_id));
+        }
+        self.bump_epoch();
+        let to = from.other();
+        let link = self.node_link(node_id);
+        let moved = self.nodes[pivot_id].child(to);
+        self.link_child(node_id, from, moved);
+        self.link_child(pivot_id, to, Child::Node(node_id));
+        // parenthesis around the node now surround the pivot, and
+        // the ones around the pivot don't match a node anymore
+        self.nodes[pivot_id].explicit = core::mem::take(&mut self.nodes[node_id].explicit);
+        match link {
+            Some((parent_id, side)) => self.link_child(parent_id, side, Child::Node(pivot_id)),
+            None => {
+                self.nodes[pivot_id].set_parent(None);
+                if self.head == node_id {
+                    self.head = pivot_id;
+                }
+            }
+        }
+        self.fix_tail();
+        Ok(())
+    }
+
+    /// do a left rotation of a binary node: its right operand, which
+    /// must be a binary node, takes its place.
+    ///
+    /// For example `a & (b | c)`, rotated at its root, becomes `(a & b) | c`.
+    ///
+    /// This keeps the order of the atoms but changes the association
+    /// (and thus, for most operators, the evaluation) of the expression.
+    pub fn rotate_left(&mut self, node_id: NodeId) -> Result<(), EditError> {
+        self.rotate(node_id, Side::Right)
+    }
+
+    /// do a right rotation of a binary node: its left operand, which
+    /// must be a binary node, takes its place.
+    ///
+    /// For example `(a & b) | c`, rotated at its root, becomes `a & (b | c)`.
+    ///
+    /// This keeps the order of the atoms but changes the association
+    /// (and thus, for most operators, the evaluation) of the expression.
+    pub fn rotate_right(&mut self, node_id: NodeId) -> Result<(), EditError> {
+        self.rotate(node_id, Side::Left)
+    }
+}
+
+/// Deduplication of atoms
+#[cfg(feature = "std")]
+impl<Op, Atom> BeTree<Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone + Eq + Hash,
+{
+    /// make all reachable occurrences of equal atoms refer to the same
+    /// atom, and return the number of links which were redirected.
+    ///
+    /// The kept atom is the first one in tree order. The duplicates stay
+    /// in the arena, so that atom ids stay valid, but they're not reachable
+    /// anymore: `iter_atoms`, which iterates over the arena, still returns
+    /// them while evaluating the tree won't.
+    pub fn dedup_atoms(&mut self) -> usize {
+        self.bump_epoch();
+        let mut kept: HashMap<Atom, AtomId> = HashMap::new();
+        let mut count = 0;
+        // the links to visit, as (parent, side), in tree order
+        let mut stack = vec![(self.head, Side::Right), (self.head, Side::Left)];
+        while let Some((node_idx, side)) = stack.pop() {
+            match self.nodes[node_idx].child(side) {
+                Child::None => {}
+                Child::Node(child_idx) => {
+                    stack.push((child_idx, Side::Right));
+                    stack.push((child_idx, Side::Left));
+                }
+                Child::Atom(atom_idx) => {
+                    let kept_idx = *kept.entry(self.atoms[atom_idx].clone()).or_insert(atom_idx);
+                    if kept_idx != atom_idx {
+                        self.nodes[node_idx].set_child(side, Child::Atom(kept_idx));
+                        count += 1;
+                    }
+                }
+            }
+        }
+        self.debug_check_structure();
+        count
+    }
+}
@@ -0,0 +1,59 @@
+use {crate::*, core::fmt};
+
+/// Error returned by a structural edit of the tree
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum EditError {
+    /// There's no atom with this id
+    UnknownAtom(AtomId),
+    /// The atom isn't reachable from the head of the tree
+    UnreachableAtom(AtomId),
+    /// There's no node with this id
+    UnknownNode(NodeId),
+    /// The node isn't reachable from the head of the tree
+    UnreachableNode(NodeId),
+    /// The node isn't a binary node with its two operands
+    NotBinaryNode(NodeId),
+    /// The node can't be rotated because the child which should take
+    /// its place isn't a complete binary node
+    NotRotatable(NodeId),
+    /// The node already has a child on the side where one was attached
+    OccupiedChild(NodeId),
+    /// The edits of a transaction left an inconsistent tree
+    InvalidStructure(StructureError),
+}
+
+impl fmt::Display for EditError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownAtom(atom_id) => write!(f, "no atom with id {}", atom_id),
+            Self::UnreachableAtom(atom_id) => write!(f, "atom {} isn't reachable", atom_id),
+            Self::UnknownNode(node_id) => write!(f, "no node with id {}", node_id),
+            Self::UnreachableNode(node_id) => write!(f, "node {} isn't reachable", node_id),
+            Self::NotBinaryNode(node_id) => {
+                write!(f, "node {} isn't a complete binary node", node_id)
+            }
+            Self::OccupiedChild(node_id) => {
+                write!(f, "node {} already has a child on this side", node_id)
+            }
+            Self::NotRotatable(node_id) => {
+                write!(
+                    f,
+                    "node {} can't be rotated: its child isn't a binary node",
+                    node_id
+                )
+            }
+            Self::InvalidStructure(e) => write!(f, "the edits left an invalid structure: {}", e),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for EditError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::InvalidStructure(e) => Some(e),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use crate::*;
+
+/// Empty groups, like the `()` in `a & ()`
+impl<Op, Atom, Meta> BeTree<Op, Atom, Meta> {
+    /// tell whether the node is a group (a node without operator)
+    /// with no content, the head of an empty tree excepted
+    pub(crate) fn is_empty_group(&self, node_idx: NodeId) -> bool {
+        let node = &self.nodes[node_idx];
+        node_idx != self.head && node.operator.is_none() && node.left().is_none()
+    }
+
+    /// skip the groups around the child like `skip_groups`, but stop
+    /// at an empty group, so that it's not taken for a missing operand
+    pub(crate) fn skip_non_empty_groups(&self, mut child: Child) -> Child {
+        while let Child::Node(node_idx) = child {
+            let node = &self.nodes[node_idx];
+            if node.operator.is_some() || node.right().is_some() || self.is_empty_group(node_idx) {
+                break;
+            }
+            child = node.left();
+        }
+        child
+    }
+
+    /// return the ids of the reachable empty groups, in tree order.
+    ///
+    /// A group whose only content is an empty group (as the outer
+    /// group of `(())`) isn't itself empty.
+    pub fn empty_groups(&self) -> Vec<NodeId> {
+        let mut empty_groups = Vec::new();
+        let mut stack = vec![self.head];
+        while let Some(node_idx) = stack.pop() {
+            if self.is_empty_group(node_idx) {
+                empty_groups.push(node_idx);
+            }
+            let node = &self.nodes[node_idx];
+            for child in [node.right(), node.left()] {
+                if let Child::Node(child_idx) = child {
+                    stack.push(child_idx);
+                }
+            }
+        }
+        empty_groups
+    }
+
+    fn eval_child_with_empty_groups<R, EvalAtom, EvalOp, ShortCircuit, EmptyGroup>(
+        &self,
+        eval_atom: &EvalAtom,
+        eval_op: &EvalOp,
+        short_circuit: &ShortCircuit,
+        empty_group: &EmptyGroup,
+        child: Child,
+    ) -> Option<R>
+    where
+        EvalAtom: Fn(&Atom) -> R,
+        EvalOp: Fn(&Op, R, Option<R>) -> R,
+        ShortCircuit: Fn(&Op, &R) -> bool,
+        EmptyGroup: Fn() -> R,
+    {
+        let node_idx = match child {
+            Child::None => return None,
+            Child::Atom(atom_idx) => return Some(eval_atom(&self.atoms[atom_idx])),
+            Child::Node(node_idx) => node_idx,
+        };
+        if self.is_empty_group(node_idx) {
+            return Some(empty_group());
+        }
+        let node = &self.nodes[node_idx];
+        let left_value = self.eval_child_with_empty_groups(
+            eval_atom,
+            eval_op,
+            short_circuit,
+            empty_group,
+            node.left(),
+        );
+        let Some(op) = &node.operator else {
+            return left_value;
+        };
+        let left_value = left_value?;
+        if short_circuit(op, &left_value) {
+            return Some(left_value);
+        }
+        let right_value = self.eval_child_with_empty_groups(
+            eval_atom,
+            eval_op,
+            short_circuit,
+            empty_group,
+            node.right(),
+        );
+        Some(eval_op(op, left_value, right_value))
+    }
+
+    /// evaluate the expression like `eval`, but giving to the empty
+    /// groups, like the `()` in `a & ()` or `!()`, the value returned
+    /// by `empty_group` instead of letting them poison their parent
+    /// operations.
+    ///
+    /// `empty_group` usually returns the neutral element of the
+    /// operators, for example `true` for a conjunction. An empty tree
+    /// still evaluates to `None`.
+    pub fn eval_with_empty_groups<R, EvalAtom, EvalOp, ShortCircuit, EmptyGroup>(
+        &self,
+        eval_atom: EvalAtom,
+        eval_op: EvalOp,
+        short_circuit: ShortCircuit,
+        empty_group: EmptyGroup,
+    ) -> Option<R>
+    where
+        EvalAtom: Fn(&Atom) -> R,
+        EvalOp: Fn(&Op, R, Option<R>) -> R,
+        ShortCircuit: Fn(&Op, &R) -> bool,
+        EmptyGroup: Fn() -> R,
+    {
+        self.eval_child_with_empty_groups(
+            &eval_atom,
+            &eval_op,
+            &short_circuit,
+            &empty_group,
+            Child::Node(self.head),
+        )
+    }
+}
@@ -0,0 +1,111 @@
+use crate::*;
+
+/// The operator evaluation of `eval_faillible_dyn`
+type FaillibleEvalOp<'f, Op, R, Err> = dyn FnMut(&Op, R, Option<R>) -> Result<R, Err> + 'f;
+
+/// Evaluation through trait objects
+impl<Op, Atom, Meta> BeTree<Op, Atom, Meta> {
+    fn eval_child_dyn<R>(
+        &self,
+        eval_atom: &mut dyn FnMut(&Atom) -> R,
+        eval_op: &mut dyn FnMut(&Op, R, Option<R>) -> R,
+        short_circuit: &mut dyn FnMut(&Op, &R) -> bool,
+        child: Child,
+    ) -> Option<R> {
+        match child {
+            Child::None => None,
+            Child::Node(node_idx) => {
+                self.eval_node_dyn(eval_atom, eval_op, short_circuit, node_idx)
+            }
+            Child::Atom(atom_idx) => Some(eval_atom(&self.atoms[atom_idx])),
+        }
+    }
+
+    fn eval_node_dyn<R>(
+        &self,
+        eval_atom: &mut dyn FnMut(&Atom) -> R,
+        eval_op: &mut dyn FnMut(&Op, R, Option<R>) -> R,
+        short_circuit: &mut dyn FnMut(&Op, &R) -> bool,
+        node_idx: NodeId,
+    ) -> Option<R> {
+        let node = &self.nodes[node_idx];
+        let left_value = self.eval_child_dyn(eval_atom, eval_op, short_circuit, node.left());
+        let Some(op) = &node.operator else {
+            return left_value;
+        };
+        let left_value = left_value?;
+        if short_circuit(op, &left_value) {
+            return Some(left_value);
+        }
+        let right_value = self.eval_child_dyn(eval_atom, eval_op, short_circuit, node.right());
+        Some(eval_op(op, left_value, right_value))
+    }
+
+    fn eval_child_faillible_dyn<Err, R>(
+        &self,
+        eval_atom: &mut dyn FnMut(&Atom) -> Result<R, Err>,
+        eval_op: &mut FaillibleEvalOp<'_, Op, R, Err>,
+        short_circuit: &mut dyn FnMut(&Op, &R) -> bool,
+        child: Child,
+    ) -> Result<Option<R>, Err> {
+        Ok(match child {
+            Child::None => None,
+            Child::Node(node_idx) => {
+                self.eval_node_faillible_dyn(eval_atom, eval_op, short_circuit, node_idx)?
+            }
+            Child::Atom(atom_idx) => Some(eval_atom(&self.atoms[atom_idx])?),
+        })
+    }
+
+    fn eval_node_faillible_dyn<Err, R>(
+        &self,
+        eval_atom: &mut dyn FnMut(&Atom) -> Result<R, Err>,
+        eval_op: &mut FaillibleEvalOp<'_, Op, R, Err>,
+        short_circuit: &mut dyn FnMut(&Op, &R) -> bool,
+        node_idx: NodeId,
+    ) -> Result<Option<R>, Err> {
+        let node = &self.nodes[node_idx];
+        let left_value =
+            self.eval_child_faillible_dyn(eval_atom, eval_op, short_circuit, node.left())?;
+        let Some(op) = &node.operator else {
+            return Ok(left_value);
+        };
+        let Some(left_value) = left_value else {
+            return Ok(None);
+        };
+        if short_circuit(op, &left_value) {
+            return Ok(Some(left_value));
+        }
+        let right_value =
+            self.eval_child_faillible_dyn(eval_atom, eval_op, short_circuit, node.right())?;
+        Ok(Some(eval_op(op, left_value, right_value)?))
+    }
+
+    /// evaluate the expression exactly like `eval`, but through trait
+    /// objects, so that the evaluation code is compiled once per result
+    /// type instead of once per combination of closure types.
+    ///
+    /// This makes binaries smaller and compilation faster when many
+    /// different closures are used, at the price of indirect calls which
+    /// can't be inlined. With the `dyn-eval` feature, `eval` and
+    /// `eval_faillible` forward to the `_dyn` functions.
+    pub fn eval_dyn<R>(
+        &self,
+        eval_atom: &mut dyn FnMut(&Atom) -> R,
+        eval_op: &mut dyn FnMut(&Op, R, Option<R>) -> R,
+        short_circuit: &mut dyn FnMut(&Op, &R) -> bool,
+    ) -> Option<R> {
+        self.eval_node_dyn(eval_atom, eval_op, short_circuit, self.head)
+    }
+
+    /// evaluate the expression exactly like `eval_faillible`, but through
+    /// trait objects (see `eval_dyn`)
+    pub fn eval_faillible_dyn<Err, R>(
+        &self,
+        eval_atom: &mut dyn FnMut(&Atom) -> Result<R, Err>,
+        eval_op: &mut FaillibleEvalOp<'_, Op, R, Err>,
+        short_circuit: &mut dyn FnMut(&Op, &R) -> bool,
+    ) -> Result<Option<R>, Err> {
+        self.eval_node_faillible_dyn(eval_atom, eval_op, short_circuit, self.head)
+    }
+}
@@ -0,0 +1,31 @@
+use {crate::*, core::fmt};
+
+/// Error returned by `BeTree::eval_faillible_located`: the error of
+/// the evaluation, with the element whose evaluation failed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EvalFailure<Err> {
+    pub error: Err,
+    /// The atom whose evaluation failed, or the node of the
+    /// operation which failed
+    pub child: Child,
+}
+
+impl<Err: fmt::Display> fmt::Display for EvalFailure<Err> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.child {
+            Child::Atom(atom_id) => write!(f, "{} in atom {}", self.error, atom_id),
+            Child::Node(node_id) => write!(f, "{} in operation {}", self.error, node_id),
+            Child::None => write!(f, "{}", self.error),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<Err> std::error::Error for EvalFailure<Err>
+where
+    Err: std::error::Error + 'static,
+{
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
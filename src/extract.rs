@@ -0,0 +1,92 @@
+use {crate::*, core::fmt};
+
+/// Extraction of parts of the tree as independent trees
+impl<Op, Atom> BeTree<Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone,
+{
+    /// build a new compact tree from a copy of the child and its descendants
+    pub(crate) fn child_tree(&self, child: Child) -> Self {
+        let mut tree = Self::new();
+        tree.nodes.clear();
+        let root = self.copy_child_into(child, &mut tree);
+        tree.set_root(root);
+        if !tree.is_empty() {
+            tree.last_pushed = TokenType::ClosingPar;
+        }
+        tree
+    }
+
+    /// split the expression on its top level operator, if it's the given one.
+    ///
+    /// For example splitting `a & b & (c | d)` on `&` returns three trees:
+    /// `a`, `b` and `c | d`. Groups whose operator is the same are split
+    /// too, so `a & (b & c)` is also split in three trees.
+    ///
+    /// If the top level operator isn't the given one, a vec with a clone
+    /// of the whole expression is returned. If the tree is empty, an empty
+    /// vec is returned.
+    ///
+    /// The returned trees are compact (they contain only the structure
+    /// of their part of the expression).
+    pub fn split_top_level(&self, operator: &Op) -> Vec<Self> {
+        if self.is_empty() {
+            return Vec::new();
+        }
+        let mut operands = Vec::new();
+        self.collect_chain(
+            Child::Node(self.head),
+            operator,
+            &mut operands,
+            &mut Vec::new(),
+        );
+        if operands.len() == 1 {
+            return vec![self.clone()];
+        }
+        operands
+            .into_iter()
+            .map(|operand| self.child_tree(operand))
+            .collect()
+    }
+
+    /// build a new tree from a copy of the subexpression whose root
+    /// is the given node, or return `None` if there's no such node
+    /// reachable from the head.
+    ///
+    /// The returned tree is compact: it contains only the nodes and
+    /// atoms of the subexpression. Extracting the subtree of the head
+    /// gives a compacted clone of the whole expression.
+    pub fn subtree(&self, node_id: NodeId) -> Option<Self> {
+        if node_id.index() >= self.nodes.len() || !self.is_reachable(node_id) {
+            return None;
+        }
+        Some(self.child_tree(Child::Node(node_id)))
+    }
+
+    /// build a tree containing exactly the nodes and atoms reachable
+    /// from the head, numbered in tree order (nodes in pre-order, the
+    /// head being the node 0, and atoms from left to right).
+    ///
+    /// The normalized tree is in the same state than after a closing
+    /// parenthesis: the tail is the head and all parenthesis are closed.
+    ///
+    /// Two trees with the same structure, whatever the way they were
+    /// built or edited, have equal normalized forms. Explicit groups
+    /// (see `was_explicit_group`) are kept.
+    pub fn normalize(self) -> Self {
+        let mut tree = self.child_tree(Child::Node(self.head));
+        tree.epoch = self.epoch;
+        tree.bump_epoch();
+        tree
+    }
+
+    /// normalize the tree in place, removing the nodes and atoms which
+    /// aren't reachable anymore (see `normalize`).
+    ///
+    /// Node and atom ids aren't preserved.
+    pub fn compact(&mut self) {
+        *self = core::mem::take(self).normalize();
+        self.debug_check_structure();
+    }
+}
@@ -0,0 +1,142 @@
+use crate::*;
+
+/// An element of an expression prepared for the evaluation of many
+/// items: groups are skipped and operands are indices in the steps
+enum Step<'t, Op, Atom> {
+    Atom(&'t Atom),
+    Operation {
+        operator: &'t Op,
+        left: Option<usize>,
+        right: Option<usize>,
+    },
+}
+
+/// The reachable part of an expression, prepared once for
+/// the evaluation of many items
+struct PreparedFilter<'t, Op, Atom> {
+    steps: Vec<Step<'t, Op, Atom>>,
+    root: Option<usize>,
+}
+
+impl<'t, Op, Atom> PreparedFilter<'t, Op, Atom> {
+    fn new<Meta>(tree: &'t BeTree<Op, Atom, Meta>) -> Self {
+        let mut steps = Vec::new();
+        let root = Self::prepare_child(tree, Child::Node(tree.head), &mut steps);
+        Self { steps, root }
+    }
+
+    fn prepare_child<Meta>(
+        tree: &'t BeTree<Op, Atom, Meta>,
+        child: Child,
+        steps: &mut Vec<Step<'t, Op, Atom>>,
+    ) -> Option<usize> {
+        let step = match child {
+            Child::None => return None,
+            Child::Atom(atom_idx) => Step::Atom(&tree.atoms[atom_idx]),
+            Child::Node(node_idx) => {
+                let node = &tree.nodes[node_idx];
+                let left = Self::prepare_child(tree, node.left(), steps);
+                let Some(operator) = &node.operator else {
+                    return left;
+                };
+                let right = Self::prepare_child(tree, node.right(), steps);
+                Step::Operation {
+                    operator,
+                    left,
+                    right,
+                }
+            }
+        };
+        steps.push(step);
+        Some(steps.len() - 1)
+    }
+
+    /// evaluate the step, exactly as `BeTree::eval` would evaluate
+    /// the corresponding child
+    fn eval_step<EvalAtom, EvalOp, ShortCircuit>(
+        &self,
+        step_idx: usize,
+        eval_atom: &mut EvalAtom,
+        eval_op: &EvalOp,
+        short_circuit: &ShortCircuit,
+    ) -> Option<bool>
+    where
+        EvalAtom: FnMut(&Atom) -> bool,
+        EvalOp: Fn(&Op, bool, Option<bool>) -> bool,
+        ShortCircuit: Fn(&Op, &bool) -> bool,
+    {
+        match &self.steps[step_idx] {
+            Step::Atom(atom) => Some(eval_atom(atom)),
+            Step::Operation {
+                operator,
+                left,
+                right,
+            } => {
+                let left_value = self.eval_step((*left)?, eval_atom, eval_op, short_circuit)?;
+                if short_circuit(operator, &left_value) {
+                    return Some(left_value);
+                }
+                let right_value = right
+                    .and_then(|right| self.eval_step(right, eval_atom, eval_op, short_circuit));
+                Some(eval_op(operator, left_value, right_value))
+            }
+        }
+    }
+}
+
+impl<Op, Atom, Meta> BeTree<Op, Atom, Meta> {
+    /// lazily filter a stream of items, keeping the ones for which the
+    /// boolean expression evaluates to true.
+    ///
+    /// `eval_atom` receives the item and the atom, `eval_op` and
+    /// `short_circuit` are the same as for `eval`. The expression is
+    /// prepared once, before the first item, instead of at every
+    /// evaluation. Items for which the evaluation gives no value
+    /// (because the expression is empty or incomplete) are dropped.
+    ///
+    /// As items are only evaluated when the next match is requested,
+    /// keeping the first matches stops the evaluations:
+    ///
+    /// ```
+    /// use bet::BeTree;
+    ///
+    /// // a filter for the multiples of 2 and of 3
+    /// let mut expr = BeTree::new();
+    /// expr.push_atom(2);
+    /// expr.push_operator("and");
+    /// expr.push_atom(3);
+    /// let first_matches: Vec<u32> = expr
+    ///     .filter_stream(
+    ///         1..,
+    ///         |n, d| n % d == 0,
+    ///         |_, a, b| a && b.unwrap_or(true),
+    ///         |_, &a| !a,
+    ///     )
+    ///     .take(3)
+    ///     .collect();
+    /// assert_eq!(first_matches, vec![6, 12, 18]);
+    /// ```
+    pub fn filter_stream<'t, I, C, EvalAtom, EvalOp, ShortCircuit>(
+        &'t self,
+        items: I,
+        mut eval_atom: EvalAtom,
+        eval_op: EvalOp,
+        short_circuit: ShortCircuit,
+    ) -> impl Iterator<Item = C> + 't
+    where
+        I: IntoIterator<Item = C>,
+        I::IntoIter: 't,
+        EvalAtom: FnMut(&C, &Atom) -> bool + 't,
+        EvalOp: Fn(&Op, bool, Option<bool>) -> bool + 't,
+        ShortCircuit: Fn(&Op, &bool) -> bool + 't,
+    {
+        let prepared = PreparedFilter::new(self);
+        items.into_iter().filter(move |item| {
+            let Some(root) = prepared.root else {
+                return false;
+            };
+            let mut eval_item_atom = |atom: &Atom| eval_atom(item, atom);
+            prepared.eval_step(root, &mut eval_item_atom, &eval_op, &short_circuit) == Some(true)
+        })
+    }
+}
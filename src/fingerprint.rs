@@ -0,0 +1,123 @@
+use {crate::*, core::fmt};
+
+/// The state of the computation of a fingerprint, given by
+/// `BeTree::fingerprint` to the functions hashing operators and atoms.
+///
+/// It's a 64-bit FNV-1a hash, whose constants are part of the format:
+/// the offset basis is `0xcbf29ce484222325` and the prime is
+/// `0x100000001b3`. Numbers are written in little endian order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fingerprinter {
+    hash: u64,
+}
+
+impl Default for Fingerprinter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Fingerprinter {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    pub fn new() -> Self {
+        Self {
+            hash: Self::OFFSET_BASIS,
+        }
+    }
+    pub fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.hash ^= u64::from(byte);
+            self.hash = self.hash.wrapping_mul(Self::PRIME);
+        }
+    }
+    pub fn write_u8(&mut self, n: u8) {
+        self.write(&[n]);
+    }
+    pub fn write_u32(&mut self, n: u32) {
+        self.write(&n.to_le_bytes());
+    }
+    pub fn write_u64(&mut self, n: u64) {
+        self.write(&n.to_le_bytes());
+    }
+    pub fn write_i64(&mut self, n: i64) {
+        self.write(&n.to_le_bytes());
+    }
+    pub fn write_bool(&mut self, b: bool) {
+        self.write_u8(b.into());
+    }
+    /// write the length of the string, as an u64, then its UTF-8 bytes,
+    /// so that consecutive strings can't be confused
+    pub fn write_str(&mut self, s: &str) {
+        self.write_u64(s.len() as u64);
+        self.write(s.as_bytes());
+    }
+    /// the fingerprint of what has been written
+    pub fn finish(&self) -> u64 {
+        self.hash
+    }
+}
+
+/// Stable fingerprint
+impl<Op, Atom> BeTree<Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone,
+{
+    fn fingerprint_child<HashOp, HashAtom>(
+        &self,
+        child: Child,
+        hash_op: &HashOp,
+        hash_atom: &HashAtom,
+        fp: &mut Fingerprinter,
+    ) where
+        HashOp: Fn(&Op, &mut Fingerprinter),
+        HashAtom: Fn(&Atom, &mut Fingerprinter),
+    {
+        match self.skip_groups(child) {
+            Child::None => fp.write_u8(0),
+            Child::Atom(atom_idx) => {
+                fp.write_u8(1);
+                hash_atom(&self.atoms[atom_idx], fp);
+            }
+            Child::Node(node_idx) => {
+                let node = &self.nodes[node_idx];
+                match &node.operator {
+                    Some(op) => {
+                        fp.write_u8(if node.unary { 2 } else { 3 });
+                        hash_op(op, fp);
+                    }
+                    // a group with a right child, which is pathological
+                    None => fp.write_u8(4),
+                }
+                self.fingerprint_child(node.left(), hash_op, hash_atom, fp);
+                self.fingerprint_child(node.right(), hash_op, hash_atom, fp);
+            }
+        }
+    }
+
+    /// compute a fingerprint of the expression which, unlike `Hash`,
+    /// doesn't depend on the process, the platform, or the version of
+    /// the crate, so that it can for example be a key of a persistent
+    /// cache.
+    ///
+    /// The fingerprint covers the same structure as `structural_eq`:
+    /// node ids and groups without operator don't change it. The
+    /// reachable tree is written in pre-order: `0` for a missing operand,
+    /// `1` then what `hash_atom` writes for an atom, `2` for a unary and
+    /// `3` for a binary operation followed by what `hash_op` writes and
+    /// the left then right operands. This algorithm won't change.
+    ///
+    /// `hash_op` and `hash_atom` must themselves be stable, for example
+    /// by writing explicit discriminants rather than relying on `Hash`.
+    pub fn fingerprint<HashOp, HashAtom>(&self, hash_op: HashOp, hash_atom: HashAtom) -> u64
+    where
+        HashOp: Fn(&Op, &mut Fingerprinter),
+        HashAtom: Fn(&Atom, &mut Fingerprinter),
+    {
+        let mut fp = Fingerprinter::new();
+        self.fingerprint_child(Child::Node(self.head), &hash_op, &hash_atom, &mut fp);
+        fp.finish()
+    }
+}
@@ -0,0 +1,73 @@
+use {crate::*, core::fmt};
+
+/// Flattening of expressions whose atoms are expressions
+impl<Op, Atom> BeTree<Op, BeTree<Op, Atom>>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone,
+{
+    /// build a single expression by inserting every inner expression
+    /// in place of its atom, as if it had been parenthesized there.
+    ///
+    /// Evaluating the flattened expression gives the same result than
+    /// evaluating the outer expression with atoms evaluated as inner
+    /// expressions.
+    ///
+    /// An empty inner expression is handled as a removed atom (see
+    /// `remove_atom`): the operator joining it to its sibling is removed
+    /// too, and unary operators and groups left empty are removed.
+    pub fn flatten(self) -> BeTree<Op, Atom> {
+        // the links to reachable atoms, with the number of uses of each atom
+        let mut links = Vec::new();
+        let mut uses = vec![0; self.atoms.len()];
+        let mut stack = vec![self.head];
+        while let Some(node_idx) = stack.pop() {
+            let node = &self.nodes[node_idx];
+            for side in [Side::Right, Side::Left] {
+                match node.child(side) {
+                    Child::None => {}
+                    Child::Node(child_idx) => stack.push(child_idx),
+                    Child::Atom(atom_idx) => {
+                        links.push((node_idx, side, atom_idx));
+                        uses[atom_idx] += 1;
+                    }
+                }
+            }
+        }
+        let mut inner_trees = self.atoms;
+        let mut tree = BeTree {
+            atoms: Atoms::new(),
+            nodes: self.nodes,
+            head: self.head,
+            tail: self.tail,
+            last_pushed: self.last_pushed,
+            op_count: self.op_count,
+            openness: self.openness,
+            epoch: self.epoch.wrapping_add(1),
+        };
+        let mut empty_links = Vec::new();
+        for (node_idx, side, atom_idx) in links {
+            tree.nodes[node_idx].set_child(side, Child::None);
+            uses[atom_idx] -= 1;
+            let inner = if uses[atom_idx] == 0 {
+                core::mem::take(&mut inner_trees[atom_idx])
+            } else {
+                inner_trees[atom_idx].clone()
+            };
+            if inner.is_empty() {
+                empty_links.push((node_idx, side));
+            } else {
+                let inner_head = tree.graft(inner, Some(node_idx));
+                tree.nodes[node_idx].set_child(side, Child::Node(inner_head));
+            }
+        }
+        for (node_idx, side) in empty_links {
+            // a previous removal may have removed the node
+            if tree.is_reachable(node_idx) {
+                tree.remove_child(node_idx, side);
+            }
+        }
+        tree.fix_tail();
+        tree
+    }
+}
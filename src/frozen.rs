@@ -0,0 +1,78 @@
+use {
+    crate::*,
+    alloc::sync::Arc,
+    core::{fmt, ops::Deref},
+};
+
+/// An immutable expression which can be cheaply cloned and shared
+/// between threads, as the parsed filter of a pool of workers.
+///
+/// It gives access to all the methods of `BeTree` which don't modify
+/// the tree (the evaluation functions among them) but to none of the
+/// ones which would.
+///
+/// Get it with `BeTree::freeze`, and back to a mutable tree with `thaw`.
+pub struct FrozenBeTree<Op, Atom, Meta = ()> {
+    tree: Arc<BeTree<Op, Atom, Meta>>,
+}
+
+impl<Op, Atom, Meta> BeTree<Op, Atom, Meta> {
+    /// make the tree immutable, so that it can be shared
+    pub fn freeze(self) -> FrozenBeTree<Op, Atom, Meta> {
+        FrozenBeTree {
+            tree: Arc::new(self),
+        }
+    }
+}
+
+impl<Op, Atom, Meta> FrozenBeTree<Op, Atom, Meta> {
+    /// the number of handles to this tree, this one included
+    pub fn handle_count(&self) -> usize {
+        Arc::strong_count(&self.tree)
+    }
+}
+
+impl<Op, Atom, Meta> FrozenBeTree<Op, Atom, Meta>
+where
+    Op: Clone,
+    Atom: Clone,
+    Meta: Clone,
+{
+    /// get back a mutable tree, which is this one if this handle is
+    /// the only one, and a clone if the tree is still shared
+    pub fn thaw(self) -> BeTree<Op, Atom, Meta> {
+        Arc::try_unwrap(self.tree).unwrap_or_else(|tree| BeTree::clone(&tree))
+    }
+}
+
+impl<Op, Atom, Meta> Clone for FrozenBeTree<Op, Atom, Meta> {
+    /// make another handle to the same tree, without cloning it
+    fn clone(&self) -> Self {
+        Self {
+            tree: Arc::clone(&self.tree),
+        }
+    }
+}
+
+impl<Op, Atom, Meta> Deref for FrozenBeTree<Op, Atom, Meta> {
+    type Target = BeTree<Op, Atom, Meta>;
+    fn deref(&self) -> &Self::Target {
+        &self.tree
+    }
+}
+
+impl<Op, Atom, Meta> fmt::Debug for FrozenBeTree<Op, Atom, Meta>
+where
+    Op: fmt::Debug,
+    Atom: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&*self.tree, f)
+    }
+}
+
+impl<Op, Atom, Meta> From<BeTree<Op, Atom, Meta>> for FrozenBeTree<Op, Atom, Meta> {
+    fn from(tree: BeTree<Op, Atom, Meta>) -> Self {
+        tree.freeze()
+    }
+}
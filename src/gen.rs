@@ -0,0 +1,218 @@
+//! A seeded generator of random expressions of controlled shape, for
+//! example to build reproducible benchmark workloads.
+
+use {
+    crate::*,
+    rand::{seq::index, Rng},
+    std::fmt,
+};
+
+/// Whether an operator is put before one operand or between two
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OpKind {
+    Unary,
+    Binary,
+}
+
+/// The shape of the expressions built by `generate`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GenConfig {
+    /// The exact number of atoms of the expression
+    pub atoms: usize,
+    /// The mean number of operands of a chain of binary operations
+    /// (an operation whose operands are atoms or groups), at least 2
+    pub mean_chain_length: f64,
+    /// The maximal number of nested groups (parenthesis)
+    pub max_nesting: usize,
+}
+
+impl Default for GenConfig {
+    fn default() -> Self {
+        Self {
+            atoms: 100,
+            mean_chain_length: 4.0,
+            max_nesting: 8,
+        }
+    }
+}
+
+/// The generation state
+struct Generator<'o, R, Op, Atom, MA> {
+    rng: R,
+    config: GenConfig,
+    make_atom: MA,
+    binary_ops: Vec<(&'o Op, f64)>,
+    unary_ops: Vec<(&'o Op, f64)>,
+    unary_probability: f64,
+    tree: BeTree<Op, Atom>,
+}
+
+impl<R, Op, Atom, MA> Generator<'_, R, Op, Atom, MA>
+where
+    R: Rng,
+    Op: fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone,
+    MA: FnMut(usize) -> Atom,
+{
+    fn choose_op(&mut self, ops: &[(&Op, f64)]) -> Op {
+        let total: f64 = ops.iter().map(|(_, weight)| weight).sum();
+        let mut target = self.rng.gen::<f64>() * total;
+        for (op, weight) in ops {
+            if target < *weight {
+                return (*op).clone();
+            }
+            target -= weight;
+        }
+        ops[ops.len() - 1].0.clone()
+    }
+    fn chain_length(&mut self) -> usize {
+        // 2 plus a geometric distribution, so that the mean is the
+        // configured one
+        let extra_mean = (self.config.mean_chain_length - 2.0).max(0.0);
+        let continue_probability = extra_mean / (extra_mean + 1.0);
+        let mut len = 2;
+        while self.rng.gen::<f64>() < continue_probability {
+            len += 1;
+        }
+        len
+    }
+    fn push_atom(&mut self) {
+        if !self.unary_ops.is_empty() && self.rng.gen::<f64>() < self.unary_probability {
+            let unary_ops = std::mem::take(&mut self.unary_ops);
+            let op = self.choose_op(&unary_ops);
+            self.unary_ops = unary_ops;
+            self.tree.push_operator(op);
+        }
+        let atom = (self.make_atom)(self.tree.atoms.len());
+        self.tree.push_atom(atom);
+    }
+    /// push a chain of operations with the given number of atoms
+    fn push_chain(&mut self, atoms: usize, nesting: usize) {
+        let binary_ops = std::mem::take(&mut self.binary_ops);
+        let op = self.choose_op(&binary_ops);
+        self.binary_ops = binary_ops;
+        let len = self.chain_length();
+        // Some operands are groups, so that the chains of the whole
+        // expression have the configured mean length. There are
+        // (atoms - 1) / (mean - 1) chains in an expression of this size.
+        let mean = self.config.mean_chain_length.max(2.0);
+        let chains = ((atoms - 1) as f64 / (mean - 1.0)).round() as usize;
+        let group_count = if len >= atoms || nesting >= self.config.max_nesting {
+            0
+        } else {
+            len.min(chains.saturating_sub(1)).min(atoms - len)
+        };
+        if group_count == 0 {
+            for i in 0..atoms {
+                if i > 0 {
+                    self.tree.push_operator(op.clone());
+                }
+                self.push_atom();
+            }
+            return;
+        }
+        let group_atoms = atoms - (len - group_count);
+        // sizes of groups, at least 2, given by random cuts
+        let mut sizes = vec![2; group_count];
+        let extra = group_atoms - 2 * group_count;
+        let mut cuts =
+            index::sample(&mut self.rng, extra + group_count - 1, group_count - 1).into_vec();
+        cuts.sort_unstable();
+        cuts.push(extra + group_count - 1);
+        let mut start = 0;
+        for (size, cut) in sizes.iter_mut().zip(cuts) {
+            *size += cut - start;
+            start = cut + 1;
+        }
+        let mut group_positions = index::sample(&mut self.rng, len, group_count).into_vec();
+        group_positions.sort_unstable();
+        let mut sizes = sizes.into_iter();
+        let mut group_positions = group_positions.into_iter().peekable();
+        for i in 0..len {
+            if i > 0 {
+                self.tree.push_operator(op.clone());
+            }
+            if group_positions.peek() == Some(&i) {
+                group_positions.next();
+                let size = sizes.next().unwrap_or(2);
+                self.tree.open_par();
+                self.push_chain(size, nesting + 1);
+                self.tree.close_par();
+            } else {
+                self.push_atom();
+            }
+        }
+    }
+}
+
+/// generate a random expression with the given shape.
+///
+/// `ops` lists the operators with their kind and their weight: binary
+/// operators are chosen, for each chain, according to their weights,
+/// and unary operators are put before atoms so that their proportion
+/// among all operators follows their weights.
+///
+/// `make_atom` is given the index of the atom to build.
+///
+/// The expression is complete and has exactly `config.atoms` atoms. The
+/// length of chains, whose operands are atoms or groups, follows the
+/// configured mean but may be longer in the deepest allowed groups, which
+/// contain only atoms. The same random generator state always gives the
+/// same expression.
+///
+/// # Panics
+///
+/// Panics if there's more than one atom but no binary operator with
+/// a positive weight.
+pub fn generate<R, Op, Atom, MA>(
+    rng: R,
+    config: GenConfig,
+    make_atom: MA,
+    ops: &[(Op, OpKind, f64)],
+) -> BeTree<Op, Atom>
+where
+    R: Rng,
+    Op: fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone,
+    MA: FnMut(usize) -> Atom,
+{
+    let weighted = |kind| -> Vec<(&Op, f64)> {
+        ops.iter()
+            .filter(|(_, k, weight)| *k == kind && *weight > 0.0)
+            .map(|(op, _, weight)| (op, *weight))
+            .collect()
+    };
+    let binary_ops = weighted(OpKind::Binary);
+    let unary_ops = weighted(OpKind::Unary);
+    let binary_weight: f64 = binary_ops.iter().map(|(_, w)| w).sum();
+    let unary_weight: f64 = unary_ops.iter().map(|(_, w)| w).sum();
+    let atoms = config.atoms;
+    // a complete expression has one binary operator less than atoms
+    let unary_probability = if binary_weight > 0.0 && atoms > 1 {
+        (unary_weight / binary_weight * (atoms - 1) as f64 / atoms as f64).min(1.0)
+    } else if unary_weight > 0.0 {
+        0.5
+    } else {
+        0.0
+    };
+    assert!(
+        atoms <= 1 || !binary_ops.is_empty(),
+        "no binary operator to build an expression of {} atoms",
+        atoms,
+    );
+    let mut generator = Generator {
+        rng,
+        config,
+        make_atom,
+        binary_ops,
+        unary_ops,
+        unary_probability,
+        tree: BeTree::new(),
+    };
+    match atoms {
+        0 => {}
+        1 => generator.push_atom(),
+        _ => generator.push_chain(atoms, 0),
+    }
+    generator.tree
+}
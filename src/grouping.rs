@@ -0,0 +1,135 @@
+use crate::*;
+
+/// The operation joining an atom to the rest of the expression
+/// at one level of its ancestors, in an `AtomGrouping`
+#[derive(Debug, PartialEq)]
+pub struct AtomJoin<'t, Op> {
+    /// The node of the operation
+    pub node: NodeId,
+    pub operator: &'t Op,
+    pub unary: bool,
+    /// The side of the operation holding the atom
+    pub side: Side,
+    /// The other operand of a binary operation, holding the
+    /// atoms joined to this one by the operator
+    pub sibling: Child,
+}
+
+/// The position of one occurrence of an atom, in a `GroupingReport`
+#[derive(Debug, PartialEq)]
+pub struct AtomGrouping<'t, Op> {
+    ancestors: Vec<NodeId>,
+    group: Option<NodeId>,
+    joins: Vec<AtomJoin<'t, Op>>,
+}
+
+impl<'t, Op> AtomGrouping<'t, Op> {
+    /// the ids of the nodes enclosing the atom, from the head
+    /// to the node holding the atom
+    pub fn ancestors(&self) -> &[NodeId] {
+        &self.ancestors
+    }
+    /// the nearest group (node without operator) enclosing the atom,
+    /// the head excepted.
+    ///
+    /// Parenthesis around a binary operation don't leave a group, they
+    /// become the node of this operation: in `(A | B) & C`, the nearest
+    /// group of `A` is `None` while in `!(A) | B` it's the group under
+    /// the `!`.
+    pub fn group(&self) -> Option<NodeId> {
+        self.group
+    }
+    /// the operations joining the atom to the rest of the expression,
+    /// from the nearest one to the one of the head, groups being skipped
+    pub fn joins(&self) -> &[AtomJoin<'t, Op>] {
+        &self.joins
+    }
+}
+
+/// The positions of the atoms reachable from the head of a tree,
+/// as returned by `BeTree::grouping`
+#[derive(Debug)]
+pub struct GroupingReport<'t, Op> {
+    atoms: Vec<Vec<AtomGrouping<'t, Op>>>,
+}
+
+impl<'t, Op> GroupingReport<'t, Op> {
+    /// the position of the atom, or of its first occurrence in tree
+    /// order when it's linked several times (see `dedup_atoms`), or
+    /// None if the atom isn't reachable
+    pub fn atom(&self, atom_id: AtomId) -> Option<&AtomGrouping<'t, Op>> {
+        self.occurrences(atom_id).first()
+    }
+    /// the positions of all the occurrences of the atom, in tree order
+    pub fn occurrences(&self, atom_id: AtomId) -> &[AtomGrouping<'t, Op>] {
+        self.atoms
+            .get(atom_id.index())
+            .map_or(&[], |occurrences| occurrences.as_slice())
+    }
+}
+
+impl<Op, Atom, Meta> BeTree<Op, Atom, Meta> {
+    /// make the grouping of an atom from its path, the list of the
+    /// nodes enclosing it with the side leading to the atom in each
+    fn atom_grouping(&self, path: &[(NodeId, Side)]) -> AtomGrouping<'_, Op> {
+        let ancestors: Vec<NodeId> = path.iter().map(|&(node_idx, _)| node_idx).collect();
+        let group = ancestors
+            .iter()
+            .rev()
+            .find(|&&node_idx| node_idx != self.head && self.nodes[node_idx].operator.is_none())
+            .copied();
+        let joins = path
+            .iter()
+            .rev()
+            .filter_map(|&(node_idx, side)| {
+                let node = &self.nodes[node_idx];
+                node.operator.as_ref().map(|operator| AtomJoin {
+                    node: node_idx,
+                    operator,
+                    unary: node.unary,
+                    side,
+                    sibling: node.child(side.other()),
+                })
+            })
+            .collect();
+        AtomGrouping {
+            ancestors,
+            group,
+            joins,
+        }
+    }
+
+    /// describe the position of every atom reachable from the head:
+    /// the nodes enclosing it, its nearest enclosing group, and the
+    /// operations joining it to the other atoms at each level.
+    ///
+    /// This is useful to highlight the parts of an expression
+    /// related to an atom, for example the one which matched.
+    pub fn grouping(&self) -> GroupingReport<'_, Op> {
+        let mut atoms: Vec<Vec<AtomGrouping<'_, Op>>> = Vec::new();
+        atoms.resize_with(self.atoms.len(), Vec::new);
+        let mut path: Vec<(NodeId, Side)> = Vec::new();
+        let mut stack = vec![(Child::Node(self.head), 0, Side::Left)];
+        while let Some((child, depth, side)) = stack.pop() {
+            path.truncate(depth);
+            if let Some(last) = path.last_mut() {
+                last.1 = side;
+            }
+            match child {
+                Child::None => {}
+                Child::Atom(atom_idx) => {
+                    if let Some(occurrences) = atoms.get_mut(atom_idx.index()) {
+                        occurrences.push(self.atom_grouping(&path));
+                    }
+                }
+                Child::Node(node_idx) => {
+                    path.push((node_idx, Side::Left));
+                    let node = &self.nodes[node_idx];
+                    stack.push((node.right(), depth + 1, Side::Right));
+                    stack.push((node.left(), depth + 1, Side::Left));
+                }
+            }
+        }
+        GroupingReport { atoms }
+    }
+}
@@ -0,0 +1,123 @@
+use {
+    crate::*,
+    std::{collections::HashMap, fmt},
+};
+
+/// An atom of a template, which is either already known or a named
+/// hole to fill with `BeTree::bind`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Hole<Atom> {
+    /// An atom which doesn't need binding
+    Filled(Atom),
+    /// A placeholder, replaced with the value bound to this name.
+    /// All the holes with the same name receive the same value.
+    Named(String),
+}
+
+/// Error returned by `BeTree::bind` and `BeTree::bind_strict`
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct BindError {
+    /// The names of the holes without value, sorted
+    pub unbound: Vec<String>,
+    /// The names of the values which don't match any hole, sorted
+    pub unused: Vec<String>,
+}
+
+impl fmt::Display for BindError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.unbound.is_empty() {
+            write!(f, "unbound holes: {}", self.unbound.join(", "))?;
+            if !self.unused.is_empty() {
+                write!(f, "; ")?;
+            }
+        }
+        if !self.unused.is_empty() {
+            write!(f, "unused bindings: {}", self.unused.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for BindError {}
+
+/// Binding of templates
+impl<Op, Atom, Meta> BeTree<Op, Hole<Atom>, Meta> {
+    /// return the distinct names of the holes, sorted
+    pub fn hole_names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self
+            .iter_atoms()
+            .filter_map(|atom| match atom {
+                Hole::Named(name) => Some(name.as_str()),
+                Hole::Filled(_) => None,
+            })
+            .collect();
+        names.sort_unstable();
+        names.dedup();
+        names
+    }
+
+    /// check the binding, returning the names of the unbound holes and
+    /// of the unused values
+    fn check_binding(&self, values: &HashMap<String, Atom>) -> BindError {
+        let names = self.hole_names();
+        let unbound = names
+            .iter()
+            .filter(|&&name| !values.contains_key(name))
+            .map(|&name| name.to_string())
+            .collect();
+        let mut unused: Vec<String> = values
+            .keys()
+            .filter(|key| names.binary_search(&key.as_str()).is_err())
+            .cloned()
+            .collect();
+        unused.sort_unstable();
+        BindError { unbound, unused }
+    }
+
+    /// fill the holes with the given values, cloned for every hole
+    /// with their name.
+    ///
+    /// The structure of the tree, including node and atom ids and the
+    /// node metadata, is preserved.
+    ///
+    /// Return an error listing all the unbound names (and the unused
+    /// ones, which aren't an error here) when a hole has no value.
+    pub fn bind(self, values: &HashMap<String, Atom>) -> Result<BeTree<Op, Atom, Meta>, BindError>
+    where
+        Atom: Clone,
+    {
+        let check = self.check_binding(values);
+        if !check.unbound.is_empty() {
+            return Err(check);
+        }
+        Ok(self.fill(values))
+    }
+
+    /// fill the holes with the given values, like `bind`, but also
+    /// fail when some values don't match any hole
+    pub fn bind_strict(
+        self,
+        values: &HashMap<String, Atom>,
+    ) -> Result<BeTree<Op, Atom, Meta>, BindError>
+    where
+        Atom: Clone,
+    {
+        let check = self.check_binding(values);
+        if !check.unbound.is_empty() || !check.unused.is_empty() {
+            return Err(check);
+        }
+        Ok(self.fill(values))
+    }
+
+    /// fill the holes, which must all be bound
+    fn fill(self, values: &HashMap<String, Atom>) -> BeTree<Op, Atom, Meta>
+    where
+        Atom: Clone,
+    {
+        self.into_map_atoms(|hole| match hole {
+            Hole::Filled(atom) => atom,
+            Hole::Named(name) => values[&name].clone(),
+        })
+    }
+}
@@ -0,0 +1,143 @@
+use {
+    crate::*,
+    core::fmt::{self, Write},
+};
+
+/// The class names of the elements generated by `BeTree::to_html`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HtmlOptions {
+    /// class of the spans containing an atom
+    pub atom_class: String,
+    /// class of the spans containing an operator
+    pub operator_class: String,
+    /// class of the spans containing a parenthesis
+    pub parenthesis_class: String,
+    /// class of the spans containing a whole group, parenthesis included
+    pub group_class: String,
+}
+
+impl Default for HtmlOptions {
+    fn default() -> Self {
+        Self {
+            atom_class: "bet-atom".to_string(),
+            operator_class: "bet-op".to_string(),
+            parenthesis_class: "bet-paren".to_string(),
+            group_class: "bet-group".to_string(),
+        }
+    }
+}
+
+/// escape the text so that it can be put in an element or in
+/// an attribute value
+fn escape_html(text: &str, html: &mut String) {
+    for c in text.chars() {
+        match c {
+            '<' => html.push_str("&lt;"),
+            '>' => html.push_str("&gt;"),
+            '&' => html.push_str("&amp;"),
+            '"' => html.push_str("&quot;"),
+            '\'' => html.push_str("&#39;"),
+            c => html.push(c),
+        }
+    }
+}
+
+/// write a span with the given class and escaped text
+fn push_span(class: &str, text: &str, html: &mut String) {
+    html.push_str("<span class=\"");
+    escape_html(class, html);
+    html.push_str("\">");
+    escape_html(text, html);
+    html.push_str("</span>");
+}
+
+/// HTML rendering
+impl<Op, Atom> BeTree<Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone,
+{
+    fn push_child_html<RA, RO>(
+        &self,
+        child: Child,
+        needs_group: bool,
+        render_atom: &RA,
+        render_op: &RO,
+        options: &HtmlOptions,
+        html: &mut String,
+    ) where
+        RA: Fn(&Atom) -> String,
+        RO: Fn(&Op) -> String,
+    {
+        let node_idx = match child {
+            Child::None => return,
+            Child::Atom(atom_idx) => {
+                html.push_str("<span class=\"");
+                escape_html(&options.atom_class, html);
+                let _ = write!(html, "\" data-atom-id=\"{}\">", atom_idx);
+                escape_html(&render_atom(&self.atoms[atom_idx]), html);
+                html.push_str("</span>");
+                return;
+            }
+            Child::Node(node_idx) => node_idx,
+        };
+        let node = &self.nodes[node_idx];
+        let in_group = match &node.operator {
+            None => true,
+            Some(_) => needs_group && !node.unary,
+        };
+        if in_group {
+            html.push_str("<span class=\"");
+            escape_html(&options.group_class, html);
+            html.push_str("\">");
+            push_span(&options.parenthesis_class, "(", html);
+        }
+        match &node.operator {
+            None => {
+                self.push_child_html(node.left(), false, render_atom, render_op, options, html);
+            }
+            Some(op) if node.unary => {
+                push_span(&options.operator_class, &render_op(op), html);
+                self.push_child_html(node.left(), true, render_atom, render_op, options, html);
+            }
+            Some(op) => {
+                self.push_child_html(node.left(), false, render_atom, render_op, options, html);
+                html.push(' ');
+                push_span(&options.operator_class, &render_op(op), html);
+                if node.right().is_some() {
+                    html.push(' ');
+                    self.push_child_html(node.right(), true, render_atom, render_op, options, html);
+                }
+            }
+        }
+        if in_group {
+            push_span(&options.parenthesis_class, ")", html);
+            html.push_str("</span>");
+        }
+    }
+
+    /// render the expression as HTML, with the atoms, operators and
+    /// parenthesis wrapped in spans whose classes are given by the options.
+    ///
+    /// The rendered texts are escaped. The spans of atoms have a
+    /// `data-atom-id` attribute. A group, with its parenthesis, is wrapped
+    /// in a span, so that a whole subexpression can be highlighted.
+    ///
+    /// Parenthesis are the ones of the expression, with the ones required
+    /// by its structure, as in `to_tokens`.
+    pub fn to_html<RA, RO>(&self, render_atom: RA, render_op: RO, options: HtmlOptions) -> String
+    where
+        RA: Fn(&Atom) -> String,
+        RO: Fn(&Op) -> String,
+    {
+        let mut html = String::new();
+        let head = &self.nodes[self.head];
+        let root = if head.operator.is_none() {
+            head.left()
+        } else {
+            Child::Node(self.head)
+        };
+        self.push_child_html(root, false, &render_atom, &render_op, &options, &mut html);
+        html
+    }
+}
@@ -0,0 +1,155 @@
+use {
+    crate::*,
+    core::{
+        convert::TryFrom,
+        fmt,
+        ops::{Index, IndexMut},
+    },
+};
+
+/// The greatest index of a node or atom.
+///
+/// Ids are 32 bits but nodes pack their links in 32 bits words whose
+/// high bit tells atoms from nodes, so a tree holds at most 2^31 - 1
+/// nodes and as many atoms.
+pub const MAX_ID: u32 = 0x7FFF_FFFE;
+
+/// Error returned when an index doesn't fit in an id, that is when
+/// a tree would have more than `MAX_ID + 1` nodes or atoms
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct IdOverflow;
+
+impl fmt::Display for IdOverflow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "too many nodes or atoms, the greatest id is {}", MAX_ID)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for IdOverflow {}
+
+macro_rules! define_id {
+    ($(#[$attr:meta])* $name:ident) => {
+        $(#[$attr])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+        #[cfg_attr(feature = "serde", serde(try_from = "u32", into = "u32"))]
+        pub struct $name(u32);
+
+        impl $name {
+            /// build the id from its index in the arena
+            ///
+            /// Panics if the index is greater than `MAX_ID`.
+            pub const fn new(index: u32) -> Self {
+                assert!(index <= MAX_ID, "index too big for an id");
+                Self(index)
+            }
+            /// build the id from an index known to be at most `MAX_ID`
+            pub(crate) const fn new_unchecked(index: u32) -> Self {
+                Self(index)
+            }
+            /// the index in the arena, usable to index a slice
+            pub const fn index(self) -> usize {
+                self.0 as usize
+            }
+            /// build the id from an index in a slice, panicking if
+            /// it doesn't fit in 32 bits
+            pub(crate) fn from_index(index: usize) -> Self {
+                match Self::try_from(index) {
+                    Ok(id) => id,
+                    Err(_) => panic!("index {} is too big for an id", index),
+                }
+            }
+        }
+
+        impl TryFrom<usize> for $name {
+            type Error = IdOverflow;
+            fn try_from(index: usize) -> Result<Self, IdOverflow> {
+                match u32::try_from(index) {
+                    Ok(index) => Self::try_from(index),
+                    Err(_) => Err(IdOverflow),
+                }
+            }
+        }
+
+        impl TryFrom<u32> for $name {
+            type Error = IdOverflow;
+            fn try_from(index: u32) -> Result<Self, IdOverflow> {
+                if index <= MAX_ID {
+                    Ok(Self(index))
+                } else {
+                    Err(IdOverflow)
+                }
+            }
+        }
+
+        impl From<$name> for u32 {
+            fn from(id: $name) -> Self {
+                id.0
+            }
+        }
+
+        impl From<$name> for usize {
+            fn from(id: $name) -> Self {
+                id.index()
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                self.0.fmt(f)
+            }
+        }
+    };
+}
+
+define_id!(
+    /// The id of a node, which is its index in the arena of the nodes of
+    /// the tree.
+    ///
+    /// It's 32 bits wide whatever the target, which keeps nodes small.
+    ///
+    /// Edits keep the removed nodes in the arena, so an id stays in
+    /// bounds, but it may refer to an unreachable node, or to another
+    /// node after `compact`, `clear` or `take`. Compare `BeTree::edits_epoch`
+    /// to know whether the structure changed since the id was obtained.
+    NodeId
+);
+
+define_id!(
+    /// The id of an atom, which is its index in the arena of the atoms of
+    /// the tree.
+    ///
+    /// It's 32 bits wide whatever the target, which keeps nodes small.
+    ///
+    /// Edits keep the removed atoms in the arena, so an id stays in
+    /// bounds, but it may refer to an unreachable atom, or to another
+    /// atom after `compact`, `clear` or `take`. Compare `BeTree::edits_epoch`
+    /// to know whether the structure changed since the id was obtained.
+    AtomId
+);
+
+macro_rules! impl_index {
+    ($id:ident, $container:ty) => {
+        impl<T> Index<$id> for $container {
+            type Output = T;
+            fn index(&self, id: $id) -> &T {
+                &self[id.index()]
+            }
+        }
+
+        impl<T> IndexMut<$id> for $container {
+            fn index_mut(&mut self, id: $id) -> &mut T {
+                &mut self[id.index()]
+            }
+        }
+    };
+}
+
+// Slices and vecs indexed by ids, which is convenient both for the
+// arenas of the tree and for the side tables of algorithms
+impl_index!(NodeId, [T]);
+impl_index!(NodeId, Vec<T>);
+impl_index!(AtomId, [T]);
+impl_index!(AtomId, Vec<T>);
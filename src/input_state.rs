@@ -0,0 +1,55 @@
+use {crate::*, core::fmt};
+
+/// What can be pushed next in a tree being built, and where the
+/// insertion point is, as given by `BeTree::input_state`.
+///
+/// This is typically used for autocompletion or for hints.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InputState<Op> {
+    /// Whether an atom may be pushed
+    pub accepts_atom: bool,
+    /// Whether a unary operator may be pushed
+    pub accepts_unary_operator: bool,
+    /// Whether a binary operator may be pushed
+    pub accepts_binary_operator: bool,
+    /// Whether a parenthesis may be opened
+    pub accepts_opening_par: bool,
+    /// Whether a parenthesis may be closed
+    pub accepts_closing_par: bool,
+    /// The number of open parenthesis at the insertion point
+    pub depth: usize,
+    /// Whether the last pushed token is an atom, which may still be
+    /// modified (see `mutate_or_create_atom`)
+    pub in_atom: bool,
+    /// The operator waiting for an operand, if any (for example the `&`
+    /// after `a &`, or the `!` after `a & !`)
+    pub pending_operator: Option<Op>,
+}
+
+/// Input state
+impl<Op, Atom> BeTree<Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone,
+{
+    /// tell what can be pushed next and where the insertion point is,
+    /// in one call
+    pub fn input_state(&self) -> InputState<Op> {
+        let tail = &self.nodes[self.tail];
+        let pending_operator = if tail.is_full() {
+            None
+        } else {
+            tail.operator.clone()
+        };
+        InputState {
+            accepts_atom: self.accept_atom(),
+            accepts_unary_operator: self.accept_unary_operator(),
+            accepts_binary_operator: self.accept_binary_operator(),
+            accepts_opening_par: self.accept_opening_par(),
+            accepts_closing_par: self.accept_closing_par(),
+            depth: self.get_openness(),
+            in_atom: self.last_pushed == TokenType::Atom,
+            pending_operator,
+        }
+    }
+}
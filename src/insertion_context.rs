@@ -0,0 +1,95 @@
+use {crate::*, core::fmt};
+
+/// Where the next pushed operand goes in a tree being built, as given
+/// by `BeTree::insertion_context`.
+///
+/// This is typically used for contextual suggestions, for example to
+/// tell the user they're completing the right operand of an `&` whose
+/// left operand is `name~foo`.
+#[derive(Debug)]
+pub struct InsertionContext<'t, Op, Atom> {
+    tree: &'t BeTree<Op, Atom>,
+    node: NodeId,
+    side: Option<Side>,
+    ancestors: Vec<NodeId>,
+}
+
+impl<'t, Op, Atom> InsertionContext<'t, Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone,
+{
+    /// the id of the node at the insertion point (the tail)
+    pub fn node(&self) -> NodeId {
+        self.node
+    }
+    /// the operator of the node at the insertion point, with whether
+    /// it's unary, or `None` for a group or an empty tree
+    pub fn operator(&self) -> Option<(&'t Op, bool)> {
+        let node = &self.tree.nodes[self.node];
+        node.operator.as_ref().map(|op| (op, node.unary))
+    }
+    /// the side of the node where the next operand goes, or `None`
+    /// if the node is complete (an operator is expected)
+    pub fn side(&self) -> Option<Side> {
+        self.side
+    }
+    /// the operand already on the other side when the right operand
+    /// of a binary operation is being filled
+    pub fn sibling(&self) -> Option<SubExprRef<'t, Op, Atom>> {
+        match self.side {
+            Some(Side::Right) => self.tree.sub_expr(self.tree.nodes[self.node].left()),
+            _ => None,
+        }
+    }
+    /// the ids of the nodes enclosing the insertion node, from the
+    /// head to its parent
+    pub fn ancestors(&self) -> &[NodeId] {
+        &self.ancestors
+    }
+    /// iterate on the operators enclosing the insertion node, from the
+    /// head to the nearest, with for each operator whether it's unary.
+    ///
+    /// Nodes without operator (i.e. parenthesis) are skipped.
+    pub fn enclosing_operators(&self) -> impl DoubleEndedIterator<Item = (&'t Op, bool)> + '_ {
+        let nodes = &self.tree.nodes;
+        self.ancestors.iter().filter_map(move |&node_id| {
+            let node = &nodes[node_id];
+            node.operator.as_ref().map(|op| (op, node.unary))
+        })
+    }
+}
+
+/// Insertion context
+impl<Op, Atom> BeTree<Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone,
+{
+    /// describe the insertion point of the tree being built: the node
+    /// receiving the next operand, the side it goes to, the operand
+    /// already on the other side, and the enclosing operators
+    pub fn insertion_context(&self) -> InsertionContext<'_, Op, Atom> {
+        let node = &self.nodes[self.tail];
+        let side = if node.left().is_none() {
+            Some(Side::Left)
+        } else if node.is_full() || node.unary || node.operator.is_none() {
+            None
+        } else {
+            Some(Side::Right)
+        };
+        let mut ancestors = Vec::new();
+        let mut current = node.parent();
+        while let Some(node_id) = current {
+            ancestors.push(node_id);
+            current = self.nodes[node_id].parent();
+        }
+        ancestors.reverse();
+        InsertionContext {
+            tree: self,
+            node: self.tail,
+            side,
+            ancestors,
+        }
+    }
+}
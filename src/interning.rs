@@ -0,0 +1,119 @@
+use {
+    crate::*,
+    alloc::{collections::BTreeMap, sync::Arc},
+    core::{convert::TryFrom, fmt},
+};
+
+/// A string atom interned in an `InternedAtoms` pool.
+///
+/// It's a small copyable id which is only meaningful for the pool
+/// which produced it: two symbols of the same pool are equal when
+/// their strings are equal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Symbol(u32);
+
+impl Symbol {
+    /// the index of the string in its pool
+    pub const fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+impl fmt::Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// A pool of strings, shared by trees whose atoms are interned, so
+/// that each distinct string is stored only once whatever the number
+/// of trees and atoms.
+///
+/// ```
+/// use bet::{BeTree, InternedAtoms};
+///
+/// let mut pool = InternedAtoms::new();
+/// let mut filters = Vec::new();
+/// for ext in ["rs", "toml", "rs"] {
+///     let mut expr = BeTree::new();
+///     expr.push_atom(format!("ext={}", ext));
+///     expr.push_operator('|');
+///     expr.push_atom("size>10k".to_string());
+///     filters.push(expr.intern_atoms(&mut pool));
+/// }
+/// assert_eq!(pool.len(), 3);
+/// let matches = filters[2].eval(
+///     |&symbol| pool.resolve(symbol) == "ext=rs",
+///     |_, a, b| a || b.unwrap_or(false),
+///     |_, &a| a,
+/// );
+/// assert_eq!(matches, Some(true));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct InternedAtoms {
+    strings: Vec<Arc<str>>,
+    symbols: BTreeMap<Arc<str>, Symbol>,
+}
+
+impl InternedAtoms {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// the number of distinct strings in the pool
+    pub fn len(&self) -> usize {
+        self.strings.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.strings.is_empty()
+    }
+    /// return the symbol of the string, adding it to the pool if
+    /// it's not already there
+    ///
+    /// Panics if the pool already holds `u32::MAX` strings.
+    pub fn intern(&mut self, s: &str) -> Symbol {
+        if let Some(&symbol) = self.symbols.get(s) {
+            return symbol;
+        }
+        let index = u32::try_from(self.strings.len()).expect("too many interned strings");
+        let symbol = Symbol(index);
+        let s: Arc<str> = Arc::from(s);
+        self.strings.push(Arc::clone(&s));
+        self.symbols.insert(s, symbol);
+        symbol
+    }
+    /// return the symbol of the string if it's in the pool
+    pub fn get(&self, s: &str) -> Option<Symbol> {
+        self.symbols.get(s).copied()
+    }
+    /// return the string of a symbol
+    ///
+    /// Panics if the symbol wasn't produced by this pool.
+    pub fn resolve(&self, symbol: Symbol) -> &str {
+        &self.strings[symbol.index()]
+    }
+}
+
+/// Interning of string atoms
+impl<Op, Atom, Meta> BeTree<Op, Atom, Meta>
+where
+    Atom: AsRef<str>,
+{
+    /// produce a tree whose atoms are the symbols of the atoms
+    /// of this one in the pool, interning them when needed.
+    ///
+    /// Node and atom ids are preserved.
+    pub fn intern_atoms(self, pool: &mut InternedAtoms) -> BeTree<Op, Symbol, Meta> {
+        self.into_map_atoms(|atom| pool.intern(atom.as_ref()))
+    }
+}
+
+/// Resolution of interned atoms
+impl<Op, Meta> BeTree<Op, Symbol, Meta> {
+    /// produce a tree whose atoms are the strings of the symbols
+    /// of this one, which must come from the given pool
+    ///
+    /// Node and atom ids are preserved.
+    pub fn resolve_atoms(self, pool: &InternedAtoms) -> BeTree<Op, String, Meta> {
+        self.into_map_atoms(|symbol| pool.resolve(symbol).to_string())
+    }
+}
@@ -0,0 +1,432 @@
+use {crate::*, alloc::collections::VecDeque, core::fmt};
+
+/// An iterator over the nodes reachable from the head, in pre-order:
+/// parents before children, left before right.
+///
+/// It can also be iterated from the end (in reverse pre-order).
+///
+/// Get it with `BeTree::iter_nodes`.
+pub struct NodeIter<'t, Op> {
+    nodes: &'t [Node<Op>],
+    head: NodeId,
+    front: Vec<NodeId>,
+    back: Vec<(NodeId, bool)>, // nodes, and whether their children were pushed
+    front_count: usize,
+    remaining: Option<usize>, // only computed when iterating from the back
+}
+
+impl<'t, Op> NodeIter<'t, Op>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+{
+    fn child_nodes(node: &Node<Op>) -> impl Iterator<Item = NodeId> {
+        IntoIterator::into_iter([node.left(), node.right()]).filter_map(|child| match child {
+            Child::Node(child_id) => Some(child_id),
+            _ => None,
+        })
+    }
+    /// decrement the number of remaining nodes, if it's known, and
+    /// return whether there was a remaining node
+    fn take_remaining(&mut self) -> bool {
+        match self.remaining.as_mut() {
+            Some(0) => false,
+            Some(remaining) => {
+                *remaining -= 1;
+                true
+            }
+            None => true,
+        }
+    }
+}
+
+impl<'t, Op> Iterator for NodeIter<'t, Op>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+{
+    type Item = (NodeId, &'t Node<Op>);
+    fn next(&mut self) -> Option<Self::Item> {
+        if !self.take_remaining() {
+            return None;
+        }
+        let node_id = self.front.pop()?;
+        let node = &self.nodes[node_id];
+        for child in [node.right(), node.left()] {
+            if let Child::Node(child_id) = child {
+                self.front.push(child_id);
+            }
+        }
+        self.front_count += 1;
+        Some((node_id, node))
+    }
+}
+
+impl<'t, Op> DoubleEndedIterator for NodeIter<'t, Op>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.remaining.is_none() {
+            // we count the nodes so that both ends don't overlap
+            let mut count = 0;
+            let mut stack = vec![self.head];
+            while let Some(node_id) = stack.pop() {
+                count += 1;
+                stack.extend(Self::child_nodes(&self.nodes[node_id]));
+            }
+            self.remaining = Some(count - self.front_count);
+            self.back.push((self.head, false));
+        }
+        if !self.take_remaining() {
+            return None;
+        }
+        loop {
+            let (node_id, expanded) = self.back.pop()?;
+            let node = &self.nodes[node_id];
+            if expanded {
+                return Some((node_id, node));
+            }
+            self.back.push((node_id, true));
+            self.back
+                .extend(Self::child_nodes(node).map(|child_id| (child_id, false)));
+        }
+    }
+}
+
+/// An iterator over the atoms reachable from the head, in tree order
+/// (from left to right) or in reverse, with their ids.
+///
+/// Get it with `BeTree::iter_atoms_in_tree`.
+pub struct TreeAtomIter<'t, Op, Atom> {
+    tree: &'t BeTree<Op, Atom>,
+    stack: Vec<Child>,
+    reverse: bool,
+}
+
+impl<'t, Op, Atom> TreeAtomIter<'t, Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone,
+{
+    /// iterate over the atoms of the child and its descendants
+    pub(crate) fn new(tree: &'t BeTree<Op, Atom>, child: Child, reverse: bool) -> Self {
+        Self {
+            tree,
+            stack: vec![child],
+            reverse,
+        }
+    }
+}
+
+impl<'t, Op, Atom> Iterator for TreeAtomIter<'t, Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone,
+{
+    type Item = (AtomId, &'t Atom);
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.stack.pop()? {
+                Child::None => {}
+                Child::Atom(atom_id) => {
+                    return Some((atom_id, &self.tree.atoms[atom_id]));
+                }
+                Child::Node(node_id) => {
+                    let node = &self.tree.nodes[node_id];
+                    if self.reverse {
+                        self.stack.push(node.left());
+                        self.stack.push(node.right());
+                    } else {
+                        self.stack.push(node.right());
+                        self.stack.push(node.left());
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// An operator of the tree, as given by `BeTree::iter_operators`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OperatorRef<'t, Op> {
+    /// The id of the node of the operator
+    pub node: NodeId,
+    pub operator: &'t Op,
+    /// Whether the operator is unary
+    pub unary: bool,
+    /// Whether the operator has all its operands (one for a unary
+    /// operator, two for a binary one)
+    pub complete: bool,
+}
+
+/// A reference to a node or to an atom of the tree
+#[derive(Debug)]
+pub enum NodeOrAtomRef<'t, Op, Atom> {
+    Node(NodeId, &'t Node<Op>),
+    Atom(AtomId, &'t Atom),
+}
+
+impl<Op, Atom> Clone for NodeOrAtomRef<'_, Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone,
+{
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<Op, Atom> Copy for NodeOrAtomRef<'_, Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone,
+{
+}
+
+/// An iterator over the nodes and atoms reachable from the head, level
+/// by level (breadth-first), with their depth.
+///
+/// Get it with `BeTree::iter_levels`.
+pub struct LevelIter<'t, Op, Atom> {
+    tree: &'t BeTree<Op, Atom>,
+    queue: VecDeque<(usize, Child)>,
+}
+
+impl<'t, Op, Atom> Iterator for LevelIter<'t, Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone,
+{
+    type Item = (usize, NodeOrAtomRef<'t, Op, Atom>);
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (depth, child) = self.queue.pop_front()?;
+            match child {
+                Child::None => {}
+                Child::Atom(atom_id) => {
+                    return Some((
+                        depth,
+                        NodeOrAtomRef::Atom(atom_id, &self.tree.atoms[atom_id]),
+                    ));
+                }
+                Child::Node(node_id) => {
+                    let node = &self.tree.nodes[node_id];
+                    self.queue.push_back((depth + 1, node.left()));
+                    self.queue.push_back((depth + 1, node.right()));
+                    return Some((depth, NodeOrAtomRef::Node(node_id, node)));
+                }
+            }
+        }
+    }
+}
+
+/// An element of the expression, as visited by `BeTree::iter_infix`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Visit<'t, Op, Atom> {
+    /// The opening parenthesis of the group of the given node
+    OpenGroup(NodeId),
+    Atom(AtomId, &'t Atom),
+    /// The operator of the given node, with whether it's unary
+    Operator(NodeId, &'t Op, bool),
+    /// The closing parenthesis of the group of the given node
+    CloseGroup(NodeId),
+}
+
+/// What remains to be visited by an `InfixIter`
+enum InfixStep {
+    Child(Child, bool), // the child, and whether a binary operation needs a group
+    Operator(NodeId),
+    CloseGroup(NodeId),
+}
+
+/// An iterator over the elements of the expression in reading order.
+///
+/// Get it with `BeTree::iter_infix`.
+pub struct InfixIter<'t, Op, Atom> {
+    tree: &'t BeTree<Op, Atom>,
+    stack: Vec<InfixStep>,
+}
+
+impl<'t, Op, Atom> Iterator for InfixIter<'t, Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone,
+{
+    type Item = Visit<'t, Op, Atom>;
+    fn next(&mut self) -> Option<Self::Item> {
+        let tree = self.tree;
+        loop {
+            match self.stack.pop()? {
+                InfixStep::Operator(node_id) => {
+                    let node = &tree.nodes[node_id];
+                    let operator = node.operator.as_ref()?;
+                    return Some(Visit::Operator(node_id, operator, node.unary));
+                }
+                InfixStep::CloseGroup(node_id) => {
+                    return Some(Visit::CloseGroup(node_id));
+                }
+                InfixStep::Child(Child::None, _) => {}
+                InfixStep::Child(Child::Atom(atom_id), _) => {
+                    return Some(Visit::Atom(atom_id, &tree.atoms[atom_id]));
+                }
+                InfixStep::Child(Child::Node(node_id), needs_group) => {
+                    let node = &tree.nodes[node_id];
+                    if node.operator.is_none() {
+                        // parenthesis are only rendered when needed
+                        self.stack.push(InfixStep::Child(node.left(), needs_group));
+                    } else if node.unary {
+                        self.stack.push(InfixStep::Child(node.left(), true));
+                        self.stack.push(InfixStep::Operator(node_id));
+                    } else {
+                        if needs_group {
+                            self.stack.push(InfixStep::CloseGroup(node_id));
+                        }
+                        self.stack.push(InfixStep::Child(node.right(), true));
+                        self.stack.push(InfixStep::Operator(node_id));
+                        self.stack.push(InfixStep::Child(node.left(), false));
+                        if needs_group {
+                            return Some(Visit::OpenGroup(node_id));
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Iteration over the structure of the tree
+impl<Op, Atom> BeTree<Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone,
+{
+    /// iterate over the nodes reachable from the head, in pre-order:
+    /// a node is visited before its children, the left child before
+    /// the right one.
+    ///
+    /// Nodes which aren't reachable anymore after edits aren't visited.
+    /// The iterator only allocates a stack whose size is bounded by
+    /// the depth of the tree.
+    pub fn iter_nodes(&self) -> NodeIter<'_, Op> {
+        NodeIter {
+            nodes: &self.nodes,
+            head: self.head,
+            front: vec![self.head],
+            back: Vec::new(),
+            front_count: 0,
+            remaining: None,
+        }
+    }
+
+    /// iterate over the operators reachable from the head, in pre-order
+    /// (an operator before the operators of its operands, the ones of
+    /// its left operand before the ones of the right operand).
+    ///
+    /// Operators of nodes removed by edits aren't visited.
+    pub fn iter_operators(&self) -> impl Iterator<Item = OperatorRef<'_, Op>> {
+        self.iter_nodes().filter_map(|(node_id, node)| {
+            node.operator.as_ref().map(|operator| OperatorRef {
+                node: node_id,
+                operator,
+                unary: node.unary,
+                complete: node.is_full(),
+            })
+        })
+    }
+
+    /// iterate over the nodes and atoms reachable from the head, level
+    /// by level: first the head, at depth 0, then its children, at
+    /// depth 1, and so on. In a level, elements are visited from left
+    /// to right.
+    ///
+    /// Groups (i.e. parenthesis) are nodes and count in the depth.
+    pub fn iter_levels(&self) -> LevelIter<'_, Op, Atom> {
+        let mut queue = VecDeque::new();
+        queue.push_back((0, Child::Node(self.head)));
+        LevelIter { tree: self, queue }
+    }
+
+    /// the maximal depth of the nodes and atoms of the tree, the head
+    /// being at depth 0.
+    ///
+    /// This is for example useful to check the tree isn't too deep
+    /// for recursive functions, like `eval`.
+    pub fn depth(&self) -> usize {
+        self.iter_levels()
+            .map(|(depth, _)| depth)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// iterate over the atoms reachable from the head, with their ids,
+    /// from left to right, which is the order in which they're evaluated.
+    ///
+    /// `iter_atoms` iterates over the arena of atoms, in the order they
+    /// were stored: it's the tree order after parsing but not after edits
+    /// (for example a grafted tree has its atoms appended to the arena),
+    /// and it includes the atoms which were removed or replaced.
+    /// `iter_atoms_in_tree` only visits the atoms of the expression, in
+    /// their order in the expression.
+    pub fn iter_atoms_in_tree(&self) -> TreeAtomIter<'_, Op, Atom> {
+        TreeAtomIter::new(self, Child::Node(self.head), false)
+    }
+
+    /// iterate over the atoms reachable from the head, from the
+    /// rightmost one to the leftmost one.
+    ///
+    /// This isn't the same as `iter_atoms().rev()`, which iterates over
+    /// the arena of atoms, whose order isn't the tree order anymore after
+    /// some edits, and which contains the atoms which were removed.
+    pub fn iter_atoms_rev_in_tree_order(&self) -> impl Iterator<Item = &Atom> {
+        TreeAtomIter::new(self, Child::Node(self.head), true).map(|(_, atom)| atom)
+    }
+
+    /// iterate over the ids of the atoms reachable from the head, from
+    /// left to right, each atom being visited once even when it's linked
+    /// several times (see `dedup_atoms`)
+    fn iter_reachable_atom_ids(&self) -> impl Iterator<Item = AtomId> + '_ {
+        let mut seen = vec![false; self.atoms.len()];
+        self.iter_atoms_in_tree().filter_map(move |(atom_idx, _)| {
+            let seen = seen.get_mut(atom_idx.index())?;
+            (!core::mem::replace(seen, true)).then_some(atom_idx)
+        })
+    }
+
+    /// iterate over the atoms which are part of the expression, from
+    /// left to right, each atom being given once even when it's used
+    /// several times.
+    ///
+    /// Contrary to `iter_atoms`, the atoms which were removed or
+    /// replaced by edits aren't given.
+    pub fn iter_reachable_atoms(&self) -> impl Iterator<Item = &Atom> {
+        self.iter_reachable_atom_ids()
+            .map(move |atom_idx| &self.atoms[atom_idx])
+    }
+
+    /// take the atoms which are part of the expression, from left
+    /// to right, each atom being given once even when it's used
+    /// several times.
+    ///
+    /// Contrary to `atoms`, the atoms which were removed or
+    /// replaced by edits aren't given.
+    pub fn into_reachable_atoms(self) -> Vec<Atom> {
+        let ids: Vec<AtomId> = self.iter_reachable_atom_ids().collect();
+        let mut atoms: Vec<Option<Atom>> = self.atoms.into_iter().map(Some).collect();
+        ids.into_iter()
+            .filter_map(|atom_idx| atoms[atom_idx.index()].take())
+            .collect()
+    }
+
+    /// iterate over the elements of the expression (atoms, operators
+    /// and parenthesis) in the order a reader would encounter them.
+    ///
+    /// Contrary to `to_tokens`, nothing is cloned, and the visited
+    /// elements come with the ids of their atoms or nodes. Groups are only
+    /// visited where the structure of the tree requires parenthesis, as
+    /// operations are otherwise associated from left to right.
+    pub fn iter_infix(&self) -> InfixIter<'_, Op, Atom> {
+        InfixIter {
+            tree: self,
+            stack: vec![InfixStep::Child(Child::Node(self.head), false)],
+        }
+    }
+}
@@ -0,0 +1,311 @@
+use {
+    crate::*,
+    core::fmt,
+    serde_json::{Map, Value},
+};
+
+/// Error returned by `BeTree::from_jsonlogic`, with the JSON pointer
+/// (e.g. `/and/1/!`) of the offending part of the document
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum JsonLogicError {
+    /// The operation has an operator which `parse_op` didn't recognize
+    UnknownOperator { path: String, operator: String },
+    /// The value is neither an atom nor an operation with a single key
+    NotAnOperation { path: String },
+    /// The number of operands doesn't match the arity of the operator
+    WrongOperandCount { path: String, count: usize },
+}
+
+impl JsonLogicError {
+    /// the JSON pointer of the problem in the document
+    pub fn path(&self) -> &str {
+        match self {
+            Self::UnknownOperator { path, .. }
+            | Self::NotAnOperation { path }
+            | Self::WrongOperandCount { path, .. } => path,
+        }
+    }
+}
+
+impl fmt::Display for JsonLogicError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownOperator { operator, .. } => {
+                write!(f, "unknown operator {:?}", operator)?;
+            }
+            Self::NotAnOperation { .. } => {
+                write!(f, "neither an atom nor an operation")?;
+            }
+            Self::WrongOperandCount { count, .. } => {
+                write!(f, "wrong number of operands ({})", count)?;
+            }
+        }
+        if self.path().is_empty() {
+            write!(f, " at the root")
+        } else {
+            write!(f, " at {}", self.path())
+        }
+    }
+}
+
+impl std::error::Error for JsonLogicError {}
+
+/// escape a key as a JSON pointer token
+fn pointer_token(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+/// JsonLogic export and import
+impl<Op, Atom> BeTree<Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone,
+{
+    /// push in `operands` the JSON of the operands of a chain of binary
+    /// operations with the given operator, built from left to right
+    fn push_jsonlogic_operands<OpName, AtomToJson>(
+        &self,
+        operator: &Op,
+        child: Child,
+        op_name: &OpName,
+        atom_to_json: &AtomToJson,
+        operands: &mut Vec<Value>,
+    ) where
+        OpName: Fn(&Op) -> &str,
+        AtomToJson: Fn(&Atom) -> Value,
+    {
+        let child = self.skip_groups(child);
+        if let Child::Node(node_idx) = child {
+            let node = &self.nodes[node_idx];
+            if !node.unary && node.operator.as_ref() == Some(operator) {
+                self.push_jsonlogic_operands(
+                    operator,
+                    node.left(),
+                    op_name,
+                    atom_to_json,
+                    operands,
+                );
+                operands.push(self.child_to_jsonlogic(node.right(), op_name, atom_to_json));
+                return;
+            }
+        }
+        operands.push(self.child_to_jsonlogic(child, op_name, atom_to_json));
+    }
+
+    fn child_to_jsonlogic<OpName, AtomToJson>(
+        &self,
+        child: Child,
+        op_name: &OpName,
+        atom_to_json: &AtomToJson,
+    ) -> Value
+    where
+        OpName: Fn(&Op) -> &str,
+        AtomToJson: Fn(&Atom) -> Value,
+    {
+        let node_idx = match self.skip_groups(child) {
+            Child::None => return Value::Null,
+            Child::Atom(atom_idx) => return atom_to_json(&self.atoms[atom_idx]),
+            Child::Node(node_idx) => node_idx,
+        };
+        let node = &self.nodes[node_idx];
+        let Some(operator) = &node.operator else {
+            // a group with a right child, which is pathological
+            return self.child_to_jsonlogic(node.left(), op_name, atom_to_json);
+        };
+        let operands = if node.unary {
+            match self.child_to_jsonlogic(node.left(), op_name, atom_to_json) {
+                // an array would be read as the list of operands
+                Value::Array(array) => Value::Array(vec![Value::Array(array)]),
+                operand => operand,
+            }
+        } else {
+            let mut operands = Vec::new();
+            self.push_jsonlogic_operands(
+                operator,
+                Child::Node(node_idx),
+                op_name,
+                atom_to_json,
+                &mut operands,
+            );
+            Value::Array(operands)
+        };
+        let mut object = Map::new();
+        object.insert(op_name(operator).to_string(), operands);
+        Value::Object(object)
+    }
+
+    /// build the JsonLogic document of the expression.
+    ///
+    /// Chains of the same binary operator, like `a & b & c`, are flattened
+    /// into a single operation (`{"and": [a, b, c]}`), while the operand
+    /// of a unary operator isn't put in an array (`{"!": a}`).
+    ///
+    /// An empty expression gives `null`.
+    pub fn to_jsonlogic<OpName, AtomToJson>(
+        &self,
+        op_name: OpName,
+        atom_to_json: AtomToJson,
+    ) -> Value
+    where
+        OpName: Fn(&Op) -> &str,
+        AtomToJson: Fn(&Atom) -> Value,
+    {
+        self.child_to_jsonlogic(Child::Node(self.head), &op_name, &atom_to_json)
+    }
+}
+
+/// A JsonLogic value read as an atom or an operation
+enum Read<'v, Op, Atom> {
+    Atom(Atom),
+    Operation {
+        key: &'v str,
+        operator: Op,
+        unary: bool,
+        /// the operands, with their index when they're in an array
+        operands: Vec<(Option<usize>, &'v Value)>,
+    },
+}
+
+/// read a JsonLogic value, without reading the operands of an operation
+fn read<'v, Op, Atom, ParseOp, ParseAtom>(
+    value: &'v Value,
+    path: &str,
+    parse_op: &ParseOp,
+    parse_atom: &ParseAtom,
+) -> Result<Read<'v, Op, Atom>, JsonLogicError>
+where
+    Op: OpArity,
+    ParseOp: Fn(&str) -> Option<Op>,
+    ParseAtom: Fn(&Value) -> Option<Atom>,
+{
+    if let Some(atom) = parse_atom(value) {
+        return Ok(Read::Atom(atom));
+    }
+    let operation = match value {
+        Value::Object(object) if object.len() == 1 => object.iter().next(),
+        _ => None,
+    };
+    let Some((key, operands)) = operation else {
+        return Err(JsonLogicError::NotAnOperation {
+            path: path.to_string(),
+        });
+    };
+    let Some(operator) = parse_op(key) else {
+        return Err(JsonLogicError::UnknownOperator {
+            path: path.to_string(),
+            operator: key.clone(),
+        });
+    };
+    let operands: Vec<(Option<usize>, &Value)> = match operands {
+        Value::Array(operands) => operands
+            .iter()
+            .enumerate()
+            .map(|(i, operand)| (Some(i), operand))
+            .collect(),
+        operand => vec![(None, operand)],
+    };
+    let count = operands.len();
+    let unary = match (operator.arity(), count) {
+        (Arity::Unary | Arity::Either, 1) => true,
+        (Arity::Binary, 1..) | (Arity::Either, 2..) => false,
+        _ => {
+            return Err(JsonLogicError::WrongOperandCount {
+                path: path.to_string(),
+                count,
+            });
+        }
+    };
+    Ok(Read::Operation {
+        key,
+        operator,
+        unary,
+        operands,
+    })
+}
+
+impl<Op, Atom> BeTree<Op, Atom>
+where
+    Op: OpArity + fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone,
+{
+    /// push a read JsonLogic value, in parenthesis if it's a binary
+    /// operation and `group` is true
+    fn push_jsonlogic<ParseOp, ParseAtom>(
+        &mut self,
+        read_value: Read<'_, Op, Atom>,
+        group: bool,
+        path: &mut String,
+        parse_op: &ParseOp,
+        parse_atom: &ParseAtom,
+    ) -> Result<(), JsonLogicError>
+    where
+        ParseOp: Fn(&str) -> Option<Op>,
+        ParseAtom: Fn(&Value) -> Option<Atom>,
+    {
+        let (key, operator, unary, operands) = match read_value {
+            Read::Atom(atom) => {
+                self.push_atom(atom);
+                return Ok(());
+            }
+            Read::Operation {
+                key,
+                operator,
+                unary,
+                operands,
+            } => (key, operator, unary, operands),
+        };
+        let group = group && !unary && operands.len() > 1;
+        if group {
+            self.open_par();
+        }
+        let path_len = path.len();
+        for (i, (index, operand)) in operands.into_iter().enumerate() {
+            if unary || i > 0 {
+                self.push_operator(operator.clone());
+            }
+            path.push('/');
+            path.push_str(&pointer_token(key));
+            if let Some(index) = index {
+                path.push('/');
+                path.push_str(&index.to_string());
+            }
+            let operand = read(operand, path, parse_op, parse_atom)?;
+            self.push_jsonlogic(operand, true, path, parse_op, parse_atom)?;
+            path.truncate(path_len);
+        }
+        if group {
+            self.close_par();
+        }
+        Ok(())
+    }
+
+    /// build an expression from a JsonLogic document.
+    ///
+    /// Values accepted by `parse_atom` (for example `{"var": "a"}` or
+    /// comparisons) are atoms. The other ones must be operations, that
+    /// is objects with a single key that `parse_op` recognizes, and
+    /// operands matching the arity of the operator. Operations with
+    /// more than two operands become chains built from left to right,
+    /// and binary operations with a single operand are this operand.
+    ///
+    /// `null` gives an empty expression if `parse_atom` doesn't accept it.
+    pub fn from_jsonlogic<ParseOp, ParseAtom>(
+        value: &Value,
+        parse_op: ParseOp,
+        parse_atom: ParseAtom,
+    ) -> Result<Self, JsonLogicError>
+    where
+        ParseOp: Fn(&str) -> Option<Op>,
+        ParseAtom: Fn(&Value) -> Option<Atom>,
+    {
+        let mut tree = Self::new();
+        if value.is_null() && parse_atom(value).is_none() {
+            return Ok(tree);
+        }
+        let mut path = String::new();
+        let read_value = read(value, &path, &parse_op, &parse_atom)?;
+        tree.push_jsonlogic(read_value, false, &mut path, &parse_op, &parse_atom)?;
+        Ok(tree)
+    }
+}
@@ -12,8 +12,30 @@ It can then be evaluated with the `eval` function which takes as parameters
 
 Normal evaluation order is left to right but is modified with parenthesis.
 
+The tree remembers which groups were written with parenthesis, so that the expression can be rendered as the user wrote it, redundant parenthesis included, with `display_as_written`.
+
 **bet** is designed around separation of building, transformations, and evaluation, so that an expression can be efficiently applied on many inputs. **bet** is designed for very fast evaluation.
 
+**bet** can be used in `no_std` environments, provided `alloc` is available, by disabling the default `std` feature. Pattern matching, rewrite rules, the hash based analyses, and the printing functions are then not available.
+
+With the `inline-storage` feature, the nodes and atoms of small expressions are stored inline in the `BeTree` instead of in heap allocated vectors, so that building and evaluating a filter of a few atoms doesn't allocate.
+
+When many short-lived expressions are built in turn, `clear` empties a tree while keeping its storage, so that it can be reused without allocating again.
+
+With the `arena` feature, trees created with `BeTree::new_in` have their nodes and atoms allocated in a `TreeArena`, a bump arena whose memory is released all at once, or reused after a `reset`. Both features can be enabled together: the trees which aren't in an arena then keep small expressions inline.
+
+A tree can be frozen with `freeze` into a `FrozenBeTree`, an immutable handle which is cheap to clone and can be shared between threads evaluating the expression.
+
+The evaluation functions are generic over the closures they're given, for maximal inlining, which means one copy of the evaluation code per combination of closure types. `eval_dyn` and `eval_faillible_dyn` take trait objects instead, and the `dyn-eval` feature makes `eval` and `eval_faillible` forward to them, trading some evaluation speed for smaller binaries and faster compilation.
+
+With the `interning` feature, the string atoms of many trees can be interned in a shared `InternedAtoms` pool, each tree then holding small copyable `Symbol` atoms.
+
+With the `logic` feature, the `logic` module provides ready to use boolean operators, with a parser of expressions like `a & !(b or "c d")` and an evaluation function, as in the example below.
+
+With the `arith` feature, the `arith` module provides the arithmetic operators with their precedences, a parser of expressions like `-(2 + 3) * 4 ^ 2`, and evaluation functions for integers and floats.
+
+With the `jsonlogic` feature, expressions can be exported to and imported from [JsonLogic](https://jsonlogic.com) documents.
+
 # Examples: Known open-source usages
 
 ### dysk
@@ -69,97 +91,349 @@ For example, with `rhit -p 'y & !( \d{4} | sp | bl )'`, you get stats on hits on
 
 # Complete example : parsing and evaluating boolean expressions
 
-Here we parse expressions like `"(A | B) & !(C | D | E)"` and evaluate them.
+Here we parse expressions like `"(A | B) & !(C | D | E)"` and evaluate them, with the operators, parser and evaluation function of the `logic` module (`logic` feature):
 
 ```
-use bet::BeTree;
-
-/// The operators in this example are AND, OR, and NOT operating on booleans.
-/// `And` and `Or` are binary while `Not` is unary.
-#[derive(Debug, Clone, Copy, PartialEq)]
-enum BoolOperator {
-    And,
-    Or,
-    Not,
-}
-
-/// simple but realistic example of an expression parsing.
-///
-/// You don't have to parse tokens in advance, you may accumulate
-/// into atoms with `mutate_or_create_atom`.
-///
-/// For more reliable results on user inputs, you may want to check
-/// the consistency (or use default operators) during parsing
-/// with `accept_atom`, `accept_unary_operator`, etc.
-fn parse(input: &str) -> BeTree<BoolOperator, char> {
-    let mut expr = BeTree::new();
-    for c in input.chars() {
-        match c {
-            '&' => expr.push_operator(BoolOperator::And),
-            '|' => expr.push_operator(BoolOperator::Or),
-            '!' => expr.push_operator(BoolOperator::Not),
-            ' ' => {},
-            '(' => expr.open_par(),
-            ')' => expr.close_par(),
-            _ => expr.push_atom(c),
-        }
-    }
-    expr
-}
+# #[cfg(feature = "logic")] {
+use bet::{logic::*, BeTree};
 
-/// evaluate the expression.
-///
-/// `trues` is the set of chars whose value is true.
-///
-/// If no operation is expected to fail, you may use
-/// `eval` instead of `eval_faillible`, for a simpler
-/// API.
-fn eval(expr: &BeTree<BoolOperator, char>, trues: &[char]) -> bool {
-    expr.eval_faillible(
-        // the function evaluating leafs - here it's simple
-        |c| Ok(trues.contains(c)),
-        // the function applying an operator to one or two values
-        |op, a, b| match (op, b) {
-            (BoolOperator::And, Some(b)) => Ok(a & b),
-            (BoolOperator::Or, Some(b)) => Ok(a | b),
-            (BoolOperator::Not, None) => Ok(!a),
-            _ => { Err("unexpected operation") }
-        },
-        // when to short-circuit. This is essential when leaf
-        // evaluation is expensive or when the left part guards
-        // for correctness of the right part evaluation
-        |op, a| match (op, a) {
-            (BoolOperator::And, false) => true,
-            (BoolOperator::Or, true) => true,
-            _ => false,
-        },
-    ).unwrap().unwrap()
-}
-
-// checking complex evaluations with T=true and F=false
-assert_eq!(eval(&parse("!((T|F)&T)"), &['T']), false);
-assert_eq!(eval(&parse("!(!((T|F)&(F|T)&T)) & !F & (T | (T|F))"), &['T']), true);
+let parse = |input: &str| parse_bool_expr(input, |s| Ok::<_, ()>(s.to_string())).unwrap();
+let eval = |expr: &BeTree<BoolOp, String>, trues: &[&str]| {
+    eval_bool(expr, |atom| trues.contains(&atom.as_str()))
+};
 
 // we evaluate an expression with two different sets of values
 let expr = parse("(A | B) & !(C | D | E)");
-assert_eq!(eval(&expr, &['A', 'C', 'E']), false);
-assert_eq!(eval(&expr, &['A', 'B']), true);
-
-// Let's show the left to right evaluation order
-// and importance of parenthesis
-assert_eq!(eval(&parse("(A & B)|(C & D)"), &['A', 'B', 'C']), true);
-assert_eq!(eval(&parse(" A & B | C & D "), &['A', 'B', 'C']), false);
-
+assert_eq!(eval(&expr, &["A", "C", "E"]), Some(false));
+assert_eq!(eval(&expr, &["A", "B"]), Some(true));
+
+// operators have no precedence: evaluation is left to right,
+// modified with parenthesis
+assert_eq!(eval(&parse("(A & B) | (C & D)"), &["A", "B", "C"]), Some(true));
+assert_eq!(eval(&parse("A & B | C & D"), &["A", "B", "C"]), Some(false));
+# }
 ```
+
+The `logic` module is itself built with `push_operator`, `push_atom`, etc. and `eval`, which you'll use for your own operators and atoms.
 */
 
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+#[cfg(all(test, not(feature = "std")))]
+#[macro_use]
+extern crate std;
+
+#[allow(unused_imports)]
+use alloc::{
+    borrow::ToOwned,
+    boxed::Box,
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+
+mod analysis;
+mod annotate;
+#[cfg(feature = "arbitrary")]
+mod arbitrary_impl;
+#[cfg(feature = "arena")]
+mod arena;
+#[cfg(feature = "arith")]
+pub mod arith;
+mod as_written_display;
+mod atom_context;
 mod be_tree;
+mod bet_error;
+mod box_drawing;
+mod bytes;
+mod canonical_text;
+mod canonical_text_error;
 mod child;
+mod clauses;
+#[cfg(feature = "logic")]
+mod cnf_error;
+mod compact_display;
+mod conversion_error;
+mod cursor;
+mod decode_error;
+mod detached_subtree;
+mod diagnose;
+mod diff;
+#[cfg(feature = "logic")]
+mod dimacs;
+mod display;
+mod dot;
+mod edit;
+mod edit_error;
+mod empty_group;
+mod eval_dyn;
+mod eval_failure;
+mod extract;
+mod filter_stream;
+mod fingerprint;
+mod flatten;
+mod frozen;
+#[cfg(feature = "rand")]
+pub mod gen;
+mod grouping;
+#[cfg(feature = "std")]
+mod hole;
+mod html;
+mod id;
+mod input_state;
+mod insertion_context;
+#[cfg(feature = "interning")]
+mod interning;
+mod iter;
+#[cfg(feature = "jsonlogic")]
+mod jsonlogic;
+#[cfg(feature = "logic")]
+pub mod logic;
+mod logic_ops;
+mod navigation;
+#[cfg(feature = "serde")]
+mod nested;
 mod node;
-
+mod node_values;
+mod not_in_normal_form;
+mod op_arity;
+mod op_conversion;
+mod parse_borrowed;
+mod parse_error;
+mod parts;
+#[cfg(feature = "std")]
+mod pattern;
+mod plan;
+mod plan_rejected;
+mod polarity;
+mod precedence;
+mod profile;
+mod progress;
+mod push_error;
+mod query;
+mod render;
+mod rewrite;
+#[cfg(feature = "std")]
+mod rules;
+mod safe_render;
+#[cfg(feature = "serde")]
+mod serde_impl;
+mod sexpr;
+mod sexpr_error;
+#[cfg(feature = "shared")]
+mod shared;
+mod side;
+mod small_vec;
+mod stats;
+mod structural;
+mod structure_error;
+mod sub_expr;
+mod template_error;
+#[cfg(feature = "proptest")]
+pub mod testing;
+mod token;
+mod tokens;
+mod tree_builder;
+mod tree_path;
+mod tree_template;
+mod tree_transaction;
+mod validate;
+mod visitor;
+
+#[cfg(all(test, feature = "std"))]
+mod test_analysis;
+#[cfg(test)]
+mod test_annotate;
+#[cfg(all(test, feature = "arbitrary"))]
+mod test_arbitrary;
+#[cfg(all(test, feature = "arena"))]
+mod test_arena;
+#[cfg(all(test, feature = "arith"))]
+mod test_arith;
 #[cfg(test)]
 mod test_bool;
 #[cfg(test)]
 mod test_bool_faillible;
-
-pub use {be_tree::*, child::*, node::*};
+#[cfg(test)]
+mod test_bounds;
+#[cfg(test)]
+mod test_box_drawing;
+#[cfg(test)]
+mod test_bytes;
+#[cfg(test)]
+mod test_canonical_text;
+#[cfg(test)]
+mod test_clauses;
+#[cfg(test)]
+mod test_compact_display;
+#[cfg(test)]
+mod test_cursor;
+#[cfg(test)]
+mod test_detach;
+#[cfg(test)]
+mod test_diagnose;
+#[cfg(test)]
+mod test_diff;
+#[cfg(all(test, feature = "logic"))]
+mod test_dimacs;
+#[cfg(test)]
+mod test_display;
+#[cfg(test)]
+mod test_dot;
+#[cfg(test)]
+mod test_edit;
+#[cfg(test)]
+mod test_edits_epoch;
+#[cfg(test)]
+mod test_empty_group;
+#[cfg(all(test, feature = "std"))]
+mod test_error;
+#[cfg(test)]
+mod test_eval_dyn;
+#[cfg(test)]
+mod test_explicit_groups;
+#[cfg(test)]
+mod test_extract;
+#[cfg(test)]
+mod test_filter_stream;
+#[cfg(test)]
+mod test_fingerprint;
+#[cfg(test)]
+mod test_flatten;
+#[cfg(test)]
+mod test_frozen;
+#[cfg(all(test, feature = "rand"))]
+mod test_gen;
+#[cfg(test)]
+mod test_grouping;
+#[cfg(all(test, feature = "std"))]
+mod test_hole;
+#[cfg(test)]
+mod test_html;
+#[cfg(test)]
+mod test_id;
+#[cfg(test)]
+mod test_input_state;
+#[cfg(test)]
+mod test_insertion_context;
+#[cfg(all(test, feature = "interning"))]
+mod test_interning;
+#[cfg(test)]
+mod test_iter;
+#[cfg(all(test, feature = "jsonlogic"))]
+mod test_jsonlogic;
+#[cfg(all(test, feature = "logic"))]
+mod test_logic;
+#[cfg(test)]
+mod test_logic_ops;
+#[cfg(test)]
+mod test_map_atoms;
+#[cfg(test)]
+mod test_map_atoms_in_place;
+#[cfg(test)]
+mod test_map_operators;
+#[cfg(test)]
+mod test_meta;
+#[cfg(test)]
+mod test_navigation;
+#[cfg(all(test, feature = "serde"))]
+mod test_nested;
+#[cfg(test)]
+mod test_node_values;
+#[cfg(test)]
+mod test_op_arity;
+#[cfg(test)]
+mod test_op_conversion;
+#[cfg(test)]
+mod test_parse_borrowed;
+#[cfg(test)]
+mod test_parts;
+#[cfg(all(test, feature = "std"))]
+mod test_pattern;
+#[cfg(test)]
+mod test_plan;
+#[cfg(test)]
+mod test_polarity;
+#[cfg(test)]
+mod test_precedence;
+#[cfg(test)]
+mod test_profile;
+#[cfg(test)]
+mod test_progress;
+#[cfg(test)]
+mod test_query;
+#[cfg(test)]
+mod test_render;
+#[cfg(test)]
+mod test_rewrite;
+#[cfg(all(test, feature = "std"))]
+mod test_rules;
+#[cfg(test)]
+mod test_safe_render;
+#[cfg(all(test, feature = "serde"))]
+mod test_serde;
+#[cfg(test)]
+mod test_sexpr;
+#[cfg(all(test, feature = "shared"))]
+mod test_shared;
+#[cfg(test)]
+mod test_small_vec;
+#[cfg(test)]
+mod test_stats;
+#[cfg(test)]
+mod test_structural;
+#[cfg(test)]
+mod test_sub_expr;
+#[cfg(all(test, feature = "proptest"))]
+mod test_testing;
+#[cfg(test)]
+mod test_token;
+#[cfg(test)]
+mod test_tokens;
+#[cfg(test)]
+mod test_tree_builder;
+#[cfg(test)]
+mod test_tree_path;
+#[cfg(test)]
+mod test_tree_template;
+#[cfg(test)]
+mod test_tree_transaction;
+#[cfg(test)]
+mod test_util;
+#[cfg(test)]
+mod test_validate;
+#[cfg(test)]
+mod test_visitor;
+#[cfg(test)]
+mod test_write_tree;
+
+pub use {
+    annotate::*, as_written_display::*, atom_context::*, be_tree::*, bet_error::*,
+    canonical_text::*, canonical_text_error::*, child::*, clauses::*, compact_display::*,
+    conversion_error::*, cursor::*, decode_error::*, detached_subtree::*, diagnose::*, diff::*,
+    edit_error::*, eval_failure::*, fingerprint::*, frozen::*, grouping::*, html::*, id::*,
+    input_state::*, insertion_context::*, iter::*, logic_ops::*, node::*, node_values::*,
+    not_in_normal_form::*, op_arity::*, op_conversion::*, parse_borrowed::*, parse_error::*,
+    parts::*, plan::*, plan_rejected::*, polarity::*, profile::*, progress::*, push_error::*,
+    query::*, render::*, rewrite::*, safe_render::*, sexpr_error::*, side::*, stats::*,
+    structure_error::*, sub_expr::*, template_error::*, token::*, tree_builder::*, tree_path::*,
+    tree_template::*, tree_transaction::*, visitor::*,
+};
+
+#[cfg(feature = "arena")]
+pub use arena::*;
+#[cfg(feature = "interning")]
+pub use interning::*;
+#[cfg(feature = "jsonlogic")]
+pub use jsonlogic::*;
+#[cfg(feature = "serde")]
+pub use nested::*;
+#[cfg(feature = "shared")]
+pub use shared::*;
+use small_vec::*;
+#[cfg(feature = "logic")]
+pub use {cnf_error::*, dimacs::*};
+#[cfg(feature = "std")]
+pub use {hole::*, pattern::*, rules::*};
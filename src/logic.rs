@@ -0,0 +1,752 @@
+//! Boolean expressions, ready to use: the usual operators, a parser,
+//! and an evaluation function.
+//!
+//! ```
+//! use bet::logic::*;
+//!
+//! let expr = parse_bool_expr("(A | B) and not (C | \"D E\")", |s| {
+//!     Ok::<_, ()>(s.to_string())
+//! })
+//! .unwrap();
+//! let trues = ["A", "C"];
+//! assert_eq!(eval_bool(&expr, |atom| trues.contains(&atom.as_str())), Some(false));
+//! assert_eq!(eval_bool(&expr, |atom| atom == "B"), Some(true));
+//! ```
+//!
+//! Expressions can also be compared, or checked for tautologies, by
+//! enumerating the assignments of their distinct atoms (the truth table),
+//! provided there are at most `DEFAULT_VARIABLE_LIMIT` of them (or
+//! another given limit).
+//!
+//! They can also be minimized, exactly (Quine–McCluskey) when they have
+//! at most `DEFAULT_MINIMIZATION_LIMIT` distinct atoms (or another given
+//! limit), with the usual simplification laws otherwise.
+//!
+//! With the `rand` feature, random assignments satisfying an expression
+//! can be drawn, for example to generate matching test data.
+
+use {
+    crate::{parse_borrowed::*, *},
+    core::{cell::RefCell, fmt},
+};
+
+/// The default maximal number of distinct atoms of the expressions
+/// whose truth tables are enumerated
+pub const DEFAULT_VARIABLE_LIMIT: usize = 20;
+
+/// The default maximal number of distinct atoms of the expressions
+/// which are exactly minimized
+pub const DEFAULT_MINIMIZATION_LIMIT: usize = 10;
+
+/// The boolean operators
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BoolOp {
+    And,
+    Or,
+    Not,
+}
+
+impl BoolOp {
+    /// apply the operator to its operands
+    pub fn eval(self, a: bool, b: Option<bool>) -> bool {
+        match (self, b) {
+            (Self::And, Some(b)) => a & b,
+            (Self::Or, Some(b)) => a | b,
+            _ => !a,
+        }
+    }
+    /// tell whether the right operand doesn't need to be evaluated
+    pub fn short_circuit(self, a: bool) -> bool {
+        matches!((self, a), (Self::And, false) | (Self::Or, true))
+    }
+}
+
+impl fmt::Display for BoolOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::And => write!(f, "&"),
+            Self::Or => write!(f, "|"),
+            Self::Not => write!(f, "!"),
+        }
+    }
+}
+
+impl LogicOps for BoolOp {
+    fn and() -> Self {
+        Self::And
+    }
+    fn or() -> Self {
+        Self::Or
+    }
+    fn not() -> Self {
+        Self::Not
+    }
+}
+
+impl OpArity for BoolOp {
+    fn arity(&self) -> Arity {
+        match self {
+            Self::And | Self::Or => Arity::Binary,
+            Self::Not => Arity::Unary,
+        }
+    }
+}
+
+/// return the operator of a keyword (`and`, `or`, `not`, in any case)
+fn keyword_operator(word: &str) -> Option<BoolOp> {
+    [
+        ("and", BoolOp::And),
+        ("or", BoolOp::Or),
+        ("not", BoolOp::Not),
+    ]
+    .iter()
+    .find(|(keyword, _)| word.eq_ignore_ascii_case(keyword))
+    .map(|&(_, op)| op)
+}
+
+/// parse a boolean expression.
+///
+/// The operators are `&`, `|`, `!`, and the `and`, `or`, `not` keywords.
+/// Parenthesis group subexpressions and whitespaces separate atoms.
+/// Atoms are built with `parse_atom` from the words which aren't
+/// keywords and from the content of double quoted strings (which may
+/// contain any character but a double quote).
+///
+/// Operators are checked to be used at a position matching their arity,
+/// so that for example `A ! B` is rejected.
+pub fn parse_bool_expr<A, E, F>(
+    input: &str,
+    mut parse_atom: F,
+) -> Result<BeTree<BoolOp, A>, ParseError>
+where
+    F: FnMut(&str) -> Result<A, E>,
+{
+    let classify = |c: char| match c {
+        '&' => CharClass::Operator(BoolOp::And),
+        '|' => CharClass::Operator(BoolOp::Or),
+        '!' => CharClass::Operator(BoolOp::Not),
+        '(' => CharClass::OpeningPar,
+        ')' => CharClass::ClosingPar,
+        '"' => CharClass::Quote,
+        _ if c.is_whitespace() => CharClass::Space,
+        _ => CharClass::Atom,
+    };
+    let mut tree = BeTree::new();
+    scan(input, classify, |offset, lexeme| {
+        let token = match lexeme {
+            Lexeme::Word(word) => match keyword_operator(word) {
+                Some(op) => Token::Operator(op),
+                None => Token::Atom(word),
+            },
+            Lexeme::Quoted(text) => Token::Atom(text),
+            Lexeme::Operator(op) => Token::Operator(op),
+            Lexeme::OpeningPar => Token::OpeningParenthesis,
+            Lexeme::ClosingPar => Token::ClosingParenthesis,
+        };
+        match token {
+            Token::Atom(text) => {
+                if !tree.accept_atom() {
+                    return Err(ParseError::UnexpectedAtom { offset });
+                }
+                let atom = parse_atom(text).map_err(|_| ParseError::InvalidAtom { offset })?;
+                tree.push_atom(atom);
+                Ok(())
+            }
+            Token::Operator(op) => tree.push_parsed(offset, Token::Operator(op)),
+            Token::OpeningParenthesis => tree.push_parsed(offset, Token::OpeningParenthesis),
+            Token::ClosingParenthesis => tree.push_parsed(offset, Token::ClosingParenthesis),
+        }
+    })?;
+    tree.check_parsed_end(input.len())?;
+    Ok(tree)
+}
+
+/// evaluate a boolean expression, `truth` telling the value of every
+/// atom.
+///
+/// The right operand of `&` isn't evaluated when the left one is false,
+/// nor the right operand of `|` when the left one is true.
+///
+/// Return `None` if the expression is empty.
+pub fn eval_bool<A, F>(tree: &BeTree<BoolOp, A>, truth: F) -> Option<bool>
+where
+    F: FnMut(&A) -> bool,
+{
+    let truth = RefCell::new(truth);
+    tree.eval(
+        |atom| (truth.borrow_mut())(atom),
+        |op, a, b| op.eval(a, b),
+        |op, &a| op.short_circuit(a),
+    )
+}
+
+/// Error returned when an expression has too many distinct atoms for
+/// its truth table to be enumerated
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct TooManyVariables {
+    /// The number of distinct atoms
+    pub count: usize,
+    /// The maximal number of distinct atoms which was given
+    pub limit: usize,
+}
+
+impl fmt::Display for TooManyVariables {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} distinct atoms, more than the limit of {}",
+            self.count, self.limit
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for TooManyVariables {}
+
+/// The distinct atoms of some expressions, which are the variables
+/// of their truth tables
+struct Variables<'t, A> {
+    atoms: Vec<&'t A>,
+}
+
+impl<'t, A> Variables<'t, A>
+where
+    A: fmt::Debug + Clone + PartialEq,
+{
+    /// collect the distinct reachable atoms of the trees, checking
+    /// there are at most `limit` of them (and never more than 63, as
+    /// assignments are bit sets)
+    fn collect(trees: &[&'t BeTree<BoolOp, A>], limit: usize) -> Result<Self, TooManyVariables> {
+        let limit = limit.min(63);
+        let variables = Self::distinct(trees);
+        if variables.atoms.len() > limit {
+            return Err(TooManyVariables {
+                count: variables.atoms.len(),
+                limit,
+            });
+        }
+        Ok(variables)
+    }
+    /// collect the distinct reachable atoms of the trees, whatever
+    /// their number
+    fn distinct(trees: &[&'t BeTree<BoolOp, A>]) -> Self {
+        let mut atoms: Vec<&'t A> = Vec::new();
+        for tree in trees {
+            for (_, atom) in tree.iter_atoms_in_tree() {
+                if !atoms.contains(&atom) {
+                    atoms.push(atom);
+                }
+            }
+        }
+        Self { atoms }
+    }
+    /// the tree whose atoms are replaced with the indices of the variables
+    fn index_atoms(&self, tree: &BeTree<BoolOp, A>) -> BeTree<BoolOp, usize> {
+        tree.map_atoms_indexed(|_, atom| {
+            // atoms which aren't reachable get a value which isn't used
+            self.atoms.iter().position(|&a| a == atom).unwrap_or(0)
+        })
+    }
+    /// the number of assignments
+    fn assignment_count(&self) -> u64 {
+        1 << self.atoms.len()
+    }
+    /// the value of the variables, the assignment being a bit set
+    fn values(&self, assignment: u64) -> Vec<(&'t A, bool)> {
+        self.atoms
+            .iter()
+            .enumerate()
+            .map(|(i, &atom)| (atom, assignment & (1 << i) != 0))
+            .collect()
+    }
+}
+
+/// evaluate an expression whose atoms are variable indices
+fn eval_assignment(tree: &BeTree<BoolOp, usize>, assignment: u64) -> Option<bool> {
+    eval_bool(tree, |&i| assignment & (1 << i) != 0)
+}
+
+/// tell whether two expressions have the same value for all assignments
+/// of their atoms, by enumerating them.
+///
+/// Return an error if the expressions have more than `limit` distinct
+/// atoms (see `DEFAULT_VARIABLE_LIMIT`).
+pub fn equivalent<A>(
+    a: &BeTree<BoolOp, A>,
+    b: &BeTree<BoolOp, A>,
+    limit: usize,
+) -> Result<bool, TooManyVariables>
+where
+    A: fmt::Debug + Clone + PartialEq,
+{
+    Ok(counterexample(a, b, limit)?.is_none())
+}
+
+/// return an assignment of the atoms for which the two expressions
+/// have different values, or `None` if they're equivalent.
+///
+/// The assignment gives the value of each distinct atom of the
+/// expressions. Return an error if the expressions have more than
+/// `limit` distinct atoms.
+pub fn counterexample<'t, A>(
+    a: &'t BeTree<BoolOp, A>,
+    b: &'t BeTree<BoolOp, A>,
+    limit: usize,
+) -> Result<Option<Vec<(&'t A, bool)>>, TooManyVariables>
+where
+    A: fmt::Debug + Clone + PartialEq,
+{
+    let variables = Variables::collect(&[a, b], limit)?;
+    let (a, b) = (variables.index_atoms(a), variables.index_atoms(b));
+    Ok((0..variables.assignment_count())
+        .find(|&assignment| eval_assignment(&a, assignment) != eval_assignment(&b, assignment))
+        .map(|assignment| variables.values(assignment)))
+}
+
+/// Truth tables of boolean expressions
+impl<A> BeTree<BoolOp, A>
+where
+    A: fmt::Debug + Clone + PartialEq,
+{
+    /// count the assignments of the distinct atoms of the expression
+    /// for which it's true, by enumerating them.
+    ///
+    /// Return an error if the expression has more than `limit` distinct
+    /// atoms (see `DEFAULT_VARIABLE_LIMIT`).
+    pub fn count_satisfying(&self, limit: usize) -> Result<u128, TooManyVariables> {
+        let variables = Variables::collect(&[self], limit)?;
+        let tree = variables.index_atoms(self);
+        let count = (0..variables.assignment_count())
+            .filter(|&assignment| eval_assignment(&tree, assignment) == Some(true))
+            .count();
+        Ok(count as u128)
+    }
+
+    /// tell whether the expression is true for all assignments of
+    /// its atoms (it "matches everything")
+    ///
+    /// An empty expression, which has no value, isn't a tautology.
+    pub fn is_tautology(&self, limit: usize) -> Result<bool, TooManyVariables> {
+        let variables = Variables::collect(&[self], limit)?;
+        let tree = variables.index_atoms(self);
+        Ok((0..variables.assignment_count())
+            .all(|assignment| eval_assignment(&tree, assignment) == Some(true)))
+    }
+
+    /// tell whether the expression is false for all assignments of
+    /// its atoms (it "matches nothing")
+    ///
+    /// An empty expression, which has no value, is a contradiction.
+    pub fn is_contradiction(&self, limit: usize) -> Result<bool, TooManyVariables> {
+        Ok(self.count_satisfying(limit)? == 0)
+    }
+}
+
+/// The default maximal number of random assignments tried by
+/// `sample_satisfying` when there are too many atoms to enumerate
+/// the truth table
+#[cfg(feature = "rand")]
+pub const DEFAULT_SAMPLING_TRIES: usize = 10_000;
+
+/// Sampling of satisfying assignments
+#[cfg(feature = "rand")]
+impl<A> BeTree<BoolOp, A>
+where
+    A: fmt::Debug + Clone + Eq + std::hash::Hash,
+{
+    /// draw a random assignment of the distinct atoms of the expression
+    /// for which it's true, for example to generate data matching a filter.
+    ///
+    /// When there are at most `limit` distinct atoms, the truth table is
+    /// enumerated and the assignment is uniformly chosen among the
+    /// satisfying ones, None being returned if there's none (the
+    /// expression is a contradiction).
+    ///
+    /// Otherwise up to `max_tries` random assignments are tried (see
+    /// `DEFAULT_SAMPLING_TRIES`), and an error is returned if none
+    /// satisfies the expression, as it's then unknown whether one exists.
+    pub fn sample_satisfying<R: rand::Rng>(
+        &self,
+        rng: &mut R,
+        limit: usize,
+        max_tries: usize,
+    ) -> Result<Option<std::collections::HashMap<A, bool>>, TooManyVariables> {
+        let assignment = |values: Vec<(&A, bool)>| {
+            values
+                .into_iter()
+                .map(|(atom, value)| (atom.clone(), value))
+                .collect()
+        };
+        match Variables::collect(&[self], limit) {
+            Ok(variables) => {
+                let tree = variables.index_atoms(self);
+                // reservoir sampling over the satisfying assignments
+                let mut chosen = None;
+                let mut count = 0u64;
+                for a in 0..variables.assignment_count() {
+                    if eval_assignment(&tree, a) == Some(true) {
+                        count += 1;
+                        if rng.gen_range(0..count) == 0 {
+                            chosen = Some(a);
+                        }
+                    }
+                }
+                Ok(chosen.map(|a| assignment(variables.values(a))))
+            }
+            Err(e) => {
+                let variables = Variables::distinct(&[self]);
+                let tree = variables.index_atoms(self);
+                let mut values = vec![false; variables.atoms.len()];
+                for _ in 0..max_tries {
+                    for value in &mut values {
+                        *value = rng.gen();
+                    }
+                    if eval_bool(&tree, |&i| values[i]) == Some(true) {
+                        return Ok(Some(assignment(
+                            variables.atoms.iter().copied().zip(values).collect(),
+                        )));
+                    }
+                }
+                Err(e)
+            }
+        }
+    }
+}
+
+/// A boolean expression whose operands are variable indices, with
+/// n-ary conjunctions and disjunctions, used for minimization
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    False,
+    True,
+    Var(usize),
+    Not(Box<Expr>),
+    And(Vec<Expr>),
+    Or(Vec<Expr>),
+}
+
+impl Expr {
+    /// build the expression of a child of a tree whose atoms are
+    /// variable indices
+    ///
+    /// Return `None` when the tree is incomplete or has operators
+    /// without the right number of operands.
+    fn of_child(tree: &BeTree<BoolOp, usize>, child: Child) -> Option<Self> {
+        match child {
+            Child::None => None,
+            Child::Atom(atom_id) => tree.atom(atom_id).map(|&i| Self::Var(i)),
+            Child::Node(node_id) => {
+                let node = tree.node(node_id)?;
+                let left = Self::of_child(tree, node.left());
+                match (node.operator(), node.is_unary(), node.right()) {
+                    (None, _, Child::None) => left,
+                    (Some(BoolOp::Not), true, Child::None) => Some(Self::Not(Box::new(left?))),
+                    (Some(BoolOp::And), false, right) => {
+                        Some(Self::And(vec![left?, Self::of_child(tree, right)?]))
+                    }
+                    (Some(BoolOp::Or), false, right) => {
+                        Some(Self::Or(vec![left?, Self::of_child(tree, right)?]))
+                    }
+                    _ => None,
+                }
+            }
+        }
+    }
+    /// the number of atoms and operators of the tree built from the
+    /// expression
+    fn size(&self) -> usize {
+        match self {
+            Self::False | Self::True => 4, // A & !A, or A | !A
+            Self::Var(_) => 1,
+            Self::Not(operand) => 1 + operand.size(),
+            Self::And(operands) | Self::Or(operands) => {
+                operands.iter().map(Self::size).sum::<usize>() + operands.len() - 1
+            }
+        }
+    }
+    /// apply the simplification laws until the expression doesn't change
+    fn simplified(mut self) -> Self {
+        loop {
+            let simplified = self.clone().simplify();
+            if simplified == self {
+                return self;
+            }
+            self = simplified;
+        }
+    }
+    /// apply, bottom-up, the double negation, associativity, idempotence,
+    /// complement and absorption laws, and fold the constants
+    fn simplify(self) -> Self {
+        match self {
+            Self::Not(operand) => match operand.simplify() {
+                Self::Not(operand) => *operand,
+                Self::True => Self::False,
+                Self::False => Self::True,
+                operand => Self::Not(Box::new(operand)),
+            },
+            Self::And(operands) => Self::simplify_operands(operands, true),
+            Self::Or(operands) => Self::simplify_operands(operands, false),
+            expr => expr,
+        }
+    }
+    /// simplify the operands of a conjunction (when `and` is true)
+    /// or of a disjunction
+    fn simplify_operands(operands: Vec<Self>, and: bool) -> Self {
+        let (absorbing, neutral) = if and {
+            (Self::False, Self::True)
+        } else {
+            (Self::True, Self::False)
+        };
+        let mut flat: Vec<Self> = Vec::new();
+        let add = |flat: &mut Vec<Self>, operand: Self| {
+            if !flat.contains(&operand) {
+                flat.push(operand);
+            }
+        };
+        for operand in operands {
+            match operand.simplify() {
+                Self::And(operands) if and => {
+                    for operand in operands {
+                        add(&mut flat, operand);
+                    }
+                }
+                Self::Or(operands) if !and => {
+                    for operand in operands {
+                        add(&mut flat, operand);
+                    }
+                }
+                operand if operand == neutral => {}
+                operand if operand == absorbing => return absorbing,
+                operand => add(&mut flat, operand),
+            }
+        }
+        // complement: A & !A is false, A | !A is true
+        let complemented = flat.iter().any(|operand| match operand {
+            Self::Not(negated) => flat.contains(negated),
+            _ => false,
+        });
+        if complemented {
+            return absorbing;
+        }
+        // absorption: A | (A & B) is A, A & (A | B) is A
+        let absorbed: Vec<bool> = flat
+            .iter()
+            .enumerate()
+            .map(|(i, operand)| {
+                flat.iter()
+                    .enumerate()
+                    .any(|(j, other)| i != j && other.absorbs(operand, and))
+            })
+            .collect();
+        let mut operands: Vec<Self> = flat
+            .into_iter()
+            .zip(absorbed)
+            .filter(|(_, absorbed)| !absorbed)
+            .map(|(operand, _)| operand)
+            .collect();
+        match operands.len() {
+            0 => neutral,
+            1 => operands.pop().unwrap(),
+            _ if and => Self::And(operands),
+            _ => Self::Or(operands),
+        }
+    }
+    /// tell whether, as operands of a conjunction (when `and` is true)
+    /// or of a disjunction, this expression makes the other one useless
+    fn absorbs(&self, other: &Self, and: bool) -> bool {
+        let other_operands = match (other, and) {
+            (Self::Or(operands), true) | (Self::And(operands), false) => operands,
+            _ => return false,
+        };
+        match (self, and) {
+            (Self::Or(operands), true) | (Self::And(operands), false) => {
+                operands.len() < other_operands.len()
+                    && operands
+                        .iter()
+                        .all(|operand| other_operands.contains(operand))
+            }
+            _ => other_operands.contains(self),
+        }
+    }
+    /// build the minimal sum of products of the function of `n` variables
+    /// true for the given assignments, with the Quine–McCluskey method
+    /// (the prime implicants being greedily chosen after the essential ones)
+    fn minimal_sum_of_products(n: usize, minterms: &[u64]) -> Self {
+        // an implicant is a pair (value, mask of the variables which don't
+        // matter), the masked bits of the value being zero
+        let mut primes: Vec<(u64, u64)> = Vec::new();
+        let mut implicants: Vec<(u64, u64)> = minterms.iter().map(|&m| (m, 0)).collect();
+        implicants.sort_unstable();
+        while !implicants.is_empty() {
+            // two implicants combine when they differ by one unmasked
+            // variable, so each one is only looked up, in the sorted
+            // implicants, with every variable set, instead of being
+            // compared to all others
+            let mut combined = vec![false; implicants.len()];
+            let mut next = Vec::new();
+            for (i, &(value, mask)) in implicants.iter().enumerate() {
+                for bit in (0..n).map(|v| 1 << v) {
+                    if (value | mask) & bit != 0 {
+                        continue;
+                    }
+                    if let Ok(j) = implicants.binary_search(&(value | bit, mask)) {
+                        combined[i] = true;
+                        combined[j] = true;
+                        next.push((value, mask | bit));
+                    }
+                }
+            }
+            primes.extend(
+                implicants
+                    .iter()
+                    .zip(combined)
+                    .filter(|(_, combined)| !combined)
+                    .map(|(&implicant, _)| implicant),
+            );
+            next.sort_unstable();
+            next.dedup();
+            implicants = next;
+        }
+        let covers = |(value, mask): (u64, u64), minterm: u64| minterm & !mask == value;
+        let mut chosen: Vec<(u64, u64)> = Vec::new();
+        // essential prime implicants, the only ones covering some minterm
+        for &minterm in minterms {
+            let mut covering = primes.iter().filter(|&&prime| covers(prime, minterm));
+            if let (Some(&prime), None) = (covering.next(), covering.next()) {
+                if !chosen.contains(&prime) {
+                    chosen.push(prime);
+                }
+            }
+        }
+        let mut uncovered: Vec<u64> = minterms
+            .iter()
+            .copied()
+            .filter(|&minterm| !chosen.iter().any(|&prime| covers(prime, minterm)))
+            .collect();
+        while !uncovered.is_empty() {
+            // the prime covering the most minterms, then with the fewest literals
+            let prime = primes
+                .iter()
+                .copied()
+                .max_by_key(|&prime| {
+                    let count = uncovered.iter().filter(|&&m| covers(prime, m)).count();
+                    (count, prime.1.count_ones())
+                })
+                .unwrap(); // every minterm is covered by a prime
+            uncovered.retain(|&minterm| !covers(prime, minterm));
+            chosen.push(prime);
+        }
+        let products = chosen
+            .into_iter()
+            .map(|(value, mask)| {
+                let literals: Vec<Self> = (0..n)
+                    .filter(|&i| mask & (1 << i) == 0)
+                    .map(|i| {
+                        if value & (1 << i) != 0 {
+                            Self::Var(i)
+                        } else {
+                            Self::Not(Box::new(Self::Var(i)))
+                        }
+                    })
+                    .collect();
+                Self::And(literals)
+            })
+            .collect();
+        Self::Or(products).simplified()
+    }
+    /// push the expression in the tree, `atoms` giving the atom
+    /// of every variable
+    fn push_to<A: Clone>(&self, tree: &mut BeTree<BoolOp, A>, atoms: &[&A]) {
+        match self {
+            Self::False | Self::True => {
+                // the constants are expressed with the first variable
+                tree.push_atom(atoms[0].clone());
+                if self == &Self::True {
+                    tree.push_operator(BoolOp::Or);
+                } else {
+                    tree.push_operator(BoolOp::And);
+                }
+                tree.push_operator(BoolOp::Not);
+                tree.push_atom(atoms[0].clone());
+            }
+            Self::Var(i) => {
+                tree.push_atom(atoms[*i].clone());
+            }
+            Self::Not(operand) => {
+                tree.push_operator(BoolOp::Not);
+                operand.push_operand_to(tree, atoms);
+            }
+            Self::And(operands) | Self::Or(operands) => {
+                let operator = if matches!(self, Self::And(_)) {
+                    BoolOp::And
+                } else {
+                    BoolOp::Or
+                };
+                for (i, operand) in operands.iter().enumerate() {
+                    if i > 0 {
+                        tree.push_operator(operator);
+                    }
+                    operand.push_operand_to(tree, atoms);
+                }
+            }
+        }
+    }
+    /// push the expression as an operand, in parenthesis unless it's
+    /// a variable or a negation (there's no operator precedence)
+    fn push_operand_to<A: Clone>(&self, tree: &mut BeTree<BoolOp, A>, atoms: &[&A]) {
+        if matches!(self, Self::Var(_) | Self::Not(_)) {
+            self.push_to(tree, atoms);
+        } else {
+            tree.open_par();
+            self.push_to(tree, atoms);
+            tree.close_par();
+        }
+    }
+}
+
+/// Minimization of boolean expressions
+impl<A> BeTree<BoolOp, A>
+where
+    A: fmt::Debug + Clone + PartialEq,
+{
+    /// return an equivalent expression which is, as far as this
+    /// simplification goes, smaller, and never bigger.
+    ///
+    /// When the expression has at most `limit` distinct atoms (see
+    /// `DEFAULT_MINIMIZATION_LIMIT`), a minimal sum of products is
+    /// computed from its truth table with the Quine–McCluskey method,
+    /// and kept if it's smaller than the simplified expression. The
+    /// cost of this exact method grows exponentially with the number
+    /// of atoms.
+    ///
+    /// The simplification applies the double negation (`!!A` is `A`),
+    /// idempotence (`A & A` is `A`), complement (`A & !A` is false),
+    /// and absorption (`A | (A & B)` is `A`) laws. Constant results are
+    /// expressed as `A | !A` or `A & !A`.
+    ///
+    /// Empty or incomplete expressions are returned unchanged.
+    pub fn minimize(&self, limit: usize) -> Self {
+        let variables = Variables::distinct(&[self]);
+        let tree = variables.index_atoms(self);
+        let expr = match Expr::of_child(&tree, Child::Node(tree.head)) {
+            Some(expr) => expr,
+            None => return self.clone(),
+        };
+        let mut minimized = expr.simplified();
+        let n = variables.atoms.len();
+        if n <= limit.min(63) {
+            let minterms: Vec<u64> = (0..variables.assignment_count())
+                .filter(|&assignment| eval_assignment(&tree, assignment) == Some(true))
+                .collect();
+            let sum_of_products = Expr::minimal_sum_of_products(n, &minterms);
+            if sum_of_products.size() < minimized.size() {
+                minimized = sum_of_products;
+            }
+        }
+        let mut tree = BeTree::new();
+        minimized.push_to(&mut tree, &variables.atoms);
+        tree
+    }
+}
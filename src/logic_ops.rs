@@ -0,0 +1,107 @@
+use {
+    crate::*,
+    core::{
+        fmt,
+        ops::{BitAnd, BitOr, Not},
+    },
+};
+
+/// The logical operators of an operator type, which let trees be
+/// combined with the `&`, `|` and `!` Rust operators.
+///
+/// ```
+/// use bet::{BeTree, LogicOps};
+///
+/// #[derive(Debug, Clone, Copy, PartialEq)]
+/// enum Op {
+///     And,
+///     Or,
+///     Not,
+/// }
+/// impl LogicOps for Op {
+///     fn and() -> Self {
+///         Op::And
+///     }
+///     fn or() -> Self {
+///         Op::Or
+///     }
+///     fn not() -> Self {
+///         Op::Not
+///     }
+/// }
+///
+/// fn atom(name: &'static str) -> BeTree<Op, &'static str> {
+///     let mut expr = BeTree::new();
+///     expr.push_atom(name);
+///     expr
+/// }
+///
+/// let saved_filter = atom("rust") & atom("recent");
+/// let exclusions = atom("vendored");
+/// let overrides = atom("pinned");
+/// let filter = saved_filter & !exclusions | overrides;
+///
+/// let matches = |tags: &[&str]| {
+///     filter.eval(
+///         |atom| tags.contains(atom),
+///         |op, a, b| match (op, b) {
+///             (Op::And, Some(b)) => a & b,
+///             (Op::Or, Some(b)) => a | b,
+///             _ => !a,
+///         },
+///         |op, &a| matches!((op, a), (Op::And, false) | (Op::Or, true)),
+///     )
+/// };
+/// assert_eq!(matches(&["rust", "recent"]), Some(true));
+/// assert_eq!(matches(&["rust", "recent", "vendored"]), Some(false));
+/// assert_eq!(matches(&["vendored", "pinned"]), Some(true));
+/// ```
+///
+/// The operators don't fail on empty trees: `&` and `|` return the
+/// other operand when one is empty (as `combine` does), and `!` on an
+/// empty tree returns it unchanged (as `wrap_unary` does).
+pub trait LogicOps {
+    /// the binary operator true when both operands are true
+    fn and() -> Self;
+    /// the binary operator true when one of the operands is true
+    fn or() -> Self;
+    /// the unary operator negating its operand
+    fn not() -> Self;
+}
+
+impl<Op, Atom> BitAnd for BeTree<Op, Atom>
+where
+    Op: LogicOps + fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone,
+{
+    type Output = Self;
+    /// combine both trees with the `and` operator
+    fn bitand(self, other: Self) -> Self {
+        self.combine(Op::and(), other)
+    }
+}
+
+impl<Op, Atom> BitOr for BeTree<Op, Atom>
+where
+    Op: LogicOps + fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone,
+{
+    type Output = Self;
+    /// combine both trees with the `or` operator
+    fn bitor(self, other: Self) -> Self {
+        self.combine(Op::or(), other)
+    }
+}
+
+impl<Op, Atom> Not for BeTree<Op, Atom>
+where
+    Op: LogicOps + fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone,
+{
+    type Output = Self;
+    /// wrap the tree in the `not` operator
+    fn not(mut self) -> Self {
+        self.wrap_unary(Op::not());
+        self
+    }
+}
@@ -0,0 +1,80 @@
+use {crate::*, core::fmt};
+
+/// Navigation in the tree
+impl<Op, Atom> BeTree<Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone,
+{
+    /// return the parent of the node and the side of the node in it,
+    /// checking the parent really has the node as child
+    pub(crate) fn checked_link(&self, node_id: NodeId) -> Option<(NodeId, Side)> {
+        let parent_id = self.nodes.get(node_id.index())?.parent()?;
+        let parent = &self.nodes[parent_id];
+        [Side::Left, Side::Right]
+            .iter()
+            .find(|&&side| parent.child(side) == Child::Node(node_id))
+            .map(|&side| (parent_id, side))
+    }
+
+    /// skip the groups (nodes without operator) above the child
+    pub(crate) fn skip_groups(&self, mut child: Child) -> Child {
+        while let Child::Node(node_idx) = child {
+            let node = &self.nodes[node_idx];
+            if node.operator.is_some() || node.right().is_some() {
+                break;
+            }
+            child = node.left();
+        }
+        child
+    }
+
+    /// the left and right children of the node (the operand of a unary
+    /// operator is on the left), or `None` if there's no such node.
+    ///
+    /// Complexity: O(1)
+    pub fn children(&self, node_id: NodeId) -> Option<(Child, Child)> {
+        self.nodes
+            .get(node_id.index())
+            .map(|node| (node.left(), node.right()))
+    }
+
+    /// the parent of the node, or `None` if it's the head, if it's not
+    /// in the tree anymore, or if there's no such node.
+    ///
+    /// Complexity: O(1)
+    pub fn parent_of(&self, node_id: NodeId) -> Option<NodeId> {
+        self.checked_link(node_id).map(|(parent_id, _)| parent_id)
+    }
+
+    /// the other child of the parent of the node, or `None` if the node
+    /// has no parent or no sibling (e.g. when it's the operand of a
+    /// unary operator).
+    ///
+    /// Complexity: O(1)
+    pub fn sibling_of(&self, node_id: NodeId) -> Option<Child> {
+        let (parent_id, side) = self.checked_link(node_id)?;
+        let sibling = self.nodes[parent_id].child(side.other());
+        sibling.is_some().then_some(sibling)
+    }
+
+    /// tell whether the node is the left child of its parent, or `None`
+    /// if it has no parent.
+    ///
+    /// Complexity: O(1)
+    pub fn is_left_child(&self, node_id: NodeId) -> Option<bool> {
+        self.checked_link(node_id)
+            .map(|(_, side)| side == Side::Left)
+    }
+
+    /// the node holding the atom and the side of the atom in it, or
+    /// `None` if the atom isn't in the tree (or if there's no such atom).
+    ///
+    /// Complexity: O(n), as atoms don't know their parent
+    pub fn node_of_atom(&self, atom_id: AtomId) -> Option<(NodeId, Side)> {
+        if atom_id.index() >= self.atoms.len() {
+            return None;
+        }
+        self.atom_link(atom_id)
+    }
+}
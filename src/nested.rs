@@ -0,0 +1,250 @@
+use {
+    crate::*,
+    core::fmt,
+    serde::{
+        de::{self, DeserializeSeed, MapAccess, Visitor},
+        ser::{self, SerializeMap},
+        Deserialize, Deserializer, Serialize, Serializer,
+    },
+};
+
+/// A tree serialized as nested operations instead of arenas, for
+/// files read by humans.
+///
+/// An atom is serialized as `{"atom": a}`, a unary operation as
+/// `{"op": o, "operand": x}` and a binary one as
+/// `{"op": o, "left": x, "right": y}`. Missing operands and the empty
+/// expression are serialized as null. Groups and nodes which aren't
+/// reachable don't appear.
+///
+/// Deserialization builds a compact tree. Both serialization and
+/// deserialization fail on expressions nested deeper than
+/// `NestedRepr::MAX_DEPTH`, so that every serialized tree can be read back.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NestedRepr<Op, Atom>(pub BeTree<Op, Atom>);
+
+impl<Op, Atom> NestedRepr<Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone,
+{
+    /// the maximal number of nested operations accepted when serializing
+    /// and deserializing
+    pub const MAX_DEPTH: usize = 100;
+}
+
+impl<Op, Atom> From<BeTree<Op, Atom>> for NestedRepr<Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone,
+{
+    fn from(tree: BeTree<Op, Atom>) -> Self {
+        Self(tree)
+    }
+}
+
+impl<Op, Atom> From<NestedRepr<Op, Atom>> for BeTree<Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone,
+{
+    fn from(repr: NestedRepr<Op, Atom>) -> Self {
+        repr.0
+    }
+}
+
+fn too_deep<Op, Atom>() -> String
+where
+    Op: fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone,
+{
+    format!(
+        "expression nested deeper than {} levels",
+        NestedRepr::<Op, Atom>::MAX_DEPTH,
+    )
+}
+
+/// A child of a tree, serialized with its descendants
+struct NestedChild<'t, Op, Atom> {
+    tree: &'t BeTree<Op, Atom>,
+    child: Child,
+    depth: usize,
+}
+
+impl<Op, Atom> Serialize for NestedChild<'_, Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq + Serialize,
+    Atom: fmt::Debug + Clone + Serialize,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if self.depth > NestedRepr::<Op, Atom>::MAX_DEPTH {
+            return Err(ser::Error::custom(too_deep::<Op, Atom>()));
+        }
+        let tree = self.tree;
+        let node = match tree.skip_groups(self.child) {
+            Child::None => return serializer.serialize_none(),
+            Child::Atom(atom_idx) => {
+                let mut map = serializer.serialize_map(Some(1))?;
+                map.serialize_entry("atom", &tree.atoms[atom_idx])?;
+                return map.end();
+            }
+            Child::Node(node_idx) => &tree.nodes[node_idx],
+        };
+        let Some(op) = &node.operator else {
+            return serializer.serialize_none(); // empty group
+        };
+        let depth = self.depth + 1;
+        let nested = |child| NestedChild { tree, child, depth };
+        let mut map = serializer.serialize_map(Some(if node.unary { 2 } else { 3 }))?;
+        map.serialize_entry("op", op)?;
+        if node.unary {
+            map.serialize_entry("operand", &nested(node.left()))?;
+        } else {
+            map.serialize_entry("left", &nested(node.left()))?;
+            map.serialize_entry("right", &nested(node.right()))?;
+        }
+        map.end()
+    }
+}
+
+impl<Op, Atom> Serialize for NestedRepr<Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq + Serialize,
+    Atom: fmt::Debug + Clone + Serialize,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        NestedChild {
+            tree: &self.0,
+            child: Child::Node(self.0.head),
+            depth: 0,
+        }
+        .serialize(serializer)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(field_identifier, rename_all = "lowercase")]
+enum NestedField {
+    Atom,
+    Op,
+    Operand,
+    Left,
+    Right,
+}
+
+/// Deserializes a child with its descendants into a tree
+struct NestedChildSeed<'t, Op, Atom> {
+    tree: &'t mut BeTree<Op, Atom>,
+    depth: usize,
+}
+
+impl<'de, Op, Atom> DeserializeSeed<'de> for NestedChildSeed<'_, Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq + Deserialize<'de>,
+    Atom: fmt::Debug + Clone + Deserialize<'de>,
+{
+    type Value = Child;
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Child, D::Error> {
+        if self.depth > NestedRepr::<Op, Atom>::MAX_DEPTH {
+            return Err(de::Error::custom(too_deep::<Op, Atom>()));
+        }
+        deserializer.deserialize_option(self)
+    }
+}
+
+impl<'de, Op, Atom> Visitor<'de> for NestedChildSeed<'_, Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq + Deserialize<'de>,
+    Atom: fmt::Debug + Clone + Deserialize<'de>,
+{
+    type Value = Child;
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("an atom, an operation, or null")
+    }
+    fn visit_none<E: de::Error>(self) -> Result<Child, E> {
+        Ok(Child::None)
+    }
+    fn visit_unit<E: de::Error>(self) -> Result<Child, E> {
+        Ok(Child::None)
+    }
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Child, D::Error> {
+        deserializer.deserialize_map(self)
+    }
+    fn visit_map<M: MapAccess<'de>>(self, mut map: M) -> Result<Child, M::Error> {
+        let mut atom: Option<Atom> = None;
+        let mut op: Option<Op> = None;
+        let mut operand = None;
+        let mut left = None;
+        let mut right = None;
+        while let Some(field) = map.next_key()? {
+            let child = match field {
+                NestedField::Atom if atom.is_none() => {
+                    atom = Some(map.next_value()?);
+                    continue;
+                }
+                NestedField::Op if op.is_none() => {
+                    op = Some(map.next_value()?);
+                    continue;
+                }
+                NestedField::Operand if operand.is_none() => &mut operand,
+                NestedField::Left if left.is_none() => &mut left,
+                NestedField::Right if right.is_none() => &mut right,
+                NestedField::Atom => return Err(de::Error::duplicate_field("atom")),
+                NestedField::Op => return Err(de::Error::duplicate_field("op")),
+                NestedField::Operand => return Err(de::Error::duplicate_field("operand")),
+                NestedField::Left => return Err(de::Error::duplicate_field("left")),
+                NestedField::Right => return Err(de::Error::duplicate_field("right")),
+            };
+            *child = Some(map.next_value_seed(NestedChildSeed {
+                tree: &mut *self.tree,
+                depth: self.depth + 1,
+            })?);
+        }
+        let tree = self.tree;
+        match (atom, op, operand, left, right) {
+            (Some(atom), None, None, None, None) => Ok(Child::Atom(tree.store_atom(atom))),
+            (None, Some(op), Some(operand), None, None) => {
+                Ok(Child::Node(tree.new_unary_node(op, operand)))
+            }
+            (None, Some(op), None, Some(left), right) => {
+                if left.is_none() {
+                    return Err(de::Error::custom("a binary operation needs a left operand"));
+                }
+                let node_idx = tree.store_node(Node::new(Some(op), false));
+                tree.op_count += 1;
+                tree.link_child(node_idx, Side::Left, left);
+                tree.link_child(node_idx, Side::Right, right.unwrap_or(Child::None));
+                Ok(Child::Node(node_idx))
+            }
+            (Some(_), ..) => Err(de::Error::custom(
+                "an atom can't have an operator or operands",
+            )),
+            (None, None, ..) => Err(de::Error::missing_field("op")),
+            (None, Some(_), None, None, _) => Err(de::Error::missing_field("left")),
+            (None, Some(_), ..) => Err(de::Error::custom(
+                "an operation has either an operand or a left and a right operand",
+            )),
+        }
+    }
+}
+
+impl<'de, Op, Atom> Deserialize<'de> for NestedRepr<Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq + Deserialize<'de>,
+    Atom: fmt::Debug + Clone + Deserialize<'de>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut tree = BeTree::new();
+        tree.nodes.clear();
+        let root = NestedChildSeed {
+            tree: &mut tree,
+            depth: 0,
+        }
+        .deserialize(deserializer)?;
+        tree.set_root(root);
+        if !tree.is_empty() {
+            tree.last_pushed = TokenType::ClosingPar;
+        }
+        Ok(Self(tree))
+    }
+}
@@ -1,42 +1,169 @@
-use {crate::Child, std::fmt};
-
-pub type NodeId = usize;
+use {
+    crate::*,
+    core::{fmt, mem},
+};
 
 /// A node in the expression tree
 ///
 /// You probably don't need to use this struct
-/// unless you want to inspect the tree
-#[derive(Debug, Clone, PartialEq)]
-pub struct Node<Op>
-where
-    Op: fmt::Debug + Clone + PartialEq,
-{
-    pub operator: Option<Op>,
-    pub parent: Option<NodeId>,
-    pub left: Child,
-    pub right: Child,
-    pub unary: bool, // true when there's an operator in a unary position
+/// unless you want to inspect the tree or build one from its parts.
+///
+/// The links to the parent and children are packed in 32 bits each
+/// (see `MAX_ID`), so that a node with a small operator and no metadata
+/// takes 16 bytes.
+#[derive(Clone, PartialEq)]
+pub struct Node<Op, Meta = ()> {
+    pub(crate) operator: Option<Op>,
+    parent: u32,
+    left: u32,
+    right: u32,
+    pub(crate) unary: bool, // true when there's an operator in a unary position
+    pub(crate) explicit: bool, // true when the node comes from parenthesis
+    pub(crate) meta: Meta,
 }
 
-impl<Op> Node<Op>
-where
-    Op: fmt::Debug + Clone + PartialEq,
-{
+impl<Op> Node<Op> {
+    /// build a node without parent nor children, for example to
+    /// assemble the parts given to `BeTree::from_parts`
+    pub fn new(operator: Option<Op>, unary: bool) -> Self {
+        Self::with_meta(operator, unary, ())
+    }
+    pub fn empty() -> Self {
+        Self::new(None, false)
+    }
+}
+
+impl<Op, Meta> Node<Op, Meta> {
+    /// build a node with its metadata, without parent nor children
+    pub fn with_meta(operator: Option<Op>, unary: bool, meta: Meta) -> Self {
+        Self {
+            operator,
+            parent: NO_LINK,
+            left: NO_LINK,
+            right: NO_LINK,
+            unary,
+            explicit: false,
+            meta,
+        }
+    }
+    /// build a node with the same links and metadata but another operator
+    pub(crate) fn with_operator<Op2>(&self, operator: Option<Op2>) -> Node<Op2, Meta>
+    where
+        Meta: Clone,
+    {
+        Node {
+            operator,
+            parent: self.parent,
+            left: self.left,
+            right: self.right,
+            unary: self.unary,
+            explicit: self.explicit,
+            meta: self.meta.clone(),
+        }
+    }
+    /// build a node with the same links and operator but another metadata
+    pub(crate) fn into_meta<Meta2, F>(self, f: F) -> Node<Op, Meta2>
+    where
+        F: FnOnce(Meta) -> Meta2,
+    {
+        Node {
+            operator: self.operator,
+            parent: self.parent,
+            left: self.left,
+            right: self.right,
+            unary: self.unary,
+            explicit: self.explicit,
+            meta: f(self.meta),
+        }
+    }
+    /// the operator, absent for groups and for the nodes
+    /// still waiting for their operator
+    pub fn operator(&self) -> Option<&Op> {
+        self.operator.as_ref()
+    }
+    /// tell whether the operator is in a unary position
+    pub fn is_unary(&self) -> bool {
+        self.unary
+    }
+    /// tell whether the node is the one of a group written with
+    /// parenthesis, as opposed to a node made by the restructuring
+    /// of the tree (see `BeTree::was_explicit_group`)
+    pub fn is_explicit_group(&self) -> bool {
+        self.explicit
+    }
+    /// the metadata attached to the node, which is the default value
+    /// unless given with `BeTree::push_operator_with_meta` or set with
+    /// `BeTree::node_meta_mut`
+    pub fn meta(&self) -> &Meta {
+        &self.meta
+    }
+    pub fn parent(&self) -> Option<NodeId> {
+        if self.parent == NO_LINK {
+            None
+        } else {
+            Some(NodeId::new_unchecked(self.parent))
+        }
+    }
+    /// set the link to the parent, which must be the node having this
+    /// one as child
+    pub fn set_parent(&mut self, parent: Option<NodeId>) {
+        self.parent = parent.map_or(NO_LINK, u32::from);
+    }
+    pub fn left(&self) -> Child {
+        Child::unpack(self.left)
+    }
+    pub fn set_left(&mut self, child: Child) {
+        self.left = child.pack();
+    }
+    pub fn right(&self) -> Child {
+        Child::unpack(self.right)
+    }
+    pub fn set_right(&mut self, child: Child) {
+        self.right = child.pack();
+    }
     /// a node is full when we can't add other childs
     pub fn is_full(&self) -> bool {
         if self.unary {
-            self.left.is_some()
+            self.left != NO_LINK
         } else {
-            self.right.is_some()
+            self.right != NO_LINK
         }
     }
-    pub fn empty() -> Self {
-        Self {
-            operator: None,
-            parent: None,
-            left: Child::None,
-            right: Child::None,
-            unary: false,
+    /// tell whether the right child, if any, is where it can be: in a
+    /// binary operation whose left child is set
+    pub(crate) fn has_valid_shape(&self) -> bool {
+        self.right == NO_LINK || (self.operator.is_some() && !self.unary && self.left != NO_LINK)
+    }
+    /// return the child on the given side
+    pub fn child(&self, side: Side) -> Child {
+        match side {
+            Side::Left => self.left(),
+            Side::Right => self.right(),
+        }
+    }
+    /// exchange the left and right children
+    pub(crate) fn swap_children(&mut self) {
+        mem::swap(&mut self.left, &mut self.right);
+    }
+    /// set the child on the given side
+    pub fn set_child(&mut self, side: Side, child: Child) {
+        match side {
+            Side::Left => self.set_left(child),
+            Side::Right => self.set_right(child),
         }
     }
 }
+
+impl<Op: fmt::Debug, Meta: fmt::Debug> fmt::Debug for Node<Op, Meta> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Node")
+            .field("operator", &self.operator)
+            .field("parent", &self.parent())
+            .field("left", &self.left())
+            .field("right", &self.right())
+            .field("unary", &self.unary)
+            .field("explicit", &self.explicit)
+            .field("meta", &self.meta)
+            .finish()
+    }
+}
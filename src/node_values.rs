@@ -0,0 +1,117 @@
+use crate::*;
+
+/// The values computed by `BeTree::eval_annotated` for the nodes and
+/// atoms of a tree.
+///
+/// Nodes and atoms which weren't evaluated, because they were skipped
+/// by a short-circuit, weren't reachable or were incomplete, have no
+/// value. The values don't borrow the tree, which can be displayed or
+/// edited while they're read.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeValues<R> {
+    nodes: Vec<Option<R>>,
+    atoms: Vec<Option<R>>,
+}
+
+impl<R> NodeValues<R> {
+    /// the value of the node, if it was evaluated
+    pub fn get_node(&self, node_id: NodeId) -> Option<&R> {
+        self.nodes.get(node_id.index()).and_then(Option::as_ref)
+    }
+    /// the value of the atom, if it was evaluated
+    pub fn get_atom(&self, atom_id: AtomId) -> Option<&R> {
+        self.atoms.get(atom_id.index()).and_then(Option::as_ref)
+    }
+    /// the value of the child, if it was evaluated
+    pub fn get(&self, child: Child) -> Option<&R> {
+        match child {
+            Child::None => None,
+            Child::Node(node_id) => self.get_node(node_id),
+            Child::Atom(atom_id) => self.get_atom(atom_id),
+        }
+    }
+}
+
+/// Annotated evaluation
+impl<Op, Atom, Meta> BeTree<Op, Atom, Meta> {
+    fn eval_child_annotated<R, EvalAtom, EvalOp, ShortCircuit>(
+        &self,
+        values: &mut NodeValues<R>,
+        eval_atom: &EvalAtom,
+        eval_op: &EvalOp,
+        short_circuit: &ShortCircuit,
+        child: Child,
+    ) -> Option<R>
+    where
+        R: Clone,
+        EvalAtom: Fn(&Atom) -> R,
+        EvalOp: Fn(&Op, R, Option<R>) -> R,
+        ShortCircuit: Fn(&Op, &R) -> bool,
+    {
+        let node_idx = match child {
+            Child::None => return None,
+            Child::Atom(atom_idx) => {
+                let value = eval_atom(&self.atoms[atom_idx]);
+                values.atoms[atom_idx.index()] = Some(value.clone());
+                return Some(value);
+            }
+            Child::Node(node_idx) => node_idx,
+        };
+        let node = &self.nodes[node_idx];
+        let left_value =
+            self.eval_child_annotated(values, eval_atom, eval_op, short_circuit, node.left());
+        let value = match (&node.operator, left_value) {
+            (_, None) => None,
+            (None, left_value) => left_value,
+            (Some(op), Some(left_value)) => {
+                if short_circuit(op, &left_value) {
+                    Some(left_value)
+                } else {
+                    let right_value = self.eval_child_annotated(
+                        values,
+                        eval_atom,
+                        eval_op,
+                        short_circuit,
+                        node.right(),
+                    );
+                    Some(eval_op(op, left_value, right_value))
+                }
+            }
+        };
+        values.nodes[node_idx.index()] = value.clone();
+        value
+    }
+
+    /// evaluate the expression exactly like `eval`, also returning the
+    /// values computed for the nodes and atoms, for example to color
+    /// the subexpressions of a displayed tree.
+    ///
+    /// A group has the value of its content. The right operand of an
+    /// operation which short-circuited isn't evaluated, so neither it
+    /// nor its descendants have a value.
+    pub fn eval_annotated<R, EvalAtom, EvalOp, ShortCircuit>(
+        &self,
+        eval_atom: EvalAtom,
+        eval_op: EvalOp,
+        short_circuit: ShortCircuit,
+    ) -> (Option<R>, NodeValues<R>)
+    where
+        R: Clone,
+        EvalAtom: Fn(&Atom) -> R,
+        EvalOp: Fn(&Op, R, Option<R>) -> R,
+        ShortCircuit: Fn(&Op, &R) -> bool,
+    {
+        let mut values = NodeValues {
+            nodes: vec![None; self.nodes.len()],
+            atoms: vec![None; self.atoms.len()],
+        };
+        let value = self.eval_child_annotated(
+            &mut values,
+            &eval_atom,
+            &eval_op,
+            &short_circuit,
+            Child::Node(self.head),
+        );
+        (value, values)
+    }
+}
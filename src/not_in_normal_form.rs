@@ -0,0 +1,46 @@
+use {crate::*, core::fmt};
+
+/// Error returned by `BeTree::clauses` when the expression isn't
+/// in the required normal form
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum NotInNormalForm {
+    /// The operator of the node doesn't have the operands it needs
+    MissingOperand(NodeId),
+    /// The operator of the node isn't expected there: it's neither the
+    /// outer operator between clauses, nor the inner operator in a clause,
+    /// nor the negation of an atom
+    UnexpectedOperator(NodeId),
+    /// The node is a negation whose operand isn't an atom
+    NegatedOperation(NodeId),
+}
+
+impl NotInNormalForm {
+    /// the node where the problem was found
+    pub fn node(self) -> NodeId {
+        match self {
+            Self::MissingOperand(node_id)
+            | Self::UnexpectedOperator(node_id)
+            | Self::NegatedOperation(node_id) => node_id,
+        }
+    }
+}
+
+impl fmt::Display for NotInNormalForm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingOperand(node_id) => {
+                write!(f, "missing operand for the operator of node {}", node_id)
+            }
+            Self::UnexpectedOperator(node_id) => {
+                write!(f, "unexpected operator in node {}", node_id)
+            }
+            Self::NegatedOperation(node_id) => {
+                write!(f, "node {} negates an operation, not an atom", node_id)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for NotInNormalForm {}
@@ -0,0 +1,48 @@
+use crate::*;
+
+/// The number of operands an operator accepts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Arity {
+    /// the operator only applies to the operand at its right
+    Unary,
+    /// the operator only joins two operands
+    Binary,
+    /// the operator is unary or binary depending on its position,
+    /// as `-` in arithmetic
+    Either,
+}
+
+/// Operators declaring their arity, so that `push_operator_checked`
+/// can reject the ones pushed at a wrong position instead of building
+/// a wrong tree.
+pub trait OpArity {
+    fn arity(&self) -> Arity;
+}
+
+/// Pushing operators whose arity is checked
+impl<Op, Atom, Meta> BeTree<Op, Atom, Meta>
+where
+    Op: OpArity,
+    Meta: Default,
+{
+    /// add an operator right of the expression, checking that its
+    /// declared arity matches the position: a unary operator isn't
+    /// accepted just after an operand, and a binary operator isn't
+    /// accepted where an operand is expected.
+    ///
+    /// The tree isn't modified when an error is returned.
+    ///
+    /// `Either` operators are accepted everywhere and, as with
+    /// `push_operator`, the context decides whether they're unary
+    /// or binary.
+    pub fn push_operator_checked(&mut self, operator: Op) -> Result<(), PushError> {
+        match (operator.arity(), self.accept_binary_operator()) {
+            (Arity::Unary, true) => Err(PushError::UnaryInBinaryPosition),
+            (Arity::Binary, false) => Err(PushError::BinaryInUnaryPosition),
+            _ => {
+                self.push_operator(operator);
+                Ok(())
+            }
+        }
+    }
+}
@@ -0,0 +1,171 @@
+use {crate::*, core::fmt};
+
+/// What an operator becomes in `BeTree::try_convert_ops`
+#[derive(Debug, Clone, PartialEq)]
+pub enum OpConversion<Op2> {
+    /// The operator is replaced with this one
+    Operator(Op2),
+    /// The operation is replaced with this expression, whose atoms are
+    /// the operands of the operation: `Side::Left` for the left (or only)
+    /// operand, `Side::Right` for the right one.
+    ///
+    /// For example `NAND` may be expanded into `!(Left & Right)`. An
+    /// operand may appear several times, or not at all.
+    Expansion(BeTree<Op2, Side>),
+}
+
+/// Conversion to another set of operators
+impl<Op, Atom> BeTree<Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone,
+{
+    fn convert_child_into<Op2, Err, F>(
+        &self,
+        child: Child,
+        dst: &mut BeTree<Op2, Atom>,
+        f: &F,
+    ) -> Result<Child, ConversionError<Err>>
+    where
+        Op2: fmt::Debug + Clone + PartialEq,
+        F: Fn(&Op, bool) -> Result<OpConversion<Op2>, Err>,
+    {
+        let node_idx = match child {
+            Child::None => return Ok(Child::None),
+            Child::Atom(atom_idx) => {
+                return Ok(Child::Atom(dst.store_atom(self.atoms[atom_idx].clone())));
+            }
+            Child::Node(node_idx) => node_idx,
+        };
+        let node = &self.nodes[node_idx];
+        let conversion = match &node.operator {
+            Some(operator) => {
+                Some(
+                    f(operator, node.unary).map_err(|error| ConversionError::Rejected {
+                        node: node_idx,
+                        error,
+                    })?,
+                )
+            }
+            None => None,
+        };
+        let operator = match conversion {
+            Some(OpConversion::Expansion(expansion)) => {
+                let uses = |side| {
+                    expansion
+                        .iter_atoms_in_tree()
+                        .any(|(_, &atom)| atom == side)
+                };
+                let (uses_left, uses_right) = (uses(Side::Left), uses(Side::Right));
+                if expansion.is_empty() || (node.unary && uses_right) {
+                    return Err(ConversionError::InvalidExpansion { node: node_idx });
+                }
+                // operands not used by the expansion are dropped
+                let mut operands = [(Child::None, false), (Child::None, false)];
+                if uses_left {
+                    operands[0].0 = self.convert_child_into(node.left(), dst, f)?;
+                }
+                if uses_right {
+                    operands[1].0 = self.convert_child_into(node.right(), dst, f)?;
+                }
+                return Ok(expansion.expand_into(Child::Node(expansion.head), dst, &mut operands));
+            }
+            Some(OpConversion::Operator(operator)) => Some(operator),
+            None => None,
+        };
+        let left = self.convert_child_into(node.left(), dst, f)?;
+        let right = self.convert_child_into(node.right(), dst, f)?;
+        let new_idx = dst.store_node(Node::new(operator, node.unary));
+        if node.operator.is_some() {
+            dst.op_count += 1;
+        }
+        dst.link_child(new_idx, Side::Left, left);
+        dst.link_child(new_idx, Side::Right, right);
+        Ok(Child::Node(new_idx))
+    }
+
+    /// build a tree with other operators, each operator being either
+    /// replaced with another one, or expanded into a small expression
+    /// of its operands (see `OpConversion`).
+    ///
+    /// The function receives the operator and whether it's used as unary.
+    /// The conversion stops at the first rejected operator, and the
+    /// error tells its node.
+    ///
+    /// Only the reachable structure is kept, and the ids of the nodes
+    /// and atoms of the new tree aren't the ones of this tree.
+    pub fn try_convert_ops<Op2, Err, F>(
+        &self,
+        f: F,
+    ) -> Result<BeTree<Op2, Atom>, ConversionError<Err>>
+    where
+        Op2: fmt::Debug + Clone + PartialEq,
+        F: Fn(&Op, bool) -> Result<OpConversion<Op2>, Err>,
+    {
+        let mut tree = BeTree::new();
+        tree.nodes.clear();
+        let root = self.convert_child_into(Child::Node(self.head), &mut tree, &f)?;
+        tree.set_root(root);
+        if !tree.is_empty() {
+            tree.last_pushed = TokenType::ClosingPar;
+        }
+        Ok(tree)
+    }
+}
+
+/// Expansion of an operation into an expression of its operands
+impl<Op> BeTree<Op, Side>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+{
+    /// copy the child of the expansion into `dst`, replacing its atoms
+    /// with the operands, which are already in `dst` and are copied
+    /// when used more than once
+    fn expand_into<Atom>(
+        &self,
+        child: Child,
+        dst: &mut BeTree<Op, Atom>,
+        operands: &mut [(Child, bool); 2],
+    ) -> Child
+    where
+        Atom: fmt::Debug + Clone,
+    {
+        match child {
+            Child::None => Child::None,
+            Child::Atom(atom_idx) => {
+                let index = match self.atoms[atom_idx] {
+                    Side::Left => 0,
+                    Side::Right => 1,
+                };
+                let (operand, used) = &mut operands[index];
+                if !*used {
+                    *used = true;
+                    return *operand;
+                }
+                match *operand {
+                    Child::None => Child::None,
+                    Child::Atom(atom_idx) => {
+                        Child::Atom(dst.store_atom(dst.atoms[atom_idx].clone()))
+                    }
+                    Child::Node(_) => {
+                        let copy = dst.child_tree(*operand);
+                        Child::Node(dst.graft(copy, None))
+                    }
+                }
+            }
+            Child::Node(node_idx) => {
+                let node = &self.nodes[node_idx];
+                if node.operator.is_none() {
+                    return self.expand_into(node.left(), dst, operands);
+                }
+                let new_idx = dst.store_node(Node::new(node.operator.clone(), node.unary));
+                dst.op_count += 1;
+                let left = self.expand_into(node.left(), dst, operands);
+                dst.link_child(new_idx, Side::Left, left);
+                let right = self.expand_into(node.right(), dst, operands);
+                dst.link_child(new_idx, Side::Right, right);
+                Child::Node(new_idx)
+            }
+        }
+    }
+}
@@ -0,0 +1,161 @@
+use crate::*;
+
+/// The role of a character in the input of `BeTree::parse_borrowed`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CharClass<Op> {
+    /// a character of an atom
+    Atom,
+    /// an operator, unary or binary depending on the context
+    Operator(Op),
+    OpeningPar,
+    ClosingPar,
+    /// a character separating atoms, otherwise ignored
+    Space,
+    /// the delimiter of a quoted atom, which may contain any character
+    /// but this delimiter
+    Quote,
+}
+
+/// Parsing of strings into trees whose atoms borrow the input
+impl<'a, Op: OpArity> BeTree<Op, &'a str> {
+    /// parse an infix expression, the role of every character being
+    /// given by `classify`.
+    ///
+    /// The atoms of the tree are slices of the input, so that no string
+    /// is allocated. An atom is a run of `CharClass::Atom` characters,
+    /// or the content of a quoted atom, without the quotes (escape
+    /// sequences aren't supported, as they would need owned atoms).
+    ///
+    /// Operators are checked to be used at a position matching their
+    /// arity, so that for example `A ! B` is rejected.
+    ///
+    /// ```
+    /// use bet::{Arity, BeTree, CharClass, OpArity};
+    ///
+    /// struct Op(char);
+    /// impl OpArity for Op {
+    ///     fn arity(&self) -> Arity {
+    ///         if self.0 == '!' { Arity::Unary } else { Arity::Binary }
+    ///     }
+    /// }
+    ///
+    /// let input = "ext=rs & !(size>10k | \"My Documents\")";
+    /// let expr = BeTree::parse_borrowed(input, |c| match c {
+    ///     '&' | '|' | '!' => CharClass::Operator(Op(c)),
+    ///     '(' => CharClass::OpeningPar,
+    ///     ')' => CharClass::ClosingPar,
+    ///     '"' => CharClass::Quote,
+    ///     _ if c.is_whitespace() => CharClass::Space,
+    ///     _ => CharClass::Atom,
+    /// })
+    /// .unwrap();
+    /// let atoms: Vec<&str> = expr.iter_atoms().copied().collect();
+    /// assert_eq!(atoms, ["ext=rs", "size>10k", "My Documents"]);
+    /// ```
+    pub fn parse_borrowed<C>(input: &'a str, classify: C) -> Result<Self, ParseError>
+    where
+        C: Fn(char) -> CharClass<Op>,
+    {
+        let mut tree = BeTree::new();
+        scan(input, classify, |offset, lexeme| {
+            let token = match lexeme {
+                Lexeme::Word(atom) | Lexeme::Quoted(atom) => Token::Atom(atom),
+                Lexeme::Operator(op) => Token::Operator(op),
+                Lexeme::OpeningPar => Token::OpeningParenthesis,
+                Lexeme::ClosingPar => Token::ClosingParenthesis,
+            };
+            tree.push_parsed(offset, token)
+        })?;
+        tree.check_parsed_end(input.len())?;
+        Ok(tree)
+    }
+}
+
+/// Checked pushes, for parsers
+impl<Op: OpArity, Atom> BeTree<Op, Atom> {
+    /// push the token read at the given offset, returning an error
+    /// if it doesn't make sense at this point
+    pub(crate) fn push_parsed(
+        &mut self,
+        offset: usize,
+        token: Token<Op, Atom>,
+    ) -> Result<(), ParseError> {
+        match token {
+            Token::Operator(operator) => {
+                return self
+                    .push_operator_checked(operator)
+                    .map_err(|_| ParseError::UnexpectedOperator { offset });
+            }
+            Token::Atom(_) if !self.accept_atom() => {
+                return Err(ParseError::UnexpectedAtom { offset });
+            }
+            Token::OpeningParenthesis if !self.accept_opening_par() => {
+                return Err(ParseError::UnexpectedOpeningPar { offset });
+            }
+            Token::ClosingParenthesis if !self.accept_closing_par() => {
+                return Err(ParseError::UnexpectedClosingPar { offset });
+            }
+            _ => {}
+        }
+        self.push(token);
+        Ok(())
+    }
+    /// check the expression is complete at the end of the input
+    pub(crate) fn check_parsed_end(&self, offset: usize) -> Result<(), ParseError> {
+        if self.openness > 0 || self.last_pushed == TokenType::Operator {
+            return Err(ParseError::UnexpectedEnd { offset });
+        }
+        Ok(())
+    }
+}
+
+/// A token of the input, as read by `scan`
+pub(crate) enum Lexeme<'a, Op> {
+    /// a run of atom characters
+    Word(&'a str),
+    /// the content of a quoted atom, without the quotes
+    Quoted(&'a str),
+    Operator(Op),
+    OpeningPar,
+    ClosingPar,
+}
+
+/// read the input, calling `f` with the offset and the lexeme of
+/// every token, and stopping at the first error
+pub(crate) fn scan<'a, Op, C, F>(input: &'a str, classify: C, mut f: F) -> Result<(), ParseError>
+where
+    C: Fn(char) -> CharClass<Op>,
+    F: FnMut(usize, Lexeme<'a, Op>) -> Result<(), ParseError>,
+{
+    let mut word_start = None;
+    let mut pos = 0;
+    while let Some(c) = input[pos..].chars().next() {
+        let offset = pos;
+        pos += c.len_utf8();
+        let class = classify(c);
+        if let CharClass::Atom = class {
+            word_start.get_or_insert(offset);
+            continue;
+        }
+        if let Some(start) = word_start.take() {
+            f(start, Lexeme::Word(&input[start..offset]))?;
+        }
+        match class {
+            CharClass::Atom | CharClass::Space => {}
+            CharClass::Operator(op) => f(offset, Lexeme::Operator(op))?,
+            CharClass::OpeningPar => f(offset, Lexeme::OpeningPar)?,
+            CharClass::ClosingPar => f(offset, Lexeme::ClosingPar)?,
+            CharClass::Quote => {
+                let Some(len) = input[pos..].find(c) else {
+                    return Err(ParseError::UnclosedQuote { offset });
+                };
+                f(offset, Lexeme::Quoted(&input[pos..pos + len]))?;
+                pos += len + c.len_utf8();
+            }
+        }
+    }
+    if let Some(start) = word_start {
+        f(start, Lexeme::Word(&input[start..]))?;
+    }
+    Ok(())
+}
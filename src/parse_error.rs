@@ -0,0 +1,63 @@
+use core::fmt;
+
+/// Error returned by `BeTree::parse_borrowed` and the parsing
+/// functions of the `logic` module, with the byte offset in
+/// the input of the problem
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ParseError {
+    /// An atom was found where an operator or a closing parenthesis
+    /// was expected
+    UnexpectedAtom { offset: usize },
+    /// An operator was found at a position not matching its arity
+    UnexpectedOperator { offset: usize },
+    /// An opening parenthesis was found just after an operand
+    UnexpectedOpeningPar { offset: usize },
+    /// A closing parenthesis was found after an operator, or
+    /// without matching opening parenthesis
+    UnexpectedClosingPar { offset: usize },
+    /// A quoted atom isn't closed
+    UnclosedQuote { offset: usize },
+    /// The atom wasn't accepted by the atom parsing function
+    InvalidAtom { offset: usize },
+    /// The input ended before the expression was complete
+    UnexpectedEnd { offset: usize },
+    /// The expression is nested too deeply, in parenthesis or
+    /// operators, to be parsed without risking a stack overflow
+    TooDeep { offset: usize },
+}
+
+impl ParseError {
+    /// the byte offset in the input of the problem
+    pub fn offset(self) -> usize {
+        match self {
+            Self::UnexpectedAtom { offset }
+            | Self::UnexpectedOperator { offset }
+            | Self::UnexpectedOpeningPar { offset }
+            | Self::UnexpectedClosingPar { offset }
+            | Self::UnclosedQuote { offset }
+            | Self::InvalidAtom { offset }
+            | Self::UnexpectedEnd { offset }
+            | Self::TooDeep { offset } => offset,
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let problem = match self {
+            Self::UnexpectedAtom { .. } => "unexpected atom",
+            Self::UnexpectedOperator { .. } => "unexpected operator",
+            Self::UnexpectedOpeningPar { .. } => "unexpected opening parenthesis",
+            Self::UnexpectedClosingPar { .. } => "unexpected closing parenthesis",
+            Self::UnclosedQuote { .. } => "unclosed quote",
+            Self::InvalidAtom { .. } => "invalid atom",
+            Self::UnexpectedEnd { .. } => "unexpected end of input",
+            Self::TooDeep { .. } => "expression nested too deeply",
+        };
+        write!(f, "{} at offset {}", problem, self.offset())
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseError {}
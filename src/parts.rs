@@ -0,0 +1,138 @@
+use {crate::*, core::fmt};
+
+/// The raw content of a tree, as given by `BeTree::into_parts`
+/// and accepted by `BeTree::from_parts`.
+///
+/// The atoms and nodes are arenas: children refer to them by index.
+/// They may contain elements which aren't reachable from the head
+/// (for example after edits).
+#[derive(Debug, Clone, PartialEq)]
+pub struct BeTreeParts<Op, Atom> {
+    pub atoms: Vec<Atom>,
+    pub nodes: Vec<Node<Op>>,
+    pub head: NodeId,
+}
+
+/// check the links of the nodes reachable from the head, and return
+/// the number of operators of those nodes
+pub(crate) fn check_reachable_structure<Op>(
+    atom_count: usize,
+    nodes: &[Node<Op>],
+    head: NodeId,
+) -> Result<usize, StructureError>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+{
+    if nodes.is_empty() {
+        return Err(StructureError::NoNode);
+    }
+    if head.index() >= nodes.len() {
+        return Err(StructureError::UnknownNode(head));
+    }
+    if nodes[head].parent().is_some() {
+        return Err(StructureError::WrongParent(head));
+    }
+    let mut visited = vec![false; nodes.len()];
+    let mut op_count = 0;
+    let mut stack = vec![head];
+    while let Some(node_idx) = stack.pop() {
+        if visited[node_idx] {
+            return Err(StructureError::MultipleLinks(node_idx));
+        }
+        visited[node_idx] = true;
+        let node = &nodes[node_idx];
+        if node.operator.is_some() {
+            op_count += 1;
+        }
+        for child in [node.left(), node.right()] {
+            match child {
+                Child::None => {}
+                Child::Atom(atom_idx) => {
+                    if atom_idx.index() >= atom_count {
+                        return Err(StructureError::UnknownAtom(atom_idx));
+                    }
+                }
+                Child::Node(child_idx) => {
+                    if child_idx.index() >= nodes.len() {
+                        return Err(StructureError::UnknownNode(child_idx));
+                    }
+                    if nodes[child_idx].parent() != Some(node_idx) {
+                        return Err(StructureError::WrongParent(child_idx));
+                    }
+                    stack.push(child_idx);
+                }
+            }
+        }
+    }
+    Ok(op_count)
+}
+
+/// Decomposition and reconstruction
+impl<Op, Atom> BeTree<Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone,
+{
+    /// take the atoms, the nodes and the head of the tree
+    pub fn into_parts(self) -> BeTreeParts<Op, Atom> {
+        BeTreeParts {
+            atoms: self.atoms.into_iter().collect(),
+            nodes: self.nodes.into_iter().collect(),
+            head: self.head,
+        }
+    }
+
+    /// build a complete tree from its parts, after having checked
+    /// the structure reachable from the head: links must point to
+    /// existing nodes and atoms, there must be no cycle, and the parent
+    /// of every node must be the node having it as child (the head
+    /// having no parent).
+    ///
+    /// Nodes which aren't reachable from the head aren't checked.
+    ///
+    /// ```
+    /// use bet::*;
+    ///
+    /// // A & !B
+    /// let mut and = Node::new(Some('&'), false);
+    /// and.set_left(Child::Atom(AtomId::new(0)));
+    /// and.set_right(Child::Node(NodeId::new(1)));
+    /// let mut not = Node::new(Some('!'), true);
+    /// not.set_left(Child::Atom(AtomId::new(1)));
+    /// not.set_parent(Some(NodeId::new(0)));
+    /// let expr = BeTree::from_parts(BeTreeParts {
+    ///     atoms: vec![true, false],
+    ///     nodes: vec![and, not],
+    ///     head: NodeId::new(0),
+    /// })
+    /// .unwrap();
+    /// let value = expr.eval(
+    ///     |&atom| atom,
+    ///     |&op, a, b| match (op, b) {
+    ///         ('&', Some(b)) => a & b,
+    ///         _ => !a,
+    ///     },
+    ///     |_, _| false,
+    /// );
+    /// assert_eq!(value, Some(true));
+    /// ```
+    pub fn from_parts(parts: BeTreeParts<Op, Atom>) -> Result<Self, StructureError> {
+        let BeTreeParts { atoms, nodes, head } = parts;
+        let op_count = check_reachable_structure(atoms.len(), &nodes, head)?;
+        let last_pushed = if atoms.is_empty() {
+            TokenType::Nothing
+        } else {
+            TokenType::ClosingPar
+        };
+        Ok(Self {
+            atoms: atoms.into_iter().collect(),
+            nodes: nodes.into_iter().collect(),
+            head,
+            tail: head,
+            last_pushed,
+            op_count,
+            openness: 0,
+            epoch: 0,
+        })
+    }
+}
@@ -0,0 +1,147 @@
+use {
+    crate::*,
+    std::{collections::HashMap, fmt},
+};
+
+/// An atom of a pattern, as given to `find_matches`
+#[derive(Debug, Clone, PartialEq)]
+pub enum PatternAtom<Atom> {
+    /// Match an atom equal to this one
+    Exact(Atom),
+    /// Match any atom
+    AnyAtom,
+    /// Match any subexpression (atom or node), which is captured
+    /// with this name. When the same name is used several times in
+    /// a pattern, all occurrences must match equal subexpressions.
+    AnySubtree(String),
+}
+
+/// An occurrence of a pattern in a tree
+#[derive(Debug, Clone, PartialEq)]
+pub struct Match {
+    /// The node at the root of the matched subexpression
+    pub node: NodeId,
+    /// The subexpressions captured by the `AnySubtree` wildcards
+    pub bindings: HashMap<String, Child>,
+}
+
+impl Match {
+    /// the subexpression captured with the given name
+    pub fn binding(&self, name: &str) -> Option<Child> {
+        self.bindings.get(name).copied()
+    }
+}
+
+/// Pattern matching
+impl<Op, Atom> BeTree<Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone + PartialEq,
+{
+    /// tell whether two subexpressions of the tree are equal,
+    /// groups being ignored
+    pub(crate) fn same_subtree(&self, a: Child, b: Child) -> bool {
+        self.same_child_as(a, self, b)
+    }
+
+    /// try to match the child with a part of the pattern, filling
+    /// the bindings
+    fn match_child(
+        &self,
+        child: Child,
+        pattern: &BeTree<Op, PatternAtom<Atom>>,
+        pattern_child: Child,
+        bindings: &mut HashMap<String, Child>,
+    ) -> bool {
+        let child = self.skip_groups(child);
+        match pattern.skip_groups(pattern_child) {
+            Child::None => child.is_none(),
+            Child::Atom(pattern_atom_idx) => match &pattern.atoms[pattern_atom_idx] {
+                PatternAtom::Exact(atom) => match child {
+                    Child::Atom(atom_idx) => &self.atoms[atom_idx] == atom,
+                    _ => false,
+                },
+                PatternAtom::AnyAtom => matches!(child, Child::Atom(_)),
+                PatternAtom::AnySubtree(name) => {
+                    if child.is_none() {
+                        return false;
+                    }
+                    if let Some(&bound) = bindings.get(name) {
+                        return self.same_subtree(bound, child);
+                    }
+                    bindings.insert(name.clone(), child);
+                    true
+                }
+            },
+            Child::Node(pattern_node_idx) => {
+                let Child::Node(node_idx) = child else {
+                    return false;
+                };
+                let (node, pattern_node) =
+                    (&self.nodes[node_idx], &pattern.nodes[pattern_node_idx]);
+                node.operator == pattern_node.operator
+                    && node.unary == pattern_node.unary
+                    && self.match_child(node.left(), pattern, pattern_node.left(), bindings)
+                    && self.match_child(node.right(), pattern, pattern_node.right(), bindings)
+            }
+        }
+    }
+
+    /// try to match the pattern at the given node
+    pub(crate) fn match_at(
+        &self,
+        node_idx: NodeId,
+        pattern: &BeTree<Op, PatternAtom<Atom>>,
+    ) -> Option<Match> {
+        let mut bindings = HashMap::new();
+        if self.match_child(
+            Child::Node(node_idx),
+            pattern,
+            Child::Node(pattern.head),
+            &mut bindings,
+        ) {
+            Some(Match {
+                node: node_idx,
+                bindings,
+            })
+        } else {
+            None
+        }
+    }
+
+    /// find all the occurrences of a pattern in the expression.
+    ///
+    /// The pattern is an expression whose atoms may be wildcards (see
+    /// `PatternAtom`). For example `!(x | y)`, with `x` and `y` being
+    /// `AnySubtree` wildcards, finds all negations of a disjunction, while
+    /// `x & x` finds all conjunctions of two equal subexpressions.
+    ///
+    /// Operators and their arity must be equal. Groups (i.e. parenthesis)
+    /// are ignored, both in the pattern and in the expression. As matches
+    /// are reported at nodes with an operator, a pattern made of a single
+    /// atom matches nothing unless it's an `AnySubtree` wildcard.
+    ///
+    /// All matches are returned, in pre-order, even when they overlap
+    /// (for example `x & y` matches twice in `a & b & c`).
+    pub fn find_matches(&self, pattern: &BeTree<Op, PatternAtom<Atom>>) -> Vec<Match> {
+        let mut matches = Vec::new();
+        if self.is_empty() || pattern.is_empty() {
+            return matches;
+        }
+        let mut stack = vec![self.head];
+        while let Some(node_idx) = stack.pop() {
+            let node = &self.nodes[node_idx];
+            if node.operator.is_some() {
+                if let Some(m) = self.match_at(node_idx, pattern) {
+                    matches.push(m);
+                }
+            }
+            for child in [node.right(), node.left()] {
+                if let Child::Node(child_idx) = child {
+                    stack.push(child_idx);
+                }
+            }
+        }
+        matches
+    }
+}
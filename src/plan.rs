@@ -0,0 +1,115 @@
+use {crate::*, core::fmt};
+
+/// The limits checked by `BeTree::plan_check`, `None` meaning
+/// there's no limit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PlanLimits {
+    /// The maximal number of atom evaluations
+    pub max_atom_count: Option<usize>,
+    /// The maximal depth of the tree, which is the depth of the
+    /// recursion of the evaluation
+    pub max_depth: Option<usize>,
+    /// The maximal weight of the distinct atoms
+    pub max_total_weight: Option<u64>,
+    /// The maximal weight of all atom evaluations, when nothing
+    /// is skipped by short-circuits
+    pub max_worst_case_weight: Option<u64>,
+}
+
+/// The estimated work of the evaluation of an expression, as
+/// given by `BeTree::plan_check`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PlanSummary {
+    /// The number of atom evaluations, when nothing is skipped by
+    /// short-circuits (an atom linked several times, for example
+    /// after `dedup_atoms`, is counted each time)
+    pub atom_count: usize,
+    /// The maximal depth of a node or atom, the head being at depth 0
+    /// (see `BeTree::depth`)
+    pub depth: usize,
+    /// The sum of the weights of the distinct atoms
+    pub total_weight: u64,
+    /// The sum of the weights of all atom evaluations, when nothing
+    /// is skipped by short-circuits
+    pub worst_case_weight: u64,
+}
+
+impl PlanSummary {
+    fn check(self, limits: &PlanLimits) -> Result<Self, PlanRejected> {
+        let checks = [
+            (
+                PlanLimit::AtomCount,
+                self.atom_count as u64,
+                limits.max_atom_count.map(|max| max as u64),
+            ),
+            (
+                PlanLimit::Depth,
+                self.depth as u64,
+                limits.max_depth.map(|max| max as u64),
+            ),
+            (
+                PlanLimit::TotalWeight,
+                self.total_weight,
+                limits.max_total_weight,
+            ),
+            (
+                PlanLimit::WorstCaseWeight,
+                self.worst_case_weight,
+                limits.max_worst_case_weight,
+            ),
+        ];
+        for (limit, value, max) in checks {
+            if let Some(max) = max {
+                if value > max {
+                    return Err(PlanRejected { limit, value, max });
+                }
+            }
+        }
+        Ok(self)
+    }
+}
+
+/// Evaluation planning
+impl<Op, Atom> BeTree<Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone,
+{
+    /// estimate the work of evaluating the expression, without
+    /// evaluating it, and check it against the limits.
+    ///
+    /// `weights` gives the cost of evaluating an atom (for example 100
+    /// for a regular expression and 1 for an equality). It's called
+    /// once per distinct reachable atom.
+    ///
+    /// The limits are checked in the order of the fields of `PlanLimits`,
+    /// the first exceeded one being returned.
+    pub fn plan_check<W>(
+        &self,
+        weights: W,
+        limits: &PlanLimits,
+    ) -> Result<PlanSummary, PlanRejected>
+    where
+        W: Fn(&Atom) -> u64,
+    {
+        let mut atom_weights: Vec<Option<u64>> = vec![None; self.atoms.len()];
+        let mut summary = PlanSummary {
+            depth: self.depth(),
+            ..Default::default()
+        };
+        for (atom_id, atom) in self.iter_atoms_in_tree() {
+            let weight = match atom_weights[atom_id.index()] {
+                Some(weight) => weight,
+                None => {
+                    let weight = weights(atom);
+                    atom_weights[atom_id.index()] = Some(weight);
+                    summary.total_weight = summary.total_weight.saturating_add(weight);
+                    weight
+                }
+            };
+            summary.atom_count += 1;
+            summary.worst_case_weight = summary.worst_case_weight.saturating_add(weight);
+        }
+        summary.check(limits)
+    }
+}
@@ -0,0 +1,57 @@
+use core::fmt;
+
+/// A limit of `PlanLimits`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum PlanLimit {
+    AtomCount,
+    Depth,
+    TotalWeight,
+    WorstCaseWeight,
+}
+
+impl fmt::Display for PlanLimit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::AtomCount => write!(f, "atom count"),
+            Self::Depth => write!(f, "depth"),
+            Self::TotalWeight => write!(f, "total weight"),
+            Self::WorstCaseWeight => write!(f, "worst case weight"),
+        }
+    }
+}
+
+/// Error returned by `BeTree::plan_check` when the expression
+/// exceeds one of the limits
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PlanRejected {
+    /// The exceeded limit
+    pub limit: PlanLimit,
+    /// The measure of the expression
+    pub value: u64,
+    /// The maximum allowed by the limit
+    pub max: u64,
+}
+
+impl PlanRejected {
+    /// by how much the limit is exceeded
+    pub fn excess(&self) -> u64 {
+        self.value - self.max
+    }
+}
+
+impl fmt::Display for PlanRejected {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} exceeds the limit of {} by {}",
+            self.limit,
+            self.value,
+            self.max,
+            self.excess(),
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for PlanRejected {}
@@ -0,0 +1,98 @@
+use {crate::*, core::fmt};
+
+/// Whether an atom occurs positively (under an even number of negations)
+/// or negatively in an expression
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Polarity {
+    Positive,
+    Negative,
+    /// The atom is under an operator, like a XOR, for which there's
+    /// no polarity
+    Mixed,
+}
+
+impl Polarity {
+    /// the opposite polarity (a mixed polarity stays mixed)
+    pub fn flip(self) -> Self {
+        match self {
+            Self::Positive => Self::Negative,
+            Self::Negative => Self::Positive,
+            Self::Mixed => Self::Mixed,
+        }
+    }
+}
+
+/// How an operator changes the polarity of its operands,
+/// for `BeTree::atom_polarities_with`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OperatorPolarity {
+    /// The polarity of the operands is kept (e.g. AND, OR)
+    Keep,
+    /// The polarity of the operands is flipped (e.g. NOT)
+    Flip,
+    /// The polarity of the left operand is flipped, the right one being
+    /// kept (e.g. IMPLIES, as `a => b` is `!a | b`)
+    FlipLeft,
+    /// The polarity of the right operand is flipped, the left one being
+    /// kept (e.g. a "but not" operator)
+    FlipRight,
+    /// The operands have no defined polarity (e.g. XOR)
+    Scramble,
+}
+
+/// Polarity analysis
+impl<Op, Atom> BeTree<Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone,
+{
+    /// give the polarity of every reachable atom, in tree order, given
+    /// the way each operator changes the polarity of its operands.
+    pub fn atom_polarities_with<F>(&self, operator_polarity: F) -> Vec<(AtomId, Polarity)>
+    where
+        F: Fn(&Op) -> OperatorPolarity,
+    {
+        let mut polarities = Vec::new();
+        let mut stack = vec![(Child::Node(self.head), Polarity::Positive)];
+        while let Some((child, polarity)) = stack.pop() {
+            match child {
+                Child::None => {}
+                Child::Atom(atom_idx) => {
+                    polarities.push((atom_idx, polarity));
+                }
+                Child::Node(node_idx) => {
+                    let node = &self.nodes[node_idx];
+                    let (left, right) = match node.operator.as_ref().map(&operator_polarity) {
+                        None | Some(OperatorPolarity::Keep) => (polarity, polarity),
+                        Some(OperatorPolarity::Flip) => (polarity.flip(), polarity.flip()),
+                        Some(OperatorPolarity::FlipLeft) => (polarity.flip(), polarity),
+                        Some(OperatorPolarity::FlipRight) => (polarity, polarity.flip()),
+                        Some(OperatorPolarity::Scramble) => (Polarity::Mixed, Polarity::Mixed),
+                    };
+                    stack.push((node.right(), right));
+                    stack.push((node.left(), left));
+                }
+            }
+        }
+        polarities
+    }
+
+    /// give the polarity of every reachable atom, in tree order: positive
+    /// when the atom is under an even number of negating operators,
+    /// negative otherwise.
+    ///
+    /// Use `atom_polarities_with` when some operators negate only one of
+    /// their operands, or scramble the polarity.
+    pub fn atom_polarities<F>(&self, negating: F) -> Vec<(AtomId, Polarity)>
+    where
+        F: Fn(&Op) -> bool,
+    {
+        self.atom_polarities_with(|operator| {
+            if negating(operator) {
+                OperatorPolarity::Flip
+            } else {
+                OperatorPolarity::Keep
+            }
+        })
+    }
+}
@@ -0,0 +1,298 @@
+use {
+    crate::*,
+    core::{fmt, mem},
+};
+
+/// An operand of a chain of binary operations read from left to right:
+/// unary operators applied to a primary, which is an atom or a barrier
+/// (a group, or a parenthesized or grafted subexpression)
+struct Operand {
+    /// the unary operators, the outermost first
+    unaries: Vec<NodeId>,
+    primary: Child,
+}
+
+/// The operands of a chain and the binary operators between them,
+/// as they were pushed
+struct Chain {
+    operands: Vec<Operand>,
+    binaries: Vec<NodeId>,
+}
+
+/// The position of the precedence climbing in a chain
+#[derive(Default)]
+struct Cursor {
+    operand: usize,
+    unary: usize,
+    binary: usize,
+}
+
+/// An operator of the chain waiting for the operation it applies to
+/// (for a unary one) or for its right operand (for a binary one),
+/// with the minimal precedence of the operation it's part of
+enum Pending {
+    Unary {
+        node_idx: NodeId,
+        min_precedence: u16,
+    },
+    Binary {
+        node_idx: NodeId,
+        left: Child,
+        left_is_operand: bool,
+        min_precedence: u16,
+    },
+}
+
+/// Reassociation according to the operator precedences
+impl<Op, Atom> BeTree<Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone,
+{
+    /// tell whether the node is a binary operation with both operands
+    fn is_complete_binary(&self, node_idx: NodeId) -> bool {
+        let node = &self.nodes[node_idx];
+        node.operator.is_some() && !node.unary && node.left().is_some() && node.right().is_some()
+    }
+
+    fn precedence_of<P>(&self, node_idx: NodeId, precedence: &P) -> u16
+    where
+        P: Fn(&Op) -> u8,
+    {
+        self.nodes[node_idx]
+            .operator
+            .as_ref()
+            .map_or(0, |op| precedence(op).into())
+    }
+
+    /// read the operand, restructuring the barrier of its primary
+    fn read_operand<P, R>(&mut self, mut child: Child, precedence: &P, right_assoc: &R) -> Operand
+    where
+        P: Fn(&Op) -> u8,
+        R: Fn(&Op) -> bool,
+    {
+        let mut unaries = Vec::new();
+        while let Child::Node(node_idx) = child {
+            let node = &self.nodes[node_idx];
+            if node.operator.is_none() || !node.unary {
+                break;
+            }
+            unaries.push(node_idx);
+            child = node.left();
+        }
+        let primary = match child {
+            Child::Node(node_idx) if self.nodes[node_idx].operator.is_none() => {
+                let content = self.nodes[node_idx].left();
+                let content = self.restructure(content, precedence, right_assoc);
+                self.link_child(node_idx, Side::Left, content);
+                child
+            }
+            Child::Node(node_idx) if self.is_complete_binary(node_idx) => {
+                let root = match self.restructure(child, precedence, right_assoc) {
+                    // a unary operator would take the operations
+                    // following the barrier, so we keep it in a group
+                    root @ Child::Node(root_idx) if self.nodes[root_idx].unary => self.group(root),
+                    root => root,
+                };
+                // the parenthesis now surround the new root
+                if let Child::Node(root_idx) = root {
+                    self.nodes[root_idx].explicit = mem::take(&mut self.nodes[node_idx].explicit);
+                }
+                root
+            }
+            _ => child,
+        };
+        Operand { unaries, primary }
+    }
+
+    /// read the chain of binary operations starting at the child.
+    ///
+    /// As pushing a binary operator after a complete operation makes it
+    /// the left operand of a new node, the left operands of a chain are
+    /// its operations, unless they're explicit groups: a parenthesized
+    /// operation is the node of its group.
+    fn read_chain<P, R>(&mut self, child: Child, precedence: &P, right_assoc: &R) -> Chain
+    where
+        P: Fn(&Op) -> u8,
+        R: Fn(&Op) -> bool,
+    {
+        let mut binaries = Vec::new();
+        let mut rights = Vec::new();
+        let mut first = child;
+        while let Child::Node(node_idx) = first {
+            if !self.is_complete_binary(node_idx) {
+                break;
+            }
+            binaries.push(node_idx);
+            rights.push(self.nodes[node_idx].right());
+            first = self.nodes[node_idx].left();
+            if let Child::Node(left_idx) = first {
+                if self.nodes[left_idx].explicit {
+                    break;
+                }
+            }
+        }
+        binaries.reverse();
+        let mut operands = vec![self.read_operand(first, precedence, right_assoc)];
+        for right in rights.into_iter().rev() {
+            operands.push(self.read_operand(right, precedence, right_assoc));
+        }
+        Chain { operands, binaries }
+    }
+
+    /// put the operand in a group if it's an operation which, as left
+    /// operand of a node, would be read as the start of its chain
+    fn keep_barrier(&mut self, operand: Child) -> Child {
+        match operand {
+            Child::Node(operand_idx)
+                if self.is_complete_binary(operand_idx) && !self.nodes[operand_idx].explicit =>
+            {
+                self.group(operand)
+            }
+            _ => operand,
+        }
+    }
+
+    /// add a group node around the child, and return it
+    fn group(&mut self, child: Child) -> Child {
+        let group_idx = self.store_node(Node::new(None, false));
+        self.link_child(group_idx, Side::Left, child);
+        Child::Node(group_idx)
+    }
+
+    /// link the operands of the chain according to the precedences of
+    /// their operators, and return the root
+    ///
+    /// The operations waiting for their operand are kept in a stack, so
+    /// that long chains don't overflow the call stack.
+    fn climb<P, R>(&mut self, chain: &Chain, precedence: &P, right_assoc: &R) -> Child
+    where
+        P: Fn(&Op) -> u8,
+        R: Fn(&Op) -> bool,
+    {
+        let mut cursor = Cursor::default();
+        let mut pending = Vec::new();
+        let mut min_precedence = 0;
+        loop {
+            // a unary operator takes the binary operators of a greater precedence
+            let operand = &chain.operands[cursor.operand];
+            if let Some(&node_idx) = operand.unaries.get(cursor.unary) {
+                cursor.unary += 1;
+                pending.push(Pending::Unary {
+                    node_idx,
+                    min_precedence,
+                });
+                min_precedence = self.precedence_of(node_idx, precedence) + 1;
+                continue;
+            }
+            cursor.operand += 1;
+            cursor.unary = 0;
+            let mut left = operand.primary;
+            let mut left_is_operand = true;
+            loop {
+                if let Some(&node_idx) = chain.binaries.get(cursor.binary) {
+                    let op_precedence = self.precedence_of(node_idx, precedence);
+                    if op_precedence >= min_precedence {
+                        cursor.binary += 1;
+                        pending.push(Pending::Binary {
+                            node_idx,
+                            left,
+                            left_is_operand,
+                            min_precedence,
+                        });
+                        min_precedence = match &self.nodes[node_idx].operator {
+                            Some(op) if right_assoc(op) => op_precedence,
+                            _ => op_precedence + 1,
+                        };
+                        break;
+                    }
+                }
+                // the operation of this precedence is complete
+                match pending.pop() {
+                    None => return left,
+                    Some(Pending::Unary {
+                        node_idx,
+                        min_precedence: previous,
+                    }) => {
+                        self.link_child(node_idx, Side::Left, left);
+                        left = Child::Node(node_idx);
+                        left_is_operand = true;
+                        min_precedence = previous;
+                    }
+                    Some(Pending::Binary {
+                        node_idx,
+                        left: binary_left,
+                        left_is_operand: binary_left_is_operand,
+                        min_precedence: previous,
+                    }) => {
+                        let binary_left = if binary_left_is_operand {
+                            self.keep_barrier(binary_left)
+                        } else {
+                            binary_left
+                        };
+                        self.link_child(node_idx, Side::Left, binary_left);
+                        self.link_child(node_idx, Side::Right, left);
+                        left = Child::Node(node_idx);
+                        left_is_operand = false;
+                        min_precedence = previous;
+                    }
+                }
+            }
+        }
+    }
+
+    /// restructure the subexpression at the child, and return its new root
+    fn restructure<P, R>(&mut self, child: Child, precedence: &P, right_assoc: &R) -> Child
+    where
+        P: Fn(&Op) -> u8,
+        R: Fn(&Op) -> bool,
+    {
+        let chain = self.read_chain(child, precedence, right_assoc);
+        self.climb(&chain, precedence, right_assoc)
+    }
+
+    /// reassociate the operations of the reachable tree according to
+    /// the precedences of their operators (the higher, the tighter),
+    /// as if the pushed tokens had been parsed with these precedences
+    /// instead of from left to right.
+    ///
+    /// For example, with `&` having a higher precedence than `|`,
+    /// `A | B & C`, pushed as `(A | B) & C`, becomes `A | (B & C)`.
+    ///
+    /// Operators of the same precedence are grouped from left to right,
+    /// unless `right_assoc` is true for them. Parenthesized groups (see
+    /// `was_explicit_group`), groups without operator, and right operands
+    /// which are operations (for example grafted by `combine`) are
+    /// restructured independently. Any other operation in a left operand
+    /// is read as pushed before its parent, so the result doesn't depend
+    /// on the order of the nodes (it's the same after `normalize`).
+    ///
+    /// A unary operator applies to the operations of a higher precedence
+    /// following it, so it binds tighter than the binary operators when
+    /// given a greater or equal precedence: `!A & B` is `(!A) & B` if `!`
+    /// doesn't have a lower precedence than `&`, and `!(A & B)` otherwise.
+    ///
+    /// Node and atom ids are preserved, only the links between nodes
+    /// are changed (group nodes may be added so that grafted operations
+    /// stay barriers when the precedences are applied again, and the
+    /// explicit group of a parenthesized operation moves to its new root).
+    pub fn apply_precedence<P, R>(&mut self, precedence: P, right_assoc: R)
+    where
+        P: Fn(&Op) -> u8,
+        R: Fn(&Op) -> bool,
+    {
+        self.bump_epoch();
+        let head = self.head;
+        if self.nodes[head].operator.is_none() {
+            let content = self.nodes[head].left();
+            let content = self.restructure(content, &precedence, &right_assoc);
+            self.link_child(head, Side::Left, content);
+        } else if let Child::Node(new_head) =
+            self.restructure(Child::Node(head), &precedence, &right_assoc)
+        {
+            self.nodes[new_head].set_parent(None);
+            self.head = new_head;
+        }
+        self.fix_tail();
+    }
+}
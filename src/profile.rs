@@ -0,0 +1,258 @@
+use {
+    crate::*,
+    core::sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// The counters of an operation
+#[derive(Debug, Default)]
+struct NodeCounters {
+    hits: AtomicUsize,
+    short_circuits: AtomicUsize,
+}
+
+/// Counters accumulated over evaluations of a tree with
+/// `BeTree::eval_profiled`, telling how often each operation
+/// short-circuited and each atom was evaluated.
+///
+/// The counters are atomic, so a profile can be shared between
+/// threads evaluating the same tree, for example with `std::thread::scope`.
+///
+/// The crate has no parallel evaluation and doesn't reorder operands
+/// itself: the `ProfileReport` is data for the caller deciding, for
+/// example, which operand of a commutative operation should come first.
+#[derive(Debug)]
+pub struct Profile {
+    evaluations: AtomicUsize,
+    nodes: Vec<NodeCounters>,
+    atoms: Vec<AtomicUsize>,
+    /// the operation nodes reachable from the head when the profile was made
+    reachable_nodes: Vec<NodeId>,
+    /// the atoms reachable from the head when the profile was made
+    reachable_atoms: Vec<AtomId>,
+}
+
+/// The profile of an operation, in a `ProfileReport`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NodeProfile {
+    pub node: NodeId,
+    /// The number of evaluations in which the operation was reached
+    pub hits: usize,
+    /// The number of evaluations in which the operation wasn't reached
+    pub skips: usize,
+    /// The number of times the right operand was skipped because the
+    /// left one was enough
+    pub short_circuits: usize,
+}
+
+impl NodeProfile {
+    /// the part of the hits in which the operation short-circuited,
+    /// or None if it was never reached
+    pub fn short_circuit_rate(&self) -> Option<f64> {
+        (self.hits > 0).then(|| self.short_circuits as f64 / self.hits as f64)
+    }
+}
+
+/// The profile of an atom, in a `ProfileReport`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AtomProfile {
+    pub atom: AtomId,
+    /// The number of evaluations in which the atom was evaluated
+    pub hits: usize,
+    /// The number of evaluations in which the atom wasn't evaluated
+    pub skips: usize,
+}
+
+impl AtomProfile {
+    /// the part of the evaluations in which the atom was evaluated,
+    /// or None if there was no evaluation
+    pub fn hit_rate(&self) -> Option<f64> {
+        let evaluations = self.hits + self.skips;
+        (evaluations > 0).then(|| self.hits as f64 / evaluations as f64)
+    }
+}
+
+/// A snapshot of the counters of a `Profile`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProfileReport {
+    /// The number of profiled evaluations
+    pub evaluations: usize,
+    /// The operations reachable from the head, ordered by id
+    pub nodes: Vec<NodeProfile>,
+    /// The atoms reachable from the head, ordered by id
+    pub atoms: Vec<AtomProfile>,
+}
+
+impl ProfileReport {
+    /// the profile of the operation of the node, if it's reachable
+    pub fn node(&self, node: NodeId) -> Option<&NodeProfile> {
+        self.nodes.iter().find(|profile| profile.node == node)
+    }
+    /// the profile of the atom, if it's reachable
+    pub fn atom(&self, atom: AtomId) -> Option<&AtomProfile> {
+        self.atoms.iter().find(|profile| profile.atom == atom)
+    }
+}
+
+impl Profile {
+    /// make a profile with all counters at zero for the tree, whose
+    /// structure must not change while it's profiled
+    pub fn new<Op, Atom, Meta>(tree: &BeTree<Op, Atom, Meta>) -> Self {
+        let mut reachable_nodes = Vec::new();
+        let mut reachable_atoms = Vec::new();
+        let mut stack = vec![Child::Node(tree.head)];
+        while let Some(child) = stack.pop() {
+            match child {
+                Child::None => {}
+                Child::Atom(atom_idx) => reachable_atoms.push(atom_idx),
+                Child::Node(node_idx) => {
+                    let node = &tree.nodes[node_idx];
+                    if node.operator.is_some() {
+                        reachable_nodes.push(node_idx);
+                    }
+                    stack.push(node.right());
+                    stack.push(node.left());
+                }
+            }
+        }
+        reachable_nodes.sort_unstable_by_key(|node_idx| node_idx.index());
+        reachable_atoms.sort_unstable_by_key(|atom_idx| atom_idx.index());
+        Self {
+            evaluations: AtomicUsize::new(0),
+            nodes: tree.nodes.iter().map(|_| NodeCounters::default()).collect(),
+            atoms: tree.atoms.iter().map(|_| AtomicUsize::new(0)).collect(),
+            reachable_nodes,
+            reachable_atoms,
+        }
+    }
+
+    /// the number of profiled evaluations
+    pub fn evaluations(&self) -> usize {
+        self.evaluations.load(Ordering::Relaxed)
+    }
+
+    /// report the counters of the reachable operations and atoms
+    pub fn report(&self) -> ProfileReport {
+        let evaluations = self.evaluations();
+        let nodes = self
+            .reachable_nodes
+            .iter()
+            .map(|&node| {
+                let counters = &self.nodes[node.index()];
+                let hits = counters.hits.load(Ordering::Relaxed);
+                NodeProfile {
+                    node,
+                    hits,
+                    skips: evaluations.saturating_sub(hits),
+                    short_circuits: counters.short_circuits.load(Ordering::Relaxed),
+                }
+            })
+            .collect();
+        let atoms = self
+            .reachable_atoms
+            .iter()
+            .map(|&atom| {
+                let hits = self.atoms[atom.index()].load(Ordering::Relaxed);
+                AtomProfile {
+                    atom,
+                    hits,
+                    skips: evaluations.saturating_sub(hits),
+                }
+            })
+            .collect();
+        ProfileReport {
+            evaluations,
+            nodes,
+            atoms,
+        }
+    }
+
+    /// set all counters back to zero
+    pub fn reset(&self) {
+        self.evaluations.store(0, Ordering::Relaxed);
+        for counters in &self.nodes {
+            counters.hits.store(0, Ordering::Relaxed);
+            counters.short_circuits.store(0, Ordering::Relaxed);
+        }
+        for counter in &self.atoms {
+            counter.store(0, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Profiled evaluation
+impl<Op, Atom, Meta> BeTree<Op, Atom, Meta> {
+    fn eval_child_profiled<R, EvalAtom, EvalOp, ShortCircuit>(
+        &self,
+        profile: &Profile,
+        eval_atom: &EvalAtom,
+        eval_op: &EvalOp,
+        short_circuit: &ShortCircuit,
+        child: Child,
+    ) -> Option<R>
+    where
+        EvalAtom: Fn(&Atom) -> R,
+        EvalOp: Fn(&Op, R, Option<R>) -> R,
+        ShortCircuit: Fn(&Op, &R) -> bool,
+    {
+        let node_idx = match child {
+            Child::None => return None,
+            Child::Atom(atom_idx) => {
+                if let Some(counter) = profile.atoms.get(atom_idx.index()) {
+                    counter.fetch_add(1, Ordering::Relaxed);
+                }
+                return Some(eval_atom(&self.atoms[atom_idx]));
+            }
+            Child::Node(node_idx) => node_idx,
+        };
+        let node = &self.nodes[node_idx];
+        let counters = profile.nodes.get(node_idx.index());
+        if node.operator.is_some() {
+            if let Some(counters) = counters {
+                counters.hits.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        let left_value =
+            self.eval_child_profiled(profile, eval_atom, eval_op, short_circuit, node.left());
+        let Some(op) = &node.operator else {
+            return left_value;
+        };
+        let left_value = left_value?;
+        if short_circuit(op, &left_value) {
+            if let Some(counters) = counters {
+                counters.short_circuits.fetch_add(1, Ordering::Relaxed);
+            }
+            return Some(left_value);
+        }
+        let right_value =
+            self.eval_child_profiled(profile, eval_atom, eval_op, short_circuit, node.right());
+        Some(eval_op(op, left_value, right_value))
+    }
+
+    /// evaluate the expression exactly like `eval`, counting in the
+    /// profile the operations reached, the short-circuits, and the
+    /// evaluated atoms.
+    ///
+    /// The profile should have been made for this tree with
+    /// `Profile::new`: nodes and atoms it doesn't know aren't counted.
+    pub fn eval_profiled<R, EvalAtom, EvalOp, ShortCircuit>(
+        &self,
+        profile: &Profile,
+        eval_atom: EvalAtom,
+        eval_op: EvalOp,
+        short_circuit: ShortCircuit,
+    ) -> Option<R>
+    where
+        EvalAtom: Fn(&Atom) -> R,
+        EvalOp: Fn(&Op, R, Option<R>) -> R,
+        ShortCircuit: Fn(&Op, &R) -> bool,
+    {
+        profile.evaluations.fetch_add(1, Ordering::Relaxed);
+        self.eval_child_profiled(
+            profile,
+            &eval_atom,
+            &eval_op,
+            &short_circuit,
+            Child::Node(self.head),
+        )
+    }
+}
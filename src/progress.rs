@@ -0,0 +1,138 @@
+use crate::*;
+
+/// The advancement of an evaluation, given by `BeTree::eval_with_progress`
+/// to its progress callback
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Progress {
+    /// The number of atoms evaluated so far
+    pub evaluated: usize,
+    /// The number of atoms which won't be evaluated because the operand
+    /// containing them was short-circuited
+    pub skipped: usize,
+    /// The number of atoms reachable from the head, computed before
+    /// the evaluation
+    pub total: usize,
+}
+
+impl Progress {
+    /// the number of atoms either evaluated or skipped
+    pub fn done(&self) -> usize {
+        self.evaluated + self.skipped
+    }
+}
+
+/// The progress of an evaluation, with the callback to notify
+struct ProgressTracker<F> {
+    progress: Progress,
+    on_progress: F,
+}
+
+/// Evaluation with progress notifications
+impl<Op, Atom, Meta> BeTree<Op, Atom, Meta> {
+    /// count the atoms reachable from the child
+    fn reachable_atom_count(&self, child: Child) -> usize {
+        let mut count = 0;
+        let mut stack = vec![child];
+        while let Some(child) = stack.pop() {
+            match child {
+                Child::None => {}
+                Child::Atom(_) => count += 1,
+                Child::Node(node_idx) => {
+                    let node = &self.nodes[node_idx];
+                    stack.push(node.right());
+                    stack.push(node.left());
+                }
+            }
+        }
+        count
+    }
+
+    fn eval_child_with_progress<R, EvalAtom, EvalOp, ShortCircuit, F>(
+        &self,
+        eval_atom: &EvalAtom,
+        eval_op: &EvalOp,
+        short_circuit: &ShortCircuit,
+        tracker: &mut ProgressTracker<F>,
+        child: Child,
+    ) -> Option<R>
+    where
+        EvalAtom: Fn(&Atom) -> R,
+        EvalOp: Fn(&Op, R, Option<R>) -> R,
+        ShortCircuit: Fn(&Op, &R) -> bool,
+        F: FnMut(Progress),
+    {
+        match child {
+            Child::None => None,
+            Child::Node(node_idx) => {
+                let node = &self.nodes[node_idx];
+                let left_value = self.eval_child_with_progress(
+                    eval_atom,
+                    eval_op,
+                    short_circuit,
+                    tracker,
+                    node.left(),
+                );
+                let Some(op) = &node.operator else {
+                    return left_value;
+                };
+                let left_value = left_value?;
+                if short_circuit(op, &left_value) {
+                    let skipped = self.reachable_atom_count(node.right());
+                    if skipped > 0 {
+                        tracker.progress.skipped += skipped;
+                        (tracker.on_progress)(tracker.progress);
+                    }
+                    return Some(left_value);
+                }
+                let right_value = self.eval_child_with_progress(
+                    eval_atom,
+                    eval_op,
+                    short_circuit,
+                    tracker,
+                    node.right(),
+                );
+                Some(eval_op(op, left_value, right_value))
+            }
+            Child::Atom(atom_idx) => {
+                let value = eval_atom(&self.atoms[atom_idx]);
+                tracker.progress.evaluated += 1;
+                (tracker.on_progress)(tracker.progress);
+                Some(value)
+            }
+        }
+    }
+
+    /// evaluate the expression exactly like `eval`, calling `on_progress`
+    /// after every atom evaluation and every short-circuit skipping
+    /// some atoms.
+    ///
+    /// When the evaluation is complete, the evaluated and skipped atoms
+    /// of the last progress add up to its total, which is the atom count
+    /// of `stats`.
+    ///
+    /// As it's a distinct function, there's no cost to progress tracking
+    /// in `eval`.
+    pub fn eval_with_progress<R, EvalAtom, EvalOp, ShortCircuit, F>(
+        &self,
+        eval_atom: EvalAtom,
+        eval_op: EvalOp,
+        short_circuit: ShortCircuit,
+        on_progress: F,
+    ) -> Option<R>
+    where
+        EvalAtom: Fn(&Atom) -> R,
+        EvalOp: Fn(&Op, R, Option<R>) -> R,
+        ShortCircuit: Fn(&Op, &R) -> bool,
+        F: FnMut(Progress),
+    {
+        let root = Child::Node(self.head);
+        let mut tracker = ProgressTracker {
+            progress: Progress {
+                total: self.reachable_atom_count(root),
+                ..Default::default()
+            },
+            on_progress,
+        };
+        self.eval_child_with_progress(&eval_atom, &eval_op, &short_circuit, &mut tracker, root)
+    }
+}
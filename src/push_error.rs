@@ -0,0 +1,31 @@
+use core::fmt;
+
+/// Error returned by `BeTree::push_operator_checked` when the arity
+/// declared by the operator doesn't match its position
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PushError {
+    /// A unary only operator was pushed after an operand, where a
+    /// binary operator is expected
+    UnaryInBinaryPosition,
+    /// A binary only operator was pushed where an operand is expected
+    /// (at the start of the expression or of a group, or after another
+    /// operator)
+    BinaryInUnaryPosition,
+}
+
+impl fmt::Display for PushError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnaryInBinaryPosition => {
+                write!(f, "unary operator where a binary operator is expected")
+            }
+            Self::BinaryInUnaryPosition => {
+                write!(f, "binary operator where an operand is expected")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for PushError {}
@@ -0,0 +1,115 @@
+use {crate::*, core::fmt};
+
+/// Where an atom is in the tree, as found by `BeTree::find_atom`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AtomLocation {
+    /// The id of the atom
+    pub atom: AtomId,
+    /// The node holding the atom
+    pub node: NodeId,
+    /// The nodes to go through from the head to reach the atom, with
+    /// the side taken in each of them. The last element is the node
+    /// holding the atom.
+    pub path: Vec<(NodeId, Side)>,
+}
+
+/// Search and queries
+impl<Op, Atom> BeTree<Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone,
+{
+    /// locate the atoms matching the predicate, in tree order,
+    /// stopping at the first one if `first_only`
+    fn locate_atoms<F>(&self, pred: F, first_only: bool) -> Vec<AtomLocation>
+    where
+        F: Fn(&Atom) -> bool,
+    {
+        let mut locations = Vec::new();
+        let mut path = Vec::new();
+        // the children to visit, with how they're linked, and the length
+        // of the path to their parent
+        let mut stack = vec![(Child::Node(self.head), None, 0)];
+        while let Some((child, link, depth)) = stack.pop() {
+            path.truncate(depth);
+            if let Some(link) = link {
+                path.push(link);
+            }
+            match child {
+                Child::None => {}
+                Child::Atom(atom_idx) => {
+                    if pred(&self.atoms[atom_idx]) {
+                        let (node, _) = path[path.len() - 1];
+                        locations.push(AtomLocation {
+                            atom: atom_idx,
+                            node,
+                            path: path.clone(),
+                        });
+                        if first_only {
+                            break;
+                        }
+                    }
+                }
+                Child::Node(node_idx) => {
+                    let node = &self.nodes[node_idx];
+                    for side in [Side::Right, Side::Left] {
+                        stack.push((node.child(side), Some((node_idx, side)), path.len()));
+                    }
+                }
+            }
+        }
+        locations
+    }
+
+    /// find the first atom, in tree order, matching the predicate, and
+    /// its location in the tree.
+    ///
+    /// Only the atoms reachable from the head are considered.
+    pub fn find_atom<F>(&self, pred: F) -> Option<AtomLocation>
+    where
+        F: Fn(&Atom) -> bool,
+    {
+        self.locate_atoms(pred, true).pop()
+    }
+
+    /// find all the atoms matching the predicate, in tree order,
+    /// and their locations in the tree.
+    ///
+    /// Only the atoms reachable from the head are considered.
+    pub fn find_atoms<F>(&self, pred: F) -> Vec<AtomLocation>
+    where
+        F: Fn(&Atom) -> bool,
+    {
+        self.locate_atoms(pred, false)
+    }
+
+    /// tell whether the operator is used in the expression
+    pub fn contains_operator(&self, operator: &Op) -> bool {
+        self.iter_nodes()
+            .any(|(_, node)| node.operator.as_ref() == Some(operator))
+    }
+
+    /// count the occurrences of the operator in the expression
+    pub fn count_operator(&self, operator: &Op) -> usize {
+        self.iter_nodes()
+            .filter(|(_, node)| node.operator.as_ref() == Some(operator))
+            .count()
+    }
+
+    /// tell whether an atom of the expression matches the predicate
+    pub fn any_atom<F>(&self, pred: F) -> bool
+    where
+        F: Fn(&Atom) -> bool,
+    {
+        self.iter_atoms_in_tree().any(|(_, atom)| pred(atom))
+    }
+
+    /// tell whether all atoms of the expression match the predicate
+    /// (which is true when there's no atom)
+    pub fn all_atoms<F>(&self, pred: F) -> bool
+    where
+        F: Fn(&Atom) -> bool,
+    {
+        self.iter_atoms_in_tree().all(|(_, atom)| pred(atom))
+    }
+}
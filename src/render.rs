@@ -0,0 +1,163 @@
+use {crate::*, core::fmt};
+
+/// What `BeTree::render` calls to write the parts of an expression.
+///
+/// Only atoms and operators must be rendered by implementations, the
+/// default methods giving spaces around binary operators, no space
+/// after unary operators, and `(`/`)` around groups.
+///
+/// As the syntax targeted by the renderer may give precedences to its
+/// operators, a binary operation which is the operand of another one
+/// is put in a group unless `needs_group` tells otherwise.
+pub trait Renderer<Op, Atom> {
+    /// write an atom
+    fn write_atom(&self, out: &mut dyn fmt::Write, atom: &Atom) -> fmt::Result;
+
+    /// write an operator, which may be unary or binary
+    fn write_operator(&self, out: &mut dyn fmt::Write, operator: &Op, unary: bool) -> fmt::Result;
+
+    /// write what separates the left operand from a binary operator
+    fn write_before_binary_operator(&self, out: &mut dyn fmt::Write) -> fmt::Result {
+        out.write_char(' ')
+    }
+
+    /// write what separates a binary operator from its right operand
+    fn write_after_binary_operator(&self, out: &mut dyn fmt::Write) -> fmt::Result {
+        out.write_char(' ')
+    }
+
+    /// write what separates a unary operator from its operand
+    fn write_after_unary_operator(&self, _out: &mut dyn fmt::Write) -> fmt::Result {
+        Ok(())
+    }
+
+    /// tell whether a binary operation must be put in a group when it's
+    /// the operand, on the given side, of another binary operation.
+    ///
+    /// The default is to group all right operands, and the left operands
+    /// whose operator is different, which is correct for left associative
+    /// operators whatever their precedences.
+    fn needs_group(&self, operator: &Op, operand_operator: &Op, side: Side) -> bool
+    where
+        Op: PartialEq,
+    {
+        side == Side::Right || operator != operand_operator
+    }
+
+    /// write the start of a group
+    fn write_group_start(&self, out: &mut dyn fmt::Write) -> fmt::Result {
+        out.write_char('(')
+    }
+
+    /// write the end of a group
+    fn write_group_end(&self, out: &mut dyn fmt::Write) -> fmt::Result {
+        out.write_char(')')
+    }
+}
+
+/// Rendering with a custom renderer
+impl<Op, Atom> BeTree<Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone,
+{
+    fn render_child<R>(&self, child: Child, renderer: &R, out: &mut dyn fmt::Write) -> fmt::Result
+    where
+        R: Renderer<Op, Atom>,
+    {
+        let node_idx = match child {
+            Child::None => return Ok(()),
+            Child::Atom(atom_idx) => return renderer.write_atom(out, &self.atoms[atom_idx]),
+            Child::Node(node_idx) => node_idx,
+        };
+        let node = &self.nodes[node_idx];
+        match &node.operator {
+            None => {
+                renderer.write_group_start(out)?;
+                self.render_child(node.left(), renderer, out)?;
+                renderer.write_group_end(out)
+            }
+            Some(op) if node.unary => {
+                renderer.write_operator(out, op, true)?;
+                if node.left().is_some() {
+                    renderer.write_after_unary_operator(out)?;
+                }
+                self.render_operand(op, true, node.left(), Side::Left, renderer, out)
+            }
+            Some(op) => {
+                self.render_operand(op, false, node.left(), Side::Left, renderer, out)?;
+                renderer.write_before_binary_operator(out)?;
+                renderer.write_operator(out, op, false)?;
+                if node.right().is_some() {
+                    renderer.write_after_binary_operator(out)?;
+                    self.render_operand(op, false, node.right(), Side::Right, renderer, out)?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// render the operand of an operation, in a group if the renderer
+    /// requires it or if it's a binary operation operand of a unary one
+    fn render_operand<R>(
+        &self,
+        operator: &Op,
+        unary: bool,
+        operand: Child,
+        side: Side,
+        renderer: &R,
+        out: &mut dyn fmt::Write,
+    ) -> fmt::Result
+    where
+        R: Renderer<Op, Atom>,
+    {
+        let needs_group = match operand {
+            Child::Node(node_idx) => {
+                let node = &self.nodes[node_idx];
+                match &node.operator {
+                    Some(operand_operator) if !node.unary => {
+                        unary || renderer.needs_group(operator, operand_operator, side)
+                    }
+                    _ => false,
+                }
+            }
+            _ => false,
+        };
+        if needs_group {
+            renderer.write_group_start(out)?;
+            self.render_child(operand, renderer, out)?;
+            renderer.write_group_end(out)
+        } else {
+            self.render_child(operand, renderer, out)
+        }
+    }
+
+    /// write the expression with the given renderer, for example to
+    /// produce a query in another syntax.
+    ///
+    /// Parenthesis of the expression are kept, and groups are added
+    /// where the structure requires them (see `Renderer::needs_group`).
+    pub fn render<R, W>(&self, renderer: &R, out: &mut W) -> fmt::Result
+    where
+        R: Renderer<Op, Atom>,
+        W: fmt::Write,
+    {
+        let head = &self.nodes[self.head];
+        let root = if head.operator.is_none() {
+            head.left()
+        } else {
+            Child::Node(self.head)
+        };
+        self.render_child(root, renderer, out)
+    }
+
+    /// render the expression into a string with the given renderer
+    pub fn to_string_with<R>(&self, renderer: &R) -> String
+    where
+        R: Renderer<Op, Atom>,
+    {
+        let mut s = String::new();
+        let _ = self.render(renderer, &mut s);
+        s
+    }
+}
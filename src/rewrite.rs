@@ -0,0 +1,547 @@
+use {crate::*, core::fmt};
+
+/// What to do with a negated atom when pushing down a unary
+/// operator with `push_down_unary`
+#[derive(Debug, Clone, PartialEq)]
+pub enum NegatedAtom<Atom> {
+    /// Replace the atom with an atom holding its negation
+    Replace(Atom),
+    /// Keep the unary operator above the atom
+    Keep,
+}
+
+/// Rewritings of the tree
+impl<Op, Atom> BeTree<Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone,
+{
+    /// create a node with the given unary operator above the child
+    pub(crate) fn new_unary_node(&mut self, operator: Op, child: Child) -> NodeId {
+        let mut node = Node::new(Some(operator), true);
+        node.set_left(child);
+        let node_idx = self.store_node(node);
+        if let Child::Node(child_idx) = child {
+            self.nodes[child_idx].set_parent(Some(node_idx));
+        }
+        self.op_count += 1;
+        node_idx
+    }
+
+    /// set the child of a node, updating the parent link of the child
+    pub(crate) fn link_child(&mut self, node_idx: NodeId, side: Side, child: Child) {
+        self.nodes[node_idx].set_child(side, child);
+        if let Child::Node(child_idx) = child {
+            self.nodes[child_idx].set_parent(Some(node_idx));
+        }
+    }
+
+    /// collect, from left to right, the operands of the chain of the given
+    /// binary operator starting at the child, along with the nodes of the
+    /// chain (in pre-order). Groups in the chain are traversed.
+    pub(crate) fn collect_chain(
+        &self,
+        child: Child,
+        operator: &Op,
+        operands: &mut Vec<Child>,
+        chain_nodes: &mut Vec<NodeId>,
+    ) {
+        let mut stack = vec![child];
+        while let Some(child) = stack.pop() {
+            if let Child::Node(node_idx) = child {
+                let node = &self.nodes[node_idx];
+                match &node.operator {
+                    None => {
+                        stack.push(node.left());
+                        continue;
+                    }
+                    Some(op) if op == operator && !node.unary && node.right().is_some() => {
+                        chain_nodes.push(node_idx);
+                        stack.push(node.right());
+                        stack.push(node.left());
+                        continue;
+                    }
+                    _ => {}
+                }
+            }
+            operands.push(child);
+        }
+    }
+
+    /// make the given child the whole expression
+    pub(crate) fn set_root(&mut self, root: Child) {
+        match root {
+            Child::Node(node_idx) => {
+                self.nodes[node_idx].set_parent(None);
+                self.head = node_idx;
+            }
+            Child::None => {
+                self.clear();
+            }
+            Child::Atom(_) => {
+                // the head must be a node
+                let mut node = Node::empty();
+                node.set_left(root);
+                self.head = self.store_node(node);
+            }
+        }
+        self.fix_tail();
+    }
+
+    /// set the root of a decoded expression, which may be an empty
+    /// group: it's then kept in a head node, as an empty group as head
+    /// would be the empty expression
+    pub(crate) fn set_decoded_root(&mut self, root: Child) {
+        match root {
+            Child::Node(node_idx) if self.nodes[node_idx].operator.is_none() => {
+                let head = self.store_node(Node::empty());
+                self.link_child(head, Side::Left, root);
+                self.set_root(Child::Node(head));
+            }
+            _ => self.set_root(root),
+        }
+    }
+
+    fn push_down_unary_child<Dual, NegateAtom>(
+        &mut self,
+        child: Child,
+        negated: bool,
+        unary: &Op,
+        dual: &Dual,
+        negate_atom: &mut NegateAtom,
+    ) -> Child
+    where
+        Dual: Fn(&Op) -> Option<Op>,
+        NegateAtom: FnMut(&Atom) -> NegatedAtom<Atom>,
+    {
+        match child {
+            Child::None => Child::None,
+            Child::Atom(atom_idx) => {
+                if !negated {
+                    return child;
+                }
+                match negate_atom(&self.atoms[atom_idx]) {
+                    NegatedAtom::Replace(atom) => Child::Atom(self.store_atom(atom)),
+                    NegatedAtom::Keep => Child::Node(self.new_unary_node(unary.clone(), child)),
+                }
+            }
+            Child::Node(node_idx) => {
+                let node = &self.nodes[node_idx];
+                let (left, right) = (node.left(), node.right());
+                match &node.operator {
+                    Some(op) if node.unary && op == unary => {
+                        // the unary node is removed, its operand gets the negation
+                        self.op_count -= 1;
+                        self.push_down_unary_child(left, !negated, unary, dual, negate_atom)
+                    }
+                    None => {
+                        // a group: the negation applies to its content
+                        let left =
+                            self.push_down_unary_child(left, negated, unary, dual, negate_atom);
+                        self.link_child(node_idx, Side::Left, left);
+                        child
+                    }
+                    Some(op) => {
+                        let dual_op = if negated && !node.unary {
+                            dual(op)
+                        } else {
+                            None
+                        };
+                        // the negation goes down only if there's a dual operator
+                        let pushed = dual_op.is_some();
+                        if let Some(dual_op) = dual_op {
+                            self.nodes[node_idx].operator = Some(dual_op);
+                        }
+                        let left =
+                            self.push_down_unary_child(left, pushed, unary, dual, negate_atom);
+                        self.link_child(node_idx, Side::Left, left);
+                        let right =
+                            self.push_down_unary_child(right, pushed, unary, dual, negate_atom);
+                        self.link_child(node_idx, Side::Right, right);
+                        if negated && !pushed {
+                            Child::Node(self.new_unary_node(unary.clone(), child))
+                        } else {
+                            child
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// push the given unary operator (usually a negation) down the tree,
+    /// as far as possible, towards the atoms.
+    ///
+    /// With boolean operators, this converts the expression to the
+    /// negation normal form: `!(a & b)` becomes `!a | !b`, `!(a | b)`
+    /// becomes `!a & !b` and `!!a` becomes `a`.
+    ///
+    /// `dual` gives the operator by which a binary operator must be
+    /// replaced when the unary operator is pushed through it (for example
+    /// `Or` for `And`). If it returns `None`, the unary operator is kept
+    /// above this operator's subtree.
+    ///
+    /// `negate_atom` is called for atoms reached by the unary operator;
+    /// it can either return an atom holding the negation or ask for the
+    /// unary operator to be kept above the atom.
+    pub fn push_down_unary<Dual, NegateAtom>(
+        &mut self,
+        unary: &Op,
+        dual: Dual,
+        mut negate_atom: NegateAtom,
+    ) where
+        Dual: Fn(&Op) -> Option<Op>,
+        NegateAtom: FnMut(&Atom) -> NegatedAtom<Atom>,
+    {
+        if self.is_empty() {
+            return;
+        }
+        self.bump_epoch();
+        let root = self.push_down_unary_child(
+            Child::Node(self.head),
+            false,
+            unary,
+            &dual,
+            &mut negate_atom,
+        );
+        self.set_root(root);
+    }
+}
+
+/// A clause in the conversion to a normal form: a list of literals,
+/// which are subtrees of the source expression
+type Clause = Vec<Child>;
+
+/// Conversions to normal forms
+impl<Op, Atom> BeTree<Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone,
+{
+    /// deep copy the child (and its descendants) into another tree
+    pub(crate) fn copy_child_into(&self, child: Child, dst: &mut BeTree<Op, Atom>) -> Child {
+        match child {
+            Child::None => Child::None,
+            Child::Atom(atom_idx) => Child::Atom(dst.store_atom(self.atoms[atom_idx].clone())),
+            Child::Node(node_idx) => {
+                let node = &self.nodes[node_idx];
+                let mut new_node = Node::new(node.operator.clone(), node.unary);
+                new_node.explicit = node.explicit;
+                let new_idx = dst.store_node(new_node);
+                if node.operator.is_some() {
+                    dst.op_count += 1;
+                }
+                let left = self.copy_child_into(node.left(), dst);
+                dst.link_child(new_idx, Side::Left, left);
+                let right = self.copy_child_into(node.right(), dst);
+                dst.link_child(new_idx, Side::Right, right);
+                Child::Node(new_idx)
+            }
+        }
+    }
+
+    /// build a left to right chain of binary operations
+    pub(crate) fn build_chain(&mut self, operator: &Op, operands: Vec<Child>) -> Child {
+        let mut operands = operands.into_iter();
+        let Some(mut chain) = operands.next() else {
+            return Child::None;
+        };
+        for operand in operands {
+            let node_idx = self.store_node(Node::new(Some(operator.clone()), false));
+            self.op_count += 1;
+            self.link_child(node_idx, Side::Left, chain);
+            self.link_child(node_idx, Side::Right, operand);
+            chain = Child::Node(node_idx);
+        }
+        chain
+    }
+
+    /// compute the clauses of the normal form whose outer operator is `outer`
+    /// and inner operator is `inner`, or None if it would be too big
+    fn normal_form_clauses(
+        &self,
+        child: Child,
+        outer: &Op,
+        inner: &Op,
+        max_literals: usize,
+    ) -> Option<Vec<Clause>> {
+        let Child::Node(node_idx) = child else {
+            return Some(vec![vec![child]]);
+        };
+        let node = &self.nodes[node_idx];
+        let Some(op) = &node.operator else {
+            // a group
+            return self.normal_form_clauses(node.left(), outer, inner, max_literals);
+        };
+        if node.unary || node.right().is_none() || (op != outer && op != inner) {
+            // this subtree is a literal
+            return Some(vec![vec![child]]);
+        }
+        let left = self.normal_form_clauses(node.left(), outer, inner, max_literals)?;
+        let right = self.normal_form_clauses(node.right(), outer, inner, max_literals)?;
+        let literals = |clauses: &[Clause]| clauses.iter().map(Vec::len).sum::<usize>();
+        if op == outer {
+            if literals(&left) + literals(&right) > max_literals {
+                return None;
+            }
+            let mut clauses = left;
+            clauses.extend(right);
+            Some(clauses)
+        } else {
+            // distribution of the inner operator over the outer one
+            let size = left.len() * literals(&right) + right.len() * literals(&left);
+            if size > max_literals {
+                return None;
+            }
+            let mut clauses = Vec::with_capacity(left.len() * right.len());
+            for a in &left {
+                for b in &right {
+                    let mut clause = a.clone();
+                    clause.extend_from_slice(b);
+                    clauses.push(clause);
+                }
+            }
+            Some(clauses)
+        }
+    }
+
+    fn to_normal_form(&self, outer: &Op, inner: &Op, max_literals: usize) -> Option<Self> {
+        let mut dst = Self::new();
+        if self.is_empty() {
+            return Some(dst);
+        }
+        let clauses =
+            self.normal_form_clauses(Child::Node(self.head), outer, inner, max_literals)?;
+        let mut operands = Vec::with_capacity(clauses.len());
+        for clause in clauses {
+            let literals = clause
+                .into_iter()
+                .map(|literal| self.copy_child_into(literal, &mut dst))
+                .collect();
+            operands.push(dst.build_chain(inner, literals));
+        }
+        let root = dst.build_chain(outer, operands);
+        dst.set_root(root);
+        dst.last_pushed = TokenType::ClosingPar;
+        Some(dst)
+    }
+
+    /// build the disjunctive normal form of the expression: a chain
+    /// of `or_op` operations whose operands are chains of `and_op`
+    /// operations, by distributing `and_op` over `or_op`.
+    ///
+    /// Subtrees whose operator is neither `and_op` nor `or_op` (for
+    /// example negations) are considered as literals and copied as is,
+    /// so you'll usually want to call `push_down_unary` before.
+    ///
+    /// As the size of the normal form may be exponential, None is
+    /// returned when the number of literals in the result would
+    /// exceed `max_literals`.
+    pub fn to_dnf(&self, and_op: &Op, or_op: &Op, max_literals: usize) -> Option<Self> {
+        self.to_normal_form(or_op, and_op, max_literals)
+    }
+
+    /// build the conjunctive normal form of the expression: a chain
+    /// of `and_op` operations whose operands are chains of `or_op`
+    /// operations, by distributing `or_op` over `and_op`.
+    ///
+    /// Subtrees whose operator is neither `and_op` nor `or_op` (for
+    /// example negations) are considered as literals and copied as is,
+    /// so you'll usually want to call `push_down_unary` before.
+    ///
+    /// As the size of the normal form may be exponential, None is
+    /// returned when the number of literals in the result would
+    /// exceed `max_literals`.
+    pub fn to_cnf(&self, and_op: &Op, or_op: &Op, max_literals: usize) -> Option<Self> {
+        self.to_normal_form(and_op, or_op, max_literals)
+    }
+}
+
+/// An element of the canonical key of a subtree, used to order
+/// the operands of commutative operators
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum CanonicalKeyPart<Op, K> {
+    Atom(K),
+    Operator(Op, bool),
+    Open,
+    Close,
+}
+
+/// Canonicalization
+impl<Op, Atom> BeTree<Op, Atom>
+where
+    Op: fmt::Debug + Clone + Ord,
+    Atom: fmt::Debug + Clone,
+{
+    /// canonicalize the child, return what should replace it and its
+    /// canonical key
+    fn canonicalize_child<K, Commutative, AtomKey>(
+        &mut self,
+        child: Child,
+        commutative: &Commutative,
+        atom_key: &AtomKey,
+    ) -> (Child, Vec<CanonicalKeyPart<Op, K>>)
+    where
+        K: Ord,
+        Commutative: Fn(&Op) -> bool,
+        AtomKey: Fn(&Atom) -> K,
+    {
+        let node_idx = match child {
+            Child::None => return (child, Vec::new()),
+            Child::Atom(atom_idx) => {
+                return (
+                    child,
+                    vec![CanonicalKeyPart::Atom(atom_key(&self.atoms[atom_idx]))],
+                );
+            }
+            Child::Node(node_idx) => node_idx,
+        };
+        let node = &self.nodes[node_idx];
+        let Some(operator) = node.operator.clone() else {
+            // groups are removed
+            return self.canonicalize_child(node.left(), commutative, atom_key);
+        };
+        let (left, right, unary) = (node.left(), node.right(), node.unary);
+        let mut key = vec![CanonicalKeyPart::Operator(operator.clone(), unary)];
+        if unary || right.is_none() || !commutative(&operator) {
+            for (side, operand) in [(Side::Left, left), (Side::Right, right)] {
+                let (operand, operand_key) =
+                    self.canonicalize_child(operand, commutative, atom_key);
+                self.link_child(node_idx, side, operand);
+                key.push(CanonicalKeyPart::Open);
+                key.extend(operand_key);
+                key.push(CanonicalKeyPart::Close);
+            }
+            return (child, key);
+        }
+        let mut operands = Vec::new();
+        let mut chain_nodes = Vec::new();
+        self.collect_chain(child, &operator, &mut operands, &mut chain_nodes);
+        let mut keyed_operands: Vec<_> = operands
+            .into_iter()
+            .map(|operand| {
+                let (operand, operand_key) =
+                    self.canonicalize_child(operand, commutative, atom_key);
+                (operand_key, operand)
+            })
+            .collect();
+        keyed_operands.sort_by(|a, b| a.0.cmp(&b.0));
+        // we rebuild the chain, left leaning, reusing its nodes, the
+        // current node staying at the top
+        chain_nodes.retain(|&idx| idx != node_idx);
+        chain_nodes.push(node_idx);
+        let mut chain = Child::None;
+        for (i, (operand_key, operand)) in keyed_operands.into_iter().enumerate() {
+            key.push(CanonicalKeyPart::Open);
+            key.extend(operand_key);
+            key.push(CanonicalKeyPart::Close);
+            if i == 0 {
+                chain = operand;
+            } else {
+                let chain_node = chain_nodes[i - 1];
+                self.link_child(chain_node, Side::Left, chain);
+                self.link_child(chain_node, Side::Right, operand);
+                chain = Child::Node(chain_node);
+            }
+        }
+        (chain, key)
+    }
+
+    /// reorder the operands of commutative operators into a canonical order,
+    /// so that expressions differing only by the order of those operands
+    /// become identical.
+    ///
+    /// Chains of a commutative operator (for example `c & a & b`) are
+    /// flattened then sorted as a whole (giving `a & b & c`), and rebuilt
+    /// as left leaning chains. Atoms are compared with the key given by
+    /// `atom_key`, operators with their `Ord` implementation, and subtrees
+    /// by their canonical form, atoms first, then operators, then their
+    /// operands in order.
+    ///
+    /// Groups (i.e. parenthesis), which have no meaning in the tree
+    /// structure, are removed.
+    pub fn canonicalize<K, Commutative, AtomKey>(
+        &mut self,
+        commutative: Commutative,
+        atom_key: AtomKey,
+    ) where
+        K: Ord,
+        Commutative: Fn(&Op) -> bool,
+        AtomKey: Fn(&Atom) -> K,
+    {
+        if self.is_empty() {
+            return;
+        }
+        self.bump_epoch();
+        let head = self.head;
+        let (root, _) = self.canonicalize_child(Child::Node(head), &commutative, &atom_key);
+        self.set_root(root);
+    }
+}
+
+/// Rebalancing
+impl<Op, Atom> BeTree<Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone,
+{
+    /// link the nodes of a chain as a balanced tree above its operands
+    /// (there must be one node less than operands), the first node
+    /// being the root of this tree
+    fn link_balanced(&mut self, operands: &[Child], chain_nodes: &[NodeId]) -> Child {
+        if operands.len() == 1 {
+            return operands[0];
+        }
+        let node_idx = chain_nodes[0];
+        let mid = operands.len() / 2;
+        let left = self.link_balanced(&operands[..mid], &chain_nodes[1..mid]);
+        self.link_child(node_idx, Side::Left, left);
+        let right = self.link_balanced(&operands[mid..], &chain_nodes[mid..]);
+        self.link_child(node_idx, Side::Right, right);
+        Child::Node(node_idx)
+    }
+
+    /// rebuild the chains of the given binary operator (for example
+    /// `a | b | c | d | e`, which the builder makes left leaning) as
+    /// balanced trees, so that the depth of a chain of n operands
+    /// is about log2(n) instead of n.
+    ///
+    /// The order of the operands is kept, so the evaluation gives the
+    /// same result as long as the operator is associative, and the atoms
+    /// are evaluated in the same order.
+    ///
+    /// Groups in the chains are removed, the number of operators is
+    /// unchanged.
+    pub fn rebalance_chains(&mut self, operator: &Op) {
+        if self.is_empty() {
+            return;
+        }
+        self.bump_epoch();
+        let mut stack = vec![self.head];
+        while let Some(node_idx) = stack.pop() {
+            let node = &self.nodes[node_idx];
+            let is_chain =
+                node.operator.as_ref() == Some(operator) && !node.unary && node.right().is_some();
+            let mut operands = Vec::new();
+            if is_chain {
+                let mut chain_nodes = Vec::new();
+                self.collect_chain(
+                    Child::Node(node_idx),
+                    operator,
+                    &mut operands,
+                    &mut chain_nodes,
+                );
+                self.link_balanced(&operands, &chain_nodes);
+            } else {
+                operands.push(node.left());
+                operands.push(node.right());
+            }
+            for operand in operands {
+                if let Child::Node(child_idx) = operand {
+                    stack.push(child_idx);
+                }
+            }
+        }
+        self.fix_tail();
+    }
+}
@@ -0,0 +1,224 @@
+use {
+    crate::*,
+    std::{collections::HashMap, fmt},
+};
+
+/// A rule replacing the occurrences of a pattern, for example `!!x`
+/// replaced with `x`, to be applied with `BeTree::rewrite`
+#[derive(Debug, Clone)]
+pub struct RewriteRule<Op, Atom> {
+    pattern: BeTree<Op, PatternAtom<Atom>>,
+    replacement: BeTree<Op, PatternAtom<Atom>>,
+}
+
+impl<Op, Atom> RewriteRule<Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone,
+{
+    /// build a rule replacing the occurrences of the pattern with the
+    /// replacement, whose `AnySubtree` wildcards are replaced with the
+    /// subexpressions captured by the pattern.
+    ///
+    /// Return `None` if the replacement is empty, contains an `AnyAtom`
+    /// wildcard, or an `AnySubtree` wildcard which isn't in the pattern.
+    pub fn new(
+        pattern: BeTree<Op, PatternAtom<Atom>>,
+        replacement: BeTree<Op, PatternAtom<Atom>>,
+    ) -> Option<Self> {
+        if replacement.is_empty() {
+            return None;
+        }
+        let is_captured = |name: &String| {
+            pattern
+                .iter_atoms()
+                .any(|atom| matches!(atom, PatternAtom::AnySubtree(n) if n == name))
+        };
+        let mut valid = true;
+        let mut replacement = replacement;
+        replacement.map_atoms_in_place(|atom, _| match atom {
+            PatternAtom::Exact(_) => {}
+            PatternAtom::AnyAtom => valid = false,
+            PatternAtom::AnySubtree(name) => valid &= is_captured(name),
+        });
+        valid.then_some(Self {
+            pattern,
+            replacement,
+        })
+    }
+    /// the pattern whose occurrences are replaced
+    pub fn pattern(&self) -> &BeTree<Op, PatternAtom<Atom>> {
+        &self.pattern
+    }
+    /// what replaces the occurrences of the pattern
+    pub fn replacement(&self) -> &BeTree<Op, PatternAtom<Atom>> {
+        &self.replacement
+    }
+}
+
+/// Why the application of rewrite rules stopped
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RewriteOutcome {
+    /// No rule could be applied anymore
+    Fixpoint,
+    /// The maximal number of passes was reached
+    PassLimit,
+    /// The rules brought back the expression to a previous state
+    Cycle,
+}
+
+/// What happened during the application of rewrite rules
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RewriteReport {
+    /// How many times each rule was applied
+    pub fired: Vec<usize>,
+    /// The number of passes over the expression
+    pub passes: usize,
+    /// Why the rewriting stopped
+    pub outcome: RewriteOutcome,
+}
+
+/// Rewriting with rules
+impl<Op, Atom> BeTree<Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone + PartialEq,
+{
+    /// deep copy the child (and its descendants) in the same tree
+    fn duplicate_child(&mut self, child: Child) -> Child {
+        match child {
+            Child::None => Child::None,
+            Child::Atom(atom_idx) => Child::Atom(self.store_atom(self.atoms[atom_idx].clone())),
+            Child::Node(node_idx) => {
+                let node = &self.nodes[node_idx];
+                let (left, right) = (node.left(), node.right());
+                let has_operator = node.operator.is_some();
+                let new_idx = self.store_node(Node::new(node.operator.clone(), node.unary));
+                if has_operator {
+                    self.op_count += 1;
+                }
+                let left = self.duplicate_child(left);
+                self.link_child(new_idx, Side::Left, left);
+                let right = self.duplicate_child(right);
+                self.link_child(new_idx, Side::Right, right);
+                Child::Node(new_idx)
+            }
+        }
+    }
+
+    /// build in the tree a part of the replacement of a rule
+    fn instantiate(
+        &mut self,
+        replacement: &BeTree<Op, PatternAtom<Atom>>,
+        replacement_child: Child,
+        bindings: &HashMap<String, Child>,
+    ) -> Child {
+        match replacement_child {
+            Child::None => Child::None,
+            Child::Atom(atom_idx) => match &replacement.atoms[atom_idx] {
+                PatternAtom::Exact(atom) => Child::Atom(self.store_atom(atom.clone())),
+                PatternAtom::AnySubtree(name) => self.duplicate_child(bindings[name]),
+                PatternAtom::AnyAtom => unreachable!("checked at rule creation"),
+            },
+            Child::Node(node_idx) => {
+                let node = &replacement.nodes[node_idx];
+                let new_idx = self.store_node(Node::new(node.operator.clone(), node.unary));
+                if node.operator.is_some() {
+                    self.op_count += 1;
+                }
+                let left = self.instantiate(replacement, node.left(), bindings);
+                self.link_child(new_idx, Side::Left, left);
+                let right = self.instantiate(replacement, node.right(), bindings);
+                self.link_child(new_idx, Side::Right, right);
+                Child::Node(new_idx)
+            }
+        }
+    }
+
+    /// apply a rule on a match
+    fn apply_rule(&mut self, rule: &RewriteRule<Op, Atom>, m: &Match) {
+        let replacement = &rule.replacement;
+        let new_child = self.instantiate(
+            replacement,
+            replacement.skip_groups(Child::Node(replacement.head)),
+            &m.bindings,
+        );
+        self.op_count -= self.count_operators(Child::Node(m.node));
+        match self.node_link(m.node) {
+            Some((parent_idx, side)) => self.link_child(parent_idx, side, new_child),
+            None => self.set_root(new_child),
+        }
+    }
+
+    /// do a pass over the tree, applying the rules, and return whether
+    /// a rule fired
+    fn rewrite_pass(&mut self, rules: &[RewriteRule<Op, Atom>], fired: &mut [usize]) -> bool {
+        let mut changed = false;
+        let mut stack = vec![self.head];
+        while let Some(node_idx) = stack.pop() {
+            let node = &self.nodes[node_idx];
+            let (left, right) = (node.left(), node.right());
+            if node.operator.is_some() {
+                let found = rules
+                    .iter()
+                    .enumerate()
+                    .find_map(|(i, rule)| self.match_at(node_idx, &rule.pattern).map(|m| (i, m)));
+                if let Some((rule_idx, m)) = found {
+                    self.apply_rule(&rules[rule_idx], &m);
+                    fired[rule_idx] += 1;
+                    changed = true;
+                    // the replacement is only visited in the next pass
+                    continue;
+                }
+            }
+            for child in [right, left] {
+                if let Child::Node(child_idx) = child {
+                    stack.push(child_idx);
+                }
+            }
+        }
+        changed
+    }
+
+    /// simplify the expression by applying rules until none applies.
+    ///
+    /// During a pass, the nodes are visited in pre-order and the first
+    /// rule (in the order of the slice) whose pattern matches at a node
+    /// is applied, replacing the node and its descendants. Passes are
+    /// repeated until none changes the expression, until `max_passes`
+    /// passes have been done, or until a previous state of the expression
+    /// is seen again (which happens when rules undo each other).
+    ///
+    /// The replaced nodes and atoms stay in the arena but aren't
+    /// reachable anymore (see `compact`).
+    pub fn rewrite(&mut self, rules: &[RewriteRule<Op, Atom>], max_passes: usize) -> RewriteReport {
+        let mut report = RewriteReport {
+            fired: vec![0; rules.len()],
+            passes: 0,
+            outcome: RewriteOutcome::Fixpoint,
+        };
+        if self.is_empty() {
+            return report;
+        }
+        self.bump_epoch();
+        let mut seen = vec![self.clone().normalize()];
+        loop {
+            if report.passes == max_passes {
+                report.outcome = RewriteOutcome::PassLimit;
+                break;
+            }
+            report.passes += 1;
+            if !self.rewrite_pass(rules, &mut report.fired) {
+                break;
+            }
+            let state = self.clone().normalize();
+            if seen.contains(&state) {
+                report.outcome = RewriteOutcome::Cycle;
+                break;
+            }
+            seen.push(state);
+        }
+        self.fix_tail();
+        report
+    }
+}
@@ -0,0 +1,223 @@
+use {crate::*, core::fmt};
+
+/// A text which is safe to put in the rendered expression: either the
+/// output of an `EscapePolicy`, or a literal of the program.
+///
+/// There's no other way to build it, so that the raw text of an atom
+/// can't be interpolated by mistake.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Escaped(String);
+
+impl Escaped {
+    /// a text which doesn't need escaping, for example the name of a
+    /// column, which must be a literal of the program
+    pub fn trusted(text: &'static str) -> Self {
+        Self(text.to_string())
+    }
+    /// append another escaped text
+    pub fn then(mut self, other: Escaped) -> Self {
+        self.0.push_str(&other.0);
+        self
+    }
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+    pub fn into_string(self) -> String {
+        self.0
+    }
+}
+
+impl fmt::Display for Escaped {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// A way to escape raw texts so that they're read verbatim in the
+/// targeted syntax.
+///
+/// A function taking the raw text and the output becomes a policy
+/// when wrapped in an `EscapeFn`.
+pub trait EscapePolicy {
+    /// write the escaped form of the raw text
+    fn escape_into(&self, raw: &str, out: &mut String);
+
+    /// escape the raw text
+    fn escape(&self, raw: &str) -> Escaped {
+        let mut out = String::with_capacity(raw.len() + 2);
+        self.escape_into(raw, &mut out);
+        Escaped(out)
+    }
+}
+
+/// A policy defined by a function writing the escaped form of the
+/// raw text in the output
+#[derive(Debug, Clone, Copy)]
+pub struct EscapeFn<F>(pub F);
+
+impl<F> EscapePolicy for EscapeFn<F>
+where
+    F: Fn(&str, &mut String),
+{
+    fn escape_into(&self, raw: &str, out: &mut String) {
+        (self.0)(raw, out)
+    }
+}
+
+/// The policy of SQL string literals: the text is quoted and the
+/// quotes it contains are doubled, `it's` giving `'it''s'`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuoteDoubling {
+    pub quote: char,
+}
+
+impl QuoteDoubling {
+    /// single quotes, as in SQL
+    pub const SINGLE: Self = Self { quote: '\'' };
+}
+
+impl EscapePolicy for QuoteDoubling {
+    fn escape_into(&self, raw: &str, out: &mut String) {
+        out.push(self.quote);
+        for c in raw.chars() {
+            if c == self.quote {
+                out.push(c);
+            }
+            out.push(c);
+        }
+        out.push(self.quote);
+    }
+}
+
+/// The policy prefixing with a backslash the special characters, and
+/// the backslashes, as in Lucene query strings
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BackslashEscaping<'s> {
+    /// the characters to escape, besides the backslash
+    pub special: &'s str,
+}
+
+impl BackslashEscaping<'static> {
+    /// the special characters of the Lucene query syntax, and the space
+    pub const LUCENE: Self = Self {
+        special: "+-&|!(){}[]^\"~*?:/ ",
+    };
+}
+
+impl EscapePolicy for BackslashEscaping<'_> {
+    fn escape_into(&self, raw: &str, out: &mut String) {
+        for c in raw.chars() {
+            if c == '\\' || self.special.contains(c) {
+                out.push('\\');
+            }
+            out.push(c);
+        }
+    }
+}
+
+/// A renderer for syntaxes where atoms must be escaped, used with
+/// `BeTree::render_safe`.
+///
+/// Unlike with `Renderer`, atoms aren't written directly but rendered
+/// as `Escaped` texts, while operators, separators and parenthesis are
+/// static strings of the renderer.
+pub trait SafeRenderer<Op, Atom> {
+    /// render an atom, with an `EscapePolicy`
+    fn render_atom(&self, atom: &Atom) -> Escaped;
+
+    /// the spelling of an operator, which may be unary or binary
+    fn operator(&self, operator: &Op, unary: bool) -> &'static str;
+
+    /// what separates a binary operator from its operands
+    fn binary_separator(&self) -> &'static str {
+        " "
+    }
+
+    /// what separates a unary operator from its operand
+    fn unary_separator(&self) -> &'static str {
+        ""
+    }
+
+    /// tell whether a binary operation must be put in a group when it's
+    /// the operand, on the given side, of another binary operation
+    /// (see `Renderer::needs_group`)
+    fn needs_group(&self, operator: &Op, operand_operator: &Op, side: Side) -> bool
+    where
+        Op: PartialEq,
+    {
+        side == Side::Right || operator != operand_operator
+    }
+
+    /// the start of a group
+    fn group_start(&self) -> &'static str {
+        "("
+    }
+
+    /// the end of a group
+    fn group_end(&self) -> &'static str {
+        ")"
+    }
+}
+
+/// The `Renderer` writing what a `SafeRenderer` renders
+struct SafeAdapter<'r, R>(&'r R);
+
+impl<Op, Atom, R> Renderer<Op, Atom> for SafeAdapter<'_, R>
+where
+    R: SafeRenderer<Op, Atom>,
+{
+    fn write_atom(&self, out: &mut dyn fmt::Write, atom: &Atom) -> fmt::Result {
+        out.write_str(self.0.render_atom(atom).as_str())
+    }
+    fn write_operator(&self, out: &mut dyn fmt::Write, operator: &Op, unary: bool) -> fmt::Result {
+        out.write_str(self.0.operator(operator, unary))
+    }
+    fn write_before_binary_operator(&self, out: &mut dyn fmt::Write) -> fmt::Result {
+        out.write_str(self.0.binary_separator())
+    }
+    fn write_after_binary_operator(&self, out: &mut dyn fmt::Write) -> fmt::Result {
+        out.write_str(self.0.binary_separator())
+    }
+    fn write_after_unary_operator(&self, out: &mut dyn fmt::Write) -> fmt::Result {
+        out.write_str(self.0.unary_separator())
+    }
+    fn needs_group(&self, operator: &Op, operand_operator: &Op, side: Side) -> bool
+    where
+        Op: PartialEq,
+    {
+        self.0.needs_group(operator, operand_operator, side)
+    }
+    fn write_group_start(&self, out: &mut dyn fmt::Write) -> fmt::Result {
+        out.write_str(self.0.group_start())
+    }
+    fn write_group_end(&self, out: &mut dyn fmt::Write) -> fmt::Result {
+        out.write_str(self.0.group_end())
+    }
+}
+
+/// Rendering with escaped atoms
+impl<Op, Atom> BeTree<Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone,
+{
+    /// write the expression with the given safe renderer, atoms being
+    /// escaped, for example to produce a SQL `WHERE` clause.
+    ///
+    /// Groups are kept or added as with `render`.
+    pub fn render_safe<R, W>(&self, renderer: &R, out: &mut W) -> fmt::Result
+    where
+        R: SafeRenderer<Op, Atom>,
+        W: fmt::Write,
+    {
+        self.render(&SafeAdapter(renderer), out)
+    }
+
+    /// render the expression into a string with the given safe renderer
+    pub fn to_safe_string<R>(&self, renderer: &R) -> String
+    where
+        R: SafeRenderer<Op, Atom>,
+    {
+        self.to_string_with(&SafeAdapter(renderer))
+    }
+}
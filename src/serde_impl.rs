@@ -0,0 +1,163 @@
+use {
+    crate::*,
+    core::fmt,
+    serde::{de, Deserialize, Deserializer, Serialize, Serializer},
+};
+
+/// The serialized form of a node, with its links unpacked
+#[derive(Serialize)]
+struct NodeReprRef<'n, Op> {
+    operator: &'n Option<Op>,
+    parent: Option<NodeId>,
+    left: Child,
+    right: Child,
+    unary: bool,
+    explicit: bool,
+}
+
+/// The deserialized form of a node
+#[derive(Deserialize)]
+struct NodeRepr<Op> {
+    operator: Option<Op>,
+    parent: Option<NodeId>,
+    left: Child,
+    right: Child,
+    unary: bool,
+    // absent from the trees serialized by older versions, which can
+    // be read only from self-describing formats
+    #[serde(default)]
+    explicit: bool,
+}
+
+impl<Op: Serialize> Serialize for Node<Op> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        NodeReprRef {
+            operator: &self.operator,
+            parent: self.parent(),
+            left: self.left(),
+            right: self.right(),
+            unary: self.unary,
+            explicit: self.explicit,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de, Op: Deserialize<'de>> Deserialize<'de> for Node<Op> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = NodeRepr::<Op>::deserialize(deserializer)?;
+        let mut node = Node::new(repr.operator, repr.unary);
+        node.set_parent(repr.parent);
+        node.set_left(repr.left);
+        node.set_right(repr.right);
+        node.explicit = repr.explicit;
+        Ok(node)
+    }
+}
+
+/// The serialized form of a tree, borrowing it
+#[derive(Serialize)]
+struct BeTreeReprRef<'t, Op, Atom> {
+    atoms: &'t [Atom],
+    nodes: &'t [Node<Op>],
+    head: NodeId,
+    tail: NodeId,
+    last_pushed: TokenType,
+    openness: usize,
+}
+
+/// The deserialized form of a tree, not yet validated
+#[derive(Deserialize)]
+struct BeTreeRepr<Op, Atom> {
+    atoms: Vec<Atom>,
+    nodes: Vec<Node<Op>>,
+    head: NodeId,
+    tail: NodeId,
+    last_pushed: TokenType,
+    openness: usize,
+}
+
+impl<Op, Atom> Serialize for BeTree<Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq + Serialize,
+    Atom: fmt::Debug + Clone + Serialize,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        BeTreeReprRef {
+            atoms: &self.atoms,
+            nodes: &self.nodes,
+            head: self.head,
+            tail: self.tail,
+            last_pushed: self.last_pushed,
+            openness: self.openness,
+        }
+        .serialize(serializer)
+    }
+}
+
+/// check that all links, including the ones of the nodes which aren't
+/// reachable, point into the arenas
+fn check_arena_links<Op>(atom_count: usize, nodes: &[Node<Op>]) -> Result<(), StructureError>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+{
+    for node in nodes {
+        if let Some(parent_idx) = node.parent() {
+            if parent_idx.index() >= nodes.len() {
+                return Err(StructureError::UnknownNode(parent_idx));
+            }
+        }
+        for child in [node.left(), node.right()] {
+            match child {
+                Child::Node(node_idx) if node_idx.index() >= nodes.len() => {
+                    return Err(StructureError::UnknownNode(node_idx));
+                }
+                Child::Atom(atom_idx) if atom_idx.index() >= atom_count => {
+                    return Err(StructureError::UnknownAtom(atom_idx));
+                }
+                _ => {}
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Deserialization checks the structure of the tree (see
+/// `BeTree::validate_structure`), and that all the links of the nodes
+/// which aren't reachable point into the arenas, so that a corrupted
+/// input is rejected instead of causing a panic later.
+///
+/// Nodes are serialized with their explicit group flag (see
+/// `was_explicit_group`). Trees serialized before this flag existed
+/// can still be read from self-describing formats like JSON, their
+/// nodes not being explicit groups, but not from formats without
+/// field names like bincode, whose nodes have a fixed layout.
+impl<'de, Op, Atom> Deserialize<'de> for BeTree<Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq + Deserialize<'de>,
+    Atom: fmt::Debug + Clone + Deserialize<'de>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = BeTreeRepr::<Op, Atom>::deserialize(deserializer)?;
+        let op_count = parts::check_reachable_structure(repr.atoms.len(), &repr.nodes, repr.head)
+            .map_err(de::Error::custom)?;
+        check_arena_links(repr.atoms.len(), &repr.nodes).map_err(de::Error::custom)?;
+        if repr.last_pushed == TokenType::Atom && repr.atoms.is_empty() {
+            return Err(de::Error::custom(
+                "last pushed token is an atom but there's no atom",
+            ));
+        }
+        let tree = Self {
+            atoms: repr.atoms.into_iter().collect(),
+            nodes: repr.nodes.into_iter().collect(),
+            head: repr.head,
+            tail: repr.tail,
+            last_pushed: repr.last_pushed,
+            op_count,
+            openness: repr.openness,
+            epoch: 0,
+        };
+        tree.validate_structure().map_err(de::Error::custom)?;
+        Ok(tree)
+    }
+}
@@ -0,0 +1,260 @@
+use {crate::*, core::fmt};
+
+/// write the text, quoted if it can't be read back as a bare token
+fn push_sexpr_text(text: &str, s: &mut String) {
+    let needs_quotes = text.is_empty()
+        || text
+            .chars()
+            .any(|c| c.is_whitespace() || matches!(c, '(' | ')' | '"' | '\\'));
+    if !needs_quotes {
+        s.push_str(text);
+        return;
+    }
+    s.push('"');
+    for c in text.chars() {
+        if matches!(c, '"' | '\\') {
+            s.push('\\');
+        }
+        s.push(c);
+    }
+    s.push('"');
+}
+
+/// A reader of S-expressions, building a tree
+struct SexprReader<'i, Op, Atom, PO, PA> {
+    input: &'i str,
+    pos: usize,
+    tree: BeTree<Op, Atom>,
+    parse_op: PO,
+    parse_atom: PA,
+}
+
+impl<'i, Op, Atom, PO, PA> SexprReader<'i, Op, Atom, PO, PA>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone,
+    PO: Fn(&str) -> Option<Op>,
+    PA: Fn(&str) -> Option<Atom>,
+{
+    fn peek(&self) -> Option<char> {
+        self.input[self.pos..].chars().next()
+    }
+    fn skip_whitespace(&mut self) {
+        while let Some(c) = self.peek() {
+            if !c.is_whitespace() {
+                break;
+            }
+            self.pos += c.len_utf8();
+        }
+    }
+    /// read a bare or quoted token
+    fn read_text(&mut self) -> Result<String, SexprError> {
+        let start = self.pos;
+        if self.peek() != Some('"') {
+            while let Some(c) = self.peek() {
+                if c.is_whitespace() || matches!(c, '(' | ')' | '"') {
+                    break;
+                }
+                self.pos += c.len_utf8();
+            }
+            return Ok(self.input[start..self.pos].to_string());
+        }
+        self.pos += 1;
+        let mut text = String::new();
+        let mut escaped = false;
+        while let Some(c) = self.peek() {
+            self.pos += c.len_utf8();
+            match c {
+                _ if escaped => {
+                    text.push(c);
+                    escaped = false;
+                }
+                '\\' => escaped = true,
+                '"' => return Ok(text),
+                _ => text.push(c),
+            }
+        }
+        Err(SexprError::UnclosedString { offset: start })
+    }
+    fn read_child(&mut self) -> Result<Child, SexprError> {
+        self.skip_whitespace();
+        let start = self.pos;
+        match self.peek() {
+            None => Err(SexprError::UnexpectedEnd { offset: start }),
+            Some(')') => Err(SexprError::UnexpectedClosingPar { offset: start }),
+            Some('(') => {
+                self.pos += 1;
+                self.skip_whitespace();
+                if self.peek() == Some(')') {
+                    self.pos += 1;
+                    return Ok(Child::None);
+                }
+                if self.peek() == Some('(') {
+                    return self.read_empty_group();
+                }
+                self.read_operation(start)
+            }
+            Some(_) => {
+                let text = self.read_text()?;
+                let atom =
+                    (self.parse_atom)(&text).ok_or(SexprError::InvalidAtom { offset: start })?;
+                Ok(Child::Atom(self.tree.store_atom(atom)))
+            }
+        }
+    }
+    /// read the end of an empty group, `(())`, after its first
+    /// opening parenthesis
+    fn read_empty_group(&mut self) -> Result<Child, SexprError> {
+        let start = self.pos;
+        self.pos += 1;
+        for _ in 0..2 {
+            self.skip_whitespace();
+            if self.peek() != Some(')') {
+                return Err(SexprError::InvalidOperator { offset: start });
+            }
+            self.pos += 1;
+        }
+        Ok(Child::Node(self.tree.store_node(Node::empty())))
+    }
+    /// read the content of a list, after its opening parenthesis
+    fn read_operation(&mut self, start: usize) -> Result<Child, SexprError> {
+        let op_start = self.pos;
+        if self.peek() == Some('(') {
+            return Err(SexprError::InvalidOperator { offset: op_start });
+        }
+        let text = self.read_text()?;
+        let operator =
+            (self.parse_op)(&text).ok_or(SexprError::InvalidOperator { offset: op_start })?;
+        let mut operands = Vec::new();
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                None => {
+                    return Err(SexprError::UnexpectedEnd { offset: self.pos });
+                }
+                Some(')') => {
+                    self.pos += 1;
+                    break;
+                }
+                Some(_) => {
+                    let operand_start = self.pos;
+                    if operands.len() == 2 {
+                        return Err(SexprError::WrongOperandCount { offset: start });
+                    }
+                    let operand = self.read_child()?;
+                    if operands.last().is_some_and(|child: &Child| child.is_none()) {
+                        return Err(SexprError::MissingOperand {
+                            offset: operand_start,
+                        });
+                    }
+                    operands.push(operand);
+                }
+            }
+        }
+        let unary = match operands.len() {
+            1 => true,
+            2 => false,
+            _ => return Err(SexprError::WrongOperandCount { offset: start }),
+        };
+        let node_idx = self.tree.store_node(Node::new(Some(operator), unary));
+        self.tree.op_count += 1;
+        for (side, operand) in IntoIterator::into_iter([Side::Left, Side::Right]).zip(operands) {
+            self.tree.link_child(node_idx, side, operand);
+        }
+        Ok(Child::Node(node_idx))
+    }
+}
+
+/// S-expression conversions
+impl<Op, Atom> BeTree<Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone,
+{
+    fn push_sexpr_child<OS, AS>(&self, child: Child, op_str: &OS, atom_str: &AS, s: &mut String)
+    where
+        OS: Fn(&Op) -> String,
+        AS: Fn(&Atom) -> String,
+    {
+        let node_idx = match self.skip_non_empty_groups(child) {
+            Child::None => {
+                s.push_str("()");
+                return;
+            }
+            Child::Atom(atom_idx) => {
+                push_sexpr_text(&atom_str(&self.atoms[atom_idx]), s);
+                return;
+            }
+            Child::Node(node_idx) => node_idx,
+        };
+        let node = &self.nodes[node_idx];
+        let Some(op) = &node.operator else {
+            s.push_str("(())"); // empty group
+            return;
+        };
+        s.push('(');
+        push_sexpr_text(&op_str(op), s);
+        s.push(' ');
+        self.push_sexpr_child(node.left(), op_str, atom_str, s);
+        if !node.unary {
+            s.push(' ');
+            self.push_sexpr_child(node.right(), op_str, atom_str, s);
+        }
+        s.push(')');
+    }
+
+    /// write the expression as a S-expression, for example
+    /// `(& (| a b) (! c))`.
+    ///
+    /// A unary operation is a list of its operator and its operand, a
+    /// binary operation is a list of its operator and its two operands.
+    /// Groups don't appear, unless they're empty.
+    ///
+    /// Operators and atoms are written with the given functions, and
+    /// enclosed in double quotes when they're empty or contain whitespaces,
+    /// parenthesis, double quotes or backslashes (the last two being
+    /// escaped with a backslash).
+    ///
+    /// A missing operand, in an incomplete expression, is written `()`,
+    /// as is the empty expression. An empty group, like the `()` in
+    /// `a & ()`, is written `(())`.
+    pub fn to_sexpr<OS, AS>(&self, op_str: OS, atom_str: AS) -> String
+    where
+        OS: Fn(&Op) -> String,
+        AS: Fn(&Atom) -> String,
+    {
+        let mut s = String::new();
+        self.push_sexpr_child(Child::Node(self.head), &op_str, &atom_str, &mut s);
+        s
+    }
+
+    /// read an expression written with `to_sexpr`, operators and atoms
+    /// being built with the given functions, which return `None` for
+    /// invalid inputs.
+    pub fn from_sexpr<PO, PA>(input: &str, parse_op: PO, parse_atom: PA) -> Result<Self, SexprError>
+    where
+        PO: Fn(&str) -> Option<Op>,
+        PA: Fn(&str) -> Option<Atom>,
+    {
+        let mut tree = BeTree::new();
+        tree.nodes.clear();
+        let mut reader = SexprReader {
+            input,
+            pos: 0,
+            tree,
+            parse_op,
+            parse_atom,
+        };
+        let root = reader.read_child()?;
+        reader.skip_whitespace();
+        if reader.pos < input.len() {
+            return Err(SexprError::TrailingInput { offset: reader.pos });
+        }
+        let mut tree = reader.tree;
+        tree.set_decoded_root(root);
+        if !tree.is_empty() {
+            tree.last_pushed = TokenType::ClosingPar;
+        }
+        Ok(tree)
+    }
+}
@@ -0,0 +1,59 @@
+use core::fmt;
+
+/// Error returned by `BeTree::from_sexpr`, with the byte offset in
+/// the input of the problem
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SexprError {
+    /// The input ended before the expression was complete
+    UnexpectedEnd { offset: usize },
+    /// A closing parenthesis was found where an expression was expected
+    UnexpectedClosingPar { offset: usize },
+    /// A quoted string isn't closed
+    UnclosedString { offset: usize },
+    /// A list has no operand, or more than two
+    WrongOperandCount { offset: usize },
+    /// An operand is missing while the next one is present
+    MissingOperand { offset: usize },
+    /// The operator wasn't accepted by the operator parsing function
+    InvalidOperator { offset: usize },
+    /// The atom wasn't accepted by the atom parsing function
+    InvalidAtom { offset: usize },
+    /// There's something after the expression
+    TrailingInput { offset: usize },
+}
+
+impl SexprError {
+    /// the byte offset in the input of the problem
+    pub fn offset(self) -> usize {
+        match self {
+            Self::UnexpectedEnd { offset }
+            | Self::UnexpectedClosingPar { offset }
+            | Self::UnclosedString { offset }
+            | Self::WrongOperandCount { offset }
+            | Self::MissingOperand { offset }
+            | Self::InvalidOperator { offset }
+            | Self::InvalidAtom { offset }
+            | Self::TrailingInput { offset } => offset,
+        }
+    }
+}
+
+impl fmt::Display for SexprError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let problem = match self {
+            Self::UnexpectedEnd { .. } => "unexpected end of input",
+            Self::UnexpectedClosingPar { .. } => "unexpected closing parenthesis",
+            Self::UnclosedString { .. } => "unclosed string",
+            Self::WrongOperandCount { .. } => "an operator needs one or two operands",
+            Self::MissingOperand { .. } => "only the last operand may be missing",
+            Self::InvalidOperator { .. } => "invalid operator",
+            Self::InvalidAtom { .. } => "invalid atom",
+            Self::TrailingInput { .. } => "unexpected input after the expression",
+        };
+        write!(f, "{} at offset {}", problem, self.offset())
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SexprError {}
@@ -0,0 +1,253 @@
+use {crate::*, alloc::sync::Arc, core::fmt};
+
+/// One of the children of a node of a `SharedBeTree`
+#[derive(Debug, Clone)]
+pub enum SharedChild<Op, Atom> {
+    None,
+    Node(Arc<SharedNode<Op, Atom>>),
+    Atom(Arc<Atom>),
+}
+
+/// A node of a `SharedBeTree`
+#[derive(Debug, Clone)]
+pub struct SharedNode<Op, Atom> {
+    pub operator: Option<Op>,
+    pub unary: bool,
+    pub left: SharedChild<Op, Atom>,
+    pub right: SharedChild<Op, Atom>,
+    atom_count: usize,
+    explicit: bool,
+}
+
+/// A complete expression whose subtrees are reference counted, so that
+/// cloning it is cheap and that its variants share all their unchanged
+/// subtrees.
+///
+/// Get it with `BeTree::shared`.
+#[derive(Debug, Clone)]
+pub struct SharedBeTree<Op, Atom> {
+    root: SharedChild<Op, Atom>,
+}
+
+impl<Op, Atom> SharedChild<Op, Atom> {
+    fn atom_count(&self) -> usize {
+        match self {
+            Self::None => 0,
+            Self::Node(node) => node.atom_count,
+            Self::Atom(_) => 1,
+        }
+    }
+}
+
+impl<Op, Atom> SharedNode<Op, Atom> {
+    fn new(
+        operator: Option<Op>,
+        unary: bool,
+        left: SharedChild<Op, Atom>,
+        right: SharedChild<Op, Atom>,
+        explicit: bool,
+    ) -> Self {
+        let atom_count = left.atom_count() + right.atom_count();
+        Self {
+            operator,
+            unary,
+            left,
+            right,
+            atom_count,
+            explicit,
+        }
+    }
+}
+
+/// Conversion to a shared tree
+impl<Op, Atom> BeTree<Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone,
+{
+    fn shared_child(&self, child: Child, atoms: &[Arc<Atom>]) -> SharedChild<Op, Atom> {
+        match child {
+            Child::None => SharedChild::None,
+            Child::Atom(atom_idx) => SharedChild::Atom(Arc::clone(&atoms[atom_idx])),
+            Child::Node(node_idx) => {
+                let node = &self.nodes[node_idx];
+                SharedChild::Node(Arc::new(SharedNode::new(
+                    node.operator.clone(),
+                    node.unary,
+                    self.shared_child(node.left(), atoms),
+                    self.shared_child(node.right(), atoms),
+                    node.explicit,
+                )))
+            }
+        }
+    }
+
+    /// convert the tree into a tree whose subtrees are reference counted.
+    ///
+    /// Atoms aren't cloned. Only the reachable structure is kept.
+    pub fn shared(mut self) -> SharedBeTree<Op, Atom> {
+        let atoms: Vec<Arc<Atom>> = core::mem::take(&mut self.atoms)
+            .into_iter()
+            .map(Arc::new)
+            .collect();
+        SharedBeTree {
+            root: self.shared_child(Child::Node(self.head), &atoms),
+        }
+    }
+}
+
+impl<Op, Atom> SharedBeTree<Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone,
+{
+    /// the root of the expression (it's a node unless the
+    /// expression is empty)
+    pub fn root(&self) -> &SharedChild<Op, Atom> {
+        &self.root
+    }
+
+    /// the number of atoms of the expression
+    pub fn atom_count(&self) -> usize {
+        self.root.atom_count()
+    }
+
+    fn copy_child_into(child: &SharedChild<Op, Atom>, tree: &mut BeTree<Op, Atom>) -> Child {
+        match child {
+            SharedChild::None => Child::None,
+            SharedChild::Atom(atom) => Child::Atom(tree.store_atom(Atom::clone(atom))),
+            SharedChild::Node(node) => {
+                let mut new_node = Node::new(node.operator.clone(), node.unary);
+                new_node.explicit = node.explicit;
+                let node_idx = tree.store_node(new_node);
+                if node.operator.is_some() {
+                    tree.op_count += 1;
+                }
+                let left = Self::copy_child_into(&node.left, tree);
+                tree.link_child(node_idx, Side::Left, left);
+                let right = Self::copy_child_into(&node.right, tree);
+                tree.link_child(node_idx, Side::Right, right);
+                Child::Node(node_idx)
+            }
+        }
+    }
+
+    /// build a plain tree, cloning the atoms
+    pub fn to_be_tree(&self) -> BeTree<Op, Atom> {
+        let mut tree = BeTree::new();
+        tree.nodes.clear();
+        let root = Self::copy_child_into(&self.root, &mut tree);
+        tree.set_root(root);
+        if !tree.is_empty() {
+            tree.last_pushed = TokenType::ClosingPar;
+        }
+        tree
+    }
+
+    /// iterate over the atoms, from left to right
+    pub fn iter_atoms(&self) -> impl Iterator<Item = &Atom> {
+        let mut stack = vec![&self.root];
+        core::iter::from_fn(move || {
+            while let Some(child) = stack.pop() {
+                match child {
+                    SharedChild::None => {}
+                    SharedChild::Atom(atom) => return Some(&**atom),
+                    SharedChild::Node(node) => {
+                        stack.push(&node.right);
+                        stack.push(&node.left);
+                    }
+                }
+            }
+            None
+        })
+    }
+
+    /// replace the atom at the given index (counting the atoms from
+    /// left to right), copying only the nodes on the path from the
+    /// root to the atom if they're shared with other trees.
+    ///
+    /// Return the replaced atom, or `None` if there's no atom
+    /// at this index.
+    pub fn replace_atom(&mut self, index: usize, atom: Atom) -> Option<Arc<Atom>> {
+        if index >= self.atom_count() {
+            return None;
+        }
+        let mut child = &mut self.root;
+        let mut index = index;
+        loop {
+            match child {
+                SharedChild::Node(node) => {
+                    let node = Arc::make_mut(node);
+                    let left_count = node.left.atom_count();
+                    if index < left_count {
+                        child = &mut node.left;
+                    } else {
+                        index -= left_count;
+                        child = &mut node.right;
+                    }
+                }
+                SharedChild::Atom(old) => {
+                    return Some(core::mem::replace(old, Arc::new(atom)));
+                }
+                SharedChild::None => {
+                    return None;
+                }
+            }
+        }
+    }
+
+    /// return a tree with the atom at the given index (counting the
+    /// atoms from left to right) replaced, sharing with this tree
+    /// all the nodes which aren't on the path from the root to the atom.
+    ///
+    /// If there's no atom at this index, the returned tree is
+    /// identical to this one.
+    pub fn with_atom_replaced(&self, index: usize, atom: Atom) -> Self {
+        let mut tree = self.clone();
+        tree.replace_atom(index, atom);
+        tree
+    }
+
+    fn eval_child<R, EvalAtom, EvalOp, ShortCircuit>(
+        child: &SharedChild<Op, Atom>,
+        eval_atom: &EvalAtom,
+        eval_op: &EvalOp,
+        short_circuit: &ShortCircuit,
+    ) -> Option<R>
+    where
+        EvalAtom: Fn(&Atom) -> R,
+        EvalOp: Fn(&Op, R, Option<R>) -> R,
+        ShortCircuit: Fn(&Op, &R) -> bool,
+    {
+        let node = match child {
+            SharedChild::None => return None,
+            SharedChild::Atom(atom) => return Some(eval_atom(atom)),
+            SharedChild::Node(node) => node,
+        };
+        let left_value = Self::eval_child(&node.left, eval_atom, eval_op, short_circuit);
+        let Some(op) = &node.operator else {
+            return left_value;
+        };
+        let left_value = left_value?;
+        if short_circuit(op, &left_value) {
+            return Some(left_value);
+        }
+        let right_value = Self::eval_child(&node.right, eval_atom, eval_op, short_circuit);
+        Some(eval_op(op, left_value, right_value))
+    }
+
+    /// evaluate the expression, exactly as `BeTree::eval` would
+    pub fn eval<R, EvalAtom, EvalOp, ShortCircuit>(
+        &self,
+        eval_atom: EvalAtom,
+        eval_op: EvalOp,
+        short_circuit: ShortCircuit,
+    ) -> Option<R>
+    where
+        EvalAtom: Fn(&Atom) -> R,
+        EvalOp: Fn(&Op, R, Option<R>) -> R,
+        ShortCircuit: Fn(&Op, &R) -> bool,
+    {
+        Self::eval_child(&self.root, &eval_atom, &eval_op, &short_circuit)
+    }
+}
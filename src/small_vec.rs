@@ -0,0 +1,232 @@
+use {
+    crate::*,
+    core::{
+        fmt,
+        iter::FromIterator,
+        mem::{self, MaybeUninit},
+        ops::{Deref, DerefMut},
+        ptr, slice,
+    },
+};
+
+/// A vector storing up to `N` items inline, and moving them to the
+/// heap when more are pushed.
+///
+/// It's the storage of the nodes of a tree, so that an empty tree
+/// doesn't allocate, and of its atoms too with the `inline-storage`
+/// feature, so that small expressions don't allocate.
+/// Only the part of the `Vec` API needed by the tree is provided, most
+/// operations going through the slice it dereferences to.
+pub(crate) enum SmallVec<T, const N: usize> {
+    /// the first `len` items are initialized
+    Inline {
+        len: usize,
+        items: [MaybeUninit<T>; N],
+    },
+    Heap(Vec<T>),
+}
+
+impl<T, const N: usize> SmallVec<T, N> {
+    pub fn new() -> Self {
+        Self::Inline {
+            len: 0,
+            items: core::array::from_fn(|_| MaybeUninit::uninit()),
+        }
+    }
+    pub fn with_capacity(capacity: usize) -> Self {
+        if capacity <= N {
+            Self::new()
+        } else {
+            Self::Heap(Vec::with_capacity(capacity))
+        }
+    }
+    pub fn capacity(&self) -> usize {
+        match self {
+            Self::Inline { .. } => N,
+            Self::Heap(vec) => vec.capacity(),
+        }
+    }
+    pub fn push(&mut self, item: T) {
+        match self {
+            Self::Inline { len, items } if *len < N => {
+                items[*len].write(item);
+                *len += 1;
+            }
+            Self::Inline { .. } => {
+                let mut vec = Vec::with_capacity(2 * N.max(1));
+                self.move_into(&mut vec);
+                vec.push(item);
+                *self = Self::Heap(vec);
+            }
+            Self::Heap(vec) => vec.push(item),
+        }
+    }
+    pub fn clear(&mut self) {
+        match self {
+            Self::Inline { len, items } => {
+                // the length is reset first so that the items
+                // can't be dropped twice
+                let len = mem::replace(len, 0);
+                // SAFETY: the first `len` items were initialized
+                unsafe {
+                    ptr::drop_in_place(ptr::slice_from_raw_parts_mut(
+                        items.as_mut_ptr() as *mut T,
+                        len,
+                    ));
+                }
+            }
+            Self::Heap(vec) => vec.clear(),
+        }
+    }
+    /// move the items back inline when they fit, or
+    /// shrink the heap allocation
+    pub fn shrink_to_fit(&mut self) {
+        if let Self::Heap(vec) = self {
+            if vec.len() <= N {
+                let vec = mem::take(vec);
+                *self = vec.into();
+            } else {
+                vec.shrink_to_fit();
+            }
+        }
+    }
+    /// move all the items at the end of a collection, leaving this
+    /// vector empty
+    pub(crate) fn move_into<E: Extend<T>>(&mut self, target: &mut E) {
+        match self {
+            Self::Inline { len, items } => {
+                // the length is reset first so that the items
+                // can't be dropped twice
+                let len = mem::replace(len, 0);
+                target.extend(items[..len].iter().map(|item| {
+                    // SAFETY: the first `len` items were initialized,
+                    // and each one is read only once
+                    unsafe { item.assume_init_read() }
+                }));
+            }
+            Self::Heap(vec) => target.extend(vec.drain(..)),
+        }
+    }
+    /// move out all the items, leaving the vector empty
+    fn take_all(&mut self) -> Vec<T> {
+        if let Self::Heap(vec) = self {
+            return mem::take(vec);
+        }
+        let mut vec = Vec::new();
+        self.move_into(&mut vec);
+        vec
+    }
+}
+
+impl<T, const N: usize> Drop for SmallVec<T, N> {
+    fn drop(&mut self) {
+        if let Self::Inline { .. } = self {
+            self.clear();
+        }
+    }
+}
+
+impl<T, const N: usize> Deref for SmallVec<T, N> {
+    type Target = [T];
+    fn deref(&self) -> &[T] {
+        match self {
+            // SAFETY: the first `len` items are initialized, and
+            // `MaybeUninit<T>` has the same layout as `T`
+            Self::Inline { len, items } => unsafe {
+                slice::from_raw_parts(items.as_ptr() as *const T, *len)
+            },
+            Self::Heap(vec) => vec,
+        }
+    }
+}
+
+impl<T, const N: usize> DerefMut for SmallVec<T, N> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        match self {
+            // SAFETY: as for `deref`
+            Self::Inline { len, items } => unsafe {
+                slice::from_raw_parts_mut(items.as_mut_ptr() as *mut T, *len)
+            },
+            Self::Heap(vec) => vec,
+        }
+    }
+}
+
+impl<T, const N: usize> Default for SmallVec<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone, const N: usize> Clone for SmallVec<T, N> {
+    fn clone(&self) -> Self {
+        match self {
+            Self::Inline { .. } => self.iter().cloned().collect(),
+            Self::Heap(vec) => Self::Heap(vec.clone()),
+        }
+    }
+}
+
+impl<T: PartialEq, const N: usize> PartialEq for SmallVec<T, N> {
+    fn eq(&self, other: &Self) -> bool {
+        **self == **other
+    }
+}
+
+impl<T: fmt::Debug, const N: usize> fmt::Debug for SmallVec<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T, const N: usize> Extend<T> for SmallVec<T, N> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.push(item);
+        }
+    }
+}
+
+impl<T, const N: usize> FromIterator<T> for SmallVec<T, N> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let iter = iter.into_iter();
+        if iter.size_hint().0 > N {
+            return Self::Heap(iter.collect());
+        }
+        let mut small_vec = Self::new();
+        small_vec.extend(iter);
+        small_vec
+    }
+}
+
+impl<T, const N: usize> IntoIterator for SmallVec<T, N> {
+    type Item = T;
+    type IntoIter = alloc::vec::IntoIter<T>;
+    fn into_iter(mut self) -> Self::IntoIter {
+        self.take_all().into_iter()
+    }
+}
+
+impl<'v, T, const N: usize> IntoIterator for &'v SmallVec<T, N> {
+    type Item = &'v T;
+    type IntoIter = slice::Iter<'v, T>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T, const N: usize> From<Vec<T>> for SmallVec<T, N> {
+    fn from(vec: Vec<T>) -> Self {
+        if vec.len() <= N {
+            vec.into_iter().collect()
+        } else {
+            Self::Heap(vec)
+        }
+    }
+}
+
+impl<T, const N: usize> From<SmallVec<T, N>> for Vec<T> {
+    fn from(mut small_vec: SmallVec<T, N>) -> Self {
+        small_vec.take_all()
+    }
+}
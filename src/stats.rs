@@ -0,0 +1,239 @@
+#[cfg(feature = "std")]
+use std::{collections::HashMap, hash::Hash};
+use {
+    crate::*,
+    core::{fmt, mem, slice},
+};
+
+/// Measures of the size and shape of an expression, as given
+/// by `BeTree::stats`.
+///
+/// Only the structure reachable from the head is measured.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ExprStats {
+    /// The number of atoms in the expression
+    pub atom_count: usize,
+    /// The number of nodes, including groups (i.e. parenthesis)
+    pub reachable_node_count: usize,
+    /// The number of operators, unary or binary
+    pub operator_count: usize,
+    /// The number of unary operators
+    pub unary_count: usize,
+    /// The number of binary operators
+    pub binary_count: usize,
+    /// The maximal depth of a node or atom, the head being at depth 0
+    /// (see `BeTree::depth`)
+    pub max_depth: usize,
+    /// The number of parenthesis which are open and not yet closed
+    pub open_parentheses: usize,
+}
+
+/// The memory used by a tree, as given by `BeTree::memory_usage`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryUsage {
+    /// The number of atoms in the arena, reachable or not
+    pub atoms_len: usize,
+    /// The capacity of the arena of atoms
+    pub atoms_capacity: usize,
+    /// The number of nodes in the arena, reachable or not
+    pub nodes_len: usize,
+    /// The capacity of the arena of nodes
+    pub nodes_capacity: usize,
+    /// The number of nodes reachable from the head
+    pub reachable_nodes: usize,
+    /// The number of distinct atoms reachable from the head
+    pub reachable_atoms: usize,
+    /// An approximation of the number of bytes used by the tree, not
+    /// counting what the atoms and operators may themselves allocate
+    pub approx_bytes: usize,
+}
+
+/// The uses of an operator in an expression, as given in an
+/// `OperatorCensus`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OperatorCount<Op> {
+    pub operator: Op,
+    /// The number of nodes where the operator is unary
+    pub unary_count: usize,
+    /// The number of nodes where the operator is binary
+    pub binary_count: usize,
+}
+
+impl<Op> OperatorCount<Op> {
+    fn new(operator: Op) -> Self {
+        Self {
+            operator,
+            unary_count: 0,
+            binary_count: 0,
+        }
+    }
+    fn add(&mut self, unary: bool) {
+        if unary {
+            self.unary_count += 1;
+        } else {
+            self.binary_count += 1;
+        }
+    }
+    /// the number of uses of the operator, unary or binary
+    pub fn count(&self) -> usize {
+        self.unary_count + self.binary_count
+    }
+}
+
+/// The uses of the distinct operators of an expression, as given
+/// by `BeTree::operator_census`.
+///
+/// Only the nodes reachable from the head are counted. Operators are
+/// in the order of their first use in the expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OperatorCensus<Op> {
+    counts: Vec<OperatorCount<Op>>,
+}
+
+impl<Op: PartialEq> OperatorCensus<Op> {
+    /// the uses of the operator, or none if it's not in the expression
+    pub fn get(&self, operator: &Op) -> Option<&OperatorCount<Op>> {
+        self.counts.iter().find(|count| &count.operator == operator)
+    }
+    /// the number of uses of the operator, unary or binary
+    pub fn count(&self, operator: &Op) -> usize {
+        self.get(operator).map_or(0, OperatorCount::count)
+    }
+    pub fn iter(&self) -> slice::Iter<'_, OperatorCount<Op>> {
+        self.counts.iter()
+    }
+    /// the number of distinct operators
+    pub fn len(&self) -> usize {
+        self.counts.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+}
+
+impl<'c, Op> IntoIterator for &'c OperatorCensus<Op> {
+    type Item = &'c OperatorCount<Op>;
+    type IntoIter = slice::Iter<'c, OperatorCount<Op>>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.counts.iter()
+    }
+}
+
+/// Census of hashable operators
+#[cfg(feature = "std")]
+impl<Op, Atom> BeTree<Op, Atom>
+where
+    Op: fmt::Debug + Clone + Eq + Hash,
+    Atom: fmt::Debug + Clone,
+{
+    /// count the uses of every distinct operator in the reachable
+    /// nodes, split by unary and binary uses
+    pub fn operator_census(&self) -> OperatorCensus<Op> {
+        let mut indices: HashMap<&Op, usize> = HashMap::new();
+        let mut counts: Vec<OperatorCount<Op>> = Vec::new();
+        for (_, node) in self.iter_nodes() {
+            let Some(operator) = &node.operator else {
+                continue;
+            };
+            let next_index = counts.len();
+            let index = *indices.entry(operator).or_insert(next_index);
+            if index == next_index {
+                counts.push(OperatorCount::new(operator.clone()));
+            }
+            counts[index].add(node.unary);
+        }
+        OperatorCensus { counts }
+    }
+}
+
+/// Statistics
+impl<Op, Atom> BeTree<Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone,
+{
+    /// measure the expression, in a single traversal
+    pub fn stats(&self) -> ExprStats {
+        let mut stats = ExprStats {
+            open_parentheses: self.openness,
+            ..Default::default()
+        };
+        let mut stack = vec![(Child::Node(self.head), 0)];
+        while let Some((child, depth)) = stack.pop() {
+            match child {
+                Child::None => continue,
+                Child::Atom(_) => {
+                    stats.atom_count += 1;
+                }
+                Child::Node(node_idx) => {
+                    let node = &self.nodes[node_idx];
+                    stats.reachable_node_count += 1;
+                    if node.operator.is_some() {
+                        stats.operator_count += 1;
+                        if node.unary {
+                            stats.unary_count += 1;
+                        } else {
+                            stats.binary_count += 1;
+                        }
+                    }
+                    stack.push((node.right(), depth + 1));
+                    stack.push((node.left(), depth + 1));
+                }
+            }
+            stats.max_depth = stats.max_depth.max(depth);
+        }
+        stats
+    }
+
+    /// count the uses of every distinct operator in the reachable nodes,
+    /// exactly like `operator_census`, but for operators which can't
+    /// be hashed.
+    ///
+    /// The complexity is quadratic in the number of distinct operators.
+    pub fn operator_census_by_eq(&self) -> OperatorCensus<Op> {
+        let mut counts: Vec<OperatorCount<Op>> = Vec::new();
+        for (_, node) in self.iter_nodes() {
+            let Some(operator) = &node.operator else {
+                continue;
+            };
+            match counts.iter_mut().find(|count| &count.operator == operator) {
+                Some(count) => count.add(node.unary),
+                None => {
+                    let mut count = OperatorCount::new(operator.clone());
+                    count.add(node.unary);
+                    counts.push(count);
+                }
+            }
+        }
+        OperatorCensus { counts }
+    }
+
+    /// measure the memory used by the tree, and how much of it is used
+    /// by the nodes and atoms which aren't reachable anymore
+    pub fn memory_usage(&self) -> MemoryUsage {
+        let mut reachable = vec![false; self.atoms.len()];
+        for (atom_id, _) in self.iter_atoms_in_tree() {
+            reachable[atom_id] = true;
+        }
+        let approx_bytes = mem::size_of::<Self>()
+            + self.atoms.capacity() * mem::size_of::<Atom>()
+            + self.nodes.capacity() * mem::size_of::<Node<Op>>();
+        MemoryUsage {
+            atoms_len: self.atoms.len(),
+            atoms_capacity: self.atoms.capacity(),
+            nodes_len: self.nodes.len(),
+            nodes_capacity: self.nodes.capacity(),
+            reachable_nodes: self.iter_nodes().count(),
+            reachable_atoms: reachable.iter().filter(|&&r| r).count(),
+            approx_bytes,
+        }
+    }
+
+    /// remove the nodes and atoms which aren't reachable (see `compact`)
+    /// and release the unused capacity of the arenas
+    pub fn shrink_to_fit(&mut self) {
+        self.compact();
+        self.atoms.shrink_to_fit();
+        self.nodes.shrink_to_fit();
+    }
+}
@@ -0,0 +1,84 @@
+use {
+    crate::*,
+    core::{
+        fmt,
+        hash::{Hash, Hasher},
+    },
+};
+
+/// Structural comparison and hashing
+impl<Op, Atom> BeTree<Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone + PartialEq,
+{
+    /// tell whether a subexpression of the tree is equal to a
+    /// subexpression of another tree, groups being ignored
+    pub(crate) fn same_child_as(&self, child: Child, other: &Self, other_child: Child) -> bool {
+        match (self.skip_groups(child), other.skip_groups(other_child)) {
+            (Child::None, Child::None) => true,
+            (Child::Atom(a), Child::Atom(b)) => self.atoms[a] == other.atoms[b],
+            (Child::Node(a), Child::Node(b)) => {
+                let (a, b) = (&self.nodes[a], &other.nodes[b]);
+                a.operator == b.operator
+                    && a.unary == b.unary
+                    && self.same_child_as(a.left(), other, b.left())
+                    && self.same_child_as(a.right(), other, b.right())
+            }
+            _ => false,
+        }
+    }
+
+    /// tell whether the two trees are the same expression, that is have
+    /// the same operations on the same atoms, whatever their
+    /// representation: orphan nodes, storage order and groups without
+    /// operator are ignored.
+    ///
+    /// This is the equality matching the `Hash` implementation, while
+    /// `PartialEq` compares the representations.
+    pub fn structural_eq(&self, other: &Self) -> bool {
+        self.same_child_as(Child::Node(self.head), other, Child::Node(other.head))
+    }
+}
+
+impl<Op, Atom> BeTree<Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq + Hash,
+    Atom: fmt::Debug + Clone + Hash,
+{
+    fn hash_child<H: Hasher>(&self, child: Child, state: &mut H) {
+        match self.skip_groups(child) {
+            Child::None => state.write_u8(0),
+            Child::Atom(atom_idx) => {
+                state.write_u8(1);
+                self.atoms[atom_idx].hash(state);
+            }
+            Child::Node(node_idx) => {
+                let node = &self.nodes[node_idx];
+                state.write_u8(2);
+                node.operator.hash(state);
+                node.unary.hash(state);
+                self.hash_child(node.left(), state);
+                self.hash_child(node.right(), state);
+            }
+        }
+    }
+}
+
+/// Hash the expression, that is only the structure reachable from the
+/// head, consistently with `structural_eq`: two structurally equal trees
+/// have the same hash.
+///
+/// Note that `PartialEq` compares the representations, so it's stricter
+/// than `structural_eq`. To use trees as keys of a `HashMap` with
+/// structural equality, you may wrap them in a newtype whose equality
+/// calls `structural_eq`.
+impl<Op, Atom> Hash for BeTree<Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq + Hash,
+    Atom: fmt::Debug + Clone + Hash,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.hash_child(Child::Node(self.head), state);
+    }
+}
@@ -0,0 +1,56 @@
+use {crate::*, core::fmt};
+
+/// Error returned when building a tree from parts which don't
+/// make a valid structure
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum StructureError {
+    /// There's no node at all (even an empty tree has a head node)
+    NoNode,
+    /// A link points to a node which doesn't exist
+    UnknownNode(NodeId),
+    /// A link points to an atom which doesn't exist
+    UnknownAtom(AtomId),
+    /// The node is reached twice when walking down from the head (this
+    /// is also how cycles are detected, when the parents are consistent)
+    MultipleLinks(NodeId),
+    /// The node given to a `TreeBuilder` has a right child without left
+    /// child, or is a group or a unary operation with a right child
+    MisplacedChild(NodeId),
+    /// The parent of the node isn't the node which has it as child
+    WrongParent(NodeId),
+    /// The tail (where the next tokens are added) isn't reachable
+    /// from the head
+    UnreachableTail(NodeId),
+    /// The count of open parenthesis doesn't match the groups
+    /// which are open between the tail and the head
+    WrongOpenness { openness: usize, open_groups: usize },
+    /// The group has no content, as in `a & ()`
+    EmptyGroup(NodeId),
+}
+
+impl fmt::Display for StructureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoNode => write!(f, "no node"),
+            Self::UnknownNode(node_id) => write!(f, "no node with id {}", node_id),
+            Self::UnknownAtom(atom_id) => write!(f, "no atom with id {}", atom_id),
+            Self::MultipleLinks(node_id) => write!(f, "node {} is linked twice", node_id),
+            Self::MisplacedChild(node_id) => write!(f, "node {} has a misplaced child", node_id),
+            Self::WrongParent(node_id) => write!(f, "node {} has a wrong parent", node_id),
+            Self::UnreachableTail(node_id) => write!(f, "tail node {} isn't reachable", node_id),
+            Self::WrongOpenness {
+                openness,
+                open_groups,
+            } => write!(
+                f,
+                "openness is {} but there are {} open groups",
+                openness, open_groups
+            ),
+            Self::EmptyGroup(node_id) => write!(f, "group {} is empty", node_id),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for StructureError {}
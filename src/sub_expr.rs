@@ -0,0 +1,165 @@
+use {crate::*, core::fmt};
+
+/// A reference to a subexpression of a tree: a node and its
+/// descendants, or an atom
+#[derive(Debug)]
+pub struct SubExprRef<'t, Op, Atom> {
+    tree: &'t BeTree<Op, Atom>,
+    child: Child,
+}
+
+/// The operands of a binary operation, as given by `BeTree::root_operands`
+pub type SubExprPair<'t, Op, Atom> = (
+    Option<SubExprRef<'t, Op, Atom>>,
+    Option<SubExprRef<'t, Op, Atom>>,
+);
+
+impl<Op, Atom> Clone for SubExprRef<'_, Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone,
+{
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<Op, Atom> Copy for SubExprRef<'_, Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone,
+{
+}
+
+impl<'t, Op, Atom> SubExprRef<'t, Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone,
+{
+    /// the node or atom at the top of the subexpression
+    pub fn child(&self) -> Child {
+        self.child
+    }
+    /// the atom, if the subexpression is just an atom
+    pub fn atom(&self) -> Option<&'t Atom> {
+        match self.child {
+            Child::Atom(atom_id) => Some(&self.tree.atoms[atom_id]),
+            _ => None,
+        }
+    }
+    /// iterate over the atoms of the subexpression, with their ids,
+    /// from left to right
+    pub fn iter_atoms(&self) -> TreeAtomIter<'t, Op, Atom> {
+        TreeAtomIter::new(self.tree, self.child, false)
+    }
+    /// build an independent tree from a copy of the subexpression
+    pub fn to_tree(&self) -> BeTree<Op, Atom> {
+        self.tree.child_tree(self.child)
+    }
+}
+
+/// An operand of a chain, as given by `BeTree::as_chain`
+#[derive(Debug)]
+pub enum ChainOperand<'t, Op, Atom> {
+    Atom(AtomId, &'t Atom),
+    SubExpr(SubExprRef<'t, Op, Atom>),
+}
+
+impl<Op, Atom> Clone for ChainOperand<'_, Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone,
+{
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<Op, Atom> Copy for ChainOperand<'_, Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone,
+{
+}
+
+/// Top level structure
+impl<Op, Atom> BeTree<Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone,
+{
+    /// a reference to the child as a subexpression, or `None`
+    /// if there's no child
+    pub(crate) fn sub_expr(&self, child: Child) -> Option<SubExprRef<'_, Op, Atom>> {
+        child.is_some().then_some(SubExprRef { tree: self, child })
+    }
+
+    /// the top level operator of the expression, and whether it's
+    /// unary, or `None` if the expression has no operator.
+    ///
+    /// The groups around the whole expression are ignored, so that
+    /// the root operator of `((a) & b)` is `&`.
+    pub fn root_operator(&self) -> Option<(&Op, bool)> {
+        let Child::Node(node_idx) = self.skip_groups(Child::Node(self.head)) else {
+            return None;
+        };
+        let node = &self.nodes[node_idx];
+        node.operator
+            .as_ref()
+            .map(|operator| (operator, node.unary))
+    }
+
+    /// the operands of the top level operator of the expression (the
+    /// second one being `None` for a unary operator, or when it's still
+    /// missing), groups around the whole expression being ignored.
+    ///
+    /// If the expression is just an atom, it's returned as first operand.
+    pub fn root_operands(&self) -> SubExprPair<'_, Op, Atom> {
+        match self.skip_groups(Child::Node(self.head)) {
+            Child::Node(node_idx) => {
+                let node = &self.nodes[node_idx];
+                (self.sub_expr(node.left()), self.sub_expr(node.right()))
+            }
+            child => (self.sub_expr(child), None),
+        }
+    }
+
+    /// if the expression is a chain of the given binary operator, for
+    /// example `a & (b | c) & !d` for `&`, return its operands from left
+    /// to right.
+    ///
+    /// Groups in the chain are traversed, so `a & (b & c)` is a chain of
+    /// three operands. An expression whose top level operator isn't a
+    /// binary operator (e.g. an atom or a negation) is a chain of one
+    /// operand. An empty expression is a chain without operand.
+    ///
+    /// Return `None` if the top level operator is another binary
+    /// operator, or if the top level operation is incomplete.
+    pub fn as_chain(&self, operator: &Op) -> Option<Vec<ChainOperand<'_, Op, Atom>>> {
+        let mut operands = Vec::new();
+        self.collect_chain(
+            Child::Node(self.head),
+            operator,
+            &mut operands,
+            &mut Vec::new(),
+        );
+        if let [Child::Node(node_idx)] = operands[..] {
+            let node = &self.nodes[node_idx];
+            if node.operator.is_some() && !node.unary {
+                return None;
+            }
+        }
+        let operands = operands
+            .into_iter()
+            .filter_map(|operand| match operand {
+                Child::None => None,
+                Child::Atom(atom_id) => Some(ChainOperand::Atom(atom_id, &self.atoms[atom_id])),
+                Child::Node(_) => Some(ChainOperand::SubExpr(SubExprRef {
+                    tree: self,
+                    child: operand,
+                })),
+            })
+            .collect();
+        Some(operands)
+    }
+}
@@ -0,0 +1,23 @@
+use core::fmt;
+
+/// Error returned when the atoms given to a `TreeTemplate` don't
+/// match its structure
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TemplateError {
+    /// The template has `expected` atoms but `found` were given
+    AtomCountMismatch { expected: usize, found: usize },
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::AtomCountMismatch { expected, found } => {
+                write!(f, "expected {} atoms but got {}", expected, found)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for TemplateError {}
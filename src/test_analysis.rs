@@ -0,0 +1,100 @@
+//! tests of the analysis of the structure
+
+use crate::{test_util::*, AtomId};
+
+fn group_indices(groups: Vec<Vec<AtomId>>) -> Vec<Vec<usize>> {
+    groups.into_iter().map(indices).collect()
+}
+
+/// the atoms of the subexpressions of the groups found
+/// by `common_subexpressions`
+fn common_atoms(input: &str, min_size: usize) -> Vec<(String, usize, usize)> {
+    let expr = parse(input);
+    expr.common_subexpressions(min_size)
+        .into_iter()
+        .map(|(nodes, size)| {
+            let atoms = expr.subtree(nodes[0]).unwrap().iter_atoms().collect();
+            for &node in &nodes {
+                let subtree = expr.subtree(node).unwrap();
+                assert_eq!(subtree.iter_atoms().collect::<String>(), atoms);
+            }
+            (atoms, nodes.len(), size)
+        })
+        .collect()
+}
+
+#[test]
+fn common_subexpressions_at_different_depths() {
+    assert_eq!(
+        common_atoms("(A & B) | !(C | (A & B)) & ((D | (A & B)) | !(A & B))", 2),
+        vec![("AB".to_string(), 4, 2)],
+    );
+    // groups don't prevent the detection
+    assert_eq!(
+        common_atoms("((A & B)) | (C | (A & (B)))", 2),
+        vec![("AB".to_string(), 2, 2)],
+    );
+    // the operators and the order of operands matter
+    assert!(common_atoms("(A & B) | (A | B) | (B & A)", 2).is_empty());
+}
+
+#[test]
+fn common_subexpressions_are_maximal() {
+    // the repeated A&B inside the repeated (A&B | C) isn't reported
+    assert_eq!(
+        common_atoms("(A & B | C) & (A & B | C)", 2),
+        vec![("ABC".to_string(), 2, 3)],
+    );
+    // but other occurrences elsewhere are
+    assert_eq!(
+        common_atoms("(A & B | C) & (A & B | C) & !(D | (A & B)) | (A & B)", 2),
+        vec![("ABC".to_string(), 2, 3), ("AB".to_string(), 2, 2)],
+    );
+}
+
+#[test]
+fn common_subexpressions_min_size() {
+    let input = "!A & !A & (B | !A) & (B | !A)";
+    assert_eq!(common_atoms(input, 2), vec![("BA".to_string(), 2, 2)]);
+    assert_eq!(
+        common_atoms(input, 1),
+        vec![("BA".to_string(), 2, 2), ("A".to_string(), 2, 1)],
+    );
+    assert!(common_atoms(input, 3).is_empty());
+}
+
+#[test]
+fn atom_groups() {
+    let expr = parse("A & (B | !(A & C)) | !!(C & (D | A))");
+    let expected = vec![vec![0, 2, 6], vec![1], vec![3, 4], vec![5]];
+    assert_eq!(group_indices(expr.atom_groups()), expected);
+    assert_eq!(group_indices(expr.atom_groups_by_eq()), expected);
+    assert_eq!(expr.count_distinct_atoms(), 4);
+    // all atoms unique
+    let expr = parse("A & (B | !(C & D))");
+    assert_eq!(
+        group_indices(expr.atom_groups()),
+        vec![vec![0], vec![1], vec![2], vec![3]]
+    );
+    assert_eq!(expr.count_distinct_atoms(), 4);
+    assert_eq!(parse("").count_distinct_atoms(), 0);
+}
+
+#[test]
+fn atom_groups_ignore_unreachable_atoms() {
+    let mut expr = parse("A & M & (A | M)");
+    expr.replace_atoms_with_trees(|&c| if c == 'M' { Some(parse("B")) } else { None });
+    assert_eq!(
+        group_indices(expr.atom_groups()),
+        vec![vec![0, 2], vec![4, 5]]
+    );
+    assert_eq!(expr.count_distinct_atoms(), 2);
+    // after a deduplication, the remaining atoms are linked several times
+    let mut expr = parse("A & B & (A | B)");
+    expr.dedup_atoms();
+    assert_eq!(group_indices(expr.atom_groups()), vec![vec![0], vec![1]]);
+    assert_eq!(
+        group_indices(expr.atom_groups_by_eq()),
+        vec![vec![0], vec![1]]
+    );
+}
@@ -0,0 +1,78 @@
+//! tests of the bottom-up annotation of trees
+
+use {
+    super::*,
+    crate::test_util::*,
+    std::{
+        collections::{hash_map::DefaultHasher, HashMap},
+        hash::{Hash, Hasher},
+    },
+};
+
+#[test]
+fn subtree_atom_counts() {
+    // nodes: 0 is the `&`, 1 the `A | B`, 2 the `!`, 4 the `... | E`, 3 the `C | D`
+    let expr = parse("(A | B) & !(C | D | E)");
+    let counts = expr.annotate(
+        |_, _| 1,
+        |_, _, _, left, right| left + right.copied().unwrap_or(0),
+    );
+    assert_eq!(counts.head(), Some(&5));
+    let node_counts: Vec<_> = (0..5)
+        .map(|node_id| counts.node(NodeId::new(node_id)).copied())
+        .collect();
+    assert_eq!(
+        node_counts,
+        vec![Some(5), Some(2), Some(3), Some(2), Some(3)]
+    );
+    assert_eq!(counts.node(NodeId::new(5)), None);
+    assert_eq!(counts.atom(AtomId::new(4)), Some(&1));
+    // nodes without operand have no value
+    let expr = parse("A & !");
+    let counts = expr.annotate(
+        |_, _| 1,
+        |_, _, _, left, right| left + right.copied().unwrap_or(0),
+    );
+    assert_eq!(counts.head(), Some(&1));
+    assert_eq!(counts.node(NodeId::new(1)), None); // the `!`
+}
+
+#[test]
+fn subtree_hashes_find_duplicates() {
+    let expr = parse("(A | !B) & C | !(D & (A | !B)) | (A | !B)");
+    let hashes = expr.annotate(
+        |_, atom| {
+            let mut hasher = DefaultHasher::new();
+            atom.hash(&mut hasher);
+            hasher.finish()
+        },
+        |_, operator, _, left, right| {
+            let mut hasher = DefaultHasher::new();
+            (operator, left, right).hash(&mut hasher);
+            hasher.finish()
+        },
+    );
+    let mut by_hash: HashMap<u64, Vec<NodeId>> = HashMap::new();
+    for (node_id, node) in expr.iter_nodes() {
+        if node.operator.is_some() {
+            by_hash
+                .entry(*hashes.node(node_id).unwrap())
+                .or_default()
+                .push(node_id);
+        }
+    }
+    let mut duplicates: Vec<Vec<String>> = by_hash
+        .into_values()
+        .filter(|ids| ids.len() > 1)
+        .map(|ids| {
+            ids.iter()
+                .map(|&id| tokens_string(&expr.subtree(id).unwrap()))
+                .collect()
+        })
+        .collect();
+    duplicates.sort();
+    assert_eq!(
+        duplicates,
+        vec![vec!["!B", "!B", "!B"], vec!["A|!B", "A|!B", "A|!B"],]
+    );
+}
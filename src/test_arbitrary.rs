@@ -0,0 +1,73 @@
+//! tests of the generation of arbitrary trees
+
+use {
+    super::*,
+    crate::test_util::*,
+    arbitrary::{Arbitrary, Unstructured},
+};
+
+/// deterministic pseudo-random bytes
+fn random_bytes(seed: u64, len: usize) -> Vec<u8> {
+    let mut state = seed;
+    (0..len)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state as u8
+        })
+        .collect()
+}
+
+/// evaluate the tree, whatever the position of the operators, as
+/// arbitrary trees may have any operator in unary or binary position
+fn eval_any(expr: &BeTree<BoolOperator, bool>) -> Option<bool> {
+    expr.eval(
+        |&b| b,
+        |op, a, b| match (op, b) {
+            (BoolOperator::And, Some(b)) => a & b,
+            (BoolOperator::Or, Some(b)) => a | b,
+            (BoolOperator::Not, Some(b)) => a ^ b,
+            (_, None) => !a,
+        },
+        |_, _| false,
+    )
+}
+
+#[test]
+fn arbitrary_trees_are_valid_and_complete() {
+    let mut non_empty = 0;
+    for seed in 1..300 {
+        let bytes = random_bytes(seed, (seed % 200) as usize);
+        let mut u = Unstructured::new(&bytes);
+        let expr = BeTree::<BoolOperator, bool>::arbitrary(&mut u).unwrap();
+        check_links(&expr);
+        if expr.is_empty() {
+            continue;
+        }
+        non_empty += 1;
+        assert!(expr.atoms.len() <= bytes.len());
+        let value = eval_any(&expr);
+        assert!(value.is_some(), "incomplete tree {:?}", expr);
+        let replayed = from_tokens(expr.to_tokens());
+        assert_eq!(value, eval_any(&replayed));
+    }
+    assert!(non_empty > 200);
+}
+
+#[test]
+fn arbitrary_tree_of_no_data_is_empty() {
+    let mut u = Unstructured::new(&[]);
+    let expr = BeTree::<BoolOperator, bool>::arbitrary(&mut u).unwrap();
+    assert!(expr.is_empty());
+    check_links(&expr);
+}
+
+#[test]
+fn arbitrary_tokens() {
+    let bytes = random_bytes(7, 64);
+    let mut u = Unstructured::new(&bytes);
+    for _ in 0..10 {
+        Token::<BoolOperator, char>::arbitrary(&mut u).unwrap();
+    }
+}
@@ -0,0 +1,133 @@
+//! tests of the trees allocated in an arena
+//!
+//! The other tests, built with the helpers of `test_util`, also run on
+//! trees allocated in arenas when the `arena` feature is enabled.
+
+use {super::*, crate::test_util::*, std::thread};
+
+/// complete the tree with the tokens of a string where atoms are single chars
+fn push_chars(mut expr: BeTree<BoolOperator, char>, input: &str) -> BeTree<BoolOperator, char> {
+    for c in input.chars() {
+        match c {
+            '&' => expr.push_operator(BoolOperator::And),
+            '|' => expr.push_operator(BoolOperator::Or),
+            '!' => expr.push_operator(BoolOperator::Not),
+            ' ' => {}
+            '(' => expr.open_par(),
+            ')' => expr.close_par(),
+            _ => expr.push_atom(c),
+        }
+    }
+    expr
+}
+
+fn parse_in(arena: &TreeArena, input: &str) -> BeTree<BoolOperator, char> {
+    push_chars(BeTree::new_in(arena), input)
+}
+
+fn parse_global(input: &str) -> BeTree<BoolOperator, char> {
+    push_chars(BeTree::new(), input)
+}
+
+#[test]
+fn arena_trees_are_built_and_evaluated_like_global_ones() {
+    let arena = TreeArena::new();
+    for input in [
+        "A",
+        "!A",
+        "A & B | C",
+        "(A | B) & !(C | (A & B))",
+        "!!A | B",
+    ] {
+        let expr = parse_in(&arena, input);
+        let global = parse_global(input);
+        assert!(expr.is_in_arena());
+        assert!(!global.is_in_arena());
+        check_links(&expr);
+        assert_eq!(expr, global);
+        check_same_eval(&expr, &global, "ABC");
+    }
+}
+
+#[test]
+fn arena_trees_keep_their_arena_alive() {
+    let arena = TreeArena::with_capacity(1 << 10);
+    let expr = parse_in(&arena, "(A | B) & !C");
+    drop(arena);
+    let clone = expr.clone();
+    assert!(clone.is_in_arena());
+    drop(expr);
+    check_same_eval(&clone, &parse_global("(A | B) & !C"), "ABC");
+}
+
+#[test]
+fn arena_reset() {
+    let mut arena = TreeArena::new();
+    let mut expr = parse_in(&arena, "A & B");
+    expr.clear();
+    assert!(expr.is_in_arena());
+    assert!(!arena.reset());
+    let allocated = arena.allocated_bytes();
+    assert!(allocated > 0);
+    drop(expr);
+    assert!(arena.reset());
+    for _ in 0..100 {
+        let expr = parse_in(&arena, "(A | B) & !(C | D)");
+        assert_eq!(eval(&expr, &['A']), Some(true));
+        drop(expr);
+        assert!(arena.reset());
+    }
+    assert!(arena.allocated_bytes() <= allocated);
+}
+
+#[test]
+fn arena_trees_can_be_sent_between_threads() {
+    let arena = TreeArena::new();
+    let trees: Vec<_> = ["A & B", "A | !B", "!(A & B)"]
+        .iter()
+        .map(|input| parse_in(&arena, input))
+        .collect();
+    let handles: Vec<_> = trees
+        .into_iter()
+        .map(|mut expr| {
+            thread::spawn(move || {
+                // the tree grows in the arena shared with the other threads
+                expr.push_operator(BoolOperator::Or);
+                expr.push_atom('C');
+                all_assignments("ABC")
+                    .iter()
+                    .filter(|trues| eval(&expr, trues) == Some(true))
+                    .count()
+            })
+        })
+        .collect();
+    let counts: Vec<usize> = handles
+        .into_iter()
+        .map(|handle| handle.join().unwrap())
+        .collect();
+    assert_eq!(counts, vec![5, 7, 7]);
+}
+
+#[test]
+fn global_trees_start_inline() {
+    let expr = BeTree::<BoolOperator, char>::new();
+    assert!(matches!(expr.nodes, ArenaVec::Inline(_)));
+    let expr = parse_in(&TreeArena::new(), "");
+    assert!(matches!(expr.nodes, ArenaVec::Allocated(_)));
+}
+
+#[cfg(feature = "inline-storage")]
+#[test]
+fn inline_storage_composes_with_arenas() {
+    let small = parse_global("A & !B");
+    assert!(matches!(small.nodes, ArenaVec::Inline(_)));
+    assert!(matches!(small.atoms, ArenaVec::Inline(_)));
+    let big = parse_global("(A | B) & !(C | D | E)");
+    assert!(matches!(big.atoms, ArenaVec::Allocated(_)));
+    assert!(!big.is_in_arena());
+    let arena = TreeArena::new();
+    let small_in_arena = parse_in(&arena, "A & !B");
+    assert!(small_in_arena.is_in_arena());
+    assert_eq!(small_in_arena, small);
+    check_same_eval(&big, &parse_in(&arena, "(A | B) & !(C | D | E)"), "ABCDE");
+}
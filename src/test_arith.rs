@@ -0,0 +1,146 @@
+//! tests of the arithmetic expressions of the `arith` module
+
+use crate::{arith::*, test_util::*, *};
+
+fn eval(input: &str) -> Result<Option<i64>, ArithError> {
+    let expr = parse_arith_expr(input).unwrap();
+    check_links(&expr);
+    eval_i64(&expr)
+}
+
+#[test]
+fn precedences() {
+    assert_eq!(eval("2+3*4"), Ok(Some(14)));
+    assert_eq!(eval("2*3+4"), Ok(Some(10)));
+    assert_eq!(eval("-2^2"), Ok(Some(-4)));
+    assert_eq!(eval("(-2)^2"), Ok(Some(4)));
+    assert_eq!(eval("-2*3"), Ok(Some(-6)));
+    assert_eq!(eval("2^-1*3"), Err(ArithError::NegativeExponent));
+    assert_eq!(eval("(1+2)*(3-4)/5"), Ok(Some(0)));
+    assert_eq!(eval("(1+2)*(3-4)/3"), Ok(Some(-1)));
+    assert_eq!(eval("1 - -1"), Ok(Some(2)));
+    assert_eq!(eval("--3"), Ok(Some(3)));
+    assert_eq!(eval("((((7))))"), Ok(Some(7)));
+}
+
+#[test]
+fn associativities() {
+    assert_eq!(eval("10 - 4 - 3"), Ok(Some(3)));
+    assert_eq!(eval("100 / 10 / 5"), Ok(Some(2)));
+    assert_eq!(eval("2 ^ 3 ^ 2"), Ok(Some(512)));
+    assert_eq!(eval("(2 ^ 3) ^ 2"), Ok(Some(64)));
+    assert_eq!(eval("2 - 3 + 4 * 5 ^ 2 / 10 - 1"), Ok(Some(8)));
+}
+
+#[test]
+fn errors_are_propagated() {
+    assert_eq!(eval("1/0"), Err(ArithError::DivisionByZero));
+    assert_eq!(
+        eval("2 + 3 * (1 / (2 - 2))"),
+        Err(ArithError::DivisionByZero)
+    );
+    assert_eq!(eval("9223372036854775807 + 1"), Err(ArithError::Overflow));
+    assert_eq!(eval("2 ^ 64"), Err(ArithError::Overflow));
+    assert_eq!(
+        BetError::from(eval("1/0").unwrap_err()).to_string(),
+        "invalid computation: division by zero"
+    );
+    assert_eq!(eval(""), Ok(None));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn floats() {
+    let eval = |input| eval_f64(&parse_arith_expr(input).unwrap());
+    assert_eq!(eval("2.5 * 4 - 1"), Ok(Some(9.0)));
+    assert_eq!(eval("-2 ^ 0.5 * -2 ^ 0.5"), Ok(Some(2.0000000000000004)));
+    assert_eq!(eval("1 / 4 ^ -1"), Ok(Some(4.0)));
+    assert_eq!(eval("(1+2)*(3-4)/5"), Ok(Some(-0.6)));
+    assert_eq!(eval("1.5 / (3 - 3)"), Err(ArithError::DivisionByZero));
+}
+
+#[test]
+fn parse_errors() {
+    let error = |input| parse_arith_expr::<i64>(input).unwrap_err();
+    assert_eq!(error("2 3"), ParseError::UnexpectedAtom { offset: 2 });
+    assert_eq!(error("*2"), ParseError::UnexpectedOperator { offset: 0 });
+    assert_eq!(
+        error("2 * / 3"),
+        ParseError::UnexpectedOperator { offset: 4 }
+    );
+    assert_eq!(
+        error("2 (3)"),
+        ParseError::UnexpectedOpeningPar { offset: 2 }
+    );
+    assert_eq!(
+        error("(2 + 3))"),
+        ParseError::UnexpectedClosingPar { offset: 7 }
+    );
+    assert_eq!(error("()"), ParseError::UnexpectedClosingPar { offset: 1 });
+    assert_eq!(error("2.5"), ParseError::InvalidAtom { offset: 0 });
+    assert_eq!(error("(2 + 3"), ParseError::UnexpectedEnd { offset: 6 });
+    assert_eq!(error("2 +"), ParseError::UnexpectedEnd { offset: 3 });
+}
+
+#[test]
+fn minimal_rendering() {
+    let expr = parse_arith_expr::<i64>("((1+2)*(3-4))/5 - (6 - 7)").unwrap();
+    let s = expr.to_string_minimal(
+        |op| op.precedence(),
+        |op, _| op.to_string(),
+        |n| n.to_string(),
+    );
+    assert_eq!(s, "(1 + 2) * (3 - 4) / 5 - (6 - 7)");
+    assert_eq!(eval(&s), eval_i64(&expr));
+}
+
+#[test]
+fn minimal_rendering_round_trip() {
+    let render = |expr: &BeTree<ArithOp, i64>| {
+        expr.to_string_minimal(
+            |op| op.precedence(),
+            |op, _| op.to_string(),
+            |n| n.to_string(),
+        )
+    };
+    for (input, expected) in [
+        ("(-2)^2", "(-2) ^ 2"),
+        ("-2^2", "-(2 ^ 2)"),
+        ("2^(-3)", "2 ^ (-3)"),
+        ("(-2)^(-3)^2", "(-2) ^ ((-3) ^ 2)"),
+        ("-2*3", "-2 * 3"),
+        ("3 - -2 * (-4)", "3 - -2 * -4"),
+        (
+            "((1+2)*(3-4))/5 - (6 - 7)",
+            "(1 + 2) * (3 - 4) / 5 - (6 - 7)",
+        ),
+    ] {
+        let expr = parse_arith_expr::<i64>(input).unwrap();
+        let minimal = render(&expr);
+        assert_eq!(minimal, expected, "input: {:?}", input);
+        let read = parse_arith_expr::<i64>(&minimal).unwrap();
+        assert_eq!(eval_i64(&read), eval_i64(&expr), "input: {:?}", input);
+        assert!(read.structural_eq(&expr), "input: {:?}", input);
+    }
+}
+
+#[test]
+fn depth_limit() {
+    let nested = |depth: usize| format!("{}1{}", "(".repeat(depth), ")".repeat(depth));
+    assert_eq!(eval(&nested(MAX_ARITH_DEPTH - 1)), Ok(Some(1)));
+    let too_deep = MAX_ARITH_DEPTH;
+    assert_eq!(
+        parse_arith_expr::<i64>(&nested(too_deep)).unwrap_err(),
+        ParseError::TooDeep { offset: too_deep },
+    );
+    for input in [
+        nested(5000),
+        "-".repeat(5000) + "1",
+        "2^".repeat(5000) + "1",
+    ] {
+        assert!(matches!(
+            parse_arith_expr::<i64>(&input),
+            Err(ParseError::TooDeep { .. })
+        ));
+    }
+}
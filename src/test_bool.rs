@@ -72,6 +72,19 @@ fn test_bool() {
     check("T & T | T & F", false);
 }
 
+#[test]
+fn parent_links_after_pushes() {
+    for input in [
+        "a & b | c",
+        "a | b | c & d",
+        "!a & b | c",
+        "(a & b) | c",
+        "a & (b | c) & d",
+    ] {
+        crate::test_util::check_links(&crate::test_util::parse(input));
+    }
+}
+
 #[test]
 fn issue_2() {
     check("F | F | F", false);
@@ -84,3 +97,17 @@ fn issue_2() {
     check("F | T & F | F", false);
     check("F | F | T & F", false);
 }
+
+#[test]
+fn unary_operand_then_binary_operator() {
+    check("!!F & F", false);
+    check("!!T | F", true);
+    check("!!!T | T", true);
+    check("T & !F | F", true);
+    check("F & !F | T", true);
+    check("!(T | !T) | T", true);
+    check("(F | !F) & T", true);
+    check("(T | !T) & F", false);
+    check("!(F | !!T) | T", true);
+    check("!T)", false);
+}
@@ -0,0 +1,75 @@
+//! tests of trees whose operators and atoms are neither Clone nor Debug
+
+use crate::*;
+
+/// An atom owning a resource, which can't be cloned
+struct OwnedFile {
+    name: &'static str,
+    content: Vec<u8>,
+}
+
+/// An operator which can't be cloned, compared or debugged
+enum Op {
+    And,
+    Or,
+    Not,
+}
+
+/// A struct embedding a tree doesn't need bounds on its parameters
+struct Filter<O, A> {
+    expr: BeTree<O, A>,
+}
+
+impl<O, A> Filter<O, A> {
+    fn atom_count(&self) -> usize {
+        self.expr.iter_atoms().count()
+    }
+}
+
+fn file(name: &'static str, content: &str) -> OwnedFile {
+    OwnedFile {
+        name,
+        content: content.as_bytes().to_vec(),
+    }
+}
+
+#[test]
+fn build_and_eval_with_unbounded_types() {
+    // (a | b) & !c
+    let mut expr = BeTree::new();
+    expr.open_par();
+    expr.push_atom(file("a", "apple"));
+    expr.push_operator(Op::Or);
+    expr.push_atom(file("b", "banana"));
+    expr.close_par();
+    expr.push_operator(Op::And);
+    expr.push_operator(Op::Not);
+    expr.push_atom(file("c", "cherry"));
+    let filter = Filter { expr };
+    assert_eq!(filter.atom_count(), 3);
+    let contains = |needle: &str| {
+        filter.expr.eval(
+            |f: &OwnedFile| {
+                f.content
+                    .windows(needle.len())
+                    .any(|w| w == needle.as_bytes())
+            },
+            |op, a, b| match (op, b) {
+                (Op::And, Some(b)) => a & b,
+                (Op::Or, Some(b)) => a | b,
+                (Op::Not, None) => !a,
+                _ => unreachable!(),
+            },
+            |op, &a| matches!((op, a), (Op::And, false) | (Op::Or, true)),
+        )
+    };
+    assert_eq!(contains("an"), Some(true));
+    assert_eq!(contains("pp"), Some(true));
+    assert_eq!(contains("err"), Some(false));
+    assert_eq!(contains("rr"), Some(false));
+    let names = filter.expr.into_map_atoms(|f| f.name);
+    assert_eq!(
+        names.iter_atoms().copied().collect::<Vec<_>>(),
+        ["a", "b", "c"]
+    );
+}
@@ -0,0 +1,87 @@
+//! tests of the rendering of trees with box-drawing connectors
+
+use crate::test_util::*;
+
+fn unicode(input: &str) -> String {
+    let mut s = String::new();
+    parse(input)
+        .render_unicode(&mut s, |op| op.to_string(), |atom| atom.to_string())
+        .unwrap();
+    s
+}
+
+fn ascii(input: &str) -> String {
+    let mut s = String::new();
+    parse(input)
+        .render_ascii(&mut s, |op| op.to_string(), |atom| atom.to_string())
+        .unwrap();
+    s
+}
+
+#[test]
+fn render_nested_expression() {
+    assert_eq!(
+        unicode("(A | B) & !(C | D | E)"),
+        "\
+&
+├─ |
+│  ├─ A
+│  └─ B
+└─ !
+   └─ |
+      ├─ |
+      │  ├─ C
+      │  └─ D
+      └─ E
+"
+    );
+    assert_eq!(
+        ascii("(A | B) & !(C | D | E)"),
+        "\
+&
+|- |
+|  |- A
+|  `- B
+`- !
+   `- |
+      |- |
+      |  |- C
+      |  `- D
+      `- E
+"
+    );
+}
+
+#[test]
+fn render_incomplete_expressions() {
+    assert_eq!(
+        unicode("A & (B |"),
+        "\
+&
+├─ A
+└─ |
+   ├─ B
+   └─ ∅
+"
+    );
+    assert_eq!(unicode("!"), "!\n└─ ∅\n");
+    assert_eq!(ascii("A |"), "|\n|- A\n`- -\n");
+    assert_eq!(unicode(""), "∅\n");
+    assert_eq!(unicode("((A))"), "A\n");
+}
+
+#[test]
+fn render_deep_tree() {
+    let depth = 5_000;
+    let mut input = String::new();
+    for _ in 0..depth {
+        input.push_str("(A&");
+    }
+    input.push('B');
+    for _ in 0..depth {
+        input.push(')');
+    }
+    let s = unicode(&input);
+    assert_eq!(s.lines().count(), 2 * depth + 1);
+    assert!(s.lines().last().unwrap().ends_with("└─ B"));
+}
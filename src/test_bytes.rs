@@ -0,0 +1,193 @@
+//! tests of the compact binary serialization
+
+use {super::*, crate::test_util::*};
+
+const OPERATORS: [BoolOperator; 3] = [BoolOperator::And, BoolOperator::Or, BoolOperator::Not];
+
+fn to_bytes(expr: &BeTree<BoolOperator, char>) -> Vec<u8> {
+    expr.to_bytes(
+        |c| c.to_string().into_bytes(),
+        |op| OPERATORS.iter().position(|o| o == op).unwrap() as u8,
+    )
+}
+
+fn from_bytes(bytes: &[u8]) -> Result<BeTree<BoolOperator, char>, DecodeError> {
+    BeTree::from_bytes(
+        bytes,
+        |payload| {
+            let s = std::str::from_utf8(payload).ok()?;
+            let mut chars = s.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => Some(c),
+                _ => None,
+            }
+        },
+        |idx| OPERATORS.get(idx as usize).copied(),
+    )
+}
+
+#[test]
+fn bytes_format() {
+    assert_eq!(to_bytes(&parse("")), vec![1, 5]);
+    assert_eq!(to_bytes(&parse("A")), vec![1, 0, 1, b'A', 5]);
+    assert_eq!(
+        to_bytes(&parse("(A | B) & !C")),
+        vec![1, 0, 1, b'A', 0, 1, b'B', 2, 1, 0, 1, b'C', 3, 2, 2, 0, 5],
+    );
+    assert_eq!(to_bytes(&parse("A &")), vec![1, 0, 1, b'A', 1, 2, 0, 5]);
+}
+
+#[test]
+fn bytes_round_trip() {
+    let inputs = [
+        "",
+        "A",
+        "!A",
+        "(A | B) & !(C | D | E)",
+        "!(!((A|B)&(B|C)&A)) & !D & (E | (A|E))",
+        "A & !",
+        "A & (B |",
+    ];
+    for input in inputs {
+        let expr = parse(input);
+        let bytes = to_bytes(&expr);
+        let read = from_bytes(&bytes).unwrap();
+        check_links(&read);
+        assert!(same_structure(&expr, &read), "{}", input);
+        assert_eq!(to_bytes(&read), bytes);
+    }
+}
+
+#[test]
+fn bytes_long_atoms() {
+    let long = "x".repeat(300);
+    let expr = parse("A | B").map_atoms_indexed(|idx, _| {
+        if idx == AtomId::new(0) {
+            long.clone()
+        } else {
+            "é".to_string()
+        }
+    });
+    let bytes = expr.to_bytes(|s| s.clone().into_bytes(), |_| 0);
+    assert_eq!(&bytes[1..4], &[0, 0xAC, 0x02]); // 300 as LEB128
+    let read = BeTree::from_bytes(
+        &bytes,
+        |payload| String::from_utf8(payload.to_vec()).ok(),
+        |_| Some(BoolOperator::Or),
+    )
+    .unwrap();
+    assert_eq!(read.atoms, expr.atoms);
+}
+
+#[test]
+fn bytes_errors() {
+    let cases: &[(&[u8], DecodeError)] = &[
+        (&[], DecodeError::Empty),
+        (&[2, 0, 1, b'A', 5], DecodeError::UnsupportedVersion(2)),
+        (&[1], DecodeError::Truncated { offset: 1 }),
+        (&[1, 0], DecodeError::Truncated { offset: 2 }),
+        (&[1, 0, 3, b'A'], DecodeError::Truncated { offset: 4 }),
+        (&[1, 0, 1, b'A', 3], DecodeError::Truncated { offset: 5 }),
+        (&[1, 9], DecodeError::UnknownRecord { offset: 1, tag: 9 }),
+        (
+            &[
+                1, 0, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x7F,
+            ],
+            DecodeError::InvalidLength { offset: 2 },
+        ),
+        (
+            &[1, 0, 2, b'A', b'B'],
+            DecodeError::InvalidAtom { offset: 2 },
+        ),
+        (
+            &[1, 0, 1, b'A', 3, 7],
+            DecodeError::InvalidOperator { offset: 5 },
+        ),
+        (
+            &[1, 0, 1, b'A', 2, 0],
+            DecodeError::MissingOperand { offset: 4 },
+        ),
+        (&[1, 3, 2], DecodeError::MissingOperand { offset: 1 }),
+        (
+            &[1, 1, 0, 1, b'A', 2, 0],
+            DecodeError::MissingOperand { offset: 5 },
+        ),
+        (
+            &[1, 0, 1, b'A', 0, 1, b'B', 5],
+            DecodeError::Unbalanced { operands: 2 },
+        ),
+        (
+            &[1, 0, 1, b'A', 0, 1, b'B'],
+            DecodeError::Truncated { offset: 7 },
+        ),
+        (
+            &[1, 0, 1, b'A', 5, 0, 1, b'B', 5],
+            DecodeError::TrailingBytes { offset: 5 },
+        ),
+    ];
+    for (bytes, error) in cases {
+        assert_eq!(from_bytes(bytes).unwrap_err(), *error, "bytes: {:?}", bytes);
+    }
+}
+
+#[test]
+fn bytes_reject_truncated_input() {
+    for input in ["A", "(A | B) & !C", "A & (B |", "A & ()"] {
+        let valid = to_bytes(&parse(input));
+        for len in 1..valid.len() {
+            assert!(
+                from_bytes(&valid[..len]).is_err(),
+                "{:?} truncated to {} bytes",
+                input,
+                len
+            );
+        }
+    }
+    // the records of `(A | B) & !C` up to the OR make a complete
+    // expression, but the end record is missing
+    let valid = to_bytes(&parse("(A | B) & !C"));
+    assert_eq!(
+        from_bytes(&valid[..9]),
+        Err(DecodeError::Truncated { offset: 9 })
+    );
+}
+
+#[test]
+fn bytes_never_panic_on_corrupted_input() {
+    let valid = to_bytes(&parse("!(!((A|B)&(B|C)&A)) & !D & (E | (A|E))"));
+    let mut state: u64 = 0x9E37_79B9_7F4A_7C15;
+    let mut next = move || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state
+    };
+    for _ in 0..2000 {
+        let mut bytes = valid.clone();
+        for _ in 0..(next() % 4 + 1) {
+            let idx = (next() as usize) % bytes.len();
+            bytes[idx] = next() as u8;
+        }
+        if let Ok(read) = from_bytes(&bytes) {
+            check_links(&read);
+        }
+        let random: Vec<u8> = (0..(next() % 40)).map(|_| (next() % 5) as u8).collect();
+        if let Ok(read) = from_bytes(&random) {
+            check_links(&read);
+        }
+    }
+}
+
+#[test]
+fn bytes_keep_empty_groups() {
+    assert_eq!(to_bytes(&parse("A & ()")), vec![1, 0, 1, b'A', 4, 2, 0, 5]);
+    assert_eq!(to_bytes(&parse("!()")), vec![1, 4, 3, 2, 5]);
+    for input in ["()", "(())", "A & ()", "!()", "() | A & !(())"] {
+        let expr = parse(input);
+        let read = from_bytes(&to_bytes(&expr)).unwrap();
+        check_same_empty_groups(&read, &expr);
+        assert_eq!(to_bytes(&read), to_bytes(&expr));
+    }
+    // an empty group isn't a missing operand
+    assert_ne!(to_bytes(&parse("A & ()")), to_bytes(&parse("A &")));
+}
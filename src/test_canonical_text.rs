@@ -0,0 +1,213 @@
+//! tests of the line based canonical text form
+
+use {
+    super::*,
+    crate::test_util::{BoolOperator::*, *},
+};
+
+fn to_text(expr: &BeTree<BoolOperator, char>) -> String {
+    expr.to_canonical_text(|op| op.to_string(), |c| c.to_string())
+}
+
+fn from_text(input: &str) -> Result<BeTree<BoolOperator, char>, CanonicalTextError> {
+    BeTree::from_canonical_text(
+        input,
+        |s| match s {
+            "&" => Some(And),
+            "|" => Some(Or),
+            "!" => Some(Not),
+            _ => None,
+        },
+        |s| {
+            let mut chars = s.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) if c.is_ascii_uppercase() => Some(c),
+                _ => None,
+            }
+        },
+    )
+}
+
+#[test]
+fn one_token_per_line() {
+    let expr = parse("(A | B) & !C");
+    assert_eq!(to_text(&expr), "&\n  |\n    A\n    B\n  !\n    C\n");
+    assert_eq!(to_text(&parse("A")), "A\n");
+    assert_eq!(to_text(&BeTree::new()), "");
+    assert_eq!(to_text(&parse("A & !")), "&\n  A\n  !\n    ()\n");
+}
+
+#[test]
+fn same_text_for_trees_built_differently() {
+    let expected = to_text(&parse("(A | B) & !(C | D)"));
+    // with useless groups
+    assert_eq!(to_text(&parse("((A | (B))) & !((C | D))")), expected);
+    // with edits leaving unreachable nodes and atoms
+    let mut edited = parse("(A | B | E) & !(C | D)");
+    edited.remove_atom(AtomId::new(2)).unwrap();
+    assert_eq!(to_text(&edited), expected);
+    // by moving a subtree
+    let mut moved = parse("(C | D) & (A | B)");
+    let head = moved.head;
+    let Child::Node(right) = moved.nodes[head].right() else {
+        panic!("no right node");
+    };
+    let left = moved.detach(right).unwrap();
+    moved.wrap_unary(Not);
+    let moved = left.into_tree().combine(And, moved);
+    assert_eq!(to_text(&moved), expected);
+    // from the parts in another order
+    let mut builder = TreeBuilder::new();
+    let not = builder.add_node(Some(Not), true);
+    let c_or_d = builder.add_node(Some(Or), false);
+    let d = builder.add_atom('D');
+    let c = builder.add_atom('C');
+    let and = builder.add_node(Some(And), false);
+    let a_or_b = builder.add_node(Some(Or), false);
+    let a = builder.add_atom('A');
+    let b = builder.add_atom('B');
+    builder.set_left(c_or_d, Child::Atom(c)).unwrap();
+    builder.set_right(c_or_d, Child::Atom(d)).unwrap();
+    builder.set_left(not, Child::Node(c_or_d)).unwrap();
+    builder.set_left(a_or_b, Child::Atom(a)).unwrap();
+    builder.set_right(a_or_b, Child::Atom(b)).unwrap();
+    builder.set_left(and, Child::Node(a_or_b)).unwrap();
+    builder.set_right(and, Child::Node(not)).unwrap();
+    assert_eq!(to_text(&builder.finish(and).unwrap()), expected);
+}
+
+#[test]
+fn changing_an_atom_changes_one_line() {
+    let before = to_text(&parse("(A | B) & !(C | D) | (A & !B)"));
+    let after = to_text(&parse("(A | B) & !(C | E) | (A & !B)"));
+    let before: Vec<&str> = before.lines().collect();
+    let after: Vec<&str> = after.lines().collect();
+    assert_eq!(before.len(), after.len());
+    let changed: Vec<(&str, &str)> = before
+        .into_iter()
+        .zip(after)
+        .filter(|(a, b)| a != b)
+        .collect();
+    assert_eq!(changed, vec![("        D", "        E")]);
+}
+
+#[test]
+fn round_trip() {
+    for input in [
+        "A",
+        "!A",
+        "A & B",
+        "(A | B) & !(C | D) | (A & !B)",
+        "!!(A & (B | !C))",
+        "A & !",
+        "A |",
+    ] {
+        let expr = parse(input);
+        let text = to_text(&expr);
+        let read = from_text(&text).unwrap();
+        check_links(&read);
+        assert!(read.structural_eq(&expr), "{:?}", input);
+        assert_eq!(to_text(&read), text);
+    }
+    assert!(from_text("").unwrap().is_empty());
+    assert!(from_text("\n\n").unwrap().is_empty());
+    // empty lines, trailing spaces and windows line endings are accepted
+    let read = from_text("&\r\n  A \r\n\r\n  B\r\n").unwrap();
+    assert!(read.structural_eq(&parse("A & B")));
+}
+
+#[test]
+fn quoted_tokens() {
+    let mut expr: BeTree<String, String> = BeTree::new();
+    expr.push_atom("a b".to_string());
+    expr.push_operator("and also".to_string());
+    expr.push_atom("say \"hi\"\n".to_string());
+    let text = expr.to_canonical_text(|op| op.clone(), |atom| atom.clone());
+    assert_eq!(text, "\"and also\"\n  \"a b\"\n  \"say \\\"hi\\\"\\n\"\n");
+    let read: BeTree<String, String> =
+        BeTree::from_canonical_text(&text, |s| Some(s.to_string()), |s| Some(s.to_string()))
+            .unwrap();
+    assert!(read.structural_eq(&expr));
+}
+
+#[test]
+fn positioned_errors() {
+    use CanonicalTextError::*;
+    assert_eq!(from_text(" A\n"), Err(WrongIndentation { line: 1 }));
+    assert_eq!(
+        from_text("&\n   A\n  B\n"),
+        Err(WrongIndentation { line: 2 })
+    );
+    assert_eq!(
+        from_text("&\n    A\n  B\n"),
+        Err(WrongIndentation { line: 2 })
+    );
+    assert_eq!(from_text("&\n  \"A\n"), Err(WrongQuoting { line: 2 }));
+    assert_eq!(from_text("&\n  \"A\"B\n"), Err(WrongQuoting { line: 2 }));
+    assert_eq!(
+        from_text("&\n  A\n  B\n  C\n"),
+        Err(WrongOperandCount { line: 1 })
+    );
+    assert_eq!(
+        from_text("&\n  ()\n    A\n"),
+        Err(WrongOperandCount { line: 2 })
+    );
+    assert_eq!(from_text("&\n  ()\n  B\n"), Err(MissingOperand { line: 3 }));
+    assert_eq!(from_text("+\n  A\n  B\n"), Err(InvalidOperator { line: 1 }));
+    assert_eq!(from_text("&\n  A\n  b\n"), Err(InvalidAtom { line: 3 }));
+    assert_eq!(
+        from_text("&\n  A\n  B\n\nC\n"),
+        Err(TrailingInput { line: 5 })
+    );
+    assert_eq!(
+        from_text("&\n  A\n  b\n").unwrap_err().to_string(),
+        "invalid atom at line 3",
+    );
+}
+
+#[test]
+fn depth_limit() {
+    // a chain of negations whose atom is at the given depth
+    let nested = |depth: usize| {
+        let mut text = String::new();
+        for level in 0..depth {
+            text.push_str(&"  ".repeat(level));
+            text.push_str("!\n");
+        }
+        text.push_str(&"  ".repeat(depth));
+        text.push_str("A\n");
+        text
+    };
+    let expr = from_text(&nested(MAX_CANONICAL_TEXT_DEPTH)).unwrap();
+    assert_eq!(expr.depth(), MAX_CANONICAL_TEXT_DEPTH);
+    assert_eq!(to_text(&expr), nested(MAX_CANONICAL_TEXT_DEPTH));
+    assert_eq!(
+        from_text(&nested(MAX_CANONICAL_TEXT_DEPTH + 1)),
+        Err(CanonicalTextError::TooDeep {
+            line: MAX_CANONICAL_TEXT_DEPTH + 2
+        })
+    );
+    assert_eq!(
+        from_text(&nested(2000)).unwrap_err().to_string(),
+        format!(
+            "expression nested too deeply at line {}",
+            MAX_CANONICAL_TEXT_DEPTH + 2
+        ),
+    );
+}
+
+#[test]
+fn canonical_text_keeps_empty_groups() {
+    assert_eq!(to_text(&parse("A & ()")), "&\n  A\n  (())\n");
+    assert_eq!(to_text(&parse("!()")), "!\n  (())\n");
+    for input in ["()", "(())", "A & ()", "!()", "() | A & !(())"] {
+        let expr = parse(input);
+        let read = from_text(&to_text(&expr)).unwrap();
+        check_same_empty_groups(&read, &expr);
+        assert_eq!(to_text(&read), to_text(&expr));
+    }
+    assert_eq!(
+        from_text("(())\n  A\n").unwrap_err(),
+        CanonicalTextError::WrongOperandCount { line: 1 },
+    );
+}
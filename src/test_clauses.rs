@@ -0,0 +1,116 @@
+//! tests of the extraction of clauses from normal forms
+
+use {
+    super::*,
+    crate::test_util::{BoolOperator::*, *},
+};
+
+/// describe the clauses, with `!` before negated atoms
+fn clause_strings(clauses: &[Vec<Literal<'_, char>>]) -> Vec<String> {
+    clauses
+        .iter()
+        .map(|clause| {
+            clause
+                .iter()
+                .map(|literal| {
+                    if literal.negated {
+                        format!("!{}", literal.atom)
+                    } else {
+                        literal.atom.to_string()
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .collect()
+}
+
+fn cnf_clauses(input: &str) -> Result<Vec<String>, NotInNormalForm> {
+    let expr = parse(input);
+    expr.clauses(&And, &Or, &Not)
+        .map(|clauses| clause_strings(&clauses))
+}
+
+#[test]
+fn cnf_clauses_are_extracted() {
+    assert_eq!(
+        cnf_clauses("(A | !B) & C & (!A | B | (D))").unwrap(),
+        ["A !B", "C", "!A B D"],
+    );
+    assert_eq!(cnf_clauses("A | !B").unwrap(), ["A !B"]);
+    assert_eq!(cnf_clauses("!A").unwrap(), ["!A"]);
+    assert_eq!(cnf_clauses("((A))").unwrap(), ["A"]);
+    // the outer operations don't have to be a chain
+    assert_eq!(
+        cnf_clauses("A & ((B | C) & (D & !E))").unwrap(),
+        ["A", "B C", "D", "!E"],
+    );
+    assert!(cnf_clauses("").unwrap().is_empty());
+}
+
+#[test]
+fn dnf_clauses_are_extracted_with_swapped_operators() {
+    let expr = parse("(A & !B) | C | (!A & B & D)");
+    let clauses = expr.clauses(&Or, &And, &Not).unwrap();
+    assert_eq!(clause_strings(&clauses), ["A !B", "C", "!A B D"]);
+    // it's not a CNF
+    assert!(expr.clauses(&And, &Or, &Not).is_err());
+}
+
+#[test]
+fn clauses_of_built_normal_forms() {
+    let mut expr = parse("(A | B) & !(C & (D | !E))");
+    expr.push_down_unary(
+        &Not,
+        |op| match op {
+            And => Some(Or),
+            Or => Some(And),
+            Not => None,
+        },
+        |_| NegatedAtom::Keep,
+    );
+    let cnf = expr.to_cnf(&And, &Or, 100).unwrap();
+    let clauses = cnf.clauses(&And, &Or, &Not).unwrap();
+    assert_eq!(clause_strings(&clauses), ["A B", "!C !D", "!C E"]);
+    let dnf = expr.to_dnf(&And, &Or, 100).unwrap();
+    let clauses = dnf.clauses(&Or, &And, &Not).unwrap();
+    assert_eq!(
+        clause_strings(&clauses),
+        ["A !C", "A !D E", "B !C", "B !D E"],
+    );
+}
+
+#[test]
+fn malformed_normal_forms() {
+    let check = |input: &str, op: Option<BoolOperator>, expected: fn(NodeId) -> NotInNormalForm| {
+        let expr = parse(input);
+        let err = expr.clauses(&And, &Or, &Not).unwrap_err();
+        assert_eq!(err, expected(err.node()), "{:?}", input);
+        assert_eq!(expr.nodes[err.node()].operator, op, "{:?}", input);
+    };
+    // an outer operation in a clause
+    check(
+        "A & (B | (C & D))",
+        Some(And),
+        NotInNormalForm::UnexpectedOperator,
+    );
+    // a negated clause
+    check("A & !(B | C)", Some(Not), NotInNormalForm::NegatedOperation);
+    // a double negation
+    check("!!A | B", Some(Not), NotInNormalForm::NegatedOperation);
+    // a missing operand
+    check("A & (B |", Some(Or), NotInNormalForm::MissingOperand);
+    check("A & ()", Some(And), NotInNormalForm::MissingOperand);
+}
+
+#[test]
+fn unexpected_operators() {
+    // negation isn't accepted as binary operator, nor the others as unary
+    let expr = parse("A & B");
+    let err = expr.clauses(&Or, &Not, &And).unwrap_err();
+    assert!(matches!(err, NotInNormalForm::UnexpectedOperator(_)));
+    assert_eq!(
+        err.to_string(),
+        format!("unexpected operator in node {}", err.node()),
+    );
+}
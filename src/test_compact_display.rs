@@ -0,0 +1,64 @@
+//! tests of the compact structural rendering and of the Debug form of trees
+
+use crate::test_util::*;
+
+#[test]
+fn compact_display_snapshots() {
+    let cases = [
+        ("A", "'A'"),
+        ("(A | B) & !C", "And(Or('A', 'B'), Not('C'))"),
+        (
+            "(A | B) & !(C | D | E)",
+            "And(Or('A', 'B'), Not(Or(Or('C', 'D'), 'E')))",
+        ),
+        ("((A))", "'A'"),
+        ("A &", "And('A', _)"),
+        ("!", "Not(_)"),
+        ("A | !", "Or('A', Not(_))"),
+        ("", ""),
+    ];
+    for (input, expected) in cases {
+        let expr = parse(input);
+        assert_eq!(
+            expr.display_compact().to_string(),
+            expected,
+            "for {:?}",
+            input
+        );
+        assert_eq!(format!("{:?}", expr.display_compact()), expected);
+    }
+}
+
+#[test]
+fn debug_snapshots() {
+    let expr = parse("(A | B) & !(C | D | E)");
+    assert_eq!(
+        format!("{:?}", expr),
+        "\
+BeTree
+ [0] Some(And)
+  [1] Some(Or)
+   'A'
+   'B'
+  [2] Some(Not)
+   [4] Some(Or)
+    [3] Some(Or)
+     'C'
+     'D'
+    'E'
+   -
+"
+    );
+    let expr = parse("A & B |");
+    assert_eq!(
+        format!("{:?}", expr),
+        "\
+BeTree
+ [1] Some(Or)
+  [0] Some(And)
+   'A'
+   'B'
+  -
+"
+    );
+}
@@ -0,0 +1,112 @@
+//! tests of the cursors
+
+use {
+    super::*,
+    crate::test_util::{BoolOperator::*, *},
+};
+
+#[test]
+fn cursor_navigation() {
+    let expr = parse("(A | B) & !C");
+    let head = expr.cursor();
+    assert_eq!(head.operator(), Some(&And));
+    assert!(head.parent().is_none());
+    let or = head.left().unwrap();
+    assert_eq!(or.operator(), Some(&Or));
+    assert_eq!(or.side(), Some(Side::Left));
+    let b = or.right().unwrap();
+    assert_eq!(b.atom(), Some(&'B'));
+    assert!(b.left().is_none());
+    assert_eq!(b.parent().unwrap().node_id(), or.node_id());
+    assert_eq!(
+        b.parent().unwrap().parent().unwrap().node_id(),
+        Some(expr.head)
+    );
+    let not = head.right().unwrap();
+    assert_eq!(not.operator(), Some(&Not));
+    assert!(not.right().is_none());
+    assert_eq!(not.left().unwrap().atom(), Some(&'C'));
+    // a cursor can be created on any reachable node
+    let not = expr.cursor_at(not.node_id().unwrap()).unwrap();
+    assert_eq!(not.parent().unwrap().operator(), Some(&And));
+    assert!(expr.cursor_at(NodeId::new(1000)).is_none());
+}
+
+#[test]
+fn cursor_replace_with_atom() {
+    let mut expr = parse("(A | B) & !(C | D)");
+    let mut cursor = expr.cursor_mut();
+    assert!(cursor.move_to_right());
+    assert!(cursor.move_to_left());
+    cursor.replace_with_atom('E');
+    assert_eq!(cursor.atom(), Some(&'E'));
+    assert!(cursor.move_to_parent());
+    assert_eq!(cursor.node().unwrap().operator, Some(Not));
+    check_links(&expr);
+    assert_eq!(expr.op_count, 3);
+    check_same_eval(&expr, &parse("(A | B) & !E"), "ABCDE");
+    // replacing the whole expression
+    let mut cursor = expr.cursor_mut();
+    cursor.replace_with_atom('D');
+    assert!(cursor.move_to_parent());
+    assert!(!cursor.move_to_parent());
+    check_links(&expr);
+    assert_eq!(expr.op_count, 0);
+    check_same_eval(&expr, &parse("D"), "ABCDE");
+    // the expression can still be extended
+    expr.push_operator(Or);
+    expr.push_atom('A');
+    check_links(&expr);
+    check_same_eval(&expr, &parse("D | A"), "ABCDE");
+}
+
+#[test]
+fn cursor_wrap_in_unary() {
+    let mut expr = parse("A & B | C");
+    let mut cursor = expr.cursor_mut();
+    cursor.move_to_left();
+    cursor.move_to_right();
+    assert_eq!(cursor.atom(), Some(&'B'));
+    cursor.wrap_in_unary(Not);
+    assert_eq!(cursor.node().unwrap().operator, Some(Not));
+    cursor.move_to_parent();
+    cursor.wrap_in_unary(Not);
+    check_links(&expr);
+    assert_eq!(expr.op_count, 4);
+    check_same_eval(&expr, &parse("!(A & !B) | C"), "ABC");
+    // wrapping the head
+    expr.cursor_mut().wrap_in_unary(Not);
+    check_links(&expr);
+    assert_eq!(expr.op_count, 5);
+    check_same_eval(&expr, &parse("!(!(A & !B) | C)"), "ABC");
+    expr.push_operator(And);
+    expr.push_atom('C');
+    check_same_eval(&expr, &parse("!(!(A & !B) | C) & C"), "ABC");
+}
+
+#[test]
+fn cursor_insert_binary_above() {
+    let mut expr = parse("A | B");
+    let mut cursor = expr.cursor_mut();
+    cursor.move_to_left();
+    cursor.insert_binary_above(And, Side::Right, parse("!C"));
+    assert_eq!(cursor.node().unwrap().operator, Some(And));
+    cursor.move_to_parent();
+    cursor.move_to_right();
+    cursor.insert_binary_above(And, Side::Left, parse("C | D"));
+    check_links(&expr);
+    assert_eq!(expr.op_count, 5);
+    check_same_eval(&expr, &parse("(A & !C) | ((C | D) & B)"), "ABCD");
+    // above the head
+    let mut cursor = expr.cursor_mut();
+    cursor.insert_binary_above(Or, Side::Right, parse("E"));
+    assert!(!cursor.move_to_parent());
+    check_links(&expr);
+    assert_eq!(expr.op_count, 6);
+    check_same_eval(&expr, &parse("(A & !C) | ((C | D) & B) | E"), "ABCDE");
+    // inserting an empty tree does nothing
+    let before = expr.clone();
+    expr.cursor_mut()
+        .insert_binary_above(Or, Side::Right, BeTree::new());
+    assert_eq!(expr, before);
+}
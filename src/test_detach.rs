@@ -0,0 +1,105 @@
+//! tests of the moves of subexpressions with detach and attach
+
+use {super::*, crate::test_util::*, BoolOperator::*};
+
+/// check the structure of the tree, that it evaluates like the expected
+/// expression, and that its operators are counted
+fn check(expr: &BeTree<BoolOperator, char>, expected: &str) {
+    check_links(expr);
+    let expected = parse(expected);
+    check_same_eval(expr, &expected, "ABCDE");
+    assert_eq!(expr.op_count(), expected.op_count());
+}
+
+#[test]
+fn move_between_sibling_groups() {
+    use Side::*;
+    let mut expr = parse("(A | !C) & (B | D)");
+    let detached = expr.detach(node_at(&expr, &[Left, Right])).unwrap();
+    check(&expr, "A & (B | D)");
+    check(&detached.clone().into_tree(), "!C");
+    let target = node_at(&expr, &[Right]);
+    expr.attach(detached, target, Right, Some(And)).unwrap();
+    check(&expr, "A & (B | (D & !C))");
+    // and back
+    let detached = expr.detach(node_at(&expr, &[Right, Right])).unwrap();
+    check(&expr, "A & B");
+    check(detached.tree(), "D & !C");
+    let head = expr.head;
+    expr.attach(detached, head, Left, Some(Or)).unwrap();
+    check(&expr, "(A | (D & !C)) & B");
+}
+
+#[test]
+fn move_across_nesting_levels() {
+    use Side::*;
+    let mut expr = parse("A & (B | !(C & D))");
+    // up: the deepest operation becomes an operand of the head
+    let detached = expr.detach(node_at(&expr, &[Right, Right, Left])).unwrap();
+    check(&expr, "A & B");
+    let head = expr.head;
+    expr.attach(detached, head, Right, Some(Or)).unwrap();
+    check(&expr, "A & (B | (C & D))");
+    // down: the first operand goes under the operation it was joined to
+    let mut expr = parse("(A | B) & (C | (D & E))");
+    let detached = expr.detach(node_at(&expr, &[Left])).unwrap();
+    check(&expr, "C | (D & E)");
+    let target = node_at(&expr, &[Right]);
+    expr.attach(detached, target, Left, Some(Or)).unwrap();
+    check(&expr, "C | ((D | (A | B)) & E)");
+    // the tree can still be extended
+    expr.push_operator(And);
+    expr.push_atom('A');
+    check(&expr, "(C | ((D | (A | B)) & E)) & A");
+}
+
+#[test]
+fn attach_in_an_empty_slot() {
+    let mut expr = parse("!(A | B)");
+    let detached = expr.detach(expr.head).unwrap();
+    assert!(expr.is_empty());
+    let head = expr.head;
+    expr.attach(detached, head, Side::Left, None).unwrap();
+    check(&expr, "!(A | B)");
+    expr.push_operator(And);
+    expr.push_atom('C');
+    check(&expr, "!(A | B) & C");
+    // attaching an empty tree changes nothing
+    let head = expr.head;
+    expr.attach(BeTree::new().into(), head, Side::Right, Some(Or))
+        .unwrap();
+    check(&expr, "!(A | B) & C");
+}
+
+#[test]
+fn refused_attachments() {
+    use Side::*;
+    let mut expr = parse("(A | B) & !(C & D)");
+    let inner = node_at(&expr, &[Right, Left]);
+    let detached = expr.detach(node_at(&expr, &[Right])).unwrap();
+    // the node was in the detached subexpression
+    assert_eq!(
+        expr.attach(detached.clone(), inner, Left, Some(And)),
+        Err(EditError::UnreachableNode(inner)),
+    );
+    assert_eq!(
+        expr.attach(detached.clone(), NodeId::new(1000), Left, None),
+        Err(EditError::UnknownNode(NodeId::new(1000))),
+    );
+    let head = expr.head;
+    assert_eq!(
+        expr.attach(detached.clone(), head, Right, None),
+        Err(EditError::OccupiedChild(head)),
+    );
+    let mut expr = parse("!(A | B) & C");
+    let not = node_at(&expr, &[Left]);
+    assert_eq!(
+        expr.attach(detached.clone(), not, Right, Some(Or)),
+        Err(EditError::NotBinaryNode(not)),
+    );
+    check(&expr, "!(A | B) & C");
+    assert_eq!(
+        expr.detach(NodeId::new(1000)).map(|_| ()),
+        Err(EditError::UnknownNode(NodeId::new(1000))),
+    );
+}
@@ -0,0 +1,177 @@
+//! tests of the diagnostics of evaluation failures
+
+use {
+    super::*,
+    crate::test_util::BoolOperator::{self, *},
+    core::ops::Range,
+};
+
+type SpannedTree = BeTree<BoolOperator, (String, Range<usize>), Range<usize>>;
+
+/// parse an expression of words, keeping the spans of the atoms
+/// and of the operators
+fn parse_spanned(input: &str) -> SpannedTree {
+    let mut expr = BeTree::default();
+    let mut word_start = None;
+    for (i, c) in input
+        .char_indices()
+        .chain(core::iter::once((input.len(), ' ')))
+    {
+        if c.is_alphanumeric() || c == '_' {
+            word_start.get_or_insert(i);
+            continue;
+        }
+        if let Some(start) = word_start.take() {
+            expr.push_atom((input[start..i].to_string(), start..i));
+        }
+        match c {
+            '&' => expr.push_operator_with_meta(And, i..i + 1),
+            '|' => expr.push_operator_with_meta(Or, i..i + 1),
+            '!' => expr.push_operator_with_meta(Not, i..i + 1),
+            '(' => expr.open_par(),
+            ')' => expr.close_par(),
+            _ => {}
+        }
+    }
+    expr
+}
+
+/// evaluate, `true` and `false` being the only known atoms, and
+/// the `Not` operator refusing `false`
+fn diagnose(input: &str) -> String {
+    let expr = parse_spanned(input);
+    let failure = expr
+        .eval_faillible_located(
+            |(word, _)| match word.as_str() {
+                "true" => Ok(true),
+                "false" => Ok(false),
+                _ => Err("unknown atom"),
+            },
+            |op, a, b| match (op, b) {
+                (And, Some(b)) => Ok(a & b),
+                (Or, Some(b)) => Ok(a | b),
+                (Not, None) if a => Ok(false),
+                _ => Err("can't negate false"),
+            },
+            |op, &a| matches!((op, a), (And, false) | (Or, true)),
+        )
+        .unwrap_err();
+    expr.diagnose(input, &failure)
+}
+
+#[test]
+fn failing_elements_are_located() {
+    let expr = parse_spanned("true & (false | bad)");
+    let failure = expr
+        .eval_faillible_located(
+            |(word, _)| match word.as_str() {
+                "true" => Ok(true),
+                "false" => Ok(false),
+                _ => Err('?'),
+            },
+            |_, a, b| Ok(a & b.unwrap_or(true)),
+            |_, _| false,
+        )
+        .unwrap_err();
+    assert_eq!(
+        failure,
+        EvalFailure {
+            error: '?',
+            child: Child::Atom(AtomId::new(2)),
+        }
+    );
+    assert_eq!(failure.to_string(), "? in atom 2");
+    // successful evaluations are the same as with eval_faillible
+    let expr = parse_spanned("true & !false");
+    assert_eq!(
+        expr.eval_faillible_located(
+            |(word, _)| Ok::<_, ()>(word == "true"),
+            |op, a, b| Ok(match (op, b) {
+                (And, Some(b)) => a & b,
+                (Or, Some(b)) => a | b,
+                _ => !a,
+            }),
+            |_, _| false,
+        ),
+        Ok(Some(true))
+    );
+}
+
+#[test]
+fn diagnostics_at_start_middle_and_end_of_line() {
+    assert_eq!(
+        diagnose("bad & true"),
+        "1 | bad & true\n  | ^^^ unknown atom",
+    );
+    assert_eq!(
+        diagnose("true & bad | false"),
+        "1 | true & bad | false\n  |        ^^^ unknown atom",
+    );
+    assert_eq!(
+        diagnose("false | true & wrong"),
+        "1 | false | true & wrong\n  |                ^^^^^ unknown atom",
+    );
+}
+
+#[test]
+fn diagnostics_of_operators() {
+    assert_eq!(
+        diagnose("true & !false"),
+        "1 | true & !false\n  |        ^ can't negate false",
+    );
+}
+
+#[test]
+fn diagnostics_in_multiline_expressions() {
+    let input = "true\n& (\n\tfalse\n\t| what\n)";
+    assert_eq!(
+        diagnose(input),
+        "4 |     | what\n  |       ^^^^ unknown atom",
+    );
+    let input = "true &\r\n  oops\r\n";
+    assert_eq!(diagnose(input), "2 |   oops\n  |   ^^^^ unknown atom",);
+}
+
+#[test]
+fn diagnostics_of_long_lines() {
+    let mut input = "true & ".repeat(20);
+    input.push_str("nope");
+    input.push_str(&" & true".repeat(20));
+    let diagnostic = diagnose(&input);
+    let mut lines = diagnostic.lines();
+    let line = lines.next().unwrap();
+    let underline = lines.next().unwrap();
+    assert!(line.starts_with("1 | ..."));
+    assert!(line.ends_with("..."));
+    assert_eq!(line.chars().count(), "1 | ".len() + 3 + 80 + 3);
+    // the underline is under the failing atom
+    let caret = underline.find('^').unwrap();
+    assert_eq!(&line[caret..caret + 4], "nope");
+    assert!(underline.ends_with("^^^^ unknown atom"));
+}
+
+#[test]
+fn unknown_spans() {
+    // operators without span
+    let expr: BeTree<BoolOperator, (String, Range<usize>)> =
+        parse_spanned("true & !false").map_meta(|_| ());
+    let failure = EvalFailure {
+        error: "can't negate false",
+        child: Child::Node(NodeId::new(1)),
+    };
+    assert_eq!(
+        expr.diagnose("true & !false", &failure),
+        "can't negate false"
+    );
+    // a span not matching the input
+    let expr = parse_spanned("unknown");
+    let failure = EvalFailure {
+        error: "error",
+        child: Child::Atom(AtomId::new(0)),
+    };
+    assert_eq!(expr.diagnose("short", &failure), "error");
+    assert_eq!(
+        expr.diagnose("unknown", &failure),
+        "1 | unknown\n  | ^^^^^^^ error",
+    );
+}
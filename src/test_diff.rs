@@ -0,0 +1,128 @@
+//! tests of the diff between trees
+
+use {
+    super::*,
+    crate::test_util::{BoolOperator::*, *},
+    Side::*,
+};
+
+#[test]
+fn identical_trees_have_no_diff() {
+    let a = parse("(A | B) & !C");
+    let b = parse("((A | B)) & (!(C))");
+    assert!(a.diff(&b).is_empty());
+    // with different arenas
+    let mut c = parse("(A | X) & !C");
+    c.remove_atom(AtomId::new(1)).unwrap();
+    let mut cursor = c.cursor_mut();
+    cursor.move_to_left();
+    cursor.insert_binary_above(Or, Side::Right, parse("B"));
+    assert_ne!(a, c);
+    assert!(a.diff(&c).is_empty());
+    assert!(c.diff(&a).is_empty());
+}
+
+#[test]
+fn diff_replaced_atoms_and_operators() {
+    let a = parse("(A | B) & !C");
+    let b = parse("(A & D) & !C");
+    assert_eq!(
+        a.diff(&b),
+        vec![
+            TreeEdit::ReplaceOperator {
+                path: vec![Left],
+                old: Or,
+                new: And,
+            },
+            TreeEdit::ReplaceAtom {
+                path: vec![Left, Right],
+                old: 'B',
+                new: 'D',
+            },
+        ]
+    );
+}
+
+#[test]
+fn diff_added_and_removed_operands() {
+    let a = parse("(A | B) & !C");
+    let b = parse("(A | B) & !(C | D)");
+    let edits = a.diff(&b);
+    assert_eq!(
+        edits,
+        vec![TreeEdit::InsertOperand {
+            path: vec![Right, Left],
+            operator: Or,
+            side: Right,
+            operand: parse("D").normalize(),
+        }]
+    );
+    assert_eq!(
+        b.diff(&a),
+        vec![TreeEdit::RemoveOperand {
+            path: vec![Right, Left],
+            operator: Or,
+            side: Right,
+            operand: parse("D").normalize(),
+        }]
+    );
+    let b = parse("D & ((A | B) & !C)");
+    let TreeEdit::InsertOperand {
+        path,
+        side,
+        operand,
+        ..
+    } = &a.diff(&b)[0]
+    else {
+        panic!("an operand should have been inserted");
+    };
+    assert!(path.is_empty());
+    assert_eq!(*side, Left);
+    assert_eq!(operand.iter_atoms().collect::<String>(), "D");
+}
+
+#[test]
+fn diff_wrapped_in_unary() {
+    let a = parse("(A | B) & C");
+    let b = parse("!(A | B) & !C");
+    assert_eq!(
+        a.diff(&b),
+        vec![
+            TreeEdit::WrapUnary {
+                path: vec![Left],
+                operator: Not,
+            },
+            TreeEdit::WrapUnary {
+                path: vec![Right],
+                operator: Not,
+            },
+        ]
+    );
+    assert_eq!(
+        b.diff(&a),
+        vec![
+            TreeEdit::UnwrapUnary {
+                path: vec![Left],
+                operator: Not,
+            },
+            TreeEdit::UnwrapUnary {
+                path: vec![Right],
+                operator: Not,
+            },
+        ]
+    );
+}
+
+#[test]
+fn diff_replaced_subtrees() {
+    let a = parse("(A | B) & C");
+    let b = parse("!(D & E) & C");
+    let edits = a.diff(&b);
+    assert_eq!(edits.len(), 1);
+    let TreeEdit::ReplaceSubtree { path, old, new } = &edits[0] else {
+        panic!("a subtree should have been replaced");
+    };
+    assert_eq!(path, &vec![Left]);
+    check_same_eval(old, &parse("A | B"), "ABCDE");
+    check_same_eval(new, &parse("!(D & E)"), "ABCDE");
+}
@@ -0,0 +1,138 @@
+//! tests of the DIMACS CNF export
+
+use {crate::test_util::*, crate::*};
+
+fn to_dimacs(expr: &BeTree<BoolOperator, char>) -> Result<DimacsCnf, CnfError> {
+    expr.to_dimacs(
+        |op| *op == BoolOperator::And,
+        |op| *op == BoolOperator::Or,
+        |op| *op == BoolOperator::Not,
+        |&c| (c as u8 - b'A' + 1) as u32,
+    )
+}
+
+/// parse the clauses of a DIMACS document, checking its header
+fn parse_dimacs(dimacs: &str) -> (u32, Vec<Vec<i64>>) {
+    let mut lines = dimacs.lines();
+    let header: Vec<&str> = lines.next().unwrap().split(' ').collect();
+    assert_eq!(header[..2], ["p", "cnf"]);
+    let variable_count: u32 = header[2].parse().unwrap();
+    let clause_count: usize = header[3].parse().unwrap();
+    let clauses: Vec<Vec<i64>> = lines
+        .map(|line| {
+            let literals: Vec<i64> = line.split(' ').map(|l| l.parse().unwrap()).collect();
+            assert_eq!(literals.last(), Some(&0));
+            literals[..literals.len() - 1].to_vec()
+        })
+        .collect();
+    assert_eq!(clauses.len(), clause_count);
+    (variable_count, clauses)
+}
+
+/// count the assignments of the variables satisfying all clauses,
+/// the variables up to `fixed` having the values given by `fixed_values`
+fn count_models(variable_count: u32, clauses: &[Vec<i64>], fixed: u32, fixed_values: u32) -> usize {
+    let free = variable_count - fixed;
+    (0u32..1 << free)
+        .filter(|free_values| {
+            let values = fixed_values | (free_values << fixed);
+            clauses.iter().all(|clause| {
+                clause.iter().any(|&literal| {
+                    let value = values & (1 << (literal.unsigned_abs() - 1)) != 0;
+                    value == (literal > 0)
+                })
+            })
+        })
+        .count()
+}
+
+#[test]
+fn dimacs_is_equisatisfiable() {
+    let inputs = [
+        "A",
+        "!A",
+        "A & B",
+        "A | B",
+        "(A | B) & !(C | D | E)",
+        "!(!((A|B)&(B|C)&A)) & !D & (E | (A|E))",
+        "A & !A",
+        "A | !A",
+        "(A & B) | (!A & C)",
+    ];
+    for input in inputs {
+        let expr = parse(input);
+        let cnf = to_dimacs(&expr).unwrap();
+        let (variable_count, clauses) = parse_dimacs(&cnf.dimacs);
+        assert_eq!(variable_count, cnf.variable_count);
+        assert_eq!(clauses.len(), cnf.clause_count);
+        // the atom variables come first, then the auxiliary ones
+        let atom_var_count = variable_count - cnf.auxiliary_variables.len() as u32;
+        // for each assignment of the atoms, the auxiliary variables are
+        // determined, and there's a model only when the expression is true
+        for atom_values in 0u32..1 << atom_var_count {
+            let trues: Vec<char> = "ABCDE"
+                .chars()
+                .enumerate()
+                .filter(|(i, _)| atom_values & (1 << i) != 0)
+                .map(|(_, c)| c)
+                .collect();
+            let expected = usize::from(eval(&expr, &trues).unwrap());
+            assert_eq!(
+                count_models(variable_count, &clauses, atom_var_count, atom_values),
+                expected,
+                "{} with {:?}",
+                input,
+                trues,
+            );
+        }
+    }
+}
+
+#[test]
+fn dimacs_snapshot() {
+    let cnf = to_dimacs(&parse("(A | B) & !C")).unwrap();
+    assert_eq!(
+        cnf.dimacs,
+        "\
+p cnf 5 7
+4 -1 0
+4 -2 0
+-4 1 2 0
+-5 4 0
+-5 -3 0
+5 -4 3 0
+5 0
+"
+    );
+    assert_eq!(
+        cnf.auxiliary_variables,
+        vec![(4, NodeId::new(1)), (5, NodeId::new(0))]
+    );
+}
+
+#[test]
+fn dimacs_errors() {
+    assert_eq!(to_dimacs(&parse("")), Err(CnfError::EmptyExpression));
+    assert_eq!(
+        to_dimacs(&parse("A &")),
+        Err(CnfError::MissingOperand {
+            node: NodeId::new(0)
+        })
+    );
+    assert!(matches!(
+        to_dimacs(&parse("A ! B")),
+        Err(CnfError::UnsupportedOperator { .. })
+    ));
+    let expr = parse("A & B");
+    assert_eq!(
+        expr.to_dimacs(
+            |op| *op == BoolOperator::And,
+            |op| *op == BoolOperator::Or,
+            |op| *op == BoolOperator::Not,
+            |&c| if c == 'B' { 0 } else { 1 },
+        ),
+        Err(CnfError::InvalidVariable {
+            atom: AtomId::new(1)
+        })
+    );
+}
@@ -0,0 +1,185 @@
+//! tests of the fully parenthesized rendering of expressions
+
+use {super::*, crate::test_util::*};
+
+fn precedence(op: &BoolOperator) -> u8 {
+    match op {
+        BoolOperator::Or => 1,
+        BoolOperator::And => 2,
+        BoolOperator::Not => 3,
+    }
+}
+
+fn minimal(expr: &BeTree<BoolOperator, char>) -> String {
+    expr.to_string_minimal(precedence, |op, _| op.to_string(), |c| c.to_string())
+}
+
+#[test]
+fn display_rendering() {
+    let cases = [
+        ("", ""),
+        ("A", "A"),
+        ("((A))", "A"),
+        ("!A", "(!(A))"),
+        ("!!A", "(!(!(A)))"),
+        ("A | B", "(A | B)"),
+        ("(A | B) & !C", "((A | B) & (!(C)))"),
+        ("A & B | C & D", "(((A & B) | C) & D)"),
+        ("(A | B) & !(C | D | E)", "((A | B) & (!((C | D) | E)))"),
+        ("A & !(B | !C)", "(A & (!(B | (!(C)))))"),
+    ];
+    for (input, displayed) in cases {
+        assert_eq!(parse(input).to_string(), displayed);
+    }
+}
+
+#[test]
+fn display_incomplete_expressions() {
+    let cases = [
+        ("A |", "(A |)"),
+        ("!", "(!)"),
+        ("A & (B |", "(A & (B |))"),
+        ("A & !", "(A & (!))"),
+    ];
+    for (input, displayed) in cases {
+        assert_eq!(parse(input).to_string(), displayed);
+    }
+}
+
+#[test]
+fn display_round_trip() {
+    let inputs = [
+        "A",
+        "!A",
+        "(A | B) & !(C | D | E)",
+        "!(!((A|B)&(B|C)&A)) & !D & (E | (A|E))",
+        "A & B | C & D",
+        "A & (B | C & D)",
+        "!!A & B",
+        "A & !(B | !C) | D",
+    ];
+    for input in inputs {
+        let expr = parse(input);
+        let reparsed = parse(&expr.to_string());
+        check_links(&reparsed);
+        assert!(same_structure(&expr, &reparsed), "{} -> {}", input, expr);
+        check_same_eval(&reparsed, &expr, "ABCDE");
+    }
+}
+
+/// a precedence climbing parser, building the tree through a fully
+/// parenthesized string
+struct PrecedenceParser {
+    chars: Vec<char>,
+    pos: usize,
+}
+impl PrecedenceParser {
+    fn parse(input: &str) -> BeTree<BoolOperator, char> {
+        let mut parser = Self {
+            chars: input.chars().filter(|c| !c.is_whitespace()).collect(),
+            pos: 0,
+        };
+        let parenthesized = parser.expr(0);
+        assert_eq!(
+            parser.pos,
+            parser.chars.len(),
+            "unparsed input in {:?}",
+            input
+        );
+        parse(&parenthesized)
+    }
+    fn expr(&mut self, min_precedence: u8) -> String {
+        let mut left = self.operand();
+        while let Some(&c) = self.chars.get(self.pos) {
+            let op = match c {
+                '&' => BoolOperator::And,
+                '|' => BoolOperator::Or,
+                _ => break,
+            };
+            let p = precedence(&op);
+            if p < min_precedence {
+                break;
+            }
+            self.pos += 1;
+            let right = self.expr(p + 1);
+            left = format!("({}{}{})", left, c, right);
+        }
+        left
+    }
+    fn operand(&mut self) -> String {
+        let c = self.chars[self.pos];
+        self.pos += 1;
+        match c {
+            '!' => format!("!{}", self.operand()),
+            '(' => {
+                let inner = self.expr(0);
+                assert_eq!(self.chars[self.pos], ')');
+                self.pos += 1;
+                format!("({})", inner)
+            }
+            c => c.to_string(),
+        }
+    }
+}
+
+/// a deterministic generator of expressions
+struct ExprGenerator(u64);
+impl ExprGenerator {
+    fn next(&mut self, n: u64) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0 % n
+    }
+    fn expr(&mut self, depth: usize) -> String {
+        if depth == 0 || self.next(4) == 0 {
+            return ['A', 'B', 'C', 'D', 'E'][self.next(5) as usize].to_string();
+        }
+        if self.next(5) == 0 {
+            return format!("!{}", self.expr(depth - 1));
+        }
+        let op = if self.next(2) == 0 { '&' } else { '|' };
+        format!("({} {} {})", self.expr(depth - 1), op, self.expr(depth - 1))
+    }
+}
+
+#[test]
+fn minimal_rendering() {
+    let cases = [
+        ("", ""),
+        ("A", "A"),
+        ("((A))", "A"),
+        ("!A", "!A"),
+        ("!!A", "!!A"),
+        ("!(A | B)", "!(A | B)"),
+        ("(A & B) | C", "A & B | C"),
+        ("A & (B | C)", "A & (B | C)"),
+        ("A | (B & C)", "A | B & C"),
+        ("A | B | C", "A | B | C"),
+        ("A | (B | C)", "A | (B | C)"),
+        ("(A | B) & !(C | D | E)", "(A | B) & !(C | D | E)"),
+        ("!(A & B) | (!C & D)", "!(A & B) | !C & D"),
+        ("A &", "A &"),
+    ];
+    for (input, rendered) in cases {
+        assert_eq!(minimal(&parse(input)), rendered);
+    }
+}
+
+#[test]
+fn minimal_rendering_round_trip() {
+    let mut generator = ExprGenerator(0x2545_f491_4f6c_dd1d);
+    for _ in 0..500 {
+        let input = generator.expr(5);
+        let expr = parse(&input);
+        let rendered = minimal(&expr);
+        let reparsed = PrecedenceParser::parse(&rendered);
+        check_links(&reparsed);
+        assert!(
+            same_structure(&expr, &reparsed),
+            "{} rendered as {}",
+            input,
+            rendered
+        );
+    }
+}
@@ -0,0 +1,54 @@
+//! tests of the Graphviz export
+
+use crate::{test_util::*, NodeId};
+
+#[test]
+fn dot_snapshot() {
+    let expr = parse("(A | B) & !(C | D | E)");
+    let dot = expr.to_dot(|op| op.to_string(), |c| c.to_string());
+    assert_eq!(
+        dot,
+        r#"digraph {
+    node [shape=circle];
+    head [shape=plaintext];
+    head -> n0;
+    n0 [label="&", penwidth=2];
+    n0 -> n1 [label="L"];
+    n0 -> n2 [label="R"];
+    n1 [label="|"];
+    n1 -> a0 [label="L"];
+    n1 -> a1 [label="R"];
+    n2 [label="!", shape=doublecircle];
+    n2 -> n4 [label="L"];
+    n4 [label="|"];
+    n4 -> n3 [label="L"];
+    n4 -> a4 [label="R"];
+    n3 [label="|"];
+    n3 -> a2 [label="L"];
+    n3 -> a3 [label="R"];
+    a0 [label="A", shape=box];
+    a1 [label="B", shape=box];
+    a2 [label="C", shape=box];
+    a3 [label="D", shape=box];
+    a4 [label="E", shape=box];
+}
+"#
+    );
+}
+
+#[test]
+fn dot_orphans() {
+    let mut expr = parse("A & (B | C)");
+    expr.cursor_at_mut(NodeId::new(1))
+        .unwrap()
+        .replace_with_atom('"');
+    check_links(&expr);
+    let dot = expr.to_dot(|op| op.to_string(), |c| c.to_string());
+    assert!(!dot.contains("n1 "));
+    assert!(!dot.contains("a1 "));
+    assert!(dot.contains(r#"a3 [label="\"", shape=box];"#));
+    let dot = expr.to_dot_with_orphans(|op| op.to_string(), |c| c.to_string());
+    assert!(dot.contains(r#"n1 [label="|", color=grey, fontcolor=grey];"#));
+    assert!(dot.contains(r#"n1 -> a1 [label="L", color=grey, fontcolor=grey];"#));
+    assert!(dot.contains(r#"a1 [label="B", shape=box, color=grey, fontcolor=grey];"#));
+}
@@ -0,0 +1,418 @@
+//! tests of the structural edits
+
+use {super::*, crate::test_util::*};
+
+#[test]
+fn replace_atoms_with_trees_in_all_positions() {
+    let cases = [
+        ("M & A", "(X | Y) & A"),
+        ("A & M", "A & (X | Y)"),
+        ("!M", "!(X | Y)"),
+        ("M", "(X | Y)"),
+        ("(A | !M) & M", "(A | !(X | Y)) & (X | Y)"),
+    ];
+    for (input, expected) in cases {
+        let mut expr = parse(input);
+        expr.replace_atoms_with_trees(|&c| if c == 'M' { Some(parse("X | Y")) } else { None });
+        check_links(&expr);
+        check_same_eval(&expr, &parse(expected), "AXY");
+    }
+}
+
+#[test]
+fn replace_atoms_with_trees_is_not_recursive() {
+    let mut expr = parse("M & A");
+    // the replacement contains the macro itself
+    expr.replace_atoms_with_trees(|&c| if c == 'M' { Some(parse("M | B")) } else { None });
+    check_same_eval(&expr, &parse("(M | B) & A"), "ABM");
+    // the replaced atom is still in the arena
+    assert_eq!(expr.atom(AtomId::new(0)), Some(&'M'));
+}
+
+#[test]
+fn remove_atom_from_every_position() {
+    let cases = [
+        ('A', "B & !(C | D | E)"),
+        ('B', "A & !(C | D | E)"),
+        ('C', "(A | B) & !(D | E)"),
+        ('D', "(A | B) & !(C | E)"),
+        ('E', "(A | B) & !(C | D)"),
+    ];
+    for (removed, expected) in cases {
+        let mut expr = parse("(A | B) & !(C | D | E)");
+        let atom_id = AtomId::from_index(expr.iter_atoms().position(|&c| c == removed).unwrap());
+        expr.remove_atom(atom_id).unwrap();
+        check_links(&expr);
+        assert_eq!(expr.op_count, 4);
+        assert_eq!(expr.get_openness(), 0);
+        check_same_eval(&expr, &parse(expected), "ABCDE");
+        // the atom can't be removed twice
+        assert_eq!(
+            expr.remove_atom(atom_id),
+            Err(EditError::UnreachableAtom(atom_id))
+        );
+    }
+}
+
+#[test]
+fn remove_atom_cascading() {
+    let mut expr = parse("(A | B) & !(C | D | E)");
+    for (removed, expected, op_count) in [
+        (2, "(A | B) & !(D | E)", 4),
+        (3, "(A | B) & !E", 3),
+        (4, "A | B", 1),
+        (0, "B", 0),
+    ] {
+        expr.remove_atom(AtomId::new(removed)).unwrap();
+        check_links(&expr);
+        assert_eq!(expr.op_count, op_count);
+        check_same_eval(&expr, &parse(expected), "ABCDE");
+    }
+    assert!(!expr.is_empty());
+    expr.remove_atom(AtomId::new(1)).unwrap();
+    assert!(expr.is_empty());
+    assert_eq!(eval(&expr, &[]), None);
+    assert_eq!(
+        expr.remove_atom(AtomId::new(1)),
+        Err(EditError::UnknownAtom(AtomId::new(1)))
+    );
+}
+
+#[test]
+fn remove_atom_then_push() {
+    let mut expr = parse("A & !B");
+    expr.remove_atom(AtomId::new(1)).unwrap();
+    check_links(&expr);
+    expr.push_operator(BoolOperator::Or);
+    expr.push_atom('C');
+    check_links(&expr);
+    check_same_eval(&expr, &parse("A | C"), "AC");
+}
+
+#[test]
+fn filter_atoms_in_nested_and_unary_positions() {
+    let cases = [
+        ("(A | B) & !(C | D | E)", "ACE", "A & !(C | E)", 2),
+        ("(A | B) & !(C | D | E)", "BD", "B & !D", 3),
+        ("(A | B) & !(C | D | E)", "CDE", "!(C | D | E)", 2),
+        (
+            "!(!((A|B)&(B|C)&A)) & !D & (E | (A|E))",
+            "BE",
+            "!(!(B & B)) & (E | E)",
+            5,
+        ),
+        ("B & !!A", "B", "B", 1),
+        ("A & (B | (C & !D))", "AD", "A & !D", 2),
+    ];
+    for (input, kept, expected, removed_count) in cases {
+        let mut expr = parse(input);
+        let removed = expr.filter_atoms(|&c| kept.contains(c));
+        assert_eq!(removed, removed_count, "removed in {:?}", input);
+        check_links(&expr);
+        let expected = parse(expected);
+        assert_eq!(expr.op_count, expected.op_count, "op_count for {:?}", input);
+        check_same_eval(&expr, &expected, "ABCDE");
+    }
+}
+
+#[test]
+fn filter_atoms_removing_everything() {
+    let mut expr = parse("(A | B) & !(C | D | E)");
+    assert_eq!(expr.filter_atoms(|_| false), 5);
+    assert!(expr.is_empty());
+    assert_eq!(eval(&expr, &[]), None);
+    // the tree can be built again
+    expr.push_atom('A');
+    assert_eq!(eval(&expr, &['A']), Some(true));
+}
+
+#[test]
+fn wrap_unary_flips_evaluation() {
+    let expr = parse("(A | B) & !(C | D | E)");
+    let mut wrapped = expr.clone();
+    wrapped.wrap_unary(BoolOperator::Not);
+    check_links(&wrapped);
+    assert_eq!(wrapped.op_count, 6);
+    check_same_eval(&wrapped, &parse("!((A | B) & !(C | D | E))"), "ABCDE");
+    wrapped.wrap_unary(BoolOperator::Not);
+    check_links(&wrapped);
+    check_same_eval(&wrapped, &expr, "ABCDE");
+}
+
+#[test]
+fn wrap_unary_atomic_and_empty_trees() {
+    let mut expr = parse("A");
+    expr.wrap_unary(BoolOperator::Not);
+    check_same_eval(&expr, &parse("!A"), "A");
+    // the tree accepts a binary operator after wrapping
+    assert!(expr.accept_binary_operator());
+    expr.push_operator(BoolOperator::And);
+    expr.push_atom('B');
+    check_links(&expr);
+    check_same_eval(&expr, &parse("!A & B"), "AB");
+    let mut empty = BeTree::<BoolOperator, char>::new();
+    empty.wrap_unary(BoolOperator::Not);
+    assert!(empty.is_empty());
+    assert_eq!(empty, BeTree::new());
+}
+
+#[test]
+fn simplify_involutive_negations() {
+    let cases = [
+        ("B & !!A", "B & A", 1),
+        ("B & !!!A", "B & !A", 1),
+        ("B & !!!!A", "B & A", 2),
+        ("B & !!!!!A", "B & !A", 2),
+        ("!!(A | B)", "A | B", 1),
+        ("!!!(A | B)", "!(A | B)", 1),
+        ("!!!!!A", "!A", 2),
+        ("!!A", "A", 1),
+        ("A & (B | !!(C & !!!!D))", "A & (B | (C & D))", 3),
+        ("(A | !(!B))", "(A | B)", 1),
+        ("(A | !(B & !C))", "(A | !(B & !C))", 0),
+    ];
+    for (input, expected, pairs) in cases {
+        let mut expr = parse(input);
+        assert_eq!(
+            expr.simplify_involutive(&BoolOperator::Not),
+            pairs,
+            "pairs in {:?}",
+            input
+        );
+        check_links(&expr);
+        let expected = parse(expected);
+        assert_eq!(expr.op_count, expected.op_count, "op_count for {:?}", input);
+        check_same_eval(&expr, &expected, "ABCD");
+    }
+}
+
+#[test]
+fn simplify_involutive_ignores_other_operators() {
+    let mut expr: BeTree<char, char> = BeTree::new();
+    for c in "-!!-A".chars() {
+        match c {
+            '!' | '-' => expr.push_operator(c),
+            _ => expr.push_atom(c),
+        }
+    }
+    assert_eq!(expr.simplify_involutive(&'-'), 0);
+    assert_eq!(expr.op_count, 4);
+    assert_eq!(expr.simplify_involutive(&'!'), 1);
+    assert_eq!(expr.op_count, 2);
+    assert_eq!(expr.simplify_involutive(&'-'), 1);
+    assert_eq!(expr.op_count, 0);
+    check_links(&expr);
+    assert!(expr.is_atomic());
+}
+
+/// parse an expression of single digit numbers and subtractions
+fn parse_subtractions(input: &str) -> BeTree<char, i64> {
+    let mut expr = BeTree::new();
+    for c in input.chars() {
+        match c {
+            '-' => expr.push_operator(c),
+            ' ' => {}
+            '(' => expr.open_par(),
+            ')' => expr.close_par(),
+            _ => expr.push_atom(c.to_digit(10).unwrap() as i64),
+        }
+    }
+    expr
+}
+
+fn eval_subtractions(expr: &BeTree<char, i64>) -> Option<i64> {
+    expr.eval(|&n| n, |_, a, b| a - b.unwrap(), |_, _| false)
+}
+
+#[test]
+fn swap_children_at_root_and_at_depth() {
+    let mut expr = parse_subtractions("9 - (6 - 2)");
+    assert_eq!(eval_subtractions(&expr), Some(5));
+    let root = expr.head;
+    let Child::Node(inner) = expr.node(root).unwrap().right() else {
+        panic!("right operand should be a node");
+    };
+    expr.swap_children(inner).unwrap();
+    check_links(&expr);
+    assert_eq!(eval_subtractions(&expr), Some(13)); // 9 - (2 - 6)
+    expr.swap_children(root).unwrap();
+    check_links(&expr);
+    assert_eq!(eval_subtractions(&expr), Some(-13)); // (2 - 6) - 9
+    assert_eq!(expr.node(inner).unwrap().parent(), Some(root));
+    // swapping back gives the original structure
+    expr.swap_children(root).unwrap();
+    expr.swap_children(inner).unwrap();
+    assert_eq!(expr, parse_subtractions("9 - (6 - 2)"));
+}
+
+#[test]
+fn swap_root_children_through_groups() {
+    let mut expr = parse("((A | !B) & C)");
+    expr.swap_root_children().unwrap();
+    check_links(&expr);
+    assert_eq!(tokens_string(&expr), "C&(A|!B)");
+    let mut expr = parse_subtractions("((8 - 3))");
+    expr.swap_root_children().unwrap();
+    assert_eq!(eval_subtractions(&expr), Some(-5));
+}
+
+#[test]
+fn swap_children_errors() {
+    let mut expr = parse("!A & B");
+    let Child::Node(unary) = expr.node(expr.head).unwrap().left() else {
+        panic!("left operand should be a node");
+    };
+    assert_eq!(
+        expr.swap_children(unary),
+        Err(EditError::NotBinaryNode(unary))
+    );
+    assert_eq!(
+        expr.swap_children(NodeId::new(1000)),
+        Err(EditError::UnknownNode(NodeId::new(1000)))
+    );
+    // a node whose right operand hasn't been pushed yet
+    let mut expr = parse("A &");
+    assert_eq!(
+        expr.swap_root_children(),
+        Err(EditError::NotBinaryNode(expr.head))
+    );
+    let mut expr = parse("A");
+    assert_eq!(
+        expr.swap_root_children(),
+        Err(EditError::NotBinaryNode(expr.head))
+    );
+}
+
+#[test]
+fn rotations_change_the_association() {
+    let mut expr = parse_subtractions("9 - (6 - 2)");
+    assert_eq!(eval_subtractions(&expr), Some(5));
+    let head = expr.head;
+    expr.rotate_left(head).unwrap();
+    check_links(&expr);
+    assert_ne!(expr.head, head);
+    assert_eq!(eval_subtractions(&expr), Some(1)); // (9 - 6) - 2
+    expr.rotate_right(expr.head).unwrap();
+    check_links(&expr);
+    assert_eq!(expr.head, head);
+    assert_eq!(eval_subtractions(&expr), Some(5));
+}
+
+#[test]
+fn rotations_render() {
+    let mut expr = parse("A & (B | C)");
+    expr.rotate_left(expr.head).unwrap();
+    check_links(&expr);
+    assert_eq!(tokens_string(&expr), "A&B|C");
+    expr.rotate_right(expr.head).unwrap();
+    check_links(&expr);
+    assert_eq!(tokens_string(&expr), "A&(B|C)");
+    // a rotation below the root, preserving the evaluation of an associative operator
+    let mut expr = parse("!(A & (B & C)) | D");
+    let original = expr.clone();
+    let Child::Node(not) = expr.node(expr.head).unwrap().left() else {
+        panic!("left operand should be a node");
+    };
+    let Child::Node(and) = expr.node(not).unwrap().left() else {
+        panic!("operand of the negation should be a node");
+    };
+    expr.rotate_left(and).unwrap();
+    check_links(&expr);
+    assert_eq!(tokens_string(&expr), "!(A&B&C)|D");
+    check_same_eval(&expr, &original, "ABCD");
+}
+
+#[test]
+fn rotation_errors() {
+    let mut expr = parse("A & B");
+    let head = expr.head;
+    assert_eq!(expr.rotate_left(head), Err(EditError::NotRotatable(head)));
+    assert_eq!(expr.rotate_right(head), Err(EditError::NotRotatable(head)));
+    let mut expr = parse("(A | B) & !C");
+    let head = expr.head;
+    assert_eq!(expr.rotate_left(head), Err(EditError::NotRotatable(head)));
+    assert_eq!(
+        expr.rotate_left(NodeId::new(1000)),
+        Err(EditError::UnknownNode(NodeId::new(1000)))
+    );
+    let mut expr = parse("!(A & B)");
+    let head = expr.head;
+    assert_eq!(expr.rotate_right(head), Err(EditError::NotBinaryNode(head)));
+}
+
+#[test]
+fn prune_subtrees() {
+    use Side::*;
+    let cases: &[(&[Side], &str, usize)] = &[
+        (&[Left], "!(C | D | E)", 3),
+        (&[Right], "A | B", 1),
+        (&[Right, Left], "A | B", 1),
+        (&[Right, Left, Left], "(A | B) & !E", 3),
+    ];
+    for (path, expected, op_count) in cases {
+        let mut expr = parse("(A | B) & !(C | D | E)");
+        assert_eq!(expr.op_count, 5);
+        let node_id = node_at(&expr, path);
+        expr.prune(node_id).unwrap();
+        check_links(&expr);
+        assert_eq!(
+            expr.op_count, *op_count,
+            "op_count after pruning {:?}",
+            path
+        );
+        check_same_eval(&expr, &parse(expected), "ABCDE");
+        assert_eq!(
+            expr.prune(node_id),
+            Err(EditError::UnreachableNode(node_id))
+        );
+        // the tree can still be extended
+        expr.push_operator(BoolOperator::Or);
+        expr.push_atom('A');
+        check_links(&expr);
+        check_same_eval(&expr, &parse(&format!("{} | A", expected)), "ABCDE");
+    }
+}
+
+#[test]
+fn prune_the_head() {
+    let mut expr = parse("(A | B) & !C");
+    expr.prune(expr.head).unwrap();
+    assert!(expr.is_empty());
+    assert_eq!(
+        expr.prune(NodeId::new(1000)),
+        Err(EditError::UnknownNode(NodeId::new(1000)))
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn dedup_atoms_rewires_links() {
+    let original = parse("(A | B) & !(A | C) | (B & A)");
+    let mut expr = original.clone();
+    assert_eq!(expr.dedup_atoms(), 3);
+    check_links(&expr);
+    check_same_eval(&expr, &original, "ABC");
+    // the arena still holds the duplicates
+    assert_eq!(expr.iter_atoms().collect::<String>(), "ABACBA");
+    // but only the first occurrences are referenced
+    let mut ids = expr.map_atoms_indexed(|id, _| id);
+    let mut referenced = Vec::new();
+    ids.map_atoms_in_place(|id, _| referenced.push(*id));
+    assert_eq!(indices(referenced), vec![0, 1, 0, 3, 1, 0]);
+    assert_eq!(expr.dedup_atoms(), 0);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn dedup_atoms_then_map_atoms() {
+    let mut expr = parse("A & (B | A) & !A");
+    assert_eq!(expr.dedup_atoms(), 2);
+    // mappings preserve ids, so the shared atom is mapped once for all
+    // its occurrences, while the unreachable duplicates are mapped too
+    let mapped: Result<BeTree<BoolOperator, char>, ()> =
+        expr.try_map_atoms(|c| Ok(if *c == 'A' { 'C' } else { *c }));
+    let mapped = mapped.unwrap();
+    check_links(&mapped);
+    check_same_eval(&mapped, &parse("C & (B | C) & !C"), "ABC");
+    assert_eq!(mapped.iter_atoms().collect::<String>(), "CBCC");
+}
@@ -0,0 +1,146 @@
+//! tests of the detection of ids kept across structural edits
+
+use {
+    super::*,
+    crate::test_util::{BoolOperator::*, *},
+};
+
+/// an id kept with the epoch of the tree when it was obtained
+struct KeptId<Id> {
+    id: Id,
+    epoch: u64,
+}
+
+impl<Id: Copy> KeptId<Id> {
+    fn new(id: Id, expr: &BeTree<BoolOperator, char>) -> Self {
+        Self {
+            id,
+            epoch: expr.edits_epoch(),
+        }
+    }
+    fn get(&self, expr: &BeTree<BoolOperator, char>) -> Option<Id> {
+        (expr.edits_epoch() == self.epoch).then_some(self.id)
+    }
+}
+
+#[test]
+fn pushes_and_atom_changes_keep_ids() {
+    let mut expr = parse("A & (B");
+    let kept = KeptId::new(AtomId::new(1), &expr);
+    expr.push_operator(Or);
+    expr.push_atom('C');
+    expr.close_par();
+    *expr.atom_mut(AtomId::new(0)).unwrap() = 'D';
+    expr.set_atom(AtomId::new(2), 'E');
+    expr.replace_operator(&Or, And);
+    expr.map_atoms_in_place(|atom, _| *atom = atom.to_ascii_lowercase());
+    assert_eq!(kept.get(&expr), Some(AtomId::new(1)));
+    assert_eq!(expr.atom(AtomId::new(1)), Some(&'b'));
+}
+
+#[test]
+fn stale_ids_after_compact_and_normalize() {
+    let mut expr = parse("(A | B) & C");
+    expr.remove_atom(AtomId::new(0)).unwrap();
+    // C is the atom 2, until the tree is compacted
+    let kept = KeptId::new(AtomId::new(2), &expr);
+    assert_eq!(expr.atom(kept.get(&expr).unwrap()), Some(&'C'));
+    let mut compacted = expr.clone();
+    compacted.compact();
+    assert_eq!(kept.get(&compacted), None);
+    // without the check, the id would silently give nothing, or another atom
+    assert_eq!(compacted.atom(AtomId::new(2)), None);
+    let normalized = expr.clone().normalize();
+    assert_eq!(kept.get(&normalized), None);
+    assert!(normalized.edits_epoch() > expr.edits_epoch());
+    // a clone keeps the ids, so it keeps the epoch
+    assert_eq!(kept.get(&expr.clone()), Some(AtomId::new(2)));
+}
+
+type Edit = fn(&mut BeTree<BoolOperator, char>);
+
+#[test]
+fn stale_ids_after_edits() {
+    let edits: &[(&str, Edit)] = &[
+        ("prune", |expr| {
+            let Child::Node(right) = expr.nodes[expr.head].right() else {
+                panic!("no right node");
+            };
+            expr.prune(right).unwrap();
+        }),
+        ("remove_atom", |expr| {
+            expr.remove_atom(AtomId::new(0)).unwrap()
+        }),
+        ("filter_atoms", |expr| {
+            expr.filter_atoms(|&c| c != 'B');
+        }),
+        ("wrap_unary", |expr| expr.wrap_unary(Not)),
+        ("swap_root_children", |expr| {
+            expr.swap_root_children().unwrap();
+        }),
+        ("rotate_right", |expr| {
+            let head = expr.head;
+            expr.rotate_right(head).unwrap();
+        }),
+        ("detach", |expr| {
+            let Child::Node(left) = expr.nodes[expr.head].left() else {
+                panic!("no left node");
+            };
+            expr.detach(left).unwrap();
+        }),
+        ("attach", |expr| {
+            let Child::Node(right) = expr.nodes[expr.head].right() else {
+                panic!("no right node");
+            };
+            expr.attach(parse("D").into(), right, Side::Left, Some(Or))
+                .unwrap();
+        }),
+        ("cursor", |expr| {
+            expr.cursor_mut().wrap_in_unary(Not);
+        }),
+        ("simplify_involutive", |expr| {
+            expr.simplify_involutive(&Not);
+        }),
+        ("rebalance_chains", |expr| expr.rebalance_chains(&Or)),
+        ("clear", |expr| expr.clear()),
+        ("take", |expr| {
+            expr.take();
+        }),
+    ];
+    for (name, edit) in edits {
+        let mut expr = parse("(A | B) & !C");
+        let kept = KeptId::new(expr.head, &expr);
+        edit(&mut expr);
+        check_links(&expr);
+        assert_eq!(kept.get(&expr), None, "after {}", name);
+    }
+}
+
+#[test]
+fn failed_transaction_moves_the_epoch_forward() {
+    let mut expr = parse("(A | B) & !C");
+    let kept = KeptId::new(expr.head, &expr);
+    // a transaction without structural edit keeps the ids
+    let result = expr.edit(|tx| {
+        tx.replace_operator(&Or, And);
+        tx.prune(NodeId::new(1000))
+    });
+    assert!(result.is_err());
+    assert_eq!(kept.get(&expr), Some(expr.head));
+    // the ids obtained during a rolled back transaction stay stale,
+    // even after other edits
+    let mut kept_in_transaction = None;
+    let result = expr.edit(|tx| {
+        tx.wrap_unary(Not);
+        kept_in_transaction = Some(KeptId::new(tx.tree().head, tx.tree()));
+        tx.prune(NodeId::new(1000))
+    });
+    assert!(result.is_err());
+    let kept_in_transaction = kept_in_transaction.unwrap();
+    assert_eq!(kept.get(&expr), None);
+    assert_eq!(kept_in_transaction.get(&expr), None);
+    expr.edit(|tx| tx.tree_mut().swap_root_children()).unwrap();
+    assert_eq!(kept_in_transaction.get(&expr), None);
+    expr.swap_root_children().unwrap();
+    assert_eq!(kept_in_transaction.get(&expr), None);
+}
@@ -0,0 +1,80 @@
+//! tests of the handling of empty groups
+
+use {super::*, crate::test_util::*};
+
+/// evaluate, giving to empty groups the `neutral` value
+fn eval_neutral(expr: &BeTree<BoolOperator, char>, trues: &[char], neutral: bool) -> Option<bool> {
+    expr.eval_with_empty_groups(
+        |c| trues.contains(c),
+        |op, a, b| op.eval(a, b),
+        |op, &a| op.short_circuit(a),
+        || neutral,
+    )
+}
+
+#[test]
+fn empty_groups_are_listed() {
+    assert_eq!(parse("").empty_groups(), []);
+    assert_eq!(parse("A & (B)").empty_groups(), []);
+    assert_eq!(parse("()").empty_groups(), [NodeId::new(1)]);
+    assert_eq!(parse("A & ()").empty_groups(), [NodeId::new(1)]);
+    assert_eq!(parse("!()").empty_groups(), [NodeId::new(2)]);
+    // only the inner group is empty
+    assert_eq!(parse("(())").empty_groups(), [NodeId::new(2)]);
+    assert_eq!(
+        parse("() | A & (())").empty_groups(),
+        [NodeId::new(1), NodeId::new(4)],
+    );
+}
+
+#[test]
+fn validate_reports_empty_groups() {
+    for input in ["", "A", "(A | B) & !(C)", "((A))"] {
+        assert_eq!(parse(input).validate(), Ok(()), "input: {:?}", input);
+    }
+    for input in ["()", "A & ()", "!()", "(())"] {
+        let expr = parse(input);
+        // the structure is consistent, only the expression is wrong
+        assert_eq!(expr.validate_structure(), Ok(()));
+        let node_idx = expr.empty_groups()[0];
+        assert_eq!(expr.validate(), Err(StructureError::EmptyGroup(node_idx)));
+    }
+    assert_eq!(
+        StructureError::EmptyGroup(NodeId::new(2)).to_string(),
+        "group 2 is empty",
+    );
+}
+
+#[test]
+fn empty_groups_take_the_given_value() {
+    assert_eq!(eval_neutral(&parse(""), &[], true), None);
+    for neutral in [true, false] {
+        assert_eq!(eval_neutral(&parse("()"), &[], neutral), Some(neutral));
+        assert_eq!(eval_neutral(&parse("(())"), &[], neutral), Some(neutral));
+        assert_eq!(eval_neutral(&parse("!()"), &[], neutral), Some(!neutral));
+    }
+    // true is the neutral element of the conjunction
+    let expr = parse("A & ()");
+    assert_eq!(eval_neutral(&expr, &['A'], true), Some(true));
+    assert_eq!(eval_neutral(&expr, &[], true), Some(false));
+    // false is the neutral element of the disjunction
+    let expr = parse("() | A");
+    assert_eq!(eval_neutral(&expr, &['A'], false), Some(true));
+    assert_eq!(eval_neutral(&expr, &[], false), Some(false));
+}
+
+#[test]
+fn other_expressions_evaluate_as_with_eval() {
+    for input in ["A", "A & B | !C", "(A | B) & !(C | A)", "!!(A & (B))"] {
+        let expr = parse(input);
+        for trues in all_assignments("ABC") {
+            assert_eq!(
+                eval_neutral(&expr, &trues, false),
+                eval(&expr, &trues),
+                "input: {:?}, trues: {:?}",
+                input,
+                trues,
+            );
+        }
+    }
+}
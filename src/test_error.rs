@@ -0,0 +1,86 @@
+//! tests of the error types
+
+use {
+    crate::{test_util::*, *},
+    std::error::Error,
+};
+
+fn assert_error_type<E: Error + Send + Sync + 'static>() {}
+
+#[test]
+fn errors_are_send_sync_and_static() {
+    assert_error_type::<BetError>();
+    assert_error_type::<EditError>();
+    assert_error_type::<StructureError>();
+    assert_error_type::<DecodeError>();
+    assert_error_type::<SexprError>();
+    assert_error_type::<ParseError>();
+    assert_error_type::<PushError>();
+    assert_error_type::<IdOverflow>();
+    #[cfg(feature = "logic")]
+    assert_error_type::<CnfError>();
+    #[cfg(feature = "logic")]
+    assert_error_type::<logic::TooManyVariables>();
+}
+
+#[test]
+fn error_messages() {
+    let mut expr = parse("A & B");
+    let err = expr.prune(NodeId::new(7)).unwrap_err();
+    assert_eq!(err.to_string(), "no node with id 7");
+    assert_eq!(
+        BetError::from(err).to_string(),
+        "invalid edit: no node with id 7"
+    );
+    let err = BeTree::<BoolOperator, char>::from_sexpr("(& A", |_| None, |_| None).unwrap_err();
+    assert_eq!(
+        BetError::from(err).to_string(),
+        "invalid s-expression: invalid operator at offset 1"
+    );
+    assert_eq!(
+        BetError::from(ParseError::UnexpectedEnd { offset: 3 }).to_string(),
+        "invalid expression: unexpected end of input at offset 3"
+    );
+    assert_eq!(
+        BetError::from(DecodeError::Truncated { offset: 3 }).to_string(),
+        "invalid encoding: truncated input at offset 3"
+    );
+    assert_eq!(
+        BetError::from(StructureError::WrongParent(NodeId::new(2))).to_string(),
+        "invalid structure: node 2 has a wrong parent"
+    );
+    assert_eq!(
+        BetError::from(IdOverflow).to_string(),
+        format!("too many nodes or atoms, the greatest id is {}", MAX_ID)
+    );
+}
+
+#[test]
+fn errors_can_be_chained() {
+    fn edit(expr: &mut BeTree<BoolOperator, char>) -> Result<(), BetError> {
+        expr.prune(NodeId::new(1))?;
+        expr.remove_atom(AtomId::new(9))?;
+        Ok(())
+    }
+    fn boxed(expr: &mut BeTree<BoolOperator, char>) -> Result<(), Box<dyn Error + Send + Sync>> {
+        edit(expr)?;
+        Ok(())
+    }
+    let mut expr = parse("(A | B) & C");
+    let err = boxed(&mut expr).unwrap_err();
+    assert_eq!(err.to_string(), "invalid edit: no atom with id 9");
+    let source = err.source().unwrap();
+    assert_eq!(source.to_string(), "no atom with id 9");
+    assert!(source.source().is_none());
+    let err = err.downcast::<BetError>().unwrap();
+    assert_eq!(*err, BetError::Edit(EditError::UnknownAtom(AtomId::new(9))));
+    let err = EditError::InvalidStructure(StructureError::WrongParent(NodeId::new(2)));
+    assert_eq!(
+        err.to_string(),
+        "the edits left an invalid structure: node 2 has a wrong parent"
+    );
+    assert_eq!(
+        err.source().unwrap().to_string(),
+        "node 2 has a wrong parent"
+    );
+}
@@ -0,0 +1,95 @@
+//! tests of the evaluation through trait objects, which must give
+//! the same results as the generic one
+
+use {
+    super::*,
+    crate::test_util::{BoolOperator::*, *},
+    core::cell::RefCell,
+};
+
+const EXPRESSIONS: &[&str] = &[
+    "",
+    "A",
+    "!A",
+    "A & B",
+    "A | !B & C",
+    "(A | B) & !(C | !A)",
+    "!(A & (B | !C)) | (A & !!B)",
+    "A & (",
+    "(A | B) &",
+];
+
+/// evaluate an operation, tolerating missing operands
+fn eval_op(op: &BoolOperator, a: bool, b: Option<bool>) -> bool {
+    match (op, b) {
+        (Not, _) => !a,
+        (_, None) => a,
+        (op, b) => op.eval(a, b),
+    }
+}
+
+#[test]
+fn dyn_and_generic_evaluations_are_identical() {
+    for input in EXPRESSIONS {
+        let expr = parse(input);
+        for trues in all_assignments("ABC") {
+            // the atoms evaluated, in order
+            let generic_calls = RefCell::new(Vec::new());
+            let generic = expr.eval(
+                |c| {
+                    generic_calls.borrow_mut().push(*c);
+                    trues.contains(c)
+                },
+                eval_op,
+                |op, &a| op.short_circuit(a),
+            );
+            let mut dyn_calls = Vec::new();
+            let dynamic = expr.eval_dyn(
+                &mut |c| {
+                    dyn_calls.push(*c);
+                    trues.contains(c)
+                },
+                &mut eval_op,
+                &mut |op, &a| op.short_circuit(a),
+            );
+            assert_eq!(dynamic, generic, "{:?} with {:?}", input, trues);
+            assert_eq!(dyn_calls, generic_calls.into_inner());
+        }
+    }
+}
+
+#[test]
+fn dyn_and_generic_faillible_evaluations_are_identical() {
+    let eval_atom = |c: &char| match c {
+        'A' => Ok(true),
+        'B' => Ok(false),
+        _ => Err(*c),
+    };
+    for input in EXPRESSIONS {
+        let expr = parse(input);
+        let generic = expr.eval_faillible(
+            eval_atom,
+            |op, a, b| Ok(eval_op(op, a, b)),
+            |op, &a| op.short_circuit(a),
+        );
+        let dynamic = expr.eval_faillible_dyn(
+            &mut |c| eval_atom(c),
+            &mut |op, a, b| Ok(eval_op(op, a, b)),
+            &mut |op, &a| op.short_circuit(a),
+        );
+        assert_eq!(dynamic, generic, "{:?}", input);
+    }
+    let expr = parse("A & (B | C)");
+    assert_eq!(
+        expr.eval_faillible_dyn(
+            &mut |c| eval_atom(c),
+            &mut |op: &BoolOperator, a, b| if *op == Or {
+                Err('|')
+            } else {
+                Ok(eval_op(op, a, b))
+            },
+            &mut |op, &a| op.short_circuit(a),
+        ),
+        Err('C'),
+    );
+}
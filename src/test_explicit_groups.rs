@@ -0,0 +1,151 @@
+//! tests of the memory of the groups written with parenthesis
+
+use {super::*, crate::test_util::*};
+
+fn precedence(op: &BoolOperator) -> u8 {
+    match op {
+        BoolOperator::Or => 1,
+        BoolOperator::And => 2,
+        BoolOperator::Not => 3,
+    }
+}
+
+fn minimal_keeping_groups(expr: &BeTree<BoolOperator, char>) -> String {
+    expr.to_string_minimal_keeping_groups(precedence, |op, _| op.to_string(), |c| c.to_string())
+}
+
+fn tokens_as_written(expr: &BeTree<BoolOperator, char>) -> String {
+    expr.to_tokens_as_written()
+        .into_iter()
+        .map(|token| match token {
+            Token::Atom(c) => c.to_string(),
+            Token::Operator(op) => op.to_string(),
+            Token::OpeningParenthesis => "(".to_string(),
+            Token::ClosingParenthesis => ")".to_string(),
+        })
+        .collect()
+}
+
+/// the explicit groups of the reachable nodes, in pre-order
+fn explicit_groups(expr: &BeTree<BoolOperator, char>) -> Vec<bool> {
+    let mut groups = Vec::new();
+    let mut stack = vec![expr.head];
+    while let Some(node_id) = stack.pop() {
+        groups.push(expr.was_explicit_group(node_id));
+        let (left, right) = expr.children(node_id).unwrap();
+        for child in [right, left] {
+            if let Child::Node(child_id) = child {
+                stack.push(child_id);
+            }
+        }
+    }
+    groups
+}
+
+#[test]
+fn as_written_rendering_keeps_redundant_parenthesis() {
+    let inputs = [
+        "",
+        "a",
+        "(a)",
+        "((a)) & (b | c)",
+        "(a & b) | c",
+        "(a | b & c)",
+        "!(a)",
+        "!(a & b)",
+        "(!a) | ((!(b)))",
+        "a & !((b | (c)))",
+    ];
+    for input in inputs {
+        let expr = parse(input);
+        let written = expr.display_as_written().to_string();
+        assert_eq!(written, input);
+        assert_eq!(tokens_as_written(&expr), input.replace(' ', ""));
+        // parsing the rendering gives back the same tree
+        assert_eq!(parse(&written), expr);
+        let replayed = from_tokens(expr.to_tokens_as_written());
+        assert_eq!(replayed.display_as_written().to_string(), input);
+        check_same_eval(&replayed, &expr, "abc");
+    }
+}
+
+#[test]
+fn minimal_rendering_keeping_groups() {
+    let cases = [
+        ("((a)) & (b | c)", "((a)) & (b | c)", "a & (b | c)"),
+        ("(a & b) | c", "(a & b) | c", "a & b | c"),
+        ("((a & b)) | c", "((a & b)) | c", "a & b | c"),
+        // the precedence still requires parenthesis
+        ("(a | b & c)", "((a | b) & c)", "(a | b) & c"),
+        ("!(a) & !(b | c)", "!(a) & !(b | c)", "!a & !(b | c)"),
+    ];
+    for (input, keeping_groups, minimal) in cases {
+        let expr = parse(input);
+        assert_eq!(minimal_keeping_groups(&expr), keeping_groups);
+        assert_eq!(
+            expr.to_string_minimal(precedence, |op, _| op.to_string(), |c| c.to_string()),
+            minimal,
+        );
+    }
+}
+
+#[test]
+fn other_renderings_are_unchanged() {
+    let expr = parse("((a)) & (b | c)");
+    assert_eq!(expr.to_string(), "(a & (b | c))");
+    assert_eq!(tokens_string(&expr), "((a))&(b|c)");
+}
+
+#[test]
+fn explicit_group_flags() {
+    // the head and the group of `a` are operator-less groups, the
+    // node of `|` is the group around `b | c`
+    let expr = parse("((a)) & (b | c)");
+    assert_eq!(explicit_groups(&expr), [false, true, true, true]);
+    // the restructuring of `(a & b | c)` moves the group
+    // from the node of `&` to the node of `|`
+    let expr = parse("(a & b | c)");
+    assert_eq!(explicit_groups(&expr), [false, true, false]);
+    assert!(!expr.was_explicit_group(NodeId::new(99)));
+    // nodes built without parenthesis aren't explicit groups
+    let expr = parse("a & b | !c");
+    assert_eq!(explicit_groups(&expr), [false, false, false]);
+}
+
+#[test]
+fn simplify_keeping_explicit_groups() {
+    let mut expr = parse("((a & b))");
+    expr.simplify_keeping_explicit_groups();
+    check_links(&expr);
+    assert!(expr.was_explicit_group(expr.head));
+    assert_eq!(expr.display_as_written().to_string(), "((a & b))");
+    // the node of the operation is still the inner group
+    expr.simplify();
+    check_links(&expr);
+    assert_eq!(expr.display_as_written().to_string(), "(a & b)");
+    let mut expr = parse("((a & b))");
+    expr.simplify();
+    assert_eq!(expr.display_as_written().to_string(), "(a & b)");
+    expr.push_operator(BoolOperator::Or);
+    expr.push_atom('c');
+    check_links(&expr);
+    assert_eq!(expr.display_as_written().to_string(), "(a & b) | c");
+    // only the implicit head group is removed
+    let mut expr = parse("(a) | b");
+    expr.simplify_keeping_explicit_groups();
+    assert_eq!(expr.display_as_written().to_string(), "(a) | b");
+    // the tree can still be completed
+    let mut expr = parse("((a))");
+    expr.simplify_keeping_explicit_groups();
+    expr.push_operator(BoolOperator::Or);
+    expr.push_atom('b');
+    check_links(&expr);
+    assert_eq!(expr.display_as_written().to_string(), "((a)) | b");
+    check_same_eval(&expr, &parse("a | b"), "ab");
+    let mut expr = parse("((a))");
+    expr.simplify();
+    expr.push_operator(BoolOperator::And);
+    expr.push_atom('b');
+    check_links(&expr);
+    assert_eq!(expr.display_as_written().to_string(), "(a) & b");
+}
@@ -0,0 +1,158 @@
+//! tests of the extraction of parts of trees
+
+use {
+    super::*,
+    crate::test_util::{BoolOperator::*, *},
+};
+
+#[test]
+fn split_top_level_on_and() {
+    let cases: &[(&str, &[&str])] = &[
+        ("A & B & (C | D)", &["A", "B", "C | D"]),
+        ("A & (B & C)", &["A", "B", "C"]),
+        ("((A & B)) & (C & (D & E))", &["A", "B", "C", "D", "E"]),
+        ("!(A & B) & !C", &["!(A & B)", "!C"]),
+        ("(A | B) & !(C | D | E)", &["A | B", "!(C | D | E)"]),
+        ("A | B & C", &["A | B", "C"]),
+        ("A | (B & C)", &["A | (B & C)"]),
+        ("!(A & B)", &["!(A & B)"]),
+        ("A", &["A"]),
+    ];
+    for (input, parts) in cases {
+        let expr = parse(input);
+        let split = expr.split_top_level(&And);
+        assert_eq!(split.len(), parts.len(), "parts of {:?}", input);
+        for (tree, part) in split.iter().zip(parts.iter()) {
+            check_links(tree);
+            check_same_eval(tree, &parse(part), "ABCDE");
+            // the extracted tree contains only its atoms
+            assert_eq!(
+                tree.iter_atoms().collect::<String>(),
+                part.chars()
+                    .filter(char::is_ascii_uppercase)
+                    .collect::<String>(),
+            );
+        }
+    }
+    assert!(BeTree::<BoolOperator, char>::new()
+        .split_top_level(&And)
+        .is_empty());
+}
+
+#[test]
+fn split_top_level_parts_can_be_extended() {
+    let expr = parse("A & (B | C)");
+    let mut parts = expr.split_top_level(&And);
+    let mut part = parts.pop().unwrap();
+    part.push_operator(Or);
+    part.push_atom('D');
+    check_links(&part);
+    check_same_eval(&part, &parse("B | C | D"), "BCD");
+}
+
+#[test]
+fn subtrees_of_the_root_can_be_recombined() {
+    let expr = parse("(A | B) & !(C | D | E)");
+    let root = expr.node(expr.head).unwrap();
+    let (Child::Node(left), Child::Node(right)) = (root.left(), root.right()) else {
+        panic!("both operands should be nodes");
+    };
+    let left = expr.subtree(left).unwrap();
+    let right = expr.subtree(right).unwrap();
+    check_links(&left);
+    check_links(&right);
+    assert_eq!(left.iter_atoms().collect::<String>(), "AB");
+    assert_eq!(right.iter_atoms().collect::<String>(), "CDE");
+    check_same_eval(&left, &parse("A | B"), "ABCDE");
+    check_same_eval(&right, &parse("!(C | D | E)"), "ABCDE");
+    let combined = left.combine(And, right);
+    check_links(&combined);
+    check_same_eval(&combined, &expr, "ABCDE");
+    // the combined tree can be extended
+    let mut extended = combined.combine(Or, parse("E"));
+    extended.push_operator(And);
+    extended.push_atom('A');
+    check_links(&extended);
+    check_same_eval(
+        &extended,
+        &parse("((A | B) & !(C | D | E) | E) & A"),
+        "ABCDE",
+    );
+}
+
+#[test]
+fn subtree_of_the_head_is_a_compact_clone() {
+    let mut expr = parse("A & (B | C) & D");
+    expr.remove_atom(AtomId::new(1)).unwrap();
+    assert_eq!(expr.iter_atoms().count(), 4);
+    let clone = expr.subtree(expr.head).unwrap();
+    check_links(&clone);
+    assert_eq!(clone.iter_atoms().collect::<String>(), "ACD");
+    check_same_eval(&clone, &expr, "ABCD");
+    assert!(expr.subtree(NodeId::new(1000)).is_none());
+}
+
+#[test]
+fn combine_with_empty_trees() {
+    let empty = BeTree::<BoolOperator, char>::new();
+    let expr = parse("A | B");
+    assert_eq!(expr.clone().combine(And, empty.clone()), expr);
+    assert_eq!(empty.clone().combine(And, expr.clone()), expr);
+    assert!(empty.clone().combine(And, empty).is_empty());
+}
+
+#[test]
+fn normalize_trees_built_differently() {
+    // built with the builder
+    let a = parse("(A | B) & !C");
+    // built then edited with a cursor
+    let mut b = parse("(X | B) & !C");
+    let mut cursor = b.cursor_mut();
+    cursor.move_to_left();
+    cursor.move_to_left();
+    cursor.replace_with_atom('A');
+    // built from parts
+    let mut c = parse("(A | B) & !(D & C)");
+    c.remove_atom(AtomId::new(2)).unwrap();
+    assert_ne!(a, b);
+    assert_ne!(a, c);
+    let a = a.normalize();
+    assert_eq!(a, b.normalize());
+    assert_eq!(a, c.normalize());
+    assert_eq!(a.nodes.len(), 3);
+    assert_eq!(a.atoms.len(), 3);
+    assert_eq!((a.head.index(), a.tail.index(), a.openness), (0, 0, 0));
+    assert_eq!(a.iter_atoms().collect::<String>(), "ABC");
+    // rotated
+    let mut d = parse("A & (B & C)");
+    d.rotate_left(d.head).unwrap();
+    assert_eq!(d.normalize(), parse("A & B & C").normalize());
+}
+
+#[test]
+fn normalize_degenerate_trees() {
+    let empty = BeTree::<BoolOperator, char>::new();
+    assert_eq!(empty.clone().normalize(), empty);
+    let mut a = parse("A & B");
+    a.remove_atom(AtomId::new(1)).unwrap();
+    let a = a.normalize();
+    check_links(&a);
+    assert_eq!(a, parse("A").normalize());
+    assert_eq!(a.nodes.len(), 1);
+    let mut b = parse("A & B");
+    b.prune(b.head).unwrap();
+    assert_eq!(b.normalize(), empty);
+}
+
+#[test]
+fn compacted_tree_can_be_extended() {
+    let mut expr = parse("(A | B) & !(C | D)");
+    expr.remove_atom(AtomId::new(2)).unwrap();
+    expr.compact();
+    check_links(&expr);
+    assert_eq!(expr.atoms.len(), 3);
+    expr.push_operator(Or);
+    expr.push_atom('C');
+    check_links(&expr);
+    check_same_eval(&expr, &parse("(A | B) & !D | C"), "ABCD");
+}
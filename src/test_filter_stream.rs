@@ -0,0 +1,86 @@
+//! tests of the filtering of streams of items
+
+use {super::*, crate::test_util::*};
+
+/// filter the assignments (sets of true atoms) with the expression
+fn filter<'t>(
+    expr: &'t BeTree<BoolOperator, char>,
+    assignments: &'t [Vec<char>],
+) -> impl Iterator<Item = &'t Vec<char>> + 't {
+    expr.filter_stream(
+        assignments,
+        |trues, c| trues.contains(c),
+        |op, a, b| op.eval(a, b),
+        |op, &a| op.short_circuit(a),
+    )
+}
+
+#[test]
+fn filter_stream_is_the_naive_loop() {
+    let assignments = all_assignments("ABCD");
+    for input in [
+        "A",
+        "A & B",
+        "(A | B) & !(C | D)",
+        "!!A | ((B & !C)) | D",
+        "!(A & (B | !(C & D)))",
+    ] {
+        let expr = parse(input);
+        let naive: Vec<&Vec<char>> = assignments
+            .iter()
+            .filter(|trues| eval(&expr, trues) == Some(true))
+            .collect();
+        let filtered: Vec<&Vec<char>> = filter(&expr, &assignments).collect();
+        assert_eq!(filtered, naive, "input: {:?}", input);
+    }
+}
+
+#[test]
+fn empty_and_incomplete_expressions_match_nothing() {
+    let assignments = all_assignments("AB");
+    for input in ["", "()", "!", "(!"] {
+        let expr = parse(input);
+        assert_eq!(filter(&expr, &assignments).count(), 0, "input: {:?}", input);
+    }
+}
+
+#[test]
+fn filter_stream_is_lazy() {
+    let expr = parse("A & (B | C)");
+    let evaluated = core::cell::RefCell::new(Vec::new());
+    let matches: Vec<usize> = expr
+        .filter_stream(
+            0..100,
+            |&n, c| {
+                evaluated.borrow_mut().push((n, *c));
+                match c {
+                    'A' => n % 2 == 0,
+                    'B' => n % 3 == 0,
+                    _ => n % 5 == 0,
+                }
+            },
+            |op, a, b| op.eval(a, b),
+            |op, &a| op.short_circuit(a),
+        )
+        .take(3)
+        .collect();
+    assert_eq!(matches, [0, 6, 10]);
+    let evaluated = evaluated.into_inner();
+    // items after the last match were never evaluated
+    assert_eq!(evaluated.iter().map(|&(n, _)| n).max(), Some(10));
+    // and evaluations were short-circuited
+    assert_eq!(
+        evaluated
+            .iter()
+            .filter(|&&(n, _)| n == 7)
+            .collect::<Vec<_>>(),
+        [&(7, 'A')]
+    );
+    assert_eq!(
+        evaluated
+            .iter()
+            .filter(|&&(n, _)| n == 6)
+            .collect::<Vec<_>>(),
+        [&(6, 'A'), &(6, 'B')]
+    );
+}
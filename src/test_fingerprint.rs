@@ -0,0 +1,66 @@
+//! tests of the stable fingerprints
+
+use {
+    super::*,
+    crate::test_util::{BoolOperator::*, *},
+};
+
+fn fingerprint(input: &str) -> u64 {
+    parse(input).fingerprint(
+        |op, fp| {
+            fp.write_u8(match op {
+                And => 1,
+                Or => 2,
+                Not => 3,
+            })
+        },
+        |&c, fp| fp.write_u32(c.into()),
+    )
+}
+
+#[test]
+fn fnv_test_vectors() {
+    let hash = |bytes: &[u8]| {
+        let mut fp = Fingerprinter::new();
+        fp.write(bytes);
+        fp.finish()
+    };
+    assert_eq!(hash(b""), 0xcbf29ce484222325);
+    assert_eq!(hash(b"a"), 0xaf63dc4c8601ec8c);
+    assert_eq!(hash(b"foobar"), 0x85944171f73967e8);
+}
+
+#[test]
+fn golden_fingerprints() {
+    // these values must never change
+    assert_eq!(fingerprint(""), 0xaf63bd4c8601b7df);
+    assert_eq!(fingerprint("A"), 0x76b31acc8b815fbd);
+    assert_eq!(fingerprint("A & B"), 0x4f2fe638254318da);
+    assert_eq!(fingerprint("(A | !B) & C"), 0xaf1f4b2c2db2abd2);
+}
+
+#[test]
+fn fingerprints_are_structural() {
+    // groups and representation don't matter
+    assert_eq!(fingerprint("((A)) & (B)"), fingerprint("A & B"));
+    let mut expr = parse("A & B & C");
+    let shrunk = {
+        let mut expr = expr.clone();
+        expr.shrink_to_fit();
+        expr
+    };
+    let fp = |expr: &BeTree<BoolOperator, char>| {
+        expr.fingerprint(
+            |op, fp| fp.write_str(&op.to_string()),
+            |&c, fp| fp.write_u32(c.into()),
+        )
+    };
+    assert_eq!(fp(&expr), fp(&shrunk));
+    expr.push_operator(Or);
+    assert_ne!(fp(&expr), fp(&shrunk));
+    // the structure matters
+    assert_ne!(fingerprint("A & (B & C)"), fingerprint("A & B & C"));
+    assert_ne!(fingerprint("A & B"), fingerprint("B & A"));
+    assert_ne!(fingerprint("A & B"), fingerprint("A | B"));
+    assert_ne!(fingerprint("!A"), fingerprint("A"));
+}
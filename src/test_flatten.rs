@@ -0,0 +1,96 @@
+//! tests of the flattening of expressions of expressions
+
+use {
+    super::*,
+    crate::test_util::{BoolOperator::*, *},
+};
+
+/// parse an expression whose atoms are expressions written
+/// between brackets
+fn parse_nested(input: &str) -> BeTree<BoolOperator, BeTree<BoolOperator, char>> {
+    let mut expr = BeTree::new();
+    let mut inner: Option<String> = None;
+    for c in input.chars() {
+        match (c, inner.as_mut()) {
+            (']', Some(s)) => {
+                expr.push_atom(parse(s));
+                inner = None;
+            }
+            (_, Some(s)) => s.push(c),
+            ('[', None) => inner = Some(String::new()),
+            ('&', None) => expr.push_operator(And),
+            ('|', None) => expr.push_operator(Or),
+            ('!', None) => expr.push_operator(Not),
+            ('(', None) => expr.open_par(),
+            (')', None) => expr.close_par(),
+            _ => {}
+        }
+    }
+    expr
+}
+
+fn eval_nested(
+    expr: &BeTree<BoolOperator, BeTree<BoolOperator, char>>,
+    trues: &[char],
+) -> Option<bool> {
+    expr.eval(
+        |inner| eval(inner, trues).unwrap(),
+        |op, a, b| op.eval(a, b),
+        |op, &a| op.short_circuit(a),
+    )
+}
+
+#[test]
+fn flatten_evaluates_as_nested() {
+    let inputs = [
+        "[A]",
+        "[A | B]",
+        "[A | B] & [C]",
+        "![A & B] | [!C]",
+        "([A] | [B & !C]) & !([D | E] & [A])",
+        "[A & (B | C)] & [(A | B) & C | D]",
+        "!![!!A]",
+    ];
+    for input in inputs {
+        let nested = parse_nested(input);
+        let flat = nested.clone().flatten();
+        check_links(&flat);
+        for trues in all_assignments("ABCDE") {
+            assert_eq!(
+                eval(&flat, &trues),
+                eval_nested(&nested, &trues),
+                "{}",
+                input
+            );
+        }
+    }
+}
+
+#[test]
+fn flatten_shared_inner_trees() {
+    let mut nested = parse_nested("[A | B] & ![] | [C]");
+    // the second atom is made to refer to the first inner tree
+    let (node_idx, side) = nested.atom_link(AtomId::new(1)).unwrap();
+    nested.nodes[node_idx].set_child(side, Child::Atom(AtomId::new(0)));
+    let flat = nested.clone().flatten();
+    check_links(&flat);
+    check_same_eval(&flat, &parse("(A | B) & !(A | B) | C"), "ABC");
+    assert_eq!(flat.op_count, 5);
+}
+
+#[test]
+fn flatten_empty_inner_trees() {
+    let cases = [
+        ("[A] & []", "A"),
+        ("[] | [B]", "B"),
+        ("([A] & ![]) | [B]", "A | B"),
+        ("[A] & ([] | []) & [C]", "A & C"),
+    ];
+    for (input, expected) in cases {
+        let flat = parse_nested(input).flatten();
+        check_links(&flat);
+        check_same_eval(&flat, &parse(expected), "ABC");
+    }
+    assert!(parse_nested("[]").flatten().is_empty());
+    assert!(parse_nested("![] & []").flatten().is_empty());
+}
@@ -0,0 +1,68 @@
+//! tests of the immutable shareable trees
+
+use {super::*, crate::test_util::*, std::thread};
+
+fn assert_send_sync<T: Send + Sync>() {}
+
+#[test]
+fn frozen_trees_are_send_and_sync() {
+    assert_send_sync::<FrozenBeTree<BoolOperator, char>>();
+    assert_send_sync::<FrozenBeTree<BoolOperator, char, u32>>();
+}
+
+#[test]
+fn frozen_trees_evaluate_like_trees() {
+    let expr = parse("(A | B) & !(C | A)");
+    let frozen = expr.clone().freeze();
+    for trues in all_assignments("ABC") {
+        assert_eq!(eval(&frozen, &trues), eval(&expr, &trues));
+    }
+    assert_eq!(frozen.atom_count(), 4);
+    assert_eq!(format!("{:?}", frozen), format!("{:?}", expr));
+}
+
+#[test]
+fn evaluation_from_several_threads() {
+    let expr = parse("A & (B | !C)");
+    let expected: Vec<_> = all_assignments("ABC")
+        .iter()
+        .map(|trues| eval(&expr, trues))
+        .collect();
+    let frozen = expr.freeze();
+    let handles: Vec<_> = (0..4)
+        .map(|_| {
+            let frozen = frozen.clone();
+            thread::spawn(move || {
+                all_assignments("ABC")
+                    .iter()
+                    .map(|trues| eval(&frozen, trues))
+                    .collect::<Vec<_>>()
+            })
+        })
+        .collect();
+    for handle in handles {
+        assert_eq!(handle.join().unwrap(), expected);
+    }
+    assert_eq!(frozen.handle_count(), 1);
+}
+
+#[test]
+fn thaw_gives_back_the_tree() {
+    let expr = parse("A & B");
+    // a unique handle gives back the tree itself
+    let frozen = expr.clone().freeze();
+    let mut thawed = frozen.thaw();
+    assert_eq!(thawed, expr);
+    thawed.push_operator(BoolOperator::Or);
+    thawed.push_atom('C');
+    assert_eq!(tokens_string(&thawed), "A&B|C");
+    // a shared one gives a clone, the other handles being unchanged
+    let frozen = expr.clone().freeze();
+    let other = frozen.clone();
+    assert_eq!(other.handle_count(), 2);
+    let mut thawed = frozen.thaw();
+    thawed.push_operator(BoolOperator::Or);
+    thawed.push_atom('C');
+    assert_eq!(*other, expr);
+    assert_eq!(other.handle_count(), 1);
+}
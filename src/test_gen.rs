@@ -0,0 +1,117 @@
+//! tests of the random generation of expressions
+
+use {
+    super::{gen::*, *},
+    crate::test_util::*,
+    rand::{rngs::StdRng, SeedableRng},
+};
+
+const OPS: [(BoolOperator, OpKind, f64); 3] = [
+    (BoolOperator::And, OpKind::Binary, 70.0),
+    (BoolOperator::Or, OpKind::Binary, 25.0),
+    (BoolOperator::Not, OpKind::Unary, 5.0),
+];
+
+fn generate_with_seed(seed: u64, config: GenConfig) -> BeTree<BoolOperator, usize> {
+    generate(StdRng::seed_from_u64(seed), config, |i| i, &OPS)
+}
+
+/// the maximal nesting of parenthesis in the tokens of the tree
+fn max_nesting(expr: &BeTree<BoolOperator, usize>) -> usize {
+    let mut nesting = 0;
+    let mut max = 0;
+    for token in expr.to_tokens() {
+        match token {
+            Token::OpeningParenthesis => {
+                nesting += 1;
+                max = max.max(nesting);
+            }
+            Token::ClosingParenthesis => nesting -= 1,
+            _ => {}
+        }
+    }
+    max
+}
+
+#[test]
+fn generation_is_deterministic() {
+    let config = GenConfig::default();
+    let a = generate_with_seed(42, config);
+    let b = generate_with_seed(42, config);
+    let c = generate_with_seed(43, config);
+    assert_eq!(a, b);
+    assert_ne!(a.to_tokens().len(), 0);
+    assert!(a != c);
+}
+
+#[test]
+fn generated_shape() {
+    let config = GenConfig {
+        atoms: 10_000,
+        mean_chain_length: 8.0,
+        max_nesting: 12,
+    };
+    let expr = generate_with_seed(7, config);
+    check_links(&expr);
+    let stats = expr.stats();
+    assert_eq!(stats.atom_count, 10_000);
+    assert_eq!(stats.binary_count, 9_999);
+    assert_eq!(stats.open_parentheses, 0);
+    let and_share = expr.count_operator(&BoolOperator::And) as f64 / 9_999.0;
+    assert!(
+        (and_share - 70.0 / 95.0).abs() < 0.05,
+        "and share: {}",
+        and_share
+    );
+    let unary_share = stats.unary_count as f64 / stats.operator_count as f64;
+    assert!(
+        (unary_share - 0.05).abs() < 0.01,
+        "unary share: {}",
+        unary_share
+    );
+    assert!(max_nesting(&expr) <= 12);
+    let groups = expr
+        .to_tokens()
+        .iter()
+        .filter(|token| matches!(token, Token::OpeningParenthesis))
+        .count();
+    let mean_chain_length = (stats.binary_count as f64) / (groups + 1) as f64 + 1.0;
+    assert!(
+        (mean_chain_length - 8.0).abs() < 1.0,
+        "mean chain length: {}",
+        mean_chain_length
+    );
+}
+
+#[test]
+fn generation_respects_nesting() {
+    for max in 0..4 {
+        let config = GenConfig {
+            atoms: 500,
+            mean_chain_length: 2.0,
+            max_nesting: max,
+        };
+        let expr = generate_with_seed(max as u64, config);
+        check_links(&expr);
+        assert_eq!(expr.stats().atom_count, 500);
+        assert_eq!(max_nesting(&expr), max);
+    }
+}
+
+#[test]
+fn generation_of_tiny_trees() {
+    for atoms in 0..40 {
+        let config = GenConfig {
+            atoms,
+            ..GenConfig::default()
+        };
+        let expr = generate_with_seed(1, config);
+        check_links(&expr);
+        assert_eq!(expr.stats().atom_count, atoms);
+        if atoms > 0 {
+            assert!(expr
+                .eval(|_| true, |op, a, b| op.eval(a, b), |_, _| false)
+                .is_some());
+        }
+    }
+}
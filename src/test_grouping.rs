@@ -0,0 +1,146 @@
+//! tests of the grouping report
+
+use {
+    super::*,
+    crate::test_util::{BoolOperator::*, *},
+};
+
+/// describe the joins of the first occurrence of the atom, from
+/// the nearest one, as the operator with the side of the atom and
+/// the sibling
+fn joins(expr: &BeTree<BoolOperator, char>, atom: u32) -> Vec<(BoolOperator, Side, Child)> {
+    let report = expr.grouping();
+    report
+        .atom(AtomId::new(atom))
+        .unwrap()
+        .joins()
+        .iter()
+        .map(|join| (*join.operator, join.side, join.sibling))
+        .collect()
+}
+
+#[test]
+fn ancestors_and_joins() {
+    let expr = parse("A & (B | !C)");
+    let report = expr.grouping();
+    let b = report.atom(AtomId::new(1)).unwrap();
+    assert_eq!(b.ancestors(), [NodeId::new(0), NodeId::new(1)]);
+    assert_eq!(b.group(), None);
+    let c = report.atom(AtomId::new(2)).unwrap();
+    assert_eq!(
+        c.ancestors(),
+        [NodeId::new(0), NodeId::new(1), NodeId::new(2)]
+    );
+    assert_eq!(
+        c.joins(),
+        [
+            AtomJoin {
+                node: NodeId::new(2),
+                operator: &Not,
+                unary: true,
+                side: Side::Left,
+                sibling: Child::None,
+            },
+            AtomJoin {
+                node: NodeId::new(1),
+                operator: &Or,
+                unary: false,
+                side: Side::Right,
+                sibling: Child::Atom(AtomId::new(1)),
+            },
+            AtomJoin {
+                node: NodeId::new(0),
+                operator: &And,
+                unary: false,
+                side: Side::Right,
+                sibling: Child::Atom(AtomId::new(0)),
+            },
+        ]
+    );
+    assert_eq!(
+        joins(&expr, 0),
+        [(And, Side::Left, Child::Node(NodeId::new(1)))]
+    );
+}
+
+#[test]
+fn groups_and_unary_operators() {
+    let expr = parse("!(A) | ((B))");
+    let report = expr.grouping();
+    let a = report.atom(AtomId::new(0)).unwrap();
+    let not = a.ancestors()[1];
+    assert_eq!(expr.node(not).unwrap().operator, Some(Not));
+    let group = a.group().unwrap();
+    assert_eq!(a.ancestors().last(), Some(&group));
+    // groups aren't joins
+    assert_eq!(
+        joins(&expr, 0),
+        [
+            (Not, Side::Left, Child::None),
+            (Or, Side::Left, Child::Node(NodeId::new(3))),
+        ]
+    );
+    // the nearest group of B is the inner one
+    let b = report.atom(AtomId::new(1)).unwrap();
+    assert_eq!(b.ancestors().len(), 3);
+    assert_eq!(b.group(), Some(b.ancestors()[2]));
+    assert_eq!(joins(&expr, 1), [(Or, Side::Right, Child::Node(not))]);
+}
+
+#[test]
+fn grouping_of_simplified_trees() {
+    let mut expr = parse("((A | B))");
+    assert_eq!(
+        expr.grouping()
+            .atom(AtomId::new(0))
+            .unwrap()
+            .ancestors()
+            .len(),
+        3
+    );
+    expr.simplify();
+    let report = expr.grouping();
+    for atom in 0..2 {
+        let grouping = report.atom(AtomId::new(atom)).unwrap();
+        // the wrapping groups were removed
+        assert_eq!(grouping.ancestors().len(), 1);
+        assert_eq!(grouping.ancestors()[0], expr.head);
+        assert_eq!(grouping.group(), None);
+        assert_eq!(grouping.joins().len(), 1);
+    }
+    // after simplification, the head may be a group, which isn't reported
+    let mut expr = parse("((A))");
+    expr.simplify();
+    let report = expr.grouping();
+    let a = report.atom(AtomId::new(0)).unwrap();
+    assert_eq!(a.ancestors(), [expr.head]);
+    assert_eq!(a.group(), None);
+    assert!(a.joins().is_empty());
+}
+
+#[test]
+fn unreachable_and_shared_atoms() {
+    let mut expr = parse("(A | B) & C");
+    expr.remove_atom(AtomId::new(1)).unwrap();
+    let report = expr.grouping();
+    assert!(report.atom(AtomId::new(1)).is_none());
+    assert!(report.occurrences(AtomId::new(1)).is_empty());
+    assert!(report.atom(AtomId::new(9)).is_none());
+    assert_eq!(report.occurrences(AtomId::new(2)).len(), 1);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn occurrences_of_deduplicated_atoms() {
+    let mut expr = parse("A & A | B");
+    expr.dedup_atoms();
+    let report = expr.grouping();
+    let occurrences = report.occurrences(AtomId::new(0));
+    assert_eq!(occurrences.len(), 2);
+    assert_eq!(occurrences[0].joins()[0].side, Side::Left);
+    assert_eq!(occurrences[1].joins()[0].side, Side::Right);
+    assert_eq!(
+        occurrences[0].joins()[0].sibling,
+        Child::Atom(AtomId::new(0))
+    );
+}
@@ -0,0 +1,85 @@
+//! tests of the binding of templates with named holes
+
+use {super::*, crate::test_util::*, std::collections::HashMap};
+
+/// parse a template where lowercase letters are named holes
+fn template(input: &str) -> BeTree<BoolOperator, Hole<char>> {
+    parse(input).map_atoms_indexed(|_, &c| {
+        if c.is_ascii_lowercase() {
+            Hole::Named(c.to_string())
+        } else {
+            Hole::Filled(c)
+        }
+    })
+}
+
+fn values(pairs: &[(&str, char)]) -> HashMap<String, char> {
+    pairs
+        .iter()
+        .map(|&(name, value)| (name.to_string(), value))
+        .collect()
+}
+
+#[test]
+fn successful_bind() {
+    let expr = template("(A | x) & !(y | D)");
+    assert_eq!(expr.hole_names(), vec!["x", "y"]);
+    let bound = expr.bind(&values(&[("x", 'B'), ("y", 'C')])).unwrap();
+    check_links(&bound);
+    assert_eq!(tokens_string(&bound), "A|B&!(C|D)");
+    assert_eq!(bound, parse("(A | B) & !(C | D)"));
+}
+
+#[test]
+fn repeated_holes() {
+    let expr = template("x & (y | !x) | x");
+    assert_eq!(expr.hole_names(), vec!["x", "y"]);
+    let bound = expr.bind(&values(&[("x", 'A'), ("y", 'B')])).unwrap();
+    assert_eq!(tokens_string(&bound), "A&(B|!A)|A");
+}
+
+#[test]
+fn missing_bindings() {
+    let expr = template("a & (B | c) & !d & a");
+    let err = expr
+        .clone()
+        .bind(&values(&[("c", 'C'), ("z", 'Z')]))
+        .unwrap_err();
+    assert_eq!(err.unbound, vec!["a", "d"]);
+    assert_eq!(err.unused, vec!["z"]);
+    assert_eq!(err.to_string(), "unbound holes: a, d; unused bindings: z");
+    // unused bindings are only an error when binding strictly
+    let all = values(&[("a", 'A'), ("c", 'C'), ("d", 'D'), ("z", 'Z')]);
+    assert!(expr.clone().bind(&all).is_ok());
+    let err = expr.clone().bind_strict(&all).unwrap_err();
+    assert!(err.unbound.is_empty());
+    assert_eq!(err.to_string(), "unused bindings: z");
+    let exact = values(&[("a", 'A'), ("c", 'C'), ("d", 'D')]);
+    assert_eq!(
+        tokens_string(&expr.bind_strict(&exact).unwrap()),
+        "A&(B|C)&!D&A"
+    );
+}
+
+#[test]
+fn bind_preserves_meta() {
+    let mut next = 0;
+    let expr = template("(A | x) & !(x | y)").map_meta(|_| {
+        next += 1;
+        next
+    });
+    let metas: Vec<Option<i32>> = (0..expr.nodes.len())
+        .map(|i| expr.node_meta(NodeId::from_index(i)).copied())
+        .collect();
+    let bound = expr.bind(&values(&[("x", 'B'), ("y", 'C')])).unwrap();
+    for (i, meta) in metas.into_iter().enumerate() {
+        assert_eq!(bound.node_meta(NodeId::from_index(i)).copied(), meta);
+    }
+}
+
+#[test]
+fn templates_without_holes() {
+    let expr = template("A & B");
+    assert!(expr.hole_names().is_empty());
+    assert_eq!(expr.bind(&HashMap::new()).unwrap(), parse("A & B"));
+}
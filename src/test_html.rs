@@ -0,0 +1,54 @@
+//! tests of the HTML rendering
+
+use {super::*, crate::test_util::*};
+
+fn render_op(op: &BoolOperator) -> String {
+    op.to_string()
+}
+
+#[test]
+fn html_escapes_atoms_and_operators() {
+    let expr = parse("A & !B").map_atoms_indexed(|_, c| match c {
+        'A' => "x < 3".to_string(),
+        _ => "\"R&D\"".to_string(),
+    });
+    assert_eq!(
+        expr.to_html(|s| s.clone(), render_op, HtmlOptions::default()),
+        "<span class=\"bet-atom\" data-atom-id=\"0\">x &lt; 3</span> \
+        <span class=\"bet-op\">&amp;</span> \
+        <span class=\"bet-op\">!</span>\
+        <span class=\"bet-atom\" data-atom-id=\"1\">&quot;R&amp;D&quot;</span>",
+    );
+}
+
+#[test]
+fn html_nests_groups() {
+    let expr = parse("A & (B | C)");
+    let options = HtmlOptions {
+        atom_class: "a".to_string(),
+        operator_class: "o".to_string(),
+        parenthesis_class: "p".to_string(),
+        group_class: "g".to_string(),
+    };
+    assert_eq!(
+        expr.to_html(|c| c.to_string(), render_op, options),
+        "<span class=\"a\" data-atom-id=\"0\">A</span> \
+        <span class=\"o\">&amp;</span> \
+        <span class=\"g\">\
+        <span class=\"p\">(</span>\
+        <span class=\"a\" data-atom-id=\"1\">B</span> \
+        <span class=\"o\">|</span> \
+        <span class=\"a\" data-atom-id=\"2\">C</span>\
+        <span class=\"p\">)</span>\
+        </span>",
+    );
+}
+
+#[test]
+fn html_of_empty_expression() {
+    let expr = parse("");
+    assert_eq!(
+        expr.to_html(|c| c.to_string(), render_op, HtmlOptions::default()),
+        ""
+    );
+}
@@ -0,0 +1,75 @@
+//! tests of the node and atom ids, and of the packing of nodes
+
+use {
+    crate::{test_util::*, *},
+    core::{convert::TryFrom, mem::size_of},
+};
+
+#[test]
+fn ids_are_32_bits() {
+    assert_eq!(size_of::<NodeId>(), 4);
+    assert_eq!(size_of::<AtomId>(), 4);
+    assert_eq!(size_of::<Option<NodeId>>(), 8);
+    assert_eq!(size_of::<Child>(), 8);
+}
+
+#[test]
+fn nodes_are_packed() {
+    // with usize ids and unpacked links, a node took 56 bytes
+    // on 64 bits targets
+    assert_eq!(size_of::<Node<BoolOperator>>(), 16);
+    assert_eq!(size_of::<Node<u8>>(), 16);
+    let mut node = Node::new(Some(BoolOperator::Not), true);
+    assert_eq!(node.parent(), None);
+    assert_eq!(node.left(), Child::None);
+    assert!(!node.is_full());
+    node.set_parent(Some(NodeId::new(MAX_ID)));
+    node.set_left(Child::Atom(AtomId::new(MAX_ID)));
+    node.set_right(Child::Node(NodeId::new(0)));
+    assert_eq!(node.parent(), Some(NodeId::new(MAX_ID)));
+    assert_eq!(node.left(), Child::Atom(AtomId::new(MAX_ID)));
+    assert_eq!(node.right(), Child::Node(NodeId::new(0)));
+    assert_eq!(node.operator(), Some(&BoolOperator::Not));
+    assert!(node.is_unary());
+    assert!(node.is_full());
+}
+
+#[test]
+fn id_conversions() {
+    let id = NodeId::new(7);
+    assert_eq!(id.index(), 7);
+    assert_eq!(u32::from(id), 7);
+    assert_eq!(usize::from(id), 7);
+    assert_eq!(NodeId::try_from(7u32), Ok(id));
+    assert_eq!(NodeId::try_from(7usize), Ok(id));
+    assert_eq!(id.to_string(), "7");
+    assert_eq!(AtomId::try_from(MAX_ID as usize), Ok(AtomId::new(MAX_ID)));
+    let mut atoms: Vec<char> = "AB".chars().collect();
+    atoms[AtomId::new(1)] = 'C';
+    assert_eq!(atoms[AtomId::new(1)], 'C');
+}
+
+#[test]
+fn id_overflow() {
+    assert_eq!(NodeId::try_from(MAX_ID + 1), Err(IdOverflow));
+    assert_eq!(AtomId::try_from(MAX_ID as usize + 1), Err(IdOverflow));
+    assert_eq!(AtomId::try_from(u32::MAX), Err(IdOverflow));
+    assert!(std::panic::catch_unwind(|| NodeId::new(MAX_ID + 1)).is_err());
+}
+
+#[cfg(target_pointer_width = "64")]
+#[test]
+fn id_overflow_beyond_u32() {
+    let too_big = u32::MAX as usize + 1;
+    assert_eq!(NodeId::try_from(too_big), Err(IdOverflow));
+    assert_eq!(AtomId::try_from(too_big), Err(IdOverflow));
+}
+
+#[test]
+fn try_push_builds_like_push() {
+    let mut expr = BeTree::new();
+    for token in parse("(A | B) & !C").to_tokens() {
+        expr.try_push(token).unwrap();
+    }
+    assert!(expr.structural_eq(&parse("(A | B) & !C")));
+}
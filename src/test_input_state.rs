@@ -0,0 +1,96 @@
+//! tests of the input state of trees being built
+
+use {
+    super::*,
+    crate::test_util::{BoolOperator::*, *},
+};
+
+/// the expected state, with the accepted tokens given as a string
+/// of `a` (atom), `u` (unary operator), `b` (binary operator), `(` and `)`
+fn state(
+    accepted: &str,
+    depth: usize,
+    in_atom: bool,
+    pending_operator: Option<BoolOperator>,
+) -> InputState<BoolOperator> {
+    InputState {
+        accepts_atom: accepted.contains('a'),
+        accepts_unary_operator: accepted.contains('u'),
+        accepts_binary_operator: accepted.contains('b'),
+        accepts_opening_par: accepted.contains('('),
+        accepts_closing_par: accepted.contains(')'),
+        depth,
+        in_atom,
+        pending_operator,
+    }
+}
+
+#[test]
+fn input_state_after_each_push() {
+    let mut expr = BeTree::new();
+    assert_eq!(expr.input_state(), state("au(", 0, false, None));
+    expr.open_par();
+    assert_eq!(expr.input_state(), state("au(", 1, false, None));
+    expr.push_atom('A');
+    assert_eq!(expr.input_state(), state("b)", 1, true, None));
+    expr.push_operator(And);
+    assert_eq!(expr.input_state(), state("au(", 1, false, Some(And)));
+    expr.push_operator(Not);
+    assert_eq!(expr.input_state(), state("au(", 1, false, Some(Not)));
+    expr.open_par();
+    assert_eq!(expr.input_state(), state("au(", 2, false, None));
+    expr.push_atom('B');
+    assert_eq!(expr.input_state(), state("b)", 2, true, None));
+    expr.close_par();
+    assert_eq!(expr.input_state(), state("b)", 1, false, None));
+    expr.close_par();
+    assert_eq!(expr.input_state(), state("b", 0, false, None));
+    expr.push_operator(Or);
+    assert_eq!(expr.input_state(), state("au(", 0, false, Some(Or)));
+    expr.push_atom('C');
+    assert_eq!(expr.input_state(), state("b", 0, true, None));
+    assert_eq!(tokens_string(&expr), "A&!(B)|C");
+}
+
+#[test]
+fn input_state_after_unmatched_closing_par() {
+    let mut expr = parse("A)");
+    assert_eq!(expr.input_state(), state("b", 0, false, None));
+    expr.close_par();
+    assert_eq!(expr.input_state(), state("b", 0, false, None));
+    expr.push_operator(And);
+    assert_eq!(expr.input_state(), state("au(", 0, false, Some(And)));
+    expr.push_atom('B');
+    assert_eq!(expr.input_state(), state("b", 0, true, None));
+}
+
+#[test]
+fn current_atom_mut_only_after_an_atom() {
+    let mut expr = BeTree::new();
+    assert_eq!(expr.current_atom_mut(), None);
+    expr.open_par();
+    assert_eq!(expr.current_atom_mut(), None);
+    expr.push_atom('a');
+    if let Some(atom) = expr.current_atom_mut() {
+        *atom = atom.to_ascii_uppercase();
+    }
+    assert_eq!(expr.current_atom(), Some(&'A'));
+    assert_eq!(expr.input_state(), state("b)", 1, true, None));
+    expr.push_operator(And);
+    assert_eq!(expr.current_atom_mut(), None);
+    expr.push_operator(Not);
+    assert_eq!(expr.current_atom_mut(), None);
+    expr.push_atom('b');
+    *expr.current_atom_mut().unwrap() = 'B';
+    expr.close_par();
+    assert_eq!(expr.current_atom_mut(), None);
+    assert_eq!(expr.input_state(), state("b", 0, false, None));
+    expr.push_operator(Or);
+    expr.push_atom('C');
+    assert_eq!(expr.current_atom_mut(), Some(&mut 'C'));
+    // the atom is still the current one, so it's mutated, not created
+    *expr.mutate_or_create_atom(|| 'D') = 'E';
+    assert_eq!(expr.input_state(), state("b", 0, true, None));
+    assert_eq!(tokens_string(&expr), "A&!B|E");
+    check_links(&expr);
+}
@@ -0,0 +1,116 @@
+//! tests of the insertion context of trees being built
+
+use {super::*, crate::test_util::*};
+
+/// describe the insertion context as `operator side sibling enclosing`,
+/// with `()` for a group, `-` for no side, the atoms of the sibling,
+/// and the enclosing operators from the head
+fn describe(expr: &BeTree<BoolOperator, char>) -> String {
+    let context = expr.insertion_context();
+    assert_eq!(context.node(), expr.tail);
+    let operator = match context.operator() {
+        Some((op, _)) => op.to_string(),
+        None => "()".to_string(),
+    };
+    let side = match context.side() {
+        Some(Side::Left) => "L",
+        Some(Side::Right) => "R",
+        None => "-",
+    };
+    let sibling: String = context
+        .sibling()
+        .map(|sibling| sibling.iter_atoms().map(|(_, c)| *c).collect())
+        .unwrap_or_default();
+    let enclosing: String = context
+        .enclosing_operators()
+        .map(|(op, _)| op.to_string())
+        .collect();
+    assert_eq!(context.ancestors().len(), {
+        let mut count = 0;
+        let mut node = expr.nodes[expr.tail].parent();
+        while let Some(parent) = node {
+            count += 1;
+            node = expr.nodes[parent].parent();
+        }
+        count
+    });
+    [operator.as_str(), side, &sibling, &enclosing]
+        .iter()
+        .filter(|part| !part.is_empty())
+        .copied()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// check the description of the insertion context after each token
+fn check(input: &str, expected: &[&str]) {
+    let tokens: Vec<char> = input.chars().filter(|&c| c != ' ').collect();
+    assert_eq!(tokens.len(), expected.len(), "{:?}", input);
+    for (i, expected) in expected.iter().enumerate() {
+        let prefix: String = tokens[..=i].iter().collect();
+        let expr = parse(&prefix);
+        assert_eq!(&describe(&expr), expected, "after {:?}", prefix);
+    }
+}
+
+#[test]
+fn empty_tree() {
+    assert_eq!(describe(&BeTree::new()), "() L");
+}
+
+#[test]
+fn atoms_and_groups() {
+    check("T", &["() -"]);
+    check(
+        "(((T)))",
+        &["() L", "() L", "() L", "() -", "() -", "() -", "() -"],
+    );
+}
+
+#[test]
+fn unary_operators() {
+    check("!T", &["! L", "! -"]);
+    check("!!F", &["! L", "! L !", "! - !"]);
+    check(
+        "!(F & T)",
+        &["! L", "() L !", "() - !", "& R F !", "& - !", "! -"],
+    );
+}
+
+#[test]
+fn binary_operators() {
+    check("F | T", &["() -", "| R F", "| -"]);
+    check("F | !T", &["() -", "| R F", "! L |", "! - |"]);
+    check("F | F & F", &["() -", "| R F", "| -", "& R FF", "& -"]);
+    check("!!F & F", &["! L", "! L !", "! - !", "& R F", "& -"]);
+    // the binary operator takes the operation whose unary operand is complete
+    check(
+        "T & !F | F",
+        &["() -", "& R T", "! L &", "! - &", "| R TF", "| -"],
+    );
+    check(
+        "T & T | T & F",
+        &["() -", "& R T", "& -", "| R TT", "| -", "& R TTT", "& -"],
+    );
+}
+
+#[test]
+fn nested_groups() {
+    check(
+        "T & (T & F)",
+        &[
+            "() -", "& R T", "() L &", "() - &", "& R T &", "& - &", "& -",
+        ],
+    );
+    check(
+        "(T | F) & !T",
+        &[
+            "() L", "() -", "| R T", "| -", "() -", "& R TF", "! L &", "! - &",
+        ],
+    );
+}
+
+#[test]
+fn unmatched_closing_par() {
+    check("!T)", &["! L", "! -", "() -"]);
+}
@@ -0,0 +1,67 @@
+//! tests of the interning of string atoms
+
+use crate::{test_util::*, *};
+
+/// parse an expression whose atoms are words
+fn parse_words(input: &str) -> BeTree<BoolOperator, String> {
+    parse(input).into_map_atoms(|c| match c {
+        'A' => "ext=rs".to_string(),
+        'B' => "ext=toml".to_string(),
+        'C' => "size>10k".to_string(),
+        _ => c.to_string(),
+    })
+}
+
+#[test]
+fn round_trip_through_pool() {
+    let mut pool = InternedAtoms::new();
+    assert!(pool.is_empty());
+    let words = parse_words("(A | B) & !C & D");
+    let interned = words.clone().intern_atoms(&mut pool);
+    assert_eq!(pool.len(), 4);
+    assert_eq!(pool.get("ext=toml").map(Symbol::index), Some(1));
+    assert_eq!(pool.get("ext=json"), None);
+    assert_eq!(
+        pool.resolve(*interned.atom(AtomId::new(2)).unwrap()),
+        "size>10k"
+    );
+    assert_eq!(interned.nodes, parse("(A | B) & !C & D").nodes);
+    check_links(&interned);
+    let resolved = interned.resolve_atoms(&pool);
+    assert_eq!(resolved, words);
+}
+
+#[test]
+fn trees_share_one_pool() {
+    let mut pool = InternedAtoms::new();
+    let filters: Vec<_> = (0..1000)
+        .map(|i| {
+            let input = if i % 2 == 0 { "A & !C" } else { "(A | B) & C" };
+            parse_words(input).intern_atoms(&mut pool)
+        })
+        .collect();
+    // each distinct string is stored once
+    assert_eq!(pool.len(), 3);
+    assert_eq!(core::mem::size_of::<Symbol>(), 4);
+    let ext_rs = pool.intern("ext=rs");
+    assert_eq!(pool.len(), 3);
+    assert!(filters
+        .iter()
+        .all(|f| f.iter_atoms().next() == Some(&ext_rs)));
+    // atoms are evaluated with the pool captured in the closure
+    let size = pool.get("size>10k").unwrap();
+    let trues = [ext_rs, size];
+    let matching = filters
+        .iter()
+        .filter(|f| {
+            f.eval(
+                |symbol| trues.contains(symbol),
+                |op, a, b| op.eval(a, b),
+                |op, &a| op.short_circuit(a),
+            ) == Some(true)
+        })
+        .count();
+    assert_eq!(matching, 500);
+    let matching_names = filters[1].clone().resolve_atoms(&pool);
+    assert_eq!(matching_names, parse_words("(A | B) & C"));
+}
@@ -0,0 +1,321 @@
+//! tests of the iterators
+
+use {
+    super::*,
+    crate::test_util::{BoolOperator::*, *},
+};
+
+#[test]
+fn iter_nodes_of_doc_example() {
+    let expr = parse("(A | B) & !(C | D | E)");
+    let nodes: Vec<_> = expr
+        .iter_nodes()
+        .map(|(id, node)| (id.index(), node.operator))
+        .collect();
+    assert_eq!(
+        nodes,
+        vec![
+            (0, Some(And)),
+            (1, Some(Or)),
+            (2, Some(Not)),
+            (4, Some(Or)),
+            (3, Some(Or)),
+        ]
+    );
+}
+
+#[test]
+fn iter_nodes_skips_orphans() {
+    // removing B makes the (A | B) node useless
+    let mut expr = parse("(A | B) & !C");
+    expr.remove_atom(AtomId::new(1)).unwrap();
+    let ids: Vec<_> = expr.iter_nodes().map(|(id, _)| id.index()).collect();
+    assert_eq!(ids, vec![0, 2]);
+    // the double negation nodes are bypassed
+    let mut expr = parse("!!(A & B) | C");
+    assert_eq!(expr.simplify_involutive(&Not), 1);
+    let operators: Vec<_> = expr.iter_nodes().map(|(_, node)| node.operator).collect();
+    assert_eq!(operators, vec![Some(Or), Some(And)]);
+    // the nodes of a rebalanced chain without its groups
+    let mut expr = parse("A | (B | (C | D))");
+    expr.rebalance_chains(&Or);
+    assert_eq!(expr.iter_nodes().count(), 3);
+    for (id, _) in expr.iter_nodes() {
+        assert!(expr.is_reachable(id));
+    }
+}
+
+/// render the expression from its infix visit
+fn render_infix(expr: &BeTree<BoolOperator, char>) -> String {
+    expr.iter_infix()
+        .map(|visit| match visit {
+            Visit::OpenGroup(_) => '(',
+            Visit::Atom(_, &c) => c,
+            Visit::Operator(_, op, _) => match op {
+                And => '&',
+                Or => '|',
+                Not => '!',
+            },
+            Visit::CloseGroup(_) => ')',
+        })
+        .collect()
+}
+
+#[test]
+fn iter_infix_renderings() {
+    let cases = [
+        ("A", "A"),
+        ("((A))", "A"),
+        ("!(A)", "!A"),
+        ("(A | B) & !(C | D | E)", "A|B&!(C|D|E)"),
+        ("A & (B | C)", "A&(B|C)"),
+        ("(A & B) | (C & D)", "A&B|(C&D)"),
+        ("!!A & B", "!!A&B"),
+        ("A & !(B | !C) | D", "A&!(B|!C)|D"),
+        ("", ""),
+    ];
+    for (input, rendering) in cases {
+        let expr = parse(input);
+        assert_eq!(render_infix(&expr), rendering);
+        // the rendering builds the same expression
+        check_same_eval(&parse(rendering), &expr, "ABCDE");
+    }
+}
+
+#[test]
+fn iter_infix_ids() {
+    let expr = parse("A & !(B | C)");
+    for visit in expr.iter_infix() {
+        match visit {
+            Visit::OpenGroup(id) | Visit::CloseGroup(id) => {
+                assert_eq!(expr.node(id).unwrap().operator, Some(Or));
+            }
+            Visit::Atom(id, atom) => assert_eq!(expr.atom(id), Some(atom)),
+            Visit::Operator(id, op, unary) => {
+                let node = expr.node(id).unwrap();
+                assert_eq!(node.operator.as_ref(), Some(op));
+                assert_eq!(node.unary, unary);
+            }
+        }
+    }
+    assert_eq!(expr.iter_infix().count(), 8);
+}
+
+#[test]
+fn iter_nodes_from_both_ends() {
+    let expr = parse("(A | B) & !(C | D | E)");
+    let forward: Vec<_> = expr.iter_nodes().map(|(id, _)| id.index()).collect();
+    assert_eq!(forward, vec![0, 1, 2, 4, 3]);
+    let backward: Vec<_> = expr.iter_nodes().rev().map(|(id, _)| id.index()).collect();
+    assert_eq!(backward, vec![3, 4, 2, 1, 0]);
+    // alternating ends, without overlap
+    let mut iter = expr.iter_nodes();
+    assert_eq!(iter.next().map(|(id, _)| id.index()), Some(0));
+    assert_eq!(iter.next_back().map(|(id, _)| id.index()), Some(3));
+    assert_eq!(iter.next().map(|(id, _)| id.index()), Some(1));
+    assert_eq!(iter.next_back().map(|(id, _)| id.index()), Some(4));
+    assert_eq!(iter.next().map(|(id, _)| id.index()), Some(2));
+    assert!(iter.next_back().is_none());
+    assert!(iter.next().is_none());
+}
+
+#[test]
+fn atoms_in_reverse_tree_order() {
+    let cases = [
+        ("A", "A"),
+        ("(A | B) & !(C | D | E)", "EDCBA"),
+        ("!A & (!(B | !C) | !!D)", "DCBA"),
+        ("A & !(B & !(C | (D & E))) | F", "FEDCBA"),
+    ];
+    for (input, reversed) in cases {
+        let expr = parse(input);
+        assert_eq!(
+            expr.iter_atoms_rev_in_tree_order().collect::<String>(),
+            reversed
+        );
+    }
+    // after a replacement, the arena isn't in tree order anymore
+    let mut expr = parse("A & M & C");
+    expr.replace_atoms_with_trees(|&c| {
+        if c == 'M' {
+            Some(parse("B | !D"))
+        } else {
+            None
+        }
+    });
+    assert_eq!(expr.iter_atoms().rev().collect::<String>(), "DBCMA");
+    assert_eq!(
+        expr.iter_atoms_rev_in_tree_order().collect::<String>(),
+        "CDBA"
+    );
+}
+
+fn render_levels(expr: &BeTree<BoolOperator, char>) -> Vec<(usize, String)> {
+    expr.iter_levels()
+        .map(|(depth, element)| {
+            let s = match element {
+                NodeOrAtomRef::Node(_, node) => match node.operator {
+                    Some(op) => format!("{:?}", op),
+                    None => "()".to_string(),
+                },
+                NodeOrAtomRef::Atom(_, atom) => atom.to_string(),
+            };
+            (depth, s)
+        })
+        .collect()
+}
+
+#[test]
+fn levels_of_nested_expression() {
+    let expr = parse("A & !(B | C) | D");
+    let expected = [
+        (0, "Or"),
+        (1, "And"),
+        (1, "D"),
+        (2, "A"),
+        (2, "Not"),
+        (3, "Or"),
+        (4, "B"),
+        (4, "C"),
+    ];
+    let expected: Vec<_> = expected
+        .iter()
+        .map(|&(depth, s)| (depth, s.to_string()))
+        .collect();
+    assert_eq!(render_levels(&expr), expected);
+    assert_eq!(expr.depth(), 4);
+}
+
+#[test]
+fn depth_of_left_leaning_chain() {
+    let input = (0..100).map(|_| "A").collect::<Vec<_>>().join(" | ");
+    let expr = parse(&input);
+    assert_eq!(expr.depth(), 99);
+    assert_eq!(parse("A").depth(), 1);
+    assert_eq!(BeTree::<BoolOperator, char>::new().depth(), 0);
+}
+
+#[test]
+fn atoms_in_tree_after_grafting() {
+    let mut expr = parse("A & C");
+    let mut cursor = expr.cursor_mut();
+    assert!(cursor.move_to_left());
+    cursor.insert_binary_above(BoolOperator::Or, Side::Right, parse("B"));
+    assert_eq!(expr.iter_atoms().collect::<String>(), "ACB");
+    assert_eq!(
+        expr.iter_atoms_in_tree()
+            .map(|(_, &c)| c)
+            .collect::<String>(),
+        "ABC"
+    );
+    let ids: Vec<usize> = expr
+        .iter_atoms_in_tree()
+        .map(|(id, _)| id.index())
+        .collect();
+    assert_eq!(ids, vec![0, 2, 1]);
+    // replaced atoms stay in the arena but aren't in the tree
+    let mut expr = parse("A & M & !D");
+    expr.replace_atoms_with_trees(|&c| if c == 'M' { Some(parse("B | C")) } else { None });
+    assert_eq!(expr.iter_atoms().collect::<String>(), "AMDBC");
+    assert_eq!(
+        expr.iter_atoms_in_tree()
+            .map(|(_, &c)| c)
+            .collect::<String>(),
+        "ABCD"
+    );
+}
+
+fn reachable_atoms(expr: &BeTree<BoolOperator, char>) -> String {
+    let reachable: String = expr.iter_reachable_atoms().collect();
+    assert_eq!(
+        expr.clone()
+            .into_reachable_atoms()
+            .into_iter()
+            .collect::<String>(),
+        reachable
+    );
+    reachable
+}
+
+#[test]
+fn reachable_atoms_after_edits() {
+    let mut expr = parse("A & (B | C) & D");
+    assert_eq!(reachable_atoms(&expr), "ABCD");
+    // removing an atom leaves it in the arena
+    expr.remove_atom(AtomId::new(1)).unwrap();
+    assert_eq!(reachable_atoms(&expr), "ACD");
+    assert_eq!(expr.iter_atoms().collect::<String>(), "ABCD");
+    // as does pruning a branch
+    let mut expr = parse("(A | B) & !(C | D)");
+    let not = expr
+        .iter_nodes()
+        .find(|(_, node)| node.operator == Some(Not))
+        .map(|(id, _)| id)
+        .unwrap();
+    expr.prune(not).unwrap();
+    assert_eq!(reachable_atoms(&expr), "AB");
+    assert_eq!(expr.clone().atoms(), ['A', 'B', 'C', 'D']);
+    // replaced atoms are orphaned, the new ones are given in tree order
+    let mut expr = parse("A & M & !D");
+    expr.replace_atoms_with_trees(|&c| if c == 'M' { Some(parse("B | C")) } else { None });
+    assert_eq!(reachable_atoms(&expr), "ABCD");
+    assert_eq!(expr.iter_atoms().collect::<String>(), "AMDBC");
+    // an atom used several times is given once
+    #[cfg(feature = "std")]
+    {
+        let mut expr = parse("A & B | A & C");
+        expr.dedup_atoms();
+        assert_eq!(expr.iter_atoms_in_tree().count(), 4);
+        assert_eq!(reachable_atoms(&expr), "ABC");
+    }
+}
+
+fn render_operators(expr: &BeTree<BoolOperator, char>) -> Vec<String> {
+    expr.iter_operators()
+        .map(|op| {
+            format!(
+                "{}{:?}{}",
+                op.node,
+                op.operator,
+                if op.complete { "" } else { "..." },
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn operators_in_pre_order() {
+    let expr = parse("(A | B) & !(C | D | E)");
+    assert_eq!(
+        render_operators(&expr),
+        vec!["0And", "1Or", "2Not", "4Or", "3Or"]
+    );
+    let unary: Vec<_> = expr.iter_operators().map(|op| op.unary).collect();
+    assert_eq!(unary, vec![false, false, true, false, false]);
+    // an incomplete expression
+    let expr = parse("A & !(B |");
+    assert_eq!(render_operators(&expr), vec!["0And", "1Not", "2Or..."]);
+    assert!(parse("((A))").iter_operators().next().is_none());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn operators_after_edits() {
+    let mut expr = parse("(A | B) & !(C | D | E)");
+    expr.prune(NodeId::new(2)).unwrap();
+    assert_eq!(render_operators(&expr), vec!["1Or"]);
+    let mut expr = parse("A & M & !C");
+    expr.replace_atoms_with_trees(|&c| {
+        if c == 'M' {
+            Some(parse("!B | D"))
+        } else {
+            None
+        }
+    });
+    assert_eq!(expr.iter_operators().count(), 5);
+    assert_eq!(expr.iter_operators().filter(|op| op.unary).count(), 2);
+    let mut expr = parse("!!(A | A) & B");
+    let rules = [RewriteRule::new(parse_pattern("!!x"), parse_pattern("x")).unwrap()];
+    expr.rewrite(&rules, 10);
+    assert_eq!(render_operators(&expr), vec!["0And", "4Or"]); // the operand was copied
+}
@@ -0,0 +1,187 @@
+//! tests of the JsonLogic export and import
+
+use {
+    super::*,
+    crate::test_util::*,
+    serde_json::{json, Value},
+};
+
+fn op_name(op: &BoolOperator) -> &str {
+    match op {
+        BoolOperator::And => "and",
+        BoolOperator::Or => "or",
+        BoolOperator::Not => "!",
+    }
+}
+
+fn parse_op(name: &str) -> Option<BoolOperator> {
+    match name {
+        "and" => Some(BoolOperator::And),
+        "or" => Some(BoolOperator::Or),
+        "!" => Some(BoolOperator::Not),
+        _ => None,
+    }
+}
+
+fn var(c: &char) -> Value {
+    json!({ "var": c.to_ascii_lowercase().to_string() })
+}
+
+fn parse_var(value: &Value) -> Option<char> {
+    let name = value.get("var")?.as_str()?;
+    let mut chars = name.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Some(c.to_ascii_uppercase()),
+        _ => None,
+    }
+}
+
+/// accept as atoms the comparisons, the variables and the literals
+fn parse_condition(value: &Value) -> Option<Value> {
+    match value {
+        Value::Object(object) if object.len() == 1 => {
+            let key = object.keys().next()?;
+            ["var", "==", "!=", "<", "<=", ">", ">=", "in"]
+                .contains(&key.as_str())
+                .then(|| value.clone())
+        }
+        Value::Bool(_) | Value::Number(_) | Value::String(_) => Some(value.clone()),
+        _ => None,
+    }
+}
+
+fn export(input: &str) -> Value {
+    parse(input).to_jsonlogic(op_name, var)
+}
+
+fn import(value: &Value) -> Result<BeTree<BoolOperator, char>, JsonLogicError> {
+    BeTree::from_jsonlogic(value, parse_op, parse_var)
+}
+
+#[test]
+fn export_flattens_chains() {
+    assert_eq!(export("A"), json!({"var": "a"}));
+    assert_eq!(
+        export("A & !B"),
+        json!({"and": [{"var": "a"}, {"!": {"var": "b"}}]})
+    );
+    assert_eq!(
+        export("(A | B) & !C & D"),
+        json!({"and": [
+            {"or": [{"var": "a"}, {"var": "b"}]},
+            {"!": {"var": "c"}},
+            {"var": "d"},
+        ]})
+    );
+    // only left chains are flattened, right ones being nested
+    assert_eq!(
+        export("((A | B)) | (C | D)"),
+        json!({"or": [
+            {"var": "a"},
+            {"var": "b"},
+            {"or": [{"var": "c"}, {"var": "d"}]},
+        ]})
+    );
+    assert_eq!(export(""), Value::Null);
+}
+
+#[test]
+fn import_builds_left_to_right_chains() {
+    let expr = import(&json!({"and": [
+        {"var": "a"},
+        {"or": [{"var": "b"}, {"!": [{"var": "c"}]}, {"var": "d"}]},
+        {"!": {"!": {"var": "e"}}},
+    ]}))
+    .unwrap();
+    check_links(&expr);
+    assert_eq!(tokens_string(&expr), "A&(B|!C|D)&!!E");
+    check_same_eval(&expr, &parse("A & (B | !C | D) & !!E"), "ABCDE");
+    // a binary operation with a single operand is this operand
+    let expr = import(&json!({"or": [{"var": "a"}]})).unwrap();
+    assert_eq!(tokens_string(&expr), "A");
+    assert!(import(&Value::Null).unwrap().is_empty());
+}
+
+#[test]
+fn round_trips() {
+    for input in [
+        "A",
+        "!A",
+        "A & B & C",
+        "(A | !B) & !(C | D | E)",
+        "A & (B & (C | D)) | !(!E)",
+    ] {
+        let expr = parse(input);
+        let json = expr.to_jsonlogic(op_name, var);
+        let imported = import(&json).unwrap();
+        assert_eq!(imported.to_jsonlogic(op_name, var), json);
+        check_same_eval(&imported, &expr, "ABCDE");
+    }
+}
+
+#[test]
+fn reference_documents() {
+    // documents of the JsonLogic reference, with comparisons as atoms
+    let documents = [
+        json!({"and": [
+            {"<": [{"var": "temp"}, 110]},
+            {"==": [{"var": "pie.filling"}, "apple"]},
+        ]}),
+        json!({"or": [
+            {"==": [{"var": "a"}, 1]},
+            {"and": [{"in": ["Spring", {"var": "name"}]}, {">=": [{"var": "age"}, 18]}]},
+            {"!": {"var": "banned"}},
+        ]}),
+        json!({"!": {"or": [true, {"var": "x"}, false]}}),
+    ];
+    for document in documents {
+        let expr: BeTree<BoolOperator, Value> =
+            BeTree::from_jsonlogic(&document, parse_op, parse_condition).unwrap();
+        assert_eq!(expr.to_jsonlogic(op_name, Value::clone), document);
+    }
+}
+
+#[test]
+fn positioned_errors() {
+    let err =
+        import(&json!({"and": [{"var": "a"}, {"xor": [{"var": "b"}, {"var": "c"}]}]})).unwrap_err();
+    assert_eq!(
+        err,
+        JsonLogicError::UnknownOperator {
+            path: "/and/1".to_string(),
+            operator: "xor".to_string(),
+        }
+    );
+    assert_eq!(err.to_string(), "unknown operator \"xor\" at /and/1");
+    let err = import(&json!({"or": [{"!": 5}, {"var": "a"}]})).unwrap_err();
+    assert_eq!(err.path(), "/or/0/!");
+    assert_eq!(
+        err.to_string(),
+        "neither an atom nor an operation at /or/0/!"
+    );
+    let err = import(&json!({"!": [{"var": "a"}, {"var": "b"}]})).unwrap_err();
+    assert_eq!(
+        err,
+        JsonLogicError::WrongOperandCount {
+            path: String::new(),
+            count: 2,
+        }
+    );
+    assert_eq!(err.to_string(), "wrong number of operands (2) at the root");
+    let err = import(&json!({"and": [{"var": "a"}, {"or": []}]})).unwrap_err();
+    assert_eq!(err.path(), "/and/1");
+    let err = import(&json!({"and": [{"var": "a"}], "or": [{"var": "b"}]})).unwrap_err();
+    assert_eq!(
+        err,
+        JsonLogicError::NotAnOperation {
+            path: String::new()
+        }
+    );
+    let err = import(&json!([{"var": "a"}])).unwrap_err();
+    assert_eq!(
+        err,
+        JsonLogicError::NotAnOperation {
+            path: String::new()
+        }
+    );
+}
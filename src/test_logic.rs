@@ -0,0 +1,413 @@
+//! tests of the boolean expressions of the `logic` module, with the
+//! checks of `test_bool`
+
+use crate::{logic::*, test_util::*, *};
+
+fn check(input: &str, expected: bool) {
+    let expr = parse_bool_expr(input, |s| match s {
+        "T" => Ok(true),
+        "F" => Ok(false),
+        _ => Err("not a boolean"),
+    })
+    .unwrap();
+    assert_eq!(eval_bool(&expr, |&b| b), Some(expected), "in {:?}", input);
+}
+
+#[test]
+fn test_bool() {
+    check("T", true);
+    check("(((T)))", true);
+    check("F", false);
+    check("!T", false);
+    check("!F", true);
+    check("!!F", false);
+    check("!!!F", true);
+    check("F | T", true);
+    check("F & T", false);
+    check("F | !T", false);
+    check("!F | !T", true);
+    check("!(F & T)", true);
+    check("!(T | T)", false);
+    check("T | !(T | T)", true);
+    check("T & (T & F)", false);
+    check("!F & !(T & F & T)", true);
+    check("!((T|F)&T)", false);
+    check("!(!((T|F)&(F|T)&T)) & !F & (T | (T|F))", true);
+    check("(T | F) & !T", false);
+    check("!(T | F | T)", false);
+    check("(T | F) & !(T | F | T)", false);
+    check("F | !T | !(T & T | F)", false);
+    check("(T & T) | (T & F)", true);
+    check("T & T | T & F", false);
+}
+
+#[test]
+fn issue_2() {
+    check("F | F | F", false);
+    check("F | F | F | F", false);
+    check("F | T | F", true);
+    check("F | T | F | F", true);
+    check("F | F & F", false);
+    check("F | F & F | F", false);
+    check("F | T & F", false);
+    check("F | T & F | F", false);
+    check("F | F | T & F", false);
+}
+
+#[test]
+fn unary_operand_then_binary_operator() {
+    check("!!F & F", false);
+    check("!!T | F", true);
+    check("!!!T | T", true);
+    check("T & !F | F", true);
+    check("F & !F | T", true);
+    check("!(T | !T) | T", true);
+    check("(F | !F) & T", true);
+    check("(T | !T) & F", false);
+    check("!(F | !!T) | T", true);
+}
+
+#[test]
+fn keywords_and_quoted_atoms() {
+    check("T and not F", true);
+    check("not (F or T) OR F", false);
+    check("\"T\" And \"F\"", false);
+    let expr = parse_bool_expr("ext=rs and not \"My Documents\" | android", |s| {
+        Ok::<_, ()>(s.to_string())
+    })
+    .unwrap();
+    let atoms: Vec<&str> = expr.iter_atoms().map(String::as_str).collect();
+    assert_eq!(atoms, ["ext=rs", "My Documents", "android"]);
+    assert_eq!(eval_bool(&expr, |atom| atom == "ext=rs"), Some(true));
+    assert_eq!(eval_bool(&expr, |atom| atom != "android"), Some(false));
+}
+
+#[test]
+fn short_circuit_is_built_in() {
+    let expr = parse_bool_expr("A & B | C", |s| Ok::<_, ()>(s.chars().next().unwrap())).unwrap();
+    let mut evaluated = Vec::new();
+    let value = eval_bool(&expr, |&c| {
+        evaluated.push(c);
+        c == 'C'
+    });
+    assert_eq!(value, Some(true));
+    assert_eq!(evaluated, vec!['A', 'C']);
+}
+
+#[test]
+fn bool_parse_errors() {
+    let error = |input| {
+        parse_bool_expr(input, |s| if s == "X" { Err(()) } else { Ok(s.len()) }).unwrap_err()
+    };
+    assert_eq!(error("!T)"), ParseError::UnexpectedClosingPar { offset: 2 });
+    assert_eq!(error("A ! B"), ParseError::UnexpectedOperator { offset: 2 });
+    assert_eq!(error("& A"), ParseError::UnexpectedOperator { offset: 0 });
+    assert_eq!(
+        error("A or and B"),
+        ParseError::UnexpectedOperator { offset: 5 }
+    );
+    assert_eq!(error("A B"), ParseError::UnexpectedAtom { offset: 2 });
+    assert_eq!(error("A & X"), ParseError::InvalidAtom { offset: 4 });
+    assert_eq!(error("A & \"B"), ParseError::UnclosedQuote { offset: 4 });
+    assert_eq!(error("(A | B"), ParseError::UnexpectedEnd { offset: 6 });
+    assert!(parse_bool_expr("", |s| Ok::<_, ()>(s.len()))
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn bool_op_combines_trees() {
+    let atom = |c| parse_bool_expr(c, |s| Ok::<_, ()>(s == "T")).unwrap();
+    let expr = atom("T") & !atom("F") | atom("F");
+    check_links(&expr);
+    assert_eq!(eval_bool(&expr, |&b| b), Some(true));
+}
+
+fn parse_chars(input: &str) -> BeTree<BoolOp, char> {
+    parse_bool_expr(input, |s| Ok::<_, ()>(s.chars().next().unwrap())).unwrap()
+}
+
+#[test]
+fn de_morgan_pairs_are_equivalent() {
+    let pairs = [
+        ("!(A & B)", "!A | !B"),
+        ("!(A | B)", "!A & !B"),
+        ("!(A | B | C)", "!A & !B & !C"),
+        ("!(A & (B | C))", "!A | (!B & !C)"),
+        ("A & (B | C)", "(A & B) | (A & C)"),
+    ];
+    for (a, b) in pairs {
+        let (a, b) = (parse_chars(a), parse_chars(b));
+        assert_eq!(equivalent(&a, &b, DEFAULT_VARIABLE_LIMIT), Ok(true));
+        assert_eq!(counterexample(&a, &b, DEFAULT_VARIABLE_LIMIT), Ok(None));
+    }
+}
+
+#[test]
+fn and_is_not_or() {
+    let (a, b) = (parse_chars("A & B"), parse_chars("A | B"));
+    assert_eq!(equivalent(&a, &b, 2), Ok(false));
+    let assignment = counterexample(&a, &b, 2).unwrap().unwrap();
+    // the first differing assignment in enumeration order
+    assert_eq!(assignment, vec![(&'A', true), (&'B', false)]);
+    let value = |expr: &BeTree<BoolOp, char>| {
+        eval_bool(expr, |c| assignment.iter().any(|&(a, v)| a == c && v))
+    };
+    assert_ne!(value(&a), value(&b));
+    // atoms only present in one of the expressions are variables too
+    let (a, b) = (parse_chars("A | (B & !B)"), parse_chars("A"));
+    assert_eq!(equivalent(&a, &b, 2), Ok(true));
+    let (a, b) = (parse_chars("A"), parse_chars("A & C"));
+    assert_eq!(
+        counterexample(&a, &b, 2),
+        Ok(Some(vec![(&'A', true), (&'C', false)]))
+    );
+}
+
+#[test]
+fn variable_limit() {
+    let a = parse_chars("A & B & C & D");
+    let b = parse_chars("D & C & B & E");
+    assert_eq!(
+        equivalent(&a, &b, 4),
+        Err(TooManyVariables { count: 5, limit: 4 })
+    );
+    assert_eq!(equivalent(&a, &b, 5), Ok(false));
+    assert_eq!(
+        BetError::from(equivalent(&a, &b, 3).unwrap_err()).to_string(),
+        "no truth table: 5 distinct atoms, more than the limit of 3"
+    );
+}
+
+#[test]
+fn variable_limit_above_63() {
+    let a = parse_chars("A & B");
+    let b = parse_chars("B & A");
+    assert_eq!(equivalent(&a, &b, usize::MAX), Ok(true));
+    assert_eq!(a.count_satisfying(64), Ok(1));
+    let mut wide = BeTree::new();
+    for i in 0..64 {
+        if i > 0 {
+            wide.push_operator(BoolOp::Or);
+        }
+        wide.push_atom(i);
+    }
+    assert_eq!(
+        wide.is_tautology(usize::MAX),
+        Err(TooManyVariables {
+            count: 64,
+            limit: 63
+        })
+    );
+}
+
+#[test]
+fn tautologies_and_contradictions() {
+    let expr = parse_chars("A | !A");
+    assert_eq!(expr.is_tautology(DEFAULT_VARIABLE_LIMIT), Ok(true));
+    assert_eq!(expr.is_contradiction(DEFAULT_VARIABLE_LIMIT), Ok(false));
+    assert_eq!(expr.count_satisfying(DEFAULT_VARIABLE_LIMIT), Ok(2));
+    let expr = parse_chars("A & !A");
+    assert_eq!(expr.is_tautology(DEFAULT_VARIABLE_LIMIT), Ok(false));
+    assert_eq!(expr.is_contradiction(DEFAULT_VARIABLE_LIMIT), Ok(true));
+    let expr = parse_chars("(A & B) | !(A & B)");
+    assert_eq!(expr.is_tautology(DEFAULT_VARIABLE_LIMIT), Ok(true));
+    let empty = parse_chars("");
+    assert_eq!(empty.is_tautology(DEFAULT_VARIABLE_LIMIT), Ok(false));
+    assert_eq!(empty.is_contradiction(DEFAULT_VARIABLE_LIMIT), Ok(true));
+}
+
+#[test]
+fn count_satisfying_assignments() {
+    let count = |input| parse_chars(input).count_satisfying(3).unwrap();
+    assert_eq!(count("A & B & C"), 1);
+    assert_eq!(count("A | B | C"), 7);
+    assert_eq!(count("A & (B | C)"), 3);
+    assert_eq!(count("(A | B) & !C"), 3);
+    // operators have no precedence: this is ((A & !B) | B) & !C
+    assert_eq!(count("A & !B | B & !C"), 3);
+    assert_eq!(count("!(A & B) & C"), 3);
+    assert_eq!(
+        parse_chars("A & B & C & D").count_satisfying(3),
+        Err(TooManyVariables { count: 4, limit: 3 })
+    );
+    assert_eq!(
+        parse_chars("A | B | C | D").is_tautology(3),
+        Err(TooManyVariables { count: 4, limit: 3 })
+    );
+}
+
+#[cfg(feature = "rand")]
+#[test]
+fn sampled_assignments_satisfy_the_expression() {
+    use {
+        rand::{rngs::StdRng, SeedableRng},
+        std::collections::HashSet,
+    };
+    let mut rng = StdRng::seed_from_u64(7);
+    for input in ["A | B", "A & !B & C", "(A | B) & !(C & D)", "A | !A"] {
+        let expr = parse_chars(input);
+        for limit in [DEFAULT_VARIABLE_LIMIT, 0] {
+            let assignment = expr
+                .sample_satisfying(&mut rng, limit, DEFAULT_SAMPLING_TRIES)
+                .unwrap()
+                .unwrap();
+            assert_eq!(
+                assignment.len(),
+                expr.iter_atoms().collect::<HashSet<_>>().len()
+            );
+            assert_eq!(
+                eval_bool(&expr, |c| assignment[c]),
+                Some(true),
+                "{:?}",
+                input
+            );
+        }
+    }
+    // all the satisfying assignments of `A | B` are drawn
+    let expr = parse_chars("A | B");
+    let drawn: HashSet<(bool, bool)> = (0..100)
+        .map(|_| {
+            let assignment = expr
+                .sample_satisfying(&mut rng, DEFAULT_VARIABLE_LIMIT, 0)
+                .unwrap()
+                .unwrap();
+            (assignment[&'A'], assignment[&'B'])
+        })
+        .collect();
+    assert_eq!(drawn.len(), 3);
+}
+
+#[cfg(feature = "rand")]
+#[test]
+fn sampling_contradictions() {
+    use rand::{rngs::StdRng, SeedableRng};
+    let mut rng = StdRng::seed_from_u64(7);
+    let expr = parse_chars("A & !A & B");
+    assert_eq!(expr.sample_satisfying(&mut rng, 3, 100), Ok(None));
+    // beyond the limit, it can't be proven there's no solution
+    assert_eq!(
+        expr.sample_satisfying(&mut rng, 1, 100),
+        Err(TooManyVariables { count: 2, limit: 1 })
+    );
+    assert_eq!(
+        BeTree::<BoolOp, char>::new().sample_satisfying(&mut rng, 3, 100),
+        Ok(None)
+    );
+}
+
+/// the number of atoms and operators of an expression
+fn size(expr: &BeTree<BoolOp, char>) -> usize {
+    expr.atom_count() + expr.op_count()
+}
+
+fn minimized_string(input: &str, limit: usize) -> String {
+    let expr = parse_chars(input);
+    let minimized = expr.minimize(limit);
+    check_links(&minimized);
+    assert_eq!(
+        equivalent(&expr, &minimized, DEFAULT_VARIABLE_LIMIT),
+        Ok(true)
+    );
+    assert!(size(&minimized) <= size(&expr));
+    let minimized = minimized.map_operators(|op, _| match op {
+        BoolOp::And => BoolOperator::And,
+        BoolOp::Or => BoolOperator::Or,
+        BoolOp::Not => BoolOperator::Not,
+    });
+    tokens_string(&minimized)
+}
+
+#[test]
+fn minimize_shrinks_common_cases() {
+    for limit in [0, DEFAULT_MINIMIZATION_LIMIT] {
+        assert_eq!(minimized_string("A | (A & B)", limit), "A");
+        assert_eq!(minimized_string("A & (A | B)", limit), "A");
+        assert_eq!(minimized_string("!!A", limit), "A");
+        assert_eq!(minimized_string("!!!A", limit), "!A");
+        assert_eq!(minimized_string("A & B & A", limit), "A&B");
+        assert_eq!(minimized_string("(A | B) | (B | A)", limit), "A|B");
+        assert_eq!(minimized_string("!(A & !!B) | C", limit), "!(A&B)|C");
+        assert_eq!(minimized_string("(A | !A) & B", limit), "B");
+        assert_eq!(minimized_string("A | !A", limit), "A|!A");
+        assert_eq!(minimized_string("B & A & !B", limit), "B&!B");
+    }
+}
+
+#[test]
+fn minimize_with_truth_table() {
+    let limit = DEFAULT_MINIMIZATION_LIMIT;
+    // found with the exact method but not with the laws
+    assert_eq!(minimized_string("(A & B) | (A & !B)", limit), "A");
+    assert_eq!(minimized_string("(A & B) | (A & !B)", 0), "A&B|(A&!B)");
+    assert_eq!(minimized_string("A | (!A & B)", limit), "A|B");
+    assert_eq!(minimized_string("(A | B) & (A | !B)", limit), "A");
+    // the simplified expression is kept when it's not bigger
+    assert_eq!(minimized_string("(A | B) & C", limit), "A|B&C");
+    assert_eq!(minimized_string("!(A | B | C)", limit), "!(A|B|C)");
+}
+
+#[test]
+fn minimize_keeps_equivalence() {
+    let inputs = [
+        "A",
+        "A & B | C & !A",
+        "!(A & (B | !C)) | (D & !(A | D))",
+        "(A | B) & (!A | C) & (B | C)",
+        "A & !B & C | A & B & C | !A & B & !C | !A & !B & !C",
+        "!(!(A | B) & !(C & !D)) & (E | !E)",
+        "A | B & C | D & (E | !A) & F",
+    ];
+    for input in inputs {
+        for limit in [0, 3, DEFAULT_MINIMIZATION_LIMIT] {
+            minimized_string(input, limit);
+        }
+    }
+}
+
+#[test]
+fn minimize_beyond_the_limit() {
+    let input = "(A | (A & B)) & (C | !!D) & (E | (E & F)) & (G | H | G) & (I | J) & (K | L)";
+    let expr = parse_chars(input);
+    let minimized = expr.minimize(DEFAULT_MINIMIZATION_LIMIT);
+    // too many variables to check with the truth table limit of this
+    // minimization, but few enough for the equivalence check
+    assert_eq!(
+        equivalent(&expr, &minimized, DEFAULT_VARIABLE_LIMIT),
+        Ok(true)
+    );
+    assert_eq!(
+        size(&minimized),
+        size(&parse_chars(
+            "A & (C | D) & E & (G | H) & (I | J) & (K | L)"
+        ))
+    );
+}
+
+#[test]
+fn minimize_empty() {
+    let empty = parse_chars("");
+    assert!(empty.minimize(DEFAULT_MINIMIZATION_LIMIT).is_empty());
+}
+
+#[test]
+fn minimize_at_the_limit_is_fast() {
+    // all the implicants of a disjunction of 10 atoms, which took
+    // seconds when comparing all pairs of implicants
+    let start = std::time::Instant::now();
+    assert_eq!(
+        minimized_string(
+            "A | B | C | D | E | F | G | H | I | J",
+            DEFAULT_MINIMIZATION_LIMIT
+        ),
+        "A|B|C|D|E|F|G|H|I|J",
+    );
+    assert_eq!(
+        minimized_string(
+            "!(A & B & C & D & E & F & G & H & I & J)",
+            DEFAULT_MINIMIZATION_LIMIT
+        ),
+        "!(A&B&C&D&E&F&G&H&I&J)",
+    );
+    assert!(start.elapsed() < std::time::Duration::from_secs(2));
+}
@@ -0,0 +1,37 @@
+//! tests of the combination of trees with the `&`, `|` and `!` operators
+
+use crate::{test_util::*, *};
+
+impl LogicOps for BoolOperator {
+    fn and() -> Self {
+        Self::And
+    }
+    fn or() -> Self {
+        Self::Or
+    }
+    fn not() -> Self {
+        Self::Not
+    }
+}
+
+#[test]
+fn combine_with_operators() {
+    let filter = parse("A | B") & !parse("C") | parse("D & E");
+    check_links(&filter);
+    check_same_eval(&filter, &parse("((A | B) & !C) | (D & E)"), "ABCDE");
+    let filter = !(parse("A") & parse("!B"));
+    check_links(&filter);
+    check_same_eval(&filter, &parse("!(A & !B)"), "AB");
+}
+
+#[test]
+fn operators_on_empty_trees() {
+    let empty = BeTree::<BoolOperator, char>::new;
+    assert_eq!(empty() & parse("A | B"), parse("A | B"));
+    assert_eq!(parse("A | B") & empty(), parse("A | B"));
+    assert_eq!(parse("A") | empty(), parse("A"));
+    assert!((empty() | empty()).is_empty());
+    assert!((!empty()).is_empty());
+    let filter = !empty() & parse("A");
+    check_same_eval(&filter, &parse("A"), "A");
+}
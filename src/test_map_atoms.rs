@@ -0,0 +1,92 @@
+//! tests of the atom mapping functions
+
+use crate::{test_util::*, AtomId};
+
+/// An atom which can't be cloned without panicking, to check
+/// it's really moved
+#[derive(Debug)]
+struct Owned(Box<char>);
+impl Clone for Owned {
+    fn clone(&self) -> Self {
+        panic!("atom cloned");
+    }
+}
+
+#[test]
+fn into_map_atoms_moves_atoms() {
+    let expr = parse("(A | B) & !(C | D | E)");
+    let trues = ['A', 'C'];
+    let expected = eval(&expr, &trues);
+    let owned = expr.clone().into_map_atoms(|c| Owned(Box::new(c)));
+    let pointers: Vec<*const char> = owned.iter_atoms().map(|o| &*o.0 as *const char).collect();
+    let mut moved_pointers = Vec::new();
+    let back = owned.into_map_atoms(|o| {
+        moved_pointers.push(&*o.0 as *const char);
+        *o.0
+    });
+    assert_eq!(pointers, moved_pointers);
+    assert_eq!(back, expr);
+    assert_eq!(eval(&back, &trues), expected);
+}
+
+#[test]
+fn try_into_map_atoms_stops_on_error() {
+    let expr = parse("A & !(B | C)");
+    let upper = expr.clone().try_into_map_atoms(|c| {
+        if c.is_ascii_uppercase() {
+            Ok(c.to_ascii_lowercase())
+        } else {
+            Err(c)
+        }
+    });
+    assert_eq!(upper.unwrap().iter_atoms().collect::<String>(), "abc");
+    let expr = parse("A & !(b | C)");
+    let res = expr.try_into_map_atoms(|c| {
+        if c.is_ascii_uppercase() {
+            Ok(c)
+        } else {
+            Err(c)
+        }
+    });
+    assert_eq!(res, Err('b'));
+}
+
+#[test]
+fn map_atoms_indexed_with_side_table() {
+    use std::collections::HashMap;
+    let expr = parse("(A | B) & !(C | D | E)");
+    // a side table, keyed by atom id, giving the position of the atom in the input
+    let mut spans = HashMap::new();
+    for (atom_id, atom) in expr.iter_atoms().enumerate() {
+        spans.insert(
+            AtomId::from_index(atom_id),
+            "(A | B) & !(C | D | E)".find(*atom).unwrap(),
+        );
+    }
+    let spanned = expr
+        .try_map_atoms_indexed(|atom_id, &atom| match spans.get(&atom_id) {
+            Some(&pos) => Ok((atom, pos)),
+            None => Err(atom_id),
+        })
+        .unwrap();
+    assert_eq!(
+        spanned.iter_atoms().copied().collect::<Vec<_>>(),
+        vec![('A', 1), ('B', 5), ('C', 12), ('D', 16), ('E', 20)],
+    );
+    for atom_id in (0..5).map(AtomId::new) {
+        assert_eq!(
+            spanned.atom(atom_id).unwrap().0,
+            *expr.atom(atom_id).unwrap()
+        );
+    }
+    let ids = expr.map_atoms_indexed(|atom_id, _| atom_id);
+    assert_eq!(indices(ids.iter_atoms().copied()), vec![0, 1, 2, 3, 4]);
+    assert_eq!(
+        expr.try_map_atoms_indexed(|atom_id, _| if atom_id < AtomId::new(3) {
+            Ok(())
+        } else {
+            Err(atom_id)
+        }),
+        Err(AtomId::new(3))
+    );
+}
@@ -0,0 +1,87 @@
+//! tests of map_atoms_in_place, of the atom contexts, and of atom replacement
+
+use {
+    super::*,
+    crate::test_util::{BoolOperator::*, *},
+};
+
+#[test]
+fn map_atoms_in_place_contexts() {
+    let mut expr = parse("(A | B) & !(C | D | E)");
+    let mut contexts = Vec::new();
+    expr.map_atoms_in_place(|atom, context| {
+        let operators: Vec<_> = context
+            .operators()
+            .map(|(op, unary)| (*op, unary))
+            .collect();
+        contexts.push((*atom, operators, context.side()));
+    });
+    assert_eq!(
+        contexts,
+        vec![
+            ('A', vec![(And, false), (Or, false)], Side::Left),
+            ('B', vec![(And, false), (Or, false)], Side::Right),
+            (
+                'C',
+                vec![(And, false), (Not, true), (Or, false), (Or, false)],
+                Side::Left
+            ),
+            (
+                'D',
+                vec![(And, false), (Not, true), (Or, false), (Or, false)],
+                Side::Right
+            ),
+            (
+                'E',
+                vec![(And, false), (Not, true), (Or, false)],
+                Side::Right
+            ),
+        ]
+    );
+}
+
+#[test]
+fn map_atoms_in_place_depends_on_negation() {
+    // lowercase the atoms which are under a negation
+    let mut expr = parse("(A | B) & !(C | !D | E)");
+    expr.map_atoms_in_place(|atom, context| {
+        let negations = context.operators().filter(|(op, _)| **op == Not).count();
+        if negations % 2 == 1 {
+            *atom = atom.to_ascii_lowercase();
+        }
+    });
+    assert_eq!(expr.iter_atoms().collect::<String>(), "ABcDe");
+}
+
+#[test]
+fn map_atoms_in_place_follows_tree_order() {
+    let mut expr = parse("A & B | C");
+    let mut visited = String::new();
+    expr.map_atoms_in_place(|atom, context| {
+        visited.push(*atom);
+        assert_eq!(context.ancestors().last(), Some(&context.parent()));
+    });
+    assert_eq!(visited, "ABC");
+}
+
+#[test]
+fn set_atoms_of_a_template() {
+    let mut expr = parse("(A | B) & !C");
+    let before = expr.clone();
+    assert_eq!(expr.set_atom(AtomId::new(0), 'D'), Some('A'));
+    assert_eq!(expr.set_atom(AtomId::new(2), 'E'), Some('C'));
+    if let Some(atom) = expr.atom_mut(AtomId::new(1)) {
+        *atom = 'F';
+    }
+    assert_eq!(expr.iter_atoms().copied().collect::<String>(), "DFE");
+    assert_eq!(expr.set_atom(AtomId::new(3), 'G'), None);
+    assert_eq!(expr.atom_mut(AtomId::new(3)), None);
+    assert_eq!(expr.iter_atoms().count(), 3);
+    assert_eq!(expr.nodes, before.nodes);
+    check_links(&expr);
+    check_same_eval(&expr, &parse("(D | F) & !E"), "DEF");
+    assert_eq!(eval(&expr, &['D']), Some(true));
+    expr.set_atom(AtomId::new(0), 'E');
+    assert_eq!(eval(&expr, &['D']), Some(false));
+    assert_eq!(eval(&expr, &['F']), Some(true));
+}
@@ -0,0 +1,108 @@
+//! tests of the operator mapping functions
+
+use {
+    super::*,
+    crate::test_util::{BoolOperator::*, *},
+};
+
+/// parse with operators kept as chars
+fn parse_raw(input: &str) -> BeTree<char, char> {
+    let mut expr = BeTree::new();
+    for c in input.chars() {
+        match c {
+            '&' | '|' | '!' | '-' => expr.push_operator(c),
+            ' ' => {}
+            '(' => expr.open_par(),
+            ')' => expr.close_par(),
+            _ => expr.push_atom(c),
+        }
+    }
+    expr
+}
+
+#[test]
+fn map_operators_lowering() {
+    let inputs = [
+        "(A | B) & !(C | D | E)",
+        "!(!((A|B)&(B|A)&A)) & !B & (A | (A|B))",
+        "A & A | A & B",
+        "!!B",
+    ];
+    for input in inputs {
+        let raw = parse_raw(input);
+        let lowered = raw.map_operators(|&c, _| match c {
+            '&' => And,
+            '|' => Or,
+            _ => Not,
+        });
+        let parsed = parse(input);
+        assert_eq!(lowered, parsed);
+        for trues in [&['A'][..], &['B'], &['A', 'B'], &[]] {
+            assert_eq!(eval(&lowered, trues), eval(&parsed, trues));
+        }
+    }
+}
+
+#[test]
+fn map_operators_depending_on_arity() {
+    // '-' is a "Not" when unary and an "And" when binary (as in "A - B")
+    let raw = parse_raw("-(A - B) | -C");
+    let lowered = raw.map_operators(|&c, unary| match (c, unary) {
+        ('-', true) => Not,
+        ('-', false) => And,
+        _ => Or,
+    });
+    assert_eq!(lowered, parse("!(A & B) | !C"));
+}
+
+#[test]
+fn try_map_operators_rejects_unknown_operators() {
+    let raw = parse_raw("A & B | -C");
+    let res = raw.try_map_operators(|&c, unary| match (c, unary) {
+        ('&', false) => Ok(And),
+        ('|', false) => Ok(Or),
+        ('!', true) => Ok(Not),
+        _ => Err(c),
+    });
+    assert_eq!(res, Err('-'));
+    let raw = parse_raw("A & B | !C");
+    let res: Result<_, char> = raw.try_map_operators(|&c, _| {
+        Ok(match c {
+            '&' => And,
+            '|' => Or,
+            _ => Not,
+        })
+    });
+    assert_eq!(res.unwrap(), parse("A & B | !C"));
+}
+
+#[test]
+fn replace_operator_in_chains_and_groups() {
+    let mut expr = parse("(A | B) & !(C | D | E)");
+    assert_eq!(expr.replace_operator(&Or, And), 3);
+    check_same_eval(&expr, &parse("(A & B) & !(C & D & E)"), "ABCDE");
+    let mut expr = parse("!(A & B) & !(!C & (D & !E))");
+    assert_eq!(expr.replace_operator(&And, Or), 4);
+    check_same_eval(&expr, &parse("!(A | B) | !(!C | (D | !E))"), "ABCDE");
+    assert_eq!(expr.replace_operator(&And, Or), 0);
+}
+
+#[test]
+fn replace_operator_ignores_unreachable_nodes() {
+    let mut expr = parse("(A | B) & C");
+    expr.remove_atom(AtomId::new(1)).unwrap(); // the Or node isn't reachable anymore
+    assert_eq!(expr.replace_operator(&Or, And), 0);
+    assert_eq!(expr.replace_operator(&And, Or), 1);
+    check_same_eval(&expr, &parse("A | C"), "ABC");
+}
+
+#[test]
+fn map_operators_in_place_depending_on_arity() {
+    let mut raw = parse_raw("-(A - B) | -C - !A");
+    raw.map_operators_in_place(|c, unary| {
+        if *c == '-' && !unary {
+            *c = '&';
+        }
+    });
+    assert_eq!(raw, parse_raw("-(A & B) | -C & !A"));
+}
@@ -0,0 +1,86 @@
+//! tests of the metadata attached to nodes
+
+use {
+    crate::{test_util::*, *},
+    core::ops::Range,
+};
+
+/// parse the expression, attaching to each operator its span in the input
+fn parse_with_spans(input: &str) -> BeTree<BoolOperator, char, Range<usize>> {
+    let mut expr = BeTree::default();
+    for (i, c) in input.char_indices() {
+        let span = i..i + c.len_utf8();
+        match c {
+            '&' => expr.push_operator_with_meta(BoolOperator::And, span),
+            '|' => expr.push_operator_with_meta(BoolOperator::Or, span),
+            '!' => expr.push_operator_with_meta(BoolOperator::Not, span),
+            ' ' => {}
+            '(' => expr.open_par(),
+            ')' => expr.close_par(),
+            _ => expr.push_atom(c),
+        }
+    }
+    expr
+}
+
+/// check that every operator node has the span of its operator
+fn check_spans(input: &str) {
+    let expr = parse_with_spans(input);
+    let mut operators = 0;
+    for node_id in (0..expr.nodes.len()).map(NodeId::from_index) {
+        let node = expr.node(node_id).unwrap();
+        let Some(op) = node.operator() else {
+            assert_eq!(expr.node_meta(node_id), Some(&(0..0)));
+            continue;
+        };
+        let span = expr.node_meta(node_id).unwrap().clone();
+        assert_eq!(&input[span], op.to_string(), "in {:?}", input);
+        operators += 1;
+    }
+    assert_eq!(
+        operators,
+        input.chars().filter(|c| "&|!".contains(*c)).count()
+    );
+    // the metadata doesn't change the structure nor the evaluation
+    let plain = expr.map_meta(|_| ());
+    check_links(&plain);
+    assert_eq!(plain, parse(input));
+}
+
+#[test]
+fn meta_follows_restructured_nodes() {
+    // binary operators replacing the tail, whose node keeps its span
+    check_spans("A & B | C & D");
+    check_spans("A | B & !C | D");
+    // operators set on existing group nodes
+    check_spans("(A & B) | (C | D)");
+    check_spans("!(A & !B) & ((C))");
+}
+
+#[test]
+fn node_meta_mut() {
+    let mut expr: BeTree<BoolOperator, char, Option<&str>> = BeTree::default();
+    expr.push_atom('A');
+    expr.push_operator_with_meta(BoolOperator::And, Some("both"));
+    expr.push_atom('B');
+    expr.push_operator(BoolOperator::Or);
+    expr.push_atom('C');
+    let head = expr.head;
+    assert_eq!(expr.node_meta(head), Some(&None));
+    *expr.node_meta_mut(head).unwrap() = Some("either");
+    let Child::Node(left) = expr.head().left() else {
+        panic!("the left operand of the head should be a node");
+    };
+    assert_eq!(expr.node(left).unwrap().meta(), &Some("both"));
+    assert_eq!(expr.node_meta(NodeId::new(7)), None);
+    assert_eq!(expr.node_meta_mut(NodeId::new(7)), None);
+    let labels = expr.clone().map_meta(|meta| meta.unwrap_or("?").len());
+    assert_eq!(labels.node_meta(head), Some(&6));
+    assert_eq!(labels.node_meta(left), Some(&4));
+    let value = expr.eval(
+        |&atom| atom == 'C',
+        |op, a, b| op.eval(a, b),
+        |op, &a| op.short_circuit(a),
+    );
+    assert_eq!(value, Some(true));
+}
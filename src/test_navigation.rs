@@ -0,0 +1,108 @@
+//! tests of the navigation helpers
+
+use {super::*, crate::test_util::*};
+
+// In the doc example `(A | B) & !(C | D | E)`, the nodes are
+//  0: the `&`, with node 1 on the left and node 2 on the right
+//  1: the `A | B` node
+//  2: the `!`, with node 4 as operand
+//  4: the `... | E` node, with node 3 on the left and atom 4 (E) on the right
+//  3: the `C | D` node
+
+#[test]
+fn children_and_parents() {
+    let expr = parse("(A | B) & !(C | D | E)");
+    assert_eq!(
+        expr.children(NodeId::new(0)),
+        Some((Child::Node(NodeId::new(1)), Child::Node(NodeId::new(2))))
+    );
+    assert_eq!(
+        expr.children(NodeId::new(1)),
+        Some((Child::Atom(AtomId::new(0)), Child::Atom(AtomId::new(1))))
+    );
+    assert_eq!(
+        expr.children(NodeId::new(2)),
+        Some((Child::Node(NodeId::new(4)), Child::None))
+    );
+    assert_eq!(
+        expr.children(NodeId::new(4)),
+        Some((Child::Node(NodeId::new(3)), Child::Atom(AtomId::new(4))))
+    );
+    assert_eq!(expr.children(NodeId::new(5)), None);
+    assert_eq!(expr.parent_of(NodeId::new(0)), None);
+    assert_eq!(expr.parent_of(NodeId::new(1)), Some(NodeId::new(0)));
+    assert_eq!(expr.parent_of(NodeId::new(2)), Some(NodeId::new(0)));
+    assert_eq!(expr.parent_of(NodeId::new(4)), Some(NodeId::new(2)));
+    assert_eq!(expr.parent_of(NodeId::new(3)), Some(NodeId::new(4)));
+    assert_eq!(expr.parent_of(NodeId::new(5)), None);
+}
+
+#[test]
+fn siblings_and_sides() {
+    let expr = parse("(A | B) & !(C | D | E)");
+    assert_eq!(expr.sibling_of(NodeId::new(0)), None);
+    assert_eq!(
+        expr.sibling_of(NodeId::new(1)),
+        Some(Child::Node(NodeId::new(2)))
+    );
+    assert_eq!(
+        expr.sibling_of(NodeId::new(2)),
+        Some(Child::Node(NodeId::new(1)))
+    );
+    assert_eq!(expr.sibling_of(NodeId::new(4)), None); // operand of a unary operator
+    assert_eq!(
+        expr.sibling_of(NodeId::new(3)),
+        Some(Child::Atom(AtomId::new(4)))
+    );
+    assert_eq!(expr.is_left_child(NodeId::new(0)), None);
+    assert_eq!(expr.is_left_child(NodeId::new(1)), Some(true));
+    assert_eq!(expr.is_left_child(NodeId::new(2)), Some(false));
+    assert_eq!(expr.is_left_child(NodeId::new(4)), Some(true));
+    assert_eq!(expr.is_left_child(NodeId::new(3)), Some(true));
+    assert_eq!(expr.is_left_child(NodeId::new(42)), None);
+}
+
+#[test]
+fn node_of_atom() {
+    let expr = parse("(A | B) & !(C | D | E)");
+    assert_eq!(
+        expr.node_of_atom(AtomId::new(0)),
+        Some((NodeId::new(1), Side::Left))
+    );
+    assert_eq!(
+        expr.node_of_atom(AtomId::new(1)),
+        Some((NodeId::new(1), Side::Right))
+    );
+    assert_eq!(
+        expr.node_of_atom(AtomId::new(2)),
+        Some((NodeId::new(3), Side::Left))
+    );
+    assert_eq!(
+        expr.node_of_atom(AtomId::new(3)),
+        Some((NodeId::new(3), Side::Right))
+    );
+    assert_eq!(
+        expr.node_of_atom(AtomId::new(4)),
+        Some((NodeId::new(4), Side::Right))
+    );
+    assert_eq!(expr.node_of_atom(AtomId::new(5)), None);
+    let expr = parse("!A");
+    assert_eq!(
+        expr.node_of_atom(AtomId::new(0)),
+        Some((NodeId::new(1), Side::Left))
+    );
+}
+
+#[test]
+fn navigation_ignores_removed_nodes() {
+    let mut expr = parse("(A | B) & !(C | D | E)");
+    expr.prune(NodeId::new(2)).unwrap();
+    assert_eq!(expr.parent_of(NodeId::new(2)), None);
+    assert_eq!(expr.sibling_of(NodeId::new(2)), None);
+    assert_eq!(expr.is_left_child(NodeId::new(4)), Some(true)); // still linked to the removed node
+    assert_eq!(expr.node_of_atom(AtomId::new(4)), None);
+    assert_eq!(
+        expr.node_of_atom(AtomId::new(0)),
+        Some((NodeId::new(1), Side::Left))
+    );
+}
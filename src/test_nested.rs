@@ -0,0 +1,148 @@
+//! tests of the nested serde representation
+
+use {super::*, crate::test_util::*};
+
+type Repr = NestedRepr<BoolOperator, char>;
+
+#[test]
+fn nested_json() {
+    let json = serde_json::to_string(&Repr::from(parse("(A | B) & !C"))).unwrap();
+    assert_eq!(
+        json,
+        r#"{"op":"And","left":{"op":"Or","left":{"atom":"A"},"right":{"atom":"B"}},"right":{"op":"Not","operand":{"atom":"C"}}}"#
+    );
+    assert_eq!(
+        serde_json::to_string(&Repr::from(parse(""))).unwrap(),
+        "null"
+    );
+    assert_eq!(
+        serde_json::to_string(&Repr::from(parse("A |"))).unwrap(),
+        r#"{"op":"Or","left":{"atom":"A"},"right":null}"#
+    );
+}
+
+#[test]
+fn nested_round_trip() {
+    let inputs = [
+        "",
+        "A",
+        "!A",
+        "(A | B) & !(C | D | E)",
+        "!(!((A|B)&(B|C)&A)) & !D & (E | (A|E))",
+        "A & !",
+        "A & (B |",
+    ];
+    for input in inputs {
+        let expr = parse(input);
+        let json = serde_json::to_string(&Repr::from(expr.clone())).unwrap();
+        let read: BeTree<_, _> = serde_json::from_str::<Repr>(&json).unwrap().into();
+        check_links(&read);
+        assert!(same_structure(&expr, &read), "{} -> {}", input, json);
+        let yaml = serde_yaml::to_string(&Repr::from(expr.clone())).unwrap();
+        let read: BeTree<_, _> = serde_yaml::from_str::<Repr>(&yaml).unwrap().into();
+        check_links(&read);
+        assert!(same_structure(&expr, &read), "{} -> {}", input, yaml);
+    }
+}
+
+#[test]
+fn nested_deserialization_is_compact() {
+    let mut expr = parse("A & (B | C) & D");
+    expr.cursor_at_mut(NodeId::new(1))
+        .unwrap()
+        .replace_with_atom('E');
+    let json = serde_json::to_string(&Repr::from(expr.clone())).unwrap();
+    let read = serde_json::from_str::<Repr>(&json).unwrap().0;
+    check_same_eval(&expr, &read, "ABCDE");
+    let usage = read.memory_usage();
+    assert_eq!(usage.nodes_len, usage.reachable_nodes);
+    assert_eq!(usage.atoms_len, usage.reachable_atoms);
+}
+
+#[test]
+fn nested_yaml_fixture() {
+    let yaml = "
+op: Or
+left:
+  op: And
+  left:
+    atom: A
+  right:
+    op: Not
+    operand:
+      atom: B
+right:
+  atom: C
+";
+    let read = serde_yaml::from_str::<Repr>(yaml).unwrap().0;
+    check_links(&read);
+    assert!(same_structure(&read, &parse("A & !B | C")));
+}
+
+#[test]
+fn nested_rejects_malformed_input() {
+    let cases = [
+        (r#"{"atom":"A","op":"Not"}"#, "an atom can't have"),
+        (r#"{"left":{"atom":"A"}}"#, "missing field `op`"),
+        (r#"{"op":"And"}"#, "missing field `left`"),
+        (
+            r#"{"op":"And","left":null,"right":{"atom":"A"}}"#,
+            "left operand",
+        ),
+        (
+            r#"{"op":"Not","operand":{"atom":"A"},"left":{"atom":"B"}}"#,
+            "either",
+        ),
+        (r#"{"op":"Not","op":"And"}"#, "duplicate field `op`"),
+        (r#"{"op":"Xor","operand":{"atom":"A"}}"#, "unknown variant"),
+        (r#"{"atom":"A","size":3}"#, "unknown field `size`"),
+        (r#"{"atom":"AB"}"#, "expected a character"),
+        (r#"[1, 2]"#, "expected an atom"),
+    ];
+    for (json, message) in cases {
+        let err = serde_json::from_str::<Repr>(json).unwrap_err();
+        assert!(
+            err.to_string().contains(message),
+            "{} doesn't contain {:?}",
+            err,
+            message
+        );
+    }
+}
+
+#[test]
+fn nested_rejects_too_deep_expressions() {
+    let nested = |depth: usize| {
+        let mut json = r#"{"atom":"A"}"#.to_string();
+        for _ in 0..depth {
+            json = format!(r#"{{"op":"Not","operand":{}}}"#, json);
+        }
+        json
+    };
+    let read = serde_json::from_str::<Repr>(&nested(Repr::MAX_DEPTH))
+        .unwrap()
+        .0;
+    assert_eq!(read.depth(), Repr::MAX_DEPTH);
+    let err = serde_json::from_str::<Repr>(&nested(Repr::MAX_DEPTH + 1)).unwrap_err();
+    assert!(err.to_string().contains("nested deeper than"), "{}", err);
+}
+
+#[test]
+fn nested_round_trip_at_the_depth_limit() {
+    // a left chain of n binary operations is nested n levels deep
+    let chain = |len: usize| {
+        let atoms: Vec<&str> = (0..=len).map(|i| ["A", "B"][i % 2]).collect();
+        parse(&atoms.join(" | "))
+    };
+    let expr = chain(Repr::MAX_DEPTH);
+    assert_eq!(expr.depth(), Repr::MAX_DEPTH);
+    let json = serde_json::to_string(&Repr::from(expr.clone())).unwrap();
+    let read = serde_json::from_str::<Repr>(&json).unwrap().0;
+    check_links(&read);
+    assert_eq!(read.depth(), Repr::MAX_DEPTH);
+    check_same_eval(&read, &expr, "AB");
+    for len in [Repr::MAX_DEPTH + 1, 150] {
+        let err = serde_json::to_string(&Repr::from(chain(len))).unwrap_err();
+        assert!(err.to_string().contains("nested deeper than"), "{}", err);
+    }
+}
@@ -0,0 +1,122 @@
+//! tests of the evaluation keeping the values of nodes and atoms
+
+use {super::*, crate::test_util::*};
+
+fn child_at(expr: &BeTree<BoolOperator, char>, path: &[Side]) -> Child {
+    let mut child = Child::Node(expr.head);
+    for &side in path {
+        let Child::Node(node_id) = child else {
+            panic!("no node in {:?}", path);
+        };
+        child = expr.node(node_id).unwrap().child(side);
+    }
+    child
+}
+
+fn eval_annotated(
+    expr: &BeTree<BoolOperator, char>,
+    trues: &[char],
+) -> (Option<bool>, NodeValues<bool>) {
+    expr.eval_annotated(
+        |c| trues.contains(c),
+        |op, a, b| op.eval(a, b),
+        |op, &a| op.short_circuit(a),
+    )
+}
+
+/// the paths of the evaluated nodes and atoms, with their values
+fn evaluated(
+    expr: &BeTree<BoolOperator, char>,
+    values: &NodeValues<bool>,
+    paths: &[&[Side]],
+) -> Vec<Option<bool>> {
+    paths
+        .iter()
+        .map(|path| values.get(child_at(expr, path)).copied())
+        .collect()
+}
+
+#[test]
+fn skipped_subtrees_have_no_value() {
+    use Side::*;
+    // pushed from left to right: ((A & (B | C)) | !D)
+    let expr = parse("A & (B | C) | !D");
+    let paths: &[&[Side]] = &[
+        &[],                   // |
+        &[Left],               // &
+        &[Left, Left],         // A
+        &[Left, Right],        // B | C
+        &[Left, Right, Left],  // B
+        &[Left, Right, Right], // C
+        &[Right],              // !
+        &[Right, Left],        // D
+    ];
+    // A is false, so the & short-circuits
+    let (value, values) = eval_annotated(&expr, &['C']);
+    assert_eq!(value, Some(true));
+    assert_eq!(
+        evaluated(&expr, &values, paths),
+        [
+            Some(true),
+            Some(false),
+            Some(false),
+            None,
+            None,
+            None,
+            Some(true),
+            Some(false),
+        ],
+    );
+    // B is true, so the | of the group short-circuits, but not the &
+    let (value, values) = eval_annotated(&expr, &['A', 'B', 'D']);
+    assert_eq!(value, Some(true));
+    assert_eq!(
+        evaluated(&expr, &values, paths),
+        [
+            Some(true),
+            Some(true),
+            Some(true),
+            Some(true),
+            Some(true),
+            None,
+            None,
+            None,
+        ],
+    );
+    // the values of the atoms can also be read by id
+    assert_eq!(values.get_atom(AtomId::new(1)), Some(&true));
+    assert_eq!(values.get_atom(AtomId::new(2)), None);
+    assert_eq!(values.get_atom(AtomId::new(1000)), None);
+    assert_eq!(values.get_node(NodeId::new(1000)), None);
+}
+
+#[test]
+fn same_result_as_eval() {
+    let expr = parse("!(A | B) & (C | !A) | B & !C");
+    for trues in all_assignments("ABC") {
+        let (value, values) = eval_annotated(&expr, &trues);
+        assert_eq!(value, eval(&expr, &trues));
+        assert_eq!(values.get_node(expr.head), value.as_ref());
+    }
+}
+
+#[test]
+fn incomplete_expressions() {
+    let expr = parse("A | !");
+    let (value, values) = expr.eval_annotated(
+        |_| false,
+        |op, a, b| match b {
+            Some(b) => op.eval(a, Some(b)),
+            None => a,
+        },
+        |op, &a| op.short_circuit(a),
+    );
+    assert_eq!(value, Some(false));
+    assert_eq!(values.get_atom(AtomId::new(0)), Some(&false));
+    // the unary operator has no operand so it has no value
+    let Child::Node(not) = expr.node(expr.head).unwrap().right() else {
+        panic!("no unary operator");
+    };
+    assert_eq!(values.get_node(not), None);
+    assert_eq!(eval_annotated(&BeTree::new(), &[]).0, None);
+}
@@ -0,0 +1,85 @@
+//! tests of the pushing of operators with a declared arity
+
+use crate::*;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    And,
+    Not,
+    Minus,
+}
+
+impl OpArity for Op {
+    fn arity(&self) -> Arity {
+        match self {
+            Self::And => Arity::Binary,
+            Self::Not => Arity::Unary,
+            Self::Minus => Arity::Either,
+        }
+    }
+}
+
+#[test]
+fn unary_operator_in_binary_position() {
+    let mut expr: BeTree<Op, char> = BeTree::new();
+    expr.push_atom('a');
+    let before = expr.clone();
+    assert_eq!(
+        expr.push_operator_checked(Op::Not),
+        Err(PushError::UnaryInBinaryPosition)
+    );
+    assert_eq!(expr, before);
+    expr.push_operator_checked(Op::And).unwrap();
+    expr.push_operator_checked(Op::Not).unwrap();
+    expr.push_atom('b');
+    assert_eq!(expr.display_compact().to_string(), "And('a', Not('b'))");
+}
+
+#[test]
+fn binary_operator_in_unary_position() {
+    let mut expr: BeTree<Op, char> = BeTree::new();
+    assert_eq!(
+        expr.push_operator_checked(Op::And),
+        Err(PushError::BinaryInUnaryPosition)
+    );
+    assert!(expr.is_empty());
+    expr.push_atom('a');
+    expr.push_operator_checked(Op::And).unwrap();
+    expr.open_par();
+    assert_eq!(
+        expr.push_operator_checked(Op::And),
+        Err(PushError::BinaryInUnaryPosition)
+    );
+    expr.push_operator_checked(Op::Not).unwrap();
+    assert_eq!(
+        expr.push_operator_checked(Op::And),
+        Err(PushError::BinaryInUnaryPosition)
+    );
+    expr.push_atom('b');
+    expr.close_par();
+    assert_eq!(expr.display_compact().to_string(), "And('a', Not('b'))");
+}
+
+#[test]
+fn either_operator_follows_the_context() {
+    let mut checked: BeTree<Op, char> = BeTree::new();
+    let mut guessed: BeTree<Op, char> = BeTree::new();
+    for expr in [&mut checked, &mut guessed] {
+        expr.push_operator(Op::Minus);
+        expr.push_atom('a');
+    }
+    checked.push_operator_checked(Op::Minus).unwrap();
+    guessed.push_operator(Op::Minus);
+    for expr in [&mut checked, &mut guessed] {
+        expr.push_atom('b');
+    }
+    assert_eq!(checked, guessed);
+    assert_eq!(
+        checked.display_compact().to_string(),
+        "Minus(Minus('a'), 'b')"
+    );
+    let mut expr: BeTree<Op, char> = BeTree::new();
+    expr.push_operator_checked(Op::Minus).unwrap();
+    expr.push_atom('a');
+    assert_eq!(expr.display_compact().to_string(), "Minus('a')");
+}
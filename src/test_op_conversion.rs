@@ -0,0 +1,169 @@
+//! tests of the conversion between sets of operators
+
+use {
+    super::*,
+    crate::test_util::{BoolOperator::*, *},
+};
+
+/// The operators of another frontend
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum FrontOp {
+    And,
+    Or,
+    Not,
+    Xor,
+    Nand,
+    /// unary, true when the operand is false, written `~`
+    Never,
+}
+
+fn parse_front(input: &str) -> BeTree<FrontOp, char> {
+    let mut expr = BeTree::new();
+    for c in input.chars() {
+        match c {
+            '&' => expr.push_operator(FrontOp::And),
+            '|' => expr.push_operator(FrontOp::Or),
+            '!' => expr.push_operator(FrontOp::Not),
+            '^' => expr.push_operator(FrontOp::Xor),
+            '/' => expr.push_operator(FrontOp::Nand),
+            '~' => expr.push_operator(FrontOp::Never),
+            ' ' => {}
+            '(' => expr.open_par(),
+            ')' => expr.close_par(),
+            _ => expr.push_atom(c),
+        }
+    }
+    expr
+}
+
+fn eval_front(expr: &BeTree<FrontOp, char>, trues: &[char]) -> Option<bool> {
+    expr.eval(
+        |c| trues.contains(c),
+        |op, a, b| match (op, b) {
+            (FrontOp::And, Some(b)) => a & b,
+            (FrontOp::Or, Some(b)) => a | b,
+            (FrontOp::Xor, Some(b)) => a ^ b,
+            (FrontOp::Nand, Some(b)) => !(a & b),
+            (FrontOp::Not | FrontOp::Never, None) => !a,
+            _ => unreachable!(),
+        },
+        |_, _| false,
+    )
+}
+
+/// make an expansion, `L` and `R` standing for the operands
+fn expansion(pattern: &str) -> OpConversion<BoolOperator> {
+    OpConversion::Expansion(parse(pattern).map_atoms_indexed(|_, &c| match c {
+        'L' => Side::Left,
+        _ => Side::Right,
+    }))
+}
+
+fn convert(op: &FrontOp, _unary: bool) -> Result<OpConversion<BoolOperator>, &'static str> {
+    Ok(match op {
+        FrontOp::And => OpConversion::Operator(And),
+        FrontOp::Or => OpConversion::Operator(Or),
+        FrontOp::Not => OpConversion::Operator(Not),
+        FrontOp::Nand => expansion("!(L & R)"),
+        FrontOp::Xor => expansion("(L & !R) | (!L & R)"),
+        FrontOp::Never => expansion("!L"),
+    })
+}
+
+fn check_conversion(input: &str) {
+    let front = parse_front(input);
+    let converted = front.try_convert_ops(convert).unwrap();
+    converted.check_invariants();
+    for trues in all_assignments("ABC") {
+        assert_eq!(
+            eval(&converted, &trues),
+            eval_front(&front, &trues),
+            "input: {:?}, trues: {:?}",
+            input,
+            trues
+        );
+    }
+}
+
+#[test]
+fn plain_renaming() {
+    let front = parse_front("(A | B) & !(C | A)");
+    let converted = front.try_convert_ops(convert).unwrap();
+    assert_eq!(
+        tokens_string(&converted),
+        tokens_string(&parse("(A | B) & !(C | A)"))
+    );
+    assert!(converted.structural_eq(&parse("(A | B) & !(C | A)")));
+}
+
+#[test]
+fn expansions_are_equivalent() {
+    check_conversion("A / B");
+    check_conversion("!(A / B) | C");
+    check_conversion("A ^ B");
+    check_conversion("(A ^ B) ^ C");
+    check_conversion("A / (B ^ !C)");
+    check_conversion("~A & B");
+    check_conversion("~(A / B)");
+    check_conversion("~~A ^ ~(B | C)");
+    let converted = parse_front("A / B").try_convert_ops(convert).unwrap();
+    assert_eq!(tokens_string(&converted), "!(A&B)");
+    // operands used twice are copied
+    let converted = parse_front("(A | B) ^ C").try_convert_ops(convert).unwrap();
+    assert_eq!(converted.atom_count(), 6);
+    assert_eq!(tokens_string(&converted), "A|B&!C|(!(A|B)&C)");
+}
+
+#[test]
+fn unsupported_operators_are_rejected() {
+    let front = parse_front("A & (B | C ^ D)");
+    let xor = front
+        .iter_nodes()
+        .find(|(_, node)| node.operator == Some(FrontOp::Xor))
+        .map(|(node_id, _)| node_id)
+        .unwrap();
+    let result = front.try_convert_ops(|op, unary| match op {
+        FrontOp::Xor => Err("xor isn't supported"),
+        _ => convert(op, unary),
+    });
+    assert_eq!(
+        result,
+        Err(ConversionError::Rejected {
+            node: xor,
+            error: "xor isn't supported",
+        })
+    );
+    let error = result.unwrap_err();
+    assert_eq!(error.node(), xor);
+    assert_eq!(
+        error.to_string(),
+        format!("operator of node {} rejected: xor isn't supported", xor),
+    );
+}
+
+#[test]
+fn invalid_expansions() {
+    let front = parse_front("A & ~B");
+    let never = front
+        .iter_nodes()
+        .find(|(_, node)| node.operator == Some(FrontOp::Never))
+        .map(|(node_id, _)| node_id)
+        .unwrap();
+    // a unary operation has no right operand
+    let result = front.try_convert_ops(|op, unary| match op {
+        FrontOp::Never => Ok(expansion("!R")),
+        _ => convert(op, unary),
+    });
+    assert_eq!(
+        result,
+        Err(ConversionError::InvalidExpansion { node: never })
+    );
+    let result = front.try_convert_ops(|op, unary| match op {
+        FrontOp::Never => Ok(OpConversion::Expansion(BeTree::new())),
+        _ => convert(op, unary),
+    });
+    assert_eq!(
+        result,
+        Err(ConversionError::InvalidExpansion { node: never })
+    );
+}
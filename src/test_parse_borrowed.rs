@@ -0,0 +1,82 @@
+//! tests of the parsing into trees whose atoms borrow the input
+
+use crate::{
+    test_util::{BoolOperator::*, *},
+    *,
+};
+
+fn classify(c: char) -> CharClass<BoolOperator> {
+    match c {
+        '&' => CharClass::Operator(And),
+        '|' => CharClass::Operator(Or),
+        '!' => CharClass::Operator(Not),
+        '(' => CharClass::OpeningPar,
+        ')' => CharClass::ClosingPar,
+        '\'' => CharClass::Quote,
+        ' ' => CharClass::Space,
+        _ => CharClass::Atom,
+    }
+}
+
+/// parse the input, checking the atoms are slices of it, and return
+/// the offsets of the atoms in the input
+fn atom_ranges(input: &str) -> Vec<(usize, usize)> {
+    let expr = BeTree::parse_borrowed(input, classify).unwrap();
+    check_links(&expr);
+    let start = input.as_ptr() as usize;
+    expr.iter_atoms()
+        .map(|atom| {
+            let offset = atom.as_ptr() as usize - start;
+            assert!(
+                offset + atom.len() <= input.len(),
+                "{:?} isn't in the input",
+                atom
+            );
+            assert_eq!(&input[offset..offset + atom.len()], *atom);
+            (offset, offset + atom.len())
+        })
+        .collect()
+}
+
+#[test]
+fn atoms_are_slices_of_the_input() {
+    assert_eq!(atom_ranges("ab&(cd|!e)"), vec![(0, 2), (4, 6), (8, 9)]);
+    assert_eq!(atom_ranges(" ab  & cd "), vec![(1, 3), (7, 9)]);
+    assert_eq!(atom_ranges("!(ab)|'c d'"), vec![(2, 4), (7, 10)]);
+    assert_eq!(atom_ranges("''&é"), vec![(1, 1), (3, 5)]);
+    assert_eq!(atom_ranges(""), vec![]);
+}
+
+#[test]
+fn parsed_structure() {
+    let expr = BeTree::parse_borrowed("A & !(B|C) | D", classify).unwrap();
+    let chars = expr.into_map_atoms(|atom| atom.chars().next().unwrap());
+    assert_eq!(tokens_string(&chars), "A&!(B|C)|D");
+    check_same_eval(&chars, &parse("A & !(B|C) | D"), "ABCD");
+}
+
+#[test]
+fn parse_errors() {
+    let error = |input| BeTree::parse_borrowed(input, classify).unwrap_err();
+    assert_eq!(error("A B"), ParseError::UnexpectedAtom { offset: 2 });
+    assert_eq!(error("(A)'B'"), ParseError::UnexpectedAtom { offset: 3 });
+    assert_eq!(
+        error("A (B)"),
+        ParseError::UnexpectedOpeningPar { offset: 2 }
+    );
+    assert_eq!(
+        error("A & )"),
+        ParseError::UnexpectedClosingPar { offset: 4 }
+    );
+    assert_eq!(error("A)"), ParseError::UnexpectedClosingPar { offset: 1 });
+    assert_eq!(error("A & 'B"), ParseError::UnclosedQuote { offset: 4 });
+    assert_eq!(error("(A & B"), ParseError::UnexpectedEnd { offset: 6 });
+    assert_eq!(error("A &"), ParseError::UnexpectedEnd { offset: 3 });
+    assert_eq!(error("A ! B"), ParseError::UnexpectedOperator { offset: 2 });
+    assert_eq!(error("& A"), ParseError::UnexpectedOperator { offset: 0 });
+    assert_eq!(
+        error("A & (| B)"),
+        ParseError::UnexpectedOperator { offset: 5 }
+    );
+    assert_eq!(error("A B").to_string(), "unexpected atom at offset 2");
+}
@@ -0,0 +1,116 @@
+//! tests of the decomposition of trees in parts and of their reconstruction
+
+use {super::*, crate::test_util::*};
+
+#[test]
+fn parts_round_trip() {
+    for input in ["A", "A & B", "(A | B) & !(C | D | E)", "!!A | (B & !C)"] {
+        let expr = parse(input);
+        let rebuilt = BeTree::from_parts(expr.clone().into_parts()).unwrap();
+        check_links(&rebuilt);
+        assert_eq!(tokens_string(&rebuilt), tokens_string(&expr));
+        assert_eq!(rebuilt.to_tokens().len(), expr.to_tokens().len());
+        // a normalized tree is rebuilt exactly
+        let normalized = expr.normalize();
+        let rebuilt = BeTree::from_parts(normalized.clone().into_parts()).unwrap();
+        assert_eq!(rebuilt, normalized);
+    }
+    let empty = BeTree::<BoolOperator, char>::new();
+    let rebuilt = BeTree::from_parts(empty.clone().into_parts()).unwrap();
+    assert_eq!(rebuilt, empty);
+}
+
+#[test]
+fn parts_with_orphans() {
+    let mut expr = parse("A & M & C");
+    expr.replace_atoms_with_trees(|&c| {
+        if c == 'M' {
+            Some(parse("B | !D"))
+        } else {
+            None
+        }
+    });
+    let rebuilt = BeTree::from_parts(expr.clone().into_parts()).unwrap();
+    assert_eq!(tokens_string(&rebuilt), "A&(B|!D)&C");
+    // the rebuilt tree can be completed
+    let mut rebuilt = rebuilt;
+    assert!(rebuilt.accept_binary_operator());
+    rebuilt.push_operator(BoolOperator::Or);
+    rebuilt.push_atom('E');
+    assert_eq!(tokens_string(&rebuilt), "A&(B|!D)&C|E");
+}
+
+#[test]
+fn corrupted_parts_are_rejected() {
+    let parts = parse("(A | B) & !C").into_parts();
+    let (or_node, not_node) = match (
+        parts.nodes[parts.head].left(),
+        parts.nodes[parts.head].right(),
+    ) {
+        (Child::Node(or_node), Child::Node(not_node)) => (or_node, not_node),
+        _ => panic!("unexpected structure"),
+    };
+
+    let mut corrupted = parts.clone();
+    corrupted.nodes.clear();
+    assert_eq!(BeTree::from_parts(corrupted), Err(StructureError::NoNode));
+
+    let mut corrupted = parts.clone();
+    corrupted.head = NodeId::new(42);
+    assert_eq!(
+        BeTree::from_parts(corrupted),
+        Err(StructureError::UnknownNode(NodeId::new(42)))
+    );
+
+    let mut corrupted = parts.clone();
+    corrupted.nodes[not_node].set_left(Child::Atom(AtomId::new(42)));
+    assert_eq!(
+        BeTree::from_parts(corrupted),
+        Err(StructureError::UnknownAtom(AtomId::new(42)))
+    );
+
+    let mut corrupted = parts.clone();
+    corrupted.nodes[not_node].set_left(Child::Node(NodeId::new(42)));
+    assert_eq!(
+        BeTree::from_parts(corrupted),
+        Err(StructureError::UnknownNode(NodeId::new(42)))
+    );
+
+    let mut corrupted = parts.clone();
+    corrupted.nodes[not_node].set_parent(Some(or_node));
+    assert_eq!(
+        BeTree::from_parts(corrupted),
+        Err(StructureError::WrongParent(not_node))
+    );
+
+    let mut corrupted = parts.clone();
+    corrupted.nodes[parts.head].set_parent(Some(not_node));
+    assert_eq!(
+        BeTree::from_parts(corrupted),
+        Err(StructureError::WrongParent(parts.head))
+    );
+
+    // a node which is the child of two nodes
+    let mut corrupted = parts.clone();
+    corrupted.nodes[not_node].set_left(Child::Node(or_node));
+    assert_eq!(
+        BeTree::from_parts(corrupted),
+        Err(StructureError::WrongParent(or_node))
+    );
+
+    // a node which is both children of its parent
+    let mut corrupted = parts.clone();
+    corrupted.nodes[parts.head].set_right(Child::Node(or_node));
+    assert_eq!(
+        BeTree::from_parts(corrupted),
+        Err(StructureError::MultipleLinks(or_node))
+    );
+
+    // a cycle
+    let mut corrupted = parts;
+    corrupted.nodes[not_node].set_left(Child::Node(not_node));
+    assert_eq!(
+        BeTree::from_parts(corrupted),
+        Err(StructureError::WrongParent(not_node))
+    );
+}
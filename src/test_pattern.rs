@@ -0,0 +1,67 @@
+//! tests of the pattern matching
+
+use {super::*, crate::test_util::*, std::collections::HashMap};
+
+/// render the captured subtrees as strings of atoms
+fn captures(expr: &BeTree<BoolOperator, char>, m: &Match) -> HashMap<String, String> {
+    m.bindings
+        .iter()
+        .map(|(name, &child)| {
+            let subtree = match child {
+                Child::Node(node_id) => expr.subtree(node_id).unwrap(),
+                Child::Atom(atom_id) => parse(&expr.atom(atom_id).unwrap().to_string()),
+                Child::None => panic!("empty capture"),
+            };
+            (name.clone(), tokens_string(&subtree))
+        })
+        .collect()
+}
+
+#[test]
+fn find_not_above_or() {
+    let expr = parse("!(A | B) & !C | !((C & D) | !E)");
+    let matches = expr.find_matches(&parse_pattern("!(x | y)"));
+    assert_eq!(matches.len(), 2);
+    let first = captures(&expr, &matches[0]);
+    assert_eq!(first["x"], "A");
+    assert_eq!(first["y"], "B");
+    let second = captures(&expr, &matches[1]);
+    assert_eq!(second["x"], "C&D");
+    assert_eq!(second["y"], "!E");
+}
+
+#[test]
+fn find_repeated_wildcards() {
+    let expr = parse("(A & A) | ((B | C) & (B | C)) | (A & B) | (!A & (!A))");
+    let matches = expr.find_matches(&parse_pattern("x & x"));
+    let captured: Vec<String> = matches
+        .iter()
+        .map(|m| captures(&expr, m)["x"].clone())
+        .collect();
+    assert_eq!(captured, vec!["A", "B|C", "!A"]);
+}
+
+#[test]
+fn find_with_exact_and_any_atoms() {
+    let expr = parse("(A & B) | (A & (B | C)) | (C & B) | !(A & D)");
+    let matches = expr.find_matches(&parse_pattern("A & _"));
+    assert_eq!(matches.len(), 2);
+    assert!(matches.iter().all(|m| m.bindings.is_empty()));
+    // arity must match: a unary pattern doesn't match binary nodes
+    assert_eq!(expr.find_matches(&parse_pattern("!x")).len(), 1);
+    assert_eq!(expr.find_matches(&parse_pattern("!(A & _)")).len(), 1);
+    assert_eq!(expr.find_matches(&parse_pattern("!(B & _)")).len(), 0);
+}
+
+#[test]
+fn overlapping_matches() {
+    let expr = parse("A & B & C");
+    let matches = expr.find_matches(&parse_pattern("x & y"));
+    assert_eq!(matches.len(), 2);
+    assert_eq!(matches[0].node, expr.head);
+    assert_eq!(captures(&expr, &matches[0])["x"], "A&B");
+    assert_eq!(matches[1].binding("x"), Some(Child::Atom(AtomId::new(0))));
+    // a single wildcard matches every operator
+    assert_eq!(expr.find_matches(&parse_pattern("x")).len(), 2);
+    assert_eq!(expr.find_matches(&parse_pattern("A")).len(), 0);
+}
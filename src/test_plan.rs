@@ -0,0 +1,142 @@
+//! tests of the estimation of the work of evaluations
+
+use {super::*, crate::test_util::*};
+
+/// `R` and `S` are expensive atoms (say regular expressions), the
+/// other ones are cheap
+fn weight(c: &char) -> u64 {
+    match c {
+        'R' | 'S' => 100,
+        _ => 1,
+    }
+}
+
+fn summary(input: &str) -> PlanSummary {
+    parse(input)
+        .plan_check(weight, &PlanLimits::default())
+        .unwrap()
+}
+
+#[test]
+fn summaries() {
+    assert_eq!(summary(""), PlanSummary::default());
+    assert_eq!(
+        summary("A"),
+        PlanSummary {
+            atom_count: 1,
+            depth: 1,
+            total_weight: 1,
+            worst_case_weight: 1,
+        },
+    );
+    assert_eq!(
+        summary("(A | R) & !(B | S)"),
+        PlanSummary {
+            atom_count: 4,
+            depth: 3,
+            total_weight: 202,
+            worst_case_weight: 202,
+        },
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn shared_atoms_are_weighted_once_in_the_total() {
+    let mut expr = parse("R & A | R & B");
+    expr.dedup_atoms();
+    let summary = expr.plan_check(weight, &PlanLimits::default()).unwrap();
+    assert_eq!(summary.atom_count, 4);
+    assert_eq!(summary.total_weight, 102);
+    assert_eq!(summary.worst_case_weight, 202);
+}
+
+#[test]
+fn unreachable_atoms_are_ignored() {
+    let mut expr = parse("(A | R) & B");
+    expr.remove_atom(AtomId::new(1)).unwrap();
+    let summary = expr.plan_check(weight, &PlanLimits::default()).unwrap();
+    assert_eq!(summary.atom_count, 2);
+    assert_eq!(summary.total_weight, 2);
+    assert_eq!(summary.depth, expr.depth());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn each_limit_rejects() {
+    let mut expr = parse("R & A | R & (B | !C)");
+    expr.dedup_atoms();
+    let check = |limits: PlanLimits| expr.plan_check(weight, &limits);
+    let summary = check(PlanLimits::default()).unwrap();
+    assert_eq!(
+        summary,
+        PlanSummary {
+            atom_count: 5,
+            depth: 3,
+            total_weight: 103,
+            worst_case_weight: 203,
+        },
+    );
+    // limits equal to the measures are accepted
+    let exact = PlanLimits {
+        max_atom_count: Some(5),
+        max_depth: Some(3),
+        max_total_weight: Some(103),
+        max_worst_case_weight: Some(203),
+    };
+    assert_eq!(check(exact), Ok(summary));
+    let rejected = |limits: PlanLimits| check(limits).unwrap_err();
+    assert_eq!(
+        rejected(PlanLimits {
+            max_atom_count: Some(3),
+            ..exact
+        }),
+        PlanRejected {
+            limit: PlanLimit::AtomCount,
+            value: 5,
+            max: 3,
+        },
+    );
+    assert_eq!(
+        rejected(PlanLimits {
+            max_depth: Some(2),
+            ..exact
+        }),
+        PlanRejected {
+            limit: PlanLimit::Depth,
+            value: 3,
+            max: 2,
+        },
+    );
+    assert_eq!(
+        rejected(PlanLimits {
+            max_total_weight: Some(100),
+            ..exact
+        }),
+        PlanRejected {
+            limit: PlanLimit::TotalWeight,
+            value: 103,
+            max: 100,
+        },
+    );
+    let worst_case = rejected(PlanLimits {
+        max_worst_case_weight: Some(150),
+        ..exact
+    });
+    assert_eq!(worst_case.limit, PlanLimit::WorstCaseWeight);
+    assert_eq!(worst_case.excess(), 53);
+    assert_eq!(
+        worst_case.to_string(),
+        "worst case weight 203 exceeds the limit of 150 by 53",
+    );
+    // the first exceeded limit is reported
+    assert_eq!(
+        rejected(PlanLimits {
+            max_depth: Some(1),
+            max_worst_case_weight: Some(1),
+            ..exact
+        })
+        .limit,
+        PlanLimit::Depth,
+    );
+}
@@ -0,0 +1,64 @@
+//! tests of the polarity analysis
+
+use {
+    super::*,
+    crate::test_util::{BoolOperator::*, *},
+};
+
+fn polarities(input: &str) -> String {
+    let expr = parse(input);
+    expr.atom_polarities(|&op| op == Not)
+        .into_iter()
+        .map(|(atom_id, polarity)| {
+            let sign = match polarity {
+                Polarity::Positive => '+',
+                Polarity::Negative => '-',
+                Polarity::Mixed => '?',
+            };
+            format!("{}{}", sign, expr.atoms[atom_id])
+        })
+        .collect()
+}
+
+#[test]
+fn negation_polarities() {
+    assert_eq!(polarities("!(A & !B) | C"), "-A+B+C");
+    assert_eq!(polarities("A"), "+A");
+    assert_eq!(polarities("!!A & !(B | !!C)"), "+A-B-C");
+    assert_eq!(polarities("!(!(A & !(B | C)))"), "+A-B-C");
+    assert_eq!(polarities(""), "");
+}
+
+#[test]
+fn polarities_with_other_operators() {
+    // here, OR is considered as an implication and AND as a XOR
+    let expr = parse("!(A | B) | (C & !D)");
+    let polarities: Vec<Polarity> = expr
+        .atom_polarities_with(|op| match op {
+            Not => OperatorPolarity::Flip,
+            Or => OperatorPolarity::FlipLeft,
+            And => OperatorPolarity::Scramble,
+        })
+        .into_iter()
+        .map(|(_, polarity)| polarity)
+        .collect();
+    assert_eq!(
+        polarities,
+        vec![
+            Polarity::Negative, // A: left of two implications, and negated
+            Polarity::Positive, // B: left of an implication, and negated
+            Polarity::Mixed,
+            Polarity::Mixed,
+        ]
+    );
+    let expr = parse("A & (B & C)");
+    let polarities: Vec<Polarity> = expr
+        .atom_polarities_with(|_| OperatorPolarity::FlipRight)
+        .into_iter()
+        .map(|(_, polarity)| polarity)
+        .collect();
+    assert_eq!(
+        polarities,
+        vec![Polarity::Positive, Polarity::Negative, Polarity::Positive]
+    );
+}
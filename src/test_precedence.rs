@@ -0,0 +1,288 @@
+//! tests of the reassociation of trees according to operator precedences
+
+use {super::*, crate::test_util::*};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    Or,
+    And,
+    /// right associative
+    Pow,
+    /// unary, binding tighter than the binary operators
+    Not,
+    /// unary, binding looser than the binary operators
+    All,
+}
+
+impl Op {
+    fn of(c: char) -> Option<Self> {
+        match c {
+            '|' => Some(Self::Or),
+            '&' => Some(Self::And),
+            '^' => Some(Self::Pow),
+            '!' => Some(Self::Not),
+            '~' => Some(Self::All),
+            _ => None,
+        }
+    }
+    fn precedence(&self) -> u8 {
+        match self {
+            Self::All => 0,
+            Self::Or => 1,
+            Self::And => 2,
+            Self::Pow => 3,
+            Self::Not => 4,
+        }
+    }
+    fn is_unary(self) -> bool {
+        matches!(self, Self::Not | Self::All)
+    }
+    fn is_right_assoc(&self) -> bool {
+        *self == Self::Pow
+    }
+}
+
+/// build a tree from left to right, as the tokens are pushed
+fn left_to_right(input: &str) -> BeTree<Op, char> {
+    let mut expr = BeTree::new();
+    for c in input.chars() {
+        match c {
+            ' ' => {}
+            '(' => expr.open_par(),
+            ')' => expr.close_par(),
+            _ => match Op::of(c) {
+                Some(op) => expr.push_operator(op),
+                None => expr.push_atom(c),
+            },
+        }
+    }
+    expr
+}
+
+fn atom(c: char) -> BeTree<Op, char> {
+    let mut expr = BeTree::new();
+    expr.push_atom(c);
+    expr
+}
+
+/// parse with the shunting-yard algorithm, then build the tree
+/// from the reverse polish notation
+fn shunting_yard(input: &str) -> BeTree<Op, char> {
+    enum Item {
+        Atom(char),
+        Op(Op),
+    }
+    let mut output = Vec::new();
+    let mut stack: Vec<Option<Op>> = Vec::new(); // None for a parenthesis
+    for c in input.chars() {
+        match c {
+            ' ' => {}
+            '(' => stack.push(None),
+            ')' => {
+                while let Some(Some(op)) = stack.pop() {
+                    output.push(Item::Op(op));
+                }
+            }
+            _ => match Op::of(c) {
+                Some(op) if op.is_unary() => stack.push(Some(op)),
+                Some(op) => {
+                    while let Some(&Some(top)) = stack.last() {
+                        let pop = if top.is_unary() {
+                            top.precedence() >= op.precedence()
+                        } else {
+                            top.precedence() > op.precedence()
+                                || (top.precedence() == op.precedence() && !op.is_right_assoc())
+                        };
+                        if !pop {
+                            break;
+                        }
+                        output.push(Item::Op(top));
+                        stack.pop();
+                    }
+                    stack.push(Some(op));
+                }
+                None => output.push(Item::Atom(c)),
+            },
+        }
+    }
+    while let Some(Some(op)) = stack.pop() {
+        output.push(Item::Op(op));
+    }
+    let mut operands: Vec<BeTree<Op, char>> = Vec::new();
+    for item in output {
+        match item {
+            Item::Atom(c) => operands.push(atom(c)),
+            Item::Op(op) if op.is_unary() => {
+                let mut operand = operands.pop().unwrap();
+                operand.wrap_unary(op);
+                operands.push(operand);
+            }
+            Item::Op(op) => {
+                let right = operands.pop().unwrap();
+                let left = operands.pop().unwrap();
+                operands.push(left.combine(op, right));
+            }
+        }
+    }
+    assert_eq!(operands.len(), 1);
+    operands.pop().unwrap()
+}
+
+fn with_precedence(input: &str) -> BeTree<Op, char> {
+    let mut expr = left_to_right(input);
+    expr.apply_precedence(Op::precedence, Op::is_right_assoc);
+    check_links(&expr);
+    expr
+}
+
+fn check(input: &str, expected: &str) {
+    let expr = with_precedence(input);
+    assert!(
+        expr.structural_eq(&left_to_right(expected)),
+        "{:?} should be {:?}",
+        input,
+        expected,
+    );
+    assert!(expr.structural_eq(&shunting_yard(input)), "{:?}", input);
+}
+
+#[test]
+fn precedences() {
+    check("A", "A");
+    check("A | B & C", "A | (B & C)");
+    check("A & B | C", "A & B | C");
+    check("A | B & C ^ D | E", "A | (B & (C ^ D)) | E");
+    check("A ^ B & C ^ D", "(A ^ B) & (C ^ D)");
+    check("A | B | C & D & E", "A | B | (C & D & E)");
+}
+
+#[test]
+fn associativities() {
+    check("A ^ B ^ C", "A ^ (B ^ (C))");
+    check("A & B & C", "(A & B) & C");
+    check("A | B ^ C ^ D & E", "A | ((B ^ (C ^ D)) & E)");
+}
+
+#[test]
+fn unary_operators() {
+    check("!A & B", "(!A) & B");
+    check("A | !B & C", "A | ((!B) & C)");
+    check("!!A ^ B", "(!!A) ^ B");
+    // a unary operator of a low precedence takes the operations after it
+    check("~A & B | C", "~(A & B | C)");
+    check("A | ~B & C | D", "A | ~(B & C | D)");
+    check("~!A | B", "~((!A) | B)");
+}
+
+#[test]
+fn groups_are_barriers() {
+    check("(A | B) & C", "(A | B) & C");
+    check("A & (B | C & D) | E", "(A & (B | (C & D))) | E");
+    check("!(A | B & C)", "!(A | (B & C))");
+    check("~(A | B) & C", "~((A | B) & C)");
+    check("((A | B & C))", "A | (B & C)");
+}
+
+#[test]
+fn barriers_survive_normalization() {
+    let mut expr = left_to_right("A | B & C");
+    expr.compact();
+    expr.apply_precedence(Op::precedence, Op::is_right_assoc);
+    assert!(expr.structural_eq(&left_to_right("A | (B & C)")));
+    let mut expr = left_to_right("(A | B) & C").normalize();
+    expr.apply_precedence(Op::precedence, Op::is_right_assoc);
+    assert!(expr.structural_eq(&left_to_right("(A | B) & C")));
+}
+
+#[test]
+fn ids_are_preserved() {
+    let mut expr = left_to_right("A | B & C");
+    let atoms: Vec<char> = expr.iter_atoms().copied().collect();
+    let node_count = expr.nodes.len();
+    expr.apply_precedence(Op::precedence, Op::is_right_assoc);
+    assert_eq!(expr.iter_atoms().copied().collect::<Vec<_>>(), atoms);
+    assert_eq!(expr.nodes.len(), node_count);
+    assert_eq!(expr.op_count(), 2);
+}
+
+#[test]
+fn combined_subexpressions_are_barriers() {
+    // the right operand of the combination isn't reassociated with
+    // the operators of the left one
+    let mut expr = left_to_right("A | B").combine(Op::And, left_to_right("C | D & E"));
+    expr.apply_precedence(Op::precedence, Op::is_right_assoc);
+    check_links(&expr);
+    assert!(expr.structural_eq(&left_to_right("(A | B) & (C | (D & E))")));
+}
+
+/// a small pseudo random generator, so that the tests are reproducible
+struct Lcg(u64);
+impl Lcg {
+    fn below(&mut self, n: u64) -> u64 {
+        self.0 = self
+            .0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (self.0 >> 33) % n
+    }
+}
+
+fn random_expr(rng: &mut Lcg, depth: usize, s: &mut String) {
+    let operand_count = 1 + rng.below(4);
+    for i in 0..operand_count {
+        if i > 0 {
+            s.push(['|', '&', '^'][rng.below(3) as usize]);
+        }
+        for _ in 0..rng.below(3) {
+            s.push(['!', '~'][rng.below(2) as usize]);
+        }
+        if depth > 0 && rng.below(4) == 0 {
+            s.push('(');
+            random_expr(rng, depth - 1, s);
+            s.push(')');
+        } else {
+            s.push((b'A' + rng.below(6) as u8) as char);
+        }
+    }
+}
+
+#[test]
+fn differential_with_shunting_yard() {
+    let mut rng = Lcg(42);
+    for _ in 0..2000 {
+        let mut input = String::new();
+        random_expr(&mut rng, 3, &mut input);
+        let expected = shunting_yard(&input);
+        // the barriers don't depend on the order of the nodes in the arena
+        let built = left_to_right(&input);
+        let mut compacted = built.clone();
+        compacted.compact();
+        for mut expr in [built.clone(), compacted, built.normalize()] {
+            expr.apply_precedence(Op::precedence, Op::is_right_assoc);
+            check_links(&expr);
+            assert!(expr.structural_eq(&expected), "{:?}", input);
+            // applying the precedences again doesn't change the tree,
+            // even once normalized
+            let before = expr.clone();
+            expr.apply_precedence(Op::precedence, Op::is_right_assoc);
+            assert!(expr.structural_eq(&before), "{:?}", input);
+            let mut expr = expr.normalize();
+            expr.apply_precedence(Op::precedence, Op::is_right_assoc);
+            assert!(expr.structural_eq(&before), "{:?}", input);
+        }
+    }
+}
+
+#[test]
+fn long_chains_are_restructured() {
+    // a right associative chain is climbed as deep as it's long
+    let len = 100_000;
+    let input = vec!["A"; len].join(" ^ ");
+    let mut expr = left_to_right(&input);
+    expr.apply_precedence(Op::precedence, Op::is_right_assoc);
+    check_links(&expr);
+    assert_eq!(expr.op_count(), len - 1);
+    let head = &expr.nodes[expr.head];
+    assert!(matches!(head.left(), Child::Atom(_)));
+    assert!(matches!(head.right(), Child::Node(_)));
+}
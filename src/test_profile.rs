@@ -0,0 +1,91 @@
+//! tests of the profiled evaluation
+
+use {super::*, crate::test_util::*};
+
+fn eval_profiled(
+    expr: &BeTree<BoolOperator, char>,
+    profile: &Profile,
+    trues: &[char],
+) -> Option<bool> {
+    expr.eval_profiled(
+        profile,
+        |c| trues.contains(c),
+        |op, a, b| op.eval(a, b),
+        |op, &a| op.short_circuit(a),
+    )
+}
+
+#[test]
+fn profile_of_a_known_workload() {
+    let expr = parse("A & B | C");
+    let profile = Profile::new(&expr);
+    for trues in all_assignments("ABC") {
+        assert_eq!(eval_profiled(&expr, &profile, &trues), eval(&expr, &trues));
+    }
+    let report = profile.report();
+    assert_eq!(report.evaluations, 8);
+    // the AND short-circuits when A is false, the OR when A and B are true
+    let and = report.node(NodeId::from_index(0)).unwrap();
+    assert_eq!((and.hits, and.skips, and.short_circuits), (8, 0, 4));
+    assert_eq!(and.short_circuit_rate(), Some(0.5));
+    let or = report.node(NodeId::from_index(1)).unwrap();
+    assert_eq!((or.hits, or.skips, or.short_circuits), (8, 0, 2));
+    assert_eq!(or.short_circuit_rate(), Some(0.25));
+    let atom_counts: Vec<(usize, usize)> = report
+        .atoms
+        .iter()
+        .map(|atom| (atom.hits, atom.skips))
+        .collect();
+    assert_eq!(atom_counts, [(8, 0), (4, 4), (6, 2)]);
+    assert_eq!(
+        report.atom(AtomId::from_index(1)).unwrap().hit_rate(),
+        Some(0.5)
+    );
+}
+
+#[test]
+fn skipped_operations() {
+    let expr = parse("A | (B & !C)");
+    let profile = Profile::new(&expr);
+    eval_profiled(&expr, &profile, &['A']);
+    eval_profiled(&expr, &profile, &['B']);
+    let report = profile.report();
+    // the group isn't an operation
+    assert_eq!(report.nodes.len(), 3);
+    let hits: Vec<(usize, usize, usize)> = report
+        .nodes
+        .iter()
+        .map(|node| (node.hits, node.skips, node.short_circuits))
+        .collect();
+    assert_eq!(hits, [(2, 0, 1), (1, 1, 0), (1, 1, 0)]);
+    profile.reset();
+    let report = profile.report();
+    assert_eq!(report.evaluations, 0);
+    assert!(report.nodes.iter().all(|node| node.hits == 0));
+    assert_eq!(report.nodes[0].short_circuit_rate(), None);
+}
+
+#[test]
+fn profile_shared_between_threads() {
+    let expr = parse("A & B | C");
+    let profile = Profile::new(&expr);
+    let assignments = all_assignments("ABC");
+    std::thread::scope(|scope| {
+        for _ in 0..4 {
+            scope.spawn(|| {
+                for trues in &assignments {
+                    eval_profiled(&expr, &profile, trues);
+                }
+            });
+        }
+    });
+    let report = profile.report();
+    assert_eq!(report.evaluations, 32);
+    let short_circuits: Vec<usize> = report
+        .nodes
+        .iter()
+        .map(|node| node.short_circuits)
+        .collect();
+    assert_eq!(short_circuits, [16, 8]);
+    assert_eq!(report.atoms[2].hits, 24);
+}
@@ -0,0 +1,77 @@
+//! tests of the progress notifications during evaluation
+
+use {super::*, crate::test_util::*};
+
+/// evaluate with progress notifications, checking they're consistent,
+/// and return the result with the (evaluated, skipped) sequence
+fn eval_progress(
+    expr: &BeTree<BoolOperator, char>,
+    trues: &[char],
+) -> (Option<bool>, Vec<(usize, usize)>) {
+    let mut progresses: Vec<Progress> = Vec::new();
+    let result = expr.eval_with_progress(
+        |c| trues.contains(c),
+        |op, a, b| op.eval(a, b),
+        |op, &a| op.short_circuit(a),
+        |progress| progresses.push(progress),
+    );
+    assert_eq!(result, eval(expr, trues));
+    let total = expr.stats().atom_count;
+    let mut previous = Progress {
+        total,
+        ..Default::default()
+    };
+    for &progress in &progresses {
+        assert_eq!(progress.total, total);
+        assert!(progress.evaluated >= previous.evaluated);
+        assert!(progress.skipped >= previous.skipped);
+        assert!(progress.done() > previous.done());
+        previous = progress;
+    }
+    assert_eq!(previous.done(), total);
+    let sequence = progresses
+        .iter()
+        .map(|progress| (progress.evaluated, progress.skipped))
+        .collect();
+    (result, sequence)
+}
+
+#[test]
+fn progress_sequences() {
+    let expr = parse("(A | B) & !(C | D | E)");
+    assert_eq!(
+        eval_progress(&expr, &['A', 'D']),
+        (Some(false), vec![(1, 0), (1, 1), (2, 1), (3, 1), (3, 2)]),
+    );
+    assert_eq!(
+        eval_progress(&expr, &[]),
+        (Some(false), vec![(1, 0), (2, 0), (2, 3)]),
+    );
+    assert_eq!(
+        eval_progress(&expr, &['B']),
+        (Some(true), vec![(1, 0), (2, 0), (3, 0), (4, 0), (5, 0)]),
+    );
+}
+
+#[test]
+fn progress_of_all_assignments() {
+    let expr = parse("A & (B | !(C & D)) | !E & (F | A & B)");
+    for trues in all_assignments("ABCDEF") {
+        eval_progress(&expr, &trues);
+    }
+}
+
+#[test]
+fn progress_ignores_orphans() {
+    let mut expr = parse("(A | B) & !(C | D | E)");
+    expr.prune(NodeId::new(2)).unwrap();
+    let (_, sequence) = eval_progress(&expr, &['A']);
+    assert_eq!(sequence.last(), Some(&(1, 1)));
+}
+
+#[test]
+fn no_progress_on_empty_tree() {
+    let (result, sequence) = eval_progress(&parse(""), &[]);
+    assert_eq!(result, None);
+    assert!(sequence.is_empty());
+}
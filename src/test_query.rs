@@ -0,0 +1,135 @@
+//! tests of the search and query functions
+
+use {
+    super::*,
+    crate::test_util::{BoolOperator::*, *},
+};
+
+#[test]
+fn find_atoms_at_various_depths() {
+    // nodes: 0 is the `&`, 1 the `A | B`, 2 the `!`, 4 the `... | E`, 3 the `C | D`
+    let expr = parse("(A | B) & !(C | D | E)");
+    let a = expr.find_atom(|&c| c == 'A').unwrap();
+    assert_eq!(
+        a,
+        AtomLocation {
+            atom: AtomId::new(0),
+            node: NodeId::new(1),
+            path: vec![(NodeId::new(0), Side::Left), (NodeId::new(1), Side::Left)],
+        }
+    );
+    let d = expr.find_atom(|&c| c == 'D').unwrap();
+    assert_eq!(d.atom, AtomId::new(3));
+    assert_eq!(d.node, NodeId::new(3));
+    assert_eq!(
+        d.path,
+        vec![
+            (NodeId::new(0), Side::Right),
+            (NodeId::new(2), Side::Left),
+            (NodeId::new(4), Side::Left),
+            (NodeId::new(3), Side::Right)
+        ]
+    );
+    let e = expr.find_atom(|&c| c == 'E').unwrap();
+    assert_eq!(
+        e.path,
+        vec![
+            (NodeId::new(0), Side::Right),
+            (NodeId::new(2), Side::Left),
+            (NodeId::new(4), Side::Right)
+        ]
+    );
+    assert!(expr.find_atom(|&c| c == 'F').is_none());
+    // the first one in tree order
+    assert_eq!(
+        expr.find_atom(|&c| c > 'B').map(|l| l.atom),
+        Some(AtomId::new(2))
+    );
+    let all: Vec<AtomId> = expr
+        .find_atoms(|&c| c != 'C')
+        .iter()
+        .map(|l| l.atom)
+        .collect();
+    assert_eq!(indices(all), vec![0, 1, 3, 4]);
+}
+
+#[test]
+fn find_atom_under_unary_operators() {
+    let expr = parse("!!A");
+    let a = expr.find_atom(|&c| c == 'A').unwrap();
+    assert_eq!(a.path.len(), 3); // the head wrapper and the two negations
+    assert!(a.path.iter().all(|&(_, side)| side == Side::Left));
+    assert_eq!(a.node, a.path[2].0);
+    assert_eq!(expr.node(a.node).unwrap().left(), Child::Atom(a.atom));
+}
+
+#[test]
+fn find_atom_ignores_orphans() {
+    let mut expr = parse("(A | B) & !(C | D | E)");
+    let d = expr.find_atom(|&c| c == 'D').unwrap();
+    // the path can be used to edit the tree
+    let (not_node, _) = d.path[1];
+    expr.prune(not_node).unwrap();
+    assert!(expr.find_atom(|&c| c == 'D').is_none());
+    assert_eq!(expr.find_atoms(|_| true).len(), 2);
+    let mut expr = parse("A & M");
+    expr.replace_atoms_with_trees(|&c| if c == 'M' { Some(parse("B")) } else { None });
+    assert!(expr.find_atom(|&c| c == 'M').is_none());
+    assert_eq!(
+        expr.find_atom(|&c| c == 'B').map(|l| l.atom),
+        Some(AtomId::new(2))
+    );
+}
+
+#[test]
+fn query_parsed_expression() {
+    let expr = parse("(A | B) & !(C | D | E)");
+    assert!(expr.contains_operator(&Not));
+    assert_eq!(expr.count_operator(&Or), 3);
+    assert_eq!(expr.count_operator(&And), 1);
+    assert!(expr.any_atom(|&c| c == 'E'));
+    assert!(!expr.any_atom(|&c| c == 'F'));
+    assert!(expr.all_atoms(|c| c.is_ascii_uppercase()));
+    assert!(!expr.all_atoms(|&c| c < 'E'));
+    let empty = parse("");
+    assert!(!empty.contains_operator(&And));
+    assert!(!empty.any_atom(|_| true));
+    assert!(empty.all_atoms(|_| false));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn query_after_edits() {
+    // pruning
+    let mut expr = parse("(A | B) & !(C | D | E)");
+    expr.prune(NodeId::new(2)).unwrap();
+    assert!(!expr.contains_operator(&Not));
+    assert!(!expr.contains_operator(&And));
+    assert_eq!(expr.count_operator(&Or), 1);
+    assert!(expr.all_atoms(|&c| c < 'C'));
+    // replacing atoms with trees
+    let mut expr = parse("A & M & !C");
+    expr.replace_atoms_with_trees(|&c| if c == 'M' { Some(parse("B | D")) } else { None });
+    assert!(!expr.any_atom(|&c| c == 'M'));
+    assert!(expr.all_atoms(|&c| c != 'M'));
+    assert_eq!(expr.count_operator(&Or), 1);
+    // rewriting
+    let mut expr = parse("!!(A | A) & (B | B)");
+    let rules = [
+        RewriteRule::new(parse_pattern("!!x"), parse_pattern("x")).unwrap(),
+        RewriteRule::new(parse_pattern("x | x"), parse_pattern("x")).unwrap(),
+    ];
+    expr.rewrite(&rules, 10);
+    assert!(!expr.contains_operator(&Not));
+    assert!(!expr.contains_operator(&Or));
+    assert_eq!(expr.count_operator(&And), 1);
+    // replacing a subexpression with an atom, with a cursor
+    let mut expr = parse("A | !(B & C)");
+    let mut cursor = expr.cursor_mut();
+    assert!(cursor.move_to_right());
+    cursor.replace_with_atom('D');
+    assert!(!expr.contains_operator(&Not));
+    assert!(!expr.contains_operator(&And));
+    assert!(!expr.any_atom(|&c| c == 'B' || c == 'C'));
+    assert!(expr.all_atoms(|&c| c == 'A' || c == 'D'));
+}
@@ -0,0 +1,71 @@
+//! tests of the rendering with custom renderers
+
+use {super::*, crate::test_util::*, std::fmt};
+
+/// renders atoms as conditions of a SQL `WHERE` clause
+struct SqlRenderer;
+impl Renderer<BoolOperator, char> for SqlRenderer {
+    fn write_atom(&self, out: &mut dyn fmt::Write, atom: &char) -> fmt::Result {
+        write!(out, "{} = 'yes'", atom.to_ascii_lowercase())
+    }
+    fn write_operator(
+        &self,
+        out: &mut dyn fmt::Write,
+        operator: &BoolOperator,
+        _unary: bool,
+    ) -> fmt::Result {
+        out.write_str(match operator {
+            BoolOperator::And => "AND",
+            BoolOperator::Or => "OR",
+            BoolOperator::Not => "NOT",
+        })
+    }
+    fn write_after_unary_operator(&self, out: &mut dyn fmt::Write) -> fmt::Result {
+        out.write_char(' ')
+    }
+}
+
+/// renders atoms as terms of an Elasticsearch query string
+struct QueryStringRenderer;
+impl Renderer<BoolOperator, char> for QueryStringRenderer {
+    fn write_atom(&self, out: &mut dyn fmt::Write, atom: &char) -> fmt::Result {
+        write!(out, "tag:{}", atom.to_ascii_lowercase())
+    }
+    fn write_operator(
+        &self,
+        out: &mut dyn fmt::Write,
+        operator: &BoolOperator,
+        _unary: bool,
+    ) -> fmt::Result {
+        out.write_str(match operator {
+            BoolOperator::And => "AND",
+            BoolOperator::Or => "OR",
+            BoolOperator::Not => "-",
+        })
+    }
+}
+
+#[test]
+fn render_in_two_syntaxes() {
+    let expr = parse("(A | B) & !(C | D)");
+    assert_eq!(
+        expr.to_string_with(&SqlRenderer),
+        "(a = 'yes' OR b = 'yes') AND NOT (c = 'yes' OR d = 'yes')",
+    );
+    assert_eq!(
+        expr.to_string_with(&QueryStringRenderer),
+        "(tag:a OR tag:b) AND -(tag:c OR tag:d)",
+    );
+}
+
+#[test]
+fn render_adds_required_groups() {
+    let mut expr = parse("A & B");
+    expr.swap_children(NodeId::new(0)).unwrap();
+    expr.cursor_at_mut(NodeId::new(0))
+        .unwrap()
+        .insert_binary_above(BoolOperator::Or, Side::Left, parse("C | D"));
+    let mut s = String::new();
+    expr.render(&QueryStringRenderer, &mut s).unwrap();
+    assert_eq!(s, "tag:c OR tag:d OR (tag:b AND tag:a)");
+}
@@ -0,0 +1,345 @@
+//! tests of the rewritings
+
+use {
+    super::*,
+    crate::test_util::{BoolOperator::*, *},
+};
+
+/// evaluate an expression where a lowercase atom is the
+/// negation of the uppercase one
+fn eval_cased(expr: &BeTree<BoolOperator, char>, trues: &[char]) -> Option<bool> {
+    expr.eval(
+        |c| {
+            if c.is_ascii_lowercase() {
+                !trues.contains(&c.to_ascii_uppercase())
+            } else {
+                trues.contains(c)
+            }
+        },
+        |op, a, b| op.eval(a, b),
+        |op, &a| op.short_circuit(a),
+    )
+}
+
+fn dual(op: &BoolOperator) -> Option<BoolOperator> {
+    match op {
+        And => Some(Or),
+        Or => Some(And),
+        Not => None,
+    }
+}
+
+/// check there's no reachable Not above a node with an operator
+fn check_nnf(expr: &BeTree<BoolOperator, char>) {
+    let mut stack = vec![expr.head];
+    while let Some(node_idx) = stack.pop() {
+        let node = &expr.nodes[node_idx];
+        for child in [node.left(), node.right()] {
+            if let Child::Node(child_idx) = child {
+                if node.operator == Some(Not) {
+                    assert!(expr.nodes[child_idx].operator.is_none());
+                }
+                stack.push(child_idx);
+            }
+        }
+    }
+}
+
+#[test]
+fn push_down_unary_de_morgan() {
+    let inputs = [
+        "!(A & B)",
+        "!(A | B)",
+        "!!A",
+        "(A | B) & !(C | D | E)",
+        "!(!((A|B)&(B|C)&A)) & !D & (E | (A|E))",
+        "!(A & !(B | !C))",
+        "A",
+        "!A",
+    ];
+    for input in inputs {
+        let original = parse(input);
+        // first version: negated atoms are replaced with lowercase atoms
+        let mut expr = original.clone();
+        expr.push_down_unary(&Not, dual, |c| NegatedAtom::Replace(c.to_ascii_lowercase()));
+        check_links(&expr);
+        assert!(!expr.contains_not(), "remaining negation in {:?}", input);
+        for trues in all_assignments("ABCDE") {
+            assert_eq!(eval_cased(&expr, &trues), eval(&original, &trues));
+        }
+        // second version: negations are kept above the atoms
+        let mut expr = original.clone();
+        expr.push_down_unary(&Not, dual, |_| NegatedAtom::Keep);
+        check_links(&expr);
+        check_nnf(&expr);
+        check_same_eval(&expr, &original, "ABCDE");
+    }
+}
+
+#[test]
+fn push_down_unary_stops_without_dual() {
+    let original = parse("!(A & B) | !(C | D)");
+    let mut expr = original.clone();
+    // Or has no declared dual
+    expr.push_down_unary(
+        &Not,
+        |op| if *op == And { Some(Or) } else { None },
+        |_| NegatedAtom::Keep,
+    );
+    check_links(&expr);
+    check_same_eval(&expr, &original, "ABCD");
+    assert_eq!(expr.op_count, 6);
+}
+
+impl BeTree<BoolOperator, char> {
+    fn contains_not(&self) -> bool {
+        let mut stack = vec![self.head];
+        while let Some(node_idx) = stack.pop() {
+            let node = &self.nodes[node_idx];
+            if node.operator == Some(Not) {
+                return true;
+            }
+            for child in [node.left(), node.right()] {
+                if let Child::Node(child_idx) = child {
+                    stack.push(child_idx);
+                }
+            }
+        }
+        false
+    }
+}
+
+/// check the expression is a chain of `outer` operations over
+/// chains of `inner` operations over literals, and return the
+/// number of clauses
+fn check_normal_form(
+    expr: &BeTree<BoolOperator, char>,
+    outer: BoolOperator,
+    inner: BoolOperator,
+) -> usize {
+    fn chain_operands(
+        expr: &BeTree<BoolOperator, char>,
+        child: Child,
+        op: BoolOperator,
+    ) -> Vec<Child> {
+        if let Child::Node(node_idx) = child {
+            let node = &expr.nodes[node_idx];
+            if node.operator == Some(op) && !node.unary {
+                let mut operands = chain_operands(expr, node.left(), op);
+                // chains are left leaning
+                assert!(
+                    !matches!(node.right(), Child::Node(idx) if expr.nodes[idx].operator == Some(op))
+                );
+                operands.push(node.right());
+                return operands;
+            }
+        }
+        vec![child]
+    }
+    let head = &expr.nodes[expr.head];
+    let root = if head.operator.is_none() {
+        head.left()
+    } else {
+        Child::Node(expr.head)
+    };
+    let clauses = chain_operands(expr, root, outer);
+    for clause in &clauses {
+        for literal in chain_operands(expr, *clause, inner) {
+            match literal {
+                Child::Atom(_) => {}
+                Child::Node(node_idx) => {
+                    let node = &expr.nodes[node_idx];
+                    assert_eq!(node.operator, Some(Not));
+                    assert!(matches!(node.left(), Child::Atom(_)));
+                }
+                Child::None => panic!("missing literal"),
+            }
+        }
+    }
+    clauses.len()
+}
+
+#[test]
+fn normal_forms() {
+    let cases = [
+        ("A", 1, 1),
+        ("A & B", 1, 2),
+        ("A | B", 2, 1),
+        ("(A | B) & !(C | D | E)", 2, 4),
+        ("(A | B) & (C | D)", 4, 2),
+        ("(A & B) | (C & D)", 2, 4),
+        ("(A | B) & (B | C) & A & !D & (E | A | E)", 12, 5),
+        ("A & (B | (C & !D))", 2, 3),
+    ];
+    for (input, dnf_clauses, cnf_clauses) in cases {
+        let mut expr = parse(input);
+        expr.push_down_unary(&Not, dual, |_| NegatedAtom::Keep);
+        let dnf = expr.to_dnf(&And, &Or, 100).unwrap();
+        check_links(&dnf);
+        assert_eq!(
+            check_normal_form(&dnf, Or, And),
+            dnf_clauses,
+            "DNF of {:?}",
+            input
+        );
+        check_same_eval(&dnf, &expr, "ABCDE");
+        let cnf = expr.to_cnf(&And, &Or, 100).unwrap();
+        check_links(&cnf);
+        assert_eq!(
+            check_normal_form(&cnf, And, Or),
+            cnf_clauses,
+            "CNF of {:?}",
+            input
+        );
+        check_same_eval(&cnf, &expr, "ABCDE");
+    }
+}
+
+#[test]
+fn normal_form_size_limit() {
+    // the DNF of this expression has 2^5 clauses of 5 literals
+    let expr = parse("(A | B) & (C | D) & (E | F) & (G | H) & (I | J)");
+    assert!(expr.to_dnf(&And, &Or, 159).is_none());
+    let dnf = expr.to_dnf(&And, &Or, 160).unwrap();
+    assert_eq!(check_normal_form(&dnf, Or, And), 32);
+    // but the CNF is the expression itself
+    let cnf = expr.to_cnf(&And, &Or, 10).unwrap();
+    assert_eq!(check_normal_form(&cnf, And, Or), 5);
+    assert!(BeTree::<BoolOperator, char>::new()
+        .to_dnf(&And, &Or, 0)
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn canonicalize_permutations() {
+    let permutations = [
+        "A & B & C",
+        "C & A & B",
+        "B & (C & A)",
+        "(C & B) & A",
+        "C & (B & (A))",
+    ];
+    let expected = "A&B&C";
+    for input in permutations {
+        let mut expr = parse(input);
+        expr.canonicalize(|_| true, |&c| c);
+        check_links(&expr);
+        assert_eq!(
+            tokens_string(&expr),
+            expected,
+            "canonical form of {:?}",
+            input
+        );
+    }
+    let permutations = [
+        "(A | B) & !(C | D | E)",
+        "!(E | D | C) & (B | A)",
+        "!(D | (E | C)) & (B | A)",
+        "(B | A) & !(C | E | D)",
+    ];
+    let mut canonical = None;
+    for input in permutations {
+        let mut expr = parse(input);
+        expr.canonicalize(|_| true, |&c| c);
+        check_links(&expr);
+        check_same_eval(&expr, &parse(input), "ABCDE");
+        let tokens = tokens_string(&expr);
+        assert_eq!(canonical.get_or_insert_with(|| tokens.clone()), &tokens);
+    }
+}
+
+#[test]
+fn canonicalize_keeps_non_commutative_operators() {
+    let mut expr = parse("(B | A) & (D | C)");
+    expr.canonicalize(|op| *op == Or, |&c| c);
+    assert_eq!(tokens_string(&expr), "A|B&(C|D)");
+    let mut expr = parse("(D | C) & (B | A)");
+    expr.canonicalize(|op| *op == Or, |&c| c);
+    assert_eq!(tokens_string(&expr), "C|D&(A|B)");
+    // sorting on a custom key: reverse order
+    let mut expr = parse("A | C | B");
+    expr.canonicalize(|_| true, |&c| std::cmp::Reverse(c));
+    assert_eq!(tokens_string(&expr), "C|B|A");
+}
+
+#[test]
+fn canonicalize_orders_operators_by_their_ord() {
+    // OR comes before NOT in the declaration order of the operators,
+    // and so in their `Ord`, but not alphabetically
+    for input in ["!C & (A | B)", "(A | B) & !C"] {
+        let mut expr = parse(input);
+        expr.canonicalize(|op| *op == And, |&c| c);
+        assert_eq!(tokens_string(&expr), "A|B&!C", "{}", input);
+    }
+}
+
+fn depth<Atom: std::fmt::Debug + Clone>(expr: &BeTree<BoolOperator, Atom>) -> usize {
+    let mut max_depth = 0;
+    let mut stack = vec![(Child::Node(expr.head), 1)];
+    while let Some((child, depth)) = stack.pop() {
+        max_depth = max_depth.max(depth);
+        if let Child::Node(node_idx) = child {
+            let node = &expr.nodes[node_idx];
+            stack.push((node.left(), depth + 1));
+            stack.push((node.right(), depth + 1));
+        }
+    }
+    max_depth
+}
+
+#[test]
+fn rebalance_long_chain() {
+    let mut expr = BeTree::new();
+    for i in 0..1000 {
+        if i > 0 {
+            expr.push_operator(Or);
+        }
+        expr.push_atom(i);
+    }
+    let original = expr.clone();
+    assert_eq!(depth(&expr), 1000);
+    expr.rebalance_chains(&Or);
+    check_links(&expr);
+    assert!(depth(&expr) <= 13, "depth: {}", depth(&expr));
+    assert_eq!(expr.op_count, original.op_count);
+    // same result and same evaluation order
+    let eval_order = |expr: &BeTree<BoolOperator, i32>| {
+        let evaluated = std::cell::RefCell::new(Vec::new());
+        let result = expr.eval(
+            |&i| {
+                evaluated.borrow_mut().push(i);
+                i % 300 == 299
+            },
+            |op, a, b| op.eval(a, b),
+            |op, &a| op.short_circuit(a),
+        );
+        (result, evaluated.into_inner())
+    };
+    let (result, order) = eval_order(&expr);
+    assert_eq!(result, Some(true));
+    assert_eq!(order, (0..300).collect::<Vec<_>>());
+    assert_eq!(eval_order(&original), (result, order));
+}
+
+#[test]
+fn rebalance_nested_chains() {
+    let inputs = [
+        "A & B & C & D & E",
+        "A | (B & C & D & E & A) | !(C & D & (E & B)) | E",
+        "(A & B) & (C & (D & E))",
+        "!(A & B & C & D)",
+        "A",
+    ];
+    for input in inputs {
+        let original = parse(input);
+        let mut expr = original.clone();
+        expr.rebalance_chains(&And);
+        check_links(&expr);
+        check_same_eval(&expr, &original, "ABCDE");
+        assert_eq!(expr.op_count, original.op_count);
+    }
+    let mut expr = parse("A & B & C & D & E & A & B & C");
+    expr.rebalance_chains(&And);
+    assert_eq!(depth(&expr), 4); // 3 levels of And, then the atoms
+}
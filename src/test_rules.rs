@@ -0,0 +1,77 @@
+//! tests of the rewriting with rules
+
+use {
+    super::*,
+    crate::test_util::{BoolOperator::*, *},
+};
+
+fn rule(pattern: &str, replacement: &str) -> RewriteRule<BoolOperator, char> {
+    RewriteRule::new(parse_pattern(pattern), parse_pattern(replacement)).unwrap()
+}
+
+fn simplification_rules() -> Vec<RewriteRule<BoolOperator, char>> {
+    vec![
+        rule("!!x", "x"),
+        rule("x & x", "x"),
+        rule("x | x", "x"),
+        rule("x | (x & y)", "x"),
+    ]
+}
+
+#[test]
+fn rewrite_redundant_expression() {
+    let original = parse("!!(A & A) | (B | (B & C))");
+    let mut expr = original.clone();
+    let report = expr.rewrite(&simplification_rules(), 10);
+    check_links(&expr);
+    check_same_eval(&expr, &original, "ABC");
+    assert_eq!(report.outcome, RewriteOutcome::Fixpoint);
+    assert_eq!(report.fired, vec![1, 1, 0, 1]);
+    assert_eq!(report.passes, 3);
+    assert_eq!(expr.op_count, 1);
+    assert_eq!(expr.normalize(), parse("A | B").normalize());
+}
+
+#[test]
+fn rewrite_down_to_an_atom() {
+    let original = parse("!!!!((A | A) & !!(A | (A & B)))");
+    let mut expr = original.clone();
+    let report = expr.rewrite(&simplification_rules(), 10);
+    check_links(&expr);
+    check_same_eval(&expr, &original, "AB");
+    assert_eq!(report.outcome, RewriteOutcome::Fixpoint);
+    assert_eq!(expr.op_count, 0);
+    assert_eq!(
+        expr.clone().normalize().iter_atoms().collect::<String>(),
+        "A"
+    );
+    // the expression can still be extended
+    expr.push_operator(And);
+    expr.push_atom('B');
+    check_links(&expr);
+    check_same_eval(&expr, &parse("A & B"), "AB");
+}
+
+#[test]
+fn rewrite_stops() {
+    // commutation undoes itself
+    let mut expr = parse("A & B");
+    let report = expr.rewrite(&[rule("x & y", "y & x")], 10);
+    assert_eq!(report.outcome, RewriteOutcome::Cycle);
+    assert_eq!(report.passes, 2);
+    // double negation added forever
+    let mut expr = parse("!A");
+    let report = expr.rewrite(&[rule("!x", "!!!x")], 5);
+    assert_eq!(report.outcome, RewriteOutcome::PassLimit);
+    assert_eq!(report.passes, 5);
+    assert_eq!(report.fired, vec![5]);
+    check_same_eval(&expr, &parse("!A"), "A");
+}
+
+#[test]
+fn invalid_rules() {
+    assert!(RewriteRule::new(parse_pattern("x & y"), parse_pattern("z")).is_none());
+    assert!(RewriteRule::new(parse_pattern("_ & y"), parse_pattern("_")).is_none());
+    assert!(RewriteRule::new(parse_pattern("x & y"), BeTree::new()).is_none());
+    assert!(RewriteRule::new(parse_pattern("x & A"), parse_pattern("x | B")).is_some());
+}
@@ -0,0 +1,101 @@
+//! tests of the rendering with escaped atoms
+
+use {super::*, crate::test_util::*, std::fmt::Write};
+
+/// renders atoms as comparisons of the `name` column in a SQL `WHERE` clause
+struct SqlRenderer;
+impl SafeRenderer<BoolOperator, String> for SqlRenderer {
+    fn render_atom(&self, atom: &String) -> Escaped {
+        Escaped::trusted("name = ").then(QuoteDoubling::SINGLE.escape(atom))
+    }
+    fn operator(&self, operator: &BoolOperator, _unary: bool) -> &'static str {
+        match operator {
+            BoolOperator::And => "AND",
+            BoolOperator::Or => "OR",
+            BoolOperator::Not => "NOT",
+        }
+    }
+    fn unary_separator(&self) -> &'static str {
+        " "
+    }
+}
+
+/// renders atoms as terms of a Lucene query string
+struct LuceneRenderer;
+impl SafeRenderer<BoolOperator, String> for LuceneRenderer {
+    fn render_atom(&self, atom: &String) -> Escaped {
+        Escaped::trusted("tag:").then(BackslashEscaping::LUCENE.escape(atom))
+    }
+    fn operator(&self, operator: &BoolOperator, _unary: bool) -> &'static str {
+        match operator {
+            BoolOperator::And => "AND",
+            BoolOperator::Or => "OR",
+            BoolOperator::Not => "-",
+        }
+    }
+}
+
+/// parse the expression, then replace its atoms with the given texts
+fn with_atoms(input: &str, texts: &[&str]) -> BeTree<BoolOperator, String> {
+    parse(input).map_atoms_indexed(|atom_id, _| texts[atom_id.index()].to_string())
+}
+
+#[test]
+fn plain_atoms() {
+    let expr = with_atoms("(A | B) & !C", &["rust", "go", "c"]);
+    assert_eq!(
+        expr.to_safe_string(&SqlRenderer),
+        "(name = 'rust' OR name = 'go') AND NOT name = 'c'",
+    );
+    assert_eq!(
+        expr.to_safe_string(&LuceneRenderer),
+        "(tag:rust OR tag:go) AND -tag:c",
+    );
+}
+
+#[test]
+fn atoms_with_metacharacters() {
+    let texts = [
+        "O'Brien",
+        "x') OR (1=1",
+        "a & (b | !c)",
+        "say \"hi\"",
+        "C:\\dir",
+    ];
+    let expr = with_atoms("A | B & !(C | D) | E", &texts);
+    assert_eq!(
+        expr.to_safe_string(&SqlRenderer),
+        "((name = 'O''Brien' OR name = 'x'') OR (1=1') AND NOT (name = 'a & (b | !c)' \
+        OR name = 'say \"hi\"')) OR name = 'C:\\dir'",
+    );
+    assert_eq!(
+        expr.to_safe_string(&LuceneRenderer),
+        "((tag:O'Brien OR tag:x'\\)\\ OR\\ \\(1=1) AND -(tag:a\\ \\&\\ \\(b\\ \\|\\ \\!c\\) \
+        OR tag:say\\ \\\"hi\\\")) OR tag:C\\:\\\\dir",
+    );
+}
+
+#[test]
+fn escape_policies() {
+    assert_eq!(QuoteDoubling::SINGLE.escape("").as_str(), "''");
+    assert_eq!(QuoteDoubling::SINGLE.escape("''").as_str(), "''''''");
+    let double = QuoteDoubling { quote: '"' };
+    assert_eq!(double.escape("a \"b\" 'c'").as_str(), "\"a \"\"b\"\" 'c'\"");
+    let custom = BackslashEscaping { special: "%_" };
+    assert_eq!(custom.escape("100%_\\").into_string(), "100\\%\\_\\\\");
+    // any function can be wrapped as a policy
+    let hex = EscapeFn(|raw: &str, out: &mut String| {
+        for b in raw.bytes() {
+            let _ = write!(out, "{:02x}", b);
+        }
+    });
+    assert_eq!(hex.escape("a(").to_string(), "6128");
+}
+
+#[test]
+fn render_safe_into_writer() {
+    let expr = with_atoms("!(A & B)", &["a'", "b"]);
+    let mut s = String::from("WHERE ");
+    expr.render_safe(&SqlRenderer, &mut s).unwrap();
+    assert_eq!(s, "WHERE NOT (name = 'a''' AND name = 'b')");
+}
@@ -0,0 +1,210 @@
+//! tests of the serde support
+
+use {super::*, crate::test_util::*};
+
+fn json_round_trip<Atom>(expr: &BeTree<BoolOperator, Atom>) -> BeTree<BoolOperator, Atom>
+where
+    Atom: std::fmt::Debug + Clone + PartialEq + serde::Serialize + serde::de::DeserializeOwned,
+{
+    let json = serde_json::to_string(expr).unwrap();
+    let read: BeTree<BoolOperator, Atom> = serde_json::from_str(&json).unwrap();
+    check_links(&read);
+    assert_eq!(&read, expr);
+    read
+}
+
+fn bincode_round_trip<Atom>(expr: &BeTree<BoolOperator, Atom>) -> BeTree<BoolOperator, Atom>
+where
+    Atom: std::fmt::Debug + Clone + PartialEq + serde::Serialize + serde::de::DeserializeOwned,
+{
+    let bytes = bincode::serialize(expr).unwrap();
+    let read: BeTree<BoolOperator, Atom> = bincode::deserialize(&bytes).unwrap();
+    check_links(&read);
+    assert_eq!(&read, expr);
+    read
+}
+
+#[test]
+fn serde_round_trip() {
+    let inputs = ["", "A", "!A", "(A | B) & !(C | D | E)", "A & (B |", "A & !"];
+    for input in inputs {
+        let expr = parse(input);
+        json_round_trip(&expr);
+        bincode_round_trip(&expr);
+    }
+}
+
+#[test]
+fn serde_round_trip_keeps_the_building_state() {
+    let expr = parse("A & (B |");
+    let mut read = json_round_trip(&expr);
+    read.push_atom('C');
+    read.close_par();
+    read.push_operator(BoolOperator::Or);
+    read.push_atom('D');
+    check_links(&read);
+    check_same_eval(&read, &parse("A & (B | C) | D"), "ABCD");
+}
+
+#[test]
+fn serde_round_trip_with_orphans() {
+    let mut expr = parse("A & (B | C) & D");
+    expr.cursor_at_mut(NodeId::new(1))
+        .unwrap()
+        .replace_with_atom('E');
+    assert!(expr.memory_usage().nodes_len > expr.memory_usage().reachable_nodes);
+    json_round_trip(&expr);
+    bincode_round_trip(&expr);
+}
+
+#[test]
+fn serde_round_trip_with_unicode_atoms() {
+    let expr = parse("A & !(B | C)").map_atoms_indexed(|_, c| match c {
+        'A' => "été".to_string(),
+        'B' => "日本語".to_string(),
+        _ => "🦀 \"quoted\"".to_string(),
+    });
+    json_round_trip(&expr);
+    bincode_round_trip(&expr);
+}
+
+#[test]
+fn serde_tokens_and_children() {
+    let token: Token<BoolOperator, char> = Token::Operator(BoolOperator::Not);
+    let json = serde_json::to_string(&token).unwrap();
+    assert_eq!(json, r#"{"Operator":"Not"}"#);
+    let read: Token<BoolOperator, char> = serde_json::from_str(&json).unwrap();
+    assert!(matches!(read, Token::Operator(BoolOperator::Not)));
+    let child: Child = serde_json::from_str(r#"{"Atom":3}"#).unwrap();
+    assert_eq!(child, Child::Atom(AtomId::new(3)));
+}
+
+#[test]
+fn serde_rejects_corrupted_trees() {
+    let valid = serde_json::to_string(&parse("A & !B")).unwrap();
+    assert_eq!(
+        valid,
+        r#"{"atoms":["A","B"],"nodes":[{"operator":"And","parent":null,"left":{"Atom":0},"right":{"Node":1},"unary":false,"explicit":false},{"operator":"Not","parent":0,"left":{"Atom":1},"right":"None","unary":true,"explicit":false}],"head":0,"tail":1,"last_pushed":"Atom","openness":0}"#
+    );
+    let corruptions = [
+        // head out of the arena
+        (
+            r#""head":0,"tail""#,
+            r#""head":7,"tail""#,
+            "no node with id 7",
+        ),
+        // atom out of the arena
+        (r#"{"Atom":1}"#, r#"{"Atom":5}"#, "no atom with id 5"),
+        // node linked twice
+        (
+            r#""left":{"Atom":1}"#,
+            r#""left":{"Node":1}"#,
+            "wrong parent",
+        ),
+        // tail out of the arena
+        (r#""tail":1"#, r#""tail":3"#, "no node with id 3"),
+        // wrong count of open parenthesis
+        (r#""openness":0"#, r#""openness":2"#, "open"),
+    ];
+    for (from, to, message) in corruptions {
+        let corrupted = valid.replace(from, to);
+        assert_ne!(corrupted, valid);
+        let err = serde_json::from_str::<BeTree<BoolOperator, char>>(&corrupted).unwrap_err();
+        assert!(
+            err.to_string().contains(message),
+            "{} doesn't contain {:?}",
+            err,
+            message
+        );
+    }
+    // an orphan linking outside of the arena
+    let mut expr = parse("A & (B | C)");
+    expr.cursor_at_mut(NodeId::new(1))
+        .unwrap()
+        .replace_with_atom('D');
+    let json = serde_json::to_string(&expr)
+        .unwrap()
+        .replace(r#"{"Atom":2}"#, r#"{"Atom":9}"#);
+    assert!(serde_json::from_str::<BeTree<BoolOperator, char>>(&json).is_err());
+}
+
+#[test]
+fn serde_round_trip_keeps_the_explicit_groups() {
+    let expr = parse("(A & B) | ((C))");
+    let read = json_round_trip(&expr);
+    assert_eq!(read.display_as_written().to_string(), "(A & B) | ((C))");
+    let read = bincode_round_trip(&expr);
+    assert_eq!(read.display_as_written().to_string(), "(A & B) | ((C))");
+    // trees serialized without the flag have no explicit group
+    let json = serde_json::to_string(&expr)
+        .unwrap()
+        .replace(r#","explicit":true"#, "")
+        .replace(r#","explicit":false"#, "");
+    let read: BeTree<BoolOperator, char> = serde_json::from_str(&json).unwrap();
+    assert_eq!(read.display_as_written().to_string(), "A & B | C");
+    assert!(read.structural_eq(&expr));
+}
+
+/// the layout of the nodes before the explicit group flag
+#[derive(serde::Serialize)]
+struct NodeWithoutExplicitFlag {
+    operator: Option<BoolOperator>,
+    parent: Option<NodeId>,
+    left: Child,
+    right: Child,
+    unary: bool,
+}
+
+/// the layout of the trees before the explicit group flag
+#[derive(serde::Serialize)]
+struct TreeWithoutExplicitFlags {
+    atoms: Vec<char>,
+    nodes: Vec<NodeWithoutExplicitFlag>,
+    head: NodeId,
+    tail: NodeId,
+    last_pushed: TokenType,
+    openness: usize,
+}
+
+#[test]
+fn serde_format_without_explicit_flags() {
+    let expr = parse("(A & B) | !C");
+    let old = TreeWithoutExplicitFlags {
+        atoms: expr.atoms.to_vec(),
+        nodes: expr
+            .nodes
+            .iter()
+            .map(|node| NodeWithoutExplicitFlag {
+                operator: node.operator,
+                parent: node.parent(),
+                left: node.left(),
+                right: node.right(),
+                unary: node.unary,
+            })
+            .collect(),
+        head: expr.head,
+        tail: expr.tail,
+        last_pushed: expr.last_pushed,
+        openness: expr.openness,
+    };
+    // self-describing formats read the old layout
+    let json = serde_json::to_string(&old).unwrap();
+    let read: BeTree<BoolOperator, char> = serde_json::from_str(&json).unwrap();
+    assert!(read.structural_eq(&expr));
+    assert_eq!(read.display_as_written().to_string(), "A & B | !C");
+    // but the layout of the nodes changed for bincode
+    let bytes = bincode::serialize(&old).unwrap();
+    assert!(bincode::deserialize::<BeTree<BoolOperator, char>>(&bytes).is_err());
+}
+
+/// `A & !B` as serialized before the explicit group flag
+const GOLDEN_JSON_WITHOUT_EXPLICIT_FLAGS: &str = r#"{"atoms":["A","B"],"nodes":[{"operator":"And","parent":null,"left":{"Atom":0},"right":{"Node":1},"unary":false},{"operator":"Not","parent":0,"left":{"Atom":1},"right":"None","unary":true}],"head":0,"tail":1,"last_pushed":"Atom","openness":0}"#;
+
+#[test]
+fn serde_reads_the_golden_json_without_explicit_flags() {
+    let read: BeTree<BoolOperator, char> =
+        serde_json::from_str(GOLDEN_JSON_WITHOUT_EXPLICIT_FLAGS).unwrap();
+    assert_eq!(read, parse("A & !B"));
+    assert!(read.nodes.iter().all(|node| !node.is_explicit_group()));
+    check_links(&read);
+}
@@ -0,0 +1,127 @@
+//! tests of the S-expression export and import
+
+use {super::*, crate::test_util::*};
+
+fn to_sexpr(expr: &BeTree<BoolOperator, char>) -> String {
+    expr.to_sexpr(|op| op.to_string(), |c| c.to_string())
+}
+
+fn parse_op(s: &str) -> Option<BoolOperator> {
+    match s {
+        "&" => Some(BoolOperator::And),
+        "|" => Some(BoolOperator::Or),
+        "!" => Some(BoolOperator::Not),
+        _ => None,
+    }
+}
+
+fn from_sexpr(input: &str) -> Result<BeTree<BoolOperator, char>, SexprError> {
+    BeTree::from_sexpr(input, parse_op, |s| {
+        let mut chars = s.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Some(c),
+            _ => None,
+        }
+    })
+}
+
+#[test]
+fn sexpr_rendering() {
+    let cases = [
+        ("", "()"),
+        ("A", "A"),
+        ("((A))", "A"),
+        ("!A", "(! A)"),
+        ("(A | B) & !C", "(& (| A B) (! C))"),
+        ("A & B | C", "(| (& A B) C)"),
+        ("A & (B | !(C & D))", "(& A (| B (! (& C D))))"),
+        ("A |", "(| A ())"),
+        ("!", "(! ())"),
+    ];
+    for (input, sexpr) in cases {
+        assert_eq!(to_sexpr(&parse(input)), sexpr);
+    }
+}
+
+#[test]
+fn sexpr_round_trip() {
+    let inputs = [
+        "",
+        "A",
+        "!A",
+        "(A | B) & !(C | D | E)",
+        "!(!((A|B)&(B|C)&A)) & !D & (E | (A|E))",
+        "A & B | C & D",
+        "A & (B | C & D)",
+        "A & !",
+    ];
+    for input in inputs {
+        let expr = parse(input);
+        let sexpr = to_sexpr(&expr);
+        let read = from_sexpr(&sexpr).unwrap();
+        check_links(&read);
+        assert!(same_structure(&expr, &read), "{} -> {}", input, sexpr);
+        assert_eq!(to_sexpr(&read), sexpr);
+    }
+}
+
+#[test]
+fn sexpr_quoting() {
+    let expr = parse("A & (B | C)").map_atoms_indexed(|_, c| match c {
+        'A' => "size > 5T".to_string(),
+        'B' => "say \"hi\"".to_string(),
+        'C' => "(été)".to_string(),
+        _ => String::new(),
+    });
+    let sexpr = expr.to_sexpr(|op| op.to_string(), |s| s.clone());
+    assert_eq!(sexpr, r#"(& "size > 5T" (| "say \"hi\"" "(été)"))"#);
+    let read = BeTree::from_sexpr(&sexpr, parse_op, |s| Some(s.to_string())).unwrap();
+    check_links(&read);
+    assert_eq!(read.atoms, expr.atoms);
+    assert_eq!(read.to_sexpr(|op| op.to_string(), |s| s.clone()), sexpr);
+}
+
+#[test]
+fn sexpr_errors() {
+    let cases = [
+        ("(& A", SexprError::UnexpectedEnd { offset: 4 }),
+        ("", SexprError::UnexpectedEnd { offset: 0 }),
+        (")", SexprError::UnexpectedClosingPar { offset: 0 }),
+        ("(& A \"B)", SexprError::UnclosedString { offset: 5 }),
+        ("(& A B C)", SexprError::WrongOperandCount { offset: 0 }),
+        ("(! (&))", SexprError::WrongOperandCount { offset: 3 }),
+        ("(& () B)", SexprError::MissingOperand { offset: 6 }),
+        (
+            "(& (| A B) (? C))",
+            SexprError::InvalidOperator { offset: 12 },
+        ),
+        ("((A) B)", SexprError::InvalidOperator { offset: 1 }),
+        ("(& A BC)", SexprError::InvalidAtom { offset: 5 }),
+        ("(& A B) C", SexprError::TrailingInput { offset: 8 }),
+    ];
+    for (input, error) in cases {
+        assert_eq!(from_sexpr(input).unwrap_err(), error, "input: {:?}", input);
+    }
+    assert_eq!(
+        SexprError::InvalidAtom { offset: 5 }.to_string(),
+        "invalid atom at offset 5"
+    );
+}
+
+#[test]
+fn sexpr_keeps_empty_groups() {
+    assert_eq!(to_sexpr(&parse("A & ()")), "(& A (()))");
+    assert_eq!(to_sexpr(&parse("!()")), "(! (()))");
+    assert_eq!(to_sexpr(&parse("A &")), "(& A ())");
+    for input in ["()", "(())", "A & ()", "!()", "() | A & !(())"] {
+        let expr = parse(input);
+        let read = from_sexpr(&to_sexpr(&expr)).unwrap();
+        check_same_empty_groups(&read, &expr);
+        assert_eq!(to_sexpr(&read), to_sexpr(&expr));
+    }
+    assert!(from_sexpr("(& A ( ( ) ))").unwrap().validate().is_err());
+    assert_eq!(
+        from_sexpr("(& A (() B))").unwrap_err(),
+        SexprError::InvalidOperator { offset: 6 },
+    );
+}
@@ -0,0 +1,89 @@
+//! tests of the shared trees
+
+use {super::*, crate::test_util::*, std::sync::Arc};
+
+fn eval_shared(expr: &SharedBeTree<BoolOperator, char>, trues: &[char]) -> Option<bool> {
+    expr.eval(
+        |c| trues.contains(c),
+        |op, a, b| op.eval(a, b),
+        |op, &a| op.short_circuit(a),
+    )
+}
+
+fn node(child: &SharedChild<BoolOperator, char>) -> &Arc<SharedNode<BoolOperator, char>> {
+    match child {
+        SharedChild::Node(node) => node,
+        _ => panic!("not a node"),
+    }
+}
+
+#[test]
+fn shared_tree_evaluates_as_plain_tree() {
+    let inputs = [
+        "A",
+        "(A | B) & !(C | D | E)",
+        "!(!((A|B)&(B|C)&A)) & !D & (E | (A|E))",
+        "((A))",
+    ];
+    for input in inputs {
+        let plain = parse(input);
+        let shared = plain.clone().shared();
+        for trues in all_assignments("ABCDE") {
+            assert_eq!(eval_shared(&shared, &trues), eval(&plain, &trues));
+        }
+        let back = shared.to_be_tree();
+        check_links(&back);
+        assert_eq!(back, plain.clone().normalize());
+        assert_eq!(
+            shared.iter_atoms().collect::<String>(),
+            plain.iter_atoms().collect::<String>(),
+        );
+    }
+}
+
+#[test]
+fn variants_share_unchanged_subtrees() {
+    let shared = parse("(A | B) & !(C | D | E)").shared();
+    let variant = shared.with_atom_replaced(1, 'X');
+    assert_eq!(variant.iter_atoms().collect::<String>(), "AXCDE");
+    assert_eq!(shared.iter_atoms().collect::<String>(), "ABCDE");
+    // the root and the left operand were copied, but not the right one
+    let (root, variant_root) = (node(shared.root()), node(variant.root()));
+    assert!(!Arc::ptr_eq(root, variant_root));
+    assert!(!Arc::ptr_eq(node(&root.left), node(&variant_root.left)));
+    assert!(Arc::ptr_eq(node(&root.right), node(&variant_root.right)));
+    assert_eq!(Arc::strong_count(node(&root.right)), 2);
+    // the unchanged atom of the left operand is shared too
+    let SharedChild::Atom(a) = &node(&root.left).left else {
+        panic!("A should be an atom");
+    };
+    assert_eq!(Arc::strong_count(a), 2);
+    // replacing the atom in the plain tree gives the same result
+    let mut plain = parse("(A | B) & !(C | D | E)");
+    plain.map_atoms_in_place(|c, _| {
+        if *c == 'B' {
+            *c = 'X';
+        }
+    });
+    for trues in all_assignments("ABCDEX") {
+        assert_eq!(eval_shared(&variant, &trues), eval(&plain, &trues));
+    }
+}
+
+#[test]
+fn replace_atom_in_unshared_tree() {
+    let mut shared = parse("A & B").shared();
+    let root_ptr = Arc::as_ptr(node(shared.root()));
+    assert_eq!(shared.replace_atom(0, 'C').as_deref(), Some(&'A'));
+    assert_eq!(shared.replace_atom(2, 'D'), None);
+    assert_eq!(shared.iter_atoms().collect::<String>(), "CB");
+    // no other tree held the root, so it wasn't copied
+    assert_eq!(Arc::as_ptr(node(shared.root())), root_ptr);
+    // while it's copied when shared
+    let cloned = shared.clone();
+    assert_eq!(Arc::strong_count(node(cloned.root())), 2);
+    shared.replace_atom(1, 'D');
+    assert_ne!(Arc::as_ptr(node(shared.root())), root_ptr);
+    assert_eq!(Arc::strong_count(node(cloned.root())), 1);
+    assert_eq!(cloned.iter_atoms().collect::<String>(), "CB");
+}
@@ -0,0 +1,141 @@
+//! tests of the inline storage of the nodes and atoms
+
+use {
+    crate::{test_util::*, BeTree, SmallVec},
+    std::rc::Rc,
+};
+
+#[test]
+fn small_vec_spills_to_the_heap() {
+    let mut v: SmallVec<String, 2> = SmallVec::new();
+    assert!(v.is_empty());
+    assert_eq!(v.capacity(), 2);
+    v.push("a".to_string());
+    v.push("b".to_string());
+    assert!(matches!(v, SmallVec::Inline { .. }));
+    v.push("c".to_string());
+    assert!(matches!(v, SmallVec::Heap(_)));
+    assert_eq!(&*v, &["a", "b", "c"]);
+    v[1].push('!');
+    assert_eq!(v.last().map(String::as_str), Some("c"));
+    assert_eq!(v.clone(), v);
+    assert_eq!(Vec::from(v), vec!["a", "b!", "c"]);
+}
+
+#[test]
+fn small_vec_conversions() {
+    let v: SmallVec<u8, 4> = vec![1, 2].into();
+    assert!(matches!(v, SmallVec::Inline { len: 2, .. }));
+    let v: SmallVec<u8, 4> = (0..10).collect();
+    assert!(matches!(v, SmallVec::Heap(_)));
+    assert_eq!(v.into_iter().sum::<u8>(), 45);
+    let mut v: SmallVec<u8, 4> = SmallVec::with_capacity(10);
+    v.extend(0..3);
+    v.shrink_to_fit();
+    assert!(matches!(v, SmallVec::Inline { len: 3, .. }));
+    assert_eq!(format!("{:?}", v), "[0, 1, 2]");
+}
+
+#[test]
+fn small_vec_drops_its_items_once() {
+    let item = Rc::new(());
+    let mut inline: SmallVec<Rc<()>, 4> = SmallVec::new();
+    inline.extend((0..3).map(|_| Rc::clone(&item)));
+    let mut heap: SmallVec<Rc<()>, 4> = SmallVec::new();
+    heap.extend((0..6).map(|_| Rc::clone(&item)));
+    assert_eq!(Rc::strong_count(&item), 10);
+    inline.clear();
+    assert!(inline.is_empty());
+    assert_eq!(Rc::strong_count(&item), 7);
+    let taken: Vec<_> = heap.clone().into();
+    assert_eq!(Rc::strong_count(&item), 13);
+    drop(taken);
+    inline.push(Rc::clone(&item));
+    drop(inline);
+    drop(heap);
+    assert_eq!(Rc::strong_count(&item), 1);
+}
+
+#[cfg(not(feature = "arena"))]
+#[test]
+fn empty_trees_are_inline() {
+    let expr = BeTree::<BoolOperator, char>::new();
+    assert!(matches!(expr.nodes, SmallVec::Inline { len: 1, .. }));
+}
+
+#[test]
+fn default_tree_is_usable() {
+    let expr = BeTree::<BoolOperator, char>::default();
+    assert!(expr.is_empty());
+    assert!(expr.accept_atom());
+    assert!(expr.accept_unary_operator());
+    assert!(expr.accept_opening_par());
+    assert!(!expr.accept_binary_operator());
+    assert!(!expr.accept_closing_par());
+    assert_eq!(eval(&expr, &['A']), None);
+    check_links(&expr);
+}
+
+#[test]
+fn push_after_take() {
+    let mut expr = parse("A & (B | !C)");
+    let taken = expr.take();
+    check_same_eval(&taken, &parse("A & (B | !C)"), "ABC");
+    assert!(expr.is_empty());
+    #[cfg(not(feature = "arena"))]
+    assert!(matches!(expr.nodes, SmallVec::Inline { len: 1, .. }));
+    expr.push_atom('A');
+    expr.push_operator(BoolOperator::Or);
+    expr.push_atom('B');
+    check_links(&expr);
+    check_same_eval(&expr, &parse("A | B"), "AB");
+}
+
+#[test]
+fn clear_keeps_the_storage() {
+    let mut expr = parse("(A | B) & !(C | D | E) & (F | G) & (H | I)");
+    let before = expr.memory_usage();
+    expr.clear();
+    assert!(expr.is_empty());
+    assert_eq!(expr, BeTree::new());
+    let after = expr.memory_usage();
+    assert_eq!(after.nodes_capacity, before.nodes_capacity);
+    assert_eq!(after.atoms_capacity, before.atoms_capacity);
+    for input in ["A & !B", "(A | B) & C"] {
+        expr.clear();
+        for token in input.chars() {
+            match token {
+                ' ' => {}
+                '(' => expr.open_par(),
+                ')' => expr.close_par(),
+                '&' => expr.push_operator(BoolOperator::And),
+                '|' => expr.push_operator(BoolOperator::Or),
+                '!' => expr.push_operator(BoolOperator::Not),
+                c => expr.push_atom(c),
+            }
+        }
+        check_links(&expr);
+        check_same_eval(&expr, &parse(input), "ABC");
+        assert_eq!(expr.memory_usage().nodes_capacity, before.nodes_capacity);
+    }
+}
+
+#[cfg(all(feature = "inline-storage", not(feature = "arena")))]
+#[test]
+fn trees_spill_and_shrink() {
+    let mut expr = parse("A & B");
+    assert!(matches!(expr.nodes, SmallVec::Inline { .. }));
+    assert!(matches!(expr.atoms, SmallVec::Inline { .. }));
+    let big = parse("(A | B) & !(C | D | E) & (F | G)");
+    assert!(matches!(big.atoms, SmallVec::Heap(_)));
+    check_links(&big);
+    check_same_eval(&big, &big.clone(), "ABCDEFG");
+    expr.push_operator(BoolOperator::Or);
+    for c in "CDEF".chars() {
+        expr.push_atom(c);
+        expr.push_operator(BoolOperator::Or);
+    }
+    expr.push_atom('G');
+    assert!(matches!(expr.atoms, SmallVec::Heap(_)));
+    check_same_eval(&expr, &parse("A & B | C | D | E | F | G"), "ABCDEFG");
+}
@@ -0,0 +1,132 @@
+//! tests of the statistics of expressions
+
+use {super::*, crate::test_util::*};
+
+fn stats(
+    atom_count: usize,
+    reachable_node_count: usize,
+    unary_count: usize,
+    binary_count: usize,
+    max_depth: usize,
+    open_parentheses: usize,
+) -> ExprStats {
+    ExprStats {
+        atom_count,
+        reachable_node_count,
+        operator_count: unary_count + binary_count,
+        unary_count,
+        binary_count,
+        max_depth,
+        open_parentheses,
+    }
+}
+
+#[test]
+fn stats_of_parsed_expressions() {
+    assert_eq!(parse("").stats(), stats(0, 1, 0, 0, 0, 0));
+    assert_eq!(parse("A").stats(), stats(1, 1, 0, 0, 1, 0));
+    assert_eq!(
+        parse("(A | B) & !(C | D | E)").stats(),
+        stats(5, 5, 1, 4, 4, 0)
+    );
+    // groups are nodes without operator
+    assert_eq!(parse("!!A & ((B))").stats(), stats(2, 5, 2, 1, 3, 0));
+    // an incomplete expression
+    assert_eq!(parse("A & (B | !").stats(), stats(2, 3, 1, 2, 2, 1));
+}
+
+#[test]
+fn stats_ignore_orphans() {
+    let mut expr = parse("(A | B) & !(C | D | E)");
+    expr.prune(NodeId::new(2)).unwrap();
+    assert_eq!(expr.stats(), stats(2, 1, 0, 1, 1, 0));
+    let mut expr = parse("A | (B | (C | (D | E)))");
+    assert_eq!(expr.stats(), stats(5, 4, 0, 4, 4, 0));
+    expr.rebalance_chains(&BoolOperator::Or);
+    assert_eq!(expr.stats(), stats(5, 4, 0, 4, 3, 0));
+    let mut expr = parse("A & M & !C");
+    expr.replace_atoms_with_trees(|&c| {
+        if c == 'M' {
+            Some(parse("B | !D"))
+        } else {
+            None
+        }
+    });
+    assert_eq!(expr.stats(), stats(4, 5, 2, 3, 4, 0));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn memory_usage_after_shrinking() {
+    let mut expr = parse("(A | B) & (C | D)");
+    for _ in 0..20 {
+        expr.replace_atoms_with_trees(|&c| Some(parse(&format!("!!{}", c))));
+        expr.simplify_involutive(&BoolOperator::Not);
+        expr.rewrite(
+            &[RewriteRule::new(parse_pattern("x & y"), parse_pattern("y & x")).unwrap()],
+            1,
+        );
+    }
+    let before = expr.memory_usage();
+    assert_eq!(before.reachable_atoms, 4);
+    assert!(before.nodes_len > 4 * before.reachable_nodes);
+    assert!(before.atoms_len > 20 * before.reachable_atoms);
+    let reference = expr.clone();
+    expr.shrink_to_fit();
+    check_links(&expr);
+    check_same_eval(&expr, &reference, "AB");
+    let after = expr.memory_usage();
+    assert_eq!(after.reachable_atoms, 4);
+    assert!(after.reachable_nodes <= before.reachable_nodes);
+    assert_eq!(after.nodes_len, after.reachable_nodes);
+    assert_eq!(after.atoms_len, 4);
+    assert_eq!(after.nodes_capacity, after.nodes_len);
+    assert_eq!(after.atoms_capacity, 4);
+    assert!(after.approx_bytes < before.approx_bytes / 10);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn operator_census_of_doc_example() {
+    use BoolOperator::*;
+    let expr = parse("(A | B) & !(C | D | E)");
+    let census = expr.operator_census();
+    assert_eq!(census, expr.operator_census_by_eq());
+    let counts: Vec<_> = census
+        .iter()
+        .map(|count| (count.operator, count.unary_count, count.binary_count))
+        .collect();
+    assert_eq!(counts, vec![(And, 0, 1), (Or, 0, 3), (Not, 1, 0)]);
+    assert_eq!(census.len(), 3);
+    assert_eq!(census.count(&Or), 3);
+    assert_eq!(census.get(&Not).map(|count| count.count()), Some(1));
+    assert_eq!(expr.op_count(), 5);
+    assert_eq!(expr.atom_count(), 5);
+    assert!(parse("A").operator_census().is_empty());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn operator_census_ignores_orphans() {
+    use BoolOperator::*;
+    let mut expr = parse("(A | B) & !(C | D | E)");
+    expr.prune(NodeId::new(2)).unwrap(); // only A | B is left
+    let census = expr.operator_census();
+    assert_eq!(census.count(&And), 0);
+    assert_eq!(census.count(&Or), 1);
+    assert_eq!(census.get(&Not), None);
+    assert_eq!(expr.op_count(), 1);
+    assert_eq!(expr.atom_count(), 5);
+    let mut expr = parse("!!A & !!B");
+    expr.simplify_involutive(&Not);
+    let census = expr.operator_census_by_eq();
+    assert_eq!(census.count(&Not), 0);
+    assert_eq!(census.count(&And), 1);
+    assert!(
+        expr.nodes
+            .iter()
+            .filter(|node| node.operator == Some(Not))
+            .count()
+            > 0
+    );
+}
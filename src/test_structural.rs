@@ -0,0 +1,84 @@
+//! tests of the structural equality and hashing
+
+use {
+    crate::{test_util::*, *},
+    std::{
+        collections::hash_map::DefaultHasher,
+        hash::{Hash, Hasher},
+    },
+};
+
+fn hash_of(expr: &BeTree<BoolOperator, char>) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    expr.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[test]
+fn equal_expressions_have_the_same_hash() {
+    let expr = parse("(A | B) & !(C | D)");
+    let variants = [
+        parse("((A | B)) & !((C | D))"),
+        from_tokens(expr.to_tokens()),
+        {
+            // orphan nodes and atoms, then compacted
+            let mut other = parse("(A | B) & !(C | D) | E");
+            other.remove_atom(AtomId::new(4)).unwrap();
+            other
+        },
+        {
+            let mut other = parse("(A | B) & !(C | D) | E");
+            other.remove_atom(AtomId::new(4)).unwrap();
+            other.compact();
+            other
+        },
+    ];
+    for other in &variants {
+        assert!(expr.structural_eq(other), "{:?}", other.display_compact());
+        assert!(other.structural_eq(&expr));
+        assert_eq!(hash_of(&expr), hash_of(other));
+    }
+}
+
+#[test]
+fn different_expressions_are_distinguished() {
+    let expr = parse("(A | B) & !C");
+    for input in [
+        "(A | B) & C",
+        "(A & B) & !C",
+        "A | (B & !C)",
+        "(A | B) & !",
+        "(B | A) & !C",
+    ] {
+        let other = parse(input);
+        assert!(!expr.structural_eq(&other), "{}", input);
+        assert_ne!(hash_of(&expr), hash_of(&other), "{}", input);
+    }
+}
+
+#[test]
+fn trees_as_structural_keys() {
+    /// a tree whose equality is the structural one
+    #[derive(Debug)]
+    struct Key(BeTree<BoolOperator, char>);
+    impl PartialEq for Key {
+        fn eq(&self, other: &Self) -> bool {
+            self.0.structural_eq(&other.0)
+        }
+    }
+    impl Eq for Key {}
+    impl Hash for Key {
+        fn hash<H: Hasher>(&self, state: &mut H) {
+            self.0.hash(state);
+        }
+    }
+    let mut plans = std::collections::HashMap::new();
+    plans.insert(Key(parse("A & (B | C)")), 1);
+    plans.insert(Key(parse("A & ((B | C))")), 2);
+    plans.insert(Key(parse("(A & B) | C")), 3);
+    assert_eq!(plans.len(), 2);
+    assert_eq!(
+        plans.get(&Key(from_tokens(parse("A&(B|C)").to_tokens()))),
+        Some(&2)
+    );
+}
@@ -0,0 +1,126 @@
+//! tests of the top level structure of expressions
+
+use {
+    super::*,
+    crate::test_util::{BoolOperator::*, *},
+};
+
+fn operand_string(operand: Option<SubExprRef<'_, BoolOperator, char>>) -> Option<String> {
+    operand.map(|operand| tokens_string(&operand.to_tree()))
+}
+
+#[test]
+fn atomic_roots() {
+    let empty = parse("");
+    assert_eq!(empty.root_operator(), None);
+    let (left, right) = empty.root_operands();
+    assert!(left.is_none() && right.is_none());
+    for input in ["A", "((A))"] {
+        let expr = parse(input);
+        assert_eq!(expr.root_operator(), None);
+        let (left, right) = expr.root_operands();
+        assert_eq!(left.and_then(|operand| operand.atom()), Some(&'A'));
+        assert!(right.is_none());
+    }
+}
+
+#[test]
+fn unary_roots() {
+    let expr = parse("!(A | B)");
+    assert_eq!(expr.root_operator(), Some((&Not, true)));
+    let (left, right) = expr.root_operands();
+    assert_eq!(operand_string(left), Some("A|B".to_string()));
+    assert!(right.is_none());
+    let expr = parse("((!A))");
+    assert_eq!(expr.root_operator(), Some((&Not, true)));
+}
+
+#[test]
+fn binary_roots() {
+    for input in ["((A) & B)", "(((A) & B))", "(A) & B"] {
+        let expr = parse(input);
+        assert_eq!(
+            expr.root_operator(),
+            Some((&And, false)),
+            "input: {:?}",
+            input
+        );
+        let (left, right) = expr.root_operands();
+        assert_eq!(operand_string(left), Some("A".to_string()));
+        assert_eq!(operand_string(right), Some("B".to_string()));
+    }
+    let expr = parse("(A | B) & !(C | D | E)");
+    assert_eq!(expr.root_operator(), Some((&And, false)));
+    let (left, right) = expr.root_operands();
+    let right_atoms: String = right.unwrap().iter_atoms().map(|(_, &c)| c).collect();
+    assert_eq!(right_atoms, "CDE");
+    let left_ids: Vec<usize> = left
+        .unwrap()
+        .iter_atoms()
+        .map(|(id, _)| id.index())
+        .collect();
+    assert_eq!(left_ids, vec![0, 1]);
+    assert_eq!(operand_string(right), Some("!(C|D|E)".to_string()));
+    // an incomplete expression
+    let expr = parse("A | ");
+    assert_eq!(expr.root_operator(), Some((&Or, false)));
+    let (left, right) = expr.root_operands();
+    assert_eq!(operand_string(left), Some("A".to_string()));
+    assert!(right.is_none());
+}
+
+fn chain_strings(expr: &BeTree<BoolOperator, char>, operator: BoolOperator) -> Option<Vec<String>> {
+    expr.as_chain(&operator).map(|operands| {
+        operands
+            .iter()
+            .map(|operand| match operand {
+                ChainOperand::Atom(_, atom) => atom.to_string(),
+                ChainOperand::SubExpr(sub_expr) => tokens_string(&sub_expr.to_tree()),
+            })
+            .collect()
+    })
+}
+
+#[test]
+fn chains() {
+    let cases: &[(&str, &[&str])] = &[
+        ("A", &["A"]),
+        ("((A))", &["A"]),
+        ("!A", &["!A"]),
+        ("A & B", &["A", "B"]),
+        ("A & (B | C) & !D", &["A", "B|C", "!D"]),
+        ("((A) & (B | (C))) & D", &["A", "B|(C)", "D"]),
+        ("A & (B & C)", &["A", "B", "C"]),
+        ("!(A | B) & !!C", &["!(A|B)", "!!C"]),
+    ];
+    for (input, operands) in cases {
+        let expected: Vec<String> = operands.iter().map(|s| s.to_string()).collect();
+        assert_eq!(
+            chain_strings(&parse(input), And),
+            Some(expected),
+            "input: {:?}",
+            input
+        );
+    }
+    assert_eq!(chain_strings(&parse(""), And), Some(Vec::new()));
+}
+
+#[test]
+fn not_chains() {
+    for input in ["A | B", "A & B | C", "(A & B) | (C & D)", "A & ", "(A | B)"] {
+        assert_eq!(
+            chain_strings(&parse(input), And),
+            None,
+            "input: {:?}",
+            input
+        );
+    }
+    // the atoms of a chain operand
+    let expr = parse("A & !(B | C) & D");
+    let operands = expr.as_chain(&And).unwrap();
+    let ChainOperand::SubExpr(negation) = operands[1] else {
+        panic!("expected a subexpression");
+    };
+    let atoms: String = negation.iter_atoms().map(|(_, &c)| c).collect();
+    assert_eq!(atoms, "BC");
+}
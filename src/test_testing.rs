@@ -0,0 +1,62 @@
+//! tests of the proptest strategies
+
+use {
+    super::{testing::*, *},
+    crate::test_util::*,
+    proptest::prelude::*,
+};
+
+fn arb_bool_tree() -> impl Strategy<Value = BeTree<BoolOperator, bool>> {
+    arb_betree(
+        prop_oneof![Just(BoolOperator::And), Just(BoolOperator::Or)],
+        Just(BoolOperator::Not),
+        any::<bool>(),
+        30,
+        6,
+    )
+}
+
+fn eval_bool(expr: &BeTree<BoolOperator, bool>) -> Option<bool> {
+    expr.eval(
+        |&b| b,
+        |op, a, b| op.eval(a, b),
+        |op, &a| op.short_circuit(a),
+    )
+}
+
+proptest! {
+    #[test]
+    fn eval_survives_to_tokens(expr in arb_bool_tree()) {
+        check_links(&expr);
+        let value = eval_bool(&expr);
+        prop_assert!(value.is_some());
+        let replayed = from_tokens(expr.to_tokens());
+        check_links(&replayed);
+        prop_assert_eq!(eval_bool(&replayed), value);
+    }
+
+    #[test]
+    fn generated_tokens_build_the_tree(
+        (expr, tokens) in arb_betree_with_tokens(
+            prop_oneof![Just(BoolOperator::And), Just(BoolOperator::Or)],
+            Just(BoolOperator::Not),
+            proptest::char::range('A', 'E'),
+            20,
+            5,
+        )
+    ) {
+        let rendered: String = tokens
+            .iter()
+            .map(|token| match token {
+                Token::Atom(c) => c.to_string(),
+                Token::Operator(op) => op.to_string(),
+                Token::OpeningParenthesis => "(".to_string(),
+                Token::ClosingParenthesis => ")".to_string(),
+            })
+            .collect();
+        let parsed = parse(&rendered);
+        check_links(&parsed);
+        prop_assert!(same_structure(&parsed, &expr), "{}", rendered);
+        prop_assert!(expr.depth() <= 7);
+    }
+}
@@ -0,0 +1,54 @@
+//! tests of the token utilities
+
+use {super::*, crate::test_util::*};
+
+type T = Token<BoolOperator, char>;
+
+#[test]
+fn token_kinds_and_equality() {
+    let tokens: Vec<(T, TokenKind, &str)> = vec![
+        (Token::Atom('A'), TokenKind::Atom, "A"),
+        (Token::Operator(BoolOperator::And), TokenKind::Operator, "&"),
+        (
+            Token::OpeningParenthesis,
+            TokenKind::OpeningParenthesis,
+            "(",
+        ),
+        (
+            Token::ClosingParenthesis,
+            TokenKind::ClosingParenthesis,
+            ")",
+        ),
+    ];
+    for (i, (token, kind, s)) in tokens.iter().enumerate() {
+        assert_eq!(token.kind(), *kind);
+        assert_eq!(token.to_string(), *s);
+        assert_eq!(token, &token.clone());
+        for (j, (other, _, _)) in tokens.iter().enumerate() {
+            assert_eq!(token == other, i == j);
+        }
+    }
+    assert_ne!(T::Atom('A'), T::Atom('B'));
+    assert_ne!(
+        T::Operator(BoolOperator::And),
+        T::Operator(BoolOperator::Or)
+    );
+}
+
+#[test]
+fn token_mapping() {
+    let tokens = parse("(A | b) & !C").to_tokens();
+    let mapped: Vec<Token<BoolOperator, char>> = tokens
+        .iter()
+        .cloned()
+        .map(|t| t.map_atom(|c| c.to_ascii_lowercase()))
+        .collect();
+    assert_eq!(mapped, parse("(a | b) & !c").to_tokens());
+    let rendered: String = mapped
+        .into_iter()
+        .map(|t| t.map_op(|op| op == BoolOperator::Not).to_string())
+        .collect();
+    assert_eq!(rendered, "afalsebfalsetruec");
+    let kinds: Vec<TokenKind> = tokens.iter().map(Token::kind).collect();
+    assert_eq!(kinds[..2], [TokenKind::Atom, TokenKind::Operator]);
+}
@@ -0,0 +1,54 @@
+//! tests of the conversion of trees to tokens
+
+use crate::test_util::*;
+
+#[test]
+fn to_tokens_replays_the_expression() {
+    let inputs = [
+        "A",
+        "(A)",
+        "((A))",
+        "!A",
+        "(A | B) & !(C | D | E)",
+        "!(!((A|B)&(B|C)&A)) & !D & (E | (A|E))",
+        "A & B | C & D",
+        "(A & B) | (C & D)",
+        "!!A & B",
+        "A & !(B | !C) | D",
+    ];
+    for input in inputs {
+        let expr = parse(input);
+        let replayed = from_tokens(expr.to_tokens());
+        check_links(&replayed);
+        check_same_eval(&replayed, &expr, "ABCDE");
+        assert_eq!(tokens_string(&replayed), tokens_string(&expr));
+    }
+}
+
+#[test]
+fn to_tokens_rendering() {
+    let cases = [
+        ("A", "A"),
+        ("((A))", "((A))"),
+        ("(A | B) & !(C | D | E)", "A|B&!(C|D|E)"),
+        ("A & (B | C)", "A&(B|C)"),
+        ("!!A & B", "!!A&B"),
+        ("A & !(B | !C) | D", "A&!(B|!C)|D"),
+    ];
+    for (input, tokens) in cases {
+        assert_eq!(tokens_string(&parse(input)), tokens);
+    }
+}
+
+#[test]
+fn to_tokens_adds_required_parenthesis() {
+    let mut expr = parse("A & X");
+    expr.replace_atoms_with_trees(|&c| if c == 'X' { Some(parse("B | C")) } else { None });
+    assert_eq!(tokens_string(&expr), "A&(B|C)");
+    // canonicalization removes the groups
+    let mut expr = parse("C & (B | A)");
+    expr.canonicalize(|_| true, |&c| c);
+    assert_eq!(tokens_string(&expr), "C&(A|B)");
+    let replayed = from_tokens(expr.to_tokens());
+    check_same_eval(&replayed, &expr, "ABC");
+}
@@ -0,0 +1,171 @@
+//! tests of the low-level tree builder
+
+use {
+    super::*,
+    crate::test_util::{BoolOperator::*, *},
+};
+
+#[test]
+fn build_a_valid_tree() {
+    // (A | !B) & C, with the elements added in a shuffled order
+    let mut builder = TreeBuilder::new();
+    let c = builder.add_atom('C');
+    let not = builder.add_node(Some(Not), true);
+    let and = builder.add_node(Some(And), false);
+    let b = builder.add_atom('B');
+    let or = builder.add_node(Some(Or), false);
+    let a = builder.add_atom('A');
+    builder.set_left(not, Child::Atom(b)).unwrap();
+    builder.set_left(or, Child::Atom(a)).unwrap();
+    builder.set_right(or, Child::Node(not)).unwrap();
+    builder.set_left(and, Child::Node(or)).unwrap();
+    builder.set_right(and, Child::Atom(c)).unwrap();
+    let expr = builder.finish(and).unwrap();
+    check_links(&expr);
+    check_same_eval(&expr, &parse("(A | !B) & C"), "ABC");
+    assert_eq!(expr.op_count(), 3);
+    // the built tree is compact, with atoms in order
+    assert_eq!(expr.iter_atoms().copied().collect::<String>(), "ABC");
+    assert_eq!(expr.nodes.len(), 3);
+    assert_eq!(expr.head, NodeId::from_index(0));
+    // and can be completed
+    let mut expr = expr;
+    expr.push_operator(Or);
+    expr.push_atom('D');
+    check_links(&expr);
+    check_same_eval(&expr, &parse("(A | !B) & C | D"), "ABCD");
+}
+
+#[test]
+fn build_atomic_and_empty_trees() {
+    let mut builder = TreeBuilder::new();
+    let group = builder.add_node(None, false);
+    let a = builder.add_atom('A');
+    builder.set_left(group, Child::Atom(a)).unwrap();
+    let expr = builder.finish(group).unwrap();
+    assert!(expr.is_atomic());
+    assert_eq!(eval(&expr, &['A']), Some(true));
+    let mut builder = TreeBuilder::<BoolOperator, char>::new();
+    let group = builder.add_node(None, false);
+    let expr = builder.finish(group).unwrap();
+    assert!(expr.is_empty());
+    assert_eq!(expr, BeTree::new());
+}
+
+#[test]
+fn unreachable_elements_are_dropped() {
+    let mut builder = TreeBuilder::new();
+    let a = builder.add_atom('A');
+    let b = builder.add_atom('B');
+    let unused = builder.add_atom('Z');
+    let or = builder.add_node(Some(Or), false);
+    let orphan = builder.add_node(Some(And), false);
+    builder.set_left(or, Child::Atom(a)).unwrap();
+    builder.set_right(or, Child::Atom(b)).unwrap();
+    builder.set_left(orphan, Child::Atom(unused)).unwrap();
+    let expr = builder.finish(or).unwrap();
+    check_links(&expr);
+    assert_eq!(expr.atom_count(), 2);
+    assert_eq!(expr.nodes.len(), 1);
+    assert_eq!(expr.op_count(), 1);
+}
+
+#[test]
+fn invalid_structures_are_rejected() {
+    let mut builder = TreeBuilder::new();
+    let a = builder.add_atom('A');
+    let and = builder.add_node(Some(And), false);
+    let not = builder.add_node(Some(Not), true);
+    let unknown_node = NodeId::from_index(10);
+    let unknown_atom = AtomId::from_index(10);
+    assert_eq!(
+        builder.set_left(unknown_node, Child::Atom(a)),
+        Err(StructureError::UnknownNode(unknown_node)),
+    );
+    assert_eq!(
+        builder.clone().finish(unknown_node).unwrap_err(),
+        StructureError::UnknownNode(unknown_node),
+    );
+    // links to unknown elements
+    let mut b = builder.clone();
+    b.set_left(and, Child::Node(unknown_node)).unwrap();
+    assert_eq!(
+        b.finish(and).unwrap_err(),
+        StructureError::UnknownNode(unknown_node)
+    );
+    let mut b = builder.clone();
+    b.set_left(and, Child::Atom(unknown_atom)).unwrap();
+    assert_eq!(
+        b.finish(and).unwrap_err(),
+        StructureError::UnknownAtom(unknown_atom)
+    );
+    // a binary operation with only a right child
+    let mut b = builder.clone();
+    b.set_right(and, Child::Atom(a)).unwrap();
+    assert_eq!(
+        b.finish(and).unwrap_err(),
+        StructureError::MisplacedChild(and)
+    );
+    // a unary operation with a right child
+    let mut b = builder.clone();
+    b.set_left(and, Child::Atom(a)).unwrap();
+    b.set_right(and, Child::Node(not)).unwrap();
+    b.set_left(not, Child::Atom(unknown_atom)).unwrap();
+    b.set_right(not, Child::Atom(a)).unwrap();
+    assert_eq!(
+        b.finish(and).unwrap_err(),
+        StructureError::MisplacedChild(not)
+    );
+    // a group with a right child
+    let mut b = builder.clone();
+    let group = b.add_node(None, false);
+    b.set_left(group, Child::Atom(a)).unwrap();
+    b.set_right(group, Child::Atom(a)).unwrap();
+    assert_eq!(
+        b.finish(group).unwrap_err(),
+        StructureError::MisplacedChild(group)
+    );
+    // a node linked twice
+    let mut b = builder.clone();
+    b.set_left(and, Child::Node(not)).unwrap();
+    b.set_right(and, Child::Node(not)).unwrap();
+    b.set_left(not, Child::Atom(a)).unwrap();
+    assert_eq!(
+        b.finish(and).unwrap_err(),
+        StructureError::MultipleLinks(not)
+    );
+    // a cycle
+    let mut b = builder.clone();
+    b.set_left(and, Child::Atom(a)).unwrap();
+    b.set_right(and, Child::Node(not)).unwrap();
+    b.set_left(not, Child::Node(and)).unwrap();
+    assert_eq!(
+        b.finish(and).unwrap_err(),
+        StructureError::MultipleLinks(and)
+    );
+    // the root as its own child
+    let mut b = builder;
+    b.set_left(not, Child::Node(not)).unwrap();
+    assert_eq!(
+        b.finish(not).unwrap_err(),
+        StructureError::MultipleLinks(not)
+    );
+}
+
+#[test]
+fn shared_atoms_are_kept_once() {
+    // A & (B | A), with the same atom A on both sides, as after dedup_atoms
+    let mut builder = TreeBuilder::new();
+    let a = builder.add_atom('A');
+    let b = builder.add_atom('B');
+    let and = builder.add_node(Some(And), false);
+    let or = builder.add_node(Some(Or), false);
+    builder.set_left(or, Child::Atom(b)).unwrap();
+    builder.set_right(or, Child::Atom(a)).unwrap();
+    builder.set_left(and, Child::Atom(a)).unwrap();
+    builder.set_right(and, Child::Node(or)).unwrap();
+    let expr = builder.finish(and).unwrap();
+    check_links(&expr);
+    check_same_eval(&expr, &parse("A & (B | A)"), "AB");
+    assert_eq!(expr.iter_atoms().copied().collect::<String>(), "AB");
+}
@@ -0,0 +1,72 @@
+//! tests of the paths in trees
+
+use {super::*, crate::test_util::*};
+
+fn path(sides: &str) -> TreePath {
+    TreePath(
+        sides
+            .chars()
+            .map(|c| if c == 'L' { Side::Left } else { Side::Right })
+            .collect(),
+    )
+}
+
+#[test]
+fn resolve_paths() {
+    // nodes: 0 is the `&`, 1 the `A | B`, 2 the `!`, 4 the `... | E`, 3 the `C | D`
+    let expr = parse("(A | B) & !(C | D | E)");
+    assert_eq!(expr.resolve(&path("")), Some(Child::Node(NodeId::new(0))));
+    assert_eq!(expr.resolve(&path("LR")), Some(Child::Atom(AtomId::new(1))));
+    assert_eq!(expr.resolve(&path("RL")), Some(Child::Node(NodeId::new(4))));
+    assert_eq!(
+        expr.resolve(&path("RLLR")),
+        Some(Child::Atom(AtomId::new(3)))
+    );
+    assert_eq!(expr.path_to_node(NodeId::new(0)), Some(path("")));
+    assert_eq!(expr.path_to_node(NodeId::new(3)), Some(path("RLL")));
+    assert_eq!(expr.path_to_atom(AtomId::new(4)), Some(path("RLR")));
+    for atom_id in (0..5).map(AtomId::new) {
+        let p = expr.path_to_atom(atom_id).unwrap();
+        assert_eq!(expr.resolve(&p), Some(Child::Atom(atom_id)));
+    }
+}
+
+#[test]
+fn invalid_paths() {
+    let expr = parse("(A | B) & !(C | D | E)");
+    assert_eq!(expr.resolve(&path("LLL")), None); // below an atom
+    assert_eq!(expr.resolve(&path("RR")), None); // nothing right of a unary operator
+    assert_eq!(expr.path_to_node(NodeId::new(42)), None);
+    assert_eq!(expr.path_to_atom(AtomId::new(42)), None);
+    let mut expr = expr;
+    expr.prune(NodeId::new(2)).unwrap();
+    assert_eq!(expr.path_to_node(NodeId::new(3)), None);
+    assert_eq!(expr.path_to_atom(AtomId::new(2)), None);
+    assert_eq!(expr.resolve(&path("R")), Some(Child::Atom(AtomId::new(1))));
+}
+
+#[test]
+fn paths_survive_normalization() {
+    let mut expr = parse("A & M & !(C | D)");
+    expr.replace_atoms_with_trees(|&c| {
+        if c == 'M' {
+            Some(parse("B | !E"))
+        } else {
+            None
+        }
+    });
+    let normalized = expr.clone().normalize();
+    for (atom_id, atom) in expr.iter_atoms_in_tree() {
+        let path = expr.path_to_atom(atom_id).unwrap();
+        let Some(Child::Atom(new_atom_id)) = normalized.resolve(&path) else {
+            panic!("path to {} not resolved", atom);
+        };
+        assert_eq!(normalized.atom(new_atom_id), Some(atom));
+    }
+    // ids didn't survive: E was the last atom of the arena
+    let e_path = expr.path_to_atom(AtomId::new(5)).unwrap();
+    assert_eq!(
+        normalized.resolve(&e_path),
+        Some(Child::Atom(AtomId::new(2)))
+    );
+}
@@ -0,0 +1,75 @@
+//! tests of the trees split into a structure and atoms
+
+use {super::*, crate::test_util::*};
+
+#[test]
+fn instantiate_with_the_original_atoms() {
+    let expr = parse("(A | B) & !(C | A)");
+    let (template, atoms) = expr.clone().into_template();
+    assert_eq!(atoms, vec!['A', 'B', 'C', 'A']);
+    assert_eq!(template.atom_count(), 4);
+    let instance = template.instantiate(atoms).unwrap();
+    assert_eq!(instance, expr);
+    check_links(&instance);
+}
+
+#[test]
+fn instantiate_with_wrong_arity() {
+    let (template, _) = parse("A & !B").into_template();
+    assert_eq!(
+        template.instantiate(vec!['A']),
+        Err(TemplateError::AtomCountMismatch {
+            expected: 2,
+            found: 1
+        }),
+    );
+    assert_eq!(
+        template.instantiate(vec!['A', 'B', 'C']),
+        Err(TemplateError::AtomCountMismatch {
+            expected: 2,
+            found: 3
+        }),
+    );
+    assert_eq!(
+        template
+            .eval_with(&['A'], |_| true, |op, a, b| op.eval(a, b), |_, _| false)
+            .unwrap_err()
+            .to_string(),
+        "expected 2 atoms but got 1",
+    );
+}
+
+#[test]
+fn eval_with_matches_eval_of_instances() {
+    let (template, _) = parse("(A | B) & !(C & D) | !A").into_template();
+    let atom_sets = ["ABCDA", "BBCCD", "DCBAB", "AAAAA", "CDABC"];
+    for atoms in atom_sets {
+        let atoms: Vec<char> = atoms.chars().collect();
+        let instance = template.instantiate(atoms.clone()).unwrap();
+        check_links(&instance);
+        for trues in all_assignments("ABCD") {
+            let value = template
+                .eval_with(
+                    &atoms,
+                    |c| trues.contains(c),
+                    |op, a, b| op.eval(a, b),
+                    |op, &a| op.short_circuit(a),
+                )
+                .unwrap();
+            assert_eq!(value, eval(&instance, &trues));
+        }
+    }
+}
+
+#[test]
+fn template_of_a_tree_being_built() {
+    let (template, atoms) = parse("A & (B |").into_template();
+    let mut instance = template.instantiate(atoms).unwrap();
+    instance.push_atom('C');
+    instance.close_par();
+    check_links(&instance);
+    check_same_eval(&instance, &parse("A & (B | C)"), "ABC");
+    let (template, atoms) = BeTree::<BoolOperator, char>::new().into_template();
+    assert!(atoms.is_empty());
+    assert!(template.instantiate(atoms).unwrap().is_empty());
+}
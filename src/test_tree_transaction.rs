@@ -0,0 +1,154 @@
+//! tests of the transactional edits
+
+use {
+    super::*,
+    crate::test_util::{BoolOperator::*, *},
+    Side::*,
+};
+
+#[test]
+fn failing_transaction_leaves_the_tree_unchanged() {
+    let original = parse("(A | B) & !(C | D) | E");
+    let mut expr = original.clone();
+    let right = node_at(&expr, &[Left, Right]);
+    let result = expr.edit(|tx| {
+        tx.remove_atom(AtomId::new(0))?;
+        assert_eq!(tx.replace_operator(&Or, And), 2);
+        tx.wrap_node_unary(right, Not)?;
+        tx.prune(right)?;
+        // the node has already been pruned
+        tx.prune(right)
+    });
+    assert_eq!(result, Err(EditError::UnreachableNode(right)));
+    assert_eq!(expr, original);
+    check_links(&expr);
+    // the tree can still be built
+    expr.push_operator(And);
+    expr.push_atom('F');
+    check_same_eval(&expr, &parse("(A | B) & !(C | D) | E & F"), "ABCDEF");
+}
+
+#[test]
+fn failing_transaction_with_a_user_error() {
+    #[derive(Debug, PartialEq)]
+    enum MyError {
+        Edit(EditError),
+        TooManyOperators,
+    }
+    impl From<EditError> for MyError {
+        fn from(e: EditError) -> Self {
+            Self::Edit(e)
+        }
+    }
+    let original = parse("A & B | C");
+    let mut expr = original.clone();
+    let result = expr.edit(|tx| {
+        tx.wrap_unary(Not);
+        tx.rotate_right(node_at(tx.tree(), &[Left]))?;
+        if tx.tree().op_count() > 2 {
+            return Err(MyError::TooManyOperators);
+        }
+        Ok(())
+    });
+    assert_eq!(result, Err(MyError::TooManyOperators));
+    assert_eq!(expr, original);
+    let result = expr.edit(|tx| Err(MyError::Edit(tx.prune(NodeId::new(1000)).unwrap_err())));
+    assert_eq!(
+        result,
+        Err(MyError::Edit(EditError::UnknownNode(NodeId::new(1000))))
+    );
+    assert_eq!(expr, original);
+}
+
+#[test]
+fn succeeding_transaction_is_like_sequential_edits() {
+    let original = parse("(A | B) & !(C | D) | E");
+    let edits = |expr: &mut BeTree<BoolOperator, char>| -> Result<(), EditError> {
+        let group = node_at(expr, &[Left, Right, Left]);
+        expr.remove_atom(AtomId::new(1))?;
+        expr.replace_operator(&And, Or);
+        let detached = expr.detach(group)?;
+        let head = expr.head;
+        expr.attach(detached, head, Right, Some(And))?;
+        expr.wrap_unary(Not);
+        Ok(())
+    };
+    let mut sequential = original.clone();
+    edits(&mut sequential).unwrap();
+    let mut transactional = original.clone();
+    transactional
+        .edit(|tx| {
+            let group = node_at(tx.tree(), &[Left, Right, Left]);
+            tx.remove_atom(AtomId::new(1))?;
+            tx.replace_operator(&And, Or);
+            let detached = tx.detach(group)?;
+            let head = tx.tree().head;
+            tx.attach(detached, head, Right, Some(And))?;
+            tx.wrap_unary(Not);
+            assert!(tx.is_modified());
+            Ok::<(), EditError>(())
+        })
+        .unwrap();
+    assert_eq!(transactional, sequential);
+    check_links(&transactional);
+    check_same_eval(&transactional, &parse("!(A | (E & (C | D)))"), "ABCDE");
+    // the same edits, through the mutable access to the tree
+    let mut transactional = original;
+    transactional.edit(|tx| edits(tx.tree_mut())).unwrap();
+    assert_eq!(transactional, sequential);
+}
+
+#[test]
+fn transaction_without_edit() {
+    let original = parse("A & !B");
+    let mut expr = original.clone();
+    let result = expr.edit(|tx| {
+        assert!(!tx.is_modified());
+        assert_eq!(tx.tree().op_count(), 2);
+        Err(EditError::UnknownNode(NodeId::new(9)))
+    });
+    assert_eq!(result, Err(EditError::UnknownNode(NodeId::new(9))));
+    assert_eq!(expr, original);
+}
+
+#[test]
+fn transaction_leaving_an_invalid_structure_is_rolled_back() {
+    let original = parse("(A | B) & C");
+    let mut expr = original.clone();
+    let group = node_at(&expr, &[Left]);
+    let result = expr.edit(|tx| {
+        tx.replace_operator(&And, Or);
+        // the group is linked from the head but keeps its parent
+        let tree = tx.tree_mut();
+        let head = tree.head;
+        tree.nodes[head].set_right(Child::Node(group));
+        Ok::<(), EditError>(())
+    });
+    assert_eq!(
+        result,
+        Err(EditError::InvalidStructure(StructureError::MultipleLinks(
+            group
+        )))
+    );
+    assert_eq!(expr, original);
+    check_links(&expr);
+}
+
+#[test]
+fn panicking_transaction_is_rolled_back() {
+    let original = parse("(A | B) & !(C | D)");
+    let mut expr = original.clone();
+    let epoch = expr.edits_epoch();
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        expr.edit(|tx| -> Result<(), EditError> {
+            tx.replace_operator(&Or, And);
+            tx.prune(node_at(tx.tree(), &[Right]))?;
+            panic!("failure in the middle of the edits");
+        })
+    }));
+    assert!(result.is_err());
+    assert_eq!(expr, original);
+    assert!(expr.edits_epoch() != epoch);
+    check_links(&expr);
+    check_same_eval(&expr, &original, "ABCD");
+}
@@ -0,0 +1,232 @@
+//! helpers shared by the tests
+
+use {super::*, std::fmt};
+
+// the tests importing these helpers get the collections of the std
+// prelude even when the crate is built without std
+#[allow(unused_imports)]
+pub use alloc::{
+    borrow::ToOwned,
+    boxed::Box,
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum BoolOperator {
+    And,
+    Or,
+    Not,
+}
+impl BoolOperator {
+    pub fn eval(self, a: bool, b: Option<bool>) -> bool {
+        match (self, b) {
+            (Self::And, Some(b)) => a & b,
+            (Self::Or, Some(b)) => a | b,
+            (Self::Not, None) => !a,
+            _ => unreachable!(),
+        }
+    }
+    /// tell whether we can skip evaluating the second operand
+    pub fn short_circuit(self, a: bool) -> bool {
+        matches!((self, a), (Self::And, false) | (Self::Or, true))
+    }
+}
+
+impl OpArity for BoolOperator {
+    fn arity(&self) -> Arity {
+        match self {
+            Self::And | Self::Or => Arity::Binary,
+            Self::Not => Arity::Unary,
+        }
+    }
+}
+
+impl fmt::Display for BoolOperator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::And => write!(f, "&"),
+            Self::Or => write!(f, "|"),
+            Self::Not => write!(f, "!"),
+        }
+    }
+}
+
+/// create the empty tree the helpers build in: with the `arena`
+/// feature, it's allocated in its own arena, so that the tests run on
+/// the arena storage
+pub fn new_tree<Op, Atom>() -> BeTree<Op, Atom> {
+    #[cfg(feature = "arena")]
+    return BeTree::new_in(&TreeArena::new());
+    #[cfg(not(feature = "arena"))]
+    BeTree::new()
+}
+
+/// build a tree from a string where atoms are single chars
+pub fn parse(input: &str) -> BeTree<BoolOperator, char> {
+    let mut expr = new_tree();
+    for c in input.chars() {
+        match c {
+            '&' => expr.push_operator(BoolOperator::And),
+            '|' => expr.push_operator(BoolOperator::Or),
+            '!' => expr.push_operator(BoolOperator::Not),
+            ' ' => {}
+            '(' => expr.open_par(),
+            ')' => expr.close_par(),
+            _ => expr.push_atom(c),
+        }
+    }
+    expr
+}
+
+/// parse a pattern where lowercase letters are captured subtrees,
+/// `_` is any atom and uppercase letters are exact atoms
+#[cfg(feature = "std")]
+pub fn parse_pattern(input: &str) -> BeTree<BoolOperator, PatternAtom<char>> {
+    parse(input).map_atoms_indexed(|_, &c| match c {
+        '_' => PatternAtom::AnyAtom,
+        c if c.is_ascii_lowercase() => PatternAtom::AnySubtree(c.to_string()),
+        c => PatternAtom::Exact(c),
+    })
+}
+
+/// evaluate a tree of char atoms, with `trues` being the chars
+/// whose value is true
+pub fn eval(expr: &BeTree<BoolOperator, char>, trues: &[char]) -> Option<bool> {
+    expr.eval(
+        |c| trues.contains(c),
+        |op, a, b| op.eval(a, b),
+        |op, &a| op.short_circuit(a),
+    )
+}
+
+/// check the evaluation of two expressions is the same for all
+/// combinations of the given atoms
+pub fn check_same_eval(
+    a: &BeTree<BoolOperator, char>,
+    b: &BeTree<BoolOperator, char>,
+    atoms: &str,
+) {
+    for trues in all_assignments(atoms) {
+        assert_eq!(eval(a, &trues), eval(b, &trues), "trues: {:?}", trues);
+    }
+}
+
+/// check that the trees have the same empty groups, and evaluate the
+/// same when giving them a value
+pub fn check_same_empty_groups(a: &BeTree<BoolOperator, char>, b: &BeTree<BoolOperator, char>) {
+    check_links(a);
+    assert_eq!(a.empty_groups().len(), b.empty_groups().len());
+    assert_eq!(a.validate().is_ok(), b.validate().is_ok());
+    for neutral in [true, false] {
+        for trues in all_assignments("AB") {
+            let eval = |expr: &BeTree<BoolOperator, char>| {
+                expr.eval_with_empty_groups(
+                    |c| trues.contains(c),
+                    |op, a, b| op.eval(a, b),
+                    |op, &a| op.short_circuit(a),
+                    || neutral,
+                )
+            };
+            assert_eq!(eval(a), eval(b), "trues: {:?}", trues);
+        }
+    }
+}
+
+/// return all combinations of true atoms among the given ones
+pub fn all_assignments(atoms: &str) -> Vec<Vec<char>> {
+    let atoms: Vec<char> = atoms.chars().collect();
+    (0..(1 << atoms.len()))
+        .map(|bits| {
+            atoms
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| bits & (1 << i) != 0)
+                .map(|(_, &c)| c)
+                .collect()
+        })
+        .collect()
+}
+
+/// check that all reachable nodes are consistently linked to their parents,
+/// and that the other invariants of the tree hold
+pub fn check_links<Op, Atom>(expr: &BeTree<Op, Atom>)
+where
+    Op: fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone,
+{
+    expr.check_invariants();
+    assert_eq!(expr.nodes[expr.head].parent(), None, "head has a parent");
+    let mut stack = vec![expr.head];
+    while let Some(node_idx) = stack.pop() {
+        let node = &expr.nodes[node_idx];
+        for child in [node.left(), node.right()] {
+            match child {
+                Child::Node(child_idx) => {
+                    assert_eq!(expr.nodes[child_idx].parent(), Some(node_idx), "bad parent");
+                    stack.push(child_idx);
+                }
+                Child::Atom(atom_idx) => assert!(atom_idx.index() < expr.atoms.len()),
+                Child::None => {}
+            }
+        }
+        if node.unary {
+            assert!(node.right().is_none(), "unary node with right child");
+        }
+    }
+}
+
+/// the indices of some node or atom ids, easier to compare
+/// with literals than the ids themselves
+pub fn indices<Id: Into<usize>>(ids: impl IntoIterator<Item = Id>) -> Vec<usize> {
+    ids.into_iter().map(Into::into).collect()
+}
+
+/// find the node at the end of a path of sides from the head
+pub fn node_at(expr: &BeTree<BoolOperator, char>, path: &[Side]) -> NodeId {
+    let mut node_id = expr.head;
+    for &side in path {
+        let Child::Node(child_id) = expr.node(node_id).unwrap().child(side) else {
+            panic!("no node at {:?}", path);
+        };
+        node_id = child_id;
+    }
+    node_id
+}
+
+/// render the tokens of the expression as a string
+pub fn tokens_string(expr: &BeTree<BoolOperator, char>) -> String {
+    expr.to_tokens()
+        .into_iter()
+        .map(|token| match token {
+            Token::Atom(c) => c,
+            Token::Operator(BoolOperator::And) => '&',
+            Token::Operator(BoolOperator::Or) => '|',
+            Token::Operator(BoolOperator::Not) => '!',
+            Token::OpeningParenthesis => '(',
+            Token::ClosingParenthesis => ')',
+        })
+        .collect()
+}
+
+/// build a tree by pushing tokens
+pub fn from_tokens<Op, Atom>(tokens: Vec<Token<Op, Atom>>) -> BeTree<Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone,
+{
+    let mut expr = new_tree();
+    for token in tokens {
+        expr.push(token);
+    }
+    expr
+}
+
+/// tell whether two expressions have the same structure, groups being ignored
+pub fn same_structure(a: &BeTree<BoolOperator, char>, b: &BeTree<BoolOperator, char>) -> bool {
+    a.structural_eq(b)
+}
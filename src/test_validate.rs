@@ -0,0 +1,185 @@
+//! tests of the validation of the structure of trees
+
+use {
+    super::*,
+    crate::test_util::{BoolOperator::*, *},
+};
+
+#[test]
+fn built_trees_are_valid() {
+    for input in [
+        "",
+        "A",
+        "(A | B) & !(C | D | E)",
+        "!!A & ((B))",
+        "A & (B | !",
+        "((A",
+        "A & !",
+        "(A | (B & !(C",
+        "A)) | B",
+    ] {
+        let expr = parse(input);
+        assert_eq!(expr.validate_structure(), Ok(()), "input: {:?}", input);
+    }
+}
+
+#[test]
+fn trees_with_extra_atoms_are_valid() {
+    // an atom following an atom is the right child of the group
+    let mut expr: BeTree<BoolOperator, char> = BeTree::new();
+    expr.push_atom('A');
+    expr.push_atom('B');
+    assert_eq!(expr.validate_structure(), Ok(()));
+    let expr = BeTree::from_parts(expr.into_parts()).unwrap();
+    assert_eq!(expr.validate_structure(), Ok(()));
+    for input in ["!A B", "!A B & C", "A B | C D"] {
+        let expr = parse(input);
+        assert_eq!(expr.validate_structure(), Ok(()), "input: {:?}", input);
+        assert!(BeTree::from_parts(expr.clone().into_parts()).is_ok());
+    }
+}
+
+#[test]
+fn edited_trees_are_valid() {
+    let mut expr = parse("(A | B) & (C | (D & E))");
+    expr.rotate_left(NodeId::new(0)).unwrap();
+    expr.check_invariants();
+    // the tree can still be completed
+    expr.push_operator(Or);
+    expr.push_atom('F');
+    expr.check_invariants();
+    assert_eq!(tokens_string(&expr), "A|B&C|(D&E)|F");
+    let mut expr = parse("A & (B | ");
+    let mut cursor = expr.cursor_mut();
+    assert!(cursor.move_to_left());
+    cursor.wrap_in_unary(Not);
+    expr.check_invariants();
+    expr.push_atom('C');
+    expr.close_par();
+    assert_eq!(tokens_string(&expr), "!A&(B|C)");
+}
+
+#[test]
+fn corruptions_are_detected() {
+    // nodes: 0 is the `&`, 1 the `A | B`, 2 the `!`, 4 the `... | E`, 3 the `C | D`
+    let expr = parse("(A | B) & !(C | D | E)");
+
+    let mut corrupted = expr.clone();
+    corrupted.nodes[3].set_parent(Some(NodeId::new(2)));
+    assert_eq!(
+        corrupted.validate_structure(),
+        Err(StructureError::WrongParent(NodeId::new(3)))
+    );
+
+    let mut corrupted = expr.clone();
+    corrupted.nodes[0].set_parent(Some(NodeId::new(2)));
+    assert_eq!(
+        corrupted.validate_structure(),
+        Err(StructureError::WrongParent(NodeId::new(0)))
+    );
+
+    let mut corrupted = expr.clone();
+    corrupted.tail = NodeId::new(42);
+    assert_eq!(
+        corrupted.validate_structure(),
+        Err(StructureError::UnknownNode(NodeId::new(42)))
+    );
+
+    let mut corrupted = expr.clone();
+    corrupted.nodes[1].set_right(Child::Atom(AtomId::new(42)));
+    assert_eq!(
+        corrupted.validate_structure(),
+        Err(StructureError::UnknownAtom(AtomId::new(42)))
+    );
+
+    let mut corrupted = expr.clone();
+    corrupted.openness = 1;
+    assert_eq!(
+        corrupted.validate_structure(),
+        Err(StructureError::WrongOpenness {
+            openness: 1,
+            open_groups: 0
+        })
+    );
+
+    let mut corrupted = expr.clone();
+    corrupted.tail = NodeId::new(3);
+    assert_eq!(
+        corrupted.validate_structure(),
+        Err(StructureError::WrongOpenness {
+            openness: 0,
+            open_groups: 2
+        })
+    );
+
+    let mut corrupted = expr.clone();
+    corrupted.nodes[3].set_left(Child::Node(NodeId::new(2)));
+    assert_eq!(
+        corrupted.validate_structure(),
+        Err(StructureError::WrongParent(NodeId::new(2)))
+    );
+
+    // a cycle below the head, the parents being consistent
+    let mut corrupted = expr.clone();
+    corrupted.nodes[3].set_left(Child::Node(NodeId::new(4)));
+    corrupted.nodes[3].set_right(Child::Node(NodeId::new(4)));
+    corrupted.nodes[4].set_parent(Some(NodeId::new(3)));
+    assert!(corrupted.validate_structure().is_err());
+
+    // an unreachable tail
+    let mut corrupted = expr;
+    corrupted.nodes.push(Node::empty());
+    corrupted.tail = NodeId::from_index(corrupted.nodes.len() - 1);
+    assert_eq!(
+        corrupted.validate_structure(),
+        Err(StructureError::UnreachableTail(corrupted.tail))
+    );
+}
+
+#[test]
+#[should_panic(expected = "inconsistent tree: node 3 has a wrong parent")]
+fn check_invariants_panics() {
+    let mut expr = parse("(A | B) & !(C | D | E)");
+    expr.nodes[3].set_parent(Some(NodeId::new(2)));
+    expr.check_invariants();
+}
+
+/// run `check_invariants`, returning the message of its panic
+fn invariants_panic(expr: &BeTree<BoolOperator, char>) -> Result<(), String> {
+    std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| expr.check_invariants())).map_err(
+        |payload| {
+            payload
+                .downcast_ref::<String>()
+                .cloned()
+                .unwrap_or_default()
+        },
+    )
+}
+
+#[test]
+fn check_invariants_reports_cycles_and_dangling_links() {
+    // a cycle whose parents are consistent: following the links
+    // recursively would never end
+    let mut cyclic = parse("(A | B) & !C");
+    cyclic.nodes[1].set_left(Child::Node(NodeId::new(0)));
+    cyclic.nodes[0].set_parent(Some(NodeId::new(1)));
+    assert!(cyclic.validate_structure().is_err());
+    let message = invariants_panic(&cyclic).unwrap_err();
+    assert!(message.starts_with("inconsistent tree"), "{}", message);
+    assert!(
+        message.contains("[1] Some(Or) parent: Some(NodeId(0))"),
+        "{}",
+        message
+    );
+
+    // a link to an atom out of the arena
+    let mut dangling = parse("(A | B) & !C");
+    dangling.nodes[2].set_left(Child::Atom(AtomId::new(42)));
+    assert_eq!(
+        dangling.validate_structure(),
+        Err(StructureError::UnknownAtom(AtomId::new(42)))
+    );
+    let message = invariants_panic(&dangling).unwrap_err();
+    assert!(message.contains("left: Atom(AtomId(42))"), "{}", message);
+    assert!(message.contains("a2 'C'"), "{}", message);
+}
@@ -0,0 +1,128 @@
+//! tests of the visitors
+
+use {
+    super::*,
+    crate::test_util::{BoolOperator::*, *},
+    std::ops::ControlFlow,
+};
+
+/// find the first atom under a negation, recording what's visited
+#[derive(Default)]
+struct FirstNegatedAtom {
+    negated: Vec<bool>, // for each entered node, whether it's a negation
+    found: Option<char>,
+    visited: String,
+}
+
+impl Visitor<BoolOperator, char> for FirstNegatedAtom {
+    fn enter_node(&mut self, _id: NodeId, node: &Node<BoolOperator>) -> VisitAction {
+        self.negated.push(node.operator == Some(Not));
+        self.visited.push('[');
+        VisitAction::Continue
+    }
+    fn visit_atom(&mut self, _id: AtomId, &atom: &char) -> ControlFlow<()> {
+        self.visited.push(atom);
+        if self.negated.contains(&true) {
+            self.found = Some(atom);
+            return ControlFlow::Break(());
+        }
+        ControlFlow::Continue(())
+    }
+    fn leave_node(&mut self, _id: NodeId) {
+        self.negated.pop();
+        self.visited.push(']');
+    }
+}
+
+#[test]
+fn stop_at_first_negated_atom() {
+    let expr = parse("(A | B) & !(C | D) | !E");
+    let mut visitor = FirstNegatedAtom::default();
+    assert_eq!(expr.walk(&mut visitor), ControlFlow::Break(()));
+    assert_eq!(visitor.found, Some('C'));
+    // neither D nor E were visited
+    assert_eq!(visitor.visited, "[[[AB][[C");
+    let expr = parse("(A | B) & C");
+    let mut visitor = FirstNegatedAtom::default();
+    assert_eq!(expr.walk(&mut visitor), ControlFlow::Continue(()));
+    assert_eq!(visitor.found, None);
+    assert_eq!(visitor.visited, "[[AB]C]");
+}
+
+/// collect the atoms which aren't under a negation
+#[derive(Default)]
+struct PositiveAtoms {
+    atoms: String,
+    entered: Vec<NodeId>,
+}
+
+impl Visitor<BoolOperator, char> for PositiveAtoms {
+    fn enter_node(&mut self, id: NodeId, node: &Node<BoolOperator>) -> VisitAction {
+        if node.operator == Some(Not) {
+            return VisitAction::SkipChildren;
+        }
+        self.entered.push(id);
+        VisitAction::Continue
+    }
+    fn visit_atom(&mut self, _id: AtomId, &atom: &char) -> ControlFlow<()> {
+        self.atoms.push(atom);
+        ControlFlow::Continue(())
+    }
+    fn leave_node(&mut self, id: NodeId) {
+        // skipped nodes aren't left
+        assert_eq!(self.entered.pop(), Some(id));
+    }
+}
+
+#[test]
+fn skip_negations() {
+    let expr = parse("(A | !B) & !(C | D) | E");
+    let mut visitor = PositiveAtoms::default();
+    assert_eq!(expr.walk(&mut visitor), ControlFlow::Continue(()));
+    assert_eq!(visitor.atoms, "AE");
+    assert!(visitor.entered.is_empty());
+}
+
+/// lowercase the atoms, and flip the operators directly under a negation
+struct Normalizer {
+    depths: Vec<(char, usize)>,
+}
+
+impl VisitorMut<BoolOperator, char> for Normalizer {
+    fn visit_operator(
+        &mut self,
+        _id: NodeId,
+        operator: &mut BoolOperator,
+        unary: bool,
+        context: VisitContext<'_, BoolOperator>,
+    ) {
+        if context.parent_operator == Some(&Not) && !unary {
+            *operator = match operator {
+                And => Or,
+                _ => And,
+            };
+        }
+    }
+    fn visit_atom(
+        &mut self,
+        _id: AtomId,
+        atom: &mut char,
+        context: VisitContext<'_, BoolOperator>,
+    ) {
+        *atom = atom.to_ascii_lowercase();
+        self.depths.push((*atom, context.depth));
+    }
+}
+
+#[test]
+fn normalize_with_mutable_visitor() {
+    let mut expr = parse("!(A | B) & (C | !(D & !(E | F)))");
+    let mut normalizer = Normalizer { depths: Vec::new() };
+    expr.walk_mut(&mut normalizer);
+    check_links(&expr);
+    assert_eq!(tokens_string(&expr), "!(a&b)&(c|!(d|!(e&f)))");
+    assert_eq!(
+        normalizer.depths,
+        vec![('a', 3), ('b', 3), ('c', 2), ('d', 4), ('e', 6), ('f', 6)],
+    );
+}
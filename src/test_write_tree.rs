@@ -0,0 +1,54 @@
+//! tests of the writing of the debug representation of trees
+
+use crate::test_util::*;
+
+#[test]
+fn write_tree_snapshot() {
+    let expr = parse("(A | B) & !(C | D | E)");
+    let mut s = String::new();
+    expr.write_tree(&mut s).unwrap();
+    assert_eq!(
+        s,
+        "\
+[0] Some(And)
+ [1] Some(Or)
+  'A'
+  'B'
+ [2] Some(Not)
+  [4] Some(Or)
+   [3] Some(Or)
+    'C'
+    'D'
+   'E'
+  -
+"
+    );
+}
+
+#[test]
+fn write_tree_of_incomplete_expression() {
+    let expr = parse("!A |");
+    let mut s = String::new();
+    expr.write_tree(&mut s).unwrap();
+    assert_eq!(
+        s,
+        "\
+[0] Some(Or)
+ [1] Some(Not)
+  'A'
+  -
+ -
+"
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn write_tree_io_gives_the_same_output() {
+    let expr = parse("A & (B | C)");
+    let mut s = String::new();
+    expr.write_tree(&mut s).unwrap();
+    let mut bytes = Vec::new();
+    expr.write_tree_io(&mut bytes).unwrap();
+    assert_eq!(String::from_utf8(bytes).unwrap(), s);
+}
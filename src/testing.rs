@@ -0,0 +1,154 @@
+//! [proptest](https://docs.rs/proptest) strategies generating
+//! expression trees, for property based tests.
+//!
+//! Trees are generated by pushing tokens, so they're always valid
+//! and complete. Shrinking tends toward trees with fewer atoms and
+//! less nesting.
+
+use {
+    crate::*,
+    proptest::{prelude::*, strategy::BoxedStrategy},
+    std::fmt,
+};
+
+/// The shape of a generated expression
+#[derive(Debug, Clone)]
+enum Shape<Op, Atom> {
+    Atom(Atom),
+    Unary(Op, Box<Shape<Op, Atom>>),
+    Binary(Op, Box<Shape<Op, Atom>>, Box<Shape<Op, Atom>>),
+    Group(Box<Shape<Op, Atom>>),
+}
+
+impl<Op, Atom> Shape<Op, Atom>
+where
+    Op: Clone,
+    Atom: Clone,
+{
+    /// push the operand of an operation, in parenthesis if it's a
+    /// binary operation which wouldn't be built as operand otherwise
+    fn push_operand_tokens(&self, tokens: &mut Vec<Token<Op, Atom>>) {
+        if let Self::Binary(..) = self {
+            tokens.push(Token::OpeningParenthesis);
+            self.push_tokens(tokens);
+            tokens.push(Token::ClosingParenthesis);
+        } else {
+            self.push_tokens(tokens);
+        }
+    }
+    fn push_tokens(&self, tokens: &mut Vec<Token<Op, Atom>>) {
+        match self {
+            Self::Atom(atom) => tokens.push(Token::Atom(atom.clone())),
+            Self::Unary(op, operand) => {
+                tokens.push(Token::Operator(op.clone()));
+                operand.push_operand_tokens(tokens);
+            }
+            Self::Binary(op, left, right) => {
+                // the builder being left associative, the left operand
+                // doesn't need parenthesis
+                left.push_tokens(tokens);
+                tokens.push(Token::Operator(op.clone()));
+                right.push_operand_tokens(tokens);
+            }
+            Self::Group(content) => {
+                tokens.push(Token::OpeningParenthesis);
+                content.push_tokens(tokens);
+                tokens.push(Token::ClosingParenthesis);
+            }
+        }
+    }
+}
+
+fn arb_shape<Op, Atom, BS, US, AS>(
+    binary_op_strategy: BS,
+    unary_op_strategy: US,
+    atom_strategy: AS,
+    max_atoms: u32,
+    max_depth: u32,
+) -> BoxedStrategy<Shape<Op, Atom>>
+where
+    Op: fmt::Debug + Clone + 'static,
+    Atom: fmt::Debug + Clone + 'static,
+    BS: Strategy<Value = Op> + Clone + 'static,
+    US: Strategy<Value = Op> + Clone + 'static,
+    AS: Strategy<Value = Atom> + 'static,
+{
+    atom_strategy
+        .prop_map(Shape::Atom)
+        .prop_recursive(max_depth, max_atoms, 2, move |inner| {
+            prop_oneof![
+                4 => (binary_op_strategy.clone(), inner.clone(), inner.clone())
+                    .prop_map(|(op, left, right)| Shape::Binary(op, Box::new(left), Box::new(right))),
+                2 => (unary_op_strategy.clone(), inner.clone())
+                    .prop_map(|(op, operand)| Shape::Unary(op, Box::new(operand))),
+                1 => inner.prop_map(|content| Shape::Group(Box::new(content))),
+            ]
+        })
+        .boxed()
+}
+
+/// a strategy generating the infix tokens of an expression, and the
+/// tree built by pushing them, so that a parser can be tested against
+/// the generated expressions.
+///
+/// The operators given by `binary_op_strategy` are only put between
+/// two operands, and the ones given by `unary_op_strategy` before one
+/// operand. The nesting of operations and groups is at most `max_depth`
+/// and the number of atoms is around `max_atoms` at most.
+pub fn arb_betree_with_tokens<Op, Atom, BS, US, AS>(
+    binary_op_strategy: BS,
+    unary_op_strategy: US,
+    atom_strategy: AS,
+    max_atoms: u32,
+    max_depth: u32,
+) -> impl Strategy<Value = (BeTree<Op, Atom>, Vec<Token<Op, Atom>>)>
+where
+    Op: fmt::Debug + Clone + PartialEq + 'static,
+    Atom: fmt::Debug + Clone + 'static,
+    BS: Strategy<Value = Op> + Clone + 'static,
+    US: Strategy<Value = Op> + Clone + 'static,
+    AS: Strategy<Value = Atom> + 'static,
+{
+    arb_shape(
+        binary_op_strategy,
+        unary_op_strategy,
+        atom_strategy,
+        max_atoms,
+        max_depth,
+    )
+    .prop_map(|shape| {
+        let mut tokens = Vec::new();
+        shape.push_tokens(&mut tokens);
+        let mut tree = BeTree::new();
+        for token in tokens.iter().cloned() {
+            tree.push(token);
+        }
+        (tree, tokens)
+    })
+}
+
+/// a strategy generating valid and complete expression trees
+/// (see `arb_betree_with_tokens`)
+pub fn arb_betree<Op, Atom, BS, US, AS>(
+    binary_op_strategy: BS,
+    unary_op_strategy: US,
+    atom_strategy: AS,
+    max_atoms: u32,
+    max_depth: u32,
+) -> impl Strategy<Value = BeTree<Op, Atom>>
+where
+    Op: fmt::Debug + Clone + PartialEq + 'static,
+    Atom: fmt::Debug + Clone + 'static,
+    BS: Strategy<Value = Op> + Clone + 'static,
+    US: Strategy<Value = Op> + Clone + 'static,
+    AS: Strategy<Value = Atom> + 'static,
+{
+    arb_betree_with_tokens(
+        binary_op_strategy,
+        unary_op_strategy,
+        atom_strategy,
+        max_atoms,
+        max_depth,
+    )
+    .prop_map(|(tree, _)| tree)
+}
@@ -0,0 +1,75 @@
+use core::fmt;
+
+/// Something that can be added to the tree
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Token<Op, Atom> {
+    Atom(Atom),
+    Operator(Op),
+    OpeningParenthesis,
+    ClosingParenthesis,
+}
+
+/// The kind of a token, without its content
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TokenKind {
+    Atom,
+    Operator,
+    OpeningParenthesis,
+    ClosingParenthesis,
+}
+
+impl<Op, Atom> Token<Op, Atom> {
+    /// return the kind of the token
+    pub fn kind(&self) -> TokenKind {
+        match self {
+            Self::Atom(_) => TokenKind::Atom,
+            Self::Operator(_) => TokenKind::Operator,
+            Self::OpeningParenthesis => TokenKind::OpeningParenthesis,
+            Self::ClosingParenthesis => TokenKind::ClosingParenthesis,
+        }
+    }
+
+    /// build a token with the atom, if any, mapped with the given function
+    pub fn map_atom<Atom2, F>(self, f: F) -> Token<Op, Atom2>
+    where
+        F: FnOnce(Atom) -> Atom2,
+    {
+        match self {
+            Self::Atom(atom) => Token::Atom(f(atom)),
+            Self::Operator(op) => Token::Operator(op),
+            Self::OpeningParenthesis => Token::OpeningParenthesis,
+            Self::ClosingParenthesis => Token::ClosingParenthesis,
+        }
+    }
+
+    /// build a token with the operator, if any, mapped with the given function
+    pub fn map_op<Op2, F>(self, f: F) -> Token<Op2, Atom>
+    where
+        F: FnOnce(Op) -> Op2,
+    {
+        match self {
+            Self::Atom(atom) => Token::Atom(atom),
+            Self::Operator(op) => Token::Operator(f(op)),
+            Self::OpeningParenthesis => Token::OpeningParenthesis,
+            Self::ClosingParenthesis => Token::ClosingParenthesis,
+        }
+    }
+}
+
+/// Write the atom or the operator, or the parenthesis as `(` or `)`
+impl<Op, Atom> fmt::Display for Token<Op, Atom>
+where
+    Op: fmt::Display,
+    Atom: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Atom(atom) => atom.fmt(f),
+            Self::Operator(op) => op.fmt(f),
+            Self::OpeningParenthesis => f.write_str("("),
+            Self::ClosingParenthesis => f.write_str(")"),
+        }
+    }
+}
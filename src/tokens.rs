@@ -0,0 +1,92 @@
+use {crate::*, core::fmt};
+
+/// Conversion of the tree to tokens
+impl<Op, Atom> BeTree<Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone,
+{
+    /// push the tokens of the child. When `as_written`, the parenthesis
+    /// are the explicit groups, plus the ones the structure requires,
+    /// instead of all the groups
+    fn push_child_tokens(
+        &self,
+        child: Child,
+        needs_group: bool,
+        as_written: bool,
+        tokens: &mut Vec<Token<Op, Atom>>,
+    ) {
+        match child {
+            Child::None => {}
+            Child::Atom(atom_idx) => tokens.push(Token::Atom(self.atoms[atom_idx].clone())),
+            Child::Node(node_idx) => {
+                let node = &self.nodes[node_idx];
+                let explicit = as_written && node.explicit;
+                match &node.operator {
+                    None => {
+                        let in_parens = explicit || !as_written;
+                        if in_parens {
+                            tokens.push(Token::OpeningParenthesis);
+                        }
+                        self.push_child_tokens(node.left(), false, as_written, tokens);
+                        if in_parens {
+                            tokens.push(Token::ClosingParenthesis);
+                        }
+                    }
+                    Some(op) if node.unary => {
+                        if explicit {
+                            tokens.push(Token::OpeningParenthesis);
+                        }
+                        tokens.push(Token::Operator(op.clone()));
+                        self.push_child_tokens(node.left(), true, as_written, tokens);
+                        if explicit {
+                            tokens.push(Token::ClosingParenthesis);
+                        }
+                    }
+                    Some(op) => {
+                        let in_parens = needs_group || explicit;
+                        if in_parens {
+                            tokens.push(Token::OpeningParenthesis);
+                        }
+                        self.push_child_tokens(node.left(), false, as_written, tokens);
+                        tokens.push(Token::Operator(op.clone()));
+                        self.push_child_tokens(node.right(), true, as_written, tokens);
+                        if in_parens {
+                            tokens.push(Token::ClosingParenthesis);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// return the tokens which, pushed in order in a new tree, would
+    /// build an equivalent expression.
+    ///
+    /// Parenthesis of the original expression are kept, and new ones
+    /// are added where the structure requires them (for example after
+    /// a rewriting).
+    pub fn to_tokens(&self) -> Vec<Token<Op, Atom>> {
+        let mut tokens = Vec::new();
+        let head = &self.nodes[self.head];
+        if head.operator.is_none() {
+            self.push_child_tokens(head.left(), false, false, &mut tokens);
+        } else {
+            self.push_child_tokens(Child::Node(self.head), false, false, &mut tokens);
+        }
+        tokens
+    }
+
+    /// return the tokens of the expression as it was written, with
+    /// exactly the parenthesis of the user, even redundant ones like
+    /// in `((a)) & (b | c)` (see `was_explicit_group`).
+    ///
+    /// Parenthesis are still added where the structure requires them,
+    /// for example after a rewriting, so that the tokens always build
+    /// an equivalent expression.
+    pub fn to_tokens_as_written(&self) -> Vec<Token<Op, Atom>> {
+        let mut tokens = Vec::new();
+        self.push_child_tokens(Child::Node(self.head), false, true, &mut tokens);
+        tokens
+    }
+}
@@ -0,0 +1,179 @@
+use {crate::*, core::fmt};
+
+/// A low-level builder of trees, for code which produces trees
+/// directly instead of parsing tokens.
+///
+/// Atoms and nodes are added, then linked, in any order. `finish`
+/// checks the structure, sets the parent links and builds a compact
+/// tree, which holds only the nodes and atoms reachable from the root.
+#[derive(Debug, Clone)]
+pub struct TreeBuilder<Op, Atom> {
+    atoms: Vec<Atom>,
+    nodes: Vec<Node<Op>>,
+}
+
+impl<Op, Atom> Default for TreeBuilder<Op, Atom> {
+    fn default() -> Self {
+        Self {
+            atoms: Vec::new(),
+            nodes: Vec::new(),
+        }
+    }
+}
+
+impl<Op, Atom> TreeBuilder<Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// add an atom, not yet linked
+    pub fn add_atom(&mut self, atom: Atom) -> AtomId {
+        self.atoms.push(atom);
+        AtomId::from_index(self.atoms.len() - 1)
+    }
+
+    /// add a node without children, which is a group when there's no
+    /// operator
+    pub fn add_node(&mut self, operator: Option<Op>, unary: bool) -> NodeId {
+        self.nodes.push(Node::new(operator, unary));
+        NodeId::from_index(self.nodes.len() - 1)
+    }
+
+    /// set the left child (the operand of a unary operation, or the
+    /// content of a group), replacing the previous one
+    pub fn set_left(&mut self, node: NodeId, child: Child) -> Result<(), StructureError> {
+        self.set_child(node, Side::Left, child)
+    }
+
+    /// set the right child, replacing the previous one
+    pub fn set_right(&mut self, node: NodeId, child: Child) -> Result<(), StructureError> {
+        self.set_child(node, Side::Right, child)
+    }
+
+    fn set_child(&mut self, node: NodeId, side: Side, child: Child) -> Result<(), StructureError> {
+        self.nodes
+            .get_mut(node.index())
+            .ok_or(StructureError::UnknownNode(node))?
+            .set_child(side, child);
+        Ok(())
+    }
+
+    /// check the structure reachable from the root, and build the tree.
+    ///
+    /// Links must point to added nodes and atoms, and no node may be
+    /// the child of several nodes, which also excludes cycles (an atom
+    /// may be shared, as after `dedup_atoms`). A right child is only
+    /// allowed in a binary operation whose left child is set.
+    ///
+    /// In the built tree, the root is the head, nodes are numbered in
+    /// pre-order and atoms from left to right, so ids given by the
+    /// builder aren't valid anymore.
+    pub fn finish(self, root: NodeId) -> Result<BeTree<Op, Atom>, StructureError> {
+        if root.index() >= self.nodes.len() {
+            return Err(StructureError::UnknownNode(root));
+        }
+        let mut reached_nodes = vec![false; self.nodes.len()];
+        // the reached nodes in pre-order
+        let mut order = Vec::new();
+        let mut stack = vec![root];
+        reached_nodes[root.index()] = true;
+        while let Some(node_idx) = stack.pop() {
+            order.push(node_idx);
+            let node = &self.nodes[node_idx.index()];
+            if !node.has_valid_shape() {
+                return Err(StructureError::MisplacedChild(node_idx));
+            }
+            let mut node_children = Vec::new();
+            for child in [node.left(), node.right()] {
+                match child {
+                    Child::None => {}
+                    Child::Atom(atom_idx) => {
+                        if atom_idx.index() >= self.atoms.len() {
+                            return Err(StructureError::UnknownAtom(atom_idx));
+                        }
+                    }
+                    Child::Node(child_idx) => {
+                        let reached = reached_nodes
+                            .get_mut(child_idx.index())
+                            .ok_or(StructureError::UnknownNode(child_idx))?;
+                        if *reached {
+                            return Err(StructureError::MultipleLinks(child_idx));
+                        }
+                        *reached = true;
+                        node_children.push(child_idx);
+                    }
+                }
+            }
+            // the left subtree must be visited first
+            stack.extend(node_children.into_iter().rev());
+        }
+        let mut node_ids = vec![NodeId::from_index(0); self.nodes.len()];
+        for (i, &node_idx) in order.iter().enumerate() {
+            node_ids[node_idx.index()] = NodeId::from_index(i);
+        }
+        // the reached atoms from left to right, a shared atom being
+        // kept once, at its first position
+        let mut atom_order = Vec::new();
+        let mut atom_ids = vec![None; self.atoms.len()];
+        let mut stack = vec![Child::Node(root)];
+        while let Some(child) = stack.pop() {
+            match child {
+                Child::None => {}
+                Child::Atom(atom_idx) => {
+                    if atom_ids[atom_idx.index()].is_none() {
+                        atom_ids[atom_idx.index()] = Some(AtomId::from_index(atom_order.len()));
+                        atom_order.push(atom_idx);
+                    }
+                }
+                Child::Node(node_idx) => {
+                    let node = &self.nodes[node_idx.index()];
+                    stack.push(node.right());
+                    stack.push(node.left());
+                }
+            }
+        }
+        let TreeBuilder { atoms, nodes } = self;
+        let mut atoms: Vec<Option<Atom>> = atoms.into_iter().map(Some).collect();
+        let mut nodes: Vec<Option<Node<Op>>> = nodes.into_iter().map(Some).collect();
+        let renumber = |child: Child| match child {
+            Child::None => Child::None,
+            Child::Atom(atom_idx) => Child::Atom(atom_ids[atom_idx.index()].unwrap()),
+            Child::Node(node_idx) => Child::Node(node_ids[node_idx.index()]),
+        };
+        let mut tree = BeTree::new();
+        tree.nodes.clear();
+        let mut op_count = 0;
+        for &node_idx in &order {
+            let mut node = nodes[node_idx.index()].take().unwrap();
+            if node.operator.is_some() {
+                op_count += 1;
+            }
+            node.set_left(renumber(node.left()));
+            node.set_right(renumber(node.right()));
+            tree.nodes.push(node);
+        }
+        // parents are set from the children
+        for i in 0..tree.nodes.len() {
+            let parent = NodeId::from_index(i);
+            for child in [tree.nodes[parent].left(), tree.nodes[parent].right()] {
+                if let Child::Node(child_idx) = child {
+                    tree.nodes[child_idx].set_parent(Some(parent));
+                }
+            }
+        }
+        tree.nodes[NodeId::from_index(0)].set_parent(None);
+        for atom_idx in atom_order {
+            tree.atoms.push(atoms[atom_idx.index()].take().unwrap());
+        }
+        tree.op_count = op_count;
+        if !tree.atoms.is_empty() {
+            tree.last_pushed = TokenType::ClosingPar;
+        }
+        tree.debug_check_structure();
+        Ok(tree)
+    }
+}
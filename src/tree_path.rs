@@ -0,0 +1,63 @@
+use {crate::*, core::fmt};
+
+/// A position in a tree, given as the sides to take, from the head,
+/// to go down to a node or an atom.
+///
+/// Contrary to node and atom ids, which are indexes in the arenas of
+/// the tree, paths only depend on the structure of the expression:
+/// they stay valid when the tree is reorganized without being changed
+/// (for example by `compact` or `normalize`). On the other hand, they're
+/// invalidated by any structural edit above the position, and resolving
+/// them costs a walk from the head.
+///
+/// Groups (i.e. parenthesis) are nodes, and are part of paths.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct TreePath(pub Vec<Side>);
+
+/// Paths
+impl<Op, Atom> BeTree<Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone,
+{
+    /// find what's at the end of the path, or `None` if the
+    /// path doesn't lead to a node or an atom.
+    ///
+    /// The empty path leads to the head.
+    pub fn resolve(&self, path: &TreePath) -> Option<Child> {
+        let mut child = Child::Node(self.head);
+        for &side in &path.0 {
+            let Child::Node(node_idx) = child else {
+                return None;
+            };
+            child = self.nodes[node_idx].child(side);
+        }
+        child.is_some().then_some(child)
+    }
+
+    /// compute the path from the head to the node, or `None` if the
+    /// node isn't reachable from the head
+    pub fn path_to_node(&self, node_id: NodeId) -> Option<TreePath> {
+        if node_id.index() >= self.nodes.len() {
+            return None;
+        }
+        let mut sides = Vec::new();
+        let mut node_idx = node_id;
+        while node_idx != self.head {
+            let (parent_idx, side) = self.checked_link(node_idx)?;
+            sides.push(side);
+            node_idx = parent_idx;
+        }
+        sides.reverse();
+        Some(TreePath(sides))
+    }
+
+    /// compute the path from the head to the atom, or `None` if the
+    /// atom isn't reachable from the head
+    pub fn path_to_atom(&self, atom_id: AtomId) -> Option<TreePath> {
+        let (node_idx, side) = self.node_of_atom(atom_id)?;
+        let mut path = self.path_to_node(node_idx)?;
+        path.0.push(side);
+        Some(path)
+    }
+}
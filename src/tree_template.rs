@@ -0,0 +1,134 @@
+use {crate::*, core::fmt};
+
+/// The structure of a tree without its atoms, to be instantiated or
+/// evaluated with different sets of atoms.
+///
+/// Get it with `BeTree::into_template`. The atoms are referred to by
+/// their index in the atom list given with the template, so the atoms
+/// given to `instantiate` or `eval_with` must be in the same order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TreeTemplate<Op> {
+    nodes: Nodes<Op>,
+    head: NodeId,
+    tail: NodeId,
+    last_pushed: TokenType,
+    op_count: usize,
+    openness: usize,
+    atom_count: usize,
+}
+
+impl<Op> TreeTemplate<Op> {
+    /// the number of atoms the template must be given
+    pub fn atom_count(&self) -> usize {
+        self.atom_count
+    }
+
+    fn check_atom_count(&self, found: usize) -> Result<(), TemplateError> {
+        if found == self.atom_count {
+            Ok(())
+        } else {
+            Err(TemplateError::AtomCountMismatch {
+                expected: self.atom_count,
+                found,
+            })
+        }
+    }
+
+    fn eval_child<Atom, R, EvalAtom, EvalOp, ShortCircuit>(
+        &self,
+        atoms: &[Atom],
+        eval_atom: &EvalAtom,
+        eval_op: &EvalOp,
+        short_circuit: &ShortCircuit,
+        child: Child,
+    ) -> Option<R>
+    where
+        EvalAtom: Fn(&Atom) -> R,
+        EvalOp: Fn(&Op, R, Option<R>) -> R,
+        ShortCircuit: Fn(&Op, &R) -> bool,
+    {
+        let node_idx = match child {
+            Child::None => return None,
+            Child::Atom(atom_idx) => return Some(eval_atom(&atoms[atom_idx.index()])),
+            Child::Node(node_idx) => node_idx,
+        };
+        let node = &self.nodes[node_idx];
+        let left_value = self.eval_child(atoms, eval_atom, eval_op, short_circuit, node.left());
+        let Some(op) = &node.operator else {
+            return left_value;
+        };
+        let left_value = left_value?;
+        if short_circuit(op, &left_value) {
+            return Some(left_value);
+        }
+        let right_value = self.eval_child(atoms, eval_atom, eval_op, short_circuit, node.right());
+        Some(eval_op(op, left_value, right_value))
+    }
+
+    /// evaluate the expression with the given atoms, as `BeTree::eval`
+    /// would on the instantiated tree, but without building it
+    pub fn eval_with<Atom, R, EvalAtom, EvalOp, ShortCircuit>(
+        &self,
+        atoms: &[Atom],
+        eval_atom: EvalAtom,
+        eval_op: EvalOp,
+        short_circuit: ShortCircuit,
+    ) -> Result<Option<R>, TemplateError>
+    where
+        EvalAtom: Fn(&Atom) -> R,
+        EvalOp: Fn(&Op, R, Option<R>) -> R,
+        ShortCircuit: Fn(&Op, &R) -> bool,
+    {
+        self.check_atom_count(atoms.len())?;
+        Ok(self.eval_child(
+            atoms,
+            &eval_atom,
+            &eval_op,
+            &short_circuit,
+            Child::Node(self.head),
+        ))
+    }
+}
+
+impl<Op: Clone> TreeTemplate<Op> {
+    /// build a tree with the structure of the template and the given
+    /// atoms, which must be as many as the atoms of the original tree
+    pub fn instantiate<Atom>(&self, atoms: Vec<Atom>) -> Result<BeTree<Op, Atom>, TemplateError> {
+        self.check_atom_count(atoms.len())?;
+        Ok(BeTree {
+            atoms: atoms.into_iter().collect(),
+            nodes: self.nodes.clone(),
+            head: self.head,
+            tail: self.tail,
+            last_pushed: self.last_pushed,
+            op_count: self.op_count,
+            openness: self.openness,
+            epoch: 0,
+        })
+    }
+}
+
+/// Templates
+impl<Op, Atom> BeTree<Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone,
+{
+    /// split the tree into its structure and its atoms, so that the
+    /// structure can be shared by trees differing only by their atoms.
+    ///
+    /// The atoms are returned in the order of their ids, including the
+    /// ones which aren't reachable.
+    pub fn into_template(self) -> (TreeTemplate<Op>, Vec<Atom>) {
+        let template = TreeTemplate {
+            nodes: self.nodes,
+            head: self.head,
+            tail: self.tail,
+            last_pushed: self.last_pushed,
+            op_count: self.op_count,
+            openness: self.openness,
+            atom_count: self.atoms.len(),
+        };
+        (template, self.atoms.into_iter().collect())
+    }
+}
@@ -0,0 +1,171 @@
+use {crate::*, core::fmt};
+
+/// Edits of a tree applied all together or not at all, as given
+/// to the closure of `BeTree::edit`.
+///
+/// The tree is saved before the first edit, and restored if the
+/// closure fails or panics.
+pub struct TreeTransaction<'t, Op, Atom> {
+    tree: &'t mut BeTree<Op, Atom>,
+    backup: Option<BeTree<Op, Atom>>,
+}
+
+impl<Op, Atom> TreeTransaction<'_, Op, Atom> {
+    /// restore the tree saved before the first edit, if any
+    fn roll_back(&mut self) {
+        if let Some(mut backup) = self.backup.take() {
+            // the epoch must not go back: ids obtained during the
+            // transaction would be valid again after other edits
+            if backup.epoch != self.tree.epoch {
+                backup.epoch = self.tree.epoch.wrapping_add(1);
+            }
+            *self.tree = backup;
+        }
+    }
+}
+
+/// A transaction is dropped with its backup only when the closure
+/// panicked, as the backup is otherwise taken when the closure returns
+impl<Op, Atom> Drop for TreeTransaction<'_, Op, Atom> {
+    fn drop(&mut self) {
+        self.roll_back();
+    }
+}
+
+impl<Op, Atom> TreeTransaction<'_, Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone,
+{
+    /// the tree, with the edits already done in the transaction
+    pub fn tree(&self) -> &BeTree<Op, Atom> {
+        self.tree
+    }
+    /// the tree, to be edited in the transaction in any way
+    pub fn tree_mut(&mut self) -> &mut BeTree<Op, Atom> {
+        if self.backup.is_none() {
+            self.backup = Some(self.tree.clone());
+        }
+        self.tree
+    }
+    /// tell whether the tree may have been changed in the transaction
+    pub fn is_modified(&self) -> bool {
+        self.backup.is_some()
+    }
+    /// remove an atom, as `BeTree::remove_atom`
+    pub fn remove_atom(&mut self, atom_id: AtomId) -> Result<(), EditError> {
+        self.tree_mut().remove_atom(atom_id)
+    }
+    /// remove a subexpression, as `BeTree::prune`
+    pub fn prune(&mut self, node_id: NodeId) -> Result<(), EditError> {
+        self.tree_mut().prune(node_id)
+    }
+    /// replace an operator, as `BeTree::replace_operator`
+    pub fn replace_operator(&mut self, from: &Op, to: Op) -> usize {
+        self.tree_mut().replace_operator(from, to)
+    }
+    /// wrap the whole expression, as `BeTree::wrap_unary`
+    pub fn wrap_unary(&mut self, operator: Op) {
+        self.tree_mut().wrap_unary(operator)
+    }
+    /// wrap the subexpression at the node in a unary operator
+    pub fn wrap_node_unary(&mut self, node_id: NodeId, operator: Op) -> Result<(), EditError> {
+        let tree = self.tree_mut();
+        if node_id.index() >= tree.nodes.len() {
+            return Err(EditError::UnknownNode(node_id));
+        }
+        let mut cursor = tree
+            .cursor_at_mut(node_id)
+            .ok_or(EditError::UnreachableNode(node_id))?;
+        cursor.wrap_in_unary(operator);
+        Ok(())
+    }
+    /// swap the operands of a binary operation, as `BeTree::swap_children`
+    pub fn swap_children(&mut self, node_id: NodeId) -> Result<(), EditError> {
+        self.tree_mut().swap_children(node_id)
+    }
+    /// rotate an operation, as `BeTree::rotate_left`
+    pub fn rotate_left(&mut self, node_id: NodeId) -> Result<(), EditError> {
+        self.tree_mut().rotate_left(node_id)
+    }
+    /// rotate an operation, as `BeTree::rotate_right`
+    pub fn rotate_right(&mut self, node_id: NodeId) -> Result<(), EditError> {
+        self.tree_mut().rotate_right(node_id)
+    }
+    /// remove a subexpression and return it, as `BeTree::detach`
+    pub fn detach(&mut self, node_id: NodeId) -> Result<DetachedSubtree<Op, Atom>, EditError> {
+        self.tree_mut().detach(node_id)
+    }
+    /// attach a subexpression, as `BeTree::attach`
+    pub fn attach(
+        &mut self,
+        detached: DetachedSubtree<Op, Atom>,
+        target: NodeId,
+        side: Side,
+        joining_op: Option<Op>,
+    ) -> Result<(), EditError> {
+        self.tree_mut().attach(detached, target, side, joining_op)
+    }
+}
+
+/// Transactions
+impl<Op, Atom> BeTree<Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone,
+{
+    /// apply several edits as one: if the closure returns an error or
+    /// panics, the tree is restored to its state before the first edit,
+    /// so that it's never left half edited.
+    ///
+    /// The tree is cloned before the first edit only, so a transaction
+    /// without edit doesn't allocate. Once all edits are done, the
+    /// structure is checked with `validate_structure`: an inconsistent
+    /// tree is restored too, and the error is
+    /// `EditError::InvalidStructure`.
+    ///
+    /// When a transaction having done structural edits is rolled back,
+    /// the restored tree gets a new edits epoch (see `edits_epoch`), so
+    /// that the ids obtained before or during the transaction are seen
+    /// as stale.
+    ///
+    /// ```
+    /// use bet::*;
+    /// let mut expr = BeTree::new();
+    /// expr.push_atom('A');
+    /// expr.push_operator('&');
+    /// expr.push_atom('B');
+    /// let before = expr.clone();
+    /// let result = expr.edit(|tx| {
+    ///     tx.replace_operator(&'&', '|');
+    ///     tx.remove_atom(AtomId::new(1000))
+    /// });
+    /// assert_eq!(result, Err(EditError::UnknownAtom(AtomId::new(1000))));
+    /// assert_eq!(expr, before);
+    /// ```
+    pub fn edit<E, F>(&mut self, f: F) -> Result<(), E>
+    where
+        E: From<EditError>,
+        F: FnOnce(&mut TreeTransaction<'_, Op, Atom>) -> Result<(), E>,
+    {
+        let mut transaction = TreeTransaction {
+            tree: self,
+            backup: None,
+        };
+        let result = f(&mut transaction).and_then(|()| {
+            if transaction.is_modified() {
+                transaction
+                    .tree
+                    .validate_structure()
+                    .map_err(|e| EditError::InvalidStructure(e).into())
+            } else {
+                Ok(())
+            }
+        });
+        match result {
+            Ok(()) => transaction.backup = None,
+            Err(_) => transaction.roll_back(),
+        }
+        result
+    }
+}
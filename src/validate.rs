@@ -0,0 +1,119 @@
+use {crate::*, core::fmt};
+
+/// Structure validation
+impl<Op, Atom> BeTree<Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone,
+{
+    /// count the groups which are open between the tail and the head,
+    /// or return `None` if the head can't be reached from the tail
+    pub(crate) fn open_groups(&self) -> Option<usize> {
+        // Going up from the tail, every node which isn't a unary operator
+        // is a group opened and not yet closed, except the head.
+        let mut open_groups = 0;
+        let mut node_idx = self.tail;
+        for _ in 0..self.nodes.len() {
+            if node_idx == self.head {
+                return Some(open_groups);
+            }
+            let node = self.nodes.get(node_idx.index())?;
+            if !node.unary {
+                open_groups += 1;
+            }
+            node_idx = node.parent()?;
+        }
+        None
+    }
+
+    /// check the internal consistency of the tree: the links between
+    /// the nodes reachable from the head (children and parents must
+    /// agree, there must be no cycle, no link out of the arenas), and the
+    /// building state (the tail must be reachable, and the count of open
+    /// parenthesis must match the groups open between the tail and the head).
+    ///
+    /// Nodes and atoms which aren't reachable from the head aren't checked.
+    pub fn validate_structure(&self) -> Result<(), StructureError> {
+        parts::check_reachable_structure(self.atoms.len(), &self.nodes, self.head)?;
+        if self.tail.index() >= self.nodes.len() {
+            return Err(StructureError::UnknownNode(self.tail));
+        }
+        let open_groups = self
+            .open_groups()
+            .ok_or(StructureError::UnreachableTail(self.tail))?;
+        if open_groups != self.openness {
+            return Err(StructureError::WrongOpenness {
+                openness: self.openness,
+                open_groups,
+            });
+        }
+        Ok(())
+    }
+
+    /// check the structure of the tree as `validate_structure` does,
+    /// then check that it's an expression which can be evaluated
+    /// without ambiguity: there must be no empty group (like the `()`
+    /// in `a & ()`, see `eval_with_empty_groups`).
+    pub fn validate(&self) -> Result<(), StructureError> {
+        self.validate_structure()?;
+        if let Some(&node_idx) = self.empty_groups().first() {
+            return Err(StructureError::EmptyGroup(node_idx));
+        }
+        Ok(())
+    }
+
+    /// panic with a precise message if the tree isn't internally
+    /// consistent (see `validate_structure`).
+    ///
+    /// This is intended for tests of code modifying trees.
+    #[cfg(any(test, feature = "debug-checks"))]
+    pub fn check_invariants(&self) {
+        if let Err(e) = self.validate_structure() {
+            panic!("inconsistent tree: {}\n{}", e, ArenaDump(self));
+        }
+    }
+
+    /// with debug assertions, check the structure after an edit
+    pub(crate) fn debug_check_structure(&self) {
+        #[cfg(debug_assertions)]
+        if let Err(e) = self.validate_structure() {
+            panic!("edit left an inconsistent tree: {}\n{}", e, ArenaDump(self));
+        }
+    }
+}
+
+/// The nodes and atoms of a tree, one per line in arena order.
+///
+/// Unlike the `Debug` form, it doesn't follow the links, so that
+/// it can be written for an inconsistent tree, with cycles or links
+/// out of the arenas.
+#[cfg(any(test, debug_assertions, feature = "debug-checks"))]
+struct ArenaDump<'t, Op, Atom>(&'t BeTree<Op, Atom>);
+
+#[cfg(any(test, debug_assertions, feature = "debug-checks"))]
+impl<Op: fmt::Debug, Atom: fmt::Debug> fmt::Display for ArenaDump<'_, Op, Atom> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let tree = self.0;
+        writeln!(
+            f,
+            "head: {:?}, tail: {:?}, openness: {}",
+            tree.head, tree.tail, tree.openness,
+        )?;
+        for (idx, node) in tree.nodes.iter().enumerate() {
+            writeln!(
+                f,
+                "[{}] {:?}{} parent: {:?} left: {:?} right: {:?}",
+                idx,
+                node.operator,
+                if node.unary { " unary" } else { "" },
+                node.parent(),
+                node.left(),
+                node.right(),
+            )?;
+        }
+        for (idx, atom) in tree.atoms.iter().enumerate() {
+            writeln!(f, "a{} {:?}", idx, atom)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,175 @@
+use {
+    crate::*,
+    core::{fmt, ops::ControlFlow},
+};
+
+/// What to do after having entered a node, in a `Visitor`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VisitAction {
+    /// Visit the children of the node
+    Continue,
+    /// Don't visit the children of the node (nor leave it) but go on
+    /// with the rest of the tree
+    SkipChildren,
+    /// Stop the walk
+    Stop,
+}
+
+/// A visitor of the tree, given to `BeTree::walk`.
+///
+/// All methods have a default implementation doing nothing, so that
+/// only the interesting ones need to be implemented.
+pub trait Visitor<Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone,
+{
+    /// called when entering a node, before its descendants
+    fn enter_node(&mut self, _id: NodeId, _node: &Node<Op>) -> VisitAction {
+        VisitAction::Continue
+    }
+    /// called on an atom. Return `ControlFlow::Break` to stop the walk.
+    fn visit_atom(&mut self, _id: AtomId, _atom: &Atom) -> ControlFlow<()> {
+        ControlFlow::Continue(())
+    }
+    /// called when leaving a node, after its descendants
+    fn leave_node(&mut self, _id: NodeId) {}
+}
+
+/// Where the visited operator or atom is, as given to a `VisitorMut`
+#[derive(Debug, Clone, Copy)]
+pub struct VisitContext<'t, Op> {
+    /// The depth of the node or atom, the head being at depth 0
+    pub depth: usize,
+    /// The parent node, `None` for the head
+    pub parent: Option<NodeId>,
+    /// The operator of the parent node, if any (as it's visited before
+    /// its children, it's the operator after its own visit)
+    pub parent_operator: Option<&'t Op>,
+}
+
+/// A visitor of the tree which may change the operators and
+/// the atoms, given to `BeTree::walk_mut`.
+///
+/// The structure of the tree can't be changed this way (see `CursorMut`).
+pub trait VisitorMut<Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone,
+{
+    /// called on the operator of a node, before its descendants
+    fn visit_operator(
+        &mut self,
+        _id: NodeId,
+        _operator: &mut Op,
+        _unary: bool,
+        _context: VisitContext<'_, Op>,
+    ) {
+    }
+    /// called on an atom
+    fn visit_atom(&mut self, _id: AtomId, _atom: &mut Atom, _context: VisitContext<'_, Op>) {}
+}
+
+enum WalkStep {
+    Enter(Child),
+    Leave(NodeId),
+}
+
+/// Visitors
+impl<Op, Atom> BeTree<Op, Atom>
+where
+    Op: fmt::Debug + Clone + PartialEq,
+    Atom: fmt::Debug + Clone,
+{
+    /// walk the tree reachable from the head, depth first and from left
+    /// to right, calling the visitor when entering and leaving nodes and
+    /// when meeting atoms.
+    ///
+    /// Return `ControlFlow::Break` if the visitor stopped the walk.
+    pub fn walk<V>(&self, visitor: &mut V) -> ControlFlow<()>
+    where
+        V: Visitor<Op, Atom>,
+    {
+        let mut stack = vec![WalkStep::Enter(Child::Node(self.head))];
+        while let Some(step) = stack.pop() {
+            match step {
+                WalkStep::Enter(Child::None) => {}
+                WalkStep::Enter(Child::Atom(atom_idx)) => {
+                    visitor.visit_atom(atom_idx, &self.atoms[atom_idx])?;
+                }
+                WalkStep::Enter(Child::Node(node_idx)) => {
+                    let node = &self.nodes[node_idx];
+                    match visitor.enter_node(node_idx, node) {
+                        VisitAction::Continue => {
+                            stack.push(WalkStep::Leave(node_idx));
+                            stack.push(WalkStep::Enter(node.right()));
+                            stack.push(WalkStep::Enter(node.left()));
+                        }
+                        VisitAction::SkipChildren => {}
+                        VisitAction::Stop => {
+                            return ControlFlow::Break(());
+                        }
+                    }
+                }
+                WalkStep::Leave(node_idx) => {
+                    visitor.leave_node(node_idx);
+                }
+            }
+        }
+        ControlFlow::Continue(())
+    }
+
+    /// walk the tree reachable from the head, depth first and from left
+    /// to right, letting the visitor change the operators and the atoms.
+    ///
+    /// Nodes without operator (groups) aren't visited but count in the depth.
+    pub fn walk_mut<V>(&mut self, visitor: &mut V)
+    where
+        V: VisitorMut<Op, Atom>,
+    {
+        let mut stack = vec![(Child::Node(self.head), 0, None)];
+        while let Some((child, depth, parent)) = stack.pop() {
+            match child {
+                Child::None => {}
+                Child::Atom(atom_idx) => {
+                    let nodes = &self.nodes;
+                    let parent_operator =
+                        parent.and_then(|parent_idx: NodeId| nodes[parent_idx].operator.as_ref());
+                    let context = VisitContext {
+                        depth,
+                        parent,
+                        parent_operator,
+                    };
+                    visitor.visit_atom(atom_idx, &mut self.atoms[atom_idx], context);
+                }
+                Child::Node(node_idx) => {
+                    // the parent node can't be the visited one, so we split
+                    // the arena to borrow both
+                    let (node, parent_operator) = match parent {
+                        Some(parent_idx) if parent_idx < node_idx => {
+                            let (before, after) = self.nodes.split_at_mut(node_idx.index());
+                            (&mut after[0], before[parent_idx].operator.as_ref())
+                        }
+                        Some(parent_idx) => {
+                            let (before, after) = self.nodes.split_at_mut(parent_idx.index());
+                            (&mut before[node_idx], after[0].operator.as_ref())
+                        }
+                        None => (&mut self.nodes[node_idx], None),
+                    };
+                    let unary = node.unary;
+                    if let Some(operator) = node.operator.as_mut() {
+                        let context = VisitContext {
+                            depth,
+                            parent,
+                            parent_operator,
+                        };
+                        visitor.visit_operator(node_idx, operator, unary, context);
+                    }
+                    let node = &self.nodes[node_idx];
+                    stack.push((node.right(), depth + 1, Some(node_idx)));
+                    stack.push((node.left(), depth + 1, Some(node_idx)));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,158 @@
+//! Check that empty trees don't allocate, and that small expressions
+//! don't allocate either with the `inline-storage` feature, nor the
+//! trees allocated in an arena with the `arena` feature.
+//!
+//! This is a separate test binary because it installs a global allocator
+//! counting the allocations of the current thread.
+
+use {
+    bet::BeTree,
+    std::{
+        alloc::{GlobalAlloc, Layout, System},
+        cell::Cell,
+    },
+};
+
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// return the number of allocations done by the current thread in `f`
+fn count_allocations<R>(f: impl FnOnce() -> R) -> (R, usize) {
+    let before = ALLOCATIONS.with(Cell::get);
+    let result = f();
+    (result, ALLOCATIONS.with(Cell::get) - before)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    And,
+    Or,
+    Not,
+}
+
+fn eval(expr: &BeTree<Op, char>, trues: &[char]) -> Option<bool> {
+    expr.eval(
+        |c| trues.contains(c),
+        |op, a, b| match (op, b) {
+            (Op::And, Some(b)) => a & b,
+            (Op::Or, Some(b)) => a | b,
+            _ => !a,
+        },
+        |op, &a| match op {
+            Op::And => !a,
+            Op::Or => a,
+            Op::Not => false,
+        },
+    )
+}
+
+#[test]
+fn empty_trees_dont_allocate() {
+    let (expr, allocations) = count_allocations(|| {
+        let expr: BeTree<Op, char> = BeTree::default();
+        assert!(expr.is_empty());
+        assert_eq!(eval(&expr, &[]), None);
+        expr
+    });
+    assert_eq!(allocations, 0);
+    let mut filters = [expr, BeTree::new()];
+    filters[0].push_atom('A');
+    filters[0].push_operator(Op::And);
+    filters[0].push_operator(Op::Not);
+    filters[0].push_atom('B');
+    filters[0].push_operator(Op::Or);
+    filters[0].push_atom('C');
+    let (taken, allocations) = count_allocations(|| {
+        let taken = filters[0].take();
+        let _ = std::mem::take(&mut filters[1]);
+        taken
+    });
+    assert_eq!(allocations, 0);
+    assert!(filters[0].is_empty());
+    assert_eq!(eval(&taken, &['A']), Some(true));
+    assert_eq!(eval(&taken, &['B']), Some(false));
+}
+
+#[cfg(all(feature = "inline-storage", not(feature = "arena")))]
+#[test]
+fn small_expressions_dont_allocate() {
+    let (result, allocations) = count_allocations(|| {
+        let mut expr = BeTree::new();
+        expr.push_atom('A');
+        expr.push_operator(Op::And);
+        expr.push_atom('B');
+        (eval(&expr, &['A', 'B']), eval(&expr, &['A']))
+    });
+    assert_eq!(result, (Some(true), Some(false)));
+    assert_eq!(allocations, 0);
+    // a few more atoms and some groups still fit inline
+    let (result, allocations) = count_allocations(|| {
+        let mut expr = BeTree::new();
+        expr.open_par();
+        expr.push_atom('A');
+        expr.push_operator(Op::Or);
+        expr.push_atom('B');
+        expr.close_par();
+        expr.push_operator(Op::And);
+        expr.push_operator(Op::Not);
+        expr.push_atom('C');
+        let copy = expr.clone();
+        eval(&copy, &['B'])
+    });
+    assert_eq!(result, Some(true));
+    assert_eq!(allocations, 0);
+    // bigger expressions spill to the heap
+    let (result, allocations) = count_allocations(|| {
+        let mut expr = BeTree::new();
+        for c in "ABCDEFGH".chars() {
+            if !expr.is_empty() {
+                expr.push_operator(Op::Or);
+            }
+            expr.push_atom(c);
+        }
+        eval(&expr, &['H'])
+    });
+    assert_eq!(result, Some(true));
+    assert!(allocations > 0);
+}
+
+#[cfg(feature = "arena")]
+#[test]
+fn arena_trees_dont_allocate() {
+    let mut arena = bet::TreeArena::with_capacity(1 << 12);
+    let (result, allocations) = count_allocations(|| {
+        let mut result = None;
+        for _ in 0..100 {
+            let mut expr = BeTree::new_in(&arena);
+            for c in "ABCDEFGH".chars() {
+                if !expr.is_empty() {
+                    expr.push_operator(Op::Or);
+                }
+                expr.push_operator(Op::Not);
+                expr.push_atom(c);
+            }
+            result = eval(&expr.clone(), &['H']);
+            drop(expr);
+            assert!(arena.reset());
+        }
+        result
+    });
+    assert_eq!(result, Some(true));
+    assert_eq!(allocations, 0);
+}